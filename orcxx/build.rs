@@ -7,12 +7,13 @@ extern crate thiserror;
 
 use thiserror::Error;
 
-const BRIDGE_MODULES: [&str; 5] = [
+const BRIDGE_MODULES: [&str; 6] = [
     "src/kind.rs",
     "src/int128.rs",
     "src/reader.rs",
     "src/memorypool.rs",
     "src/vector.rs",
+    "src/writer.rs",
 ];
 
 #[derive(Error, Debug)]
@@ -16,13 +16,17 @@ use orcxx::reader;
 use orcxx::structured_reader::StructuredRowReader;
 use orcxx::to_json::columntree_to_json_rows;
 
-fn to_json(orc_path: &str) {
+fn to_json(orc_path: &str, columns: Option<&str>) {
     let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
     let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
 
+    let options = match columns {
+        None => reader::RowReaderOptions::default(),
+        Some(columns) => reader::RowReaderOptions::default().include_names(columns.split(',')),
+    };
     let mut row_reader = reader
-        .row_reader(&reader::RowReaderOptions::default())
-        .unwrap();
+        .row_reader(&options)
+        .expect("Could not select columns");
 
     let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 10240);
 
@@ -36,10 +40,14 @@ fn to_json(orc_path: &str) {
 fn main() {
     let args: Vec<String> = env::args().collect();
     match args.as_slice() {
-        [_, path] => to_json(path),
+        [_, path] => to_json(path, None),
+        [_, flag, columns, path] if flag == "--columns" => to_json(path, Some(columns)),
         _ => {
             io::stderr()
-                .write_all(b"Syntax: <path>\n\nReads an ORC file and prints it as JSON objects.\n")
+                .write_all(
+                    b"Syntax: [--columns <name1>,<name2>,...] <path>\n\n\
+                    Reads an ORC file and prints it as JSON objects.\n",
+                )
                 .unwrap();
             process::exit(1);
         }
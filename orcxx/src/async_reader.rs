@@ -0,0 +1,245 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tokio-friendly API to read ORC files.
+//!
+//! Reading ORC files involves blocking I/O and CPU-heavy decoding in the C++
+//! library; this module runs them on tokio's blocking thread pool (with
+//! [`tokio::task::spawn_blocking`]), so they do not stall the runtime.
+//!
+//! [`AsyncRowIterator`] implements [`Stream`], and yields rows decoded by a
+//! [`RowIterator`] one batch at a time. Like [`RowIterator`], its items need to
+//! implement [`OrcDeserialize`] trait; `orcxx_derive` can generate implementations
+//! for structures.
+//!
+//! All functions of this module which return futures or streams must be called from
+//! within a tokio runtime, or they panic.
+
+use std::convert::TryInto;
+use std::future::Future;
+use std::num::NonZeroU64;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::task::{spawn_blocking, JoinHandle};
+
+use deserialize::{CheckableKind, OrcDeserialize, OrcStruct};
+use errors::{OpenOrcError, OrcResult, ReaderError};
+use reader::{InputStream, Reader, ReaderOptions, RowReaderOptions};
+use row_iterator::RowIterator;
+
+/// Future returned by the functions of this module, which resolves to the result
+/// of a closure run on tokio's blocking thread pool.
+///
+/// If the closure panics, polling this future resumes the panic.
+pub struct Blocking<T> {
+    handle: JoinHandle<T>,
+}
+
+impl<T: Send + 'static> Blocking<T> {
+    fn spawn<F: FnOnce() -> T + Send + 'static>(f: F) -> Blocking<T> {
+        Blocking {
+            handle: spawn_blocking(f),
+        }
+    }
+}
+
+impl<T> Future for Blocking<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        match Pin::new(&mut self.handle).poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(value)) => Poll::Ready(value),
+            Poll::Ready(Err(e)) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Poll::Ready(Err(e)) => panic!("Blocking task failed: {}", e),
+        }
+    }
+}
+
+/// Asynchronously opened [`InputStream`]
+pub struct AsyncInputStream(InputStream);
+
+impl AsyncInputStream {
+    /// Opens a local file, without blocking the runtime
+    pub fn from_local_file(file_name: &str) -> Blocking<OrcResult<AsyncInputStream>> {
+        let file_name = file_name.to_owned();
+        Blocking::spawn(move || InputStream::from_local_file(&file_name).map(AsyncInputStream))
+    }
+
+    /// Returns the underlying [`InputStream`]
+    pub fn into_inner(self) -> InputStream {
+        self.0
+    }
+}
+
+impl From<InputStream> for AsyncInputStream {
+    fn from(input_stream: InputStream) -> AsyncInputStream {
+        AsyncInputStream(input_stream)
+    }
+}
+
+/// [`Reader`] whose meta-data was read without blocking the runtime.
+///
+/// Once opened, all meta-data is in memory, so [`AsyncReader::reader`] can be
+/// used to query it.
+pub struct AsyncReader(Reader);
+
+impl AsyncReader {
+    /// Reads the file's meta-data, see [`Reader::new`]
+    pub fn new(input_stream: AsyncInputStream) -> Blocking<Result<AsyncReader, ReaderError>> {
+        AsyncReader::new_with_options(input_stream, ReaderOptions::default())
+    }
+
+    /// Reads the file's meta-data, see [`Reader::new_with_options`]
+    pub fn new_with_options(
+        input_stream: AsyncInputStream,
+        options: ReaderOptions,
+    ) -> Blocking<Result<AsyncReader, ReaderError>> {
+        Blocking::spawn(move || Reader::new_with_options(input_stream.0, options).map(AsyncReader))
+    }
+
+    /// Returns the underlying [`Reader`]
+    pub fn reader(&self) -> &Reader {
+        &self.0
+    }
+
+    /// Returns the underlying [`Reader`]
+    pub fn into_inner(self) -> Reader {
+        self.0
+    }
+}
+
+impl From<Reader> for AsyncReader {
+    fn from(reader: Reader) -> AsyncReader {
+        AsyncReader(reader)
+    }
+}
+
+/// [`Stream`] of rows, decoded on tokio's blocking thread pool by a [`RowIterator`].
+///
+/// The iterator decodes up to `batch_size` rows at a time, and only starts decoding
+/// the next batch once all rows of the current one were consumed.
+///
+/// # Panics
+///
+/// Polling panics when rows cannot be deserialized, like [`RowIterator`], unless
+/// configured otherwise with [`RowIterator::on_error`].
+pub struct AsyncRowIterator<T: OrcDeserialize + Default> {
+    /// `None` while a batch is being decoded, or after the last row was decoded
+    rows: Option<RowIterator<T>>,
+
+    /// Batch being decoded
+    pending: Option<Blocking<(RowIterator<T>, Vec<T>)>>,
+
+    /// Decoded rows which were not returned yet, in reverse order
+    decoded: Vec<T>,
+
+    batch_size: usize,
+}
+
+impl<T: OrcDeserialize + OrcStruct + CheckableKind + Default + Send + 'static> AsyncRowIterator<T> {
+    /// Returns a stream of rows of the given [`AsyncReader`].
+    ///
+    /// This only selects columns needed by `T`, like [`RowIterator::new`]; see
+    /// [`AsyncRowIterator::from_row_iterator`] to configure the [`RowIterator`].
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is larger than `usize`.
+    pub fn new(
+        reader: &AsyncReader,
+        batch_size: NonZeroU64,
+    ) -> Result<AsyncRowIterator<T>, OpenOrcError> {
+        let options = RowReaderOptions::default().include_names(T::columns_in(&reader.0.kind()));
+        Self::new_with_options(reader, batch_size, &options)
+    }
+
+    /// Returns a stream of the rows and columns selected by `options`, see
+    /// [`RowIterator::new_with_options`]
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is larger than `usize`.
+    pub fn new_with_options(
+        reader: &AsyncReader,
+        batch_size: NonZeroU64,
+        options: &RowReaderOptions,
+    ) -> Result<AsyncRowIterator<T>, OpenOrcError> {
+        let rows = RowIterator::new_with_options(&reader.0, batch_size, options)?;
+        Ok(AsyncRowIterator::from_row_iterator(rows, batch_size))
+    }
+}
+
+impl<T: OrcDeserialize + Default + Send + 'static> AsyncRowIterator<T> {
+    /// Returns a stream of the rows of the given [`RowIterator`], which allows
+    /// selecting rows and columns, or setting [`RowIterator::on_error`].
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is larger than `usize`.
+    pub fn from_row_iterator(rows: RowIterator<T>, batch_size: NonZeroU64) -> AsyncRowIterator<T> {
+        let batch_size = u64::from(batch_size)
+            .try_into()
+            .expect("batch_size overflows usize");
+        AsyncRowIterator {
+            rows: Some(rows),
+            pending: None,
+            decoded: Vec::new(),
+            batch_size,
+        }
+    }
+}
+
+// No field is structurally pinned
+impl<T: OrcDeserialize + Default> Unpin for AsyncRowIterator<T> {}
+
+impl<T: OrcDeserialize + Default + Send + 'static> Stream for AsyncRowIterator<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        loop {
+            if let Some(row) = self.decoded.pop() {
+                return Poll::Ready(Some(row));
+            }
+            if let Some(mut rows) = self.rows.take() {
+                let batch_size = self.batch_size;
+                self.pending = Some(Blocking::spawn(move || {
+                    let batch: Vec<T> = rows.by_ref().take(batch_size).collect();
+                    (rows, batch)
+                }));
+            }
+            let (rows, mut batch) = match self.pending.as_mut() {
+                None => return Poll::Ready(None), // Exhausted
+                Some(pending) => match Pin::new(pending).poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(result) => result,
+                },
+            };
+            self.pending = None;
+            if batch.is_empty() {
+                return Poll::Ready(None);
+            }
+            batch.reverse();
+            self.decoded = batch;
+            self.rows = Some(rows);
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = match self.rows {
+            Some(ref rows) => rows.size_hint(),
+            // The iterator is either exhausted, or decoding a batch whose size is
+            // not known yet
+            None if self.pending.is_some() => (0, None),
+            None => (0, Some(0)),
+        };
+        (
+            lower.saturating_add(self.decoded.len()),
+            upper.and_then(|upper| upper.checked_add(self.decoded.len())),
+        )
+    }
+}
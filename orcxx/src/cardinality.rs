@@ -0,0 +1,291 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Estimates the number of distinct values of ORC columns
+//!
+//! ORC statistics contain no distinct count, so this computes an approximate one
+//! in a single pass with [HyperLogLog](https://en.wikipedia.org/wiki/HyperLogLog),
+//! hashing the raw bytes of each value with [XXH3](https://xxhash.com/).
+//! Only string, binary, and integer columns are supported; nulls are not counted.
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::*;
+//!
+//! let orc_path = "orc/examples/TestOrcFile.test1.orc";
+//! let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+//! let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let options = reader::RowReaderOptions::default().include_names(["int1", "string1"]);
+//! let mut row_reader = reader.row_reader(&options).unwrap();
+//!
+//! for (column, estimate) in cardinality::estimate_cardinalities(&mut row_reader, 1024, 14) {
+//!     println!("{}: ~{} distinct values", column, estimate);
+//! }
+//! ```
+
+use xxhash_rust::xxh3::xxh3_64;
+
+use kind::Kind;
+use reader::{push_column_paths, RowReader};
+use structured_reader::{ColumnTree, StructuredRowReader};
+use vector::{LongVectorBatch, StringVectorBatch};
+
+/// Approximate distinct counter, using `2^precision` one-byte registers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// Returns an empty counter. Its relative standard error is about
+    /// `1.04 / sqrt(2^precision)`, eg. 0.8% for a precision of 14.
+    ///
+    /// # Panics
+    ///
+    /// If `precision` is not between 4 and 18 (inclusive).
+    pub fn new(precision: u8) -> HyperLogLog {
+        assert!(
+            (4..=18).contains(&precision),
+            "HyperLogLog precision must be between 4 and 18, not {}",
+            precision
+        );
+        HyperLogLog {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    }
+
+    /// Adds a value, given its 64-bits hash
+    pub fn insert_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - self.precision)) as usize;
+        // Sentinel bit, so the rank is at most 64 - precision + 1
+        let remaining = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = remaining.leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Adds a value
+    pub fn insert(&mut self, value: &[u8]) {
+        self.insert_hash(xxh3_64(value))
+    }
+
+    /// Adds all non-null strings of a vector batch
+    pub fn update_strings(&mut self, batch: &StringVectorBatch<'_>) {
+        for s in batch.iter().flatten() {
+            self.insert(s);
+        }
+    }
+
+    /// Adds all non-null integers of a vector batch
+    pub fn update_longs(&mut self, batch: &LongVectorBatch<'_>) {
+        for n in batch.iter().flatten() {
+            self.insert(&n.to_le_bytes());
+        }
+    }
+
+    /// Adds all the values counted by `other`
+    ///
+    /// # Panics
+    ///
+    /// If both counters do not have the same precision.
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        assert_eq!(
+            self.precision, other.precision,
+            "Cannot merge HyperLogLogs with different precisions"
+        );
+        for (register, &other_register) in self.registers.iter_mut().zip(&other.registers) {
+            if other_register > *register {
+                *register = other_register;
+            }
+        }
+    }
+
+    /// Returns the estimated number of distinct values inserted so far
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1. + 1.079 / m),
+        };
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&register| 2f64.powi(-i32::from(register)))
+            .sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self
+            .registers
+            .iter()
+            .filter(|&&register| register == 0)
+            .count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            // Small range correction (linear counting)
+            m * (m / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
+
+/// Streaming estimation of the number of distinct values for each column of a
+/// [`Kind`]
+pub struct ColumnCardinalities {
+    columns: Vec<String>,
+    /// `None` for columns whose type is not supported
+    counters: Vec<Option<HyperLogLog>>,
+}
+
+impl ColumnCardinalities {
+    /// Returns empty counters for the columns of `kind`, usually the
+    /// [`selected_kind()`](RowReader::selected_kind) of a [`RowReader`].
+    ///
+    /// # Panics
+    ///
+    /// If `precision` is not valid (see [`HyperLogLog::new`]).
+    pub fn new(kind: &Kind, precision: u8) -> ColumnCardinalities {
+        let mut columns = Vec::new();
+        let mut kinds = Vec::new();
+        push_column_paths(kind, String::new(), &mut columns);
+        push_kinds(kind, &mut kinds);
+        ColumnCardinalities {
+            counters: kinds
+                .into_iter()
+                .map(|kind| match kind {
+                    Kind::Byte
+                    | Kind::Short
+                    | Kind::Int
+                    | Kind::Long
+                    | Kind::Date
+                    | Kind::String
+                    | Kind::Varchar(_)
+                    | Kind::Char(_)
+                    | Kind::Binary => Some(HyperLogLog::new(precision)),
+                    _ => None,
+                })
+                .collect(),
+            columns,
+        }
+    }
+
+    /// Updates counters with the next batch of columns.
+    ///
+    /// # Panics
+    ///
+    /// If `tree` was not built from the `kind` passed to [`ColumnCardinalities::new`].
+    pub fn update(&mut self, tree: ColumnTree<'_>) {
+        let next_column = self.update_column(tree, 0);
+        assert_eq!(
+            next_column,
+            self.counters.len(),
+            "ColumnTree has fewer columns than expected"
+        );
+    }
+
+    /// Updates the counter of the column at `index` (in depth-first order) and its
+    /// children, then returns the index of the next column.
+    fn update_column(&mut self, tree: ColumnTree<'_>, index: usize) -> usize {
+        let counter = self
+            .counters
+            .get_mut(index)
+            .expect("ColumnTree has more columns than expected");
+        match tree {
+            ColumnTree::Byte(column)
+            | ColumnTree::Short(column)
+            | ColumnTree::Int(column)
+            | ColumnTree::Long(column)
+            | ColumnTree::Date(column) => {
+                if let Some(counter) = counter {
+                    counter.update_longs(&column)
+                }
+            }
+            ColumnTree::String(column) | ColumnTree::Binary(column) => {
+                if let Some(counter) = counter {
+                    counter.update_strings(&column)
+                }
+            }
+            ColumnTree::List { elements, .. } => {
+                return self.update_column(*elements, index + 1);
+            }
+            ColumnTree::Map { keys, elements, .. } => {
+                let index = self.update_column(*keys, index + 1);
+                return self.update_column(*elements, index);
+            }
+            ColumnTree::Struct { elements, .. } => {
+                let mut index = index + 1;
+                for (_, subtree) in elements {
+                    index = self.update_column(subtree, index);
+                }
+                return index;
+            }
+            _ => (),
+        }
+        index + 1
+    }
+
+    /// Returns the path of each supported column (see
+    /// [`RowReader::selected_columns`]) and its estimated number of distinct
+    /// values so far.
+    pub fn estimates(&self) -> Vec<(String, u64)> {
+        self.columns
+            .iter()
+            .zip(self.counters.iter())
+            .filter_map(|(column, counter)| {
+                counter
+                    .as_ref()
+                    .map(|counter| (column.clone(), counter.estimate().round() as u64))
+            })
+            .collect()
+    }
+}
+
+/// Pushes `kind` and all its descendants, in the same order as
+/// [`push_column_paths`]
+fn push_kinds<'a>(kind: &'a Kind, kinds: &mut Vec<&'a Kind>) {
+    kinds.push(kind);
+    match kind {
+        Kind::List(element) => push_kinds(element, kinds),
+        Kind::Map { key, value } => {
+            push_kinds(key, kinds);
+            push_kinds(value, kinds);
+        }
+        Kind::Struct(fields) => {
+            for (_, field) in fields {
+                push_kinds(field, kinds);
+            }
+        }
+        Kind::Union(variants) => {
+            for variant in variants {
+                push_kinds(variant, kinds);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Reads all remaining rows from `row_reader`, `batch_size` at a time, and returns
+/// the path of each supported column and its estimated number of distinct values.
+///
+/// # Panics
+///
+/// If `precision` is not valid (see [`HyperLogLog::new`]).
+pub fn estimate_cardinalities(
+    row_reader: &mut RowReader,
+    batch_size: u64,
+    precision: u8,
+) -> Vec<(String, u64)> {
+    let mut cardinalities = ColumnCardinalities::new(&row_reader.selected_kind(), precision);
+    let mut structured_row_reader = StructuredRowReader::new(row_reader, batch_size);
+    while let Some(columns) = structured_row_reader.next() {
+        cardinalities.update(columns);
+    }
+    cardinalities.estimates()
+}
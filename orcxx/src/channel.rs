@@ -0,0 +1,82 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Decoding of ORC rows on a dedicated thread, which sends them through a bounded
+//! channel.
+//!
+//! This is a simple way to overlap decoding with processing for applications which
+//! use neither rayon (see `parallel_row_iterator`) nor async: [`spawn_reader`]
+//! returns a [`Receiver`] of batches of rows, and the thread blocks when `bound`
+//! batches are waiting to be received, so decoding does not get ahead of consumers.
+//!
+//! Items need to implement [`OrcDeserialize`] trait; `orcxx_derive` can generate
+//! implementations for structures.
+
+use std::convert::TryInto;
+use std::num::NonZeroU64;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread::JoinHandle;
+
+use deserialize::{CheckableKind, OrcDeserialize, OrcStruct};
+use errors::OpenOrcError;
+use reader::Reader;
+use row_iterator::RowIterator;
+
+/// Spawns a thread which reads rows of the given [`Reader`], and sends them in
+/// `Vec`s of up to `batch_size` rows through a channel holding up to `bound` of
+/// them.
+///
+/// This only selects columns needed by `T`, like [`RowIterator::new`]; see
+/// [`spawn_row_iterator`] to configure the [`RowIterator`].
+///
+/// The thread stops after sending the last rows, or as soon as the receiver is
+/// dropped.
+///
+/// # Panics
+///
+/// When `batch_size` is larger than `usize`.
+///
+/// The thread panics when rows cannot be deserialized, like [`RowIterator`]; this
+/// closes the channel, and can be detected with [`JoinHandle::join`].
+pub fn spawn_reader<T>(
+    reader: &Reader,
+    batch_size: NonZeroU64,
+    bound: usize,
+) -> Result<(JoinHandle<()>, Receiver<Vec<T>>), OpenOrcError>
+where
+    T: OrcDeserialize + OrcStruct + CheckableKind + Default + Send + 'static,
+{
+    let rows = RowIterator::new(reader, batch_size)?;
+    let batch_size = u64::from(batch_size)
+        .try_into()
+        .expect("batch_size overflows usize");
+    Ok(spawn_row_iterator(rows, batch_size, bound))
+}
+
+/// Same as [`spawn_reader`], but iterates on the given [`RowIterator`], which
+/// allows selecting rows and columns, or setting [`RowIterator::on_error`].
+///
+/// # Panics
+///
+/// If `batch_size` is 0.
+pub fn spawn_row_iterator<T>(
+    mut rows: RowIterator<T>,
+    batch_size: usize,
+    bound: usize,
+) -> (JoinHandle<()>, Receiver<Vec<T>>)
+where
+    T: OrcDeserialize + Default + Send + 'static,
+{
+    assert_ne!(batch_size, 0, "batch_size must be positive");
+    let (sender, receiver) = sync_channel(bound);
+    let handle = std::thread::spawn(move || loop {
+        let batch: Vec<T> = rows.by_ref().take(batch_size).collect();
+        // Stops when the receiver was dropped
+        if batch.is_empty() || sender.send(batch).is_err() {
+            break;
+        }
+    });
+    (handle, receiver)
+}
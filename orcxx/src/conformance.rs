@@ -0,0 +1,373 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Checks ORC files against structural invariants of the format
+//!
+//! This is meant to debug files produced by other writers, which the C++ library
+//! may read without complaining but with inconsistent vector batches.
+//! [`Reader::conformance_report`] reads the file stripe by stripe and checks:
+//!
+//! * offsets of list and map columns are monotonic, start at 0, and end at the
+//!   number of elements in their children
+//! * struct fields have as many elements as their struct, and map values as many
+//!   as their keys
+//! * lengths of string and binary values are non-negative and stay within the
+//!   vector's blob
+//! * the number of non-null values in each column matches stripe statistics, and
+//!   nulls only appear in columns whose statistics say they have some (only if
+//!   the file has stripe statistics)
+//!
+//! Unions and their children are not checked.
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::*;
+//!
+//! let orc_path = "orc/examples/TestOrcFile.test1.orc";
+//! let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+//! let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let report = reader
+//!     .conformance_report(&conformance::ConformanceOptions::default())
+//!     .unwrap();
+//! for violation in &report.violations {
+//!     println!("{}", violation);
+//! }
+//! assert!(report.is_conformant());
+//! ```
+
+use std::convert::TryInto;
+use std::fmt;
+
+use errors::OrcResult;
+use kind::Kind;
+use reader::{push_column_paths, Reader, RowReaderOptions};
+use vector::{BorrowedColumnVectorBatch, ColumnVectorBatch};
+
+/// Options of [`Reader::conformance_report`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConformanceOptions {
+    stripe_step: u64,
+    batch_size: u64,
+}
+
+impl Default for ConformanceOptions {
+    fn default() -> ConformanceOptions {
+        ConformanceOptions {
+            stripe_step: 1,
+            batch_size: 1024,
+        }
+    }
+}
+
+impl ConformanceOptions {
+    /// Only checks one stripe out of `stripe_step` (default: 1, ie. all stripes),
+    /// to sample large files.
+    ///
+    /// # Panics
+    ///
+    /// If `stripe_step` is 0.
+    pub fn stripe_step(mut self, stripe_step: u64) -> ConformanceOptions {
+        assert_ne!(stripe_step, 0, "stripe_step must be positive");
+        self.stripe_step = stripe_step;
+        self
+    }
+
+    /// Number of rows read at once (default: 1024)
+    pub fn batch_size(mut self, batch_size: u64) -> ConformanceOptions {
+        self.batch_size = batch_size;
+        self
+    }
+}
+
+/// Structural invariant broken by a column
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The end offset of a list or map is lower than its start offset
+    DecreasingOffsets { index: u64, start: i64, end: i64 },
+    /// The offsets of a list or map do not start at 0 or do not end at the number
+    /// of elements in their children
+    OffsetsOutOfBounds {
+        first_offset: i64,
+        last_offset: i64,
+        num_children: u64,
+    },
+    /// A struct field does not have as many elements as its struct, or the values
+    /// of a map as many as its keys
+    LengthMismatch { num_elements: u64, expected: u64 },
+    /// A string or binary value has a negative length
+    NegativeLength { index: u64, length: i64 },
+    /// A string or binary value ends after the end of the vector's blob
+    StringOutOfBlob { index: u64, length: i64 },
+    /// The number of non-null values read does not match stripe statistics
+    ValueCountMismatch { read: u64, statistics: u64 },
+    /// Nulls were read, but stripe statistics say there are none
+    UnexpectedNulls,
+}
+
+/// A structural invariant broken by a column of a stripe
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// Index of the stripe in the file
+    pub stripe: u64,
+    /// Row number (in the file) of the first row of the batch the violation was
+    /// found in, or of the first row of the stripe for statistics mismatches.
+    ///
+    /// Indexes in [`ViolationKind`] are relative to the column's vector in
+    /// that batch.
+    pub first_row: u64,
+    /// Path of the column (see [`RowReader::selected_columns`](crate::reader::RowReader::selected_columns))
+    pub column: String,
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Stripe {}, batch at row {}, column {:?}: {:?}",
+            self.stripe, self.first_row, self.column, self.kind
+        )
+    }
+}
+
+/// Returned by [`Reader::conformance_report`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConformanceReport {
+    /// Indexes of the stripes which were checked
+    pub checked_stripes: Vec<u64>,
+    pub violations: Vec<Violation>,
+}
+
+impl ConformanceReport {
+    /// Returns whether no violation was found
+    pub fn is_conformant(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Number of non-null values read from a column, and whether it had nulls.
+/// `None` for unchecked columns.
+type ValueCount = Option<(u64, bool)>;
+
+struct StripeChecker<'a> {
+    stripe: u64,
+    first_row: u64,
+    columns: &'a [String],
+    value_counts: Vec<ValueCount>,
+    violations: &'a mut Vec<Violation>,
+}
+
+impl StripeChecker<'_> {
+    fn report(&mut self, index: usize, kind: ViolationKind) {
+        self.violations.push(Violation {
+            stripe: self.stripe,
+            first_row: self.first_row,
+            column: self.columns[index].clone(),
+            kind,
+        })
+    }
+
+    /// Checks the column at `index` (in depth-first order) and its children, then
+    /// returns the index of the next column.
+    fn check_column(
+        &mut self,
+        kind: &Kind,
+        batch: BorrowedColumnVectorBatch<'_>,
+        index: usize,
+    ) -> OrcResult<usize> {
+        let num_elements = batch.num_elements();
+        let num_nulls = batch
+            .not_null()
+            .map(|not_null| not_null.iter().filter(|&&b| b == 0).count() as u64)
+            .unwrap_or(0);
+        if let Some((values, has_nulls)) = &mut self.value_counts[index] {
+            *values += num_elements - num_nulls;
+            *has_nulls |= num_nulls > 0;
+        }
+
+        match kind {
+            Kind::String | Kind::Varchar(_) | Kind::Char(_) | Kind::Binary => {
+                let strings = batch.try_into_strings()?;
+                let blob = strings.bytes().as_ptr_range();
+                let blob = (blob.start as usize)..(blob.end as usize);
+                let (data, lengths) = strings.raw_strings();
+                let not_null = batch.not_null();
+                for (i, (&datum, &length)) in data.iter().zip(lengths).enumerate() {
+                    if not_null.map(|not_null| not_null[i] == 0).unwrap_or(false) {
+                        continue;
+                    }
+                    let i = i as u64;
+                    if length < 0 {
+                        self.report(index, ViolationKind::NegativeLength { index: i, length });
+                    } else if blob.contains(&(datum as usize))
+                        && (datum as usize).saturating_add(length as usize) > blob.end
+                    {
+                        // Only checked for strings in the blob, as the C++ library
+                        // points to its dictionary for dictionary-encoded columns
+                        self.report(index, ViolationKind::StringOutOfBlob { index: i, length });
+                    }
+                }
+                Ok(index + 1)
+            }
+            Kind::Struct(fields) => {
+                let structs = batch.try_into_structs()?;
+                let mut index = index + 1;
+                for ((_, field_kind), field) in fields.iter().zip(structs.fields()) {
+                    if field.num_elements() != num_elements {
+                        self.report(
+                            index,
+                            ViolationKind::LengthMismatch {
+                                num_elements: field.num_elements(),
+                                expected: num_elements,
+                            },
+                        );
+                    }
+                    index = self.check_column(field_kind, field, index)?;
+                }
+                Ok(index)
+            }
+            Kind::List(element_kind) => {
+                let lists = batch.try_into_lists()?;
+                let elements = lists.elements();
+                self.check_offsets(index, lists.raw_offsets(), elements.num_elements());
+                self.check_column(element_kind, elements, index + 1)
+            }
+            Kind::Map { key, value } => {
+                let maps = batch.try_into_maps()?;
+                let keys = maps.keys();
+                let elements = maps.elements();
+                self.check_offsets(index, maps.raw_offsets(), keys.num_elements());
+                let num_keys = keys.num_elements();
+                let index = self.check_column(key, keys, index + 1)?;
+                if elements.num_elements() != num_keys {
+                    self.report(
+                        index,
+                        ViolationKind::LengthMismatch {
+                            num_elements: elements.num_elements(),
+                            expected: num_keys,
+                        },
+                    );
+                }
+                self.check_column(value, elements, index)
+            }
+            Kind::Union(_) => {
+                let mut paths = Vec::new();
+                push_column_paths(kind, String::new(), &mut paths);
+                for value_count in &mut self.value_counts[index..index + paths.len()] {
+                    *value_count = None;
+                }
+                Ok(index + paths.len())
+            }
+            _ => Ok(index + 1),
+        }
+    }
+
+    fn check_offsets(&mut self, index: usize, offsets: &[i64], num_children: u64) {
+        for (i, window) in offsets.windows(2).enumerate() {
+            if window[1] < window[0] {
+                self.report(
+                    index,
+                    ViolationKind::DecreasingOffsets {
+                        index: i as u64,
+                        start: window[0],
+                        end: window[1],
+                    },
+                );
+            }
+        }
+        let first_offset = offsets[0];
+        let last_offset = *offsets.last().unwrap();
+        let last_offset_matches = last_offset
+            .try_into()
+            .map(|last_offset: u64| last_offset == num_children)
+            .unwrap_or(false);
+        if first_offset != 0 || !last_offset_matches {
+            self.report(
+                index,
+                ViolationKind::OffsetsOutOfBounds {
+                    first_offset,
+                    last_offset,
+                    num_children,
+                },
+            );
+        }
+    }
+}
+
+pub(crate) fn check(reader: &Reader, options: &ConformanceOptions) -> OrcResult<ConformanceReport> {
+    let kind = reader.kind();
+    let mut columns = Vec::new();
+    push_column_paths(&kind, String::new(), &mut columns);
+
+    let mut row_reader = reader.row_reader(&RowReaderOptions::default())?;
+    let mut batch = row_reader.row_batch(options.batch_size);
+
+    let mut checked_stripes = Vec::new();
+    let mut violations = Vec::new();
+    let mut stripe_first_row = 0;
+    for (stripe_index, stripe) in reader.stripes().enumerate() {
+        let stripe_index = stripe_index as u64;
+        let rows_count = stripe.rows_count();
+        if stripe_index % options.stripe_step != 0 {
+            stripe_first_row += rows_count;
+            continue;
+        }
+        checked_stripes.push(stripe_index);
+
+        let mut checker = StripeChecker {
+            stripe: stripe_index,
+            first_row: stripe_first_row,
+            columns: &columns,
+            value_counts: vec![Some((0, false)); columns.len()],
+            violations: &mut violations,
+        };
+
+        // The C++ library never returns batches spanning multiple stripes
+        row_reader.seek_to_row(stripe_first_row);
+        let mut rows_read = 0;
+        while rows_read < rows_count {
+            let num_rows = match row_reader.read_rows_into(&mut batch) {
+                Some(num_rows) => num_rows,
+                None => break,
+            };
+            checker.first_row = stripe_first_row + rows_read;
+            checker.check_column(&kind, batch.borrow(), 0)?;
+            rows_read += num_rows.get();
+        }
+
+        checker.first_row = stripe_first_row;
+        if let Some(statistics) = reader.stripe_value_counts(stripe_index) {
+            let value_counts = std::mem::take(&mut checker.value_counts);
+            for (index, (value_count, statistics)) in
+                value_counts.into_iter().zip(statistics).enumerate()
+            {
+                if let Some((read, has_nulls)) = value_count {
+                    let (expected, expected_has_nulls) = statistics;
+                    if read != expected {
+                        checker.report(
+                            index,
+                            ViolationKind::ValueCountMismatch {
+                                read,
+                                statistics: expected,
+                            },
+                        );
+                    }
+                    if has_nulls && !expected_has_nulls {
+                        checker.report(index, ViolationKind::UnexpectedNulls);
+                    }
+                }
+            }
+        }
+
+        stripe_first_row += rows_count;
+    }
+
+    Ok(ConformanceReport {
+        checked_stripes,
+        violations,
+    })
+}
@@ -1,9 +1,67 @@
+#include <algorithm>
+#include <atomic>
+#include <cstdlib>
+#include <cstring>
+#include <ios>
+#include <list>
 #include <memory>
+#include <new>
+#include <set>
+#include <stdexcept>
+#include <string>
+#include <system_error>
 
+#include <orc/BloomFilter.hh>
+#include <orc/Exceptions.hh>
 #include <orc/Int128.hh>
 #include <orc/MemoryPool.hh>
+#include <orc/OrcFile.hh>
+#include <orc/Statistics.hh>
 #include <orc/Type.hh>
 #include <orc/Vector.hh>
+#include <orc/Writer.hh>
+
+#include "rust/cxx.h"
+
+// Replaces cxx's default conversion of C++ exceptions to Rust errors, in order to
+// prefix their message with the type of the exception (followed by the
+// ORCXX_ERROR_SEPARATOR character), which OrcError::kind() parses.
+#define ORCXX_ERROR_SEPARATOR '\x1e'
+namespace rust {
+    namespace behavior {
+        template<typename Fail>
+        void failWithKind(Fail &&fail, const char *kind, const char *what) {
+            std::string message(kind);
+            message.push_back(ORCXX_ERROR_SEPARATOR);
+            message.append(what);
+            fail(message.c_str());
+        }
+
+        template<typename Try, typename Fail>
+        static void trycatch(Try &&func, Fail &&fail) noexcept try {
+            func();
+        } catch (const orc::ParseError &e) {
+            failWithKind(fail, "ParseError", e.what());
+        } catch (const orc::NotImplementedYet &e) {
+            failWithKind(fail, "NotImplementedYet", e.what());
+        } catch (const orc::InvalidArgument &e) {
+            failWithKind(fail, "InvalidArgument", e.what());
+        } catch (const orc::SchemaEvolutionError &e) {
+            failWithKind(fail, "SchemaEvolutionError", e.what());
+        } catch (const rust::Error &e) {
+            // Only returned by Rust readers passed to InputStream::from_reader
+            failWithKind(fail, "IoError", e.what());
+        } catch (const std::ios_base::failure &e) {
+            failWithKind(fail, "IoError", e.what());
+        } catch (const std::system_error &e) {
+            failWithKind(fail, "IoError", e.what());
+        } catch (const std::bad_alloc &e) {
+            failWithKind(fail, "OutOfMemory", e.what());
+        } catch (const std::exception &e) {
+            failWithKind(fail, "Other", e.what());
+        }
+    }
+}
 
 
 #define getter(name) \
@@ -14,6 +72,14 @@
       return obj.name; \
     }
 
+#define setter(name) \
+    template<typename T, typename Value> \
+    void \
+    set_## name(T &obj, Value value) \
+    { \
+      obj.name = value; \
+    }
+
 namespace orcxx_rs {
 
     namespace utils {
@@ -67,6 +133,495 @@ namespace orcxx_rs {
         return orc::Type::buildTypeFromString(input);
     }
 
+    // InputStream::read() takes a void pointer, which cxx does not support.
+    template<typename T>
+    void readInputStream(T &stream, uint8_t *buf, uint64_t length, uint64_t offset) {
+        stream.read(buf, length, offset);
+    }
+
+    // orc::InputStream reading from a buffer in memory. 'Keepalive' is a
+    // rust::Box owning the buffer (if any), so it lives as long as the stream.
+    template<typename Keepalive>
+    class MemoryInputStream : public orc::InputStream {
+      public:
+        MemoryInputStream(Keepalive keepalive, const uint8_t *data, uint64_t length)
+            : keepalive(std::move(keepalive)), data(data), length(length), name("<memory>") {}
+
+        uint64_t getLength() const override {
+          return length;
+        }
+
+        uint64_t getNaturalReadSize() const override {
+          return 128 * 1024;
+        }
+
+        void read(void *buf, uint64_t length, uint64_t offset) override {
+          if (offset > this->length || length > this->length - offset) {
+            throw orc::ParseError("Read past the end of the memory buffer");
+          }
+          std::memcpy(buf, data + offset, length);
+        }
+
+        const std::string &getName() const override {
+          return name;
+        }
+
+      private:
+        Keepalive keepalive;
+        const uint8_t *data;
+        uint64_t length;
+        std::string name;
+    };
+
+    template<typename Keepalive>
+    std::unique_ptr<orc::InputStream> readMemory(Keepalive keepalive, const uint8_t *data, uint64_t length) {
+        return std::make_unique<MemoryInputStream<Keepalive>>(std::move(keepalive), data, length);
+    }
+
+    // orc::InputStream calling back into a Rust reader. 'Source' is a rust::Box
+    // of orcxx's ReadSource, which is only declared by the code generated by cxx.
+    template<typename Source>
+    class RustInputStream : public orc::InputStream {
+      public:
+        RustInputStream(Source source, uint64_t length)
+            : source(std::move(source)), length(length), name("<reader>") {}
+
+        uint64_t getLength() const override {
+          return length;
+        }
+
+        uint64_t getNaturalReadSize() const override {
+          return 128 * 1024;
+        }
+
+        void read(void *buf, uint64_t length, uint64_t offset) override {
+          source->read_at(static_cast<uint8_t*>(buf), length, offset);
+        }
+
+        const std::string &getName() const override {
+          return name;
+        }
+
+      private:
+        Source source;
+        uint64_t length;
+        std::string name;
+    };
+
+    template<typename Source>
+    std::unique_ptr<orc::InputStream> readRust(Source source, uint64_t length) {
+        return std::make_unique<RustInputStream<Source>>(std::move(source), length);
+    }
+
+    // orc::MemoryPool which fails allocations that would bring the total size
+    // of live allocations above a budget. Sizes are stored in a header before
+    // each allocation, because orc::MemoryPool::free() does not get them.
+    class BudgetedMemoryPool : public orc::MemoryPool {
+      public:
+        explicit BudgetedMemoryPool(uint64_t budget) : budget(budget), used(0) {}
+
+        char *malloc(uint64_t size) override {
+          uint64_t previous = used.fetch_add(size);
+          if (previous + size < previous || previous + size > budget) {
+            used.fetch_sub(size);
+            throw std::runtime_error(
+                "Allocating " + std::to_string(size) + " bytes would exceed the memory budget of "
+                + std::to_string(budget) + " bytes");
+          }
+          char *base = static_cast<char*>(std::malloc(size + HEADER_SIZE));
+          if (base == nullptr) {
+            used.fetch_sub(size);
+            throw std::bad_alloc();
+          }
+          std::memcpy(base, &size, sizeof(size));
+          return base + HEADER_SIZE;
+        }
+
+        void free(char *p) override {
+          if (p == nullptr) {
+            return;
+          }
+          char *base = p - HEADER_SIZE;
+          uint64_t size;
+          std::memcpy(&size, base, sizeof(size));
+          used.fetch_sub(size);
+          std::free(base);
+        }
+
+      private:
+        // Larger than sizeof(uint64_t), to keep allocations aligned like malloc's
+        static const uint64_t HEADER_SIZE = 16;
+        uint64_t budget;
+        std::atomic<uint64_t> used;
+    };
+
+    template<typename Pool>
+    Pool budgetedMemoryPool(uint64_t budget) {
+        return std::make_shared<BudgetedMemoryPool>(budget);
+    }
+
+    // orc::ReaderOptions::setMemoryPool() takes a reference, and the pool must
+    // outlive the reader; so it is owned by a shared_ptr on the Rust side.
+    template<typename T, typename Pool>
+    void setMemoryPool(T &options, const Pool &pool) {
+        options.setMemoryPool(*pool);
+    }
+
+    // Type::createRowBatch() takes a MemoryPool reference, which cxx cannot pass.
+    template<typename T>
+    std::unique_ptr<orc::ColumnVectorBatch> createRowBatch(const T &type, uint64_t size) {
+        return type.createRowBatch(size, *orc::getDefaultPool());
+    }
+
+    // Builds errors for arguments checked on the Rust side, so they are
+    // OrcErrors of the same kind as those of the C++ library.
+    inline void invalidArgument(const std::string &message) {
+        throw orc::InvalidArgument(message);
+    }
+
+    template<typename T>
+    std::unique_ptr<std::string> serializedFileTail(const T &reader) {
+        return std::make_unique<std::string>(reader.getSerializedFileTail());
+    }
+
+    // Enums cannot be shared with cxx without redefining them, so they are
+    // returned as integers and converted on the Rust side.
+    template<typename T>
+    uint8_t compressionKind(const T &reader) {
+        return static_cast<uint8_t>(reader.getCompression());
+    }
+
+    template<typename T>
+    uint32_t writerVersion(const T &reader) {
+        return static_cast<uint32_t>(reader.getWriterVersion());
+    }
+
+    template<typename T>
+    void formatVersion(const T &reader, uint32_t &major, uint32_t &minor) {
+        orc::FileVersion version = reader.getFormatVersion();
+        major = version.getMajor();
+        minor = version.getMinor();
+    }
+
+    // Reader::getMetadataKeys() returns a std::list, which cxx does not support.
+    template<typename T>
+    std::unique_ptr<std::vector<std::string>> metadataKeys(const T &reader) {
+        std::list<std::string> keys = reader.getMetadataKeys();
+        return std::make_unique<std::vector<std::string>>(keys.begin(), keys.end());
+    }
+
+    template<typename T>
+    std::unique_ptr<std::string> metadataValue(const T &reader, const std::string &key) {
+        return std::make_unique<std::string>(reader.getMetadataValue(key));
+    }
+
+    // Reader::getMemoryUse*() are not const-qualified, although they only read
+    // the footer; and there is one for each way RowReaderOptions can select
+    // columns.
+    template<typename T, typename Options>
+    uint64_t memoryUse(const T &reader, const Options &options, int32_t stripeIndex) {
+        T &mutableReader = const_cast<T &>(reader);
+        if (options.getIndexesSet()) {
+            return mutableReader.getMemoryUseByFieldId(options.getInclude(), stripeIndex);
+        } else if (options.getNamesSet()) {
+            return mutableReader.getMemoryUseByName(options.getIncludeNames(), stripeIndex);
+        } else if (options.getTypeIdsSet()) {
+            return mutableReader.getMemoryUseByTypeId(options.getInclude(), stripeIndex);
+        } else {
+            return mutableReader.getMemoryUse(stripeIndex);
+        }
+    }
+
+    // RowReader::getSelectedColumns() returns a std::vector<bool>, which cxx
+    // does not support.
+    template<typename T>
+    std::unique_ptr<std::vector<uint64_t>> selectedColumnIds(const T &rowReader) {
+        const std::vector<bool> &selected = rowReader.getSelectedColumns();
+        auto ids = std::make_unique<std::vector<uint64_t>>();
+        for (uint64_t id = 0; id < selected.size(); id++) {
+            if (selected[id]) {
+                ids->push_back(id);
+            }
+        }
+        return ids;
+    }
+
+    // Counts non-null string and binary values longer than maxLength bytes in
+    // the batch and its children, and shortens them to maxLength if 'truncate'
+    // is true.
+    template<typename T>
+    uint64_t limitValueLengths(T &batch, uint64_t maxLength, bool truncate) {
+        uint64_t oversized = 0;
+        if (auto strings = dynamic_cast<orc::StringVectorBatch*>(&batch)) {
+            for (uint64_t i = 0; i < strings->numElements; i++) {
+                if (strings->hasNulls && !strings->notNull[i]) {
+                    continue;
+                }
+                if (strings->length[i] > 0 && static_cast<uint64_t>(strings->length[i]) > maxLength) {
+                    oversized++;
+                    if (truncate) {
+                        strings->length[i] = static_cast<int64_t>(maxLength);
+                    }
+                }
+            }
+        } else if (auto structs = dynamic_cast<orc::StructVectorBatch*>(&batch)) {
+            for (auto field : structs->fields) {
+                oversized += limitValueLengths(*field, maxLength, truncate);
+            }
+        } else if (auto lists = dynamic_cast<orc::ListVectorBatch*>(&batch)) {
+            oversized += limitValueLengths(*lists->elements, maxLength, truncate);
+        } else if (auto maps = dynamic_cast<orc::MapVectorBatch*>(&batch)) {
+            oversized += limitValueLengths(*maps->keys, maxLength, truncate);
+            oversized += limitValueLengths(*maps->elements, maxLength, truncate);
+        } else if (auto unions = dynamic_cast<orc::UnionVectorBatch*>(&batch)) {
+            for (auto child : unions->children) {
+                oversized += limitValueLengths(*child, maxLength, truncate);
+            }
+        }
+        return oversized;
+    }
+
+    // Copies a value to the end of the batch's blob, and points the index-th
+    // string to it. Strings already pointing to the blob are moved along with
+    // it when it needs to be reallocated.
+    template<typename T, typename Bytes>
+    void setString(T &batch, uint64_t index, Bytes value) {
+        char *oldBase = batch.blob.data();
+        uint64_t oldSize = batch.blob.size();
+        uint64_t newSize = oldSize + value.size();
+        if (newSize > batch.blob.capacity()) {
+            // Grow geometrically, so pushing strings one by one is not quadratic
+            batch.blob.reserve(std::max(newSize, 2 * batch.blob.capacity()));
+            char *newBase = batch.blob.data();
+            for (uint64_t i = 0; i < batch.numElements; i++) {
+                if (batch.data[i] >= oldBase && batch.data[i] < oldBase + oldSize) {
+                    batch.data[i] = newBase + (batch.data[i] - oldBase);
+                }
+            }
+        }
+        batch.blob.resize(newSize);
+        std::memcpy(batch.blob.data() + oldSize, value.data(), value.size());
+        batch.data[index] = batch.blob.data() + oldSize;
+        batch.length[index] = static_cast<int64_t>(value.size());
+    }
+
+    // Sets the index-th value of a Decimal128VectorBatch, given the high and low
+    // 64 bits of its mantissa.
+    template<typename T>
+    void setInt128(T &batch, uint64_t index, int64_t highBits, uint64_t lowBits) {
+        batch.values[index] = orc::Int128(highBits, lowBits);
+    }
+
+    // Removes all rows from the batch and its children, so it can be filled
+    // again.
+    template<typename T>
+    void clearBatch(T &batch) {
+        batch.numElements = 0;
+        batch.hasNulls = false;
+        if (auto strings = dynamic_cast<orc::StringVectorBatch*>(&batch)) {
+            strings->blob.resize(0);
+        } else if (auto structs = dynamic_cast<orc::StructVectorBatch*>(&batch)) {
+            for (auto field : structs->fields) {
+                clearBatch(*field);
+            }
+        } else if (auto lists = dynamic_cast<orc::ListVectorBatch*>(&batch)) {
+            clearBatch(*lists->elements);
+        } else if (auto maps = dynamic_cast<orc::MapVectorBatch*>(&batch)) {
+            clearBatch(*maps->keys);
+            clearBatch(*maps->elements);
+        } else if (auto unions = dynamic_cast<orc::UnionVectorBatch*>(&batch)) {
+            for (auto child : unions->children) {
+                clearBatch(*child);
+            }
+        }
+    }
+
+    // orc::CompressionKind is an unscoped enum with prefixed variant names,
+    // which cxx cannot bind as a shared enum.
+    template<typename T>
+    void setCompression(T &options, uint8_t kind) {
+        options.setCompression(static_cast<orc::CompressionKind>(kind));
+    }
+
+    // WriterOptions::setColumnsUseBloomFilter() takes a std::set, which cxx does
+    // not support.
+    template<typename T, typename Columns>
+    void setColumnsUseBloomFilter(T &options, Columns columns) {
+        options.setColumnsUseBloomFilter(std::set<uint64_t>(columns.begin(), columns.end()));
+    }
+
+    // Typed accessors of column statistics. Each returns false (and leaves the
+    // other arguments unchanged) if the statistics are of an other type; has*
+    // arguments are set to whether the following values are present, as ORC
+    // omits them when all values are null or when they overflow.
+    template<typename T>
+    bool booleanStatistics(const T &statistics, bool &hasCount, uint64_t &falseCount, uint64_t &trueCount) {
+        auto booleans = dynamic_cast<const orc::BooleanColumnStatistics*>(&statistics);
+        if (booleans == nullptr) {
+            return false;
+        }
+        hasCount = booleans->hasCount();
+        if (hasCount) {
+            falseCount = booleans->getFalseCount();
+            trueCount = booleans->getTrueCount();
+        }
+        return true;
+    }
+
+    template<typename T>
+    bool integerStatistics(const T &statistics, bool &hasRange, int64_t &minimum, int64_t &maximum, bool &hasSum, int64_t &sum) {
+        auto integers = dynamic_cast<const orc::IntegerColumnStatistics*>(&statistics);
+        if (integers == nullptr) {
+            return false;
+        }
+        hasRange = integers->hasMinimum() && integers->hasMaximum();
+        if (hasRange) {
+            minimum = integers->getMinimum();
+            maximum = integers->getMaximum();
+        }
+        hasSum = integers->hasSum();
+        if (hasSum) {
+            sum = integers->getSum();
+        }
+        return true;
+    }
+
+    template<typename T>
+    bool doubleStatistics(const T &statistics, bool &hasRange, double &minimum, double &maximum, bool &hasSum, double &sum) {
+        auto doubles = dynamic_cast<const orc::DoubleColumnStatistics*>(&statistics);
+        if (doubles == nullptr) {
+            return false;
+        }
+        hasRange = doubles->hasMinimum() && doubles->hasMaximum();
+        if (hasRange) {
+            minimum = doubles->getMinimum();
+            maximum = doubles->getMaximum();
+        }
+        hasSum = doubles->hasSum();
+        if (hasSum) {
+            sum = doubles->getSum();
+        }
+        return true;
+    }
+
+    template<typename T>
+    bool stringStatistics(const T &statistics, bool &hasRange, std::string &minimum, std::string &maximum, bool &hasTotalLength, uint64_t &totalLength) {
+        auto strings = dynamic_cast<const orc::StringColumnStatistics*>(&statistics);
+        if (strings == nullptr) {
+            return false;
+        }
+        hasRange = strings->hasMinimum() && strings->hasMaximum();
+        if (hasRange) {
+            minimum = strings->getMinimum();
+            maximum = strings->getMaximum();
+        }
+        hasTotalLength = strings->hasTotalLength();
+        if (hasTotalLength) {
+            totalLength = strings->getTotalLength();
+        }
+        return true;
+    }
+
+    template<typename T>
+    bool binaryStatistics(const T &statistics, bool &hasTotalLength, uint64_t &totalLength) {
+        auto binaries = dynamic_cast<const orc::BinaryColumnStatistics*>(&statistics);
+        if (binaries == nullptr) {
+            return false;
+        }
+        hasTotalLength = binaries->hasTotalLength();
+        if (hasTotalLength) {
+            totalLength = binaries->getTotalLength();
+        }
+        return true;
+    }
+
+    // Decimals are returned as strings, as orc::Decimal wraps an Int128
+    template<typename T>
+    bool decimalStatistics(const T &statistics, bool &hasRange, std::string &minimum, std::string &maximum, bool &hasSum, std::string &sum) {
+        auto decimals = dynamic_cast<const orc::DecimalColumnStatistics*>(&statistics);
+        if (decimals == nullptr) {
+            return false;
+        }
+        hasRange = decimals->hasMinimum() && decimals->hasMaximum();
+        if (hasRange) {
+            minimum = decimals->getMinimum().toString();
+            maximum = decimals->getMaximum().toString();
+        }
+        hasSum = decimals->hasSum();
+        if (hasSum) {
+            sum = decimals->getSum().toString();
+        }
+        return true;
+    }
+
+    template<typename T>
+    bool dateStatistics(const T &statistics, bool &hasRange, int32_t &minimum, int32_t &maximum) {
+        auto dates = dynamic_cast<const orc::DateColumnStatistics*>(&statistics);
+        if (dates == nullptr) {
+            return false;
+        }
+        hasRange = dates->hasMinimum() && dates->hasMaximum();
+        if (hasRange) {
+            minimum = dates->getMinimum();
+            maximum = dates->getMaximum();
+        }
+        return true;
+    }
+
+    // Timestamps are returned as milliseconds since the epoch, and the
+    // nanoseconds within the millisecond.
+    template<typename T>
+    bool timestampStatistics(const T &statistics, bool &hasRange, int64_t &minimumMillis, int32_t &minimumNanos, int64_t &maximumMillis, int32_t &maximumNanos) {
+        auto timestamps = dynamic_cast<const orc::TimestampColumnStatistics*>(&statistics);
+        if (timestamps == nullptr) {
+            return false;
+        }
+        hasRange = timestamps->hasMinimum() && timestamps->hasMaximum();
+        if (hasRange) {
+            minimumMillis = timestamps->getMinimum();
+            minimumNanos = timestamps->getMinimumNanos();
+            maximumMillis = timestamps->getMaximum();
+            maximumNanos = timestamps->getMaximumNanos();
+        }
+        return true;
+    }
+
+    // Reader::getBloomFilters() takes a std::set and returns a std::map, which cxx
+    // does not support; so bloom filters are returned one column at a time, or
+    // nullptr if the column has none in this stripe.
+    template<typename T>
+    std::unique_ptr<orc::BloomFilterIndex> bloomFilterIndex(const T &reader, uint32_t stripeIndex, uint32_t columnId) {
+        std::set<uint32_t> included = {columnId};
+        auto bloomFilters = reader.getBloomFilters(stripeIndex, included);
+        auto bloomFilter = bloomFilters.find(columnId);
+        if (bloomFilter == bloomFilters.end()) {
+            return nullptr;
+        }
+        return std::make_unique<orc::BloomFilterIndex>(std::move(bloomFilter->second));
+    }
+
+    // BloomFilterIndex holds a std::vector of std::shared_ptr, which cxx does
+    // not support.
+    template<typename T>
+    uint64_t bloomFilterCount(const T &index) {
+        return index.entries.size();
+    }
+
+    template<typename T>
+    bool bloomFilterTestLong(const T &index, uint64_t rowGroup, int64_t value) {
+        return index.entries.at(rowGroup)->testLong(value);
+    }
+
+    template<typename T>
+    bool bloomFilterTestDouble(const T &index, uint64_t rowGroup, double value) {
+        return index.entries.at(rowGroup)->testDouble(value);
+    }
+
+    template<typename T, typename Bytes>
+    bool bloomFilterTestBytes(const T &index, uint64_t rowGroup, Bytes value) {
+        return index.entries.at(rowGroup)->testBytes(reinterpret_cast<const char*>(value.data()), value.size());
+    }
+
     namespace accessors {
         getter(numElements);
         getter(length);
@@ -82,9 +637,17 @@ namespace orcxx_rs {
         getter(scale);
         getter(hasNulls);
         getter(notNull);
+        getter(capacity);
+        getter(tags);
+        getter(children);
+
+        setter(numElements);
+        setter(hasNulls);
     }
 
     typedef orc::DataBuffer<char> CharDataBuffer;
+    typedef orc::DataBuffer<unsigned char> UCharDataBuffer;
+    typedef orc::DataBuffer<uint64_t> UInt64DataBuffer;
     typedef orc::DataBuffer<char*> StringDataBuffer;
     typedef orc::DataBuffer<int64_t> Int64DataBuffer;
     typedef orc::DataBuffer<orc::Int128> Int128DataBuffer;
@@ -92,5 +655,6 @@ namespace orcxx_rs {
     typedef orc::ColumnVectorBatch* ColumnVectorBatchPtr;
 
     typedef std::list<std::string> StringList;
+    typedef std::list<uint64_t> U64List;
 }
 
@@ -0,0 +1,147 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Drops duplicate rows, using bounded memory
+//!
+//! Datasets split across many files often contain the same record more than once,
+//! usually close to each other. [`DedupRowIterator`] wraps any iterator of rows
+//! (such as a [`RowIterator`](::row_iterator::RowIterator)) and drops rows whose
+//! key was already seen, either:
+//!
+//! * among the keys of the last rows it yielded (the window), which catches
+//!   duplicates close to each other in any order; or
+//! * as the key of the last row it yielded, which catches all duplicates when rows
+//!   are sorted by key.
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::dedup::DedupRowIterator;
+//!
+//! let rows = vec![(1, "a"), (2, "b"), (1, "a"), (3, "c"), (4, "d"), (1, "a")];
+//!
+//! let deduplicated: Vec<_> = DedupRowIterator::with_window(rows.into_iter(), 2, |row| row.0)
+//!     .collect();
+//! // The last row is kept, because (1, "a") was out of the window by then
+//! assert_eq!(
+//!     deduplicated,
+//!     vec![(1, "a"), (2, "b"), (3, "c"), (4, "d"), (1, "a")]
+//! );
+//! ```
+
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+
+enum Seen<K> {
+    /// Key of the last row yielded
+    Sorted(Option<K>),
+    /// Keys of the last rows yielded, oldest first, and the same keys as a set
+    Window {
+        size: usize,
+        keys: VecDeque<K>,
+        key_set: HashSet<K>,
+    },
+}
+
+/// Iterator adapter which drops rows whose key was recently yielded.
+///
+/// See the [module-level documentation](self).
+pub struct DedupRowIterator<I, K, F> {
+    rows: I,
+    key: F,
+    seen: Seen<K>,
+}
+
+impl<I, K, F> DedupRowIterator<I, K, F>
+where
+    I: Iterator,
+    K: Hash + Eq + Clone,
+    F: FnMut(&I::Item) -> K,
+{
+    /// Drops rows whose key is the same as one of the last `window_size` rows
+    /// yielded. Memory use is proportional to `window_size`.
+    ///
+    /// Dropped rows do not move their key back to the front of the window.
+    ///
+    /// # Panics
+    ///
+    /// If `window_size` is 0.
+    pub fn with_window(rows: I, window_size: usize, key: F) -> DedupRowIterator<I, K, F> {
+        assert_ne!(window_size, 0, "window_size must be positive");
+        DedupRowIterator {
+            rows,
+            key,
+            seen: Seen::Window {
+                size: window_size,
+                keys: VecDeque::with_capacity(window_size),
+                key_set: HashSet::with_capacity(window_size),
+            },
+        }
+    }
+
+    /// Drops rows whose key is the same as the last row yielded; this drops all
+    /// duplicates if `rows` are sorted by key.
+    pub fn sorted(rows: I, key: F) -> DedupRowIterator<I, K, F> {
+        DedupRowIterator {
+            rows,
+            key,
+            seen: Seen::Sorted(None),
+        }
+    }
+}
+
+impl<I, K, F> Iterator for DedupRowIterator<I, K, F>
+where
+    I: Iterator,
+    K: Hash + Eq + Clone,
+    F: FnMut(&I::Item) -> K,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        for row in &mut self.rows {
+            let key = (self.key)(&row);
+            match &mut self.seen {
+                Seen::Sorted(previous) => {
+                    if previous.as_ref() == Some(&key) {
+                        continue;
+                    }
+                    *previous = Some(key);
+                }
+                Seen::Window {
+                    size,
+                    keys,
+                    key_set,
+                } => {
+                    if key_set.contains(&key) {
+                        continue;
+                    }
+                    if keys.len() == *size {
+                        let oldest = keys.pop_front().expect("Empty window");
+                        key_set.remove(&oldest);
+                    }
+                    key_set.insert(key.clone());
+                    keys.push_back(key);
+                }
+            }
+            return Some(row);
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.rows.size_hint();
+        let nothing_yielded = match &self.seen {
+            Seen::Sorted(previous) => previous.is_none(),
+            Seen::Window { keys, .. } => keys.is_empty(),
+        };
+        // Once a row was yielded, all remaining rows may be duplicates of it
+        if nothing_yielded {
+            (usize::min(min, 1), max)
+        } else {
+            (0, max)
+        }
+    }
+}
@@ -10,15 +10,25 @@
 use rust_decimal::Decimal;
 use thiserror::Error;
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryInto;
-use std::iter::Map;
+use std::fmt;
+use std::hash::Hash;
+use std::iter::{FromIterator, Map};
 use std::num::TryFromIntError;
+use std::rc::Rc;
 use std::slice::IterMut;
 use std::str::Utf8Error;
+use std::sync::Arc;
 
-use errors::OrcError;
+use errors::{OrcError, ReadError};
 use kind::Kind;
-use vector::{BorrowedColumnVectorBatch, ColumnVectorBatch, DecimalVectorBatch, StructVectorBatch};
+use reader::{Reader, RowReader, SelectedColumn};
+use vector::{
+    Bitmap, BorrowedColumnVectorBatch, ColumnVectorBatch, Decimal128VectorBatch,
+    DecimalVectorBatch, ListVectorBatch, RangeVectorBatchIterator, StringVectorBatch,
+    StructVectorBatch, UnionVectorBatch,
+};
 
 /// Error returned when failing to read a particular batch of data
 #[derive(Debug, Error, PartialEq)]
@@ -50,6 +60,134 @@ pub enum DeserializationError {
     /// a `src` column batch longer than its a `dst` vector.
     #[error("Tried to deserialize {src}-long buffer into {dst}-long buffer")]
     MismatchedLength { src: u64, dst: u64 },
+    /// A structure was read without its [`Kind`], so its fields are matched to
+    /// columns by position, but there are more columns than fields.
+    ///
+    /// Contains a human-readable error.
+    #[error("Mismatched number of columns: {0}")]
+    MismatchedColumnCount(String),
+    /// A date (number of days since 1970-01-01) is out of the range supported by
+    /// the type it is deserialized into.
+    #[error("Date out of range: {0} days since 1970-01-01")]
+    DateOutOfRange(i64),
+    /// A timestamp is out of the range supported by the type it is deserialized into.
+    #[error("Timestamp out of range: {seconds}s and {nanoseconds}ns since 1970-01-01")]
+    TimestampOutOfRange { seconds: i64, nanoseconds: i64 },
+    /// A decimal column has a scale greater than [`MAX_DECIMAL_SCALE`] (28), so its
+    /// values cannot be represented by [`Decimal`].
+    #[error("Decimal scale {0} is not supported by rust_decimal")]
+    UnsupportedDecimalScale(i32),
+    /// The mantissa of a decimal (from a column with a precision greater than 28)
+    /// does not fit in the 96 bits of [`Decimal`].
+    #[error("Decimal out of range: {mantissa} with scale {scale}")]
+    DecimalOutOfRange { mantissa: i128, scale: u32 },
+    /// A row of a union column has a tag which is not one of the variants of the
+    /// type it is deserialized into, or an offset past the end of its variant's values.
+    #[error("Invalid union value: tag {tag} and offset {offset}")]
+    InvalidUnionValue { tag: u8, offset: u64 },
+    /// Rows were read again to deserialize them one at a time (see
+    /// [`RowIterator::on_error`](::row_iterator::RowIterator::on_error)), but could
+    /// not be.
+    #[error("Could not read rows again: {0}")]
+    ReadError(ReadError),
+    /// Another error, along with where it occurred.
+    ///
+    /// `row` is the index of the row in the batch passed to
+    /// [`read_from_vector_batch`](OrcDeserialize::read_from_vector_batch), if known,
+    /// and `column` is the path to the column in the same format as
+    /// [`RowReader::selected_columns`](::reader::RowReader::selected_columns)
+    /// (field names separated by dots, and `_elem`, `_key`, or `_value` for children
+    /// of lists and maps), or an empty string for the batch itself.
+    #[error("{source} (at row {row:?} of column {column:?})")]
+    Located {
+        row: Option<usize>,
+        column: String,
+        source: Box<DeserializationError>,
+    },
+}
+
+impl DeserializationError {
+    /// Returns the index of the row where the error occurred, if known
+    pub fn row(&self) -> Option<usize> {
+        match self {
+            DeserializationError::Located { row, .. } => *row,
+            _ => None,
+        }
+    }
+
+    /// Returns the path to the column where the error occurred, or an empty
+    /// string if unknown
+    pub fn column(&self) -> &str {
+        match self {
+            DeserializationError::Located { column, .. } => column,
+            _ => "",
+        }
+    }
+
+    /// Returns the error without its location
+    pub fn inner(&self) -> &DeserializationError {
+        match self {
+            DeserializationError::Located { source, .. } => source,
+            _ => self,
+        }
+    }
+
+    /// Sets the index of the row where the error occurred
+    pub(crate) fn at_row(self, row: usize) -> DeserializationError {
+        match self {
+            DeserializationError::Located { column, source, .. } => DeserializationError::Located {
+                row: Some(row),
+                column,
+                source,
+            },
+            _ => DeserializationError::Located {
+                row: Some(row),
+                column: String::new(),
+                source: Box::new(self),
+            },
+        }
+    }
+
+    /// Prepends a field name to the path of the column where the error occurred.
+    ///
+    /// This is used by code generated by `orcxx_derive`.
+    #[doc(hidden)]
+    pub fn in_column(self, field_name: &str) -> DeserializationError {
+        match self {
+            DeserializationError::Located {
+                row,
+                column,
+                source,
+            } => DeserializationError::Located {
+                row,
+                column: if column.is_empty() {
+                    field_name.to_string()
+                } else {
+                    format!("{}.{}", field_name, column)
+                },
+                source,
+            },
+            _ => DeserializationError::Located {
+                row: None,
+                column: field_name.to_string(),
+                source: Box::new(self),
+            },
+        }
+    }
+}
+
+/// Returns an [`UnexpectedNull`](DeserializationError::UnexpectedNull) error,
+/// located at the first null value of `src`.
+fn unexpected_null<'a, V: ColumnVectorBatch<'a>>(src: &V, type_name: &str) -> DeserializationError {
+    let error =
+        DeserializationError::UnexpectedNull(format!("{} column contains nulls", type_name));
+    match src
+        .not_null()
+        .and_then(|not_null| not_null.iter().position(|&b| b == 0))
+    {
+        Some(row) => error.at_row(row),
+        None => error,
+    }
 }
 
 fn check_kind_equals(
@@ -83,6 +221,121 @@ pub trait CheckableKind {
     /// to get errors early and with a human-readable error message instead of cast errors
     /// or deserialization into incorrect types (eg. if a file has two fields swapped).
     fn check_kind(kind: &Kind) -> Result<(), String>;
+
+    /// Same as [`check_kind`](CheckableKind::check_kind), but also looks up the
+    /// file statistics for columns which may contain nulls but are deserialized
+    /// into types which cannot represent them (ie. not `Option`).
+    ///
+    /// When the returned report [is ok](NullabilityReport::is_ok), this file can
+    /// be deserialized into this type without
+    /// [`UnexpectedNull`](DeserializationError::UnexpectedNull) errors, so there
+    /// is no need to wrap fields in `Option` defensively.
+    fn check_kind_with_stats(
+        reader: &Reader,
+        row_reader: &RowReader,
+    ) -> Result<NullabilityReport, String> {
+        Self::check_kind(&row_reader.selected_kind())?;
+        let mut check = NullabilityCheck {
+            has_nulls: reader.column_has_nulls().unwrap_or_default(),
+            report: NullabilityReport::default(),
+        };
+        Self::check_nulls(&row_reader.selected_column_tree(), "", &mut check);
+        Ok(check.report)
+    }
+
+    /// Adds to `check` the columns read by this type from `column` (whose path
+    /// is `path`) which may contain nulls that this type cannot represent.
+    ///
+    /// By default, requires `column` to have no nulls, then calls
+    /// [`check_children_nulls`](CheckableKind::check_children_nulls).
+    fn check_nulls(column: &SelectedColumn, path: &str, check: &mut NullabilityCheck) {
+        check.require_not_null(column, path);
+        Self::check_children_nulls(column, path, check);
+    }
+
+    /// Same as [`check_nulls`](CheckableKind::check_nulls), but only for the columns
+    /// nested in `column`. Does nothing by default, which is correct for scalars.
+    fn check_children_nulls(_column: &SelectedColumn, _path: &str, _check: &mut NullabilityCheck) {}
+}
+
+/// Columns which are deserialized into types which cannot represent nulls, but may
+/// contain nulls according to the file statistics.
+///
+/// Returned by [`CheckableKind::check_kind_with_stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NullabilityReport {
+    pub nullable_columns: Vec<NullableColumn>,
+}
+
+impl NullabilityReport {
+    /// Returns whether no column needs to be deserialized into an `Option`
+    pub fn is_ok(&self) -> bool {
+        self.nullable_columns.is_empty()
+    }
+}
+
+impl fmt::Display for NullabilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "No column may contain unexpected nulls");
+        }
+        for (i, column) in self.nullable_columns.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", column)?;
+        }
+        Ok(())
+    }
+}
+
+/// Column of a [`NullabilityReport`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NullableColumn {
+    /// Path to the column (field names separated by dots), or an empty string for
+    /// the root column
+    pub path: String,
+    pub column_id: u64,
+}
+
+impl fmt::Display for NullableColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "<root>"
+        } else {
+            &self.path
+        };
+        write!(
+            f,
+            "Column {} (id {}) may contain nulls, but is not deserialized into an Option",
+            path, self.column_id
+        )
+    }
+}
+
+/// State of [`CheckableKind::check_kind_with_stats`], passed to
+/// [`CheckableKind::check_nulls`]
+pub struct NullabilityCheck {
+    has_nulls: Vec<bool>,
+    report: NullabilityReport,
+}
+
+impl NullabilityCheck {
+    /// Adds `column` to the report if the file statistics say it may contain nulls
+    /// (or have no information about it)
+    pub fn require_not_null(&mut self, column: &SelectedColumn, path: &str) {
+        let id: Option<usize> = column.id.try_into().ok();
+        let has_nulls = id
+            .and_then(|id| self.has_nulls.get(id))
+            .copied()
+            .unwrap_or(true);
+        if has_nulls {
+            self.report.nullable_columns.push(NullableColumn {
+                path: path.to_owned(),
+                column_id: column.id,
+            });
+        }
+    }
 }
 
 // Needed because most structs are going to have Option as fields, and code generated by
@@ -92,6 +345,14 @@ impl<T: CheckableKind> CheckableKind for Option<T> {
     fn check_kind(kind: &Kind) -> Result<(), String> {
         T::check_kind(kind)
     }
+
+    fn check_nulls(column: &SelectedColumn, path: &str, check: &mut NullabilityCheck) {
+        T::check_children_nulls(column, path, check)
+    }
+
+    fn check_children_nulls(column: &SelectedColumn, path: &str, check: &mut NullabilityCheck) {
+        T::check_children_nulls(column, path, check)
+    }
 }
 
 /// Types which provide a static `columns` method, which returns the names of all
@@ -106,6 +367,20 @@ pub trait OrcStruct {
     }
 
     fn columns_with_prefix(prefix: &str) -> Vec<String>;
+
+    /// Same as [`columns`](OrcStruct::columns), but without columns missing from
+    /// `kind` (usually [`reader.kind()`](::reader::Reader::kind)), as they cannot be
+    /// selected.
+    ///
+    /// Fields read from these columns must have a default value (eg. with
+    /// `#[orc(default)]` in `orcxx_derive`), or [`CheckableKind::check_kind`] fails.
+    fn columns_in(kind: &Kind) -> Vec<String> {
+        let column_names: HashSet<String> = kind.column_names().into_iter().collect();
+        Self::columns()
+            .into_iter()
+            .filter(|column| column_names.contains(column))
+            .collect()
+    }
 }
 
 impl<T: OrcStruct> OrcStruct for Option<T> {
@@ -114,8 +389,19 @@ impl<T: OrcStruct> OrcStruct for Option<T> {
     }
 }
 
+/// Marker for structures with `#[orc(unsafe_assume_utf8)]` fields
+///
+/// `#[derive(OrcDeserialize)]` only accepts this attribute on structures implementing
+/// this trait, so skipping UTF-8 validation needs an `unsafe` block in user code.
+///
+/// # Safety
+///
+/// Columns read into fields with `#[orc(unsafe_assume_utf8)]` must only contain
+/// valid UTF-8 strings.
+pub unsafe trait AssumeUtf8 {}
+
 /// Types which can be read in batch from ORC columns ([`BorrowedColumnVectorBatch`]).
-pub trait OrcDeserialize: Sized + Default + CheckableKind {
+pub trait OrcDeserialize: Sized + CheckableKind {
     /// Reads from a [`BorrowedColumnVectorBatch`] to a structure that behaves like
     /// a rewindable iterator of `&mut Self`, and returns the number of rows written.
     ///
@@ -133,6 +419,48 @@ pub trait OrcDeserialize: Sized + Default + CheckableKind {
         Self: 'a,
         &'b mut T: DeserializationTarget<'a, Item = Self> + 'b;
 
+    /// Same as [`read_from_vector_batch`](OrcDeserialize::read_from_vector_batch),
+    /// but given the kind of `src` (usually
+    /// [`row_reader.selected_kind()`](::reader::RowReader::selected_kind)).
+    ///
+    /// This only differs from
+    /// [`read_from_vector_batch`](OrcDeserialize::read_from_vector_batch) for
+    /// structures which match their fields to columns by name (with
+    /// `#[orc(match_by_name)]`) or fill missing fields with a default value (with
+    /// `#[orc(default)]`), and containers of such structures.
+    fn read_from_vector_batch_with_kind<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        _kind: &Kind,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        Self: 'a,
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        Self::read_from_vector_batch(src, dst)
+    }
+
+    /// Same as [`read_from_vector_batch`](OrcDeserialize::read_from_vector_batch),
+    /// but does not check strings are valid UTF-8.
+    ///
+    /// This is what fields with the `#[orc(unsafe_assume_utf8)]` attribute use, and it only
+    /// differs from [`read_from_vector_batch`](OrcDeserialize::read_from_vector_batch)
+    /// for [`String`] and `Option<String>`.
+    ///
+    /// # Safety
+    ///
+    /// All strings in `src` must be valid UTF-8.
+    unsafe fn read_from_vector_batch_assume_utf8<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        Self: 'a,
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        Self::read_from_vector_batch(src, dst)
+    }
+
     /// Reads from a [`BorrowedColumnVectorBatch`] and returns a `Vec<Option<Self>>`
     ///
     /// Users should call
@@ -144,7 +472,10 @@ pub trait OrcDeserialize: Sized + Default + CheckableKind {
     /// which takes care of allocating a buffer, and returns it.
     fn from_vector_batch(
         vector_batch: &BorrowedColumnVectorBatch,
-    ) -> Result<Vec<Self>, DeserializationError> {
+    ) -> Result<Vec<Self>, DeserializationError>
+    where
+        Self: Default,
+    {
         let num_elements = vector_batch.num_elements();
         let num_elements = num_elements
             .try_into()
@@ -156,6 +487,109 @@ pub trait OrcDeserialize: Sized + Default + CheckableKind {
     }
 }
 
+/// Types which can be read in batch from ORC columns, borrowing strings and
+/// binaries from the [`BorrowedColumnVectorBatch`] instead of copying them.
+///
+/// This is implemented for `&'a str`, `&'a [u8]` and their `Option`s, for
+/// structures with `#[derive(OrcDeserializeRef)]`, and for all types
+/// implementing [`OrcDeserialize`] (which are copied as usual). Values can only
+/// be used as long as the batch they were read from, so this is meant for scanning
+/// batches returned by [`RowReader::read_into`].
+///
+/// Lists, maps and `Option`s of borrowing structures are not supported.
+pub trait OrcDeserializeRef<'a>: Sized + CheckableKind + 'a {
+    /// Same as [`OrcDeserialize::read_from_vector_batch`], but values may borrow
+    /// from `src`.
+    fn read_ref_from_vector_batch<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b;
+
+    /// Same as [`OrcDeserialize::read_from_vector_batch_with_kind`], but values
+    /// may borrow from `src`.
+    fn read_ref_from_vector_batch_with_kind<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        _kind: &Kind,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        Self::read_ref_from_vector_batch(src, dst)
+    }
+
+    /// Same as [`OrcDeserialize::read_from_vector_batch_assume_utf8`], but values
+    /// may borrow from `src`.
+    ///
+    /// # Safety
+    ///
+    /// All strings in `src` must be valid UTF-8.
+    unsafe fn read_ref_from_vector_batch_assume_utf8<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        Self::read_ref_from_vector_batch(src, dst)
+    }
+
+    /// Same as [`OrcDeserialize::from_vector_batch`], but values may borrow from
+    /// `vector_batch`.
+    fn ref_from_vector_batch(
+        vector_batch: &BorrowedColumnVectorBatch<'a>,
+    ) -> Result<Vec<Self>, DeserializationError>
+    where
+        Self: Default,
+    {
+        let num_elements = vector_batch.num_elements();
+        let num_elements = num_elements
+            .try_into()
+            .map_err(DeserializationError::UsizeOverflow)?;
+        let mut values = Vec::with_capacity(num_elements);
+        values.resize_with(num_elements, Default::default);
+        Self::read_ref_from_vector_batch(vector_batch, &mut values)?;
+        Ok(values)
+    }
+}
+
+/// Types which do not borrow from batches are copied from them, so they can be
+/// used as fields of structures implementing [`OrcDeserializeRef`]
+impl<'a, D: OrcDeserialize + 'a> OrcDeserializeRef<'a> for D {
+    fn read_ref_from_vector_batch<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        D::read_from_vector_batch(src, dst)
+    }
+
+    fn read_ref_from_vector_batch_with_kind<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        kind: &Kind,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        D::read_from_vector_batch_with_kind(src, kind, dst)
+    }
+
+    unsafe fn read_ref_from_vector_batch_assume_utf8<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        D::read_from_vector_batch_assume_utf8(src, dst)
+    }
+}
+
 macro_rules! impl_scalar {
     ($ty:ty, $kind:expr, $method:ident) => {
         impl_scalar!($ty, $kind, $method, |s| Ok(s as $ty));
@@ -185,13 +619,10 @@ macro_rules! impl_scalar {
                     .$method()
                     .map_err(DeserializationError::MismatchedColumnKind)?;
                 match src.try_iter_not_null() {
-                    None => Err(DeserializationError::UnexpectedNull(format!(
-                        "{} column contains nulls",
-                        stringify!($ty)
-                    ))),
+                    None => Err(unexpected_null(&src, stringify!($ty))),
                     Some(it) => {
-                        for (s, d) in it.zip(dst.iter_mut()) {
-                            *d = ($cast)(s)?
+                        for (i, (s, d)) in it.zip(dst.iter_mut()).enumerate() {
+                            *d = ($cast)(s).map_err(|e: DeserializationError| e.at_row(i))?
                         }
 
                         Ok(src.num_elements().try_into().unwrap())
@@ -211,10 +642,12 @@ macro_rules! impl_scalar {
                 let src = src
                     .$method()
                     .map_err(DeserializationError::MismatchedColumnKind)?;
-                for (s, d) in src.iter().zip(dst.iter_mut()) {
+                for (i, (s, d)) in src.iter().zip(dst.iter_mut()).enumerate() {
                     match s {
                         None => *d = None,
-                        Some(s) => *d = Some(($cast)(s)?),
+                        Some(s) => {
+                            *d = Some(($cast)(s).map_err(|e: DeserializationError| e.at_row(i))?)
+                        }
                     }
                 }
 
@@ -231,210 +664,1149 @@ impl_scalar!(i32, [Kind::Int], try_into_longs);
 impl_scalar!(i64, [Kind::Long], try_into_longs);
 impl_scalar!(f32, [Kind::Float], try_into_doubles);
 impl_scalar!(f64, [Kind::Double], try_into_doubles);
-impl_scalar!(String, [Kind::String], try_into_strings, |s| {
-    std::str::from_utf8(s)
-        .map_err(DeserializationError::Utf8Error)
-        .map(|s| s.to_string())
-});
-impl_scalar!(Vec<u8>, [Kind::Binary], try_into_strings, |s: &[u8]| Ok(
-    s.to_vec()
-));
 
-impl_scalar!(
-    crate::Timestamp,
-    [Kind::Timestamp],
-    try_into_timestamps,
-    |s: (i64, i64)| Ok(crate::Timestamp {
-        seconds: s.0,
-        nanoseconds: s.1
-    })
-);
+/// Calls `f` with the index and value (`None` for nulls) of each string in the
+/// batch.
+///
+/// When possible, strings are sliced from the batch's
+/// [`bytes()`](StringVectorBatch::bytes) buffer using
+/// [`contiguous_ranges()`](StringVectorBatch::contiguous_ranges), which is faster
+/// than reading the pointer of each string.
+fn try_for_each_string<'s, F>(
+    src: &StringVectorBatch<'s>,
+    mut f: F,
+) -> Result<(), DeserializationError>
+where
+    F: FnMut(usize, Option<&'s [u8]>) -> Result<(), DeserializationError>,
+{
+    match src.contiguous_ranges() {
+        Some(ranges) => {
+            let bytes = src.bytes();
+            for (i, range) in ranges.into_iter().enumerate() {
+                f(i, range.map(|range| &bytes[range]))?;
+            }
+        }
+        None => {
+            for (i, s) in src.iter().enumerate() {
+                f(i, s)?;
+            }
+        }
+    }
+    Ok(())
+}
 
-impl OrcStruct for Decimal {
+/// Shared implementation of `read_from_vector_batch` for string-like types which
+/// cannot be null
+fn read_not_null_strings<'a, 'b, 's, T, U, F>(
+    src: &StringVectorBatch<'s>,
+    mut dst: &'b mut T,
+    type_name: &str,
+    mut decode: F,
+) -> Result<usize, DeserializationError>
+where
+    U: 'a,
+    &'b mut T: DeserializationTarget<'a, Item = U> + 'b,
+    F: FnMut(&'s [u8]) -> Result<U, DeserializationError>,
+{
+    if src.not_null_ptr().is_some() {
+        return Err(unexpected_null(src, type_name));
+    }
+    let mut dst_iter = dst.iter_mut();
+    try_for_each_string(src, |i, s| {
+        if let Some(d) = dst_iter.next() {
+            let s = s.expect("Got null string in batch without nulls");
+            *d = decode(s).map_err(|e| e.at_row(i))?;
+        }
+        Ok(())
+    })?;
+    Ok(src.num_elements().try_into().unwrap())
+}
+
+/// Shared implementation of `read_from_vector_batch` for `Option`s of string-like
+/// types
+fn read_nullable_strings<'a, 'b, 's, T, U, F>(
+    src: &StringVectorBatch<'s>,
+    mut dst: &'b mut T,
+    mut decode: F,
+) -> Result<usize, DeserializationError>
+where
+    U: 'a,
+    &'b mut T: DeserializationTarget<'a, Item = Option<U>> + 'b,
+    F: FnMut(&'s [u8]) -> Result<U, DeserializationError>,
+{
+    let mut dst_iter = dst.iter_mut();
+    try_for_each_string(src, |i, s| {
+        if let Some(d) = dst_iter.next() {
+            *d = match s {
+                None => None,
+                Some(s) => Some(decode(s).map_err(|e| e.at_row(i))?),
+            };
+        }
+        Ok(())
+    })?;
+    Ok(src.num_elements().try_into().unwrap())
+}
+
+impl OrcStruct for Vec<u8> {
     fn columns_with_prefix(prefix: &str) -> Vec<String> {
         vec![prefix.to_string()]
     }
 }
 
-impl CheckableKind for Decimal {
+impl CheckableKind for Vec<u8> {
     fn check_kind(kind: &Kind) -> Result<(), String> {
-        match kind {
-            Kind::Decimal { .. } => Ok(()),
-            _ => Err(format!(
-                "Decimal must be decoded from ORC Decimal, not ORC {kind:?}"
-            )),
-        }
+        check_kind_equals(kind, &[Kind::Binary], "Vec<u8>")
     }
 }
 
-impl OrcDeserialize for Decimal {
+impl OrcDeserialize for Vec<u8> {
     fn read_from_vector_batch<'a, 'b, T>(
         src: &BorrowedColumnVectorBatch,
-        mut dst: &'b mut T,
+        dst: &'b mut T,
     ) -> Result<usize, DeserializationError>
     where
         &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
     {
-        match src.try_into_decimals64() {
-            Ok(src) => match src.try_iter_not_null() {
-                None => {
-                    return Err(DeserializationError::UnexpectedNull(
-                        "Decimal column contains nulls".to_string(),
-                    ))
-                }
-                Some(it) => {
-                    for (s, d) in it.zip(dst.iter_mut()) {
-                        *d = s;
-                    }
-                }
-            },
-            Err(_) => {
-                let src = src
-                    .try_into_decimals128()
-                    .map_err(DeserializationError::MismatchedColumnKind)?;
-                match src.try_iter_not_null() {
-                    None => {
-                        return Err(DeserializationError::UnexpectedNull(
-                            "Decimal column contains nulls".to_string(),
-                        ))
-                    }
-                    Some(it) => {
-                        for (s, d) in it.zip(dst.iter_mut()) {
-                            *d = s;
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(src.num_elements().try_into().unwrap())
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        read_not_null_strings(&src, dst, "Vec<u8>", |s| Ok(s.to_vec()))
     }
 }
 
-impl OrcDeserialize for Option<Decimal> {
+impl OrcDeserialize for Option<Vec<u8>> {
     fn read_from_vector_batch<'a, 'b, T>(
         src: &BorrowedColumnVectorBatch,
-        mut dst: &'b mut T,
+        dst: &'b mut T,
     ) -> Result<usize, DeserializationError>
     where
         &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
     {
-        match src.try_into_decimals64() {
-            Ok(src) => {
-                for (s, d) in src.iter().zip(dst.iter_mut()) {
-                    match s {
-                        None => *d = None,
-                        Some(s) => *d = Some(s),
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        read_nullable_strings(&src, dst, |s| Ok(s.to_vec()))
+    }
+}
+
+impl OrcStruct for &[u8] {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        vec![prefix.to_string()]
+    }
+}
+
+impl CheckableKind for &[u8] {
+    fn check_kind(kind: &Kind) -> Result<(), String> {
+        check_kind_equals(kind, &[Kind::Binary], "&[u8]")
+    }
+}
+
+impl<'a> OrcDeserializeRef<'a> for &'a [u8] {
+    fn read_ref_from_vector_batch<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        read_not_null_strings(&src, dst, "&[u8]", Ok)
+    }
+}
+
+impl<'a> OrcDeserializeRef<'a> for Option<&'a [u8]> {
+    fn read_ref_from_vector_batch<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        read_nullable_strings(&src, dst, Ok)
+    }
+}
+
+/// Returns `true` if all strings in the batch are known to be valid UTF-8.
+///
+/// Instead of validating each string individually, this validates the whole
+/// [`bytes()`](StringVectorBatch::bytes) buffer at once, then only checks each
+/// string starts and ends on a character boundary within that buffer.
+///
+/// Returns `false` if this cannot be determined this way (the buffer is not valid
+/// UTF-8, or some strings are not stored in it, as is the case for dictionary-encoded
+/// columns), in which case callers need to validate each string.
+fn is_utf8_batch(src: &StringVectorBatch) -> bool {
+    let blob = match std::str::from_utf8(src.bytes()) {
+        Ok(blob) => blob,
+        Err(_) => return false,
+    };
+    let blob_start = blob.as_ptr() as usize;
+    src.iter().flatten().all(|s| {
+        if s.is_empty() {
+            return true;
+        }
+        // Strings stored outside the blob (even before it, as the offset wraps around)
+        // are rejected by is_char_boundary(), which returns false for out-of-bound
+        // offsets.
+        let start = (s.as_ptr() as usize).wrapping_sub(blob_start);
+        match start.checked_add(s.len()) {
+            Some(end) => blob.is_char_boundary(start) && blob.is_char_boundary(end),
+            None => false,
+        }
+    })
+}
+
+/// Decodes a string, validating it unless `assume_utf8` is `true`
+///
+/// # Safety
+///
+/// If `assume_utf8` is `true`, `s` must be valid UTF-8.
+#[inline(always)]
+unsafe fn decode_string(s: &[u8], assume_utf8: bool) -> Result<String, DeserializationError> {
+    decode_str(s, assume_utf8).map(|s| s.to_string())
+}
+
+/// Same as [`decode_string`], but borrows from `s` instead of copying it
+///
+/// # Safety
+///
+/// If `assume_utf8` is `true`, `s` must be valid UTF-8.
+#[inline(always)]
+unsafe fn decode_str(s: &[u8], assume_utf8: bool) -> Result<&str, DeserializationError> {
+    if assume_utf8 {
+        Ok(std::str::from_utf8_unchecked(s))
+    } else {
+        std::str::from_utf8(s).map_err(DeserializationError::Utf8Error)
+    }
+}
+
+impl OrcStruct for String {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        vec![prefix.to_string()]
+    }
+}
+
+/// Strings can also be read from `varchar(n)` and `char(n)` columns, whose values
+/// are stored the same way (with `char(n)` values padded with spaces).
+impl CheckableKind for String {
+    fn check_kind(kind: &Kind) -> Result<(), String> {
+        match kind {
+            Kind::Varchar(_) | Kind::Char(_) => Ok(()),
+            _ => check_kind_equals(kind, &[Kind::String], "String"),
+        }
+    }
+}
+
+impl OrcDeserialize for String {
+    fn read_from_vector_batch<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        let assume_utf8 = is_utf8_batch(&src);
+        // Safe because is_utf8_batch() checked all strings are valid UTF-8
+        unsafe { read_strings(&src, dst, assume_utf8) }
+    }
+
+    unsafe fn read_from_vector_batch_assume_utf8<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        read_strings(&src, dst, true)
+    }
+}
+
+impl OrcDeserialize for Option<String> {
+    fn read_from_vector_batch<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        let assume_utf8 = is_utf8_batch(&src);
+        // Safe because is_utf8_batch() checked all strings are valid UTF-8
+        unsafe { read_option_strings(&src, dst, assume_utf8) }
+    }
+
+    unsafe fn read_from_vector_batch_assume_utf8<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        read_option_strings(&src, dst, true)
+    }
+}
+
+/// Shared implementation of `String::read_from_vector_batch` and
+/// `String::read_from_vector_batch_assume_utf8`
+///
+/// # Safety
+///
+/// If `assume_utf8` is `true`, all strings in `src` must be valid UTF-8.
+unsafe fn read_strings<'a, 'b, T>(
+    src: &StringVectorBatch,
+    dst: &'b mut T,
+    assume_utf8: bool,
+) -> Result<usize, DeserializationError>
+where
+    &'b mut T: DeserializationTarget<'a, Item = String> + 'b,
+{
+    read_not_null_strings(src, dst, "String", |s| decode_string(s, assume_utf8))
+}
+
+/// Shared implementation of `Option<String>::read_from_vector_batch` and
+/// `Option<String>::read_from_vector_batch_assume_utf8`
+///
+/// # Safety
+///
+/// If `assume_utf8` is `true`, all strings in `src` must be valid UTF-8.
+unsafe fn read_option_strings<'a, 'b, T>(
+    src: &StringVectorBatch,
+    dst: &'b mut T,
+    assume_utf8: bool,
+) -> Result<usize, DeserializationError>
+where
+    &'b mut T: DeserializationTarget<'a, Item = Option<String>> + 'b,
+{
+    read_nullable_strings(src, dst, |s| decode_string(s, assume_utf8))
+}
+
+impl OrcStruct for &str {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        vec![prefix.to_string()]
+    }
+}
+
+/// Like [`String`], `&str` can also be read from `varchar(n)` and `char(n)` columns
+impl CheckableKind for &str {
+    fn check_kind(kind: &Kind) -> Result<(), String> {
+        match kind {
+            Kind::Varchar(_) | Kind::Char(_) => Ok(()),
+            _ => check_kind_equals(kind, &[Kind::String], "&str"),
+        }
+    }
+}
+
+impl<'a> OrcDeserializeRef<'a> for &'a str {
+    fn read_ref_from_vector_batch<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        let assume_utf8 = is_utf8_batch(&src);
+        // Safe because is_utf8_batch() checked all strings are valid UTF-8
+        read_not_null_strings(&src, dst, "&str", |s| unsafe { decode_str(s, assume_utf8) })
+    }
+
+    unsafe fn read_ref_from_vector_batch_assume_utf8<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        read_not_null_strings(&src, dst, "&str", |s| decode_str(s, true))
+    }
+}
+
+impl<'a> OrcDeserializeRef<'a> for Option<&'a str> {
+    fn read_ref_from_vector_batch<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        let assume_utf8 = is_utf8_batch(&src);
+        // Safe because is_utf8_batch() checked all strings are valid UTF-8
+        read_nullable_strings(&src, dst, |s| unsafe { decode_str(s, assume_utf8) })
+    }
+
+    unsafe fn read_ref_from_vector_batch_assume_utf8<'b, T>(
+        src: &BorrowedColumnVectorBatch<'a>,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let src = src
+            .try_into_strings()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+        read_nullable_strings(&src, dst, |s| decode_str(s, true))
+    }
+}
+
+impl_scalar!(
+    crate::Timestamp,
+    [Kind::Timestamp, Kind::TimestampInstant],
+    try_into_timestamps,
+    |s: (i64, i64)| Ok(crate::Timestamp {
+        seconds: s.0,
+        nanoseconds: s.1
+    })
+);
+
+impl_scalar!(crate::Date, [Kind::Date], try_into_longs, |s: i64| s
+    .try_into()
+    .map(crate::Date)
+    .map_err(|_| DeserializationError::DateOutOfRange(s)));
+
+#[cfg(feature = "chrono")]
+impl_scalar!(chrono::NaiveDate, [Kind::Date], try_into_longs, |s: i64| {
+    ::vector::days_to_naive_date_opt(s).ok_or(DeserializationError::DateOutOfRange(s))
+});
+
+/// Returns the timestamp as a [`chrono::DateTime`], or an error if it is out of
+/// the range supported by `chrono`
+#[cfg(feature = "chrono")]
+fn timestamp_to_datetime(
+    (seconds, nanoseconds): (i64, i64),
+) -> Result<chrono::DateTime<chrono::Utc>, DeserializationError> {
+    nanoseconds
+        .try_into()
+        .ok()
+        .and_then(|nanoseconds| chrono::DateTime::from_timestamp(seconds, nanoseconds))
+        .ok_or(DeserializationError::TimestampOutOfRange {
+            seconds,
+            nanoseconds,
+        })
+}
+
+#[cfg(feature = "chrono")]
+impl_scalar!(
+    chrono::DateTime<chrono::Utc>,
+    [Kind::Timestamp, Kind::TimestampInstant],
+    try_into_timestamps,
+    timestamp_to_datetime
+);
+
+#[cfg(feature = "chrono")]
+impl_scalar!(
+    chrono::NaiveDateTime,
+    [Kind::Timestamp, Kind::TimestampInstant],
+    try_into_timestamps,
+    |s| timestamp_to_datetime(s).map(|datetime| datetime.naive_utc())
+);
+
+impl OrcStruct for Decimal {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        vec![prefix.to_string()]
+    }
+}
+
+/// Maximum scale of [`Decimal`] values
+const MAX_DECIMAL_SCALE: u32 = 28;
+
+impl CheckableKind for Decimal {
+    fn check_kind(kind: &Kind) -> Result<(), String> {
+        match kind {
+            Kind::Decimal { scale, .. } if *scale > u64::from(MAX_DECIMAL_SCALE) => Err(format!(
+                "Decimal cannot be decoded from ORC {:?}, as its scale is greater \
+                 than {}",
+                kind, MAX_DECIMAL_SCALE
+            )),
+            Kind::Decimal { .. } => Ok(()),
+            _ => Err(format!(
+                "Decimal must be decoded from ORC Decimal, not ORC {kind:?}"
+            )),
+        }
+    }
+}
+
+/// Returns an error if `rust_decimal` cannot represent values with the batch's scale
+fn check_decimal_scale<'a, V: DecimalVectorBatch<'a>>(src: &V) -> Result<(), DeserializationError> {
+    let scale = src.scale();
+    if (0..=MAX_DECIMAL_SCALE as i32).contains(&scale) {
+        Ok(())
+    } else {
+        Err(DeserializationError::UnsupportedDecimalScale(scale))
+    }
+}
+
+/// Implementation of `OrcDeserialize for Decimal` for
+/// [`Decimal64VectorBatch`](::vector::Decimal64VectorBatch)
+fn read_decimals<'a, 'b, 'c, V, T>(src: &V, mut dst: &'b mut T) -> Result<(), DeserializationError>
+where
+    V: DecimalVectorBatch<'c> + ColumnVectorBatch<'c>,
+    &'b mut T: DeserializationTarget<'a, Item = Decimal> + 'b,
+{
+    check_decimal_scale(src)?;
+    match src.try_iter_not_null() {
+        None => Err(unexpected_null(src, "Decimal")),
+        Some(it) => {
+            for (s, d) in it.zip(dst.iter_mut()) {
+                *d = s;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Converts the mantissa and scale of a decimal to [`Decimal`], or returns an error
+/// if it does not fit
+pub(crate) fn decimal_from_raw(
+    (mantissa, scale): (i128, u32),
+) -> Result<Decimal, DeserializationError> {
+    Decimal::try_from_i128_with_scale(mantissa, scale)
+        .map_err(|_| DeserializationError::DecimalOutOfRange { mantissa, scale })
+}
+
+/// Implementation of `OrcDeserialize for Decimal` for [`Decimal128VectorBatch`],
+/// whose values may not fit in [`Decimal`]
+fn read_decimals128<'a, 'b, T>(
+    src: &Decimal128VectorBatch,
+    mut dst: &'b mut T,
+) -> Result<(), DeserializationError>
+where
+    &'b mut T: DeserializationTarget<'a, Item = Decimal> + 'b,
+{
+    check_decimal_scale(src)?;
+    for (row, (s, d)) in src.iter_raw().zip(dst.iter_mut()).enumerate() {
+        let s = s.ok_or_else(|| unexpected_null(src, "Decimal"))?;
+        *d = decimal_from_raw(s).map_err(|e| e.at_row(row))?;
+    }
+    Ok(())
+}
+
+/// Implementation of `OrcDeserialize for Option<Decimal>` for
+/// [`Decimal128VectorBatch`], whose values may not fit in [`Decimal`]
+fn read_nullable_decimals128<'a, 'b, T>(
+    src: &Decimal128VectorBatch,
+    mut dst: &'b mut T,
+) -> Result<(), DeserializationError>
+where
+    &'b mut T: DeserializationTarget<'a, Item = Option<Decimal>> + 'b,
+{
+    check_decimal_scale(src)?;
+    for (row, (s, d)) in src.iter_raw().zip(dst.iter_mut()).enumerate() {
+        *d = s
+            .map(decimal_from_raw)
+            .transpose()
+            .map_err(|e| e.at_row(row))?;
+    }
+    Ok(())
+}
+
+/// Implementation of `OrcDeserialize for Option<Decimal>` for
+/// [`Decimal64VectorBatch`](::vector::Decimal64VectorBatch)
+fn read_nullable_decimals<'a, 'b, 'c, V, T>(
+    src: &V,
+    mut dst: &'b mut T,
+) -> Result<(), DeserializationError>
+where
+    V: DecimalVectorBatch<'c>,
+    &'b mut T: DeserializationTarget<'a, Item = Option<Decimal>> + 'b,
+{
+    check_decimal_scale(src)?;
+    for (s, d) in src.iter().zip(dst.iter_mut()) {
+        *d = s;
+    }
+    Ok(())
+}
+
+impl OrcDeserialize for Decimal {
+    fn read_from_vector_batch<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        match src.try_into_decimals64() {
+            Ok(src) => read_decimals(&src, dst)?,
+            Err(_) => read_decimals128(
+                &src.try_into_decimals128()
+                    .map_err(DeserializationError::MismatchedColumnKind)?,
+                dst,
+            )?,
+        }
+
+        Ok(src.num_elements().try_into().unwrap())
+    }
+}
+
+impl OrcDeserialize for Option<Decimal> {
+    fn read_from_vector_batch<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        match src.try_into_decimals64() {
+            Ok(src) => read_nullable_decimals(&src, dst)?,
+            Err(_) => read_nullable_decimals128(
+                &src.try_into_decimals128()
+                    .map_err(DeserializationError::MismatchedColumnKind)?,
+                dst,
+            )?,
+        }
+
+        Ok(src.num_elements().try_into().unwrap())
+    }
+}
+
+impl<T: OrcStruct> OrcStruct for Vec<T> {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        T::columns_with_prefix(prefix)
+    }
+}
+
+impl<T: CheckableKind> CheckableKind for Vec<T> {
+    fn check_kind(kind: &Kind) -> Result<(), String> {
+        match kind {
+            Kind::List(inner) => T::check_kind(inner),
+            _ => Err(format!("Must be a List, not {kind:?}")),
+        }
+    }
+
+    fn check_children_nulls(column: &SelectedColumn, path: &str, check: &mut NullabilityCheck) {
+        if let Some(elements) = column.children.first() {
+            T::check_nulls(elements, path, check)
+        }
+    }
+}
+
+/// Shared initialization code of `impl<I> OrcDeserializeOption for Vec<I>`
+/// and impl<I> OrcDeserialize for Vec<I>
+macro_rules! init_list_read {
+    ($src:expr, $kind:expr, $dst: expr) => {{
+        let src = $src
+            .try_into_lists()
+            .map_err(DeserializationError::MismatchedColumnKind)?;
+
+        let num_lists: usize = src
+            .num_elements()
+            .try_into()
+            .map_err(DeserializationError::UsizeOverflow)?;
+        let num_elements: usize = src
+            .elements()
+            .num_elements()
+            .try_into()
+            .map_err(DeserializationError::UsizeOverflow)?;
+
+        if num_lists > $dst.len() {
+            return Err(DeserializationError::MismatchedLength {
+                src: num_lists as u64,
+                dst: $dst.len() as u64,
+            });
+        }
+
+        // Deserialize the inner elements recursively into this temporary buffer.
+        // TODO: write them directly to the final location to avoid a copy
+        let mut elements = Vec::new();
+        elements.resize_with(num_elements, Default::default);
+        let element_kind = match $kind {
+            Some(Kind::List(element_kind)) => Some(&**element_kind),
+            _ => None,
+        };
+        read_from_vector_batch_with_optional_kind::<I, Vec<I>>(
+            &src.elements(),
+            element_kind,
+            &mut elements,
+        )
+        .map_err(|e| locate_list_error(e, src.iter_offsets(), "_elem"))?;
+
+        let elements = elements.into_iter();
+
+        (src, elements)
+    }};
+}
+
+/// Given an error which occurred while reading the elements of a list column,
+/// replaces the index of the element with the index of the list containing it,
+/// and prepends the name of the child column (`_elem`, `_key`, or `_value`, like
+/// [`RowReader::selected_columns`](::reader::RowReader::selected_columns)) to the
+/// path of the column.
+fn locate_list_error(
+    error: DeserializationError,
+    mut offsets: RangeVectorBatchIterator,
+    child_name: &str,
+) -> DeserializationError {
+    let error = match error {
+        DeserializationError::Located {
+            row: Some(row),
+            column,
+            source,
+        } => DeserializationError::Located {
+            row: offsets.position(|range| range.map(|range| range.contains(&row)) == Some(true)),
+            column,
+            source,
+        },
+        _ => error,
+    };
+    error.in_column(child_name)
+}
+
+/// Shared loop code of `impl<I> OrcDeserializeOption for Vec<I>`
+/// and impl<I> OrcDeserialize for Vec<I>
+macro_rules! build_list_item {
+    ($range:expr, $last_offset:expr, $elements:expr) => {{
+        let range = $range;
+        assert_eq!(
+            range.start, $last_offset,
+            "Non-continuous list (jumped from offset {} to {}",
+            $last_offset, range.start
+        );
+        // Safe because offset is bounded by num_elements;
+        let mut array: Vec<I> = Vec::with_capacity((range.end - range.start) as usize);
+        for _ in range.clone() {
+            match $elements.next() {
+                Some(item) => {
+                    array.push(item);
+                }
+                None => panic!(
+                    "List too short (expected {} elements, got {})",
+                    range.end - range.start,
+                    array.len()
+                ),
+            }
+        }
+        $last_offset = range.end;
+        array
+    }};
+}
+
+/// Shared implementation of `read_options_from_vector_batch` and
+/// `read_options_from_vector_batch_with_kind` for `Vec<I>`
+fn read_nullable_lists<'a, 'b, I, T>(
+    src: &BorrowedColumnVectorBatch,
+    kind: Option<&Kind>,
+    mut dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    I: Default + OrcDeserialize,
+    &'b mut T: DeserializationTarget<'a, Item = Option<Vec<I>>> + 'b,
+{
+    let (src, mut elements) = init_list_read!(src, kind, dst);
+    let offsets = src.iter_offsets();
+    let mut dst = dst.iter_mut();
+
+    let mut last_offset = 0;
+
+    for offset in offsets {
+        // Safe because we checked dst.len() == num_elements, and num_elements
+        // is also the size of offsets
+        let dst_item: &mut Option<Vec<I>> = unsafe { dst.next().unwrap_unchecked() };
+        match offset {
+            None => *dst_item = None,
+            Some(range) => {
+                *dst_item = Some(build_list_item!(range, last_offset, elements));
+            }
+        }
+    }
+    if elements.next().is_some() {
+        panic!("List too long");
+    }
+
+    Ok(src.num_elements().try_into().unwrap())
+}
+
+/// Shared implementation of `read_from_vector_batch` and
+/// `read_from_vector_batch_with_kind` for `Vec<I>`
+fn read_lists<'a, 'b, I, T>(
+    src: &BorrowedColumnVectorBatch,
+    kind: Option<&Kind>,
+    mut dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    I: Default + OrcDeserialize,
+    &'b mut T: DeserializationTarget<'a, Item = Vec<I>> + 'b,
+{
+    let (src, mut elements) = init_list_read!(src, kind, dst);
+    match src.try_iter_offsets_not_null() {
+        None => Err(unexpected_null(&src, "Vec")),
+        Some(offsets) => {
+            let mut dst = dst.iter_mut();
+
+            let mut last_offset = 0;
+
+            for range in offsets {
+                // Safe because we checked dst.len() == num_elements, and num_elements
+                // is also the size of offsets
+                let dst_item: &mut Vec<I> = unsafe { dst.next().unwrap_unchecked() };
+
+                *dst_item = build_list_item!(range, last_offset, elements);
+            }
+            if elements.next().is_some() {
+                panic!("List too long");
+            }
+
+            Ok(src.num_elements().try_into().unwrap())
+        }
+    }
+}
+
+/// Deserialization of ORC lists with nullable values
+///
+/// cannot do `impl<I> OrcDeserialize for Option<Vec<Option<I>>>` because it causes
+/// infinite recursion in the type-checker due to this other implementation being
+/// available: `impl<I: OrcDeserializeOption> OrcDeserialize for Option<I>`.
+impl<I> OrcDeserializeOption for Vec<I>
+where
+    I: Default + OrcDeserialize,
+{
+    fn read_options_from_vector_batch<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
+    {
+        read_nullable_lists(src, None, dst)
+    }
+
+    fn read_options_from_vector_batch_with_kind<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        kind: &Kind,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
+    {
+        read_nullable_lists(src, Some(kind), dst)
+    }
+}
+
+/// Deserialization of ORC lists without nullable values
+impl<I> OrcDeserialize for Vec<I>
+where
+    I: Default + OrcDeserialize,
+{
+    fn read_from_vector_batch<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        read_lists(src, None, dst)
+    }
+
+    fn read_from_vector_batch_with_kind<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        kind: &Kind,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        read_lists(src, Some(kind), dst)
+    }
+}
+
+/// Key-value pairs of a map, in the order they are stored in
+type MapEntries<K, V> = Vec<(K, V)>;
+
+/// Reads all maps of a map column, as a list of entries for each map (or `None`
+/// for null maps)
+fn read_maps<K, V>(
+    src: &BorrowedColumnVectorBatch,
+    kind: Option<&Kind>,
+) -> Result<Vec<Option<MapEntries<K, V>>>, DeserializationError>
+where
+    K: Default + OrcDeserialize,
+    V: Default + OrcDeserialize,
+{
+    let src = src
+        .try_into_maps()
+        .map_err(DeserializationError::MismatchedColumnKind)?;
+
+    let num_entries: usize = src
+        .keys()
+        .num_elements()
+        .try_into()
+        .map_err(DeserializationError::UsizeOverflow)?;
+
+    let (key_kind, value_kind) = match kind {
+        Some(Kind::Map { key, value }) => (Some(&**key), Some(&**value)),
+        _ => (None, None),
+    };
+
+    // Deserialize keys and values recursively into temporary buffers, then split
+    // them between maps
+    let mut keys = Vec::new();
+    keys.resize_with(num_entries, Default::default);
+    read_from_vector_batch_with_optional_kind::<K, Vec<K>>(&src.keys(), key_kind, &mut keys)
+        .map_err(|e| locate_list_error(e, src.iter_offsets(), "_key"))?;
+    let mut values = Vec::new();
+    values.resize_with(num_entries, Default::default);
+    read_from_vector_batch_with_optional_kind::<V, Vec<V>>(
+        &src.elements(),
+        value_kind,
+        &mut values,
+    )
+    .map_err(|e| locate_list_error(e, src.iter_offsets(), "_value"))?;
+
+    let mut entries = keys.into_iter().zip(values);
+    let mut last_offset = 0;
+    let maps = src
+        .iter_offsets()
+        .map(|range| {
+            range.map(|range| {
+                assert_eq!(
+                    range.start, last_offset,
+                    "Non-continuous map (jumped from offset {} to {}",
+                    last_offset, range.start
+                );
+                last_offset = range.end;
+                let map: MapEntries<K, V> = entries.by_ref().take(range.len()).collect();
+                assert_eq!(map.len(), range.len(), "Map too short");
+                map
+            })
+        })
+        .collect();
+    if entries.next().is_some() {
+        panic!("Map too long");
+    }
+    Ok(maps)
+}
+
+/// Shared implementation of `read_from_vector_batch` and
+/// `read_from_vector_batch_with_kind` for collections of key-value pairs
+fn read_not_null_maps<'a, 'b, K, V, M, T>(
+    src: &BorrowedColumnVectorBatch,
+    kind: Option<&Kind>,
+    mut dst: &'b mut T,
+    type_name: &str,
+) -> Result<usize, DeserializationError>
+where
+    K: Default + OrcDeserialize,
+    V: Default + OrcDeserialize,
+    M: FromIterator<(K, V)>,
+    &'b mut T: DeserializationTarget<'a, Item = M> + 'b,
+{
+    let maps = read_maps::<K, V>(src, kind)?;
+    if maps.len() > dst.len() {
+        return Err(DeserializationError::MismatchedLength {
+            src: maps.len() as u64,
+            dst: dst.len() as u64,
+        });
+    }
+    if maps.iter().any(Option::is_none) {
+        return Err(unexpected_null(src, type_name));
+    }
+    let num_maps = maps.len();
+    for (map, d) in maps.into_iter().zip(dst.iter_mut()) {
+        // Checked above
+        *d = map.unwrap().into_iter().collect();
+    }
+    Ok(num_maps)
+}
+
+/// Shared implementation of `read_options_from_vector_batch` and
+/// `read_options_from_vector_batch_with_kind` for collections of key-value pairs
+fn read_nullable_maps<'a, 'b, K, V, M, T>(
+    src: &BorrowedColumnVectorBatch,
+    kind: Option<&Kind>,
+    mut dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    K: Default + OrcDeserialize,
+    V: Default + OrcDeserialize,
+    M: FromIterator<(K, V)>,
+    &'b mut T: DeserializationTarget<'a, Item = Option<M>> + 'b,
+{
+    let maps = read_maps::<K, V>(src, kind)?;
+    if maps.len() > dst.len() {
+        return Err(DeserializationError::MismatchedLength {
+            src: maps.len() as u64,
+            dst: dst.len() as u64,
+        });
+    }
+    let num_maps = maps.len();
+    for (map, d) in maps.into_iter().zip(dst.iter_mut()) {
+        *d = map.map(|map| map.into_iter().collect());
+    }
+    Ok(num_maps)
+}
+
+/// Implements the traits of this module for a collection of key-value pairs
+/// read from ORC maps, which must implement `FromIterator<(K, V)>`
+macro_rules! impl_map {
+    ($ty:ty, $type_name:expr $(, $key_bound:path)*) => {
+        impl<K, V> OrcStruct for $ty {
+            fn columns_with_prefix(prefix: &str) -> Vec<String> {
+                vec![prefix.to_string()]
+            }
+        }
+
+        impl<K: CheckableKind, V: CheckableKind> CheckableKind for $ty {
+            fn check_kind(kind: &Kind) -> Result<(), String> {
+                match kind {
+                    Kind::Map { key, value } => {
+                        K::check_kind(key).map_err(|e| format!("Map key: {}", e))?;
+                        V::check_kind(value).map_err(|e| format!("Map value: {}", e))
                     }
+                    _ => Err(format!("Must be a Map, not {:?}", kind)),
                 }
             }
-            Err(_) => {
-                let src = src
-                    .try_into_decimals128()
-                    .map_err(DeserializationError::MismatchedColumnKind)?;
-                for (s, d) in src.iter().zip(dst.iter_mut()) {
-                    match s {
-                        None => *d = None,
-                        Some(s) => *d = Some(s),
-                    }
+
+            fn check_children_nulls(
+                column: &SelectedColumn,
+                path: &str,
+                check: &mut NullabilityCheck,
+            ) {
+                if let [keys, values] = &column.children[..] {
+                    K::check_nulls(keys, path, check);
+                    V::check_nulls(values, path, check);
                 }
             }
         }
 
-        Ok(src.num_elements().try_into().unwrap())
-    }
+        impl<K, V> OrcDeserialize for $ty
+        where
+            K: Default + OrcDeserialize $(+ $key_bound)*,
+            V: Default + OrcDeserialize,
+        {
+            fn read_from_vector_batch<'a, 'b, T>(
+                src: &BorrowedColumnVectorBatch,
+                dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+            {
+                read_not_null_maps(src, None, dst, $type_name)
+            }
+
+            fn read_from_vector_batch_with_kind<'a, 'b, T>(
+                src: &BorrowedColumnVectorBatch,
+                kind: &Kind,
+                dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+            {
+                read_not_null_maps(src, Some(kind), dst, $type_name)
+            }
+        }
+
+        impl<K, V> OrcDeserializeOption for $ty
+        where
+            K: Default + OrcDeserialize $(+ $key_bound)*,
+            V: Default + OrcDeserialize,
+        {
+            fn read_options_from_vector_batch<'a, 'b, T>(
+                src: &BorrowedColumnVectorBatch,
+                dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
+            {
+                read_nullable_maps(src, None, dst)
+            }
+
+            fn read_options_from_vector_batch_with_kind<'a, 'b, T>(
+                src: &BorrowedColumnVectorBatch,
+                kind: &Kind,
+                dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
+            {
+                read_nullable_maps(src, Some(kind), dst)
+            }
+        }
+    };
 }
 
-impl<T: OrcStruct> OrcStruct for Vec<T> {
+// Tuples do not implement OrcDeserialize, so this does not overlap with lists
+impl_map!(Vec<(K, V)>, "Vec");
+impl_map!(HashMap<K, V>, "HashMap", Eq, Hash);
+impl_map!(BTreeMap<K, V>, "BTreeMap", Ord);
+
+impl OrcStruct for Bitmap {
     fn columns_with_prefix(prefix: &str) -> Vec<String> {
-        T::columns_with_prefix(prefix)
+        vec![prefix.to_string()]
     }
 }
 
-impl<T: CheckableKind> CheckableKind for Vec<T> {
+impl CheckableKind for Bitmap {
     fn check_kind(kind: &Kind) -> Result<(), String> {
         match kind {
-            Kind::List(inner) => T::check_kind(inner),
-            _ => Err(format!("Must be a List, not {kind:?}")),
+            Kind::List(inner) => bool::check_kind(inner),
+            _ => Err(format!("Must be a List, not {:?}", kind)),
         }
     }
-}
-
-/// Shared initialization code of `impl<I> OrcDeserializeOption for Vec<I>`
-/// and impl<I> OrcDeserialize for Vec<I>
-macro_rules! init_list_read {
-    ($src:expr, $dst: expr) => {{
-        let src = $src
-            .try_into_lists()
-            .map_err(DeserializationError::MismatchedColumnKind)?;
-
-        let num_lists: usize = src
-            .num_elements()
-            .try_into()
-            .map_err(DeserializationError::UsizeOverflow)?;
-        let num_elements: usize = src
-            .elements()
-            .num_elements()
-            .try_into()
-            .map_err(DeserializationError::UsizeOverflow)?;
 
-        if num_lists > $dst.len() {
-            return Err(DeserializationError::MismatchedLength {
-                src: num_lists as u64,
-                dst: $dst.len() as u64,
-            });
+    fn check_children_nulls(column: &SelectedColumn, path: &str, check: &mut NullabilityCheck) {
+        if let Some(elements) = column.children.first() {
+            bool::check_nulls(elements, path, check)
         }
-
-        // Deserialize the inner elements recursively into this temporary buffer.
-        // TODO: write them directly to the final location to avoid a copy
-        let mut elements = Vec::new();
-        elements.resize_with(num_elements, Default::default);
-        OrcDeserialize::read_from_vector_batch::<Vec<I>>(&src.elements(), &mut elements)?;
-
-        let elements = elements.into_iter();
-
-        (src, elements)
-    }};
+    }
 }
 
-/// Shared loop code of `impl<I> OrcDeserializeOption for Vec<I>`
-/// and impl<I> OrcDeserialize for Vec<I>
-macro_rules! build_list_item {
-    ($range:expr, $last_offset:expr, $elements:expr) => {{
-        let range = $range;
-        assert_eq!(
-            range.start, $last_offset,
-            "Non-continuous list (jumped from offset {} to {}",
-            $last_offset, range.start
-        );
-        // Safe because offset is bounded by num_elements;
-        let mut array: Vec<I> = Vec::with_capacity((range.end - range.start) as usize);
-        for _ in range.clone() {
-            match $elements.next() {
-                Some(item) => {
-                    array.push(item);
-                }
-                None => panic!(
-                    "List too short (expected {} elements, got {})",
-                    range.end - range.start,
-                    array.len()
-                ),
-            }
-        }
-        $last_offset = range.end;
-        array
-    }};
+/// Shared initialization code of `impl OrcDeserializeOption for Bitmap`
+/// and `impl OrcDeserialize for Bitmap`: packs all elements at once, so each list
+/// is then a slice of the returned bitmap.
+fn read_list_bitmap<'c>(
+    src: &BorrowedColumnVectorBatch<'c>,
+    dst_len: usize,
+) -> Result<(ListVectorBatch<'c>, Bitmap), DeserializationError> {
+    let src = src
+        .try_into_lists()
+        .map_err(DeserializationError::MismatchedColumnKind)?;
+    let dst_len: u64 = dst_len
+        .try_into()
+        .map_err(DeserializationError::UsizeOverflow)?;
+    if src.num_elements() > dst_len {
+        return Err(DeserializationError::MismatchedLength {
+            src: src.num_elements(),
+            dst: dst_len,
+        });
+    }
+    let elements = src
+        .elements()
+        .try_into_longs()
+        .map_err(DeserializationError::MismatchedColumnKind)?;
+    match elements.to_bitmap() {
+        (values, None) => Ok((src, values)),
+        (_, Some(_)) => Err(locate_list_error(
+            unexpected_null(&elements, "Bitmap"),
+            src.iter_offsets(),
+            "_elem",
+        )),
+    }
 }
 
-/// Deserialization of ORC lists with nullable values
-///
-/// cannot do `impl<I> OrcDeserialize for Option<Vec<Option<I>>>` because it causes
-/// infinite recursion in the type-checker due to this other implementation being
-/// available: `impl<I: OrcDeserializeOption> OrcDeserialize for Option<I>`.
-impl<I> OrcDeserializeOption for Vec<I>
-where
-    I: Default + OrcDeserialize,
-{
+/// Deserialization of ORC lists of booleans with nullable lists
+impl OrcDeserializeOption for Bitmap {
     fn read_options_from_vector_batch<'a, 'b, T>(
         src: &BorrowedColumnVectorBatch,
         mut dst: &'b mut T,
@@ -442,36 +1814,18 @@ where
     where
         &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
     {
-        let (src, mut elements) = init_list_read!(src, dst);
-        let offsets = src.iter_offsets();
-        let mut dst = dst.iter_mut();
-
-        let mut last_offset = 0;
-
-        for offset in offsets {
-            // Safe because we checked dst.len() == num_elements, and num_elements
-            // is also the size of offsets
-            let dst_item: &mut Option<Vec<I>> = unsafe { dst.next().unwrap_unchecked() };
-            match offset {
-                None => *dst_item = None,
-                Some(range) => {
-                    *dst_item = Some(build_list_item!(range, last_offset, elements));
-                }
-            }
-        }
-        if elements.next().is_some() {
-            panic!("List too long");
+        let (src, values) = read_list_bitmap(src, dst.len())?;
+        for (range, dst_item) in src.iter_offsets().zip(dst.iter_mut()) {
+            *dst_item = range.map(|range| values.slice(range));
         }
 
         Ok(src.num_elements().try_into().unwrap())
     }
 }
 
-/// Deserialization of ORC lists without nullable values
-impl<I> OrcDeserialize for Vec<I>
-where
-    I: OrcDeserialize,
-{
+/// Deserialization of ORC lists of booleans without nullable values, 8 times
+/// more compactly than `Vec<bool>`
+impl OrcDeserialize for Bitmap {
     fn read_from_vector_batch<'a, 'b, T>(
         src: &BorrowedColumnVectorBatch,
         mut dst: &'b mut T,
@@ -479,26 +1833,12 @@ where
     where
         &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
     {
-        let (src, mut elements) = init_list_read!(src, dst);
+        let (src, values) = read_list_bitmap(src, dst.len())?;
         match src.try_iter_offsets_not_null() {
-            None => Err(DeserializationError::UnexpectedNull(format!(
-                "{} column contains nulls",
-                stringify!($ty)
-            ))),
+            None => Err(unexpected_null(&src, "Bitmap")),
             Some(offsets) => {
-                let mut dst = dst.iter_mut();
-
-                let mut last_offset = 0;
-
-                for range in offsets {
-                    // Safe because we checked dst.len() == num_elements, and num_elements
-                    // is also the size of offsets
-                    let dst_item: &mut Vec<I> = unsafe { dst.next().unwrap_unchecked() };
-
-                    *dst_item = build_list_item!(range, last_offset, elements);
-                }
-                if elements.next().is_some() {
-                    panic!("List too long");
+                for (range, dst_item) in offsets.zip(dst.iter_mut()) {
+                    *dst_item = values.slice(range);
                 }
 
                 Ok(src.num_elements().try_into().unwrap())
@@ -611,6 +1951,22 @@ pub trait OrcDeserializeOption: Sized + CheckableKind {
     where
         Self: 'a,
         &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b;
+
+    /// Same as
+    /// [`read_options_from_vector_batch`](OrcDeserializeOption::read_options_from_vector_batch),
+    /// but given the kind of `src`, like
+    /// [`OrcDeserialize::read_from_vector_batch_with_kind`]
+    fn read_options_from_vector_batch_with_kind<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        _kind: &Kind,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        Self: 'a,
+        &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
+    {
+        Self::read_options_from_vector_batch(src, dst)
+    }
 }
 
 impl<I: OrcDeserializeOption> OrcDeserialize for Option<I> {
@@ -624,8 +1980,290 @@ impl<I: OrcDeserializeOption> OrcDeserialize for Option<I> {
     {
         I::read_options_from_vector_batch(src, dst)
     }
+
+    fn read_from_vector_batch_with_kind<'a, 'b, T>(
+        src: &BorrowedColumnVectorBatch,
+        kind: &Kind,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+        I: 'a,
+    {
+        I::read_options_from_vector_batch_with_kind(src, kind, dst)
+    }
+}
+
+/// Calls [`OrcDeserialize::read_from_vector_batch_with_kind`] if `kind` is known,
+/// or [`OrcDeserialize::read_from_vector_batch`] otherwise
+pub fn read_from_vector_batch_with_optional_kind<'a, 'b, D, T>(
+    src: &BorrowedColumnVectorBatch,
+    kind: Option<&Kind>,
+    dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    D: OrcDeserialize + 'a,
+    &'b mut T: DeserializationTarget<'a, Item = D> + 'b,
+{
+    match kind {
+        Some(kind) => D::read_from_vector_batch_with_kind(src, kind, dst),
+        None => D::read_from_vector_batch(src, dst),
+    }
+}
+
+/// Same as [`read_from_vector_batch_with_optional_kind`], for
+/// [`OrcDeserializeRef`]
+pub fn read_ref_from_vector_batch_with_optional_kind<'a, 'b, D, T>(
+    src: &BorrowedColumnVectorBatch<'a>,
+    kind: Option<&Kind>,
+    dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    D: OrcDeserializeRef<'a>,
+    &'b mut T: DeserializationTarget<'a, Item = D> + 'b,
+{
+    match kind {
+        Some(kind) => D::read_ref_from_vector_batch_with_kind(src, kind, dst),
+        None => D::read_ref_from_vector_batch(src, dst),
+    }
+}
+
+/// Returns the index of the column a structure's field is read from, and the
+/// kind of this column if known.
+///
+/// `fields` are the fields of the structure's kind, if known. Fields are matched
+/// by `name` when `fields` is known, or by `position` otherwise.
+///
+/// This is used by code generated by `orcxx_derive`.
+pub fn struct_field<'k>(
+    fields: Option<&'k [(String, Kind)]>,
+    position: usize,
+    name: &str,
+) -> Result<(usize, Option<&'k Kind>), DeserializationError> {
+    match fields {
+        Some(fields) => fields
+            .iter()
+            .position(|(field_name, _)| field_name == name)
+            .map(|index| (index, Some(&fields[index].1)))
+            .ok_or_else(|| DeserializationError::MissingField(name.to_owned())),
+        None => Ok((position, None)),
+    }
+}
+
+/// Deserializes all values of the variant `tag` of a union column, whose kind is
+/// `kind` if known.
+///
+/// This is used by code generated by `orcxx_derive` for enums.
+pub fn read_union_variant<V: Default + OrcDeserialize>(
+    src: &UnionVectorBatch,
+    kind: Option<&Kind>,
+    tag: u8,
+) -> Result<Vec<V>, DeserializationError> {
+    let children = src.children();
+    let child = match children.get(usize::from(tag)) {
+        Some(child) => child,
+        None => return Ok(Vec::new()),
+    };
+    let kind = match kind {
+        Some(Kind::Union(variants)) => variants.get(usize::from(tag)),
+        _ => None,
+    };
+    let num_elements = child
+        .num_elements()
+        .try_into()
+        .map_err(DeserializationError::UsizeOverflow)?;
+    let mut values: Vec<V> = Vec::with_capacity(num_elements);
+    values.resize_with(num_elements, Default::default);
+    read_from_vector_batch_with_optional_kind::<V, Vec<V>>(child, kind, &mut values)?;
+    Ok(values)
+}
+
+/// Moves the value at `offset` out of `values` (the values of the variant `tag`,
+/// as returned by [`read_union_variant`]), leaving a default value in its place.
+///
+/// This is used by code generated by `orcxx_derive` for enums.
+pub fn union_value<V: Default>(
+    values: &mut [V],
+    tag: u8,
+    offset: u64,
+) -> Result<V, DeserializationError> {
+    let index: Option<usize> = offset.try_into().ok();
+    index
+        .and_then(|index| values.get_mut(index))
+        .map(std::mem::take)
+        .ok_or(DeserializationError::InvalidUnionValue { tag, offset })
+}
+
+/// Sets each row of `dst` to the value built by `read_value` from the tag and offset
+/// of the matching row of `src`, or returns an error on null rows.
+///
+/// This is used by code generated by `orcxx_derive` for enums.
+pub fn read_unions<'a, 'b, E, T, F>(
+    src: &UnionVectorBatch,
+    mut dst: &'b mut T,
+    type_name: &str,
+    mut read_value: F,
+) -> Result<usize, DeserializationError>
+where
+    E: 'a,
+    &'b mut T: DeserializationTarget<'a, Item = E> + 'b,
+    F: FnMut(u8, u64) -> Result<E, DeserializationError>,
+{
+    check_union_length(src, dst.len())?;
+    for (row, (value, d)) in src.iter().zip(dst.iter_mut()).enumerate() {
+        *d = match value {
+            Some((tag, offset)) => read_value(tag, offset).map_err(|e| e.at_row(row))?,
+            None => return Err(unexpected_null(src, type_name)),
+        };
+    }
+    Ok(src.num_elements().try_into().unwrap())
+}
+
+/// Same as [`read_unions`], but sets null rows to `None`
+///
+/// This is used by code generated by `orcxx_derive` for enums.
+pub fn read_nullable_unions<'a, 'b, E, T, F>(
+    src: &UnionVectorBatch,
+    mut dst: &'b mut T,
+    mut read_value: F,
+) -> Result<usize, DeserializationError>
+where
+    E: 'a,
+    &'b mut T: DeserializationTarget<'a, Item = Option<E>> + 'b,
+    F: FnMut(u8, u64) -> Result<E, DeserializationError>,
+{
+    check_union_length(src, dst.len())?;
+    for (row, (value, d)) in src.iter().zip(dst.iter_mut()).enumerate() {
+        *d = match value {
+            Some((tag, offset)) => Some(read_value(tag, offset).map_err(|e| e.at_row(row))?),
+            None => None,
+        };
+    }
+    Ok(src.num_elements().try_into().unwrap())
+}
+
+fn check_union_length(src: &UnionVectorBatch, dst_len: usize) -> Result<(), DeserializationError> {
+    let dst_len: u64 = dst_len
+        .try_into()
+        .map_err(DeserializationError::UsizeOverflow)?;
+    if src.num_elements() > dst_len {
+        return Err(DeserializationError::MismatchedLength {
+            src: src.num_elements(),
+            dst: dst_len,
+        });
+    }
+    Ok(())
+}
+
+/// Deserializes values of type `I` into a temporary buffer, then converts each of
+/// them with `new` (which moves them into a smart pointer)
+fn read_pointers<'a, 'b, I, P, T, F>(
+    src: &BorrowedColumnVectorBatch,
+    kind: Option<&Kind>,
+    mut dst: &'b mut T,
+    new: F,
+) -> Result<usize, DeserializationError>
+where
+    I: Default + OrcDeserialize,
+    &'b mut T: DeserializationTarget<'a, Item = P> + 'b,
+    F: Fn(I) -> P,
+{
+    let mut values: Vec<I> = Vec::with_capacity(dst.len());
+    values.resize_with(dst.len(), Default::default);
+    let num_elements =
+        read_from_vector_batch_with_optional_kind::<I, Vec<I>>(src, kind, &mut values)?;
+    for (value, d) in values.into_iter().take(num_elements).zip(dst.iter_mut()) {
+        *d = new(value);
+    }
+    Ok(num_elements)
+}
+
+/// Implements the traits of this module for a smart pointer, by deserializing
+/// values into a temporary buffer, then moving each of them into a new pointer.
+macro_rules! impl_pointer {
+    ($pointer:ident) => {
+        impl<T: OrcStruct> OrcStruct for $pointer<T> {
+            fn columns_with_prefix(prefix: &str) -> Vec<String> {
+                T::columns_with_prefix(prefix)
+            }
+        }
+
+        impl<T: CheckableKind> CheckableKind for $pointer<T> {
+            fn check_kind(kind: &Kind) -> Result<(), String> {
+                T::check_kind(kind)
+            }
+
+            fn check_nulls(column: &SelectedColumn, path: &str, check: &mut NullabilityCheck) {
+                T::check_nulls(column, path, check)
+            }
+
+            fn check_children_nulls(
+                column: &SelectedColumn,
+                path: &str,
+                check: &mut NullabilityCheck,
+            ) {
+                T::check_children_nulls(column, path, check)
+            }
+        }
+
+        impl<I: Default + OrcDeserialize> OrcDeserialize for $pointer<I> {
+            fn read_from_vector_batch<'a, 'b, T>(
+                src: &BorrowedColumnVectorBatch,
+                dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+            {
+                read_pointers(src, None, dst, $pointer::new)
+            }
+
+            fn read_from_vector_batch_with_kind<'a, 'b, T>(
+                src: &BorrowedColumnVectorBatch,
+                kind: &Kind,
+                dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+            {
+                read_pointers(src, Some(kind), dst, $pointer::new)
+            }
+        }
+
+        impl<I> OrcDeserializeOption for $pointer<I>
+        where
+            I: CheckableKind,
+            Option<I>: OrcDeserialize,
+        {
+            fn read_options_from_vector_batch<'a, 'b, T>(
+                src: &BorrowedColumnVectorBatch,
+                dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
+            {
+                read_pointers(src, None, dst, |value: Option<I>| value.map($pointer::new))
+            }
+
+            fn read_options_from_vector_batch_with_kind<'a, 'b, T>(
+                src: &BorrowedColumnVectorBatch,
+                kind: &Kind,
+                dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
+            {
+                read_pointers(src, Some(kind), dst, |value: Option<I>| {
+                    value.map($pointer::new)
+                })
+            }
+        }
+    };
 }
 
+impl_pointer!(Box);
+impl_pointer!(Rc);
+impl_pointer!(Arc);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -678,7 +2316,21 @@ mod tests {
     fn test_check_kind() {
         assert_eq!(i64::check_kind(&Kind::Long), Ok(()));
         assert_eq!(crate::Timestamp::check_kind(&Kind::Timestamp), Ok(()));
+        assert_eq!(
+            crate::Timestamp::check_kind(&Kind::TimestampInstant),
+            Ok(())
+        );
+        assert_eq!(crate::Date::check_kind(&Kind::Date), Ok(()));
+        assert_eq!(
+            Decimal::check_kind(&Kind::Decimal {
+                precision: 38,
+                scale: 10
+            }),
+            Ok(())
+        );
         assert_eq!(String::check_kind(&Kind::String), Ok(()));
+        assert_eq!(String::check_kind(&Kind::Varchar(10)), Ok(()));
+        assert_eq!(String::check_kind(&Kind::Char(10)), Ok(()));
         assert_eq!(Vec::<u8>::check_kind(&Kind::Binary), Ok(()));
     }
 
@@ -708,5 +2360,27 @@ mod tests {
             Vec::<u8>::check_kind(&Kind::String),
             Err("Vec<u8> must be decoded from ORC Binary, not ORC String".to_string())
         );
+        assert!(Decimal::check_kind(&Kind::Double).is_err());
+        assert!(Decimal::check_kind(&Kind::Decimal {
+            precision: 38,
+            scale: 30
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_decimal_from_raw() {
+        assert_eq!(
+            decimal_from_raw((-12345, 2)),
+            Ok(Decimal::from_i128_with_scale(-12345, 2))
+        );
+        let mantissa = 10i128.pow(37);
+        assert_eq!(
+            decimal_from_raw((mantissa, 10)),
+            Err(DeserializationError::DecimalOutOfRange {
+                mantissa,
+                scale: 10
+            })
+        );
     }
 }
@@ -0,0 +1,282 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Semantic comparison of two ORC files
+//!
+//! Rows are decoded with [`StructuredRowReader`] and converted to JSON-like values
+//! (see [`to_json`](::to_json)), then compared either by position, or by the value
+//! of a key column.
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::*;
+//!
+//! let open = |orc_path| {
+//!     let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+//!     reader::Reader::new(input_stream).expect("Could not read .orc")
+//! };
+//!
+//! let comparison = diff::compare(
+//!     &open("orc/examples/TestOrcFile.test1.orc"),
+//!     &open("orc/examples/TestOrcFile.testSnappy.orc"),
+//!     &diff::CompareOptions::default(),
+//! )
+//! .unwrap();
+//!
+//! assert!(!comparison.is_equal());
+//! for difference in comparison.schema_differences {
+//!     println!("{}", difference);
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use json::JsonValue;
+
+use errors::OpenOrcError;
+use kind::{Kind, KindDifference};
+use reader::{Reader, RowReaderOptions};
+use structured_reader::StructuredRowReader;
+use to_json::columntree_to_json_rows;
+
+/// Options passed to [`compare`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompareOptions {
+    max_row_differences: usize,
+    key: Option<String>,
+    batch_size: u64,
+}
+
+impl Default for CompareOptions {
+    fn default() -> CompareOptions {
+        CompareOptions {
+            max_row_differences: 10,
+            key: None,
+            batch_size: 1024,
+        }
+    }
+}
+
+impl CompareOptions {
+    /// Stop after finding this many differing rows. Defaults to 10.
+    pub fn max_row_differences(mut self, max_row_differences: usize) -> CompareOptions {
+        self.max_row_differences = max_row_differences;
+        self
+    }
+
+    /// Match rows by the value of this top-level column instead of their position.
+    ///
+    /// This loads the second file in memory. Keys are expected to be unique: if
+    /// several rows of the second file have the same key, only the last one is kept,
+    /// and every row of the first file with that key is compared to it.
+    pub fn key<S: Into<String>>(mut self, key: S) -> CompareOptions {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Number of rows to read at once. Defaults to 1024.
+    pub fn batch_size(mut self, batch_size: u64) -> CompareOptions {
+        self.batch_size = batch_size;
+        self
+    }
+}
+
+/// A row which differs between the two files
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowDifference {
+    /// Position of the row, or value of its key column
+    pub key: JsonValue,
+    /// The row in the first file, if any
+    pub a: Option<JsonValue>,
+    /// The row in the second file, if any
+    pub b: Option<JsonValue>,
+}
+
+/// Result of [`compare`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// Differences between the types of the files
+    pub schema_differences: Vec<KindDifference>,
+    /// Number of rows in the first file
+    pub row_count_a: u64,
+    /// Number of rows in the second file
+    pub row_count_b: u64,
+    /// First differing rows, up to [`CompareOptions::max_row_differences`]
+    pub row_differences: Vec<RowDifference>,
+}
+
+impl Comparison {
+    /// Returns whether no difference was found
+    pub fn is_equal(&self) -> bool {
+        self.schema_differences.is_empty()
+            && self.row_count_a == self.row_count_b
+            && self.row_differences.is_empty()
+    }
+}
+
+/// Reads all rows of a file as JSON values, and passes them to `f` until it
+/// returns `false`.
+fn for_each_row<F>(reader: &Reader, batch_size: u64, mut f: F) -> Result<(), OpenOrcError>
+where
+    F: FnMut(JsonValue) -> bool,
+{
+    let mut row_reader = reader
+        .row_reader(&RowReaderOptions::default())
+        .map_err(OpenOrcError::OrcError)?;
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, batch_size);
+    while let Some(columns) = structured_row_reader.next() {
+        for row in columntree_to_json_rows(columns) {
+            if !f(row) {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn compare_rows_by_position(
+    a: &Reader,
+    b: &Reader,
+    options: &CompareOptions,
+) -> Result<Vec<RowDifference>, OpenOrcError> {
+    let mut differences = Vec::new();
+    if options.max_row_differences == 0 {
+        return Ok(differences);
+    }
+
+    // Rows of the second file not compared yet
+    let mut rows_b = VecDeque::new();
+    let mut row_reader_b = b
+        .row_reader(&RowReaderOptions::default())
+        .map_err(OpenOrcError::OrcError)?;
+    let mut structured_row_reader_b =
+        StructuredRowReader::new(&mut row_reader_b, options.batch_size);
+
+    let mut position = 0u64;
+    let mut push_difference = |position: u64, a, b| {
+        differences.push(RowDifference {
+            key: position.into(),
+            a,
+            b,
+        });
+        differences.len() < options.max_row_differences
+    };
+
+    let mut done = false;
+    for_each_row(a, options.batch_size, |row_a| {
+        if rows_b.is_empty() {
+            if let Some(columns) = structured_row_reader_b.next() {
+                rows_b.extend(columntree_to_json_rows(columns));
+            }
+        }
+        let keep_going = match rows_b.pop_front() {
+            Some(row_b) if row_b == row_a => true,
+            row_b => push_difference(position, Some(row_a), row_b),
+        };
+        position += 1;
+        done = !keep_going;
+        keep_going
+    })?;
+
+    // Rows only in the second file
+    while !done {
+        let row_b = match rows_b.pop_front() {
+            Some(row_b) => row_b,
+            None => match structured_row_reader_b.next() {
+                Some(columns) => {
+                    rows_b.extend(columntree_to_json_rows(columns));
+                    continue;
+                }
+                None => break,
+            },
+        };
+        done = !push_difference(position, None, Some(row_b));
+        position += 1;
+    }
+
+    Ok(differences)
+}
+
+fn compare_rows_by_key(
+    a: &Reader,
+    b: &Reader,
+    key: &str,
+    options: &CompareOptions,
+) -> Result<Vec<RowDifference>, OpenOrcError> {
+    for reader in [a, b] {
+        match reader.kind() {
+            Kind::Struct(fields) if fields.iter().any(|(name, _)| name == key) => (),
+            _ => {
+                return Err(OpenOrcError::KindError(format!(
+                    "Key column {} is missing",
+                    key
+                )))
+            }
+        }
+    }
+
+    // Keys are stringified, as JsonValue does not implement Hash
+    let mut rows_b = HashMap::new();
+    let mut keys_b = Vec::new();
+    for_each_row(b, options.batch_size, |row_b| {
+        let key_b = json::stringify(row_b[key].clone());
+        // Duplicate keys overwrite the previous row, see CompareOptions::key
+        if rows_b.insert(key_b.clone(), row_b).is_none() {
+            keys_b.push(key_b);
+        }
+        true
+    })?;
+
+    let mut differences = Vec::new();
+    let mut keys_a = HashSet::new();
+    for_each_row(a, options.batch_size, |row_a| {
+        let key_a = json::stringify(row_a[key].clone());
+        let row_b = rows_b.get(&key_a);
+        if row_b != Some(&row_a) {
+            differences.push(RowDifference {
+                key: row_a[key].clone(),
+                b: row_b.cloned(),
+                a: Some(row_a),
+            });
+        }
+        keys_a.insert(key_a);
+        differences.len() < options.max_row_differences
+    })?;
+
+    for key_b in keys_b {
+        if differences.len() >= options.max_row_differences {
+            break;
+        }
+        if !keys_a.contains(&key_b) {
+            let row_b = rows_b.remove(&key_b).expect("Missing row");
+            differences.push(RowDifference {
+                key: row_b[key].clone(),
+                a: None,
+                b: Some(row_b),
+            });
+        }
+    }
+
+    Ok(differences)
+}
+
+/// Compares the schemas, row counts, and rows of two ORC files
+pub fn compare(
+    a: &Reader,
+    b: &Reader,
+    options: &CompareOptions,
+) -> Result<Comparison, OpenOrcError> {
+    let row_differences = match &options.key {
+        None => compare_rows_by_position(a, b, options)?,
+        Some(key) => compare_rows_by_key(a, b, key, options)?,
+    };
+    Ok(Comparison {
+        schema_differences: a.kind().differences(&b.kind()),
+        row_count_a: a.row_count(),
+        row_count_b: b.row_count(),
+        row_differences,
+    })
+}
@@ -0,0 +1,203 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Computes content hashes of ORC columns
+//!
+//! Each column (including structs, lists, and maps, whose hash covers their nulls
+//! and lengths) gets its own [XXH3](https://xxhash.com/) hash, computed over a
+//! canonical encoding of its values. Hashes depend neither on the batch size nor on
+//! how the file is laid out (stripes, compression, encodings, ...), so they can be
+//! used to check two files hold the same data.
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::*;
+//!
+//! let orc_path = "orc/examples/TestOrcFile.test1.orc";
+//! let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+//! let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let options = reader::RowReaderOptions::default().include_names(["int1", "string1"]);
+//! let mut row_reader = reader.row_reader(&options).unwrap();
+//!
+//! for (column, hash) in digest::digest_columns(&mut row_reader, 1024) {
+//!     println!("{}: {:016x}", column, hash);
+//! }
+//! ```
+
+use xxhash_rust::xxh3::Xxh3;
+
+use kind::Kind;
+use reader::{push_column_paths, RowReader};
+use structured_reader::{ColumnTree, StructuredRowReader};
+use vector::{DecimalVectorBatch, RangeVectorBatchIterator};
+
+/// Prefix of null values in the canonical encoding
+const NULL: u8 = 0;
+/// Prefix of non-null values in the canonical encoding
+const NOT_NULL: u8 = 1;
+
+fn update_nullable<V, I: Iterator<Item = Option<V>>, F>(hasher: &mut Xxh3, values: I, mut f: F)
+where
+    F: FnMut(&mut Xxh3, V),
+{
+    for value in values {
+        match value {
+            None => hasher.update(&[NULL]),
+            Some(value) => {
+                hasher.update(&[NOT_NULL]);
+                f(hasher, value)
+            }
+        }
+    }
+}
+
+fn update_offsets(hasher: &mut Xxh3, offsets: RangeVectorBatchIterator) {
+    update_nullable(hasher, offsets, |hasher, range| {
+        hasher.update(&(range.len() as u64).to_le_bytes())
+    })
+}
+
+/// Streaming computation of a hash for each column of a [`Kind`]
+pub struct ColumnDigests {
+    columns: Vec<String>,
+    hashers: Vec<Xxh3>,
+}
+
+impl ColumnDigests {
+    /// Returns hashers for the columns of `kind`, usually the
+    /// [`selected_kind()`](RowReader::selected_kind) of a [`RowReader`].
+    pub fn new(kind: &Kind) -> ColumnDigests {
+        let mut columns = Vec::new();
+        push_column_paths(kind, String::new(), &mut columns);
+        ColumnDigests {
+            hashers: columns.iter().map(|_| Xxh3::new()).collect(),
+            columns,
+        }
+    }
+
+    /// Updates hashes with the next batch of columns.
+    ///
+    /// # Panics
+    ///
+    /// If `tree` was not built from the `kind` passed to [`ColumnDigests::new`].
+    pub fn update(&mut self, tree: ColumnTree<'_>) {
+        let next_column = self.update_column(tree, 0);
+        assert_eq!(
+            next_column,
+            self.hashers.len(),
+            "ColumnTree has fewer columns than expected"
+        );
+    }
+
+    /// Updates the hash of the column at `index` (in depth-first order) and its
+    /// children, then returns the index of the next column.
+    fn update_column(&mut self, tree: ColumnTree<'_>, index: usize) -> usize {
+        let hasher = self
+            .hashers
+            .get_mut(index)
+            .expect("ColumnTree has more columns than expected");
+        match tree {
+            ColumnTree::Boolean(column) => {
+                update_nullable(hasher, column.iter(), |h, b| h.update(&[(b != 0) as u8]))
+            }
+            ColumnTree::Byte(column)
+            | ColumnTree::Short(column)
+            | ColumnTree::Int(column)
+            | ColumnTree::Long(column)
+            | ColumnTree::Date(column) => {
+                update_nullable(hasher, column.iter(), |h, n| h.update(&n.to_le_bytes()))
+            }
+            ColumnTree::Float(column) | ColumnTree::Double(column) => {
+                update_nullable(hasher, column.iter(), |h, n| {
+                    h.update(&n.to_bits().to_le_bytes())
+                })
+            }
+            ColumnTree::String(column) | ColumnTree::Binary(column) => {
+                update_nullable(hasher, column.iter(), |h, s| {
+                    h.update(&(s.len() as u64).to_le_bytes());
+                    h.update(s);
+                })
+            }
+            ColumnTree::Timestamp(column) | ColumnTree::TimestampInstant(column) => {
+                update_nullable(hasher, column.iter(), |h, (seconds, nanoseconds)| {
+                    h.update(&seconds.to_le_bytes());
+                    h.update(&nanoseconds.to_le_bytes());
+                })
+            }
+            ColumnTree::Decimal64(column) => update_nullable(hasher, column.iter(), |h, d| {
+                h.update(&d.mantissa().to_le_bytes());
+                h.update(&d.scale().to_le_bytes());
+            }),
+            // Mantissas may not fit in Decimal, so they are read raw, but in the same
+            // encoding as Decimal64 columns
+            ColumnTree::Decimal128(column) => {
+                update_nullable(hasher, column.iter_raw(), |h, (mantissa, scale)| {
+                    h.update(&mantissa.to_le_bytes());
+                    h.update(&scale.to_le_bytes());
+                })
+            }
+            ColumnTree::List { offsets, elements } => {
+                update_offsets(hasher, offsets);
+                return self.update_column(*elements, index + 1);
+            }
+            ColumnTree::Map {
+                offsets,
+                keys,
+                elements,
+            } => {
+                update_offsets(hasher, offsets);
+                let index = self.update_column(*keys, index + 1);
+                return self.update_column(*elements, index);
+            }
+            ColumnTree::Struct {
+                not_null,
+                num_elements,
+                elements,
+            } => {
+                match not_null {
+                    None => {
+                        for _ in 0..num_elements {
+                            hasher.update(&[NOT_NULL]);
+                        }
+                    }
+                    Some(not_null) => {
+                        for &b in not_null {
+                            hasher.update(&[if b == 0 { NULL } else { NOT_NULL }]);
+                        }
+                    }
+                }
+                let mut index = index + 1;
+                for (_, subtree) in elements {
+                    index = self.update_column(subtree, index);
+                }
+                return index;
+            }
+        }
+        index + 1
+    }
+
+    /// Returns the path of each column (see [`RowReader::selected_columns`]) and
+    /// its hash so far.
+    pub fn digests(&self) -> Vec<(String, u64)> {
+        self.columns
+            .iter()
+            .cloned()
+            .zip(self.hashers.iter().map(|hasher| hasher.digest()))
+            .collect()
+    }
+}
+
+/// Reads all remaining rows from `row_reader`, `batch_size` at a time, and returns
+/// the path of each selected column and its hash.
+pub fn digest_columns(row_reader: &mut RowReader, batch_size: u64) -> Vec<(String, u64)> {
+    let mut digests = ColumnDigests::new(&row_reader.selected_kind());
+    let mut structured_row_reader = StructuredRowReader::new(row_reader, batch_size);
+    while let Some(columns) = structured_row_reader.next() {
+        digests.update(columns);
+    }
+    digests.digests()
+}
@@ -7,13 +7,53 @@ use std::fmt;
 
 use thiserror::Error;
 
+/// Separates the type of the exception from its message, see `cpp-utils.hh`
+const KIND_SEPARATOR: char = '\x1e';
+
 /// Wrapper for exceptions thrown by the underlying C++ library
-#[derive(Debug)]
+///
+/// Use [`OrcError::kind`] to tell apart the causes of the error.
 pub struct OrcError(pub cxx::Exception);
 
+/// Cause of an [`OrcError`], mostly derived from the type of the C++ exception
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrcErrorKind {
+    /// The file is invalid or corrupted (`orc::ParseError`)
+    Parse,
+    /// A column selected with
+    /// [`RowReaderOptions::include_names`](::reader::RowReaderOptions::include_names)
+    /// does not exist in the file
+    MissingColumn,
+    /// The file uses a feature not supported by the C++ library
+    /// (`orc::NotImplementedYet`)
+    NotImplemented,
+    /// An argument passed to the C++ library is invalid (`orc::InvalidArgument`),
+    /// or an index passed to orcxx is out of range (eg. a stripe index)
+    InvalidArgument,
+    /// The file's schema cannot be converted to the requested schema
+    /// (`orc::SchemaEvolutionError`)
+    Schema,
+    /// The file could not be opened or read, including errors returned by readers
+    /// passed to [`InputStream::from_reader`](::reader::InputStream::from_reader)
+    Io,
+    /// A memory allocation failed
+    OutOfMemory,
+    /// Any other exception
+    Other,
+}
+
+impl fmt::Debug for OrcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OrcError")
+            .field("kind", &self.kind())
+            .field("what", &self.what())
+            .finish()
+    }
+}
+
 impl fmt::Display for OrcError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.0.fmt(f)
+        self.what().fmt(f)
     }
 }
 
@@ -32,17 +72,128 @@ impl PartialEq for OrcError {
 }
 
 impl OrcError {
+    /// Returns the message of the exception
     pub fn what(&self) -> &str {
-        self.0.what()
+        self.split().1
+    }
+
+    /// Returns the cause of the error
+    pub fn kind(&self) -> OrcErrorKind {
+        let (exception_type, what) = self.split();
+        match exception_type {
+            // The C++ library throws ParseError for these too, so they can only
+            // be told apart by their message
+            Some("ParseError") if what.starts_with("Invalid column selected") => {
+                OrcErrorKind::MissingColumn
+            }
+            Some("ParseError")
+                if ["Can't open ", "Bad read of ", "Short read of "]
+                    .iter()
+                    .any(|prefix| what.starts_with(prefix)) =>
+            {
+                OrcErrorKind::Io
+            }
+            Some("ParseError") => OrcErrorKind::Parse,
+            Some("NotImplementedYet") => OrcErrorKind::NotImplemented,
+            Some("InvalidArgument") => OrcErrorKind::InvalidArgument,
+            Some("SchemaEvolutionError") => OrcErrorKind::Schema,
+            Some("IoError") => OrcErrorKind::Io,
+            Some("OutOfMemory") => OrcErrorKind::OutOfMemory,
+            _ => OrcErrorKind::Other,
+        }
+    }
+
+    /// Returns the type of the exception (if known) and its message
+    fn split(&self) -> (Option<&str>, &str) {
+        match self.0.what().split_once(KIND_SEPARATOR) {
+            Some((exception_type, what)) => (Some(exception_type), what),
+            None => (None, self.0.what()),
+        }
     }
 }
 
 pub type OrcResult<T> = Result<T, OrcError>;
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum OpenOrcError {
     #[error("Could not open ORC file for reading: {0}")]
     OrcError(OrcError),
     #[error("Unexpected ORC file type: {0}")]
     KindError(String),
+    /// Returned by functions which open the file themselves, such as
+    /// [`read_file`](::read_file)
+    #[error("{0}")]
+    ReaderError(ReaderError),
+    /// Returned by functions which list files themselves, such as
+    /// `parallel_stripe_iterator::par_iter_orc_dir`
+    #[error("Could not list ORC files: {0}")]
+    IoError(std::io::Error),
+}
+
+/// Error returned when opening a [`Reader`](::reader::Reader)
+#[derive(Error, Debug)]
+pub enum ReaderError {
+    /// The file does not start with the ORC magic bytes
+    #[error("Not an ORC file")]
+    NotAnOrcFile,
+    /// The file starts with the ORC magic bytes, but its meta-data is invalid,
+    /// eg. because the file is truncated.
+    #[error("Corrupted ORC file: {0}")]
+    Corrupted(OrcError),
+    #[error("Could not read ORC file: {0}")]
+    OrcError(OrcError),
+}
+
+/// Error returned by [`RowReader::try_read_into`](::reader::RowReader::try_read_into),
+/// or by [`RowIterator`](::row_iterator::RowIterator) when reading rows again
+#[derive(Error, Debug, PartialEq)]
+pub enum ReadError {
+    /// The ORC library failed to decode the batch, eg. because the file is
+    /// corrupted or because decoding it exceeds
+    /// [`ReaderOptions::memory_budget`](::reader::ReaderOptions::memory_budget)
+    #[error("Could not read batch: {0}")]
+    OrcError(OrcError),
+    #[error("{0}")]
+    OversizedValue(OversizedValueError),
+    /// Statistics of the stripe starting at `first_row` show that column
+    /// `column_id` has values longer than allowed by
+    /// [`RowReaderOptions::max_value_length`](::reader::RowReaderOptions::max_value_length),
+    /// so it was rejected before being decoded
+    #[error("Column {column_id} of the stripe starting at row {first_row} has values longer than {max_length} bytes")]
+    OversizedStripe {
+        first_row: u64,
+        column_id: u64,
+        max_length: u64,
+    },
+    /// Rows which were read before are missing when reading them again, eg.
+    /// because the file changed in the meantime
+    #[error("Rows {start}..{end} disappeared while reading them again")]
+    RowsDisappeared { start: u64, end: u64 },
+}
+
+/// Error returned by [`Writer::write_batch`](::writer::Writer::write_batch)
+#[derive(Error, Debug, PartialEq)]
+pub enum WriteError {
+    /// The batch does not have the writer's type, or its buffers, offsets, or
+    /// children are inconsistent with its number of rows
+    #[error("Invalid batch: {0}")]
+    InvalidBatch(String),
+    #[error("Could not write batch: {0}")]
+    OrcError(OrcError),
+}
+
+/// Returned by [`RowReader::try_read_into`](::reader::RowReader::try_read_into)
+/// when values are longer than allowed by
+/// [`RowReaderOptions::max_value_length`](::reader::RowReaderOptions::max_value_length)
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error(
+    "{count} values in the batch starting at row {first_row} are longer than {max_length} bytes"
+)]
+pub struct OversizedValueError {
+    /// Row number of the first row in the batch
+    pub first_row: u64,
+    /// Number of values longer than `max_length` in the batch
+    pub count: u64,
+    pub max_length: u64,
 }
@@ -0,0 +1,348 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Converts Arrow record batches to ORC vector batches, so they can be written
+//! with a [`Writer`](::writer::Writer)
+//!
+//! Columns are copied one at a time, without building intermediate rows.
+//! Only types which can be written to vector batches are supported: booleans,
+//! integers, floating-point numbers, dates, timestamps, strings, binaries, lists,
+//! maps, and structs. Other types, including decimals, are rejected with
+//! [`FromArrowError::UnsupportedDataType`].
+//!
+//! Timestamps without a time zone are written as ORC `timestamp` columns, and
+//! timestamps with a time zone as `timestamp with local time zone` columns (which
+//! are instants, so the time zone itself is not kept).
+//!
+//! # Example
+//!
+//! ```
+//! extern crate arrow;
+//! # extern crate orcxx;
+//!
+//! use std::sync::Arc;
+//!
+//! use arrow::array::{Int64Array, RecordBatch, StringArray};
+//! use orcxx::from_arrow::{arrow_schema_to_kind, record_batch_to_vector_batch};
+//! use orcxx::writer::{OutputStream, Writer, WriterOptions};
+//!
+//! let record_batch = RecordBatch::try_from_iter([
+//!     ("id", Arc::new(Int64Array::from(vec![1, 2])) as _),
+//!     ("name", Arc::new(StringArray::from(vec![Some("a"), None])) as _),
+//! ])
+//! .unwrap();
+//!
+//! let kind = arrow_schema_to_kind(&record_batch.schema()).unwrap();
+//! let output_stream = OutputStream::to_local_file("/dev/null").unwrap();
+//! let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+//! let mut batch = record_batch_to_vector_batch(&record_batch).unwrap();
+//! writer.write_batch(&mut batch).unwrap();
+//! writer.close().unwrap();
+//! ```
+
+use std::ops::Range;
+
+use arrow::array::{Array, AsArray, OffsetSizeTrait, PrimitiveArray, RecordBatch};
+use arrow::datatypes::{
+    ArrowNativeType, ArrowPrimitiveType, DataType, Date32Type, Float32Type, Float64Type, Int16Type,
+    Int32Type, Int64Type, Int8Type, Schema, TimeUnit, TimestampMicrosecondType,
+    TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType,
+};
+use thiserror::Error;
+
+use errors::OrcError;
+use kind::Kind;
+use vector::{BorrowedColumnVectorBatchMut, ColumnVectorBatchMut, OwnedColumnVectorBatch};
+
+#[derive(Error, Debug)]
+pub enum FromArrowError {
+    /// The Arrow type has no ORC equivalent, or writing it to vector batches is not
+    /// supported yet (eg. decimals)
+    #[error("Arrow type {0} cannot be converted to ORC")]
+    UnsupportedDataType(DataType),
+    #[error("Could not create vector batch: {0}")]
+    OrcError(#[from] OrcError),
+}
+
+/// Returns the ORC type used to represent the given Arrow type
+pub fn arrow_datatype_to_kind(data_type: &DataType) -> Result<Kind, FromArrowError> {
+    Ok(match data_type {
+        DataType::Boolean => Kind::Boolean,
+        DataType::Int8 => Kind::Byte,
+        DataType::Int16 => Kind::Short,
+        DataType::Int32 => Kind::Int,
+        DataType::Int64 => Kind::Long,
+        DataType::Float32 => Kind::Float,
+        DataType::Float64 => Kind::Double,
+        DataType::Utf8 | DataType::LargeUtf8 => Kind::String,
+        DataType::Binary | DataType::LargeBinary => Kind::Binary,
+        DataType::Date32 => Kind::Date,
+        DataType::Timestamp(_, None) => Kind::Timestamp,
+        DataType::Timestamp(_, Some(_)) => Kind::TimestampInstant,
+        DataType::List(field) | DataType::LargeList(field) => {
+            Kind::List(Box::new(arrow_datatype_to_kind(field.data_type())?))
+        }
+        DataType::Map(entries, _) => match entries.data_type() {
+            DataType::Struct(fields) if fields.len() == 2 => Kind::Map {
+                key: Box::new(arrow_datatype_to_kind(fields[0].data_type())?),
+                value: Box::new(arrow_datatype_to_kind(fields[1].data_type())?),
+            },
+            _ => return Err(FromArrowError::UnsupportedDataType(data_type.clone())),
+        },
+        DataType::Struct(fields) => Kind::Struct(
+            fields
+                .iter()
+                .map(|field| {
+                    Ok((
+                        field.name().clone(),
+                        arrow_datatype_to_kind(field.data_type())?,
+                    ))
+                })
+                .collect::<Result<_, FromArrowError>>()?,
+        ),
+        _ => return Err(FromArrowError::UnsupportedDataType(data_type.clone())),
+    })
+}
+
+/// Returns the ORC type of rows of the given Arrow schema, which is always a struct.
+pub fn arrow_schema_to_kind(schema: &Schema) -> Result<Kind, FromArrowError> {
+    arrow_datatype_to_kind(&DataType::Struct(schema.fields().clone()))
+}
+
+/// Returns a vector batch holding a copy of the record batch's rows, whose type
+/// is returned by [`arrow_schema_to_kind`] for the record batch's schema.
+pub fn record_batch_to_vector_batch(
+    record_batch: &RecordBatch,
+) -> Result<OwnedColumnVectorBatch, FromArrowError> {
+    let kind = arrow_schema_to_kind(&record_batch.schema())?;
+    let num_rows = record_batch.num_rows();
+    let mut batch = OwnedColumnVectorBatch::new(&kind, num_rows as u64)?;
+    {
+        let mut structs = batch
+            .borrow_mut()
+            .try_into_structs()
+            .expect("Failed to cast structs vector batch");
+        for _ in 0..num_rows {
+            structs.push();
+        }
+        for (column, field_batch) in record_batch.columns().iter().zip(structs.fields()) {
+            write_array(column.as_ref(), field_batch);
+        }
+    }
+    Ok(batch)
+}
+
+/// Appends the values of `array` to `batch`, which must have the type returned by
+/// [`arrow_datatype_to_kind`].
+fn write_array(array: &dyn Array, batch: BorrowedColumnVectorBatchMut<'_>) {
+    match array.data_type() {
+        DataType::Boolean => {
+            write_longs(array.as_boolean().iter().map(|b| b.map(i64::from)), batch)
+        }
+        DataType::Int8 => write_primitives::<Int8Type>(array.as_primitive(), batch),
+        DataType::Int16 => write_primitives::<Int16Type>(array.as_primitive(), batch),
+        DataType::Int32 => write_primitives::<Int32Type>(array.as_primitive(), batch),
+        DataType::Int64 => write_primitives::<Int64Type>(array.as_primitive(), batch),
+        DataType::Date32 => write_primitives::<Date32Type>(array.as_primitive(), batch),
+        DataType::Float32 => write_doubles(
+            array
+                .as_primitive::<Float32Type>()
+                .iter()
+                .map(|n| n.map(f64::from)),
+            batch,
+        ),
+        DataType::Float64 => write_doubles(array.as_primitive::<Float64Type>().iter(), batch),
+        DataType::Utf8 => write_strings::<i32>(array, batch),
+        DataType::LargeUtf8 => write_strings::<i64>(array, batch),
+        DataType::Binary => write_binaries::<i32>(array, batch),
+        DataType::LargeBinary => write_binaries::<i64>(array, batch),
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            write_timestamps::<TimestampSecondType>(array.as_primitive(), 1, batch)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            write_timestamps::<TimestampMillisecondType>(array.as_primitive(), 1_000, batch)
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            write_timestamps::<TimestampMicrosecondType>(array.as_primitive(), 1_000_000, batch)
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            write_timestamps::<TimestampNanosecondType>(array.as_primitive(), 1_000_000_000, batch)
+        }
+        DataType::List(_) => write_lists::<i32>(array, batch),
+        DataType::LargeList(_) => write_lists::<i64>(array, batch),
+        DataType::Map(_, _) => {
+            let array = array.as_map();
+            let mut maps = batch
+                .try_into_maps()
+                .expect("Failed to cast maps vector batch");
+            let ranges = push_lengths(array, array.value_offsets(), |length| match length {
+                Some(length) => maps.push(length),
+                None => maps.push_null(),
+            });
+            for range in ranges {
+                let length = range.end - range.start;
+                write_array(
+                    array.keys().slice(range.start, length).as_ref(),
+                    maps.keys(),
+                );
+                write_array(
+                    array.values().slice(range.start, length).as_ref(),
+                    maps.elements(),
+                );
+            }
+        }
+        DataType::Struct(_) => {
+            let array = array.as_struct();
+            let mut structs = batch
+                .try_into_structs()
+                .expect("Failed to cast structs vector batch");
+            for i in 0..array.len() {
+                if array.is_null(i) {
+                    structs.push_null();
+                } else {
+                    structs.push();
+                }
+            }
+            for (column, field_batch) in array.columns().iter().zip(structs.fields()) {
+                write_array(column.as_ref(), field_batch);
+            }
+        }
+        data_type => panic!("Unsupported Arrow type {}", data_type),
+    }
+}
+
+fn write_lists<O: OffsetSizeTrait>(array: &dyn Array, batch: BorrowedColumnVectorBatchMut<'_>) {
+    let array = array.as_list::<O>();
+    let mut lists = batch
+        .try_into_lists()
+        .expect("Failed to cast lists vector batch");
+    let ranges = push_lengths(array, array.value_offsets(), |length| match length {
+        Some(length) => lists.push(length),
+        None => lists.push_null(),
+    });
+    for range in ranges {
+        write_array(
+            array
+                .values()
+                .slice(range.start, range.end - range.start)
+                .as_ref(),
+            lists.elements(),
+        );
+    }
+}
+
+/// Calls `push` with the length of each list or map of `array` (or `None` if it is
+/// null), and returns the ranges of their items, merged when contiguous.
+///
+/// Items of null lists and maps are not in the returned ranges, as Arrow allows
+/// them to have some, but ORC does not.
+fn push_lengths<O: ArrowNativeType, F: FnMut(Option<u64>)>(
+    array: &dyn Array,
+    offsets: &[O],
+    mut push: F,
+) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (i, bounds) in offsets.windows(2).enumerate() {
+        if array.is_null(i) {
+            push(None);
+            continue;
+        }
+        let (start, end) = (bounds[0].as_usize(), bounds[1].as_usize());
+        push(Some((end - start) as u64));
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ if start == end => (),
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+/// Writes timestamps of the given unit, `units_per_second` being 1 for seconds,
+/// 1000 for milliseconds, etc.
+fn write_timestamps<T: ArrowPrimitiveType<Native = i64>>(
+    array: &PrimitiveArray<T>,
+    units_per_second: i64,
+    batch: BorrowedColumnVectorBatchMut<'_>,
+) {
+    let mut batch = batch
+        .try_into_timestamps()
+        .expect("Failed to cast timestamps vector batch");
+    let nanoseconds_per_unit = 1_000_000_000 / units_per_second;
+    for value in array.iter() {
+        match value {
+            Some(value) => batch.push(
+                value.div_euclid(units_per_second),
+                value.rem_euclid(units_per_second) * nanoseconds_per_unit,
+            ),
+            None => batch.push_null(),
+        }
+    }
+}
+
+fn write_primitives<T: ArrowPrimitiveType>(
+    array: &PrimitiveArray<T>,
+    batch: BorrowedColumnVectorBatchMut<'_>,
+) where
+    T::Native: Into<i64>,
+{
+    write_longs(array.iter().map(|n| n.map(Into::into)), batch)
+}
+
+fn write_longs<I: Iterator<Item = Option<i64>>>(
+    values: I,
+    batch: BorrowedColumnVectorBatchMut<'_>,
+) {
+    let mut batch = batch
+        .try_into_longs()
+        .expect("Failed to cast longs vector batch");
+    for value in values {
+        match value {
+            Some(value) => batch.push(value),
+            None => batch.push_null(),
+        }
+    }
+}
+
+fn write_doubles<I: Iterator<Item = Option<f64>>>(
+    values: I,
+    batch: BorrowedColumnVectorBatchMut<'_>,
+) {
+    let mut batch = batch
+        .try_into_doubles()
+        .expect("Failed to cast doubles vector batch");
+    for value in values {
+        match value {
+            Some(value) => batch.push(value),
+            None => batch.push_null(),
+        }
+    }
+}
+
+fn write_strings<O: OffsetSizeTrait>(array: &dyn Array, batch: BorrowedColumnVectorBatchMut<'_>) {
+    write_bytes(
+        array.as_string::<O>().iter().map(|s| s.map(str::as_bytes)),
+        batch,
+    )
+}
+
+fn write_binaries<O: OffsetSizeTrait>(array: &dyn Array, batch: BorrowedColumnVectorBatchMut<'_>) {
+    write_bytes(array.as_binary::<O>().iter(), batch)
+}
+
+fn write_bytes<'a, I: Iterator<Item = Option<&'a [u8]>>>(
+    values: I,
+    batch: BorrowedColumnVectorBatchMut<'_>,
+) {
+    let mut batch = batch
+        .try_into_strings()
+        .expect("Failed to cast strings vector batch");
+    for value in values {
+        match value {
+            Some(value) => batch.push(value),
+            None => batch.push_null(),
+        }
+    }
+}
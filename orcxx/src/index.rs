@@ -0,0 +1,342 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Sidecar index of ORC files, to prune stripes without opening them.
+//!
+//! An [`OrcxxIndex`] captures the offset, row count, and the minimum and maximum
+//! of each column of every stripe of a file. It is built once with [`build`], and
+//! stored next to the file with [`OrcxxIndex::write_to`] in a compact binary
+//! format; services querying static datasets can then load it with
+//! [`OrcxxIndex::read_from`], keep only the stripes which may contain matching
+//! rows with [`OrcxxIndex::retain_stripes`], and pass it to
+//! [`RowIterator::with_index`](::row_iterator::RowIterator::with_index) so other
+//! stripes are skipped, without parsing the stripe statistics of the file.
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate orcxx;
+//! use orcxx::index::{self, ColumnRange, OrcxxIndex};
+//!
+//! let orc_path = "orc/examples/TestOrcFile.test1.orc";
+//! let index = index::build(orc_path).expect("Could not build index");
+//!
+//! let mut serialized = Vec::new();
+//! index.write_to(&mut serialized).unwrap();
+//! let index = OrcxxIndex::read_from(&serialized[..]).unwrap();
+//!
+//! // Keep only stripes where 'int1' (column 4) may be negative
+//! let index = index.retain_stripes(|stripe| match stripe.column_range(4) {
+//!     Some(ColumnRange::Integer { min, .. }) => *min < 0,
+//!     _ => true, // No statistics, the stripe may contain anything
+//! });
+//! assert_eq!(index.stripes.len(), 0);
+//! ```
+
+use std::io::{Read, Write};
+use std::ops::Range;
+
+use thiserror::Error;
+
+use errors::{OrcError, ReaderError};
+use reader::{InputStream, Reader};
+use statistics::ColumnStatistics;
+
+/// Bytes every serialized [`OrcxxIndex`] starts with
+const INDEX_MAGIC: &[u8; 8] = b"ORCXXIDX";
+
+/// Version of the serialization format, incremented on incompatible changes
+const INDEX_VERSION: u8 = 1;
+
+#[derive(Error, Debug)]
+pub enum IndexError {
+    #[error("Could not open ORC file: {0}")]
+    OrcError(#[from] OrcError),
+    #[error("Could not read ORC file: {0}")]
+    ReaderError(#[from] ReaderError),
+    #[error("Could not read or write index: {0}")]
+    Io(#[from] std::io::Error),
+    /// The serialized index is not in a format supported by this version of orcxx
+    #[error("Invalid index: {0}")]
+    InvalidFormat(String),
+    /// The index was built from a different file than the one being read
+    #[error("Index does not match the file: {0}")]
+    Mismatch(String),
+}
+
+/// Minimum and maximum of the values of a column in a stripe
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnRange {
+    /// Integer types, booleans excluded
+    Integer { min: i64, max: i64 },
+    /// Floating-point types
+    Double { min: f64, max: f64 },
+    /// String, varchar, and char types
+    String { min: Vec<u8>, max: Vec<u8> },
+}
+
+impl ColumnRange {
+    fn from_statistics(statistics: &ColumnStatistics) -> Option<ColumnRange> {
+        match statistics {
+            ColumnStatistics::Integer { min, max, .. } => Some(ColumnRange::Integer {
+                min: *min,
+                max: *max,
+            }),
+            ColumnStatistics::Double { min, max, .. } => Some(ColumnRange::Double {
+                min: *min,
+                max: *max,
+            }),
+            ColumnStatistics::String { min, max, .. } => Some(ColumnRange::String {
+                min: min.clone(),
+                max: max.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Location and column statistics of a stripe
+#[derive(Debug, Clone, PartialEq)]
+pub struct StripeIndex {
+    /// Position of the stripe in the file, in bytes
+    pub offset: u64,
+    /// Size of the stripe, in bytes
+    pub length: u64,
+    /// Row number (in the file) of the first row of the stripe
+    pub first_row: u64,
+    pub row_count: u64,
+    /// Range of values of each column, indexed by column id; `None` for columns
+    /// whose type has no range in ORC statistics
+    pub columns: Vec<Option<ColumnRange>>,
+}
+
+impl StripeIndex {
+    /// Returns the row numbers (in the file) of the rows of this stripe
+    pub fn rows(&self) -> Range<u64> {
+        self.first_row..self.first_row + self.row_count
+    }
+
+    /// Returns the range of values of the given column in this stripe, if known
+    pub fn column_range(&self, column_id: u64) -> Option<&ColumnRange> {
+        self.columns.get(column_id as usize)?.as_ref()
+    }
+}
+
+/// Stripes of an ORC file, with their column statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrcxxIndex {
+    /// [`fingerprint`](::kind::Kind::fingerprint) of the file's schema
+    pub schema_fingerprint: u64,
+    /// Total number of rows in the file, even after stripes were removed with
+    /// [`OrcxxIndex::retain_stripes`]
+    pub row_count: u64,
+    /// In the order of the file
+    pub stripes: Vec<StripeIndex>,
+}
+
+/// Opens the ORC file at `path` and builds its index
+pub fn build(path: &str) -> Result<OrcxxIndex, IndexError> {
+    let input_stream = InputStream::from_local_file(path)?;
+    let reader = Reader::new(input_stream)?;
+    Ok(OrcxxIndex::from_reader(&reader))
+}
+
+impl OrcxxIndex {
+    /// Builds the index of the file read by `reader`.
+    ///
+    /// Stripes have no column ranges if the file has no stripe statistics.
+    pub fn from_reader(reader: &Reader) -> OrcxxIndex {
+        let mut first_row = 0;
+        let stripes = reader
+            .stripes()
+            .enumerate()
+            .map(|(stripe_index, stripe)| {
+                let stripe_index = stripe_index as u64;
+                let stripe = StripeIndex {
+                    offset: stripe.offset(),
+                    length: stripe.bytes_count(),
+                    first_row,
+                    row_count: stripe.rows_count(),
+                    columns: reader
+                        .stripe_statistics(stripe_index)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|column| ColumnRange::from_statistics(&column?.statistics))
+                        .collect(),
+                };
+                first_row += stripe.row_count;
+                stripe
+            })
+            .collect();
+        OrcxxIndex {
+            schema_fingerprint: reader.schema_fingerprint(),
+            row_count: first_row,
+            stripes,
+        }
+    }
+
+    /// Returns the stripe containing the given row, if it was not removed
+    pub fn stripe_of_row(&self, row_number: u64) -> Option<&StripeIndex> {
+        let i = self
+            .stripes
+            .partition_point(|stripe| stripe.first_row + stripe.row_count <= row_number);
+        self.stripes
+            .get(i)
+            .filter(|stripe| stripe.rows().contains(&row_number))
+    }
+
+    /// Removes stripes for which `predicate` returns `false`
+    pub fn retain_stripes<F>(mut self, predicate: F) -> OrcxxIndex
+    where
+        F: FnMut(&StripeIndex) -> bool,
+    {
+        self.stripes.retain(predicate);
+        self
+    }
+
+    /// Returns the row ranges of the stripes of this index, merging adjacent ones
+    pub fn row_ranges(&self) -> Vec<Range<u64>> {
+        let mut ranges: Vec<Range<u64>> = Vec::new();
+        for stripe in &self.stripes {
+            match ranges.last_mut() {
+                Some(last) if last.end == stripe.first_row => last.end = stripe.rows().end,
+                _ => ranges.push(stripe.rows()),
+            }
+        }
+        ranges
+    }
+
+    /// Serializes the index
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), IndexError> {
+        writer.write_all(INDEX_MAGIC)?;
+        writer.write_all(&[INDEX_VERSION])?;
+        write_u64(&mut writer, self.schema_fingerprint)?;
+        write_u64(&mut writer, self.row_count)?;
+        write_u64(&mut writer, self.stripes.len() as u64)?;
+        for stripe in &self.stripes {
+            write_u64(&mut writer, stripe.offset)?;
+            write_u64(&mut writer, stripe.length)?;
+            write_u64(&mut writer, stripe.first_row)?;
+            write_u64(&mut writer, stripe.row_count)?;
+            write_u64(&mut writer, stripe.columns.len() as u64)?;
+            for column in &stripe.columns {
+                match column {
+                    None => writer.write_all(&[0])?,
+                    Some(ColumnRange::Integer { min, max }) => {
+                        writer.write_all(&[1])?;
+                        writer.write_all(&min.to_le_bytes())?;
+                        writer.write_all(&max.to_le_bytes())?;
+                    }
+                    Some(ColumnRange::Double { min, max }) => {
+                        writer.write_all(&[2])?;
+                        writer.write_all(&min.to_le_bytes())?;
+                        writer.write_all(&max.to_le_bytes())?;
+                    }
+                    Some(ColumnRange::String { min, max }) => {
+                        writer.write_all(&[3])?;
+                        write_bytes(&mut writer, min)?;
+                        write_bytes(&mut writer, max)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Deserializes an index written by [`OrcxxIndex::write_to`]
+    pub fn read_from<R: Read>(mut reader: R) -> Result<OrcxxIndex, IndexError> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != INDEX_MAGIC {
+            return Err(IndexError::InvalidFormat("not an orcxx index".to_owned()));
+        }
+        let version = read_u8(&mut reader)?;
+        if version != INDEX_VERSION {
+            return Err(IndexError::InvalidFormat(format!(
+                "unsupported version {}",
+                version
+            )));
+        }
+        let schema_fingerprint = read_u64(&mut reader)?;
+        let row_count = read_u64(&mut reader)?;
+        let num_stripes = read_u64(&mut reader)?;
+        let mut stripes = Vec::new();
+        for _ in 0..num_stripes {
+            let offset = read_u64(&mut reader)?;
+            let length = read_u64(&mut reader)?;
+            let first_row = read_u64(&mut reader)?;
+            let stripe_row_count = read_u64(&mut reader)?;
+            let num_columns = read_u64(&mut reader)?;
+            let mut columns = Vec::new();
+            for _ in 0..num_columns {
+                columns.push(match read_u8(&mut reader)? {
+                    0 => None,
+                    1 => Some(ColumnRange::Integer {
+                        min: read_u64(&mut reader)? as i64,
+                        max: read_u64(&mut reader)? as i64,
+                    }),
+                    2 => Some(ColumnRange::Double {
+                        min: f64::from_bits(read_u64(&mut reader)?),
+                        max: f64::from_bits(read_u64(&mut reader)?),
+                    }),
+                    3 => Some(ColumnRange::String {
+                        min: read_bytes(&mut reader)?,
+                        max: read_bytes(&mut reader)?,
+                    }),
+                    tag => {
+                        return Err(IndexError::InvalidFormat(format!(
+                            "unknown column range type {}",
+                            tag
+                        )))
+                    }
+                });
+            }
+            stripes.push(StripeIndex {
+                offset,
+                length,
+                first_row,
+                row_count: stripe_row_count,
+                columns,
+            });
+        }
+        Ok(OrcxxIndex {
+            schema_fingerprint,
+            row_count,
+            stripes,
+        })
+    }
+}
+
+fn write_u64<W: Write>(writer: &mut W, value: u64) -> std::io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_bytes<W: Write>(writer: &mut W, bytes: &[u8]) -> std::io::Result<()> {
+    write_u64(writer, bytes.len() as u64)?;
+    writer.write_all(bytes)
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> std::io::Result<u8> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> std::io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_bytes<R: Read>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    let length = read_u64(reader)?;
+    // Not preallocated, so a corrupted length fails on EOF instead of on allocation
+    let mut bytes = Vec::new();
+    reader.take(length).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != length {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
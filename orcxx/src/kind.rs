@@ -9,6 +9,9 @@
 //!
 //! This module and its structures are named "kind" instead of "type" in order to
 //! avoid clashes with the Rust keyword.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
 use cxx::let_cxx_string;
 
 use errors::OrcResult;
@@ -214,6 +217,296 @@ impl Kind {
             ffi::TypeKind { repr } => panic!("Unexpected value for orc::TypeKind: {}", repr),
         }
     }
+
+    /// Returns this type in the syntax parsed by [`Kind::new`], eg.
+    /// `struct<a:int,b:array<string>>`.
+    pub fn to_type_string(&self) -> String {
+        let mut type_string = String::new();
+        self.push_type_string(&mut type_string);
+        type_string
+    }
+
+    fn push_type_string(&self, type_string: &mut String) {
+        match self {
+            Kind::Boolean => type_string.push_str("boolean"),
+            Kind::Byte => type_string.push_str("tinyint"),
+            Kind::Short => type_string.push_str("smallint"),
+            Kind::Int => type_string.push_str("int"),
+            Kind::Long => type_string.push_str("bigint"),
+            Kind::Float => type_string.push_str("float"),
+            Kind::Double => type_string.push_str("double"),
+            Kind::String => type_string.push_str("string"),
+            Kind::Binary => type_string.push_str("binary"),
+            Kind::Timestamp => type_string.push_str("timestamp"),
+            Kind::List(item) => {
+                type_string.push_str("array<");
+                item.push_type_string(type_string);
+                type_string.push('>');
+            }
+            Kind::Map { key, value } => {
+                type_string.push_str("map<");
+                key.push_type_string(type_string);
+                type_string.push(',');
+                value.push_type_string(type_string);
+                type_string.push('>');
+            }
+            Kind::Struct(fields) => {
+                type_string.push_str("struct<");
+                for (i, (name, field)) in fields.iter().enumerate() {
+                    if i != 0 {
+                        type_string.push(',');
+                    }
+                    // Same quoting as orc::Type::toString()
+                    if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                        type_string.push_str(name);
+                    } else {
+                        type_string.push('`');
+                        type_string.push_str(&name.replace('`', "``"));
+                        type_string.push('`');
+                    }
+                    type_string.push(':');
+                    field.push_type_string(type_string);
+                }
+                type_string.push('>');
+            }
+            Kind::Union(variants) => {
+                type_string.push_str("uniontype<");
+                for (i, variant) in variants.iter().enumerate() {
+                    if i != 0 {
+                        type_string.push(',');
+                    }
+                    variant.push_type_string(type_string);
+                }
+                type_string.push('>');
+            }
+            Kind::Decimal { precision, scale } => {
+                type_string.push_str(&format!("decimal({},{})", precision, scale))
+            }
+            Kind::Date => type_string.push_str("date"),
+            Kind::Varchar(length) => type_string.push_str(&format!("varchar({})", length)),
+            Kind::Char(length) => type_string.push_str(&format!("char({})", length)),
+            Kind::TimestampInstant => type_string.push_str("timestamp with local time zone"),
+        }
+    }
+
+    /// Returns the names of all columns which can be selected with
+    /// [`RowReaderOptions::include_names`](::reader::RowReaderOptions::include_names),
+    /// ie. fields of structures, with nested field names separated by dots.
+    ///
+    /// Like in [`OrcStruct::columns`](::deserialize::OrcStruct::columns), lists
+    /// and maps do not add a component to the names of the fields they contain.
+    pub fn column_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.push_column_names("", &mut names);
+        names
+    }
+
+    fn push_column_names(&self, prefix: &str, names: &mut Vec<String>) {
+        match self {
+            Kind::Struct(fields) => {
+                for (name, field) in fields {
+                    let name = if prefix.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{}.{}", prefix, name)
+                    };
+                    field.push_column_names(&name, names);
+                    names.push(name);
+                }
+            }
+            Kind::List(item) => item.push_column_names(prefix, names),
+            Kind::Map { key, value } => {
+                key.push_column_names(prefix, names);
+                value.push_column_names(prefix, names);
+            }
+            Kind::Union(variants) => {
+                for variant in variants {
+                    variant.push_column_names(prefix, names);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns a structural hash of this type, which is stable across platforms
+    /// and versions of this crate.
+    ///
+    /// Two types have the same fingerprint if and only if they are equal (barring
+    /// hash collisions), including field names, decimal precision and scale, and
+    /// string lengths.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = FNV_OFFSET_BASIS;
+        self.update_fingerprint(&mut hash);
+        hash
+    }
+
+    /// Updates a FNV-1a hash with a canonical encoding of this type: the
+    /// `orc::TypeKind` of each node (in depth-first order), followed by its
+    /// parameters and number of children, if any.
+    fn update_fingerprint(&self, hash: &mut u64) {
+        let type_kind = match self {
+            Kind::Boolean => ffi::TypeKind::BOOLEAN,
+            Kind::Byte => ffi::TypeKind::BYTE,
+            Kind::Short => ffi::TypeKind::SHORT,
+            Kind::Int => ffi::TypeKind::INT,
+            Kind::Long => ffi::TypeKind::LONG,
+            Kind::Float => ffi::TypeKind::FLOAT,
+            Kind::Double => ffi::TypeKind::DOUBLE,
+            Kind::String => ffi::TypeKind::STRING,
+            Kind::Binary => ffi::TypeKind::BINARY,
+            Kind::Timestamp => ffi::TypeKind::TIMESTAMP,
+            Kind::List(_) => ffi::TypeKind::LIST,
+            Kind::Map { .. } => ffi::TypeKind::MAP,
+            Kind::Struct(_) => ffi::TypeKind::STRUCT,
+            Kind::Union(_) => ffi::TypeKind::UNION,
+            Kind::Decimal { .. } => ffi::TypeKind::DECIMAL,
+            Kind::Date => ffi::TypeKind::DATE,
+            Kind::Varchar(_) => ffi::TypeKind::VARCHAR,
+            Kind::Char(_) => ffi::TypeKind::CHAR,
+            Kind::TimestampInstant => ffi::TypeKind::TIMESTAMP_INSTANT,
+        };
+        fnv1a_update(hash, &type_kind.repr.to_le_bytes());
+        match self {
+            Kind::List(item) => item.update_fingerprint(hash),
+            Kind::Map { key, value } => {
+                key.update_fingerprint(hash);
+                value.update_fingerprint(hash);
+            }
+            Kind::Struct(fields) => {
+                fnv1a_update(hash, &(fields.len() as u64).to_le_bytes());
+                for (name, kind) in fields {
+                    fnv1a_update(hash, &(name.len() as u64).to_le_bytes());
+                    fnv1a_update(hash, name.as_bytes());
+                    kind.update_fingerprint(hash);
+                }
+            }
+            Kind::Union(variants) => {
+                fnv1a_update(hash, &(variants.len() as u64).to_le_bytes());
+                for kind in variants {
+                    kind.update_fingerprint(hash);
+                }
+            }
+            Kind::Decimal { precision, scale } => {
+                fnv1a_update(hash, &precision.to_le_bytes());
+                fnv1a_update(hash, &scale.to_le_bytes());
+            }
+            Kind::Varchar(length) | Kind::Char(length) => fnv1a_update(hash, &length.to_le_bytes()),
+            _ => (),
+        }
+    }
+}
+
+/// A difference between two [`Kind`]s, returned by [`Kind::differences`]
+///
+/// Paths are built from field names joined with `.`, with `_elem` for list
+/// elements and `_key`/`_value` for map keys and values, like
+/// [`RowReader::selected_columns`](::reader::RowReader::selected_columns).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KindDifference {
+    /// A struct field is only in the first type
+    OnlyInFirst(String),
+    /// A struct field is only in the second type
+    OnlyInSecond(String),
+    /// The column has a different type in each
+    Mismatch {
+        path: String,
+        first: Kind,
+        second: Kind,
+    },
+}
+
+impl fmt::Display for KindDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KindDifference::OnlyInFirst(path) => {
+                write!(f, "Field {} is only in the first type", path)
+            }
+            KindDifference::OnlyInSecond(path) => {
+                write!(f, "Field {} is only in the second type", path)
+            }
+            KindDifference::Mismatch {
+                path,
+                first,
+                second,
+            } => write!(
+                f,
+                "Column {:?} is {:?} in the first type and {:?} in the second type",
+                path, first, second
+            ),
+        }
+    }
+}
+
+impl Kind {
+    /// Returns differences between this type and another type, in depth-first order.
+    ///
+    /// Struct fields are matched by name, so reordering fields is not a difference.
+    pub fn differences(&self, other: &Kind) -> Vec<KindDifference> {
+        let mut differences = Vec::new();
+        self.push_differences("", other, &mut differences);
+        differences
+    }
+
+    fn push_differences(&self, path: &str, other: &Kind, differences: &mut Vec<KindDifference>) {
+        let child_path = |name: &str| {
+            if path.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{}.{}", path, name)
+            }
+        };
+        match (self, other) {
+            (Kind::Struct(fields), Kind::Struct(other_fields)) => {
+                let other_fields_map: HashMap<_, _> = other_fields
+                    .iter()
+                    .map(|(name, kind)| (name.as_str(), kind))
+                    .collect();
+                let names: HashSet<_> = fields.iter().map(|(name, _)| name.as_str()).collect();
+                for (name, kind) in fields {
+                    match other_fields_map.get(name.as_str()) {
+                        Some(other_kind) => {
+                            kind.push_differences(&child_path(name), other_kind, differences)
+                        }
+                        None => differences.push(KindDifference::OnlyInFirst(child_path(name))),
+                    }
+                }
+                for (name, _) in other_fields {
+                    if !names.contains(name.as_str()) {
+                        differences.push(KindDifference::OnlyInSecond(child_path(name)))
+                    }
+                }
+            }
+            (Kind::List(item), Kind::List(other_item)) => {
+                item.push_differences(&child_path("_elem"), other_item, differences)
+            }
+            (
+                Kind::Map { key, value },
+                Kind::Map {
+                    key: other_key,
+                    value: other_value,
+                },
+            ) => {
+                key.push_differences(&child_path("_key"), other_key, differences);
+                value.push_differences(&child_path("_value"), other_value, differences);
+            }
+            _ if self != other => differences.push(KindDifference::Mismatch {
+                path: path.to_owned(),
+                first: self.clone(),
+                second: other.clone(),
+            }),
+            _ => (),
+        }
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+fn fnv1a_update(hash: &mut u64, bytes: &[u8]) {
+    for &byte in bytes {
+        *hash ^= u64::from(byte);
+        *hash = hash.wrapping_mul(FNV_PRIME);
+    }
 }
 
 #[cfg(test)]
@@ -221,6 +514,21 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn type_string_roundtrip() {
+        for type_string in [
+            "boolean",
+            "struct<a:tinyint,b:smallint,c:int,d:bigint,e:float,f:double>",
+            "struct<a:string,b:binary,c:char(10),d:varchar(20),e:decimal(10,2)>",
+            "struct<a:timestamp,b:date,c:timestamp with local time zone>",
+            "struct<a:array<map<string,uniontype<int,string>>>,`b c`:int,`d``e`:int>",
+        ] {
+            let kind = Kind::new(type_string).unwrap();
+            assert_eq!(kind.to_type_string(), type_string);
+            assert_eq!(Kind::new(&kind.to_type_string()), Ok(kind));
+        }
+    }
+
     #[test]
     fn kind_from_orc_type_error() {
         assert!(Kind::new("").is_err());
@@ -396,4 +704,72 @@ mod tests {
 
         assert!(Kind::new("uniontype<a:boolean>").is_err());
     }
+
+    #[test]
+    fn differences() {
+        let kind = Kind::new("struct<a:int,b:array<struct<c:int,d:string>>,e:string>").unwrap();
+        assert_eq!(kind.differences(&kind), vec![]);
+        assert_eq!(
+            kind.differences(
+                &Kind::new("struct<e:string,b:array<struct<c:bigint>>,f:int>").unwrap()
+            ),
+            vec![
+                KindDifference::OnlyInFirst("a".to_owned()),
+                KindDifference::Mismatch {
+                    path: "b._elem.c".to_owned(),
+                    first: Kind::Int,
+                    second: Kind::Long,
+                },
+                KindDifference::OnlyInFirst("b._elem.d".to_owned()),
+                KindDifference::OnlyInSecond("f".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn column_names() {
+        let kind = Kind::new("struct<a:int,b:array<struct<c:int,d:string>>,e:string>").unwrap();
+        assert_eq!(kind.column_names(), vec!["a", "b.c", "b.d", "b", "e"]);
+        assert_eq!(Kind::Int.column_names(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn fingerprint() {
+        // Must not change across versions
+        assert_eq!(Kind::Boolean.fingerprint(), 0x4d25767f9dce13f5);
+
+        let kinds = [
+            "struct<>",
+            "struct<a:int>",
+            "struct<b:int>",
+            "struct<a:bigint>",
+            "struct<a:int,b:int>",
+            "struct<a:struct<b:int>>",
+            "struct<ab:int>",
+            "array<int>",
+            "array<array<int>>",
+            "map<string,int>",
+            "map<int,string>",
+            "uniontype<int>",
+            "uniontype<int,string>",
+            "decimal(10,2)",
+            "decimal(10,3)",
+            "decimal(2,10)",
+            "char(10)",
+            "varchar(10)",
+            "varchar(11)",
+        ];
+        let fingerprints: Vec<_> = kinds
+            .iter()
+            .map(|kind| Kind::new(kind).unwrap().fingerprint())
+            .collect();
+        for (i, fingerprint) in fingerprints.iter().enumerate() {
+            for (j, other_fingerprint) in fingerprints.iter().enumerate() {
+                if i != j {
+                    assert_ne!(fingerprint, other_fingerprint, "{} {}", kinds[i], kinds[j]);
+                }
+            }
+            assert_eq!(Kind::new(kinds[i]).unwrap().fingerprint(), *fingerprint);
+        }
+    }
 }
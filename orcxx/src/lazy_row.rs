@@ -0,0 +1,278 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Rows whose fields are deserialized on first access.
+//!
+//! [`LazyRowIterator`] reads batches without deserializing them. Each field of a
+//! [`LazyRow`] is deserialized (for the whole batch at once) the first time it is
+//! accessed in the batch, so columns which are never accessed are never
+//! deserialized. This is faster than [`RowIterator`](::row_iterator::RowIterator)
+//! when structures have many fields but only a few are needed for most rows (eg.
+//! when filtering on one of them).
+//!
+//! Field accessors are generated by `#[derive(OrcDeserialize)]` on structures
+//! with the `#[orc(lazy)]` attribute: for a structure `T`, it generates a trait
+//! `TLazyRow` implemented by `LazyRow<'_, T>`, with a method returning a reference
+//! to the value of each field, and a `try_` method returning errors instead of
+//! panicking. See the [`orcxx_derive` documentation](https://docs.rs/orcxx_derive/)
+//! for an example.
+//!
+//! This module is only available with the `lazy_row` feature.
+
+use std::any::Any;
+use std::convert::TryInto;
+use std::marker::PhantomData;
+use std::num::NonZeroU64;
+
+use once_cell::unsync::OnceCell;
+
+use deserialize::{
+    read_from_vector_batch_with_optional_kind, struct_field, CheckableKind, DeserializationError,
+    OrcDeserialize, OrcStruct,
+};
+use errors::OpenOrcError;
+use kind::Kind;
+use reader::{Reader, RowReader, RowReaderOptions};
+use vector::{ColumnVectorBatch, OwnedColumnVectorBatch};
+
+/// Structures whose fields can be deserialized independently from each other.
+///
+/// This is implemented by `#[derive(OrcDeserialize)]` on structures with the
+/// `#[orc(lazy)]` attribute.
+pub trait LazyStruct: OrcStruct + CheckableKind {
+    /// Number of fields of the structure
+    const NUM_FIELDS: usize;
+}
+
+/// Reads batches of rows of the given [`RowReader`], without deserializing them.
+///
+/// This cannot implement [`Iterator`], because batches are reused; use
+/// [`LazyRowIterator::next_batch`] instead.
+pub struct LazyRowIterator<T: LazyStruct> {
+    row_reader: RowReader,
+    batch: LazyBatch<T>,
+}
+
+impl<T: LazyStruct> LazyRowIterator<T> {
+    /// Returns an iterator on rows of the given [`Reader`].
+    ///
+    /// This includes only the needed columns which exist in the file (see
+    /// [`OrcStruct::columns_in`]).
+    ///
+    /// Errors are either detailed descriptions of format mismatch (as returned by
+    /// [`CheckableKind::check_kind`], or C++ exceptions.
+    pub fn new(
+        reader: &Reader,
+        batch_size: NonZeroU64,
+    ) -> Result<LazyRowIterator<T>, OpenOrcError> {
+        let options = RowReaderOptions::default().include_names(T::columns_in(&reader.kind()));
+        let row_reader = reader
+            .row_reader(&options)
+            .map_err(OpenOrcError::OrcError)?;
+        Self::from_row_reader(row_reader, batch_size)
+    }
+
+    /// Returns an iterator on rows of the given [`RowReader`], for callers which
+    /// need to set it up themselves. Iteration starts at its current position.
+    ///
+    /// Errors are detailed descriptions of format mismatch (as returned by
+    /// [`CheckableKind::check_kind`].
+    pub fn from_row_reader(
+        mut row_reader: RowReader,
+        batch_size: NonZeroU64,
+    ) -> Result<LazyRowIterator<T>, OpenOrcError> {
+        let kind = row_reader.selected_kind();
+        match T::check_kind(&kind) {
+            Ok(_) => (),
+            Err(msg) => return Err(OpenOrcError::KindError(msg)),
+        }
+        Ok(LazyRowIterator {
+            batch: LazyBatch {
+                batch: row_reader.row_batch(batch_size.into()),
+                kind,
+                fields: Vec::new(),
+                marker: PhantomData,
+            },
+            row_reader,
+        })
+    }
+
+    /// Reads the next batch, or returns `None` if there are no rows left.
+    pub fn next_batch(&mut self) -> Option<&LazyBatch<T>> {
+        if !self.row_reader.read_into(&mut self.batch.batch) {
+            return None;
+        }
+        self.batch.fields = (0..T::NUM_FIELDS).map(|_| OnceCell::new()).collect();
+        Some(&self.batch)
+    }
+}
+
+/// A batch of rows read by [`LazyRowIterator`], along with the values of the
+/// fields deserialized so far
+pub struct LazyBatch<T: LazyStruct> {
+    batch: OwnedColumnVectorBatch,
+    /// Selected kind of the row reader, to match fields to columns by name
+    kind: Kind,
+    /// `Vec` of the values of each field, once deserialized
+    fields: Vec<OnceCell<Box<dyn Any>>>,
+    marker: PhantomData<T>,
+}
+
+impl<T: LazyStruct> LazyBatch<T> {
+    /// Returns the number of rows in the batch
+    pub fn len(&self) -> usize {
+        self.batch
+            .borrow()
+            .num_elements()
+            .try_into()
+            .expect("batch length overflows usize")
+    }
+
+    /// Returns whether the batch has no rows
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the row at the given index in the batch, if any
+    pub fn get(&self, index: usize) -> Option<LazyRow<'_, T>> {
+        if index < self.len() {
+            Some(LazyRow { batch: self, index })
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator on rows of the batch
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = LazyRow<'_, T>> {
+        (0..self.len()).map(move |index| LazyRow { batch: self, index })
+    }
+
+    /// Deserializes all values of a field, or fills them with `default` if the
+    /// field's column is missing
+    ///
+    /// # Safety
+    ///
+    /// If `assume_utf8` is `true`, all strings in the field's column must be valid
+    /// UTF-8.
+    unsafe fn field_values<F: OrcDeserialize + Default>(
+        &self,
+        field_index: usize,
+        column_name: &str,
+        default: Option<fn() -> F>,
+        assume_utf8: bool,
+    ) -> Result<Vec<F>, DeserializationError> {
+        let mut values = Vec::new();
+        values.resize_with(self.len(), Default::default);
+        let fields = match &self.kind {
+            Kind::Struct(fields) => Some(&fields[..]),
+            _ => None,
+        };
+        let (column_index, field_kind) =
+            match (struct_field(fields, field_index, column_name), default) {
+                (Err(DeserializationError::MissingField(_)), Some(default)) => {
+                    values.iter_mut().for_each(|value| *value = default());
+                    return Ok(values);
+                }
+                (field, _) => field?,
+            };
+        let batch = self.batch.borrow();
+        let columns = batch
+            .try_into_structs()
+            .map_err(DeserializationError::MismatchedColumnKind)?
+            .fields();
+        let column = columns
+            .get(column_index)
+            .unwrap_or_else(|| panic!("Failed to get '{}' column", column_name));
+        if assume_utf8 {
+            F::read_from_vector_batch_assume_utf8::<Vec<F>>(column, &mut values)
+        } else {
+            read_from_vector_batch_with_optional_kind::<_, Vec<F>>(column, field_kind, &mut values)
+        }
+        .map_err(|e| e.in_column(column_name))?;
+        Ok(values)
+    }
+}
+
+/// A row of a [`LazyBatch`], whose fields are accessed through the methods of the
+/// trait generated by `#[orc(lazy)]`
+pub struct LazyRow<'a, T: LazyStruct> {
+    batch: &'a LazyBatch<T>,
+    index: usize,
+}
+
+impl<'a, T: LazyStruct> Clone for LazyRow<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T: LazyStruct> Copy for LazyRow<'a, T> {}
+
+impl<'a, T: LazyStruct> LazyRow<'a, T> {
+    /// Returns the index of the row in its batch
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the value of a field in this row, after deserializing it in the
+    /// whole batch if it was not already. After an error, the field is
+    /// deserialized again on its next access.
+    ///
+    /// This is used by code generated by `orcxx_derive`.
+    ///
+    /// # Panics
+    ///
+    /// If the field was already deserialized with a different type.
+    #[doc(hidden)]
+    pub fn try_field<F: OrcDeserialize + Default + 'static>(
+        &self,
+        field_index: usize,
+        column_name: &str,
+        default: Option<fn() -> F>,
+    ) -> Result<&'a F, DeserializationError> {
+        // Safe because strings are validated
+        unsafe { self.get_or_read_field(field_index, column_name, default, false) }
+    }
+
+    /// Same as [`try_field`](LazyRow::try_field), but does not check strings are
+    /// valid UTF-8, for fields with `#[orc(unsafe_assume_utf8)]`.
+    ///
+    /// This is used by code generated by `orcxx_derive`.
+    ///
+    /// # Safety
+    ///
+    /// All strings in the field's column must be valid UTF-8.
+    #[doc(hidden)]
+    pub unsafe fn try_field_assume_utf8<F: OrcDeserialize + Default + 'static>(
+        &self,
+        field_index: usize,
+        column_name: &str,
+        default: Option<fn() -> F>,
+    ) -> Result<&'a F, DeserializationError> {
+        self.get_or_read_field(field_index, column_name, default, true)
+    }
+
+    /// # Safety
+    ///
+    /// If `assume_utf8` is `true`, all strings in the field's column must be valid
+    /// UTF-8.
+    unsafe fn get_or_read_field<F: OrcDeserialize + Default + 'static>(
+        &self,
+        field_index: usize,
+        column_name: &str,
+        default: Option<fn() -> F>,
+        assume_utf8: bool,
+    ) -> Result<&'a F, DeserializationError> {
+        let batch = self.batch;
+        let values = batch.fields[field_index].get_or_try_init(|| {
+            batch
+                .field_values(field_index, column_name, default, assume_utf8)
+                .map(|values| Box::new(values) as Box<dyn Any>)
+        })?;
+        Ok(&values
+            .downcast_ref::<Vec<F>>()
+            .expect("Field was deserialized with a different type")[self.index])
+    }
+}
@@ -5,7 +5,8 @@
 
 //! Rust wrapper for the Apache ORC C++ library.
 //!
-//! Currently, it only allows reading files, not writing.
+//! It mostly focuses on reading files; [`writer`] provides a low-level API to write
+//! column vector batches.
 //!
 //! ORC, short for Optimized Row Columnar, is a column-oriented data storage format.
 //! As such, most of the APIs in this library operate on columns, rather than rows.
@@ -51,10 +52,12 @@
 //! May panic when requesting vector batches larger than `isize`;
 //! this includes vector batches for variable-sized columns (maps and lists).
 //! This is unlikely to happen on 64-bits machines (they would OOM first).
+//! [`reader::Reader::estimated_memory`] can help choose a batch size beforehand.
 //!
 //! [`row_iterator::RowIterator`] panics when underlying calls to
 //! [`deserialize::OrcDeserialize::read_from_vector_batch`] error (so you may want to
-//! avoid the former when working with non-trusted data).
+//! avoid the former when working with non-trusted data), unless configured otherwise
+//! with [`row_iterator::RowIterator::on_error`].
 //!
 //! Panics may happen when the C++ library doesn't behave as expected, too.
 //! C++ exceptions should be converted to Rust [`Result`]s, though.
@@ -111,29 +114,91 @@
 //! ```
 
 extern crate cxx;
+#[cfg(feature = "async")]
+extern crate futures_core;
+#[cfg(feature = "lazy_row")]
+extern crate once_cell;
 #[cfg(feature = "rayon")]
 extern crate rayon;
 extern crate thiserror;
+#[cfg(feature = "async")]
+extern crate tokio;
+#[cfg(any(feature = "digest", feature = "cardinality"))]
+extern crate xxhash_rust;
 
+#[cfg(feature = "async")]
+pub mod async_reader;
+#[cfg(feature = "cardinality")]
+pub mod cardinality;
+pub mod channel;
+pub mod conformance;
+pub mod dedup;
 pub mod deserialize;
+#[cfg(feature = "json")]
+pub mod diff;
+#[cfg(feature = "digest")]
+pub mod digest;
 pub mod errors;
+pub mod index;
 mod int128;
 pub mod kind;
+#[cfg(feature = "lazy_row")]
+pub mod lazy_row;
 mod memorypool;
+pub mod multi_file_row_iterator;
 #[cfg(feature = "rayon")]
 pub mod parallel_row_iterator;
+#[cfg(feature = "rayon")]
+pub mod parallel_stripe_iterator;
+#[cfg(feature = "json")]
+pub mod partition;
 pub mod reader;
+pub mod row_index;
 pub mod row_iterator;
+pub mod schema_registry;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod statistics;
 pub mod structured_reader;
+pub mod testing;
+pub mod value;
 pub mod vector;
+pub mod writer;
 
-#[cfg(feature = "json")]
+pub use row_iterator::{read_file, stream_file};
+pub use vector::{zip_null_count, zip_nulls, zip_validity, ZipNulls};
+
+#[cfg(feature = "chrono")]
 extern crate chrono;
 #[cfg(feature = "json")]
 extern crate json;
 extern crate rust_decimal;
+#[cfg(feature = "serde")]
+extern crate serde as serde_lib;
+#[cfg(feature = "time")]
+extern crate time;
 #[cfg(feature = "json")]
 pub mod to_json;
+#[cfg(feature = "serde_json")]
+extern crate serde_json;
+#[cfg(feature = "serde_json")]
+pub mod to_serde_json;
+
+#[cfg(feature = "arrow")]
+extern crate arrow;
+#[cfg(feature = "parquet")]
+extern crate parquet;
+#[cfg(feature = "arrow")]
+pub mod from_arrow;
+#[cfg(feature = "arrow")]
+pub mod to_arrow;
+#[cfg(feature = "parquet")]
+pub mod to_parquet;
+
+#[cfg(feature = "ndarray")]
+extern crate ndarray;
+#[cfg(feature = "ndarray")]
+pub mod to_ndarray;
 
 /// ORC timestamp (timezone-less)
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
@@ -141,3 +206,7 @@ pub struct Timestamp {
     pub seconds: i64,
     pub nanoseconds: i64,
 }
+
+/// ORC date, as a number of days since 1970-01-01
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Date(pub i32);
@@ -3,6 +3,8 @@
 // License: GNU General Public License version 3, or any later version
 // See top-level LICENSE file for more information
 
+use std::os::raw::c_char;
+
 #[cxx::bridge]
 pub(crate) mod ffi {
     unsafe extern "C++" {
@@ -19,6 +21,8 @@ pub(crate) mod ffi {
         type Int64DataBuffer;
 
         fn data(&self) -> *const i64;
+        fn size(&self) -> u64;
+        fn capacity(&self) -> u64;
     }
 
     #[namespace = "orcxx_rs"]
@@ -26,6 +30,8 @@ pub(crate) mod ffi {
         type Int128DataBuffer;
 
         fn data(&self) -> *const Int128;
+        fn size(&self) -> u64;
+        fn capacity(&self) -> u64;
     }
 
     #[namespace = "orcxx_rs"]
@@ -33,6 +39,8 @@ pub(crate) mod ffi {
         type DoubleDataBuffer;
 
         fn data(&self) -> *const f64;
+        fn size(&self) -> u64;
+        fn capacity(&self) -> u64;
     }
 
     #[namespace = "orcxx_rs"]
@@ -40,6 +48,8 @@ pub(crate) mod ffi {
         type StringDataBuffer;
 
         fn data(&self) -> *const *mut c_char;
+        fn size(&self) -> u64;
+        fn capacity(&self) -> u64;
     }
 
     #[namespace = "orcxx_rs"]
@@ -48,5 +58,79 @@ pub(crate) mod ffi {
 
         fn data(&self) -> *const c_char;
         fn size(&self) -> u64;
+        fn capacity(&self) -> u64;
+    }
+
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        type UCharDataBuffer;
+
+        fn data(&self) -> *const u8;
+        fn size(&self) -> u64;
+        fn capacity(&self) -> u64;
+    }
+
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        type UInt64DataBuffer;
+
+        fn data(&self) -> *const u64;
+        fn size(&self) -> u64;
+        fn capacity(&self) -> u64;
     }
 }
+
+/// Common methods of `orc::DataBuffer` instances
+pub(crate) trait DataBuffer {
+    type Item;
+
+    fn data(&self) -> *const Self::Item;
+
+    /// Number of items in the buffer
+    fn size(&self) -> u64;
+
+    /// Number of items the buffer can hold without reallocating
+    fn capacity(&self) -> u64;
+
+    /// Same as [`DataBuffer::data`], but checks (in debug builds) the buffer has at
+    /// least `len` items, so inconsistent vector batches panic early instead of
+    /// being read out of bounds.
+    fn checked_data(&self, len: u64) -> *const Self::Item {
+        debug_assert!(
+            len <= self.size(),
+            "Expected at least {} items in buffer, but it has {} (capacity: {})",
+            len,
+            self.size(),
+            self.capacity()
+        );
+        self.data()
+    }
+}
+
+macro_rules! impl_data_buffer {
+    ($buffer:ident, $item:ty) => {
+        impl DataBuffer for ffi::$buffer {
+            type Item = $item;
+
+            fn data(&self) -> *const $item {
+                ffi::$buffer::data(self)
+            }
+
+            fn size(&self) -> u64 {
+                ffi::$buffer::size(self)
+            }
+
+            fn capacity(&self) -> u64 {
+                ffi::$buffer::capacity(self)
+            }
+        }
+    };
+}
+
+impl_data_buffer!(Int64DataBuffer, i64);
+impl_data_buffer!(Int128DataBuffer, ffi::Int128);
+impl_data_buffer!(DoubleDataBuffer, f64);
+impl_data_buffer!(StringDataBuffer, *mut c_char);
+impl_data_buffer!(CharDataBuffer, c_char);
+impl_data_buffer!(UCharDataBuffer, u8);
+impl_data_buffer!(UInt64DataBuffer, u64);
@@ -0,0 +1,280 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Iterator on rows of a dataset split across multiple ORC files.
+//!
+//! [`MultiFileRowIterator`] chains one [`RowIterator`] per file, and numbers rows
+//! as if files were concatenated in the given order, for [`MultiFileRowIterator::seek`]
+//! and [`MultiFileRowIterator::take_errors`].
+//!
+//! Iterator items need to implement [`OrcDeserialize`] trait; `orcxx_derive` can
+//! generate implementations for structures.
+
+use std::num::NonZeroU64;
+
+use deserialize::{CheckableKind, DeserializationError, OrcDeserialize, OrcStruct};
+use errors::OpenOrcError;
+use reader::{InputStream, Reader, RowReaderOptions};
+use row_iterator::{OnError, RowIterator};
+
+/// Iterator on rows of multiple files, in order.
+///
+/// # Panics
+///
+/// next() repeatedly calls [`OrcDeserialize::read_from_vector_batch`] and panics
+/// when it returns a [`DeserializationError`], unless configured otherwise with
+/// [`MultiFileRowIterator::on_error`].
+pub struct MultiFileRowIterator<T: OrcDeserialize + Default> {
+    iterators: Vec<RowIterator<T>>,
+    /// Number of the first row of each iterator in the concatenation of all of
+    /// them, followed by the total number of rows
+    offsets: Vec<u64>,
+    /// Index of the iterator [`Iterator::next`] reads from; it moves forward when
+    /// the iterator is exhausted
+    front: usize,
+    /// Index after the iterator [`DoubleEndedIterator::next_back`] reads from; it
+    /// moves backward when the iterator is exhausted.
+    ///
+    /// Both are moved to the same iterator when one of them yields a row, so mixing
+    /// calls to `next()` and `next_back()` moves a single cursor, like with
+    /// [`RowIterator`].
+    back: usize,
+}
+
+impl<T: OrcDeserialize + OrcStruct + CheckableKind + Default> MultiFileRowIterator<T> {
+    /// Returns an iterator on rows of the given [`Reader`]s, in order.
+    ///
+    /// This calls [`RowIterator::new`] for each of them, so it includes only the
+    /// needed columns which exist in each file (see [`OrcStruct::columns_in`]).
+    ///
+    /// Errors are either detailed descriptions of format mismatch (as returned by
+    /// [`CheckableKind::check_kind`], and prefixed with the index of the file), or
+    /// C++ exceptions.
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is larger than `usize`.
+    pub fn new<'a, I: IntoIterator<Item = &'a Reader>>(
+        readers: I,
+        batch_size: NonZeroU64,
+    ) -> Result<MultiFileRowIterator<T>, OpenOrcError> {
+        readers
+            .into_iter()
+            .enumerate()
+            .map(|(i, reader)| with_file_index(i, RowIterator::new(reader, batch_size)))
+            .collect::<Result<_, _>>()
+            .map(Self::from_row_iterators)
+    }
+
+    /// Opens the ORC files at the given paths, and returns an iterator on their rows,
+    /// in order.
+    ///
+    /// See [`MultiFileRowIterator::new`].
+    pub fn from_paths<I: IntoIterator>(
+        paths: I,
+        batch_size: NonZeroU64,
+    ) -> Result<MultiFileRowIterator<T>, OpenOrcError>
+    where
+        I::Item: AsRef<str>,
+    {
+        paths
+            .into_iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let input_stream =
+                    InputStream::from_local_file(path.as_ref()).map_err(OpenOrcError::OrcError)?;
+                let reader = Reader::new(input_stream).map_err(OpenOrcError::ReaderError)?;
+                with_file_index(i, RowIterator::new(&reader, batch_size))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self::from_row_iterators)
+    }
+}
+
+impl<T: OrcDeserialize + Default> MultiFileRowIterator<T> {
+    /// Returns an iterator on rows of the given [`Reader`]s, in order, reading the
+    /// columns selected by `options` in each of them.
+    ///
+    /// Errors are detailed descriptions of format mismatch (as returned by
+    /// [`CheckableKind::check_kind`], and prefixed with the index of the file), or
+    /// C++ exceptions.
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is larger than `usize`.
+    pub fn new_with_options<'a, I: IntoIterator<Item = &'a Reader>>(
+        readers: I,
+        batch_size: NonZeroU64,
+        options: &RowReaderOptions,
+    ) -> Result<MultiFileRowIterator<T>, OpenOrcError> {
+        readers
+            .into_iter()
+            .enumerate()
+            .map(|(i, reader)| {
+                with_file_index(
+                    i,
+                    RowIterator::new_with_options(reader, batch_size, options),
+                )
+            })
+            .collect::<Result<_, _>>()
+            .map(Self::from_row_iterators)
+    }
+
+    /// Returns an iterator on the rows of the given iterators, in order, for callers
+    /// which need to set them up themselves (eg. with [`RowIterator::range`]).
+    ///
+    /// Rows of each iterator are numbered from the start of its range.
+    pub fn from_row_iterators(iterators: Vec<RowIterator<T>>) -> MultiFileRowIterator<T> {
+        let mut offsets = Vec::with_capacity(iterators.len() + 1);
+        let mut offset = 0;
+        offsets.push(offset);
+        for iterator in &iterators {
+            let rows = iterator.rows();
+            offset += rows.end - rows.start;
+            offsets.push(offset);
+        }
+        MultiFileRowIterator {
+            front: 0,
+            back: iterators.len(),
+            iterators,
+            offsets,
+        }
+    }
+
+    /// Sets what to do with rows which cannot be deserialized, in all files (see
+    /// [`RowIterator::on_error`]).
+    pub fn on_error(mut self, on_error: OnError) -> Self {
+        self.iterators = self
+            .iterators
+            .into_iter()
+            .map(|iterator| iterator.on_error(on_error))
+            .collect();
+        self
+    }
+
+    /// Returns errors which occurred since the last call to this function, along
+    /// with the number of the row (in the concatenation of all files) which caused
+    /// them.
+    pub fn take_errors(&mut self) -> Vec<(u64, DeserializationError)> {
+        let mut errors = Vec::new();
+        for (iterator, offset) in self.iterators.iter_mut().zip(&self.offsets) {
+            let start = iterator.rows().start;
+            errors.extend(
+                iterator
+                    .take_errors()
+                    .into_iter()
+                    .map(|(row_number, e)| (offset + row_number - start, e)),
+            );
+        }
+        errors
+    }
+
+    /// Returns the total number of rows of all files
+    pub fn row_count(&self) -> u64 {
+        *self.offsets.last().unwrap()
+    }
+
+    /// Moves iteration to the given row (in the concatenation of all files), forward
+    /// or backward.
+    ///
+    /// Rows of previous files are then available to
+    /// [`DoubleEndedIterator::next_back`]. Seeking to [`MultiFileRowIterator::row_count`]
+    /// or past it moves to the end.
+    ///
+    /// # Panics
+    ///
+    /// Like [`RowIterator::seek`].
+    pub fn seek(mut self, row_number: u64) -> Self {
+        // Index of the last file starting at or before the row
+        let file_index = self
+            .offsets
+            .partition_point(|&offset| offset <= row_number)
+            .saturating_sub(1);
+        let offsets = &self.offsets;
+        self.iterators = self
+            .iterators
+            .into_iter()
+            .enumerate()
+            .map(|(i, iterator)| {
+                if i < file_index {
+                    iterator.fast_forward()
+                } else if i > file_index {
+                    iterator.rewind()
+                } else {
+                    let start = iterator.rows().start;
+                    iterator.seek(start + row_number - offsets[i])
+                }
+            })
+            .collect();
+        self.front = file_index;
+        self.back = usize::min(file_index + 1, self.iterators.len());
+        self
+    }
+}
+
+fn with_file_index<T>(
+    file_index: usize,
+    result: Result<T, OpenOrcError>,
+) -> Result<T, OpenOrcError> {
+    result.map_err(|e| match e {
+        OpenOrcError::KindError(msg) => {
+            OpenOrcError::KindError(format!("File {} cannot be read: {}", file_index, msg))
+        }
+        e => e,
+    })
+}
+
+/// # Panics
+///
+/// next() repeatedly calls [`OrcDeserialize::read_from_vector_batch`] and panics
+/// when it returns a [`DeserializationError`], unless configured otherwise with
+/// [`MultiFileRowIterator::on_error`].
+impl<T: OrcDeserialize + Default> Iterator for MultiFileRowIterator<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while let Some(iterator) = self.iterators.get_mut(self.front) {
+            if let Some(row) = iterator.next() {
+                self.back = self.front + 1;
+                return Some(row);
+            }
+            self.front += 1;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+/// # Panics
+///
+/// next_back() repeatedly calls [`OrcDeserialize::read_from_vector_batch`] and
+/// panics when it returns a [`DeserializationError`], unless configured otherwise
+/// with [`MultiFileRowIterator::on_error`].
+impl<T: OrcDeserialize + Default> DoubleEndedIterator for MultiFileRowIterator<T> {
+    fn next_back(&mut self) -> Option<T> {
+        while self.back > 0 {
+            if let Some(row) = self.iterators[self.back - 1].next_back() {
+                self.front = self.back - 1;
+                return Some(row);
+            }
+            self.back -= 1;
+        }
+        None
+    }
+}
+
+/// Like [`RowIterator`], this is the number of rows [`Iterator::next`] would yield
+impl<T: OrcDeserialize + Default> ExactSizeIterator for MultiFileRowIterator<T> {
+    fn len(&self) -> usize {
+        self.iterators[self.front..]
+            .iter()
+            .map(ExactSizeIterator::len)
+            .sum()
+    }
+}
@@ -8,23 +8,47 @@
 //! Iterator items need to implement [`OrcDeserialize`] trait; `orcxx_derive` can
 //! generate implementations for structures.
 //!
+//! [`ParallelRowIterator`] panics on deserialization errors, which aborts the whole
+//! iteration; use [`try_par_rows`] (or [`ParallelRowIterator::try_rows`]) to get
+//! them as [`Result`]s instead.
+//!
+//! Rayon splits the rows into ranges, which are each read by seeking a
+//! [`RowReader`] to their first row. Row readers are reused across ranges, but
+//! seeking still decodes the beginning of the row group, so ranges are never
+//! shorter than [`ParallelRowIterator::min_split_len`] rows.
+//!
 //! TODO: write a test for this after we add the write API to vector batches
 //! (currently it's only indirectly tested in `orcxx_derive`), because all the test
 //! files have a structure at the root and we can't use `#[derive(OrcDeserialize)]`
 //! in this crate to implement it.
 
-use deserialize::{CheckableKind, OrcDeserialize, OrcStruct};
+use deserialize::{CheckableKind, DeserializationError, OrcDeserialize, OrcStruct};
 use errors::OpenOrcError;
-use reader::{Reader, RowReaderOptions};
+use reader::{Reader, RowReader, RowReaderOptions};
 use std::convert::TryInto;
 use std::marker::PhantomData;
 use std::num::NonZeroU64;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use rayon::iter::plumbing::{bridge, Consumer, Producer, ProducerCallback};
 use rayon::prelude::*;
 
-use row_iterator::RowIterator;
+use row_iterator::{RowIterator, TryRowIterator};
+
+/// Returns a parallel iterator on rows of the given [`Reader`], which yields
+/// deserialization errors instead of panicking.
+///
+/// This is equivalent to `ParallelRowIterator::new(reader, batch_size)?.try_rows()`.
+///
+/// # Panics
+///
+/// When `batch_size` is larger than `usize`.
+pub fn try_par_rows<T: OrcDeserialize + OrcStruct + CheckableKind + Default>(
+    reader: Arc<Reader>,
+    batch_size: NonZeroU64,
+) -> Result<TryParallelRowIterator<T>, OpenOrcError> {
+    Ok(ParallelRowIterator::new(reader, batch_size)?.try_rows())
+}
 
 /// Parallel iterator on rows of the given [`Reader`].
 ///
@@ -36,20 +60,24 @@ use row_iterator::RowIterator;
 ///
 /// next() repeatedly calls [`OrcDeserialize::read_from_vector_batch`] and panics
 /// when it returns a [`::deserialize::DeserializationError`].
-pub struct ParallelRowIterator<T: OrcDeserialize + Clone> {
+pub struct ParallelRowIterator<T: OrcDeserialize + Default> {
     reader: Arc<Reader>,
     row_reader_options: RowReaderOptions,
+    /// Row readers of ranges which were fully read, to be reused by the next ones
+    row_readers: Mutex<Vec<RowReader>>,
     batch_size: NonZeroU64,
+    min_split_len: usize,
     start: usize,
     end: usize,
     marker: PhantomData<T>,
 }
 
-impl<T: OrcDeserialize + OrcStruct + CheckableKind + Clone> ParallelRowIterator<T> {
+impl<T: OrcDeserialize + OrcStruct + CheckableKind + Default> ParallelRowIterator<T> {
     /// Returns a parallel iterator on rows of the given [`Reader`].
     ///
     /// This calls [`ParallelRowIterator::new_with_options`] with default options and
-    /// includes only the needed columns (see [`RowReaderOptions::include_names`]).
+    /// includes only the needed columns which exist in the file (see
+    /// [`OrcStruct::columns_in`]).
     ///
     /// Errors are either detailed descriptions of format mismatch (as returned by
     /// [`CheckableKind::check_kind`], or C++ exceptions.
@@ -61,12 +89,12 @@ impl<T: OrcDeserialize + OrcStruct + CheckableKind + Clone> ParallelRowIterator<
         reader: Arc<Reader>,
         batch_size: NonZeroU64,
     ) -> Result<ParallelRowIterator<T>, OpenOrcError> {
-        let options = RowReaderOptions::default().include_names(T::columns());
+        let options = RowReaderOptions::default().include_names(T::columns_in(&reader.kind()));
         Self::new_with_options(reader, batch_size, options)
     }
 }
 
-impl<T: OrcDeserialize + Clone> ParallelRowIterator<T> {
+impl<T: OrcDeserialize + Default> ParallelRowIterator<T> {
     /// Returns a parallel iterator on rows of the given [`Reader`].
     ///
     /// Errors are detailed descriptions of format mismatch (as returned by
@@ -95,15 +123,67 @@ impl<T: OrcDeserialize + Clone> ParallelRowIterator<T> {
         Ok(ParallelRowIterator {
             reader,
             row_reader_options: options,
+            row_readers: Mutex::new(vec![row_reader]),
             batch_size,
+            min_split_len: u64::from(batch_size)
+                .try_into()
+                .expect("batch_size overflows usize"),
             start: 0,
             end: row_count,
             marker: PhantomData,
         })
     }
+
+    /// Sets the minimum number of rows read by each task. Defaults to the batch
+    /// size.
+    ///
+    /// Each task seeks to its first row, which decodes and discards rows from the
+    /// start of its row group (see [`RowReader::row_group_start`]); so lower values
+    /// allow finer load balancing, but decode more rows in total.
+    pub fn min_split_len(mut self, min_split_len: usize) -> Self {
+        self.min_split_len = min_split_len;
+        self
+    }
+
+    /// Returns a parallel iterator which yields deserialization errors instead of
+    /// panicking, in place of the rows which caused them.
+    pub fn try_rows(self) -> TryParallelRowIterator<T> {
+        TryParallelRowIterator(self)
+    }
+
+    /// Returns a [`RowIterator`] on rows `start..end`, reusing a row reader of a
+    /// previous range if any
+    fn row_iterator(&self, start: usize, end: usize) -> RowIterator<T> {
+        let start = start.try_into().expect("RowProducer::start overflows u64");
+        let end = end.try_into().expect("RowProducer::end overflows u64");
+        let row_reader = self
+            .row_readers
+            .lock()
+            .expect("Could not lock row reader pool")
+            .pop();
+        let row_reader = match row_reader {
+            Some(row_reader) => row_reader,
+            None => self
+                .reader
+                .row_reader(&self.row_reader_options)
+                .expect("Could not create RowReader"), // Should be fine, was checked before
+        };
+        RowIterator::from_row_reader(row_reader, self.batch_size)
+            .expect("Could not create RowIterator") // Ditto
+            .range(start..end)
+    }
+
+    /// Makes a row reader available to the next calls to
+    /// [`ParallelRowIterator::row_iterator`]
+    fn release_row_reader(&self, row_reader: RowReader) {
+        self.row_readers
+            .lock()
+            .expect("Could not lock row reader pool")
+            .push(row_reader);
+    }
 }
 
-impl<T: OrcDeserialize + Clone + Send + Sync> ParallelIterator for ParallelRowIterator<T> {
+impl<T: OrcDeserialize + Default + Send + Sync> ParallelIterator for ParallelRowIterator<T> {
     type Item = T;
 
     fn drive_unindexed<C: rayon::iter::plumbing::UnindexedConsumer<Self::Item>>(
@@ -118,7 +198,7 @@ impl<T: OrcDeserialize + Clone + Send + Sync> ParallelIterator for ParallelRowIt
     }
 }
 
-impl<T: OrcDeserialize + Clone + Send + Sync> IndexedParallelIterator for ParallelRowIterator<T> {
+impl<T: OrcDeserialize + Default + Send + Sync> IndexedParallelIterator for ParallelRowIterator<T> {
     fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
         callback.callback(RowProducer {
             iter: &self,
@@ -136,32 +216,29 @@ impl<T: OrcDeserialize + Clone + Send + Sync> IndexedParallelIterator for Parall
     }
 }
 
-struct RowProducer<'a, T: OrcDeserialize + Clone + Send + Sync> {
+struct RowProducer<'a, T: OrcDeserialize + Default + Send + Sync> {
     iter: &'a ParallelRowIterator<T>,
     start: usize,
     end: usize,
 }
 
-impl<T: OrcDeserialize + Clone + Send + Sync> Producer for RowProducer<'_, T> {
+impl<'a, T: OrcDeserialize + Default + Send + Sync> Producer for RowProducer<'a, T> {
     type Item = T;
-    type IntoIter = std::iter::Take<RowIterator<T>>;
+    type IntoIter = std::iter::Take<PooledRowIterator<'a, T, RowIterator<T>>>;
 
     fn into_iter(self) -> Self::IntoIter {
         assert!(self.start <= self.end);
-        let start = self
-            .start
-            .try_into()
-            .expect("RowProducer::start overflows u64");
-        RowIterator::new_with_options(
-            &self.iter.reader,
-            self.iter.batch_size,
-            &self.iter.row_reader_options,
-        )
-        .expect("Could not create RowIterator") // Should be fine, was checked before
-        .seek(start)
+        PooledRowIterator {
+            iter: self.iter,
+            rows: Some(self.iter.row_iterator(self.start, self.end)),
+        }
         .take(self.end - self.start) // TODO: tune the RowProducer buffer accordingly?
     }
 
+    fn min_len(&self) -> usize {
+        self.iter.min_split_len
+    }
+
     fn split_at(self, index: usize) -> (Self, Self) {
         (
             RowProducer {
@@ -177,3 +254,133 @@ impl<T: OrcDeserialize + Clone + Send + Sync> Producer for RowProducer<'_, T> {
         )
     }
 }
+
+/// Parallel iterator on rows of the given [`Reader`], which yields
+/// deserialization errors instead of panicking.
+///
+/// It is constructed through [`try_par_rows`] or [`ParallelRowIterator::try_rows`].
+pub struct TryParallelRowIterator<T: OrcDeserialize + Default>(ParallelRowIterator<T>);
+
+impl<T: OrcDeserialize + Default + Send + Sync> ParallelIterator for TryParallelRowIterator<T> {
+    type Item = Result<T, DeserializationError>;
+
+    fn drive_unindexed<C: rayon::iter::plumbing::UnindexedConsumer<Self::Item>>(
+        self,
+        consumer: C,
+    ) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        self.0.opt_len()
+    }
+}
+
+impl<T: OrcDeserialize + Default + Send + Sync> IndexedParallelIterator
+    for TryParallelRowIterator<T>
+{
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        callback.callback(TryRowProducer(RowProducer {
+            iter: &self.0,
+            start: self.0.start,
+            end: self.0.end,
+        }))
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+struct TryRowProducer<'a, T: OrcDeserialize + Default + Send + Sync>(RowProducer<'a, T>);
+
+impl<'a, T: OrcDeserialize + Default + Send + Sync> Producer for TryRowProducer<'a, T> {
+    type Item = Result<T, DeserializationError>;
+    type IntoIter = std::iter::Take<PooledRowIterator<'a, T, TryRowIterator<T>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let RowProducer { iter, start, end } = self.0;
+        assert!(start <= end);
+        PooledRowIterator {
+            iter,
+            rows: Some(iter.row_iterator(start, end).try_rows()),
+        }
+        .take(end - start)
+    }
+
+    fn min_len(&self) -> usize {
+        self.0.min_len()
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        let (left, right) = self.0.split_at(index);
+        (TryRowProducer(left), TryRowProducer(right))
+    }
+}
+
+/// Iterators which can give back their [`RowReader`] once done
+trait IntoRowReader {
+    fn into_row_reader(self) -> RowReader;
+}
+
+impl<T: OrcDeserialize + Default> IntoRowReader for RowIterator<T> {
+    fn into_row_reader(self) -> RowReader {
+        RowIterator::into_row_reader(self)
+    }
+}
+
+impl<T: OrcDeserialize + Default> IntoRowReader for TryRowIterator<T> {
+    fn into_row_reader(self) -> RowReader {
+        TryRowIterator::into_row_reader(self)
+    }
+}
+
+/// Wrapper for [`RowIterator`] and [`TryRowIterator`], which gives their
+/// [`RowReader`] back to the [`ParallelRowIterator`] when dropped
+struct PooledRowIterator<'a, T: OrcDeserialize + Default, I: IntoRowReader> {
+    iter: &'a ParallelRowIterator<T>,
+    /// Always `Some`, until dropped
+    rows: Option<I>,
+}
+
+impl<T: OrcDeserialize + Default, I: IntoRowReader + Iterator> Iterator
+    for PooledRowIterator<'_, T, I>
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        self.rows.as_mut().expect("Missing rows").next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.as_ref().expect("Missing rows").size_hint()
+    }
+}
+
+impl<T: OrcDeserialize + Default, I: IntoRowReader + DoubleEndedIterator> DoubleEndedIterator
+    for PooledRowIterator<'_, T, I>
+{
+    fn next_back(&mut self) -> Option<I::Item> {
+        self.rows.as_mut().expect("Missing rows").next_back()
+    }
+}
+
+impl<T: OrcDeserialize + Default, I: IntoRowReader + ExactSizeIterator> ExactSizeIterator
+    for PooledRowIterator<'_, T, I>
+{
+    fn len(&self) -> usize {
+        self.rows.as_ref().expect("Missing rows").len()
+    }
+}
+
+impl<T: OrcDeserialize + Default, I: IntoRowReader> Drop for PooledRowIterator<'_, T, I> {
+    fn drop(&mut self) {
+        if let Some(rows) = self.rows.take() {
+            self.iter.release_row_reader(rows.into_row_reader());
+        }
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Rayon-powered Iterator on ORC stripes.
+//!
+//! [`ParStripeIterator`] yields the rows of each stripe as a [`Vec`], and gives whole
+//! stripes to workers, which read them from start to end with
+//! [`Reader::stripe_row_reader`]. This is usually more efficient than
+//! [`ParallelRowIterator`](::parallel_row_iterator::ParallelRowIterator), which
+//! splits the file at arbitrary rows, so each worker seeks to its first row and
+//! decodes again the start of its row group.
+//!
+//! [`par_iter_orc_dir`] does the same on all ORC files in a directory, so workers
+//! are given stripes of every file.
+//!
+//! Iterator items need to implement [`OrcDeserialize`] trait; `orcxx_derive` can
+//! generate implementations for structures.
+
+use std::convert::TryInto;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::marker::PhantomData;
+use std::num::NonZeroU64;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rayon::iter::plumbing::{bridge, Consumer, Producer, ProducerCallback};
+use rayon::iter::Either;
+use rayon::prelude::*;
+
+use deserialize::{CheckableKind, OrcDeserialize, OrcStruct};
+use errors::OpenOrcError;
+use reader::{InputStream, Reader, RowReaderOptions, StripeInformation};
+
+/// Returns a parallel iterator on rows of all files with the `.orc` extension in
+/// `dir` and its subdirectories.
+///
+/// Files are ordered by path, and each is read with a [`ParStripeIterator`], so
+/// rows are yielded in order (when collected), and workers are given stripes of
+/// all files.
+///
+/// The directory is listed before returning, which fails if it cannot be read.
+/// Files are then opened lazily by workers, so only files being read are open
+/// at any given time; when a file cannot be opened, or when its schema does not
+/// match `T`, its rows are replaced by a single error item. These errors are
+/// either failures to open files, detailed descriptions of format mismatch (as
+/// returned by [`CheckableKind::check_kind`], and prefixed with the path of the
+/// file), or C++ exceptions.
+///
+/// # Panics
+///
+/// When `batch_size` is larger than `usize`.
+pub fn par_iter_orc_dir<T>(
+    dir: &Path,
+    batch_size: NonZeroU64,
+) -> Result<impl ParallelIterator<Item = Result<T, OpenOrcError>>, OpenOrcError>
+where
+    T: OrcDeserialize + OrcStruct + CheckableKind + Default + Send + Sync,
+{
+    iter_dir(dir, move |reader| {
+        ParStripeIterator::new(reader, batch_size)
+    })
+}
+
+/// Same as [`par_iter_orc_dir`], but reads the columns selected by `options` in each
+/// file.
+///
+/// # Panics
+///
+/// When `batch_size` is larger than `usize`.
+pub fn par_iter_orc_dir_with_options<T>(
+    dir: &Path,
+    batch_size: NonZeroU64,
+    options: &RowReaderOptions,
+) -> Result<impl ParallelIterator<Item = Result<T, OpenOrcError>>, OpenOrcError>
+where
+    T: OrcDeserialize + Default + Send + Sync,
+{
+    let options = options.clone();
+    iter_dir(dir, move |reader| {
+        ParStripeIterator::new_with_options(reader, batch_size, options.clone())
+    })
+}
+
+/// Lists ORC files in `dir`, and returns the rows of all their stripes, in order.
+///
+/// Each file is opened by the worker which first reads it, and passed to
+/// `new_iterator`.
+fn iter_dir<T, F>(
+    dir: &Path,
+    new_iterator: F,
+) -> Result<impl ParallelIterator<Item = Result<T, OpenOrcError>>, OpenOrcError>
+where
+    T: OrcDeserialize + Default + Send + Sync,
+    F: Fn(Arc<Reader>) -> Result<ParStripeIterator<T>, OpenOrcError> + Send + Sync,
+{
+    let files = orc_files_in_dir(dir).map_err(OpenOrcError::IoError)?;
+    Ok(files
+        .into_par_iter()
+        .flat_map(move |path| match open_file(&path, &new_iterator) {
+            Ok(iterator) => Either::Left(iterator.flat_map_iter(|rows| rows.into_iter().map(Ok))),
+            Err(e) => Either::Right(rayon::iter::once(Err(e))),
+        }))
+}
+
+/// Opens the ORC file at `path` and calls `new_iterator` on it
+fn open_file<T, F>(path: &Path, new_iterator: &F) -> Result<ParStripeIterator<T>, OpenOrcError>
+where
+    T: OrcDeserialize + Default,
+    F: Fn(Arc<Reader>) -> Result<ParStripeIterator<T>, OpenOrcError>,
+{
+    let path_str = path.to_str().ok_or_else(|| {
+        OpenOrcError::IoError(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not valid UTF-8", path.display()),
+        ))
+    })?;
+    let input_stream = InputStream::from_local_file(path_str).map_err(OpenOrcError::OrcError)?;
+    let reader = Reader::new(input_stream).map_err(OpenOrcError::ReaderError)?;
+    new_iterator(Arc::new(reader)).map_err(|e| match e {
+        OpenOrcError::KindError(msg) => {
+            OpenOrcError::KindError(format!("{} cannot be read: {}", path_str, msg))
+        }
+        e => e,
+    })
+}
+
+/// Returns the paths of files with the `.orc` extension in `dir` and its
+/// subdirectories, sorted. Symbolic links to directories are not followed.
+fn orc_files_in_dir(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                dirs.push(path);
+            } else if path.extension() == Some(OsStr::new("orc")) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Parallel iterator on stripes of the given [`Reader`], which yields the rows of
+/// each stripe, in order.
+///
+/// # Panics
+///
+/// Workers call [`OrcDeserialize::read_from_vector_batch_with_kind`] and panic when
+/// it returns a [`::deserialize::DeserializationError`].
+pub struct ParStripeIterator<T: OrcDeserialize + Default> {
+    reader: Arc<Reader>,
+    row_reader_options: RowReaderOptions,
+    batch_size: NonZeroU64,
+    stripes: Range<usize>,
+    /// Information on all stripes of the file, read once instead of for each stripe
+    stripe_informations: Vec<StripeInformation>,
+    marker: PhantomData<T>,
+}
+
+impl<T: OrcDeserialize + OrcStruct + CheckableKind + Default> ParStripeIterator<T> {
+    /// Returns a parallel iterator on stripes of the given [`Reader`].
+    ///
+    /// This calls [`ParStripeIterator::new_with_options`] with default options and
+    /// includes only the needed columns which exist in the file (see
+    /// [`OrcStruct::columns_in`]).
+    ///
+    /// Errors are either detailed descriptions of format mismatch (as returned by
+    /// [`CheckableKind::check_kind`], or C++ exceptions.
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is larger than `usize`.
+    pub fn new(
+        reader: Arc<Reader>,
+        batch_size: NonZeroU64,
+    ) -> Result<ParStripeIterator<T>, OpenOrcError> {
+        let options = RowReaderOptions::default().include_names(T::columns_in(&reader.kind()));
+        Self::new_with_options(reader, batch_size, options)
+    }
+}
+
+impl<T: OrcDeserialize + Default> ParStripeIterator<T> {
+    /// Returns a parallel iterator on stripes of the given [`Reader`].
+    ///
+    /// Any [`RowReaderOptions::range`] set in `options` is ignored, as each stripe
+    /// is read with [`Reader::stripe_row_reader`].
+    ///
+    /// Errors are either detailed descriptions of format mismatch (as returned by
+    /// [`CheckableKind::check_kind`], or C++ exceptions.
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is larger than `usize`.
+    pub fn new_with_options(
+        reader: Arc<Reader>,
+        batch_size: NonZeroU64,
+        options: RowReaderOptions,
+    ) -> Result<ParStripeIterator<T>, OpenOrcError> {
+        let row_reader = reader
+            .row_reader(&options)
+            .map_err(OpenOrcError::OrcError)?;
+        match T::check_kind(&row_reader.selected_kind()) {
+            Ok(_) => (),
+            Err(msg) => return Err(OpenOrcError::KindError(msg)),
+        }
+
+        let stripe_informations: Vec<_> = reader.stripes().collect();
+        Ok(ParStripeIterator {
+            reader,
+            row_reader_options: options,
+            batch_size,
+            stripes: 0..stripe_informations.len(),
+            stripe_informations,
+            marker: PhantomData,
+        })
+    }
+
+    /// Reads and deserializes all rows of the given stripe
+    fn read_stripe(&self, stripe_index: usize) -> Vec<T> {
+        let rows_count = self.stripe_informations[stripe_index]
+            .rows_count()
+            .try_into()
+            .expect("stripe row count overflows usize");
+        let stripe_index = stripe_index.try_into().expect("stripe index overflows u64");
+        let mut row_reader = self
+            .reader
+            .stripe_row_reader(stripe_index, &self.row_reader_options)
+            .expect("Could not create RowReader"); // Should be fine, was checked before
+        let kind = row_reader.selected_kind();
+        let batch_size = self.batch_size.into();
+        let mut batch = row_reader.row_batch(batch_size);
+
+        let batch_size = batch_size.try_into().expect("batch_size overflows usize");
+        let mut decoded_batch = Vec::with_capacity(batch_size);
+        let mut rows = Vec::with_capacity(rows_count);
+        while row_reader.read_into(&mut batch) {
+            decoded_batch.resize_with(batch_size, Default::default);
+            let decoded_items =
+                T::read_from_vector_batch_with_kind(&batch.borrow(), &kind, &mut decoded_batch)
+                    .unwrap_or_else(|e| {
+                        panic!(
+                            "OrcDeserialize::read_from_vector_batch() call from ParStripeIterator returns a deserialization error: {:?}",
+                            e
+                        )
+                    });
+            rows.extend(decoded_batch.drain(0..decoded_items));
+        }
+        rows
+    }
+}
+
+impl<T: OrcDeserialize + Default + Send + Sync> ParallelIterator for ParStripeIterator<T> {
+    type Item = Vec<T>;
+
+    fn drive_unindexed<C: rayon::iter::plumbing::UnindexedConsumer<Self::Item>>(
+        self,
+        consumer: C,
+    ) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.stripes.len())
+    }
+}
+
+impl<T: OrcDeserialize + Default + Send + Sync> IndexedParallelIterator for ParStripeIterator<T> {
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        callback.callback(StripeProducer {
+            iter: &self,
+            stripes: self.stripes.clone(),
+        })
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn len(&self) -> usize {
+        self.stripes.len()
+    }
+}
+
+struct StripeProducer<'a, T: OrcDeserialize + Default + Send + Sync> {
+    iter: &'a ParStripeIterator<T>,
+    stripes: Range<usize>,
+}
+
+impl<'a, T: OrcDeserialize + Default + Send + Sync> Producer for StripeProducer<'a, T> {
+    type Item = Vec<T>;
+    type IntoIter = StripeIterator<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        StripeIterator {
+            iter: self.iter,
+            stripes: self.stripes,
+        }
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        let middle = self.stripes.start + index;
+        assert!(middle <= self.stripes.end);
+        (
+            StripeProducer {
+                iter: self.iter,
+                stripes: self.stripes.start..middle,
+            },
+            StripeProducer {
+                iter: self.iter,
+                stripes: middle..self.stripes.end,
+            },
+        )
+    }
+}
+
+/// Sequential iterator on the stripes given to a worker, which reads each of them
+/// only when it is reached
+struct StripeIterator<'a, T: OrcDeserialize + Default> {
+    iter: &'a ParStripeIterator<T>,
+    stripes: Range<usize>,
+}
+
+impl<T: OrcDeserialize + Default> Iterator for StripeIterator<'_, T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        self.stripes
+            .next()
+            .map(|stripe_index| self.iter.read_stripe(stripe_index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stripes.size_hint()
+    }
+}
+
+impl<T: OrcDeserialize + Default> DoubleEndedIterator for StripeIterator<'_, T> {
+    fn next_back(&mut self) -> Option<Vec<T>> {
+        self.stripes
+            .next_back()
+            .map(|stripe_index| self.iter.read_stripe(stripe_index))
+    }
+}
+
+impl<T: OrcDeserialize + Default> ExactSizeIterator for StripeIterator<'_, T> {}
@@ -0,0 +1,246 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Splits the rows of ORC files into multiple line-separated JSON outputs
+//!
+//! Rows are dispatched according to the value of a top-level column, typically a
+//! low-cardinality one like a type or a date. Each batch is first split into one
+//! selection vector (ie. a list of row indices) per partition, so the output of a
+//! partition is only looked up once per batch rather than once per row.
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::*;
+//!
+//! let orc_path = "orc/examples/TestOrcFile.test1.orc";
+//! let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+//! let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let mut row_reader = reader.row_reader(&reader::RowReaderOptions::default()).unwrap();
+//!
+//! let partitions = partition::partition_to_ndjson(&mut row_reader, 1024, "string1", |key| {
+//!     println!("New partition: {:?}", key);
+//!     Ok(Vec::new())
+//! })
+//! .unwrap();
+//!
+//! for (key, ndjson) in partitions {
+//!     println!("{:?}: {}", key, String::from_utf8(ndjson).unwrap());
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io;
+use std::io::Write;
+
+use thiserror::Error;
+
+use kind::Kind;
+use reader::RowReader;
+use structured_reader::{ColumnTree, StructuredRowReader};
+use to_json::columntree_to_json_rows;
+
+#[derive(Error, Debug)]
+pub enum PartitionError {
+    /// The partition column is not a field of the row reader's selected columns
+    #[error("Unknown partition column: {0}")]
+    UnknownColumn(String),
+    /// The partition column is neither a boolean, an integer, a date, a string,
+    /// nor a binary
+    #[error("Cannot partition by column {column} of type {kind:?}")]
+    UnsupportedKind { column: String, kind: Kind },
+    #[error("Could not write partition: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Outputs of partitions, and the rows of the current batch selected for each
+struct Partitions<W, F> {
+    open: F,
+    /// Index of each non-null key in `outputs`
+    indexes: HashMap<String, usize>,
+    /// Index of the null key in `outputs`
+    null_index: Option<usize>,
+    outputs: Vec<(Option<String>, W)>,
+    /// Indices of the rows of the current batch in each partition
+    selections: Vec<Vec<usize>>,
+}
+
+impl<W, F> Partitions<W, F>
+where
+    F: FnMut(Option<&str>) -> io::Result<W>,
+{
+    /// Adds the next row of the current batch to the partition of `key`
+    fn select(&mut self, row: usize, key: Option<&str>) -> io::Result<()> {
+        let index = match key {
+            None => self.null_index,
+            Some(key) => self.indexes.get(key).copied(),
+        };
+        let index = match index {
+            Some(index) => index,
+            None => {
+                let index = self.outputs.len();
+                self.outputs
+                    .push((key.map(ToOwned::to_owned), (self.open)(key)?));
+                self.selections.push(Vec::new());
+                match key {
+                    None => self.null_index = Some(index),
+                    Some(key) => {
+                        self.indexes.insert(key.to_owned(), index);
+                    }
+                }
+                index
+            }
+        };
+        self.selections[index].push(row);
+        Ok(())
+    }
+}
+
+/// Yields the values of a field for each row of its struct, including `None` for
+/// rows where the struct itself is null.
+///
+/// Fields have a value for every row of their struct, including rows where it is
+/// null.
+fn expand_nulls<'a, V: 'a, I>(
+    not_null: Option<&'a [i8]>,
+    num_rows: u64,
+    mut values: I,
+) -> impl Iterator<Item = Option<V>> + 'a
+where
+    I: Iterator<Item = Option<V>> + 'a,
+{
+    (0..num_rows as usize).map(move |row| {
+        let value = values
+            .next()
+            .expect("Struct field has fewer values than its struct");
+        match not_null {
+            Some(not_null) if not_null[row] == 0 => None,
+            _ => value,
+        }
+    })
+}
+
+/// Reads all remaining rows from `row_reader`, `batch_size` at a time, and writes
+/// each of them as a line of JSON (see [`columntree_to_json_rows`]) to the output
+/// of its partition.
+///
+/// The partition of a row is the value of its `column` field, formatted as a string
+/// (`"true"`/`"false"` for booleans, days since the epoch for dates, and lossily
+/// decoded as UTF-8 for strings and binaries), or `None` if it is null.
+/// `open` is called to create the output of each partition when it is first seen.
+///
+/// Returns all partitions and their outputs, in the order they were first seen.
+pub fn partition_to_ndjson<W, F>(
+    row_reader: &mut RowReader,
+    batch_size: u64,
+    column: &str,
+    open: F,
+) -> Result<Vec<(Option<String>, W)>, PartitionError>
+where
+    W: Write,
+    F: FnMut(Option<&str>) -> io::Result<W>,
+{
+    let field_index = match row_reader.selected_kind() {
+        Kind::Struct(fields) => match fields.iter().position(|(name, _)| name == column) {
+            Some(index) => match &fields[index].1 {
+                Kind::Boolean
+                | Kind::Byte
+                | Kind::Short
+                | Kind::Int
+                | Kind::Long
+                | Kind::Date
+                | Kind::String
+                | Kind::Varchar(_)
+                | Kind::Char(_)
+                | Kind::Binary => index,
+                kind => {
+                    return Err(PartitionError::UnsupportedKind {
+                        column: column.to_owned(),
+                        kind: kind.clone(),
+                    })
+                }
+            },
+            None => return Err(PartitionError::UnknownColumn(column.to_owned())),
+        },
+        _ => return Err(PartitionError::UnknownColumn(column.to_owned())),
+    };
+
+    let mut partitions = Partitions {
+        open,
+        indexes: HashMap::new(),
+        null_index: None,
+        outputs: Vec::new(),
+        selections: Vec::new(),
+    };
+    let mut key = String::new();
+    let mut structured_row_reader = StructuredRowReader::new(row_reader, batch_size);
+    while let Some(tree) = structured_row_reader.next() {
+        for selection in &mut partitions.selections {
+            selection.clear();
+        }
+
+        {
+            let (not_null, num_rows, field) = match &tree {
+                ColumnTree::Struct {
+                    not_null,
+                    num_elements,
+                    elements,
+                } => (*not_null, *num_elements, &elements[field_index].1),
+                _ => panic!("Expected a struct at the root of the ColumnTree"),
+            };
+            match field {
+                ColumnTree::Boolean(column) => {
+                    for (row, value) in expand_nulls(not_null, num_rows, column.iter()).enumerate()
+                    {
+                        let value = value.map(|b| if b != 0 { "true" } else { "false" });
+                        partitions.select(row, value)?;
+                    }
+                }
+                ColumnTree::Byte(column)
+                | ColumnTree::Short(column)
+                | ColumnTree::Int(column)
+                | ColumnTree::Long(column)
+                | ColumnTree::Date(column) => {
+                    for (row, value) in expand_nulls(not_null, num_rows, column.iter()).enumerate()
+                    {
+                        match value {
+                            None => partitions.select(row, None)?,
+                            Some(value) => {
+                                key.clear();
+                                write!(key, "{}", value).expect("Could not format integer");
+                                partitions.select(row, Some(&key))?;
+                            }
+                        }
+                    }
+                }
+                ColumnTree::String(column) | ColumnTree::Binary(column) => {
+                    for (row, value) in expand_nulls(not_null, num_rows, column.iter()).enumerate()
+                    {
+                        let value = value.map(String::from_utf8_lossy);
+                        partitions.select(row, value.as_ref().map(Cow::as_ref))?;
+                    }
+                }
+                _ => unreachable!("Unexpected ColumnTree for a {:?} column", field),
+            }
+        }
+
+        let rows = columntree_to_json_rows(tree);
+        for ((_, output), selection) in partitions
+            .outputs
+            .iter_mut()
+            .zip(partitions.selections.iter())
+        {
+            for &row in selection {
+                rows[row].write(output)?;
+                output.write_all(b"\n")?;
+            }
+        }
+    }
+
+    Ok(partitions.outputs)
+}
@@ -5,11 +5,27 @@
 
 //! Low-level column-oriented parser for ORC files.
 
-use cxx::{let_cxx_string, UniquePtr};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::ops::Range;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
 
-use errors::{OrcError, OrcResult};
+use cxx::{let_cxx_string, SharedPtr, UniquePtr};
+
+use conformance;
+use deserialize::{CheckableKind, OrcDeserialize};
+use errors::{OpenOrcError, OrcError, OrcResult, OversizedValueError, ReadError, ReaderError};
 use kind;
+use row_index;
+use row_iterator::{ColumnIterator, DEFAULT_BATCH_SIZE};
+use statistics;
 use vector;
+use vector::ColumnVectorBatch;
+use writer::Compression;
 
 #[cxx::bridge]
 pub(crate) mod ffi {
@@ -29,6 +45,9 @@ pub(crate) mod ffi {
 
         #[rust_name = "StringList_new"]
         fn construct() -> UniquePtr<StringList>;
+
+        #[rust_name = "U64List_new"]
+        fn construct() -> UniquePtr<U64List>;
     }
 
     #[namespace = "orcxx_rs"]
@@ -38,6 +57,13 @@ pub(crate) mod ffi {
         fn push_back(self: Pin<&mut StringList>, value: &CxxString);
     }
 
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        type U64List;
+
+        fn push_back(self: Pin<&mut U64List>, value: &u64);
+    }
+
     // Reimport types from other modules
     #[namespace = "orc"]
     unsafe extern "C++" {
@@ -50,11 +76,81 @@ pub(crate) mod ffi {
         type InputStream;
         type ReaderOptions;
         type ReaderMetrics;
+        type MemoryPool;
 
         unsafe fn readLocalFile(
             path: &CxxString,
             metrics: *mut ReaderMetrics,
         ) -> Result<UniquePtr<InputStream>>;
+
+        fn getLength(self: &InputStream) -> u64;
+
+        fn setTailLocation(self: Pin<&mut ReaderOptions>, offset: u64) -> Pin<&mut ReaderOptions>;
+        fn setSerializedFileTail<'a>(
+            self: Pin<&'a mut ReaderOptions>,
+            serialization: &CxxString,
+        ) -> Pin<&'a mut ReaderOptions>;
+    }
+
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        #[rust_name = "MemoryPool_budgeted"]
+        fn budgetedMemoryPool(budget: u64) -> SharedPtr<MemoryPool>;
+
+        #[rust_name = "ReaderOptions_setMemoryPool"]
+        fn setMemoryPool(options: Pin<&mut ReaderOptions>, pool: &SharedPtr<MemoryPool>);
+
+        fn invalidArgument(message: &CxxString) -> Result<()>;
+
+        #[rust_name = "Reader_serializedFileTail"]
+        fn serializedFileTail(reader: &Reader) -> UniquePtr<CxxString>;
+
+        #[rust_name = "Reader_compressionKind"]
+        fn compressionKind(reader: &Reader) -> u8;
+
+        #[rust_name = "Reader_writerVersion"]
+        fn writerVersion(reader: &Reader) -> u32;
+
+        #[rust_name = "Reader_formatVersion"]
+        fn formatVersion(reader: &Reader, major: &mut u32, minor: &mut u32);
+
+        #[rust_name = "Reader_metadataKeys"]
+        fn metadataKeys(reader: &Reader) -> UniquePtr<CxxVector<CxxString>>;
+
+        #[rust_name = "Reader_metadataValue"]
+        fn metadataValue(reader: &Reader, key: &CxxString) -> Result<UniquePtr<CxxString>>;
+
+        #[rust_name = "Reader_memoryUse"]
+        fn memoryUse(reader: &Reader, options: &RowReaderOptions, stripeIndex: i32) -> Result<u64>;
+    }
+
+    #[namespace = "orcxx_rs"]
+    extern "Rust" {
+        type MemoryBuffer;
+        type ReadSource;
+
+        unsafe fn read_at(self: &ReadSource, buf: *mut u8, length: u64, offset: u64) -> Result<()>;
+    }
+
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        #[rust_name = "InputStream_from_memory"]
+        unsafe fn readMemory(
+            keepalive: Box<MemoryBuffer>,
+            data: *const u8,
+            length: u64,
+        ) -> UniquePtr<InputStream>;
+
+        #[rust_name = "InputStream_from_rust"]
+        fn readRust(source: Box<ReadSource>, length: u64) -> UniquePtr<InputStream>;
+
+        #[rust_name = "InputStream_read"]
+        unsafe fn readInputStream(
+            stream: Pin<&mut InputStream>,
+            buf: *mut u8,
+            length: u64,
+            offset: u64,
+        ) -> Result<()>;
     }
 
     #[namespace = "orc"]
@@ -66,6 +162,26 @@ pub(crate) mod ffi {
             self: Pin<&'a mut RowReaderOptions>,
             include: &StringList,
         ) -> Pin<&'a mut RowReaderOptions>;
+
+        #[rust_name = "include_indexes"]
+        fn include<'a>(
+            self: Pin<&'a mut RowReaderOptions>,
+            include: &U64List,
+        ) -> Pin<&'a mut RowReaderOptions>;
+
+        fn includeTypes<'a>(
+            self: Pin<&'a mut RowReaderOptions>,
+            types: &U64List,
+        ) -> Pin<&'a mut RowReaderOptions>;
+
+        fn range<'a>(
+            self: Pin<&'a mut RowReaderOptions>,
+            offset: u64,
+            length: u64,
+        ) -> Pin<&'a mut RowReaderOptions>;
+
+        fn getOffset(&self) -> u64;
+        fn getLength(&self) -> u64;
     }
 
     #[namespace = "orc"]
@@ -86,42 +202,291 @@ pub(crate) mod ffi {
 
         fn getNumberOfStripes(&self) -> u64;
         fn getStripe(&self, stripeIndex: u64) -> UniquePtr<StripeInformation>;
+        fn getStripeStatistics(&self, stripeIndex: u64) -> Result<UniquePtr<StripeStatistics>>;
+        fn getStatistics(&self) -> Result<UniquePtr<Statistics>>;
+        fn getRowIndexStride(&self) -> u64;
+        fn getCompressionSize(&self) -> u64;
+        fn getWriterIdValue(&self) -> u32;
     }
 
     #[namespace = "orc"]
     unsafe extern "C++" {
         type RowReader;
 
-        fn createRowBatch(&self, size: u64) -> UniquePtr<ColumnVectorBatch>;
+        fn createRowBatch(&self, size: u64) -> Result<UniquePtr<ColumnVectorBatch>>;
 
-        fn next(self: Pin<&mut RowReader>, data: Pin<&mut ColumnVectorBatch>) -> bool;
+        fn next(self: Pin<&mut RowReader>, data: Pin<&mut ColumnVectorBatch>) -> Result<bool>;
 
         fn getSelectedType(&self) -> &Type;
         fn getRowNumber(&self) -> u64;
         fn seekToRow(self: Pin<&mut RowReader>, rowNumber: u64);
     }
 
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        #[rust_name = "ColumnVectorBatch_limitValueLengths"]
+        fn limitValueLengths(
+            batch: Pin<&mut ColumnVectorBatch>,
+            maxLength: u64,
+            truncate: bool,
+        ) -> u64;
+    }
+
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        #[rust_name = "RowReader_selectedColumnIds"]
+        fn selectedColumnIds(row_reader: &RowReader) -> UniquePtr<CxxVector<u64>>;
+    }
+
     #[namespace = "orc"]
     unsafe extern "C++" {
         type StripeInformation;
 
+        fn getOffset(&self) -> u64;
         fn getLength(&self) -> u64;
         fn getNumberOfRows(&self) -> u64;
     }
+
+    #[namespace = "orc"]
+    unsafe extern "C++" {
+        type StripeStatistics;
+
+        fn getNumberOfColumns(&self) -> u32;
+        unsafe fn getColumnStatistics(&self, columnId: u32) -> *const ColumnStatistics;
+        fn getNumberOfRowIndexStats(&self, columnId: u32) -> u32;
+        unsafe fn getRowIndexStatistics(
+            &self,
+            columnId: u32,
+            rowIndex: u32,
+        ) -> *const ColumnStatistics;
+    }
+
+    #[namespace = "orc"]
+    unsafe extern "C++" {
+        type BloomFilterIndex;
+    }
+
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        #[rust_name = "Reader_bloomFilterIndex"]
+        fn bloomFilterIndex(
+            reader: &Reader,
+            stripeIndex: u32,
+            columnId: u32,
+        ) -> Result<UniquePtr<BloomFilterIndex>>;
+        #[rust_name = "BloomFilterIndex_count"]
+        fn bloomFilterCount(index: &BloomFilterIndex) -> u64;
+        #[rust_name = "BloomFilterIndex_testLong"]
+        fn bloomFilterTestLong(index: &BloomFilterIndex, rowGroup: u64, value: i64) -> bool;
+        #[rust_name = "BloomFilterIndex_testDouble"]
+        fn bloomFilterTestDouble(index: &BloomFilterIndex, rowGroup: u64, value: f64) -> bool;
+        #[rust_name = "BloomFilterIndex_testBytes"]
+        fn bloomFilterTestBytes(index: &BloomFilterIndex, rowGroup: u64, value: &[u8]) -> bool;
+    }
+
+    #[namespace = "orc"]
+    unsafe extern "C++" {
+        type Statistics;
+
+        fn getNumberOfColumns(&self) -> u32;
+        unsafe fn getColumnStatistics(&self, columnId: u32) -> *const ColumnStatistics;
+    }
+
+    #[namespace = "orc"]
+    unsafe extern "C++" {
+        type ColumnStatistics;
+
+        fn getNumberOfValues(&self) -> u64;
+        fn hasNull(&self) -> bool;
+    }
+
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        #[rust_name = "ColumnStatistics_boolean"]
+        fn booleanStatistics(
+            statistics: &ColumnStatistics,
+            hasCount: &mut bool,
+            falseCount: &mut u64,
+            trueCount: &mut u64,
+        ) -> bool;
+        #[rust_name = "ColumnStatistics_integer"]
+        fn integerStatistics(
+            statistics: &ColumnStatistics,
+            hasRange: &mut bool,
+            minimum: &mut i64,
+            maximum: &mut i64,
+            hasSum: &mut bool,
+            sum: &mut i64,
+        ) -> bool;
+        #[rust_name = "ColumnStatistics_double"]
+        fn doubleStatistics(
+            statistics: &ColumnStatistics,
+            hasRange: &mut bool,
+            minimum: &mut f64,
+            maximum: &mut f64,
+            hasSum: &mut bool,
+            sum: &mut f64,
+        ) -> bool;
+        #[rust_name = "ColumnStatistics_string"]
+        fn stringStatistics(
+            statistics: &ColumnStatistics,
+            hasRange: &mut bool,
+            minimum: Pin<&mut CxxString>,
+            maximum: Pin<&mut CxxString>,
+            hasTotalLength: &mut bool,
+            totalLength: &mut u64,
+        ) -> bool;
+        #[rust_name = "ColumnStatistics_binary"]
+        fn binaryStatistics(
+            statistics: &ColumnStatistics,
+            hasTotalLength: &mut bool,
+            totalLength: &mut u64,
+        ) -> bool;
+        #[rust_name = "ColumnStatistics_decimal"]
+        fn decimalStatistics(
+            statistics: &ColumnStatistics,
+            hasRange: &mut bool,
+            minimum: Pin<&mut CxxString>,
+            maximum: Pin<&mut CxxString>,
+            hasSum: &mut bool,
+            sum: Pin<&mut CxxString>,
+        ) -> bool;
+        #[rust_name = "ColumnStatistics_date"]
+        fn dateStatistics(
+            statistics: &ColumnStatistics,
+            hasRange: &mut bool,
+            minimum: &mut i32,
+            maximum: &mut i32,
+        ) -> bool;
+        #[rust_name = "ColumnStatistics_timestamp"]
+        fn timestampStatistics(
+            statistics: &ColumnStatistics,
+            hasRange: &mut bool,
+            minimumMillis: &mut i64,
+            minimumNanos: &mut i32,
+            maximumMillis: &mut i64,
+            maximumNanos: &mut i32,
+        ) -> bool;
+    }
 }
 
-/// Options passed to [Reader::new]
-pub struct ReaderOptions(UniquePtr<ffi::ReaderOptions>);
+/// Options passed to [Reader::new_with_options]
+pub struct ReaderOptions(
+    UniquePtr<ffi::ReaderOptions>,
+    // Referenced by the options, so it must live as long as them and as the
+    // readers they create
+    Option<SharedPtr<ffi::MemoryPool>>,
+);
 
 impl Default for ReaderOptions {
     fn default() -> ReaderOptions {
-        ReaderOptions(ffi::ReaderOptions_new())
+        ReaderOptions(ffi::ReaderOptions_new(), None)
+    }
+}
+
+impl ReaderOptions {
+    /// Limits the total size (in bytes) of the buffers allocated by the reader and
+    /// by the row readers it creates, including the batches they return. By default,
+    /// there is no limit.
+    ///
+    /// Allocations which would exceed the budget fail: [`Reader::new_with_options`],
+    /// [`Reader::row_reader`], [`RowReader::try_row_batch`], and
+    /// [`RowReader::try_read_into`] return an error (and [`RowReader::row_batch`]
+    /// and [`RowReader::read_into`] panic).
+    ///
+    /// Batches keep the budget's memory pool alive, so they can outlive the reader.
+    pub fn memory_budget(mut self, bytes: u64) -> ReaderOptions {
+        let pool = ffi::MemoryPool_budgeted(bytes);
+        ffi::ReaderOptions_setMemoryPool(self.0.pin_mut(), &pool);
+        self.1 = Some(pool);
+        self
+    }
+
+    /// Sets the position right after the last byte of the file's postscript, for
+    /// files which are followed by other data. By default, this is the end of the
+    /// file.
+    pub fn tail_location(mut self, offset: u64) -> ReaderOptions {
+        self.0.pin_mut().setTailLocation(offset);
+        self
+    }
+
+    /// Uses a file tail (footer, metadata, and postscript) returned by
+    /// [`Reader::serialized_file_tail`] instead of reading it from the file, to avoid
+    /// parsing it again when opening the same file repeatedly.
+    pub fn serialized_file_tail(mut self, tail: &[u8]) -> ReaderOptions {
+        let_cxx_string!(cxx_tail = tail);
+        self.0.pin_mut().setSerializedFileTail(&cxx_tail);
+        self
     }
 }
 
 unsafe impl Send for ReaderOptions {}
 unsafe impl Sync for ReaderOptions {}
 
+/// Bytes every ORC file starts with
+const ORC_MAGIC: &[u8; 3] = b"ORC";
+
+/// Buffer read by an [`InputStream`] built from memory, owned by the C++ stream
+/// so it outlives any [`Reader`] or [`RowReader`] using it.
+pub(crate) enum MemoryBuffer {
+    /// Memory owned by the caller, see [`InputStream::from_raw_parts`]
+    Borrowed,
+    Shared {
+        _buffer: Arc<[u8]>,
+    },
+    Owned {
+        _buffer: Vec<u8>,
+    },
+    /// See [`InputStream::from_bytes`]
+    Bytes {
+        _buffer: Box<dyn AsRef<[u8]> + Send>,
+    },
+}
+
+/// Implemented by all types accepted by [`InputStream::from_reader`]
+trait ReadSeek: Read + Seek + Send {}
+
+impl<R: Read + Seek + Send> ReadSeek for R {}
+
+/// Rust reader read by an [`InputStream`] built with [`InputStream::from_reader`],
+/// owned by the C++ stream.
+///
+/// It is behind a mutex because the stream may be shared by [`RowReader`]s running
+/// on different threads.
+pub(crate) struct ReadSource(Mutex<Box<dyn ReadSeek>>);
+
+impl ReadSource {
+    /// Called by the C++ stream to read `length` bytes at `offset` into `buf`
+    ///
+    /// # Safety
+    ///
+    /// `buf` must point to `length` writable bytes.
+    unsafe fn read_at(&self, buf: *mut u8, length: u64, offset: u64) -> std::io::Result<()> {
+        // cxx aborts on panics, so they are returned as errors instead, which cxx
+        // turns into C++ exceptions
+        let length = length.try_into().map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Cannot read {} bytes: length overflows usize", length),
+            )
+        })?;
+        let buf = std::slice::from_raw_parts_mut(buf, length);
+        std::panic::catch_unwind(AssertUnwindSafe(|| {
+            // The reader is always seeked before reading, so it is fine to keep using
+            // it after a panic
+            let mut reader = self.0.lock().unwrap_or_else(|e| e.into_inner());
+            reader.seek(SeekFrom::Start(offset))?;
+            reader.read_exact(buf)
+        }))
+        .unwrap_or_else(|_| {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "Reader panicked",
+            ))
+        })
+    }
+}
+
 /// Input for [Reader::new]
 pub struct InputStream(UniquePtr<ffi::InputStream>);
 
@@ -132,42 +497,512 @@ impl InputStream {
             .map(InputStream)
             .map_err(OrcError)
     }
+
+    /// Reads an ORC file from memory, without copying it first.
+    ///
+    /// The buffer is kept alive until all [`Reader`]s and [`RowReader`]s built
+    /// from this stream are dropped.
+    pub fn from_arc(buffer: Arc<[u8]>) -> InputStream {
+        let data = buffer.as_ptr();
+        let length = buffer.len() as u64;
+        // Safe because the buffer is owned by the C++ stream
+        InputStream(unsafe {
+            ffi::InputStream_from_memory(
+                Box::new(MemoryBuffer::Shared { _buffer: buffer }),
+                data,
+                length,
+            )
+        })
+    }
+
+    /// Reads an ORC file from memory, without copying it first, eg. a blob fetched
+    /// over HTTP or stored in a database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use orcxx::reader::{InputStream, Reader};
+    ///
+    /// let orc_file: Vec<u8> =
+    ///     std::fs::read("orc/examples/TestOrcFile.test1.orc").expect("Could not read .orc");
+    ///
+    /// let reader = Reader::new(InputStream::from_vec(orc_file)).expect("Could not read .orc");
+    /// assert_eq!(reader.row_count(), 2);
+    /// ```
+    pub fn from_vec(buffer: Vec<u8>) -> InputStream {
+        let data = buffer.as_ptr();
+        let length = buffer.len() as u64;
+        // Safe because the buffer is owned by the C++ stream, and moving a Vec does
+        // not move its content
+        InputStream(unsafe {
+            ffi::InputStream_from_memory(
+                Box::new(MemoryBuffer::Owned { _buffer: buffer }),
+                data,
+                length,
+            )
+        })
+    }
+
+    /// Reads an ORC file from a copy of the given memory.
+    ///
+    /// See [`InputStream::from_vec`], [`InputStream::from_arc`], and
+    /// [`InputStream::from_bytes`] to avoid the copy.
+    pub fn from_slice(bytes: &[u8]) -> InputStream {
+        InputStream::from_vec(bytes.to_vec())
+    }
+
+    /// Reads an ORC file from any owned or shared buffer, such as static memory
+    /// (eg. a file embedded in the binary with [`include_bytes!`]), a [`Vec<u8>`],
+    /// an [`Arc<[u8]>`](Arc), or a `bytes::Bytes`, without copying it first.
+    ///
+    /// The buffer is kept alive until all [`Reader`]s and [`RowReader`]s built
+    /// from this stream are dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use orcxx::reader::{InputStream, Reader};
+    ///
+    /// let orc_file: Vec<u8> =
+    ///     std::fs::read("orc/examples/TestOrcFile.test1.orc").expect("Could not read .orc");
+    ///
+    /// // Usually 'include_bytes!("path/to/file.orc")'
+    /// let static_orc_file: &'static [u8] = orc_file.clone().leak();
+    /// let reader = Reader::new(InputStream::from_bytes(static_orc_file))
+    ///     .expect("Could not read .orc");
+    /// assert_eq!(reader.row_count(), 2);
+    ///
+    /// let shared_orc_file: Arc<[u8]> = orc_file.clone().into();
+    /// let reader = Reader::new(InputStream::from_bytes(shared_orc_file))
+    ///     .expect("Could not read .orc");
+    /// assert_eq!(reader.row_count(), 2);
+    ///
+    /// let reader = Reader::new(InputStream::from_bytes(orc_file)).expect("Could not read .orc");
+    /// assert_eq!(reader.row_count(), 2);
+    /// ```
+    pub fn from_bytes<B: AsRef<[u8]> + Send + 'static>(bytes: B) -> InputStream {
+        // Boxed first, so the data does not move afterward even if it is stored
+        // inline (eg. in an array)
+        let buffer: Box<dyn AsRef<[u8]> + Send> = Box::new(bytes);
+        let data = (*buffer).as_ref();
+        let (data, length) = (data.as_ptr(), data.len() as u64);
+        // Safe because the buffer is owned by the C++ stream
+        InputStream(unsafe {
+            ffi::InputStream_from_memory(
+                Box::new(MemoryBuffer::Bytes { _buffer: buffer }),
+                data,
+                length,
+            )
+        })
+    }
+
+    /// Reads an ORC file from memory (eg. a shared memory region), without
+    /// copying it first.
+    ///
+    /// # Safety
+    ///
+    /// `data` must point to `length` readable bytes, which must not be modified
+    /// nor deallocated until all [`Reader`]s and [`RowReader`]s built from this
+    /// stream are dropped.
+    pub unsafe fn from_raw_parts(data: *const u8, length: usize) -> InputStream {
+        InputStream(ffi::InputStream_from_memory(
+            Box::new(MemoryBuffer::Borrowed),
+            data,
+            length as u64,
+        ))
+    }
+
+    /// Reads an ORC file from any seekable reader, such as a client of an object
+    /// store or a decrypting wrapper.
+    ///
+    /// The reader is kept alive until all [`Reader`]s and [`RowReader`]s built
+    /// from this stream are dropped. ORC files are read from the end, and columns
+    /// are read independently, so it should be cheap to seek.
+    ///
+    /// Returns an error if the reader cannot seek to its end, to get the file size.
+    /// Later errors and panics of the reader are returned as [`OrcError`]s by the
+    /// functions reading the file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use orcxx::reader::{InputStream, Reader};
+    ///
+    /// let file = std::fs::File::open("orc/examples/TestOrcFile.test1.orc")
+    ///     .expect("Could not open .orc");
+    /// let input_stream = InputStream::from_reader(std::io::BufReader::new(file))
+    ///     .expect("Could not get file size");
+    /// let reader = Reader::new(input_stream).expect("Could not read .orc");
+    /// assert_eq!(reader.row_count(), 2);
+    /// ```
+    pub fn from_reader<R: Read + Seek + Send + 'static>(
+        mut reader: R,
+    ) -> std::io::Result<InputStream> {
+        let length = reader.seek(SeekFrom::End(0))?;
+        Ok(InputStream(ffi::InputStream_from_rust(
+            Box::new(ReadSource(Mutex::new(Box::new(reader)))),
+            length,
+        )))
+    }
+
+    /// Returns whether the stream starts with the ORC magic bytes.
+    fn has_magic(&mut self) -> OrcResult<bool> {
+        let mut magic = [0u8; ORC_MAGIC.len()];
+        if self.0.getLength() < magic.len() as u64 {
+            return Ok(false);
+        }
+        // Safe because the buffer is exactly 'length' bytes long
+        unsafe {
+            ffi::InputStream_read(self.0.pin_mut(), magic.as_mut_ptr(), magic.len() as u64, 0)
+        }?;
+        Ok(&magic == ORC_MAGIC)
+    }
 }
 
 unsafe impl Send for InputStream {}
 
 /// Reads ORC file meta-data and constructs [`RowReader`]
-pub struct Reader(UniquePtr<ffi::Reader>);
+pub struct Reader {
+    inner: UniquePtr<ffi::Reader>,
+    /// Memory pool of the reader, see [`ReaderOptions::memory_budget`]. Declared
+    /// after the reader, so it is dropped after it.
+    memory_pool: Option<SharedPtr<ffi::MemoryPool>>,
+    /// Fingerprint of the file's schema, computed once as row readers need it too
+    schema_fingerprint: u64,
+}
 
 impl Reader {
-    pub fn new(input_stream: InputStream) -> OrcResult<Reader> {
+    /// Reads the file's meta-data.
+    ///
+    /// Returns [`ReaderError::NotAnOrcFile`] if the stream does not start with the
+    /// ORC magic bytes, and [`ReaderError::Corrupted`] if it does but its meta-data
+    /// cannot be parsed (eg. because the file is truncated).
+    pub fn new(input_stream: InputStream) -> Result<Reader, ReaderError> {
         Reader::new_with_options(input_stream, ReaderOptions::default())
     }
 
     pub fn new_with_options(
-        input_stream: InputStream,
+        mut input_stream: InputStream,
         options: ReaderOptions,
-    ) -> OrcResult<Reader> {
+    ) -> Result<Reader, ReaderError> {
+        if !input_stream.has_magic().map_err(ReaderError::OrcError)? {
+            return Err(ReaderError::NotAnOrcFile);
+        }
         ffi::createReader(input_stream.0, &options.0)
-            .map_err(OrcError)
-            .map(Reader)
+            .map_err(|e| ReaderError::Corrupted(OrcError(e)))
+            .map(|reader| {
+                let fingerprint = kind::Kind::new_from_orc_type(reader.getType()).fingerprint();
+                Reader {
+                    inner: reader,
+                    memory_pool: options.1,
+                    schema_fingerprint: fingerprint,
+                }
+            })
+    }
+
+    /// Returns the file's tail (footer, metadata, and postscript), serialized so it
+    /// can be cached and passed to [`ReaderOptions::serialized_file_tail`].
+    pub fn serialized_file_tail(&self) -> Vec<u8> {
+        ffi::Reader_serializedFileTail(&self.inner)
+            .as_bytes()
+            .to_vec()
+    }
+
+    /// Returns the compression codec of the file
+    pub fn compression(&self) -> Compression {
+        match ffi::Reader_compressionKind(&self.inner) {
+            0 => Compression::None,
+            1 => Compression::Zlib,
+            2 => Compression::Snappy,
+            3 => Compression::Lzo,
+            4 => Compression::Lz4,
+            5 => Compression::Zstd,
+            kind => panic!("Unknown compression kind: {}", kind),
+        }
+    }
+
+    /// Returns the size (in bytes) of the compression blocks of the file
+    pub fn compression_block_size(&self) -> u64 {
+        self.inner.getCompressionSize()
+    }
+
+    /// Returns the version of the ORC specification the file follows
+    pub fn format_version(&self) -> FormatVersion {
+        let (mut major, mut minor) = (0, 0);
+        ffi::Reader_formatVersion(&self.inner, &mut major, &mut minor);
+        FormatVersion { major, minor }
+    }
+
+    /// Returns the implementation that wrote the file
+    pub fn writer_id(&self) -> WriterId {
+        match self.inner.getWriterIdValue() {
+            0 => WriterId::OrcJava,
+            1 => WriterId::OrcCpp,
+            2 => WriterId::Presto,
+            3 => WriterId::ScritchleyGo,
+            4 => WriterId::Trino,
+            id => WriterId::Unknown(id),
+        }
+    }
+
+    /// Returns the version of the writer, which tells which of its bugs readers
+    /// need to work around
+    pub fn writer_version(&self) -> WriterVersion {
+        match ffi::Reader_writerVersion(&self.inner) {
+            0 => WriterVersion::Original,
+            1 => WriterVersion::Hive8732,
+            2 => WriterVersion::Hive4243,
+            3 => WriterVersion::Hive12055,
+            4 => WriterVersion::Hive13083,
+            5 => WriterVersion::Orc101,
+            6 => WriterVersion::Orc135,
+            7 => WriterVersion::Orc517,
+            8 => WriterVersion::Orc203,
+            9 => WriterVersion::Orc14,
+            version => WriterVersion::Unknown(version),
+        }
+    }
+
+    /// Returns the key-value pairs set by the writer of the file. Keys that are not
+    /// valid UTF-8 are decoded lossily.
+    pub fn user_metadata(&self) -> HashMap<String, Vec<u8>> {
+        ffi::Reader_metadataKeys(&self.inner)
+            .iter()
+            .map(|key| {
+                let value = ffi::Reader_metadataValue(&self.inner, key)
+                    .expect("Missing value for metadata key");
+                (
+                    key.to_string_lossy().into_owned(),
+                    value.as_bytes().to_vec(),
+                )
+            })
+            .collect()
     }
 
+    /// Returns a reader of the rows and columns selected by `options`
+    ///
+    /// Returns an error of kind [`InvalidArgument`](::errors::OrcErrorKind::InvalidArgument)
+    /// when a map column is selected without any of its key columns or without
+    /// any of its value columns, see [`RowReaderOptions::include_types`].
     pub fn row_reader(&self, options: &RowReaderOptions) -> OrcResult<RowReader> {
-        self.0
+        let mut row_reader = self
+            .inner
             .createRowReader(&options.0)
-            .map(RowReader)
-            .map_err(OrcError)
+            .map(|row_reader| RowReader {
+                inner: row_reader,
+                row_count: self.row_count(),
+                max_value_length: options.1,
+                row_groups: self.row_groups(),
+                last_batch_rows: 0,
+                memory_pool: self.memory_pool.clone(),
+                schema_fingerprint: self.schema_fingerprint(),
+                oversized_stripes: Vec::new(),
+            })
+            .map_err(OrcError)?;
+        let selected_ids = ffi::RowReader_selectedColumnIds(&row_reader.inner);
+        let selected_ids: HashSet<u64> = selected_ids.iter().copied().collect();
+        check_selected_maps(&self.kind(), &mut 0, &selected_ids)?;
+        if let Some((max_length, OversizedValuePolicy::Fail)) = options.1 {
+            row_reader.oversized_stripes =
+                self.oversized_stripes(options, max_length, &selected_ids);
+        }
+        Ok(row_reader)
+    }
+
+    /// Returns the rows of each stripe read with `options`, along with the id of a
+    /// selected string or binary column whose statistics in the stripe show it has
+    /// values longer than `max_length`, if any.
+    ///
+    /// Statistics only have the total length of values, so this only finds columns
+    /// whose values are longer than `max_length` on average.
+    fn oversized_stripes(
+        &self,
+        options: &RowReaderOptions,
+        max_length: u64,
+        selected_ids: &HashSet<u64>,
+    ) -> Vec<(Range<u64>, Option<u64>)> {
+        let mut string_ids = Vec::new();
+        push_string_column_ids(&self.kind(), &mut 0, &mut string_ids);
+        string_ids.retain(|id| selected_ids.contains(id));
+
+        let (offset, length) = (options.0.getOffset(), options.0.getLength());
+        self.stripes()
+            .enumerate()
+            // Same as the ORC library, which reads stripes starting in the range
+            .filter(|(_, stripe)| stripe.offset() >= offset && stripe.offset() - offset < length)
+            .map(|(stripe_index, stripe)| {
+                let rows = stripe.first_row()..stripe.first_row() + stripe.rows_count();
+                if string_ids.is_empty() {
+                    return (rows, None);
+                }
+                let statistics = self
+                    .stripe_statistics(stripe_index as u64)
+                    .unwrap_or_default();
+                let oversized_id = string_ids.iter().copied().find(|&id| {
+                    matches!(
+                        statistics.get(id as usize),
+                        Some(Some(summary)) if is_oversized(summary, max_length)
+                    )
+                });
+                (rows, oversized_id)
+            })
+            .collect()
+    }
+
+    /// Returns a reader of the rows of a single stripe, with the columns selected
+    /// by `options`.
+    ///
+    /// This replaces any [`RowReaderOptions::range`] set in `options` with the
+    /// bytes of the stripe, so each stripe can be read by a different thread or
+    /// machine. Row numbers (eg. returned by [`RowReader::get_row_number`]) are
+    /// still those of the file, starting at [`StripeInformation::first_row`].
+    ///
+    /// Returns an error of kind [`InvalidArgument`](::errors::OrcErrorKind::InvalidArgument)
+    /// if the file has no such stripe, and in the same cases as [`Reader::row_reader`].
+    pub fn stripe_row_reader(
+        &self,
+        stripe_index: u64,
+        options: &RowReaderOptions,
+    ) -> OrcResult<RowReader> {
+        let stripe = self.stripe_ffi(stripe_index)?;
+        self.row_reader(
+            &options
+                .clone()
+                .range(stripe.getOffset(), stripe.getLength()),
+        )
+    }
+
+    /// Returns the given stripe, or an error if the file has no such stripe
+    fn stripe_ffi(&self, stripe_index: u64) -> OrcResult<UniquePtr<ffi::StripeInformation>> {
+        self.check_stripe_index(stripe_index)?;
+        Ok(self.inner.getStripe(stripe_index))
+    }
+
+    /// Returns an error if the file has no such stripe
+    fn check_stripe_index(&self, stripe_index: u64) -> OrcResult<()> {
+        let stripes_count = self.inner.getNumberOfStripes();
+        if stripe_index >= stripes_count {
+            return Err(invalid_argument(&format!(
+                "Stripe {} out of range ({} stripes)",
+                stripe_index, stripes_count
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns an estimate of the memory (in bytes) the C++ library needs to read
+    /// the columns selected by `options`, in the stripe `stripe_index` or in the
+    /// largest stripe if `None`.
+    ///
+    /// This counts the buffers used to read and decompress streams, but not the
+    /// batches returned by [`RowReader::row_batch`], whose size depends on the data;
+    /// so it helps choose a batch size (and [`ReaderOptions::memory_budget`])
+    /// before reading files with large list or map columns.
+    ///
+    /// Returns an error of kind [`InvalidArgument`](::errors::OrcErrorKind::InvalidArgument)
+    /// if the file has no such stripe.
+    pub fn estimated_memory(
+        &self,
+        options: &RowReaderOptions,
+        stripe_index: Option<u64>,
+    ) -> OrcResult<u64> {
+        let stripe_index = match stripe_index {
+            None => -1,
+            Some(stripe_index) => {
+                self.check_stripe_index(stripe_index)?;
+                stripe_index.try_into().map_err(|_| {
+                    invalid_argument(&format!("Stripe {} does not fit in i32", stripe_index))
+                })?
+            }
+        };
+        ffi::Reader_memoryUse(&self.inner, &options.0, stripe_index).map_err(OrcError)
+    }
+
+    /// Returns an iterator on values of the top-level column `name`, deserialized
+    /// as `T`, without defining a structure for rows.
+    ///
+    /// Only this column is read; see [`ColumnIterator::new`] to choose the batch
+    /// size.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use orcxx::reader::{InputStream, Reader};
+    ///
+    /// let input_stream = InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+    ///     .expect("Could not open .orc");
+    /// let reader = Reader::new(input_stream).expect("Could not read .orc");
+    ///
+    /// let sum: i64 = reader
+    ///     .column_iter::<Option<i32>>("int1")
+    ///     .expect("Could not read int1")
+    ///     .flatten()
+    ///     .map(i64::from)
+    ///     .sum();
+    /// assert_eq!(sum, 131072);
+    /// ```
+    pub fn column_iter<T: OrcDeserialize + CheckableKind + Default>(
+        &self,
+        name: &str,
+    ) -> Result<ColumnIterator<T>, OpenOrcError> {
+        ColumnIterator::new(self, name, NonZeroU64::new(DEFAULT_BATCH_SIZE).unwrap())
+    }
+
+    /// Returns the id and path of every column in the file, in the same format as
+    /// [`RowReader::selected_columns`]
+    ///
+    /// This can be used to look up the ids to pass to
+    /// [`RowReaderOptions::include_types`].
+    pub fn columns(&self) -> Vec<(u64, String)> {
+        let mut paths = Vec::new();
+        push_column_paths(&self.kind(), String::new(), &mut paths);
+        // Column ids are assigned depth-first, parents first, starting from 0
+        (0..).zip(paths).collect()
     }
 
     /// Returns the data type of the file being read. This is usually a struct.
     pub fn kind(&self) -> kind::Kind {
-        kind::Kind::new_from_orc_type(self.0.getType())
+        kind::Kind::new_from_orc_type(self.inner.getType())
+    }
+
+    /// Returns the [`fingerprint`](kind::Kind::fingerprint) of the data type of
+    /// the file being read.
+    pub fn schema_fingerprint(&self) -> u64 {
+        self.schema_fingerprint
     }
 
     /// Returns an iterator of [`StripeInformation`]
     pub fn stripes(&self) -> impl Iterator<Item = StripeInformation> + '_ {
-        (0..self.0.getNumberOfStripes()).map(move |i| StripeInformation(self.0.getStripe(i)))
+        let mut first_row = 0;
+        (0..self.inner.getNumberOfStripes()).map(move |i| {
+            let stripe = StripeInformation(self.inner.getStripe(i), first_row);
+            first_row += stripe.rows_count();
+            stripe
+        })
+    }
+
+    /// Returns the [`StripeInformation`] of the given stripe, or `None` if the file
+    /// has no such stripe
+    ///
+    /// This reads all the previous stripes to compute
+    /// [`StripeInformation::first_row`]; use [`Reader::stripes`] to get many of them.
+    pub fn stripe(&self, stripe_index: u64) -> Option<StripeInformation> {
+        self.stripes().nth(stripe_index.try_into().ok()?)
+    }
+
+    /// Returns the number of rows between row index entries (usually 10000), or 0
+    /// if the file has no row indexes
+    pub fn row_index_stride(&self) -> u64 {
+        self.inner.getRowIndexStride()
+    }
+
+    fn row_groups(&self) -> RowGroups {
+        RowGroups {
+            stride: self.row_index_stride(),
+            stripe_starts: self.stripes().map(|stripe| stripe.first_row()).collect(),
+        }
     }
 
     /// Returns the total number of rows in the file
@@ -176,17 +1011,261 @@ impl Reader {
             .map(|stripe| stripe.rows_count())
             .sum::<u64>()
     }
+
+    /// Splits the file's rows into `n_shards` contiguous ranges, which start and end
+    /// on stripe boundaries and have row counts as balanced as stripes allow.
+    ///
+    /// Ranges are returned in order and cover all rows exactly once; some are empty
+    /// when the file has fewer stripes than `n_shards`. They can be read with
+    /// [`RowIterator::range`](::row_iterator::RowIterator::range), or
+    /// [`RowIterator::shards`](::row_iterator::RowIterator::shards) builds an
+    /// iterator for each of them.
+    pub fn shard(&self, n_shards: NonZeroUsize) -> Vec<Range<u64>> {
+        // Number of the first row of each stripe, and the total number of rows
+        let mut boundaries = vec![0];
+        for stripe in self.stripes() {
+            let last = *boundaries.last().unwrap();
+            boundaries.push(last + stripe.rows_count());
+        }
+        let row_count = u128::from(*boundaries.last().unwrap());
+        let n_shards = n_shards.get() as u128;
+
+        let mut start = 0;
+        (1..=n_shards)
+            .map(|i| {
+                // End the shard on the stripe boundary closest to its ideal end
+                let target = (row_count * i / n_shards) as u64;
+                let end = match boundaries.binary_search(&target) {
+                    Ok(j) => boundaries[j],
+                    Err(j) => {
+                        let (before, after) = (boundaries[j - 1], boundaries[j]);
+                        if target - before <= after - target {
+                            before
+                        } else {
+                            after
+                        }
+                    }
+                };
+                let range = start..end;
+                start = end;
+                range
+            })
+            .collect()
+    }
+
+    /// Checks the structural invariants of the file's data, and reports the
+    /// violations with their stripe and column.
+    ///
+    /// See [`conformance`] for the list of checks.
+    pub fn conformance_report(
+        &self,
+        options: &conformance::ConformanceOptions,
+    ) -> OrcResult<conformance::ConformanceReport> {
+        conformance::check(self, options)
+    }
+
+    /// Returns the statistics of each column (indexed by column id) over the whole
+    /// file; or `None` if the file has no statistics.
+    ///
+    /// Items are `None` for columns missing from the statistics.
+    pub fn statistics(&self) -> Option<Vec<Option<statistics::ColumnSummary>>> {
+        let statistics = self.inner.getStatistics().ok()?;
+        match statistics.getNumberOfColumns() {
+            0 => None,
+            num_columns => Some(
+                (0..num_columns)
+                    .map(|column_id| {
+                        // This should be safe because the pointer is either null or
+                        // owned by 'statistics'
+                        unsafe { statistics.getColumnStatistics(column_id).as_ref() }
+                            .map(statistics::ColumnSummary::new)
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns the statistics of each column (indexed by column id) in the given
+    /// stripe; or `None` if the file has no stripe statistics.
+    ///
+    /// Items are `None` for columns missing from the statistics.
+    pub fn stripe_statistics(
+        &self,
+        stripe_index: u64,
+    ) -> Option<Vec<Option<statistics::ColumnSummary>>> {
+        let statistics = self.inner.getStripeStatistics(stripe_index).ok()?;
+        Some(
+            (0..statistics.getNumberOfColumns())
+                .map(|column_id| {
+                    // This should be safe because the pointer is either null or owned
+                    // by 'statistics'
+                    unsafe { statistics.getColumnStatistics(column_id).as_ref() }
+                        .map(statistics::ColumnSummary::new)
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the statistics of a column in each row group of the given stripe
+    /// (see [`row_index`]); or `None` if the file has no stripe statistics or no
+    /// such column.
+    ///
+    /// The vector is empty if the file has no row indexes.
+    pub fn row_group_statistics(
+        &self,
+        stripe_index: u64,
+        column_id: u32,
+    ) -> Option<Vec<statistics::ColumnSummary>> {
+        let statistics = self.inner.getStripeStatistics(stripe_index).ok()?;
+        if column_id >= statistics.getNumberOfColumns() {
+            return None;
+        }
+        (0..statistics.getNumberOfRowIndexStats(column_id))
+            .map(|row_group| {
+                // This should be safe because the pointer is either null or owned
+                // by 'statistics'
+                unsafe {
+                    statistics
+                        .getRowIndexStatistics(column_id, row_group)
+                        .as_ref()
+                }
+                .map(statistics::ColumnSummary::new)
+            })
+            .collect()
+    }
+
+    /// Returns the bloom filters of a column in each row group of the given stripe
+    /// (see [`row_index`]); or `None` if the column has no bloom filters.
+    ///
+    /// Returns an error of kind [`InvalidArgument`](::errors::OrcErrorKind::InvalidArgument)
+    /// if the file has no such stripe.
+    pub fn bloom_filters(
+        &self,
+        stripe_index: u64,
+        column_id: u32,
+    ) -> OrcResult<Option<row_index::BloomFilters>> {
+        self.check_stripe_index(stripe_index)?;
+        let stripe_index = stripe_index.try_into().map_err(|_| {
+            invalid_argument(&format!("Stripe {} does not fit in u32", stripe_index))
+        })?;
+        let index =
+            ffi::Reader_bloomFilterIndex(&self.inner, stripe_index, column_id).map_err(OrcError)?;
+        if index.is_null() {
+            Ok(None)
+        } else {
+            Ok(Some(row_index::BloomFilters(index)))
+        }
+    }
+
+    /// Returns the number of non-null values of each column (indexed by column
+    /// id) in the given stripe, and whether it has nulls, according to the
+    /// stripe statistics; or `None` if the file has no stripe statistics.
+    pub(crate) fn stripe_value_counts(&self, stripe_index: u64) -> Option<Vec<(u64, bool)>> {
+        self.stripe_statistics(stripe_index)?
+            .into_iter()
+            .map(|column| column.map(|column| (column.number_of_values, column.has_null)))
+            .collect()
+    }
+
+    /// Returns whether each column (indexed by column id) may contain nulls,
+    /// according to the file statistics; or `None` if the file has no statistics.
+    ///
+    /// Columns missing from the statistics are assumed to contain nulls.
+    ///
+    /// `hasNull` is optional in the statistics, and reads as `false` when old writers
+    /// omit it. Therefore, it is only trusted for files written by writers at least
+    /// as recent as ORC-101; otherwise, only fields of structures with as many values
+    /// as their parent are assumed not to contain nulls.
+    pub fn column_has_nulls(&self) -> Option<Vec<bool>> {
+        let statistics = self.statistics()?;
+        let trust_has_null = !matches!(
+            self.writer_version(),
+            WriterVersion::Original
+                | WriterVersion::Hive8732
+                | WriterVersion::Hive4243
+                | WriterVersion::Hive12055
+                | WriterVersion::Hive13083
+        );
+        let mut has_nulls: Vec<bool> = statistics
+            .iter()
+            .map(|column| {
+                column
+                    .as_ref()
+                    .map(|column| column.has_null || !trust_has_null)
+                    .unwrap_or(true)
+            })
+            .collect();
+        count_missing_values(&self.kind(), &mut 0, None, &statistics, &mut has_nulls);
+        Some(has_nulls)
+    }
+}
+
+/// Sets `has_nulls[id]` for `kind` (whose column id is `id`) and its descendants,
+/// when the number of values of a structure's field can be compared to the number
+/// of values of the structure itself.
+fn count_missing_values(
+    kind: &kind::Kind,
+    id: &mut usize,
+    parent_values: Option<u64>,
+    statistics: &[Option<statistics::ColumnSummary>],
+    has_nulls: &mut [bool],
+) {
+    let column_id = *id;
+    *id += 1;
+    let values = statistics
+        .get(column_id)
+        .and_then(|column| column.as_ref())
+        .map(|column| column.number_of_values);
+    if let (Some(parent_values), Some(values)) = (parent_values, values) {
+        if let Some(has_nulls) = has_nulls.get_mut(column_id) {
+            *has_nulls = values < parent_values;
+        }
+    }
+    match kind {
+        kind::Kind::Struct(fields) => {
+            for (_, field) in fields {
+                count_missing_values(field, id, values, statistics, has_nulls);
+            }
+        }
+        kind::Kind::List(elements) => {
+            count_missing_values(elements, id, None, statistics, has_nulls);
+        }
+        kind::Kind::Map { key, value } => {
+            count_missing_values(key, id, None, statistics, has_nulls);
+            count_missing_values(value, id, None, statistics, has_nulls);
+        }
+        kind::Kind::Union(variants) => {
+            for variant in variants {
+                count_missing_values(variant, id, None, statistics, has_nulls);
+            }
+        }
+        _ => {}
+    }
 }
 
 unsafe impl Send for Reader {}
 unsafe impl Sync for Reader {}
 
+/// What a [`RowReader`] does with string and binary values longer than
+/// [`RowReaderOptions::max_value_length`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OversizedValuePolicy {
+    /// Shortens them to the maximum length. This may split UTF-8 characters.
+    Truncate,
+    /// Makes [`RowReader::try_read_into`] return an error (and other methods
+    /// reading rows panic)
+    Fail,
+}
+
 /// Options passed to [`Reader::row_reader`]
-pub struct RowReaderOptions(UniquePtr<ffi::RowReaderOptions>);
+pub struct RowReaderOptions(
+    UniquePtr<ffi::RowReaderOptions>,
+    Option<(u64, OversizedValuePolicy)>,
+);
 
 impl Default for RowReaderOptions {
     fn default() -> RowReaderOptions {
-        RowReaderOptions(ffi::RowReaderOptions_new())
+        RowReaderOptions(ffi::RowReaderOptions_new(), None)
     }
 }
 
@@ -207,32 +1286,236 @@ impl RowReaderOptions {
         self.0.pin_mut().include_names(&cxx_names);
         self
     }
+
+    /// For files that have structs as the top-level object, select the fields
+    /// to read by their position in the struct (starting from 0). By default, all
+    /// columns are read. This option clears any previous setting of the selected
+    /// columns.
+    ///
+    /// Unlike [`RowReaderOptions::include_names`], this works when the file has
+    /// several fields with the same name, or when their names are not known.
+    pub fn include_indexes<I>(mut self, indexes: I) -> RowReaderOptions
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        let mut cxx_indexes = ffi::U64List_new();
+        for index in indexes.into_iter() {
+            cxx_indexes.pin_mut().push_back(&index);
+        }
+        self.0.pin_mut().include_indexes(&cxx_indexes);
+        self
+    }
+
+    /// Selects the columns to read by id (see [`Reader::columns`]). Selecting a
+    /// column also selects all its descendants and its ancestors. This option
+    /// clears any previous setting of the selected columns.
+    ///
+    /// Unlike [`RowReaderOptions::include_names`], this can select only some fields
+    /// of structs nested in lists or maps: for example, selecting the ids of
+    /// `m._key` and `m._value.a` in a `m: map<string,struct<a:int,b:string>>` column
+    /// reads the keys and the `a` field of values, without decoding `b`.
+    ///
+    /// The underlying ORC library cannot read maps without their keys or without
+    /// their values, so at least one column must remain selected on both sides of
+    /// each selected map; [`Reader::row_reader`] returns an error otherwise.
+    pub fn include_types<I>(mut self, ids: I) -> RowReaderOptions
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        let mut cxx_ids = ffi::U64List_new();
+        for id in ids.into_iter() {
+            cxx_ids.pin_mut().push_back(&id);
+        }
+        self.0.pin_mut().includeTypes(&cxx_ids);
+        self
+    }
+
+    /// Only reads stripes which start in the given range of bytes of the file. By
+    /// default, the whole file is read.
+    ///
+    /// Ranges which are adjacent and do not overlap select disjoint sets of stripes,
+    /// so they can be given to different workers without knowing the stripe
+    /// boundaries (see [`Reader::stripe_row_reader`] otherwise).
+    pub fn range(mut self, offset: u64, length: u64) -> RowReaderOptions {
+        self.0.pin_mut().range(offset, length);
+        self
+    }
+
+    /// Limits the length (in bytes) of string and binary values, to protect
+    /// against pathological rows with huge values. By default, there is no limit.
+    ///
+    /// With [`OversizedValuePolicy::Fail`], stripes whose statistics show a column
+    /// has values longer than the limit on average are rejected before being
+    /// decoded (so the C++ library does not allocate memory for them), with
+    /// [`ReadError::OversizedStripe`]. Other values are checked after each batch is
+    /// decoded, which does not bound the memory used by the C++ library, but bounds
+    /// the memory used when converting values to Rust or JSON, which is usually much
+    /// larger.
+    pub fn max_value_length(
+        mut self,
+        max_length: u64,
+        policy: OversizedValuePolicy,
+    ) -> RowReaderOptions {
+        self.1 = Some((max_length, policy));
+        self
+    }
 }
 
 impl Clone for RowReaderOptions {
     fn clone(&self) -> RowReaderOptions {
-        RowReaderOptions(ffi::RowReaderOptions_copy(&self.0))
+        RowReaderOptions(ffi::RowReaderOptions_copy(&self.0), self.1)
     }
 }
 
 unsafe impl Send for RowReaderOptions {}
 unsafe impl Sync for RowReaderOptions {}
 
+/// Boundaries of row groups, which are the units row indexes point to
+struct RowGroups {
+    /// Number of rows in each row group but the last of each stripe; 0 if the file
+    /// has no row indexes
+    stride: u64,
+    /// Number of the first row of each stripe
+    stripe_starts: Vec<u64>,
+}
+
 /// Reads rows from ORC files to a raw [`vector::OwnedColumnVectorBatch`]
-pub struct RowReader(UniquePtr<ffi::RowReader>);
+pub struct RowReader {
+    inner: UniquePtr<ffi::RowReader>,
+    /// Total number of rows in the file, see [`Reader::row_count`]
+    row_count: u64,
+    /// See [`RowReaderOptions::max_value_length`]
+    max_value_length: Option<(u64, OversizedValuePolicy)>,
+    /// Used by [`RowReader::row_group_start`]
+    row_groups: RowGroups,
+    /// Number of rows read by the last call to [`RowReader::read_into`], or 0 after
+    /// a seek
+    last_batch_rows: u64,
+    /// Memory pool of the reader, see [`ReaderOptions::memory_budget`]. Kept alive
+    /// as long as the row reader and the batches it creates.
+    memory_pool: Option<SharedPtr<ffi::MemoryPool>>,
+    /// Fingerprint of the file's schema, see [`Reader::schema_fingerprint`]
+    schema_fingerprint: u64,
+    /// Rows of each stripe to read, and a column with values longer than
+    /// [`RowReaderOptions::max_value_length`] according to its statistics, if any.
+    /// Empty unless the policy is [`OversizedValuePolicy::Fail`].
+    oversized_stripes: Vec<(Range<u64>, Option<u64>)>,
+}
 
 impl RowReader {
     /// Creates a vector batch, to be passed to [`RowReader::read_into`]
     ///
     /// ``size`` is the number of rows to read at once.
+    ///
+    /// # Panics
+    ///
+    /// If the batch exceeds [`ReaderOptions::memory_budget`]; use
+    /// [`RowReader::try_row_batch`] to get an error instead.
     pub fn row_batch(&mut self, size: u64) -> vector::OwnedColumnVectorBatch {
-        vector::OwnedColumnVectorBatch(self.0.createRowBatch(size))
+        match self.try_row_batch(size) {
+            Ok(batch) => batch,
+            Err(e) => panic!("Could not create batch: {}", e),
+        }
+    }
+
+    /// Same as [`RowReader::row_batch`], but returns an error instead of panicking
+    /// if the batch exceeds [`ReaderOptions::memory_budget`]
+    pub fn try_row_batch(&mut self, size: u64) -> OrcResult<vector::OwnedColumnVectorBatch> {
+        self.inner
+            .createRowBatch(size)
+            .map(|batch| vector::OwnedColumnVectorBatch(batch, self.memory_pool.clone()))
+            .map_err(OrcError)
     }
 
     /// Read the next stripe into the batch, or returns false if there are no
     /// more stripes.
+    ///
+    /// # Panics
+    ///
+    /// If the batch has values longer than allowed by
+    /// [`RowReaderOptions::max_value_length`] with [`OversizedValuePolicy::Fail`],
+    /// or if the ORC library fails to decode it (eg. because the file is corrupted,
+    /// or decoding it exceeds [`ReaderOptions::memory_budget`]); use
+    /// [`RowReader::try_read_into`] to get an error instead.
     pub fn read_into(&mut self, batch: &mut vector::OwnedColumnVectorBatch) -> bool {
-        self.0.pin_mut().next(batch.0.pin_mut())
+        match self.try_read_into(batch) {
+            Ok(read) => read,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Same as [`RowReader::read_into`], but returns an error instead of panicking
+    /// if the batch has values longer than allowed by
+    /// [`RowReaderOptions::max_value_length`] with [`OversizedValuePolicy::Fail`],
+    /// or if the ORC library fails to decode it.
+    pub fn try_read_into(
+        &mut self,
+        batch: &mut vector::OwnedColumnVectorBatch,
+    ) -> Result<bool, ReadError> {
+        self.check_oversized_stripe()?;
+        match self.inner.pin_mut().next(batch.0.pin_mut()) {
+            Ok(true) => self.last_batch_rows = batch.borrow().num_elements(),
+            Ok(false) => {
+                self.last_batch_rows = 0;
+                return Ok(false);
+            }
+            Err(e) => return Err(ReadError::OrcError(OrcError(e))),
+        }
+        if let Some((max_length, policy)) = self.max_value_length {
+            let truncate = policy == OversizedValuePolicy::Truncate;
+            let count =
+                ffi::ColumnVectorBatch_limitValueLengths(batch.0.pin_mut(), max_length, truncate);
+            if count > 0 && !truncate {
+                return Err(ReadError::OversizedValue(OversizedValueError {
+                    first_row: self.get_row_number(),
+                    count,
+                    max_length,
+                }));
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns an error if statistics show the stripe of the next batch has values
+    /// longer than allowed by [`RowReaderOptions::max_value_length`], so it is
+    /// rejected before being decoded.
+    fn check_oversized_stripe(&self) -> Result<(), ReadError> {
+        let next_row = match self.get_row_number() {
+            // Nothing read yet, so reading starts at the first stripe
+            u64::MAX => match self.oversized_stripes.first() {
+                Some((rows, _)) => rows.start,
+                None => return Ok(()),
+            },
+            row_number => row_number + self.last_batch_rows,
+        };
+        let stripe = self
+            .oversized_stripes
+            .iter()
+            .find(|(rows, _)| rows.contains(&next_row));
+        match (self.max_value_length, stripe) {
+            (Some((max_length, _)), Some((rows, Some(column_id)))) => {
+                Err(ReadError::OversizedStripe {
+                    first_row: rows.start,
+                    column_id: *column_id,
+                    max_length,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Same as [`RowReader::read_into`], but returns the number of rows read into
+    /// the batch (which may be lower than its size, eg. at the end of stripes),
+    /// or `None` if there are no more stripes.
+    pub fn read_rows_into(
+        &mut self,
+        batch: &mut vector::OwnedColumnVectorBatch,
+    ) -> Option<NonZeroU64> {
+        if self.read_into(batch) {
+            NonZeroU64::new(batch.borrow().num_elements())
+        } else {
+            None
+        }
     }
 
     /// Returns the data type being read.
@@ -240,26 +1523,327 @@ impl RowReader {
     /// With the default [`RowReaderOptions`], this is the same as [`Reader::kind`].
     /// Otherwise this is usually a subset [`Reader::kind`].
     pub fn selected_kind(&self) -> kind::Kind {
-        kind::Kind::new_from_orc_type(self.0.getSelectedType())
+        kind::Kind::new_from_orc_type(self.inner.getSelectedType())
+    }
+
+    /// Returns the id and path of each selected column, in the order of
+    /// [`RowReader::selected_kind`] (depth-first, parents first).
+    ///
+    /// Column ids are those of the file, so they do not depend on which columns are
+    /// selected. Paths are made of field names, joined by `.`; list elements are
+    /// named `_elem`, map keys and values `_key` and `_value`, and union variants
+    /// by their index. The root column has id 0 and an empty path.
+    pub fn selected_columns(&self) -> Vec<(u64, String)> {
+        let ids = ffi::RowReader_selectedColumnIds(&self.inner);
+        let mut paths = Vec::new();
+        push_column_paths(&self.selected_kind(), String::new(), &mut paths);
+        assert_eq!(
+            ids.len(),
+            paths.len(),
+            "Number of selected columns does not match the selected type"
+        );
+        ids.iter().copied().zip(paths).collect()
+    }
+
+    /// Returns the selected columns as a tree mirroring [`RowReader::selected_kind`],
+    /// with the id of each column in the file.
+    ///
+    /// This can be given to
+    /// [`StructVectorBatch::named_fields`](vector::StructVectorBatch::named_fields) to
+    /// look up the fields of batches read by this row reader by name or column id.
+    pub fn selected_column_tree(&self) -> SelectedColumn {
+        let ids = ffi::RowReader_selectedColumnIds(&self.inner);
+        let mut ids = ids.iter().copied();
+        let column = SelectedColumn::new(self.selected_kind(), &mut ids);
+        assert!(
+            ids.next().is_none(),
+            "Number of selected columns does not match the selected type"
+        );
+        column
     }
 
     /// Get the row number of the first row in the previously read batch.
+    ///
+    /// This is `u64::MAX` before the first batch is read, and the given row after
+    /// [`RowReader::seek_to_row`].
     pub fn get_row_number(&self) -> u64 {
-        self.0.getRowNumber()
+        self.inner.getRowNumber()
+    }
+
+    /// Returns the [`fingerprint`](kind::Kind::fingerprint) of the schema of the
+    /// whole file (not only of the selected columns)
+    pub(crate) fn schema_fingerprint(&self) -> u64 {
+        self.schema_fingerprint
     }
 
     /// Seek to a given row.
+    ///
+    /// Along with [`RowReader::row_number_range_of_current_batch`], this allows
+    /// resuming reading from a checkpoint, by seeking to the end of the range of
+    /// the last batch processed (possibly with a new `RowReader`).
     pub fn seek_to_row(&mut self, row_number: u64) {
-        self.0.pin_mut().seekToRow(row_number)
+        self.last_batch_rows = 0;
+        self.inner.pin_mut().seekToRow(row_number)
+    }
+
+    /// Returns the numbers of the rows in the previously read batch.
+    ///
+    /// The range is empty before the first batch is read, after the last one, and
+    /// after [`RowReader::seek_to_row`]; its end is always the number of the next
+    /// row to be read, except before the first batch is read.
+    pub fn row_number_range_of_current_batch(&self) -> Range<u64> {
+        let start = self.get_row_number();
+        start..start.saturating_add(self.last_batch_rows)
+    }
+
+    /// Returns the number of the first row of the row group containing the given
+    /// row, or of its stripe if the file has no row indexes.
+    ///
+    /// [`RowReader::seek_to_row`] uses row indexes to jump to this row, then
+    /// decodes (and discards) rows until the given row; so seeking to this row
+    /// instead costs the same.
+    pub fn row_group_start(&self, row_number: u64) -> u64 {
+        let RowGroups {
+            stride,
+            stripe_starts,
+        } = &self.row_groups;
+        let stripe_start = match stripe_starts.partition_point(|&start| start <= row_number) {
+            0 => 0,
+            i => stripe_starts[i - 1],
+        };
+        match stride {
+            0 => stripe_start,
+            stride => row_number - (row_number - stripe_start) % stride,
+        }
+    }
+
+    /// Returns the total number of rows in the file, like [`Reader::row_count`]
+    pub fn row_count(&self) -> u64 {
+        self.row_count
     }
 }
 
 unsafe impl Send for RowReader {}
 
+/// A column selected by a [`RowReader`], with its id in the file and its children.
+///
+/// Returned by [`RowReader::selected_column_tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectedColumn {
+    /// Id of the column in the file; the root column has id 0
+    pub id: u64,
+    pub kind: kind::Kind,
+    /// Columns of the struct fields, list elements, map keys and values, or union
+    /// variants, in the same order as in `kind`
+    pub children: Vec<SelectedColumn>,
+}
+
+impl SelectedColumn {
+    /// Builds the tree of `kind`, taking column ids from `ids` depth-first
+    fn new<I: Iterator<Item = u64>>(kind: kind::Kind, ids: &mut I) -> SelectedColumn {
+        let id = ids
+            .next()
+            .expect("Number of selected columns does not match the selected type");
+        let children = match &kind {
+            kind::Kind::List(item) => vec![SelectedColumn::new((**item).clone(), ids)],
+            kind::Kind::Map { key, value } => vec![
+                SelectedColumn::new((**key).clone(), ids),
+                SelectedColumn::new((**value).clone(), ids),
+            ],
+            kind::Kind::Struct(fields) => fields
+                .iter()
+                .map(|(_, field)| SelectedColumn::new(field.clone(), ids))
+                .collect(),
+            kind::Kind::Union(variants) => variants
+                .iter()
+                .map(|variant| SelectedColumn::new(variant.clone(), ids))
+                .collect(),
+            _ => Vec::new(),
+        };
+        SelectedColumn { id, kind, children }
+    }
+}
+
+/// Returns an [`OrcError`] of kind
+/// [`InvalidArgument`](::errors::OrcErrorKind::InvalidArgument), for arguments
+/// checked before they are passed to the C++ library
+pub(crate) fn invalid_argument(message: &str) -> OrcError {
+    let_cxx_string!(message = message);
+    match ffi::invalidArgument(&message) {
+        Ok(()) => unreachable!("invalidArgument() did not throw"),
+        Err(e) => OrcError(e),
+    }
+}
+
+/// Returns an error if a map in `kind` is selected without its keys or without
+/// its values.
+///
+/// `next_id` is the column id of `kind` in the file, and is advanced past all its
+/// descendants.
+fn check_selected_maps(
+    kind: &kind::Kind,
+    next_id: &mut u64,
+    selected_ids: &HashSet<u64>,
+) -> OrcResult<()> {
+    let id = *next_id;
+    *next_id += 1;
+    match kind {
+        kind::Kind::List(item) => check_selected_maps(item, next_id, selected_ids)?,
+        kind::Kind::Map { key, value } => {
+            let key_id = *next_id;
+            check_selected_maps(key, next_id, selected_ids)?;
+            let value_id = *next_id;
+            check_selected_maps(value, next_id, selected_ids)?;
+            if selected_ids.contains(&id)
+                && !(selected_ids.contains(&key_id) && selected_ids.contains(&value_id))
+            {
+                return Err(invalid_argument(&format!(
+                    "Map column {} must be read with both its keys and its values",
+                    id
+                )));
+            }
+        }
+        kind::Kind::Struct(fields) => {
+            for (_, field) in fields {
+                check_selected_maps(field, next_id, selected_ids)?;
+            }
+        }
+        kind::Kind::Union(variants) => {
+            for variant in variants {
+                check_selected_maps(variant, next_id, selected_ids)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Returns whether the statistics of a string or binary column show it has values
+/// longer than `max_length`
+fn is_oversized(summary: &statistics::ColumnSummary, max_length: u64) -> bool {
+    match summary.statistics {
+        statistics::ColumnStatistics::String {
+            total_length: Some(total_length),
+            ..
+        }
+        | statistics::ColumnStatistics::Binary {
+            total_length: Some(total_length),
+        } => total_length > max_length.saturating_mul(summary.number_of_values),
+        _ => false,
+    }
+}
+
+/// Pushes the ids of the string, varchar, char, and binary columns of `kind`.
+///
+/// `next_id` is the column id of `kind` in the file, and is advanced past all its
+/// descendants.
+fn push_string_column_ids(kind: &kind::Kind, next_id: &mut u64, ids: &mut Vec<u64>) {
+    let id = *next_id;
+    *next_id += 1;
+    match kind {
+        kind::Kind::String | kind::Kind::Varchar(_) | kind::Kind::Char(_) | kind::Kind::Binary => {
+            ids.push(id)
+        }
+        kind::Kind::List(item) => push_string_column_ids(item, next_id, ids),
+        kind::Kind::Map { key, value } => {
+            push_string_column_ids(key, next_id, ids);
+            push_string_column_ids(value, next_id, ids);
+        }
+        kind::Kind::Struct(fields) => {
+            for (_, field) in fields {
+                push_string_column_ids(field, next_id, ids);
+            }
+        }
+        kind::Kind::Union(variants) => {
+            for variant in variants {
+                push_string_column_ids(variant, next_id, ids);
+            }
+        }
+        _ => (),
+    }
+}
+
+pub(crate) fn push_column_paths(kind: &kind::Kind, path: String, paths: &mut Vec<String>) {
+    paths.push(path.clone());
+    let child_path = |name: &str| {
+        if path.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}.{}", path, name)
+        }
+    };
+    match kind {
+        kind::Kind::List(item) => push_column_paths(item, child_path("_elem"), paths),
+        kind::Kind::Map { key, value } => {
+            push_column_paths(key, child_path("_key"), paths);
+            push_column_paths(value, child_path("_value"), paths);
+        }
+        kind::Kind::Struct(fields) => {
+            for (name, field) in fields {
+                push_column_paths(field, child_path(name), paths);
+            }
+        }
+        kind::Kind::Union(variants) => {
+            for (i, variant) in variants.iter().enumerate() {
+                push_column_paths(variant, child_path(&i.to_string()), paths);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Version of the ORC specification a file follows, returned by
+/// [`Reader::format_version`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FormatVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl fmt::Display for FormatVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Implementation that wrote an ORC file, returned by [`Reader::writer_id`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WriterId {
+    OrcJava,
+    OrcCpp,
+    Presto,
+    ScritchleyGo,
+    Trino,
+    Unknown(u32),
+}
+
+/// Version of the writer of an ORC file, returned by [`Reader::writer_version`].
+///
+/// Each version is named after the issue whose fix it includes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WriterVersion {
+    Original,
+    Hive8732,
+    Hive4243,
+    Hive12055,
+    Hive13083,
+    Orc101,
+    Orc135,
+    Orc517,
+    Orc203,
+    Orc14,
+    Unknown(u32),
+}
+
 /// Metadata about a stripe (a bunch of rows) of an ORC file.
-pub struct StripeInformation(UniquePtr<ffi::StripeInformation>);
+pub struct StripeInformation(UniquePtr<ffi::StripeInformation>, u64);
 
 impl StripeInformation {
+    /// Returns the position of the stripe in the file, in bytes
+    pub fn offset(&self) -> u64 {
+        self.0.getOffset()
+    }
+
     /// Returns the stripe's size in bytes
     pub fn bytes_count(&self) -> u64 {
         self.0.getLength()
@@ -269,6 +1853,12 @@ impl StripeInformation {
     pub fn rows_count(&self) -> u64 {
         self.0.getNumberOfRows()
     }
+
+    /// Returns the number of the stripe's first row in the file, ie. the total
+    /// number of rows in the previous stripes
+    pub fn first_row(&self) -> u64 {
+        self.1
+    }
 }
 
 unsafe impl Send for StripeInformation {}
@@ -0,0 +1,162 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Row group statistics and bloom filters, to skip parts of stripes.
+//!
+//! Stripes are divided in row groups of
+//! [`Reader::row_index_stride`](::reader::Reader::row_index_stride) rows (usually
+//! 10000), and the row index of each stripe stores the statistics of every column
+//! in each of its row groups, returned by
+//! [`Reader::row_group_statistics`](::reader::Reader::row_group_statistics).
+//! Files may also store bloom filters of some columns in each row group, returned
+//! by [`Reader::bloom_filters`](::reader::Reader::bloom_filters).
+//!
+//! Unlike [`index`](::index), which works on whole stripes, this allows skipping
+//! row groups within a stripe: [`row_groups`] returns the rows of each row group,
+//! so the row groups which may contain matching rows can be read with
+//! [`RowReader::seek_to_row`](::reader::RowReader::seek_to_row).
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate orcxx;
+//! use orcxx::reader::{InputStream, Reader};
+//! use orcxx::row_index;
+//! use orcxx::statistics::ColumnStatistics;
+//!
+//! let input_stream = InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+//!     .expect("Could not open .orc");
+//! let reader = Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let row_groups = row_index::row_groups(&reader);
+//! assert_eq!(row_groups.len(), 1);
+//! assert_eq!(row_groups[0].rows, 0..2);
+//!
+//! // Column 4 is 'int1'
+//! let statistics = reader
+//!     .row_group_statistics(row_groups[0].stripe, 4)
+//!     .expect("Missing statistics");
+//! assert_eq!(
+//!     statistics[row_groups[0].index].statistics,
+//!     ColumnStatistics::Integer {
+//!         min: 65536,
+//!         max: 65536,
+//!         sum: Some(131072)
+//!     }
+//! );
+//! ```
+
+use std::convert::TryInto;
+use std::ops::Range;
+
+use cxx::UniquePtr;
+
+use reader::{ffi, Reader};
+
+/// Rows of a stripe which have their own entry in its row index and bloom filters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowGroup {
+    /// Index of the stripe containing the row group
+    pub stripe: u64,
+    /// Index of the row group in its stripe, ie. of its items in the vector returned
+    /// by [`Reader::row_group_statistics`] and in [`BloomFilters`]
+    pub index: usize,
+    /// Numbers of the rows of the row group in the file
+    pub rows: Range<u64>,
+}
+
+/// Returns the row groups of every stripe of the file, in order.
+///
+/// If the file has no row indexes, each stripe is returned as a single row group.
+pub fn row_groups(reader: &Reader) -> Vec<RowGroup> {
+    let stride = reader.row_index_stride();
+    let mut row_groups = Vec::new();
+    let mut stripe_start = 0;
+    for (stripe, stripe_information) in (0..).zip(reader.stripes()) {
+        let stripe_end = stripe_start + stripe_information.rows_count();
+        let row_group_size = if stride == 0 {
+            u64::max(stripe_end - stripe_start, 1)
+        } else {
+            stride
+        };
+        let mut start = stripe_start;
+        let mut index = 0;
+        while start < stripe_end {
+            let end = u64::min(start + row_group_size, stripe_end);
+            row_groups.push(RowGroup {
+                stripe,
+                index,
+                rows: start..end,
+            });
+            start = end;
+            index += 1;
+        }
+        stripe_start = stripe_end;
+    }
+    row_groups
+}
+
+/// Bloom filters of a column in each row group of a stripe, returned by
+/// [`Reader::bloom_filters`].
+///
+/// Bloom filters may return false positives, but no false negatives: when a
+/// `might_contain_*` method returns `false`, the row group has no row with this
+/// value.
+pub struct BloomFilters(pub(crate) UniquePtr<ffi::BloomFilterIndex>);
+
+impl BloomFilters {
+    /// Returns the number of row groups
+    pub fn len(&self) -> usize {
+        ffi::BloomFilterIndex_count(&self.0)
+            .try_into()
+            .expect("Number of row groups overflows usize")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether the row group may contain the given value, for columns of
+    /// integer types (booleans excluded) and dates
+    ///
+    /// # Panics
+    ///
+    /// If `row_group` is not lower than [`len`](BloomFilters::len)
+    pub fn might_contain_long(&self, row_group: usize, value: i64) -> bool {
+        ffi::BloomFilterIndex_testLong(&self.0, self.checked_row_group(row_group), value)
+    }
+
+    /// Returns whether the row group may contain the given value, for columns of
+    /// floating-point types
+    ///
+    /// # Panics
+    ///
+    /// If `row_group` is not lower than [`len`](BloomFilters::len)
+    pub fn might_contain_double(&self, row_group: usize, value: f64) -> bool {
+        ffi::BloomFilterIndex_testDouble(&self.0, self.checked_row_group(row_group), value)
+    }
+
+    /// Returns whether the row group may contain the given value, for columns of
+    /// string, varchar, char, and binary types
+    ///
+    /// # Panics
+    ///
+    /// If `row_group` is not lower than [`len`](BloomFilters::len)
+    pub fn might_contain_bytes<V: AsRef<[u8]>>(&self, row_group: usize, value: V) -> bool {
+        ffi::BloomFilterIndex_testBytes(&self.0, self.checked_row_group(row_group), value.as_ref())
+    }
+
+    fn checked_row_group(&self, row_group: usize) -> u64 {
+        assert!(
+            row_group < self.len(),
+            "Row group {} out of range ({} row groups)",
+            row_group,
+            self.len()
+        );
+        row_group as u64
+    }
+}
+
+unsafe impl Send for BloomFilters {}
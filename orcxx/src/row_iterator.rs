@@ -7,19 +7,63 @@
 //!
 //! Iterator items need to implement [`OrcDeserialize`] trait; `orcxx_derive` can
 //! generate implementations for structures.
-//!
-//! TODO: write a test for this after we add the write API to vector batches
-//! (currently it's only indirectly tested in `orcxx_derive`), because all the test
-//! files have a structure at the root and we can't use `#[derive(OrcDeserialize)]`
-//! in this crate to implement it.
 
 use std::convert::TryInto;
-use std::num::NonZeroU64;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::ops::Range;
+
+use deserialize::{CheckableKind, DeserializationError, OrcDeserialize, OrcStruct};
+use errors::{OpenOrcError, ReadError};
+use index::{IndexError, OrcxxIndex};
+use kind::Kind;
+use reader::{InputStream, Reader, RowReader, RowReaderOptions};
+use vector::{ColumnVectorBatch, OwnedColumnVectorBatch};
+
+/// What [`RowIterator`] does with rows it fails to deserialize
+///
+/// See [`RowIterator::skip_errors`] to skip them instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnError {
+    /// Panic
+    #[default]
+    Panic,
+    /// Yield [`Default::default()`] instead of the row (ie. `None` for `Option<T>`)
+    Default,
+}
+
+/// Number of rows deserialized at once by [`read_file`], [`stream_file`], and
+/// [`Reader::column_iter`]
+pub(crate) const DEFAULT_BATCH_SIZE: u64 = 1024;
+
+/// Reads all rows of the ORC file at `path`.
+///
+/// This opens the file, only selects columns needed by `T`, and collects a
+/// [`RowIterator`]; see [`stream_file`] to iterate on rows instead, and the
+/// [`orcxx_derive` documentation](https://docs.rs/orcxx_derive/) for an example.
+///
+/// Like [`RowIterator`], this requires `T` to implement [`Default`]; structures
+/// which do not can be read as `Option<T>`.
+///
+/// # Panics
+///
+/// When rows cannot be deserialized, like [`RowIterator`].
+pub fn read_file<T: OrcDeserialize + OrcStruct + CheckableKind + Default>(
+    path: &str,
+) -> Result<Vec<T>, OpenOrcError> {
+    Ok(stream_file(path)?.collect())
+}
 
-use deserialize::{CheckableKind, OrcDeserialize, OrcStruct};
-use errors::OpenOrcError;
-use reader::{Reader, RowReader, RowReaderOptions};
-use vector::OwnedColumnVectorBatch;
+/// Returns an iterator on rows of the ORC file at `path`.
+///
+/// This opens the file and calls [`RowIterator::new`], which only selects columns
+/// needed by `T`.
+pub fn stream_file<T: OrcDeserialize + OrcStruct + CheckableKind + Default>(
+    path: &str,
+) -> Result<RowIterator<T>, OpenOrcError> {
+    let input_stream = InputStream::from_local_file(path).map_err(OpenOrcError::OrcError)?;
+    let reader = Reader::new(input_stream).map_err(OpenOrcError::ReaderError)?;
+    RowIterator::new(&reader, NonZeroU64::new(DEFAULT_BATCH_SIZE).unwrap())
+}
 
 /// Iterator on rows of the given [`RowReader`].
 ///
@@ -27,30 +71,74 @@ use vector::OwnedColumnVectorBatch;
 /// [`OrcDeserialize::read_from_vector_batch`] and working on the column vector,
 /// but provides a more familiar API to work with individual rows.
 ///
+/// Rows are deserialized into a buffer allocated beforehand, so `T` must
+/// implement [`Default`]; derived structures which do not can be read as
+/// `Option<T>`, which is built from the values of their fields.
+///
 /// # Panics
 ///
 /// next() repeatedly calls [`OrcDeserialize::read_from_vector_batch`] and panics
-/// when it returns a [`::deserialize::DeserializationError`].
-pub struct RowIterator<T: OrcDeserialize + Clone> {
+/// when it returns a [`DeserializationError`], unless configured otherwise with
+/// [`RowIterator::on_error`]; or use [`RowIterator::try_next`] or
+/// [`RowIterator::try_rows`] to get errors instead, or [`RowIterator::skip_errors`]
+/// to skip the rows which caused them.
+pub struct RowIterator<T: OrcDeserialize + Default> {
     row_reader: RowReader,
+    /// Selected kind of `row_reader`, passed to
+    /// [`OrcDeserialize::read_from_vector_batch_with_kind`]
+    kind: Kind,
     batch: OwnedColumnVectorBatch,
     decoded_batch: Vec<T>,
 
+    /// Whether each row of `decoded_batch` was moved out of it by [`Iterator::next`]
+    /// or [`DoubleEndedIterator::next_back`], so it needs to be deserialized again
+    /// before it is yielded again
+    taken: Vec<bool>,
+
+    on_error: OnError,
+
+    /// Whether rows which fail to deserialize are skipped, regardless of
+    /// `on_error` (see [`RowIterator::skip_errors`])
+    skip_errors: bool,
+
+    /// Errors not returned by [`RowIterator::take_errors`] yet
+    errors: Vec<(u64, DeserializationError)>,
+
     /// Index in the decoded batch
     index: usize,
 
     /// Maximum value of the index + 1
     decoded_items: usize,
 
-    /// Total number of lines in the file
-    row_count: u64,
+    /// Number of the first row to iterate on (0, unless set with [`RowIterator::range`])
+    start: u64,
+
+    /// Number of the row after the last one to iterate on (the total number of rows
+    /// in the file, unless set with [`RowIterator::range`])
+    end: u64,
+
+    /// Rows of stripes missing from the index passed to [`RowIterator::with_index`],
+    /// sorted and merged
+    skipped: Vec<Range<u64>>,
+
+    /// Number of the first row of the next batch read by [`Iterator::next`]
+    next_row: u64,
+
+    /// Whether some rows of the current batch failed to deserialize, so the decoded
+    /// batch cannot be reused by [`RowIterator::seek`]
+    batch_has_errors: bool,
+
+    /// Numbers of the rows of the current batch skipped because of
+    /// [`RowIterator::skip_errors`], in order
+    skipped_in_batch: Vec<u64>,
 }
 
-impl<T: OrcDeserialize + OrcStruct + CheckableKind + Clone> RowIterator<T> {
+impl<T: OrcDeserialize + OrcStruct + CheckableKind + Default> RowIterator<T> {
     /// Returns an iterator on rows of the given [`Reader`].
     ///
     /// This calls [`RowIterator::new_with_options`] with default options and
-    /// includes only the needed columns (see [`RowReaderOptions::include_names`]).
+    /// includes only the needed columns which exist in the file (see
+    /// [`OrcStruct::columns_in`]).
     ///
     /// Errors are either detailed descriptions of format mismatch (as returned by
     /// [`CheckableKind::check_kind`], or C++ exceptions.
@@ -59,12 +147,49 @@ impl<T: OrcDeserialize + OrcStruct + CheckableKind + Clone> RowIterator<T> {
     ///
     /// When `batch_size` is larger than `usize`.
     pub fn new(reader: &Reader, batch_size: NonZeroU64) -> Result<RowIterator<T>, OpenOrcError> {
-        let options = RowReaderOptions::default().include_names(T::columns());
+        let options = RowReaderOptions::default().include_names(T::columns_in(&reader.kind()));
         Self::new_with_options(reader, batch_size, &options)
     }
+
+    /// Same as [`RowIterator::new`], but starts iteration at the given row, which is
+    /// usually a checkpoint returned by [`RowIterator::position`] in an earlier run.
+    ///
+    /// The underlying [`RowReader`] uses stripe row counts and row indexes to seek,
+    /// so earlier stripes are neither read nor decoded. Unlike [`RowIterator::seek`],
+    /// no batch is decoded before the first call to [`Iterator::next`]; rows before
+    /// the given row are still available to [`DoubleEndedIterator::next_back`].
+    pub fn resume_at(
+        reader: &Reader,
+        batch_size: NonZeroU64,
+        row_number: u64,
+    ) -> Result<RowIterator<T>, OpenOrcError> {
+        let iterator = Self::new(reader, batch_size)?;
+        let row_number = u64::min(row_number, iterator.end);
+        Ok(iterator.restart(row_number))
+    }
+
+    /// Returns one iterator for each of the `n_shards` row ranges returned by
+    /// [`Reader::shard`], so they can be consumed independently (eg. by different
+    /// workers of a distributed execution framework).
+    ///
+    /// This calls [`RowIterator::shards_with_options`] with default options and
+    /// includes only the needed columns which exist in the file (see
+    /// [`OrcStruct::columns_in`]).
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is larger than `usize`.
+    pub fn shards(
+        reader: &Reader,
+        batch_size: NonZeroU64,
+        n_shards: NonZeroUsize,
+    ) -> Result<Vec<RowIterator<T>>, OpenOrcError> {
+        let options = RowReaderOptions::default().include_names(T::columns_in(&reader.kind()));
+        Self::shards_with_options(reader, batch_size, &options, n_shards)
+    }
 }
 
-impl<T: OrcDeserialize + Clone> RowIterator<T> {
+impl<T: OrcDeserialize + Default> RowIterator<T> {
     /// Returns an iterator on rows of the given [`RowReader`].
     ///
     /// Errors are detailed descriptions of format mismatch (as returned by
@@ -78,8 +203,47 @@ impl<T: OrcDeserialize + Clone> RowIterator<T> {
         batch_size: NonZeroU64,
         options: &RowReaderOptions,
     ) -> Result<RowIterator<T>, OpenOrcError> {
-        let mut row_reader = reader.row_reader(options).map_err(OpenOrcError::OrcError)?;
-        match T::check_kind(&row_reader.selected_kind()) {
+        let row_reader = reader.row_reader(options).map_err(OpenOrcError::OrcError)?;
+        Self::from_row_reader(row_reader, batch_size)
+    }
+
+    /// Returns one iterator on rows of the given [`Reader`] for each of the
+    /// `n_shards` row ranges returned by [`Reader::shard`].
+    ///
+    /// Errors are detailed descriptions of format mismatch (as returned by
+    /// [`CheckableKind::check_kind`].
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is larger than `usize`.
+    pub fn shards_with_options(
+        reader: &Reader,
+        batch_size: NonZeroU64,
+        options: &RowReaderOptions,
+        n_shards: NonZeroUsize,
+    ) -> Result<Vec<RowIterator<T>>, OpenOrcError> {
+        reader
+            .shard(n_shards)
+            .into_iter()
+            .map(|rows| Ok(Self::new_with_options(reader, batch_size, options)?.range(rows)))
+            .collect()
+    }
+
+    /// Returns an iterator on rows of the given [`RowReader`], for callers which
+    /// need to set it up themselves. Iteration starts at its current position.
+    ///
+    /// Errors are detailed descriptions of format mismatch (as returned by
+    /// [`CheckableKind::check_kind`].
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is larger than `usize`.
+    pub fn from_row_reader(
+        mut row_reader: RowReader,
+        batch_size: NonZeroU64,
+    ) -> Result<RowIterator<T>, OpenOrcError> {
+        let kind = row_reader.selected_kind();
+        match T::check_kind(&kind) {
             Ok(_) => (),
             Err(msg) => return Err(OpenOrcError::KindError(msg)),
         }
@@ -87,102 +251,660 @@ impl<T: OrcDeserialize + Clone> RowIterator<T> {
         let batch_size_usize = batch_size.try_into().expect("batch_size overflows usize");
         let mut decoded_batch = Vec::with_capacity(batch_size_usize);
         decoded_batch.resize_with(batch_size_usize, Default::default);
+        let end = row_reader.row_count();
+        let next_row = match row_reader.row_number_range_of_current_batch().end {
+            u64::MAX => 0, // Nothing was read yet
+            next_row => next_row,
+        };
         Ok(RowIterator {
             batch: row_reader.row_batch(batch_size),
             row_reader,
+            kind,
             decoded_batch,
+            taken: vec![false; batch_size_usize],
             index: 0,
             decoded_items: 0, // Will be filled on the first run of next()
-            row_count: reader.row_count(),
+            start: next_row,
+            end,
+            on_error: OnError::default(),
+            skip_errors: false,
+            errors: Vec::new(),
+            skipped: Vec::new(),
+            next_row,
+            batch_has_errors: false,
+            skipped_in_batch: Vec::new(),
         })
     }
 
+    /// Sets what to do with rows which cannot be deserialized (see [`OnError`]).
+    ///
+    /// Unless this is [`OnError::Panic`] (the default), a batch which fails to
+    /// deserialize is deserialized again one row at a time, so only the offending
+    /// rows are lost, and errors are recorded to be returned by
+    /// [`RowIterator::take_errors`].
+    pub fn on_error(mut self, on_error: OnError) -> Self {
+        self.on_error = on_error;
+        self
+    }
+
+    /// Returns errors which occurred since the last call to this function, along
+    /// with the number of the row (in the file) which caused them.
+    ///
+    /// Each row is returned at most once, even if it was decoded again (eg. after a
+    /// [`RowIterator::seek`]) since the last call.
+    ///
+    /// When rows cannot be read again to deserialize them one at a time, this
+    /// returns a [`DeserializationError::ReadError`] and iteration stops before them.
+    pub fn take_errors(&mut self) -> Vec<(u64, DeserializationError)> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Returns an iterator which yields deserialization errors instead of
+    /// panicking, in place of the rows which caused them.
+    ///
+    /// This overrides [`RowIterator::on_error`].
+    pub fn try_rows(self) -> TryRowIterator<T> {
+        TryRowIterator(self.on_error(OnError::Default))
+    }
+
+    /// Returns an iterator which skips rows which cannot be deserialized, and
+    /// records their errors to be returned by [`SkippingRowIterator::take_errors`].
+    ///
+    /// This overrides [`RowIterator::on_error`]. As rows which fail to deserialize
+    /// are only known once their batch is decoded, the returned iterator does not
+    /// implement [`ExactSizeIterator`].
+    pub fn skip_errors(mut self) -> SkippingRowIterator<T> {
+        self.skip_errors = true;
+        SkippingRowIterator(self)
+    }
+
+    /// Same as [`Iterator::next`], but returns deserialization errors instead of
+    /// panicking, in place of the rows which caused them.
+    ///
+    /// Unlike [`RowIterator::try_rows`], this does not consume the iterator, so
+    /// it can be used along with [`RowIterator::seek`] or [`RowIterator::range`].
+    ///
+    /// Note that if [`Iterator::next`] is called next, rows of the batch decoded
+    /// by this function which failed to deserialize are replaced by
+    /// [`Default::default()`], as with [`OnError::Default`].
+    pub fn try_next(&mut self) -> Option<Result<T, DeserializationError>> {
+        let row = self.without_panics(Self::next)?;
+        let index = self.index - 1;
+        Some(self.row_or_error(row, index))
+    }
+
+    /// Same as [`RowIterator::try_next`], but for [`DoubleEndedIterator::next_back`]
+    pub fn try_next_back(&mut self) -> Option<Result<T, DeserializationError>> {
+        let row = self.without_panics(Self::next_back)?;
+        let index = self.index;
+        Some(self.row_or_error(row, index))
+    }
+
+    /// Returns the rows [`Iterator::next`] would yield until the end of the current
+    /// batch (or of the next batch, if the current one is exhausted), without
+    /// cloning them.
+    ///
+    /// This is meant for consumers which process rows in chunks; chunks have up to
+    /// `batch_size` rows, and may be shorter at the end of stripes or after a
+    /// [`RowIterator::seek`]. Calls to [`Iterator::next`] and this function can be
+    /// mixed.
+    ///
+    /// # Panics
+    ///
+    /// Like [`Iterator::next`], when the new batch fails to deserialize and
+    /// [`RowIterator::on_error`] is [`OnError::Panic`].
+    pub fn next_batch(&mut self) -> Option<&[T]> {
+        if !self.fill_batch("next_batch") {
+            return None;
+        }
+
+        self.restore_rows(self.index..self.decoded_items, "next_batch");
+        let rows = &self.decoded_batch[self.index..self.decoded_items];
+        self.index = self.decoded_items;
+        Some(rows)
+    }
+
+    /// Calls `f` with [`OnError::Panic`] replaced by [`OnError::Default`]
+    fn without_panics<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let on_error = self.on_error;
+        if on_error == OnError::Panic {
+            self.on_error = OnError::Default;
+        }
+        let result = f(self);
+        self.on_error = on_error;
+        result
+    }
+
+    /// Returns the row at `index` in the decoded batch, or the error which
+    /// occurred while deserializing it.
+    fn row_or_error(&mut self, row: T, index: usize) -> Result<T, DeserializationError> {
+        let row_number = self.row_number_at(index);
+        // Errors are recorded in order, and removed as soon as they are yielded,
+        // so this is usually the first item.
+        match self
+            .errors
+            .iter()
+            .position(|(error_row_number, _)| *error_row_number == row_number)
+        {
+            Some(i) => Err(self.errors.remove(i).1),
+            None => Ok(row),
+        }
+    }
+
+    /// Returns the underlying [`RowReader`], so it can be reused by another iterator
+    #[cfg(feature = "rayon")]
+    pub(crate) fn into_row_reader(self) -> RowReader {
+        self.row_reader
+    }
+
+    /// Moves iteration to the given row (in the file), forward or backward.
+    ///
+    /// If the row is in the current batch, this only moves within it. Otherwise,
+    /// this decodes a batch starting at the beginning of the row's row group (see
+    /// [`RowReader::row_group_start`]), which the underlying [`RowReader`] would
+    /// decode anyway to reach the row; so rows before it are then available to
+    /// [`DoubleEndedIterator::next_back`] and to later seeks without being decoded
+    /// again.
+    ///
+    /// # Panics
+    ///
+    /// Like [`Iterator::next`], when the new batch fails to deserialize and
+    /// [`RowIterator::on_error`] is [`OnError::Panic`].
     pub fn seek(mut self, row_number: u64) -> Self {
-        // TODO: avoid seeking in the underlying row_reader if the row we see is already
-        // in the current buffer.
+        if let Some(index) = self.buffered_index(row_number) {
+            self.index = index;
+            return self;
+        }
+
+        let batch_start = u64::max(self.row_reader.row_group_start(row_number), self.start);
+        let batch_size: u64 = self
+            .decoded_batch
+            .len()
+            .try_into()
+            .expect("batch size overflowed u64");
+        if (batch_start..u64::min(batch_start + batch_size, self.end)).contains(&row_number)
+            && !self.is_skipped(row_number)
+        {
+            self.row_reader.seek_to_row(batch_start);
+            if self.row_reader.read_into(&mut self.batch) {
+                self.next_row = batch_start + self.batch.borrow().num_elements();
+                self.decoded_items = self.decode_batch("seek");
+                if let Some(index) = self.buffered_index(row_number) {
+                    self.index = index;
+                    return self;
+                }
+            }
+        }
+
+        self.restart(row_number)
+    }
+
+    /// Returns the number of the row [`Iterator::next`] would yield next (or of the
+    /// first row after it, if it fails to deserialize with
+    /// [`RowIterator::skip_errors`]).
+    ///
+    /// This can be saved as a checkpoint, to later continue iteration from the same
+    /// row with [`RowIterator::resume_at`]. After the last row is yielded, this is
+    /// the end of the range of rows (see [`RowIterator::range`]).
+    pub fn position(&self) -> u64 {
+        if self.index < self.decoded_items {
+            self.row_number_at(self.index)
+        } else {
+            u64::min(self.next_row, self.end)
+        }
+    }
+
+    /// Returns the rows to iterate on, as set by [`RowIterator::range`]
+    pub(crate) fn rows(&self) -> Range<u64> {
+        self.start..self.end
+    }
+
+    /// Restarts iteration at the start of the range, without decoding a batch
+    pub(crate) fn rewind(self) -> Self {
+        let start = self.start;
+        self.restart(start)
+    }
+
+    /// Moves iteration to the end of the range, without decoding a batch, so only
+    /// [`DoubleEndedIterator::next_back`] yields rows
+    pub(crate) fn fast_forward(self) -> Self {
+        let end = self.end;
+        self.restart(end)
+    }
+
+    /// Seeks the underlying [`RowReader`] to the given row, and drops the current
+    /// batch so the next call to [`Iterator::next`] reads from there.
+    fn restart(mut self, row_number: u64) -> Self {
         self.row_reader.seek_to_row(row_number);
+        self.next_row = row_number;
         self.index = 0;
         self.decoded_items = 0;
         self
     }
+
+    /// Returns the index of the given row in the decoded batch, if it is there
+    fn buffered_index(&self, row_number: u64) -> Option<usize> {
+        if self.decoded_items == 0 || self.batch_has_errors || self.is_skipped(row_number) {
+            return None;
+        }
+        if row_number < self.start || row_number >= self.end {
+            return None;
+        }
+        let index = row_number.checked_sub(self.row_reader.get_row_number())?;
+        let index: usize = index.try_into().ok()?;
+        if index < self.decoded_items {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether the given row is in a stripe skipped by [`RowIterator::with_index`]
+    fn is_skipped(&self, row_number: u64) -> bool {
+        self.skipped.iter().any(|rows| rows.contains(&row_number))
+    }
+
+    /// Skips to the last `n` rows (or all rows, if there are fewer than `n`).
+    ///
+    /// The underlying [`RowReader`] uses stripe row counts and row indexes to seek,
+    /// so only the stripe(s) containing these rows are decoded.
+    pub fn tail(self, n: u64) -> Self {
+        let row_number = self.end.saturating_sub(n);
+        self.seek(row_number)
+    }
+
+    /// Only iterates on rows in the given range (clamped to the number of rows in
+    /// the file), such as one returned by [`Reader::shard`].
+    ///
+    /// Iteration stops exactly at `rows.end`; this is cheapest when it is on a
+    /// stripe boundary, because the underlying [`RowReader`] does not read batches
+    /// across stripes.
+    pub fn range(mut self, rows: Range<u64>) -> Self {
+        self.end = u64::min(rows.end, self.row_reader.row_count());
+        self.start = u64::min(rows.start, self.end);
+        let start = self.start;
+        self.restart(start)
+    }
+
+    /// Only iterates on rows of the stripes in `index`, which is usually filtered
+    /// with [`OrcxxIndex::retain_stripes`]; other stripes are skipped without being
+    /// read. Like [`RowIterator::range`], this restarts iteration at the start of
+    /// the range.
+    ///
+    /// Stripes are skipped both by [`Iterator::next`] and by
+    /// [`DoubleEndedIterator::next_back`], and their rows are not counted by
+    /// [`ExactSizeIterator::len`].
+    ///
+    /// Returns an error if `index` was built from a file with a different schema or
+    /// number of rows.
+    pub fn with_index(mut self, index: &OrcxxIndex) -> Result<Self, IndexError> {
+        let schema_fingerprint = self.row_reader.schema_fingerprint();
+        if index.schema_fingerprint != schema_fingerprint {
+            return Err(IndexError::Mismatch(format!(
+                "index has schema fingerprint {:#x}, but the file has {:#x}",
+                index.schema_fingerprint, schema_fingerprint
+            )));
+        }
+        let row_count = self.row_reader.row_count();
+        if index.row_count != row_count {
+            return Err(IndexError::Mismatch(format!(
+                "index has {} rows, but the file has {}",
+                index.row_count, row_count
+            )));
+        }
+        let mut skipped = Vec::new();
+        let mut previous_end = 0;
+        for rows in index.row_ranges() {
+            if rows.start > previous_end {
+                skipped.push(previous_end..rows.start);
+            }
+            previous_end = rows.end;
+        }
+        if previous_end < row_count {
+            skipped.push(previous_end..row_count);
+        }
+        self.skipped = skipped;
+        let start = self.start;
+        Ok(self.restart(start))
+    }
+
+    /// If the next batch would start in a skipped stripe, seeks past it; then
+    /// returns the number of the first row of the next batch.
+    fn skip_stripes(&mut self) -> u64 {
+        let next_row = self.next_row;
+        if let Some(rows) = self.skipped.iter().find(|rows| rows.contains(&next_row)) {
+            let end = rows.end;
+            if end < self.end {
+                self.row_reader.seek_to_row(end);
+            }
+            self.next_row = end;
+        }
+        self.next_row
+    }
+
+    /// Returns the number of rows in skipped stripes between `start` and `end`
+    fn skipped_rows(&self, start: u64, end: u64) -> u64 {
+        self.skipped
+            .iter()
+            .map(|rows| u64::min(rows.end, end).saturating_sub(u64::max(rows.start, start)))
+            .sum()
+    }
+
+    /// Returns the number of rows of the current batch before the end of the range
+    fn rows_before_end(&self) -> usize {
+        self.end
+            .saturating_sub(self.row_reader.get_row_number())
+            .try_into()
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Returns the number (in the file) of the row at `index` in the decoded batch
+    fn row_number_at(&self, index: usize) -> u64 {
+        let index: u64 = index.try_into().expect("index overflows u64");
+        let mut row_number = self.row_reader.get_row_number() + index;
+        // Skipped rows are not in the decoded batch
+        for &skipped_row_number in &self.skipped_in_batch {
+            if skipped_row_number <= row_number {
+                row_number += 1;
+            }
+        }
+        row_number
+    }
+
+    /// If the current batch is exhausted, reads and deserializes the next one (and
+    /// the one after if all rows of the batch were skipped, see
+    /// [`RowIterator::skip_errors`]).
+    ///
+    /// Returns `false` when there are no rows left.
+    fn fill_batch(&mut self, caller: &str) -> bool {
+        while self.index == self.decoded_items {
+            self.index = 0;
+            if self.skip_stripes() >= self.end
+                || !self.row_reader.read_into(&mut self.batch)
+                || self.row_reader.get_row_number() >= self.end
+            {
+                return false;
+            }
+            self.next_row = self.row_reader.get_row_number() + self.batch.borrow().num_elements();
+            self.decoded_items = self.decode_batch(caller);
+        }
+        true
+    }
+
+    /// Deserializes `batch` into `decoded_batch`, and returns the number of rows
+    /// written in `decoded_batch`.
+    fn decode_batch(&mut self, caller: &str) -> usize {
+        self.batch_has_errors = false;
+        self.skipped_in_batch.clear();
+        self.taken.fill(false);
+        match T::read_from_vector_batch_with_kind(
+            &self.batch.borrow(),
+            &self.kind,
+            &mut self.decoded_batch,
+        ) {
+            Ok(decoded_items) => usize::min(decoded_items, self.rows_before_end()),
+            Err(_) if self.skip_errors => self.decode_batch_rows(),
+            Err(e) => match self.on_error {
+                OnError::Panic => panic!(
+                    "OrcDeserialize::read_from_vector_batch() call from RowIterator::{}() returns a deserialization error: {:?}",
+                    caller, e
+                ),
+                OnError::Default => self.decode_batch_rows(),
+            },
+        }
+    }
+
+    /// Deserializes the current batch again if any row in `rows` was moved out of
+    /// `decoded_batch`.
+    fn restore_rows(&mut self, rows: Range<usize>, caller: &str) {
+        if self.taken[rows].contains(&true) {
+            // Errors of this batch were already recorded when it was first
+            // deserialized, unless its rows cannot be read anymore
+            let errors_len = self.errors.len();
+            self.decoded_items = self.decode_batch(caller);
+            let mut new_errors = self.errors.split_off(errors_len);
+            new_errors.retain(|(_, e)| matches!(e, DeserializationError::ReadError(_)));
+            self.errors.append(&mut new_errors);
+        }
+    }
+
+    /// Moves the row at `index` out of the decoded batch
+    fn take_row(&mut self, index: usize, caller: &str) -> T {
+        self.restore_rows(index..index + 1, caller);
+        self.taken[index] = true;
+        std::mem::take(&mut self.decoded_batch[index])
+    }
+
+    /// Same as [`RowIterator::decode_batch`], but reads and deserializes rows one
+    /// at a time, in order to skip or replace the ones which fail.
+    fn decode_batch_rows(&mut self) -> usize {
+        let batch_start = self.row_reader.get_row_number();
+        let batch_len = self.batch.borrow().num_elements();
+        let decoded_len = u64::min(batch_len, self.end.saturating_sub(batch_start));
+
+        let mut row_batch = self.row_reader.row_batch(1);
+        let mut decoded_row = vec![T::default()];
+        let mut decoded_items = 0;
+        self.row_reader.seek_to_row(batch_start);
+        for row_number in batch_start..batch_start + decoded_len {
+            if let Err(e) = read_again(&mut self.row_reader, &mut row_batch, row_number, 1) {
+                // `batch` was not overwritten, so the rows decoded so far can still
+                // be deserialized again by restore_rows()
+                self.row_reader.seek_to_row(batch_start);
+                self.stop_at(row_number, e);
+                return decoded_items;
+            }
+            match T::read_from_vector_batch_with_kind(
+                &row_batch.borrow(),
+                &self.kind,
+                &mut decoded_row,
+            ) {
+                Ok(_) => {
+                    std::mem::swap(&mut self.decoded_batch[decoded_items], &mut decoded_row[0]);
+                    decoded_items += 1;
+                }
+                Err(e) => {
+                    self.record_error(row_number, e);
+                    if self.skip_errors {
+                        self.skipped_in_batch.push(row_number);
+                    } else {
+                        self.decoded_batch[decoded_items] = T::default();
+                        decoded_items += 1;
+                    }
+                }
+            }
+        }
+
+        // Read the whole batch again, so the row reader is in the same state as if
+        // the batch had been deserialized at once.
+        self.row_reader.seek_to_row(batch_start);
+        if let Err(e) = read_again(
+            &mut self.row_reader,
+            &mut self.batch,
+            batch_start,
+            batch_len,
+        ) {
+            // `batch` may be partially overwritten, so none of its rows can be
+            // deserialized again
+            self.row_reader.seek_to_row(batch_start);
+            self.stop_at(batch_start, e);
+            return 0;
+        }
+
+        decoded_items
+    }
+
+    /// Records the error of the given row, so it is returned by
+    /// [`RowIterator::take_errors`]
+    fn record_error(&mut self, row_number: u64, error: DeserializationError) {
+        // Seeking may decode the same row again before its error is taken
+        if !self
+            .errors
+            .iter()
+            .any(|(error_row_number, _)| *error_row_number == row_number)
+        {
+            self.errors.push((row_number, error));
+        }
+        self.batch_has_errors = true;
+    }
+
+    /// Ends the range of rows to iterate on at the given row, because it cannot be
+    /// read again, and records the error.
+    fn stop_at(&mut self, row_number: u64, error: ReadError) {
+        self.record_error(row_number, DeserializationError::ReadError(error));
+        self.end = u64::min(self.end, row_number);
+    }
+}
+
+/// Reads `len` rows starting at `row_number` into `batch`, which were already read
+/// before, or returns an error if there are fewer of them.
+fn read_again(
+    row_reader: &mut RowReader,
+    batch: &mut OwnedColumnVectorBatch,
+    row_number: u64,
+    len: u64,
+) -> Result<(), ReadError> {
+    let read_len = match row_reader.try_read_into(batch)? {
+        true => batch.borrow().num_elements(),
+        false => 0,
+    };
+    if read_len < len {
+        return Err(ReadError::RowsDisappeared {
+            start: row_number + read_len,
+            end: row_number + len,
+        });
+    }
+    Ok(())
 }
 
 /// # Panics
 ///
 /// next() repeatedly calls [`OrcDeserialize::read_from_vector_batch`] and panics
-/// when it returns a [`::deserialize::DeserializationError`].
-impl<T: OrcDeserialize + Clone> Iterator for RowIterator<T> {
+/// when it returns a [`DeserializationError`], unless configured otherwise with
+/// [`RowIterator::on_error`].
+impl<T: OrcDeserialize + Default> Iterator for RowIterator<T> {
     type Item = T;
 
     fn next(&mut self) -> Option<T> {
-        // Exhausted the current batch, read the next one.
-        if self.index == self.decoded_items {
-            self.index = 0;
-            if !self.row_reader.read_into(&mut self.batch) {
-                return None;
-            }
-            self.decoded_items = T::read_from_vector_batch(&self.batch.borrow(), &mut self.decoded_batch).expect("OrcDeserialize::read_from_vector_batch() call from RowIterator::next() returns a deserialization error");
+        if !self.fill_batch("next") {
+            return None;
         }
 
-        let item = self.decoded_batch.get(self.index);
+        let item = self.take_row(self.index, "next");
         self.index += 1;
 
-        item.cloned()
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining_rows();
+        (len, Some(len))
     }
 }
 
 /// # Panics
 ///
 /// next() repeatedly calls [`OrcDeserialize::read_from_vector_batch`] and panics
-/// when it returns a [`::deserialize::DeserializationError`].
-impl<T: OrcDeserialize + Clone> DoubleEndedIterator for RowIterator<T> {
+/// when it returns a [`DeserializationError`], unless configured otherwise with
+/// [`RowIterator::on_error`].
+impl<T: OrcDeserialize + Default> DoubleEndedIterator for RowIterator<T> {
     fn next_back(&mut self) -> Option<T> {
-        // Exhausted the current batch, read the next one.
-        if self.index == 0 {
-            let row_number = self.row_reader.get_row_number();
+        // Exhausted the current batch, read the previous one (and the one before if
+        // all rows of the batch were skipped, see RowIterator::skip_errors).
+        while self.index == 0 {
+            let mut row_number = self.row_reader.get_row_number();
+            // Skip stripes ending at the current batch, like skip_stripes() does
+            // in the other direction
+            if let Some(rows) = self
+                .skipped
+                .iter()
+                .find(|rows| rows.start < row_number && row_number <= rows.end)
+            {
+                row_number = rows.start;
+            }
             let batch_size: u64 = self
                 .decoded_batch
                 .len()
                 .try_into()
                 .expect("batch size overflowed u64");
-            if row_number == 0 {
+            if row_number <= self.start {
                 return None;
             }
-            let seek_to = row_number - u64::min(row_number, batch_size);
+            // Don't read rows of skipped stripes before the current batch either
+            let seek_to = self
+                .skipped
+                .iter()
+                .map(|rows| rows.end)
+                .filter(|&end| end <= row_number)
+                .fold(
+                    u64::max(self.start, row_number - u64::min(row_number, batch_size)),
+                    u64::max,
+                );
             self.row_reader.seek_to_row(seek_to);
-            assert!(
-                self.row_reader.read_into(&mut self.batch),
-                "Rows {}..{} disappeared while rewinding",
-                seek_to,
-                row_number
-            );
-            self.decoded_items = T::read_from_vector_batch(&self.batch.borrow(), &mut self.decoded_batch).expect("OrcDeserialize::read_from_vector_batch() call from RowIterator::next_back() returns a deserialization error");
-            self.index = self.decoded_items;
-            assert_ne!(self.index, 0, "Got empty batch")
+            // The batch may end before `row_number`, at the end of a stripe
+            if let Err(e) = read_again(&mut self.row_reader, &mut self.batch, seek_to, 1) {
+                if self.on_error == OnError::Panic && !self.skip_errors {
+                    panic!("Could not read rows while rewinding: {}", e);
+                }
+                // Stop iterating backward before the current batch, which is read
+                // again by the next call to next()
+                self.row_reader.seek_to_row(row_number);
+                self.next_row = row_number;
+                self.decoded_items = 0;
+                self.record_error(seek_to, DeserializationError::ReadError(e));
+                self.start = row_number;
+                return None;
+            }
+            self.next_row = seek_to + self.batch.borrow().num_elements();
+            self.decoded_items = self.decode_batch("next_back");
+            // The batch may extend past the rows already yielded, if it was shortened
+            // to start at the beginning of the range or after a skipped stripe
+            let rows_before: usize = (row_number - seek_to)
+                .try_into()
+                .expect("row count overflows usize");
+            let skipped_before = self
+                .skipped_in_batch
+                .iter()
+                .filter(|&&skipped_row_number| skipped_row_number < row_number)
+                .count();
+            self.index = usize::min(self.decoded_items, rows_before - skipped_before);
         }
 
         self.index -= 1;
-        let item = self.decoded_batch.get(self.index);
-
-        item.cloned()
+        Some(self.take_row(self.index, "next_back"))
     }
 }
 
-impl<T: OrcDeserialize + Clone> ExactSizeIterator for RowIterator<T> {
-    fn len(&self) -> usize {
+impl<T: OrcDeserialize + Default> ExactSizeIterator for RowIterator<T> {}
+
+impl<T: OrcDeserialize + Default> RowIterator<T> {
+    /// Number of rows [`Iterator::next`] would yield, including those of batches
+    /// which are not decoded yet and fail to deserialize with
+    /// [`RowIterator::skip_errors`]
+    fn remaining_rows(&self) -> usize {
         let row_number = self.row_reader.get_row_number(); // number of the first row in the *current* batch
         if row_number == u64::MAX {
             // We didn't read anything yet
-            self.row_count
+            (self.end - self.start - self.skipped_rows(self.start, self.end))
                 .try_into()
                 .expect("row count overflows usize")
         } else {
             assert!(
-                row_number <= self.row_count,
+                row_number <= self.end,
                 "Iterated past the end (at row {})",
                 row_number
             );
-            let len_after_batch_start: usize = (self.row_count - row_number)
+            // Rows of the current batch which failed to deserialize are not in
+            // the decoded batch
+            let skipped_in_batch: u64 = self
+                .skipped_in_batch
+                .len()
+                .try_into()
+                .expect("skipped row count overflows u64");
+            let len_after_batch_start: usize = (self.end
+                - row_number
+                - self.skipped_rows(row_number, self.end)
+                - skipped_in_batch)
                 .try_into()
                 .expect("row count overflows usize");
             assert!(
@@ -196,3 +918,217 @@ impl<T: OrcDeserialize + Clone> ExactSizeIterator for RowIterator<T> {
         }
     }
 }
+
+/// Iterator on rows of the given [`RowReader`], which skips rows which cannot be
+/// deserialized instead of panicking.
+///
+/// It is constructed through [`RowIterator::skip_errors`].
+pub struct SkippingRowIterator<T: OrcDeserialize + Default>(RowIterator<T>);
+
+impl<T: OrcDeserialize + Default> SkippingRowIterator<T> {
+    /// Returns errors which occurred since the last call to this function, like
+    /// [`RowIterator::take_errors`].
+    pub fn take_errors(&mut self) -> Vec<(u64, DeserializationError)> {
+        self.0.take_errors()
+    }
+
+    /// Moves iteration to the given row (in the file), like [`RowIterator::seek`].
+    pub fn seek(self, row_number: u64) -> Self {
+        SkippingRowIterator(self.0.seek(row_number))
+    }
+
+    /// Returns the number of the row [`Iterator::next`] would yield next (or of
+    /// the first row after it, if it fails to deserialize), like
+    /// [`RowIterator::position`].
+    pub fn position(&self) -> u64 {
+        self.0.position()
+    }
+}
+
+impl<T: OrcDeserialize + Default> Iterator for SkippingRowIterator<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Rows of batches which are not decoded yet may be skipped
+        let decoded_len = self.0.decoded_items - self.0.index;
+        (decoded_len, Some(self.0.remaining_rows()))
+    }
+}
+
+impl<T: OrcDeserialize + Default> DoubleEndedIterator for SkippingRowIterator<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.next_back()
+    }
+}
+
+/// Iterator on rows of the given [`RowReader`], which yields deserialization
+/// errors instead of panicking.
+///
+/// It is constructed through [`RowIterator::try_rows`].
+pub struct TryRowIterator<T: OrcDeserialize + Default>(RowIterator<T>);
+
+impl<T: OrcDeserialize + Default> TryRowIterator<T> {
+    /// Returns the underlying [`RowReader`], so it can be reused by another iterator
+    #[cfg(feature = "rayon")]
+    pub(crate) fn into_row_reader(self) -> RowReader {
+        self.0.into_row_reader()
+    }
+}
+
+impl<T: OrcDeserialize + Default> Iterator for TryRowIterator<T> {
+    type Item = Result<T, DeserializationError>;
+
+    fn next(&mut self) -> Option<Result<T, DeserializationError>> {
+        self.0.try_next()
+    }
+}
+
+impl<T: OrcDeserialize + Default> DoubleEndedIterator for TryRowIterator<T> {
+    fn next_back(&mut self) -> Option<Result<T, DeserializationError>> {
+        self.0.try_next_back()
+    }
+}
+
+impl<T: OrcDeserialize + Default> ExactSizeIterator for TryRowIterator<T> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Iterator on the values of a single top-level column, which does not need a
+/// structure to deserialize rows to.
+///
+/// It is usually constructed through [`Reader::column_iter`].
+///
+/// # Panics
+///
+/// next() repeatedly calls [`OrcDeserialize::read_from_vector_batch`] and panics
+/// when it returns a [`DeserializationError`].
+pub struct ColumnIterator<T: OrcDeserialize + Default> {
+    row_reader: RowReader,
+    /// Kind of the column, passed to
+    /// [`OrcDeserialize::read_from_vector_batch_with_kind`]
+    kind: Kind,
+    batch: OwnedColumnVectorBatch,
+    decoded_batch: Vec<T>,
+
+    /// Index in the decoded batch
+    index: usize,
+
+    /// Maximum value of the index + 1
+    decoded_items: usize,
+
+    /// Number of rows in the batches read so far
+    read_rows: u64,
+}
+
+impl<T: OrcDeserialize + CheckableKind + Default> ColumnIterator<T> {
+    /// Returns an iterator on values of the top-level column `name` of the given
+    /// [`Reader`].
+    ///
+    /// Errors are either detailed descriptions of format mismatch (as returned by
+    /// [`CheckableKind::check_kind`], or C++ exceptions.
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is larger than `usize`.
+    pub fn new(
+        reader: &Reader,
+        name: &str,
+        batch_size: NonZeroU64,
+    ) -> Result<ColumnIterator<T>, OpenOrcError> {
+        let kind = match reader.kind() {
+            Kind::Struct(fields) => fields
+                .into_iter()
+                .find(|(field_name, _)| field_name == name)
+                .map(|(_, kind)| kind),
+            _ => None,
+        };
+        let kind =
+            kind.ok_or_else(|| OpenOrcError::KindError(format!("File has no column {:?}", name)))?;
+        match T::check_kind(&kind) {
+            Ok(_) => (),
+            Err(msg) => return Err(OpenOrcError::KindError(msg)),
+        }
+
+        let options = RowReaderOptions::default().include_names([name]);
+        let mut row_reader = reader
+            .row_reader(&options)
+            .map_err(OpenOrcError::OrcError)?;
+        let batch_size: u64 = batch_size.into();
+        let batch_size_usize = batch_size.try_into().expect("batch_size overflows usize");
+        let mut decoded_batch = Vec::with_capacity(batch_size_usize);
+        decoded_batch.resize_with(batch_size_usize, Default::default);
+        Ok(ColumnIterator {
+            batch: row_reader.row_batch(batch_size),
+            row_reader,
+            kind,
+            decoded_batch,
+            index: 0,
+            decoded_items: 0, // Will be filled on the first run of next()
+            read_rows: 0,
+        })
+    }
+}
+
+impl<T: OrcDeserialize + Default> ColumnIterator<T> {
+    /// Deserializes the column of `batch` into `decoded_batch`, and returns the
+    /// number of rows written in `decoded_batch`.
+    fn decode_batch(&mut self) -> usize {
+        let batch = self.batch.borrow();
+        let structs = batch
+            .try_into_structs()
+            .expect("Selected columns are not a structure");
+        let column = structs
+            .fields()
+            .into_iter()
+            .next()
+            .expect("Selected column disappeared");
+        T::read_from_vector_batch_with_kind(&column, &self.kind, &mut self.decoded_batch)
+            .unwrap_or_else(|e| {
+                panic!(
+                    "OrcDeserialize::read_from_vector_batch() call from ColumnIterator::next() returns a deserialization error: {:?}",
+                    e
+                )
+            })
+    }
+}
+
+/// # Panics
+///
+/// next() repeatedly calls [`OrcDeserialize::read_from_vector_batch`] and panics
+/// when it returns a [`DeserializationError`].
+impl<T: OrcDeserialize + Default> Iterator for ColumnIterator<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while self.index == self.decoded_items {
+            if !self.row_reader.read_into(&mut self.batch) {
+                return None;
+            }
+            self.read_rows += self.batch.borrow().num_elements();
+            self.index = 0;
+            self.decoded_items = self.decode_batch();
+        }
+
+        // Values are not reused, so they can be moved out of the decoded batch
+        let item = std::mem::take(&mut self.decoded_batch[self.index]);
+        self.index += 1;
+
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.row_reader.row_count() - self.read_rows)
+            .try_into()
+            .ok()
+            .and_then(|len: usize| len.checked_add(self.decoded_items - self.index));
+        (len.unwrap_or(usize::MAX), len)
+    }
+}
+
+impl<T: OrcDeserialize + Default> ExactSizeIterator for ColumnIterator<T> {}
@@ -0,0 +1,135 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Central registry of the expected schemas of datasets
+//!
+//! Applications register the [`Kind`] (or the [`CheckableKind`] type, such as a
+//! structure with `#[derive(OrcDeserialize)]`) expected for each dataset once, then
+//! open files with [`SchemaRegistry::open_checked`], which returns an error
+//! describing how the file's schema drifted from the expected one.
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::kind::Kind;
+//! use orcxx::schema_registry::{SchemaRegistry, SchemaRegistryError};
+//!
+//! let mut registry = SchemaRegistry::new();
+//! registry.register_kind("ints", Kind::new("struct<int1:int,long1:bigint>").unwrap());
+//!
+//! let orc_path = "orc/examples/TestOrcFile.test1.orc";
+//! match registry.open_checked(orc_path, "ints") {
+//!     Err(SchemaRegistryError::Drift { differences, .. }) => {
+//!         for difference in differences {
+//!             println!("{}", difference);
+//!         }
+//!     }
+//!     _ => panic!("Expected the schema to have drifted"),
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use deserialize::CheckableKind;
+use errors::{OrcError, ReaderError};
+use kind::{Kind, KindDifference};
+use reader::{InputStream, Reader};
+
+#[derive(Error, Debug)]
+pub enum SchemaRegistryError {
+    #[error("No schema is registered for dataset {0}")]
+    UnknownDataset(String),
+    #[error("Could not open ORC file: {0}")]
+    OrcError(#[from] OrcError),
+    #[error("Could not read ORC file: {0}")]
+    ReaderError(#[from] ReaderError),
+    /// The schema differs from a registered [`Kind`]
+    #[error("Schema of dataset {dataset} drifted:\n\t{}", differences.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("\n\t"))]
+    Drift {
+        dataset: String,
+        differences: Vec<KindDifference>,
+    },
+    /// The schema cannot be read by a registered [`CheckableKind`] type
+    #[error("Schema of dataset {dataset} is not supported: {message}")]
+    Unsupported { dataset: String, message: String },
+}
+
+#[derive(Debug, Clone)]
+enum ExpectedSchema {
+    Kind(Kind),
+    CheckableKind(fn(&Kind) -> Result<(), String>),
+}
+
+/// Maps dataset names to their expected schema
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    schemas: HashMap<String, ExpectedSchema>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> SchemaRegistry {
+        SchemaRegistry::default()
+    }
+
+    /// Registers the exact schema of a dataset, replacing any previously registered one
+    pub fn register_kind<S: Into<String>>(&mut self, dataset: S, kind: Kind) -> &mut Self {
+        self.schemas
+            .insert(dataset.into(), ExpectedSchema::Kind(kind));
+        self
+    }
+
+    /// Registers a type which files of a dataset must be deserializable to,
+    /// replacing any previously registered schema
+    pub fn register<T: CheckableKind, S: Into<String>>(&mut self, dataset: S) -> &mut Self {
+        self.schemas
+            .insert(dataset.into(), ExpectedSchema::CheckableKind(T::check_kind));
+        self
+    }
+
+    /// Returns whether a schema is registered for this dataset
+    pub fn contains(&self, dataset: &str) -> bool {
+        self.schemas.contains_key(dataset)
+    }
+
+    /// Checks a schema against the one registered for the dataset
+    pub fn check(&self, dataset: &str, kind: &Kind) -> Result<(), SchemaRegistryError> {
+        match self.schemas.get(dataset) {
+            None => Err(SchemaRegistryError::UnknownDataset(dataset.to_owned())),
+            Some(ExpectedSchema::Kind(expected_kind)) => {
+                let differences = expected_kind.differences(kind);
+                if differences.is_empty() {
+                    Ok(())
+                } else {
+                    Err(SchemaRegistryError::Drift {
+                        dataset: dataset.to_owned(),
+                        differences,
+                    })
+                }
+            }
+            Some(ExpectedSchema::CheckableKind(check_kind)) => {
+                check_kind(kind).map_err(|message| SchemaRegistryError::Unsupported {
+                    dataset: dataset.to_owned(),
+                    message,
+                })
+            }
+        }
+    }
+
+    /// Opens a local ORC file, and checks its schema against the one registered for
+    /// the dataset.
+    ///
+    /// In [`SchemaRegistryError::Drift`], the registered schema is the "first" type
+    /// of each [`KindDifference`], and the file's is the "second" type.
+    pub fn open_checked(&self, path: &str, dataset: &str) -> Result<Reader, SchemaRegistryError> {
+        if !self.contains(dataset) {
+            return Err(SchemaRegistryError::UnknownDataset(dataset.to_owned()));
+        }
+        let reader = Reader::new(InputStream::from_local_file(path)?)?;
+        self.check(dataset, &reader.kind())?;
+        Ok(reader)
+    }
+}
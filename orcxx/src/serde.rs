@@ -0,0 +1,293 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Deserialization of ORC rows with [`serde`](https://docs.rs/serde/), as an
+//! alternative to `orcxx_derive`.
+//!
+//! This allows reusing types which already implement `Deserialize`, and serde's
+//! features which `orcxx_derive` does not have, such as enums or flattening.
+//! It is slower than `orcxx_derive`, as rows are first converted to [`OrcValue`]s.
+//!
+//! Values are mapped to the serde data model as follows:
+//!
+//! * integers, floating-point numbers, booleans and strings to their serde
+//!   counterparts (dates are numbers of days since the epoch)
+//! * binaries to byte buffers, which can also be deserialized as sequences
+//! * decimals to strings
+//! * timestamps to structs with `seconds` and `nanoseconds` fields
+//! * lists to sequences, maps and structs to maps
+//! * nulls to `None` (or unit)
+//!
+//! Strings deserialize to enum unit variants, and maps with a single entry
+//! to other variants.
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate orcxx;
+//! extern crate serde;
+//!
+//! use serde::Deserialize;
+//!
+//! use orcxx::reader::{InputStream, Reader, RowReaderOptions};
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! #[serde(rename_all = "lowercase")]
+//! enum Greeting {
+//!     Hi,
+//!     Bye,
+//! }
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! struct Row {
+//!     int1: i32,
+//!     string1: Greeting,
+//! }
+//!
+//! let input_stream = InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+//!     .expect("Could not open .orc");
+//! let reader = Reader::new(input_stream).expect("Could not read .orc");
+//! let options = RowReaderOptions::default().include_names(["int1", "string1"]);
+//! let mut row_reader = reader.row_reader(&options).unwrap();
+//! let mut batch = row_reader.row_batch(1024);
+//! assert!(row_reader.read_into(&mut batch));
+//!
+//! let rows: Vec<Row> =
+//!     orcxx::serde::from_vector_batch(batch.borrow(), &row_reader.selected_kind()).unwrap();
+//! assert_eq!(
+//!     rows,
+//!     vec![
+//!         Row { int1: 65536, string1: Greeting::Hi },
+//!         Row { int1: 65536, string1: Greeting::Bye },
+//!     ]
+//! );
+//! ```
+
+use std::fmt;
+
+use serde_lib::de::value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer};
+use serde_lib::de::{
+    self, DeserializeOwned, Deserializer, EnumAccess, IntoDeserializer, VariantAccess, Visitor,
+};
+use serde_lib::forward_to_deserialize_any;
+use thiserror::Error;
+
+use kind::Kind;
+use value::OrcValue;
+use vector::BorrowedColumnVectorBatch;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SerdeError {
+    #[error("ORC type {0:?} cannot be deserialized with serde")]
+    UnsupportedKind(Kind),
+    #[error("Could not deserialize row {row}: {message}")]
+    Row { row: usize, message: String },
+    #[error("{0}")]
+    Custom(String),
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError::Custom(msg.to_string())
+    }
+}
+
+/// Deserializes each row of a vector batch of the given kind (usually
+/// [`RowReader::selected_kind`](::reader::RowReader::selected_kind)).
+///
+/// # Panics
+///
+/// If `batch` is not a vector batch of type `kind`.
+pub fn from_vector_batch<T: DeserializeOwned>(
+    batch: BorrowedColumnVectorBatch<'_>,
+    kind: &Kind,
+) -> Result<Vec<T>, SerdeError> {
+    check_kind(kind)?;
+    OrcValue::rows_from_vector_batch(batch, kind)
+        .map_err(|e| SerdeError::Custom(e.to_string()))?
+        .into_iter()
+        .enumerate()
+        .map(|(row, value)| {
+            T::deserialize(value).map_err(|e| SerdeError::Row {
+                row,
+                message: e.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Returns an error if `kind` contains types which cannot be converted to
+/// [`OrcValue`]s
+fn check_kind(kind: &Kind) -> Result<(), SerdeError> {
+    match kind {
+        Kind::Union(_) | Kind::TimestampInstant => Err(SerdeError::UnsupportedKind(kind.clone())),
+        Kind::List(element) => check_kind(element),
+        Kind::Map { key, value } => {
+            check_kind(key)?;
+            check_kind(value)
+        }
+        Kind::Struct(fields) => fields.iter().try_for_each(|(_, field)| check_kind(field)),
+        _ => Ok(()),
+    }
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for OrcValue {
+    type Deserializer = OrcValue;
+
+    fn into_deserializer(self) -> OrcValue {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for OrcValue {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            OrcValue::Null => visitor.visit_none(),
+            OrcValue::Boolean(b) => visitor.visit_bool(b),
+            OrcValue::Byte(n) => visitor.visit_i8(n),
+            OrcValue::Short(n) => visitor.visit_i16(n),
+            OrcValue::Int(n) => visitor.visit_i32(n),
+            OrcValue::Long(n) | OrcValue::Date(n) => visitor.visit_i64(n),
+            OrcValue::Float(n) => visitor.visit_f32(n),
+            OrcValue::Double(n) => visitor.visit_f64(n),
+            OrcValue::String(s) => visitor.visit_string(s),
+            OrcValue::Binary(b) => visitor.visit_byte_buf(b),
+            OrcValue::Decimal(d) => visitor.visit_string(d.to_string()),
+            OrcValue::Timestamp(ts) => visitor.visit_map(MapDeserializer::new(
+                vec![("seconds", ts.seconds), ("nanoseconds", ts.nanoseconds)].into_iter(),
+            )),
+            OrcValue::List(elements) => {
+                visitor.visit_seq(SeqDeserializer::new(elements.into_iter()))
+            }
+            OrcValue::Map(entries) => visitor.visit_map(MapDeserializer::new(entries.into_iter())),
+            OrcValue::Struct(fields) => visitor.visit_map(MapDeserializer::new(fields.into_iter())),
+            OrcValue::Union { value, .. } => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            OrcValue::Null => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            OrcValue::Null => visitor.visit_unit(),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            // So binaries can be deserialized to Vec<u8>
+            OrcValue::Binary(b) => visitor.visit_seq(SeqDeserializer::new(b.into_iter())),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            OrcValue::String(s) => visitor.visit_byte_buf(s.into_bytes()),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self {
+            OrcValue::String(variant) => visitor.visit_enum(UnitVariant(variant)),
+            OrcValue::Map(entries) => {
+                MapAccessDeserializer::new(MapDeserializer::new(entries.into_iter()))
+                    .deserialize_enum(name, variants, visitor)
+            }
+            OrcValue::Struct(fields) => {
+                MapAccessDeserializer::new(MapDeserializer::new(fields.into_iter()))
+                    .deserialize_enum(name, variants, visitor)
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Variant of an enum, represented by its name
+struct UnitVariant(String);
+
+impl<'de> EnumAccess<'de> for UnitVariant {
+    type Error = SerdeError;
+    type Variant = UnitVariant;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, UnitVariant), SerdeError> {
+        let variant = seed.deserialize(OrcValue::String(self.0.clone()))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for UnitVariant {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        _seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"newtype variant",
+        ))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"struct variant",
+        ))
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Typed column statistics of ORC files and stripes.
+//!
+//! Returned by [`Reader::statistics`](::reader::Reader::statistics) for the
+//! whole file, and [`Reader::stripe_statistics`](::reader::Reader::stripe_statistics)
+//! for a single stripe.
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate orcxx;
+//! use orcxx::reader::{InputStream, Reader};
+//! use orcxx::statistics::ColumnStatistics;
+//!
+//! let input_stream = InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+//!     .expect("Could not open .orc");
+//! let reader = Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let statistics = reader.statistics().expect("File has no statistics");
+//! let byte1 = statistics[2].as_ref().unwrap(); // Column 2 is 'byte1'
+//! assert_eq!(byte1.number_of_values, 2);
+//! assert_eq!(
+//!     byte1.statistics,
+//!     ColumnStatistics::Integer {
+//!         min: 1,
+//!         max: 100,
+//!         sum: Some(101)
+//!     }
+//! );
+//! ```
+
+use std::str::FromStr;
+
+use cxx::let_cxx_string;
+use rust_decimal::Decimal;
+
+use reader::ffi;
+use Timestamp;
+
+/// Type-specific statistics of a column.
+///
+/// Columns whose type has no specific statistics, or whose values are all null
+/// (so they have no minimum and maximum), have [`ColumnStatistics::Generic`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnStatistics {
+    Generic,
+    Boolean {
+        false_count: u64,
+        true_count: u64,
+    },
+    /// Integer types, booleans excluded. `sum` is `None` if it overflowed.
+    Integer {
+        min: i64,
+        max: i64,
+        sum: Option<i64>,
+    },
+    /// Floating-point types
+    Double {
+        min: f64,
+        max: f64,
+        sum: Option<f64>,
+    },
+    /// String, varchar, and char types
+    String {
+        min: Vec<u8>,
+        max: Vec<u8>,
+        total_length: Option<u64>,
+    },
+    Binary {
+        total_length: Option<u64>,
+    },
+    /// `sum` is `None` if it overflowed.
+    Decimal {
+        min: Decimal,
+        max: Decimal,
+        sum: Option<Decimal>,
+    },
+    /// Numbers of days since the epoch
+    Date {
+        min: i64,
+        max: i64,
+    },
+    Timestamp {
+        min: Timestamp,
+        max: Timestamp,
+    },
+}
+
+/// Statistics of a column in a file or stripe
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSummary {
+    /// Number of non-null values
+    pub number_of_values: u64,
+    pub has_null: bool,
+    pub statistics: ColumnStatistics,
+}
+
+impl ColumnSummary {
+    pub(crate) fn new(statistics: &ffi::ColumnStatistics) -> ColumnSummary {
+        ColumnSummary {
+            number_of_values: statistics.getNumberOfValues(),
+            has_null: statistics.hasNull(),
+            statistics: ColumnStatistics::new(statistics),
+        }
+    }
+}
+
+impl ColumnStatistics {
+    fn new(statistics: &ffi::ColumnStatistics) -> ColumnStatistics {
+        let (mut has_range, mut has_sum) = (false, false);
+
+        let (mut false_count, mut true_count) = (0u64, 0u64);
+        if ffi::ColumnStatistics_boolean(
+            statistics,
+            &mut has_range,
+            &mut false_count,
+            &mut true_count,
+        ) {
+            return if has_range {
+                ColumnStatistics::Boolean {
+                    false_count,
+                    true_count,
+                }
+            } else {
+                ColumnStatistics::Generic
+            };
+        }
+
+        let (mut min, mut max, mut sum) = (0i64, 0i64, 0i64);
+        if ffi::ColumnStatistics_integer(
+            statistics,
+            &mut has_range,
+            &mut min,
+            &mut max,
+            &mut has_sum,
+            &mut sum,
+        ) {
+            return if has_range {
+                ColumnStatistics::Integer {
+                    min,
+                    max,
+                    sum: if has_sum { Some(sum) } else { None },
+                }
+            } else {
+                ColumnStatistics::Generic
+            };
+        }
+
+        let (mut min, mut max, mut sum) = (0f64, 0f64, 0f64);
+        if ffi::ColumnStatistics_double(
+            statistics,
+            &mut has_range,
+            &mut min,
+            &mut max,
+            &mut has_sum,
+            &mut sum,
+        ) {
+            return if has_range {
+                ColumnStatistics::Double {
+                    min,
+                    max,
+                    sum: if has_sum { Some(sum) } else { None },
+                }
+            } else {
+                ColumnStatistics::Generic
+            };
+        }
+
+        let mut total_length = 0u64;
+        let_cxx_string!(min = "");
+        let_cxx_string!(max = "");
+        if ffi::ColumnStatistics_string(
+            statistics,
+            &mut has_range,
+            min.as_mut(),
+            max.as_mut(),
+            &mut has_sum,
+            &mut total_length,
+        ) {
+            return if has_range {
+                ColumnStatistics::String {
+                    min: min.as_bytes().to_vec(),
+                    max: max.as_bytes().to_vec(),
+                    total_length: if has_sum { Some(total_length) } else { None },
+                }
+            } else {
+                ColumnStatistics::Generic
+            };
+        }
+
+        if ffi::ColumnStatistics_binary(statistics, &mut has_sum, &mut total_length) {
+            return ColumnStatistics::Binary {
+                total_length: if has_sum { Some(total_length) } else { None },
+            };
+        }
+
+        let_cxx_string!(sum = "");
+        if ffi::ColumnStatistics_decimal(
+            statistics,
+            &mut has_range,
+            min.as_mut(),
+            max.as_mut(),
+            &mut has_sum,
+            sum.as_mut(),
+        ) {
+            let parse = |s: &cxx::CxxString| Decimal::from_str(s.to_str().ok()?).ok();
+            // Decimals with more than 28 digits do not fit in rust_decimal
+            return match (has_range, parse(&min), parse(&max)) {
+                (true, Some(min), Some(max)) => ColumnStatistics::Decimal {
+                    min,
+                    max,
+                    sum: if has_sum { parse(&sum) } else { None },
+                },
+                _ => ColumnStatistics::Generic,
+            };
+        }
+
+        let (mut min, mut max) = (0i32, 0i32);
+        if ffi::ColumnStatistics_date(statistics, &mut has_range, &mut min, &mut max) {
+            return if has_range {
+                ColumnStatistics::Date {
+                    min: min.into(),
+                    max: max.into(),
+                }
+            } else {
+                ColumnStatistics::Generic
+            };
+        }
+
+        let (mut min_millis, mut min_nanos, mut max_millis, mut max_nanos) =
+            (0i64, 0i32, 0i64, 0i32);
+        if ffi::ColumnStatistics_timestamp(
+            statistics,
+            &mut has_range,
+            &mut min_millis,
+            &mut min_nanos,
+            &mut max_millis,
+            &mut max_nanos,
+        ) {
+            return if has_range {
+                ColumnStatistics::Timestamp {
+                    min: timestamp_from_millis(min_millis, min_nanos),
+                    max: timestamp_from_millis(max_millis, max_nanos),
+                }
+            } else {
+                ColumnStatistics::Generic
+            };
+        }
+
+        ColumnStatistics::Generic
+    }
+}
+
+/// Builds a [`Timestamp`] from milliseconds since the epoch, and nanoseconds
+/// within that millisecond
+fn timestamp_from_millis(millis: i64, nanos: i32) -> Timestamp {
+    Timestamp {
+        seconds: millis.div_euclid(1000),
+        nanoseconds: millis.rem_euclid(1000) * 1_000_000 + i64::from(nanos),
+    }
+}
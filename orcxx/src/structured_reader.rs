@@ -26,6 +26,7 @@ use vector::ColumnVectorBatch;
 pub struct StructuredRowReader<'a> {
     inner: &'a mut RowReader,
     vector_batch: vector::OwnedColumnVectorBatch,
+    size: u64,
 }
 
 impl<'a> StructuredRowReader<'a> {
@@ -36,6 +37,7 @@ impl<'a> StructuredRowReader<'a> {
         StructuredRowReader {
             vector_batch: row_reader.row_batch(size),
             inner: row_reader,
+            size,
         }
     }
 
@@ -57,6 +59,82 @@ impl<'a> StructuredRowReader<'a> {
             &self.inner.selected_kind(),
         ))
     }
+
+    /// Same as [`StructuredRowReader::next`], but reads into a new vector batch,
+    /// which is returned instead of being reused.
+    ///
+    /// This allocates a vector batch for every call, but the result can be kept
+    /// while reading the next batches, or sent to another thread.
+    pub fn next_owned(&mut self) -> Option<OwnedColumnTree> {
+        let mut vector_batch = self.inner.row_batch(self.size);
+        if !self.inner.read_into(&mut vector_batch) {
+            // No more batches.
+            return None;
+        }
+
+        Some(OwnedColumnTree {
+            vector_batch,
+            kind: self.inner.selected_kind(),
+        })
+    }
+
+    /// Calls `f` on every remaining batch of columns.
+    ///
+    /// This is the same as calling [`StructuredRowReader::next`] in a loop, so it
+    /// reuses the same data buffer.
+    pub fn for_each_batch<F: FnMut(ColumnTree<'_>)>(&mut self, mut f: F) {
+        while let Some(columns) = self.next() {
+            f(columns)
+        }
+    }
+
+    /// Returns an iterator on the remaining batches of columns, which calls
+    /// [`StructuredRowReader::next_owned`], so it can be used with iterator adapters.
+    pub fn owned_batches<'b>(&'b mut self) -> OwnedColumnTrees<'a, 'b> {
+        OwnedColumnTrees(self)
+    }
+}
+
+/// Iterator on batches of columns returned by [`StructuredRowReader::owned_batches`]
+pub struct OwnedColumnTrees<'a, 'b>(&'b mut StructuredRowReader<'a>);
+
+impl Iterator for OwnedColumnTrees<'_, '_> {
+    type Item = OwnedColumnTree;
+
+    fn next(&mut self) -> Option<OwnedColumnTree> {
+        self.0.next_owned()
+    }
+}
+
+/// A vector batch and its type, returned by [`StructuredRowReader::next_owned`]
+///
+/// Unlike [`ColumnTree`], this does not borrow from the [`StructuredRowReader`].
+#[derive(Debug)]
+pub struct OwnedColumnTree {
+    vector_batch: vector::OwnedColumnVectorBatch,
+    kind: Kind,
+}
+
+impl OwnedColumnTree {
+    /// Returns the columns of the vector batch, structured like [`ColumnTree`]
+    /// returned by [`StructuredRowReader::next`]
+    pub fn borrow(&self) -> ColumnTree<'_> {
+        columnvectorbatch_to_columntree(self.vector_batch.borrow(), &self.kind)
+    }
+
+    /// Returns the type of the vector batch
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    /// Returns the number of rows in the vector batch
+    pub fn num_elements(&self) -> u64 {
+        self.vector_batch.borrow().num_elements()
+    }
+
+    pub fn into_vector_batch(self) -> vector::OwnedColumnVectorBatch {
+        self.vector_batch
+    }
 }
 
 /// A set of columns from ORC file
@@ -81,7 +159,9 @@ pub enum ColumnTree<'a> {
     ///
     /// The `offsets` are ranges in the `elements` vector.
     ///
-    /// None values in `offsets` indicates a null instead of a list.
+    /// None values in `offsets` indicates a null instead of a list. Null lists have
+    /// no elements, and elements have their own nullability (eg. a `ColumnTree::Struct`
+    /// with `not_null`), independently of the lists.
     ///
     /// Therefore, offsets.collect().len() is exactly the number of lists.
     List {
@@ -92,6 +172,9 @@ pub enum ColumnTree<'a> {
     ///
     /// The `offsets` are ranges in the `keys` and `elements` vectors.
     ///
+    /// Like lists, null maps have no entries, and keys and elements have their own
+    /// nullability. Keys may be null, even though ORC writers usually forbid it.
+    ///
     /// Therefore, offsets.len() is exactly the number of maps.
     Map {
         offsets: vector::RangeVectorBatchIterator<'a>,
@@ -101,8 +184,8 @@ pub enum ColumnTree<'a> {
     /// Pairs of (field_name, column_tree)
     ///
     /// if not [`None`], `not_null` is an array of booleans indicating which rows
-    /// are present, so there are exactly as many values in the child `ColumnTree`s
-    /// as there are true values in `not_null`.
+    /// are present. Child `ColumnTree`s have exactly `num_elements` values, including
+    /// for rows where the struct is null (which are null in the children as well).
     Struct {
         not_null: Option<&'a [i8]>,
         num_elements: u64, // TODO: deduplicate this with the not_null slice size?
@@ -110,10 +193,11 @@ pub enum ColumnTree<'a> {
     },
     Decimal64(vector::Decimal64VectorBatch<'a>),
     Decimal128(vector::Decimal128VectorBatch<'a>),
-    TimestampInstant, // TODO
+    /// Timestamps with local time zone, which are stored as instants in UTC
+    TimestampInstant(vector::TimestampVectorBatch<'a>),
 }
 
-fn columnvectorbatch_to_columntree<'a>(
+pub(crate) fn columnvectorbatch_to_columntree<'a>(
     vector_batch: vector::BorrowedColumnVectorBatch<'a>,
     kind: &Kind,
 ) -> ColumnTree<'a> {
@@ -233,6 +317,10 @@ fn columnvectorbatch_to_columntree<'a>(
                     .expect("Failed to cast decimal vector_batch"),
             ),
         },
-        Kind::TimestampInstant => ColumnTree::TimestampInstant, // TODO
+        Kind::TimestampInstant => ColumnTree::TimestampInstant(
+            vector_batch
+                .try_into_timestamps()
+                .expect("Failed to cast timestamp instants vector batch"),
+        ),
     }
 }
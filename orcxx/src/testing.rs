@@ -0,0 +1,171 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Helpers to test code on arbitrary ORC schemas
+//!
+//! The example files shipped with Apache ORC only cover a few schemas; this
+//! generates random rows for any [`Kind`] (deep nesting, unions, bounded
+//! `varchar`s, ...), which property-based tests can use as input.
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::kind::Kind;
+//! use orcxx::testing::generate_rows;
+//!
+//! let kind = Kind::Struct(vec![
+//!     ("id".to_owned(), Kind::Long),
+//!     ("tags".to_owned(), Kind::List(Box::new(Kind::Varchar(8)))),
+//! ]);
+//! let rows = generate_rows(&kind, 100, 42);
+//! assert_eq!(rows.len(), 100);
+//! assert!(rows.iter().all(|row| row.matches_kind(&kind)));
+//! assert_eq!(rows, generate_rows(&kind, 100, 42));
+//! ```
+
+use rust_decimal::Decimal;
+
+use kind::Kind;
+use value::OrcValue;
+use Timestamp;
+
+/// Probability for any value (except rows themselves) to be null is
+/// `1 / NULL_RATIO`
+const NULL_RATIO: u64 = 8;
+/// Maximum number of elements of generated lists and maps
+const MAX_CONTAINER_LENGTH: u64 = 4;
+/// Maximum length of generated strings (in characters) and binaries (in bytes)
+const MAX_STRING_LENGTH: u64 = 16;
+/// Characters generated strings are made of; includes multi-byte ones to test UTF-8
+/// handling
+const CHARACTERS: &[char] = &[
+    'a', 'b', 'c', 'x', 'y', 'z', 'A', 'Z', '0', '9', ' ', '-', '_', '"', '\\', 'é', 'ß', '€',
+    '日', '🦀',
+];
+/// Maximum precision of [`Decimal`] values
+const MAX_DECIMAL_PRECISION: u64 = 28;
+
+/// SplitMix64 pseudo-random number generator.
+///
+/// Implemented here rather than using an external crate, so generated rows never
+/// change for a given seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns an integer in `0..bound`
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    fn string(&mut self, max_length: u64) -> String {
+        let length = self.below(max_length + 1);
+        (0..length)
+            .map(|_| CHARACTERS[self.below(CHARACTERS.len() as u64) as usize])
+            .collect()
+    }
+}
+
+/// Returns `n` random rows of the given kind, as a function of `seed`.
+///
+/// Rows themselves are never null, but any value within them may be (and values
+/// of unions without variants always are). Decimals are limited to 28 digits, as they are represented by [`Decimal`].
+pub fn generate_rows(kind: &Kind, n: usize, seed: u64) -> Vec<OrcValue> {
+    let mut rng = Rng(seed);
+    (0..n).map(|_| generate_value(kind, &mut rng)).collect()
+}
+
+fn generate_nullable_value(kind: &Kind, rng: &mut Rng) -> OrcValue {
+    if rng.below(NULL_RATIO) == 0 {
+        OrcValue::Null
+    } else {
+        generate_value(kind, rng)
+    }
+}
+
+fn generate_value(kind: &Kind, rng: &mut Rng) -> OrcValue {
+    match kind {
+        Kind::Boolean => OrcValue::Boolean(rng.bool()),
+        Kind::Byte => OrcValue::Byte(rng.next_u64() as i8),
+        Kind::Short => OrcValue::Short(rng.next_u64() as i16),
+        Kind::Int => OrcValue::Int(rng.next_u64() as i32),
+        Kind::Long => OrcValue::Long(rng.next_u64() as i64),
+        Kind::Float => OrcValue::Float((rng.next_u64() as i32) as f32 / 1024.),
+        Kind::Double => OrcValue::Double((rng.next_u64() as i64) as f64 / 1048576.),
+        Kind::String => OrcValue::String(rng.string(MAX_STRING_LENGTH)),
+        Kind::Varchar(max_length) | Kind::Char(max_length) => {
+            OrcValue::String(rng.string(u64::min(*max_length, MAX_STRING_LENGTH)))
+        }
+        Kind::Binary => {
+            let length = rng.below(MAX_STRING_LENGTH + 1);
+            OrcValue::Binary((0..length).map(|_| rng.next_u64() as u8).collect())
+        }
+        Kind::Timestamp | Kind::TimestampInstant => OrcValue::Timestamp(Timestamp {
+            // Between 1900 and 2100
+            seconds: rng.below(200 * 365 * 86400) as i64 - 70 * 365 * 86400,
+            nanoseconds: rng.below(1_000_000_000) as i64,
+        }),
+        Kind::Date => OrcValue::Date(rng.below(200 * 365) as i64 - 70 * 365),
+        Kind::Decimal { precision, scale } => {
+            let precision = u64::min(*precision, MAX_DECIMAL_PRECISION) as u32;
+            let max_mantissa = 10u128.pow(precision);
+            let mantissa =
+                (u128::from(rng.next_u64()) << 64 | u128::from(rng.next_u64())) % max_mantissa;
+            // Cannot overflow, because max_mantissa is at most 10^28
+            let mantissa = mantissa as i128;
+            let mantissa = if rng.bool() { -mantissa } else { mantissa };
+            OrcValue::Decimal(Decimal::from_i128_with_scale(
+                mantissa,
+                u64::min(*scale, MAX_DECIMAL_PRECISION) as u32,
+            ))
+        }
+        Kind::List(element_kind) => {
+            let length = rng.below(MAX_CONTAINER_LENGTH + 1);
+            OrcValue::List(
+                (0..length)
+                    .map(|_| generate_nullable_value(element_kind, rng))
+                    .collect(),
+            )
+        }
+        Kind::Map { key, value } => {
+            let length = rng.below(MAX_CONTAINER_LENGTH + 1);
+            OrcValue::Map(
+                (0..length)
+                    .map(|_| {
+                        // ORC allows null keys, but most readers don't
+                        let entry_key = generate_value(key, rng);
+                        (entry_key, generate_nullable_value(value, rng))
+                    })
+                    .collect(),
+            )
+        }
+        Kind::Struct(fields) => OrcValue::Struct(
+            fields
+                .iter()
+                .map(|(name, field_kind)| (name.clone(), generate_nullable_value(field_kind, rng)))
+                .collect(),
+        ),
+        // Unions without variants cannot hold any value
+        Kind::Union(variants) if variants.is_empty() => OrcValue::Null,
+        Kind::Union(variants) => {
+            let tag = rng.below(variants.len() as u64);
+            OrcValue::Union {
+                tag: tag as u8,
+                value: Box::new(generate_nullable_value(&variants[tag as usize], rng)),
+            }
+        }
+    }
+}
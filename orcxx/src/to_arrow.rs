@@ -0,0 +1,471 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Converts ORC vector batches to Arrow record batches and arrays
+//!
+//! Arrow arrays own their buffers, so values are copied out of the ORC vector batch;
+//! 64-bit integer and floating-point columns without nulls, and strings and binaries
+//! stored contiguously, are copied in bulk, as ORC and Arrow represent them the same
+//! way.
+//!
+//! Strings which are not valid UTF-8 make conversion fail, as Arrow strings must be
+//! valid UTF-8; use `binary` columns for arbitrary bytes.
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::*;
+//!
+//! let orc_path = "orc/examples/TestOrcFile.test1.orc";
+//! let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+//! let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let mut row_reader = reader.row_reader(&reader::RowReaderOptions::default()).unwrap();
+//! let kind = row_reader.selected_kind();
+//! let mut batch = row_reader.row_batch(1024);
+//! assert!(row_reader.read_into(&mut batch));
+//!
+//! let record_batch = to_arrow::vector_batch_to_record_batch(batch.borrow(), &kind)
+//!     .expect("Could not convert to Arrow");
+//! assert_eq!(record_batch.num_rows(), 2);
+//! ```
+
+use std::convert::TryInto;
+use std::ops::Range;
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, BinaryArray, BooleanArray, Date32Array, Decimal128Array, Float32Array, Float64Array,
+    Int16Array, Int32Array, Int64Array, Int8Array, ListArray, MapArray, RecordBatch,
+    RecordBatchOptions, StringArray, StructArray, TimestampNanosecondArray,
+};
+use arrow::buffer::{Buffer, NullBuffer, OffsetBuffer, ScalarBuffer};
+use arrow::datatypes::{DataType, Field, FieldRef, Fields, Schema, SchemaRef, TimeUnit};
+use arrow::error::ArrowError;
+use thiserror::Error;
+
+use kind::Kind;
+use structured_reader::{columnvectorbatch_to_columntree, ColumnTree};
+use vector::{
+    BorrowedColumnVectorBatch, DecimalVectorBatch, StringVectorBatch, TimestampVectorBatch,
+};
+
+#[derive(Error, Debug)]
+pub enum ToArrowError {
+    #[error("ORC type {0:?} cannot be converted to Arrow")]
+    UnsupportedKind(Kind),
+    #[error("ORC root type must be a struct, not {0:?}")]
+    NotAStruct(Kind),
+    #[error("Timestamp {seconds}s {nanoseconds}ns overflows 64-bits nanoseconds")]
+    TimestampOverflow { seconds: i64, nanoseconds: i64 },
+    #[error("Offsets of list or map are not contiguous: {0:?}")]
+    NonContiguousOffsets(Range<usize>),
+    #[error("Offset {0} does not fit in i32")]
+    OffsetOverflow(usize),
+    #[error(
+        "Decimal {mantissa} with scale {scale} cannot be represented with scale {target_scale}"
+    )]
+    DecimalOutOfRange {
+        mantissa: i128,
+        scale: u32,
+        target_scale: u32,
+    },
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] ArrowError),
+}
+
+fn list_item_field(kind: &Kind) -> Result<FieldRef, ToArrowError> {
+    Ok(Arc::new(Field::new(
+        "item",
+        kind_to_arrow_datatype(kind)?,
+        true,
+    )))
+}
+
+fn map_entries_field(key: &Kind, value: &Kind) -> Result<FieldRef, ToArrowError> {
+    Ok(Arc::new(Field::new(
+        "entries",
+        DataType::Struct(map_entries_fields(key, value)?),
+        false,
+    )))
+}
+
+fn map_entries_fields(key: &Kind, value: &Kind) -> Result<Fields, ToArrowError> {
+    Ok(Fields::from(vec![
+        Field::new("key", kind_to_arrow_datatype(key)?, false),
+        Field::new("value", kind_to_arrow_datatype(value)?, true),
+    ]))
+}
+
+fn struct_fields(fields: &[(String, Kind)]) -> Result<Fields, ToArrowError> {
+    fields
+        .iter()
+        .map(|(name, kind)| Ok(Field::new(name, kind_to_arrow_datatype(kind)?, true)))
+        .collect()
+}
+
+/// Returns the Arrow type used to represent the given ORC type
+pub fn kind_to_arrow_datatype(kind: &Kind) -> Result<DataType, ToArrowError> {
+    Ok(match kind {
+        Kind::Boolean => DataType::Boolean,
+        Kind::Byte => DataType::Int8,
+        Kind::Short => DataType::Int16,
+        Kind::Int => DataType::Int32,
+        Kind::Long => DataType::Int64,
+        Kind::Float => DataType::Float32,
+        Kind::Double => DataType::Float64,
+        Kind::String | Kind::Varchar(_) | Kind::Char(_) => DataType::Utf8,
+        Kind::Binary => DataType::Binary,
+        Kind::Timestamp => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        // Instants are stored in UTC
+        Kind::TimestampInstant => DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into())),
+        Kind::Date => DataType::Date32,
+        Kind::Decimal { precision, scale } => DataType::Decimal128(
+            (*precision)
+                .try_into()
+                .map_err(|_| ToArrowError::UnsupportedKind(kind.clone()))?,
+            (*scale)
+                .try_into()
+                .map_err(|_| ToArrowError::UnsupportedKind(kind.clone()))?,
+        ),
+        Kind::List(item) => DataType::List(list_item_field(item)?),
+        Kind::Map { key, value } => DataType::Map(map_entries_field(key, value)?, false),
+        Kind::Struct(fields) => DataType::Struct(struct_fields(fields)?),
+        Kind::Union(_) => return Err(ToArrowError::UnsupportedKind(kind.clone())),
+    })
+}
+
+/// Returns the Arrow schema used to represent rows of the given ORC type,
+/// which must be a struct.
+pub fn kind_to_arrow_schema(kind: &Kind) -> Result<Schema, ToArrowError> {
+    match kind {
+        Kind::Struct(fields) => Ok(Schema::new(struct_fields(fields)?)),
+        _ => Err(ToArrowError::NotAStruct(kind.clone())),
+    }
+}
+
+/// Converts ORC ranges (with `None` for null lists/maps) to Arrow offsets and
+/// null buffer.
+fn ranges_to_offsets<I: Iterator<Item = Option<Range<usize>>>>(
+    ranges: I,
+) -> Result<(OffsetBuffer<i32>, Option<NullBuffer>), ToArrowError> {
+    let to_i32 = |offset: usize| {
+        offset
+            .try_into()
+            .map_err(|_| ToArrowError::OffsetOverflow(offset))
+    };
+    let mut ranges = ranges.peekable();
+    let first_offset = match ranges.peek() {
+        Some(Some(range)) => range.start,
+        _ => 0,
+    };
+    let mut last_offset = first_offset;
+    let mut offsets = vec![to_i32(first_offset)?];
+    let mut not_null = Vec::new();
+    for range in ranges {
+        match range {
+            Some(range) => {
+                if range.start != last_offset {
+                    return Err(ToArrowError::NonContiguousOffsets(range));
+                }
+                last_offset = range.end;
+                not_null.push(true);
+            }
+            None => not_null.push(false),
+        }
+        offsets.push(to_i32(last_offset)?);
+    }
+
+    let nulls = if not_null.iter().all(|&b| b) {
+        None
+    } else {
+        Some(NullBuffer::from(not_null))
+    };
+    Ok((OffsetBuffer::new(ScalarBuffer::from(offsets)), nulls))
+}
+
+/// Given a set of columns (as a [`ColumnTree`]) and its ORC type, returns an
+/// Arrow array.
+///
+/// # Panics
+///
+/// If `tree` was not built from a vector batch of type `kind`.
+pub fn columntree_to_arrow_array(
+    tree: ColumnTree<'_>,
+    kind: &Kind,
+) -> Result<ArrayRef, ToArrowError> {
+    Ok(match tree {
+        ColumnTree::Boolean(column) => {
+            let (values, validity) = column.to_bitmap();
+            Arc::new(BooleanArray::new(values.into(), validity.map(Into::into)))
+        }
+        ColumnTree::Byte(column) => Arc::new(
+            column
+                .iter()
+                .map(|n| n.map(|n| n as i8))
+                .collect::<Int8Array>(),
+        ),
+        ColumnTree::Short(column) => Arc::new(
+            column
+                .iter()
+                .map(|n| n.map(|n| n as i16))
+                .collect::<Int16Array>(),
+        ),
+        ColumnTree::Int(column) => Arc::new(
+            column
+                .iter()
+                .map(|n| n.map(|n| n as i32))
+                .collect::<Int32Array>(),
+        ),
+        ColumnTree::Long(column) => match column.try_as_slice_not_null() {
+            Some(values) => Arc::new(Int64Array::from(values.to_vec())),
+            None => Arc::new(column.iter().collect::<Int64Array>()),
+        },
+        ColumnTree::Float(column) => Arc::new(
+            column
+                .iter()
+                .map(|n| n.map(|n| n as f32))
+                .collect::<Float32Array>(),
+        ),
+        ColumnTree::Double(column) => match column.try_as_slice_not_null() {
+            Some(values) => Arc::new(Float64Array::from(values.to_vec())),
+            None => Arc::new(column.iter().collect::<Float64Array>()),
+        },
+        ColumnTree::String(column) => Arc::new(StringArray::try_from_binary(
+            strings_to_binary_array(&column)?,
+        )?),
+        ColumnTree::Binary(column) => Arc::new(strings_to_binary_array(&column)?),
+        ColumnTree::Timestamp(column) => Arc::new(timestamps_to_arrow_array(&column)?),
+        ColumnTree::TimestampInstant(column) => {
+            Arc::new(timestamps_to_arrow_array(&column)?.with_timezone("UTC"))
+        }
+        ColumnTree::Date(column) => Arc::new(
+            column
+                .iter()
+                .map(|n| n.map(|n| n as i32))
+                .collect::<Date32Array>(),
+        ),
+        ColumnTree::Decimal64(column) => decimals_to_arrow_array(
+            column.iter().map(|d| d.map(|d| (d.mantissa(), d.scale()))),
+            kind,
+        )?,
+        ColumnTree::Decimal128(column) => decimals_to_arrow_array(column.iter_raw(), kind)?,
+        ColumnTree::List { offsets, elements } => {
+            let item_kind = match kind {
+                Kind::List(item_kind) => item_kind,
+                _ => panic!("Got ColumnTree::List for {:?}", kind),
+            };
+            let (offsets, nulls) = ranges_to_offsets(offsets)?;
+            Arc::new(ListArray::try_new(
+                list_item_field(item_kind)?,
+                offsets,
+                columntree_to_arrow_array(*elements, item_kind)?,
+                nulls,
+            )?)
+        }
+        ColumnTree::Map {
+            offsets,
+            keys,
+            elements,
+        } => {
+            let (key_kind, value_kind) = match kind {
+                Kind::Map { key, value } => (key, value),
+                _ => panic!("Got ColumnTree::Map for {:?}", kind),
+            };
+            let (offsets, nulls) = ranges_to_offsets(offsets)?;
+            let entries = StructArray::try_new(
+                map_entries_fields(key_kind, value_kind)?,
+                vec![
+                    columntree_to_arrow_array(*keys, key_kind)?,
+                    columntree_to_arrow_array(*elements, value_kind)?,
+                ],
+                None,
+            )?;
+            Arc::new(MapArray::try_new(
+                map_entries_field(key_kind, value_kind)?,
+                offsets,
+                entries,
+                nulls,
+                false,
+            )?)
+        }
+        ColumnTree::Struct {
+            not_null,
+            num_elements: _,
+            elements,
+        } => {
+            let fields = match kind {
+                Kind::Struct(fields) => fields,
+                _ => panic!("Got ColumnTree::Struct for {:?}", kind),
+            };
+            let arrays = elements
+                .into_iter()
+                .zip(fields.iter())
+                .map(|((_, subtree), (_, subkind))| columntree_to_arrow_array(subtree, subkind))
+                .collect::<Result<Vec<_>, _>>()?;
+            let nulls = not_null.map(|not_null| {
+                NullBuffer::from(not_null.iter().map(|&b| b != 0).collect::<Vec<_>>())
+            });
+            Arc::new(StructArray::try_new(struct_fields(fields)?, arrays, nulls)?)
+        }
+    })
+}
+
+fn timestamps_to_arrow_array(
+    column: &TimestampVectorBatch<'_>,
+) -> Result<TimestampNanosecondArray, ToArrowError> {
+    Ok(TimestampNanosecondArray::from(
+        column
+            .iter()
+            .map(|ts| {
+                ts.map(|(seconds, nanoseconds)| {
+                    seconds
+                        .checked_mul(1_000_000_000)
+                        .and_then(|ns| ns.checked_add(nanoseconds))
+                        .ok_or(ToArrowError::TimestampOverflow {
+                            seconds,
+                            nanoseconds,
+                        })
+                })
+                .transpose()
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    ))
+}
+
+/// Converts strings or binaries to an Arrow binary array, copying the batch's
+/// buffer at once if they are stored contiguously in it
+fn strings_to_binary_array(column: &StringVectorBatch<'_>) -> Result<BinaryArray, ToArrowError> {
+    match column.contiguous_ranges() {
+        Some(ranges) => {
+            let (offsets, nulls) = ranges_to_offsets(ranges.into_iter())?;
+            Ok(BinaryArray::try_new(
+                offsets,
+                Buffer::from_slice_ref(column.bytes()),
+                nulls,
+            )?)
+        }
+        None => Ok(column.iter().collect::<BinaryArray>()),
+    }
+}
+
+/// Changes the scale of a decimal's mantissa, or returns an error if it overflows
+/// or loses digits
+fn rescale_mantissa(mantissa: i128, scale: u32, target_scale: u32) -> Result<i128, ToArrowError> {
+    let rescaled = if target_scale >= scale {
+        10i128
+            .checked_pow(target_scale - scale)
+            .and_then(|factor| mantissa.checked_mul(factor))
+    } else {
+        10i128
+            .checked_pow(scale - target_scale)
+            .filter(|factor| mantissa % factor == 0)
+            .map(|factor| mantissa / factor)
+    };
+    rescaled.ok_or(ToArrowError::DecimalOutOfRange {
+        mantissa,
+        scale,
+        target_scale,
+    })
+}
+
+/// Converts the mantissa and scale of decimals to an Arrow array with the precision
+/// and scale of `kind`
+fn decimals_to_arrow_array<I: Iterator<Item = Option<(i128, u32)>>>(
+    decimals: I,
+    kind: &Kind,
+) -> Result<ArrayRef, ToArrowError> {
+    let (precision, scale) = match kind_to_arrow_datatype(kind)? {
+        DataType::Decimal128(precision, scale) => (precision, scale),
+        _ => panic!("Got decimal ColumnTree for {:?}", kind),
+    };
+    let target_scale = scale
+        .try_into()
+        .map_err(|_| ToArrowError::UnsupportedKind(kind.clone()))?;
+    Ok(Arc::new(
+        decimals
+            .map(|d| {
+                d.map(|(mantissa, scale)| rescale_mantissa(mantissa, scale, target_scale))
+                    .transpose()
+            })
+            .collect::<Result<Decimal128Array, _>>()?
+            .with_precision_and_scale(precision, scale)?,
+    ))
+}
+
+/// Given the columns of the root struct (as a [`ColumnTree`]), returns an Arrow
+/// record batch with the given `schema`, as returned by [`kind_to_arrow_schema`].
+///
+/// # Panics
+///
+/// If `tree` was not built from a vector batch of type `kind`.
+pub fn columntree_to_record_batch(
+    tree: ColumnTree<'_>,
+    kind: &Kind,
+    schema: SchemaRef,
+) -> Result<RecordBatch, ToArrowError> {
+    let fields = match kind {
+        Kind::Struct(fields) => fields,
+        _ => return Err(ToArrowError::NotAStruct(kind.clone())),
+    };
+    match tree {
+        ColumnTree::Struct {
+            num_elements,
+            elements,
+            ..
+        } => {
+            let columns = elements
+                .into_iter()
+                .zip(fields.iter())
+                .map(|((_, subtree), (_, subkind))| columntree_to_arrow_array(subtree, subkind))
+                .collect::<Result<Vec<_>, _>>()?;
+            let options = RecordBatchOptions::new().with_row_count(Some(
+                num_elements
+                    .try_into()
+                    .expect("Could not convert u64 to usize"),
+            ));
+            Ok(RecordBatch::try_new_with_options(
+                schema, columns, &options,
+            )?)
+        }
+        _ => panic!("Got {:?} for {:?}", tree, kind),
+    }
+}
+
+/// Converts a vector batch whose rows are of type `kind` (usually
+/// [`RowReader::selected_kind`](::reader::RowReader::selected_kind)), to an Arrow
+/// record batch with the schema returned by [`kind_to_arrow_schema`].
+///
+/// # Panics
+///
+/// If `batch` is not a vector batch of type `kind`.
+pub fn vector_batch_to_record_batch(
+    batch: BorrowedColumnVectorBatch<'_>,
+    kind: &Kind,
+) -> Result<RecordBatch, ToArrowError> {
+    let schema = Arc::new(kind_to_arrow_schema(kind)?);
+    columntree_to_record_batch(columnvectorbatch_to_columntree(batch, kind), kind, schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rescale_mantissa() {
+        assert_eq!(rescale_mantissa(12345, 2, 2).unwrap(), 12345);
+        assert_eq!(rescale_mantissa(-12345, 2, 4).unwrap(), -1234500);
+        assert_eq!(rescale_mantissa(12300, 4, 2).unwrap(), 123);
+        assert!(matches!(
+            rescale_mantissa(12345, 4, 2),
+            Err(ToArrowError::DecimalOutOfRange {
+                mantissa: 12345,
+                scale: 4,
+                target_scale: 2
+            })
+        ));
+        assert!(rescale_mantissa(i128::MAX / 10, 0, 2).is_err());
+    }
+}
@@ -25,14 +25,17 @@
 //! }
 //! ```
 
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
+use std::io;
 use std::iter;
+use std::ops::Range;
 
 use json::JsonValue;
-use rust_decimal::prelude::ToPrimitive;
 
 use structured_reader::ColumnTree;
-use vector::DecimalVectorBatch;
+use vector;
+use vector::{decimal_to_string, trim_decimal, DecimalVectorBatch};
 
 fn map_nullable_json_values<V, C: Iterator<Item = Option<V>>, F>(column: C, f: F) -> Vec<JsonValue>
 where
@@ -46,9 +49,796 @@ where
         .collect()
 }
 
+/// Functions applied to the values of some columns while converting them to JSON,
+/// eg. to hash or redact sensitive columns, as well as renamed and omitted struct
+/// fields.
+///
+/// Columns are identified by their path, as returned by
+/// [`RowReader::selected_columns`](::reader::RowReader::selected_columns).
+///
+/// # Example
+///
+/// ```
+/// use orcxx::*;
+/// use orcxx::to_json::ColumnTransforms;
+///
+/// let orc_path = "orc/examples/TestOrcFile.test1.orc";
+/// let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+/// let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+///
+/// let mut row_reader = reader.row_reader(&reader::RowReaderOptions::default()).unwrap();
+///
+/// let mut transforms = ColumnTransforms::new();
+/// transforms.insert("string1", |_| "<redacted>".into());
+///
+/// let mut structured_row_reader = structured_reader::StructuredRowReader::new(&mut row_reader, 1024);
+///
+/// while let Some(columns) = structured_row_reader.next() {
+///     for object in to_json::columntree_to_json_rows_with_transforms(columns, &transforms) {
+///         assert_eq!(object["string1"], "<redacted>");
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct ColumnTransforms {
+    transforms: HashMap<String, Box<dyn Fn(JsonValue) -> JsonValue + Send + Sync>>,
+    renames: HashMap<String, String>,
+    omissions: HashSet<String>,
+}
+
+impl ColumnTransforms {
+    pub fn new() -> ColumnTransforms {
+        ColumnTransforms::default()
+    }
+
+    /// Sets the function applied to each value of the column (including nulls),
+    /// replacing any previously set for that column.
+    pub fn insert<S, F>(&mut self, column_path: S, transform: F) -> &mut Self
+    where
+        S: Into<String>,
+        F: Fn(JsonValue) -> JsonValue + Send + Sync + 'static,
+    {
+        self.transforms
+            .insert(column_path.into(), Box::new(transform));
+        self
+    }
+
+    /// Sets the key of a struct field in JSON objects, instead of its name in the
+    /// ORC file. `column_path` is the path of the field in the ORC file, regardless
+    /// of renames of its parents.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use orcxx::*;
+    /// use orcxx::to_json::ColumnTransforms;
+    ///
+    /// let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    /// let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    /// let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    ///
+    /// let options = reader::RowReaderOptions::default().include_names(["int1", "string1"]);
+    /// let mut row_reader = reader.row_reader(&options).unwrap();
+    ///
+    /// let mut transforms = ColumnTransforms::new();
+    /// transforms.rename("string1", "name").omit("int1");
+    ///
+    /// let mut structured_row_reader = structured_reader::StructuredRowReader::new(&mut row_reader, 1024);
+    ///
+    /// while let Some(columns) = structured_row_reader.next() {
+    ///     for object in to_json::columntree_to_json_rows_with_transforms(columns, &transforms) {
+    ///         assert_eq!(object.len(), 1);
+    ///         assert!(object["name"].is_string());
+    ///     }
+    /// }
+    /// ```
+    pub fn rename<S1, S2>(&mut self, column_path: S1, key: S2) -> &mut Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        self.renames.insert(column_path.into(), key.into());
+        self
+    }
+
+    /// Removes a struct field (and all its children) from JSON objects.
+    ///
+    /// This is cheaper than a transform returning [`JsonValue::Null`], as the
+    /// column is not converted at all, but it is even cheaper to not select it in
+    /// the [`RowReaderOptions`](::reader::RowReaderOptions) in the first place.
+    pub fn omit<S: Into<String>>(&mut self, column_path: S) -> &mut Self {
+        self.omissions.insert(column_path.into());
+        self
+    }
+
+    fn apply(&self, column_path: &str, values: Vec<JsonValue>) -> Vec<JsonValue> {
+        match self.transforms.get(column_path) {
+            None => values,
+            Some(transform) => values.into_iter().map(transform).collect(),
+        }
+    }
+}
+
+/// Representation of `float` and `double` values, as JSON numbers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// Shortest decimal representation which parses back to the same value, as a
+    /// `f32` for `float` columns and a `f64` for `double` columns (default)
+    #[default]
+    ShortestRoundtrip,
+    /// Rounded to this many digits after the decimal point
+    Fixed(usize),
+    /// Rounded to this many digits after the decimal point of the scientific
+    /// notation, ie. to one more significant digits
+    Scientific(usize),
+}
+
+/// Options of [`columntree_to_json_rows_with_options`]
+#[derive(Clone, Copy, Default)]
+pub struct JsonOptions<'a> {
+    transforms: Option<&'a ColumnTransforms>,
+    float_format: FloatFormat,
+}
+
+impl<'a> JsonOptions<'a> {
+    /// Transforms applied to columns (default: none)
+    pub fn transforms(mut self, transforms: &'a ColumnTransforms) -> JsonOptions<'a> {
+        self.transforms = Some(transforms);
+        self
+    }
+
+    /// Representation of `float` and `double` values (default:
+    /// [`FloatFormat::ShortestRoundtrip`])
+    pub fn float_format(mut self, float_format: FloatFormat) -> JsonOptions<'a> {
+        self.float_format = float_format;
+        self
+    }
+}
+
+/// Converts the decimal representation of a finite float, as formatted by Rust's
+/// `Display` or `LowerExp`, to a JSON number
+///
+/// Digits which do not fit in the number's 64 bits mantissa are truncated, like
+/// [`json::parse`] does; they are far beyond the precision of `f64` anyway.
+fn parse_float(s: &str) -> json::number::Number {
+    let (positive, s) = match s.strip_prefix('-') {
+        Some(s) => (false, s),
+        None => (true, s),
+    };
+    let (digits, mut exponent) = match s.split_once('e') {
+        Some((digits, exponent)) => (
+            digits,
+            exponent
+                .parse::<i16>()
+                .expect("Could not parse float exponent"),
+        ),
+        None => (s, 0),
+    };
+    let mut mantissa = 0u64;
+    let mut after_point = false;
+    for c in digits.chars() {
+        if c == '.' {
+            after_point = true;
+            continue;
+        }
+        let digit = c.to_digit(10).expect("Unexpected character in float");
+        match mantissa
+            .checked_mul(10)
+            .and_then(|mantissa| mantissa.checked_add(digit.into()))
+        {
+            Some(new_mantissa) => {
+                mantissa = new_mantissa;
+                if after_point {
+                    exponent -= 1;
+                }
+            }
+            None => {
+                if !after_point {
+                    exponent += 1;
+                }
+            }
+        }
+    }
+    json::number::Number::from_parts(positive, mantissa, exponent)
+}
+
+fn float_to_json(value: f64, is_f32: bool, float_format: FloatFormat) -> JsonValue {
+    if !value.is_finite() {
+        // Serialized as null
+        return JsonValue::Number(value.into());
+    }
+    let s = match float_format {
+        FloatFormat::ShortestRoundtrip if is_f32 => format!("{:e}", value as f32),
+        FloatFormat::ShortestRoundtrip => format!("{:e}", value),
+        FloatFormat::Fixed(precision) => format!("{:.*}", precision, value),
+        FloatFormat::Scientific(precision) => format!("{:.*e}", precision, value),
+    };
+    JsonValue::Number(parse_float(&s))
+}
+
 /// Given a set of columns (as a [`ColumnTree`]), returns a vector of rows
 /// represented as a JSON-like data structure.
+///
+/// Objects only have the fields in the [`RowReader`](::reader::RowReader)'s
+/// [`selected_kind`](::reader::RowReader::selected_kind), so projected reads
+/// (eg. with [`RowReaderOptions::include_names`](::reader::RowReaderOptions::include_names))
+/// produce objects with only the selected keys.
 pub fn columntree_to_json_rows(tree: ColumnTree<'_>) -> Vec<JsonValue> {
+    columntree_to_json_values(tree, "", &JsonOptions::default())
+}
+
+/// Same as [`columntree_to_json_rows`], but applies the given functions to the
+/// values of their respective columns, and renames or omits struct fields.
+///
+/// This is a shorthand for [`columntree_to_json_rows_with_options`] with
+/// [`JsonOptions::transforms`] as only option.
+pub fn columntree_to_json_rows_with_transforms(
+    tree: ColumnTree<'_>,
+    transforms: &ColumnTransforms,
+) -> Vec<JsonValue> {
+    columntree_to_json_rows_with_options(tree, &JsonOptions::default().transforms(transforms))
+}
+
+/// Same as [`columntree_to_json_rows`], but with non-default options
+///
+/// # Example
+///
+/// ```
+/// use orcxx::*;
+/// use orcxx::to_json::{FloatFormat, JsonOptions};
+///
+/// let orc_path = "orc/examples/TestOrcFile.test1.orc";
+/// let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+/// let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+///
+/// let options = reader::RowReaderOptions::default().include_names(["double1"]);
+/// let mut row_reader = reader.row_reader(&options).unwrap();
+///
+/// let json_options = JsonOptions::default().float_format(FloatFormat::Fixed(2));
+///
+/// let mut structured_row_reader = structured_reader::StructuredRowReader::new(&mut row_reader, 1024);
+///
+/// while let Some(columns) = structured_row_reader.next() {
+///     for object in to_json::columntree_to_json_rows_with_options(columns, &json_options) {
+///         println!("{}", object["double1"]);
+///     }
+/// }
+/// ```
+pub fn columntree_to_json_rows_with_options(
+    tree: ColumnTree<'_>,
+    options: &JsonOptions<'_>,
+) -> Vec<JsonValue> {
+    columntree_to_json_values(tree, "", options)
+}
+
+/// Same as [`columntree_to_json_rows`], but writes rows to `writer` as they are
+/// converted, one JSON document per line, instead of building a vector of
+/// [`JsonValue`].
+///
+/// This is faster and uses much less memory for large batches, as values are
+/// written directly from the vector batch.
+///
+/// # Example
+///
+/// ```
+/// use orcxx::*;
+///
+/// let orc_path = "orc/examples/TestOrcFile.test1.orc";
+/// let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+/// let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+///
+/// let options = reader::RowReaderOptions::default().include_names(["int1", "string1"]);
+/// let mut row_reader = reader.row_reader(&options).unwrap();
+///
+/// let mut structured_row_reader = structured_reader::StructuredRowReader::new(&mut row_reader, 1024);
+///
+/// let mut lines = Vec::new();
+/// while let Some(columns) = structured_row_reader.next() {
+///     to_json::write_json_lines(columns, &mut lines).expect("Could not write");
+/// }
+/// assert_eq!(
+///     String::from_utf8(lines).unwrap(),
+///     "{\"int1\":65536,\"string1\":\"hi\"}\n{\"int1\":65536,\"string1\":\"bye\"}\n"
+/// );
+/// ```
+pub fn write_json_lines<W: io::Write>(tree: ColumnTree<'_>, writer: &mut W) -> io::Result<()> {
+    write_json_lines_with_options(tree, &JsonOptions::default(), writer)
+}
+
+/// Same as [`write_json_lines`], but with non-default options
+///
+/// Columns with a transform (see [`ColumnTransforms::insert`]) are converted to
+/// [`JsonValue`] first, like [`columntree_to_json_rows_with_options`] does.
+pub fn write_json_lines_with_options<W: io::Write>(
+    tree: ColumnTree<'_>,
+    options: &JsonOptions<'_>,
+    writer: &mut W,
+) -> io::Result<()> {
+    let mut column = JsonColumnWriter::new(tree, "", options);
+    for _ in 0..column.len() {
+        column.write_next(writer)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Cursor on the values of a column, which writes them as JSON one at a time,
+/// in order
+enum JsonColumnWriter<'a> {
+    Boolean(vector::LongVectorBatchIterator<'a>),
+    Integer(vector::LongVectorBatchIterator<'a>),
+    Float {
+        values: vector::DoubleVectorBatchIterator<'a>,
+        is_f32: bool,
+        float_format: FloatFormat,
+    },
+    String(vector::StringVectorBatchIterator<'a>),
+    Binary(vector::StringVectorBatchIterator<'a>),
+    /// Formatted timestamps or dates
+    FormattedString(std::vec::IntoIter<Option<String>>),
+    Decimal64(vector::Decimal64VectorBatchIterator<'a>),
+    Decimal128(vector::RawDecimal128VectorBatchIterator<'a>),
+    Struct {
+        not_null: Option<std::slice::Iter<'a, i8>>,
+        /// Number of rows not written yet
+        remaining: u64,
+        /// Pairs of (key, writer) of fields which are not omitted
+        fields: Vec<(String, JsonColumnWriter<'a>)>,
+    },
+    List {
+        offsets: vector::RangeVectorBatchIterator<'a>,
+        /// Index of the next value of `elements`
+        position: usize,
+        elements: Box<JsonColumnWriter<'a>>,
+    },
+    Map {
+        offsets: vector::RangeVectorBatchIterator<'a>,
+        /// Index of the next value of `keys` and `elements`
+        position: usize,
+        keys: Box<JsonColumnWriter<'a>>,
+        elements: Box<JsonColumnWriter<'a>>,
+    },
+    /// Values of a column with a transform, already converted
+    Values(std::vec::IntoIter<JsonValue>),
+}
+
+impl<'a> JsonColumnWriter<'a> {
+    fn new(tree: ColumnTree<'a>, path: &str, options: &JsonOptions<'_>) -> JsonColumnWriter<'a> {
+        if let Some(transforms) = options.transforms {
+            if transforms.transforms.contains_key(path) {
+                let values = columntree_to_json_values(tree, path, options);
+                return JsonColumnWriter::Values(values.into_iter());
+            }
+        }
+        match tree {
+            ColumnTree::Boolean(column) => JsonColumnWriter::Boolean(column.iter()),
+            ColumnTree::Byte(column)
+            | ColumnTree::Short(column)
+            | ColumnTree::Int(column)
+            | ColumnTree::Long(column) => JsonColumnWriter::Integer(column.iter()),
+            ColumnTree::Float(column) => JsonColumnWriter::Float {
+                values: column.iter(),
+                is_f32: true,
+                float_format: options.float_format,
+            },
+            ColumnTree::Double(column) => JsonColumnWriter::Float {
+                values: column.iter(),
+                is_f32: false,
+                float_format: options.float_format,
+            },
+            ColumnTree::String(column) => JsonColumnWriter::String(column.iter()),
+            ColumnTree::Binary(column) => JsonColumnWriter::Binary(column.iter()),
+            ColumnTree::Timestamp(column) | ColumnTree::TimestampInstant(column) => {
+                JsonColumnWriter::FormattedString(column.to_timestamp_strings().into_iter())
+            }
+            ColumnTree::Date(column) => {
+                JsonColumnWriter::FormattedString(column.to_date_strings().into_iter())
+            }
+            ColumnTree::Decimal64(column) => JsonColumnWriter::Decimal64(column.iter()),
+            ColumnTree::Decimal128(column) => JsonColumnWriter::Decimal128(column.iter_raw()),
+            ColumnTree::Struct {
+                not_null,
+                num_elements,
+                elements,
+            } => {
+                if let Some(not_null) = not_null {
+                    assert_eq!(num_elements, not_null.len() as u64);
+                }
+                let fields = elements
+                    .into_iter()
+                    .filter_map(|(field_name, subtree)| {
+                        let field_path = child_path(path, &field_name);
+                        let mut key = field_name;
+                        if let Some(transforms) = options.transforms {
+                            if transforms.omissions.contains(&field_path) {
+                                return None;
+                            }
+                            if let Some(new_key) = transforms.renames.get(&field_path) {
+                                key = new_key.clone();
+                            }
+                        }
+                        let writer = JsonColumnWriter::new(subtree, &field_path, options);
+                        assert_eq!(
+                            writer.len() as u64,
+                            num_elements,
+                            "Struct field {} has {} values, but the struct has {} rows",
+                            field_path,
+                            writer.len(),
+                            num_elements
+                        );
+                        Some((key, writer))
+                    })
+                    .collect();
+                JsonColumnWriter::Struct {
+                    not_null: not_null.map(|not_null| not_null.iter()),
+                    remaining: num_elements,
+                    fields,
+                }
+            }
+            ColumnTree::List { offsets, elements } => JsonColumnWriter::List {
+                offsets,
+                position: 0,
+                elements: Box::new(JsonColumnWriter::new(
+                    *elements,
+                    &child_path(path, "_elem"),
+                    options,
+                )),
+            },
+            ColumnTree::Map {
+                offsets,
+                keys,
+                elements,
+            } => JsonColumnWriter::Map {
+                offsets,
+                position: 0,
+                keys: Box::new(JsonColumnWriter::new(
+                    *keys,
+                    &child_path(path, "_key"),
+                    options,
+                )),
+                elements: Box::new(JsonColumnWriter::new(
+                    *elements,
+                    &child_path(path, "_value"),
+                    options,
+                )),
+            },
+        }
+    }
+
+    /// Returns the number of values not written yet
+    fn len(&self) -> usize {
+        match self {
+            JsonColumnWriter::Boolean(values) | JsonColumnWriter::Integer(values) => values.len(),
+            JsonColumnWriter::Float { values, .. } => values.len(),
+            JsonColumnWriter::String(values) | JsonColumnWriter::Binary(values) => values.len(),
+            JsonColumnWriter::FormattedString(values) => values.len(),
+            JsonColumnWriter::Decimal64(values) => values.len(),
+            JsonColumnWriter::Decimal128(values) => values.len(),
+            JsonColumnWriter::Struct { remaining, .. } => {
+                (*remaining).try_into().expect("Row count overflows usize")
+            }
+            JsonColumnWriter::List { offsets, .. } | JsonColumnWriter::Map { offsets, .. } => {
+                offsets.len()
+            }
+            JsonColumnWriter::Values(values) => values.len(),
+        }
+    }
+
+    /// Writes the next value
+    ///
+    /// # Panics
+    ///
+    /// If there is no value left
+    fn write_next<W: io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        match self {
+            JsonColumnWriter::Boolean(values) => match next_value(values) {
+                None => writer.write_all(b"null"),
+                Some(0) => writer.write_all(b"false"),
+                Some(_) => writer.write_all(b"true"),
+            },
+            JsonColumnWriter::Integer(values) => match next_value(values) {
+                None => writer.write_all(b"null"),
+                Some(n) => write!(writer, "{}", n),
+            },
+            JsonColumnWriter::Float {
+                values,
+                is_f32,
+                float_format,
+            } => match next_value(values) {
+                None => writer.write_all(b"null"),
+                Some(f) => float_to_json(f, *is_f32, *float_format).write(writer),
+            },
+            JsonColumnWriter::String(values) => match next_value(values) {
+                None => writer.write_all(b"null"),
+                Some(s) => write_json_string(writer, &String::from_utf8_lossy(s)),
+            },
+            JsonColumnWriter::Binary(values) => match next_value(values) {
+                None => writer.write_all(b"null"),
+                Some(bytes) => {
+                    writer.write_all(b"[")?;
+                    for (i, byte) in bytes.iter().enumerate() {
+                        if i > 0 {
+                            writer.write_all(b",")?;
+                        }
+                        write!(writer, "{}", byte)?;
+                    }
+                    writer.write_all(b"]")
+                }
+            },
+            JsonColumnWriter::FormattedString(values) => match next_value(values) {
+                None => writer.write_all(b"null"),
+                Some(s) => write_json_string(writer, &s),
+            },
+            JsonColumnWriter::Decimal64(values) => match next_value(values) {
+                None => writer.write_all(b"null"),
+                Some(n) => writer.write_all(decimal_to_string(n.mantissa(), n.scale()).as_bytes()),
+            },
+            JsonColumnWriter::Decimal128(values) => match next_value(values) {
+                None => writer.write_all(b"null"),
+                Some((mantissa, scale)) => {
+                    writer.write_all(decimal_to_string(mantissa, scale).as_bytes())
+                }
+            },
+            JsonColumnWriter::Struct {
+                not_null,
+                remaining,
+                fields,
+            } => {
+                *remaining = remaining
+                    .checked_sub(1)
+                    .expect("Struct has fewer rows than its parent");
+                let is_null = match not_null {
+                    Some(not_null) => {
+                        *not_null
+                            .next()
+                            .expect("Struct has fewer rows than its parent")
+                            == 0
+                    }
+                    None => false,
+                };
+                if is_null {
+                    // Fields have a value for every row, including rows where the
+                    // struct is null
+                    for (_, field) in fields.iter_mut() {
+                        field.skip_next();
+                    }
+                    return writer.write_all(b"null");
+                }
+                writer.write_all(b"{")?;
+                for (i, (key, field)) in fields.iter_mut().enumerate() {
+                    if i > 0 {
+                        writer.write_all(b",")?;
+                    }
+                    write_json_string(writer, key)?;
+                    writer.write_all(b":")?;
+                    field.write_next(writer)?;
+                }
+                writer.write_all(b"}")
+            }
+            JsonColumnWriter::List {
+                offsets,
+                position,
+                elements,
+            } => match next_value(offsets) {
+                None => writer.write_all(b"null"),
+                Some(range) => {
+                    skip_to(&mut [elements], position, range.start);
+                    writer.write_all(b"[")?;
+                    for i in range.clone() {
+                        if i > range.start {
+                            writer.write_all(b",")?;
+                        }
+                        elements.write_next(writer)?;
+                    }
+                    *position = range.end;
+                    writer.write_all(b"]")
+                }
+            },
+            JsonColumnWriter::Map {
+                offsets,
+                position,
+                keys,
+                elements,
+            } => match next_value(offsets) {
+                None => writer.write_all(b"null"),
+                Some(range) => {
+                    skip_to(&mut [keys, elements], position, range.start);
+                    writer.write_all(b"[")?;
+                    for i in range.clone() {
+                        if i > range.start {
+                            writer.write_all(b",")?;
+                        }
+                        writer.write_all(b"{\"key\":")?;
+                        keys.write_next(writer)?;
+                        writer.write_all(b",\"value\":")?;
+                        elements.write_next(writer)?;
+                        writer.write_all(b"}")?;
+                    }
+                    *position = range.end;
+                    writer.write_all(b"]")
+                }
+            },
+            JsonColumnWriter::Values(values) => values
+                .next()
+                .expect("Column has fewer values than its parent")
+                .write(writer),
+        }
+    }
+
+    /// Skips the next value, which belongs to a row where the parent struct is null
+    ///
+    /// # Panics
+    ///
+    /// If there is no value left
+    fn skip_next(&mut self) {
+        match self {
+            JsonColumnWriter::Boolean(values) | JsonColumnWriter::Integer(values) => {
+                next_value(values);
+            }
+            JsonColumnWriter::Float { values, .. } => {
+                next_value(values);
+            }
+            JsonColumnWriter::String(values) | JsonColumnWriter::Binary(values) => {
+                next_value(values);
+            }
+            JsonColumnWriter::FormattedString(values) => {
+                next_value(values);
+            }
+            JsonColumnWriter::Decimal64(values) => {
+                next_value(values);
+            }
+            JsonColumnWriter::Decimal128(values) => {
+                next_value(values);
+            }
+            JsonColumnWriter::Struct {
+                not_null,
+                remaining,
+                fields,
+            } => {
+                *remaining = remaining
+                    .checked_sub(1)
+                    .expect("Struct has fewer rows than its parent");
+                if let Some(not_null) = not_null {
+                    not_null
+                        .next()
+                        .expect("Struct has fewer rows than its parent");
+                }
+                for (_, field) in fields.iter_mut() {
+                    field.skip_next();
+                }
+            }
+            JsonColumnWriter::List {
+                offsets,
+                position,
+                elements,
+            } => {
+                if let Some(range) = next_value(offsets) {
+                    skip_to(&mut [elements], position, range.end);
+                }
+            }
+            JsonColumnWriter::Map {
+                offsets,
+                position,
+                keys,
+                elements,
+            } => {
+                if let Some(range) = next_value(offsets) {
+                    skip_to(&mut [keys, elements], position, range.end);
+                }
+            }
+            JsonColumnWriter::Values(values) => {
+                values
+                    .next()
+                    .expect("Column has fewer values than its parent");
+            }
+        }
+    }
+}
+
+/// Returns the next value of a column, or `None` if it is null
+///
+/// # Panics
+///
+/// If there is no value left
+fn next_value<T, I: Iterator<Item = Option<T>>>(values: &mut I) -> Option<T> {
+    values
+        .next()
+        .expect("Column has fewer values than its parent")
+}
+
+/// Skips values of the `columns` of a list or map, from `position` to `end`
+///
+/// # Panics
+///
+/// If `end` is before `position`, which happens when offsets are not increasing.
+fn skip_to(columns: &mut [&mut Box<JsonColumnWriter<'_>>], position: &mut usize, end: usize) {
+    let skipped: Range<usize> = *position..end;
+    assert!(
+        skipped.start <= skipped.end,
+        "List or map offsets are not increasing"
+    );
+    for _ in skipped {
+        for column in columns.iter_mut() {
+            column.skip_next();
+        }
+    }
+    *position = end;
+}
+
+/// Writes a JSON string, escaped the same way as [`json::stringify`]
+fn write_json_string<W: io::Write>(writer: &mut W, s: &str) -> io::Result<()> {
+    writer.write_all(b"\"")?;
+    let bytes = s.as_bytes();
+    let mut start = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let escape: &[u8] = match byte {
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            0x08 => b"\\b",
+            0x0c => b"\\f",
+            0x00..=0x1f => b"",
+            _ => continue,
+        };
+        writer.write_all(&bytes[start..i])?;
+        if escape.is_empty() {
+            write!(writer, "\\u{:04x}", byte)?;
+        } else {
+            writer.write_all(escape)?;
+        }
+        start = i + 1;
+    }
+    writer.write_all(&bytes[start..])?;
+    writer.write_all(b"\"")
+}
+
+/// Decimals whose mantissa fits in 64 bits are exact JSON numbers. Wider ones
+/// cannot be represented by [`json::number::Number`], so they are strings with
+/// their exact value instead of truncated numbers.
+///
+/// [`write_json_lines`] does not have this limitation, and writes all decimals as
+/// numbers.
+fn decimal_to_json(mantissa: i128, scale: u32) -> JsonValue {
+    let (mantissa, scale) = trim_decimal(mantissa, scale);
+    let abs_mantissa: Result<u64, _> = mantissa.unsigned_abs().try_into();
+    let exponent: Result<i16, _> = scale.try_into();
+    match (abs_mantissa, exponent) {
+        (Ok(abs_mantissa), Ok(exponent)) => JsonValue::Number(json::number::Number::from_parts(
+            mantissa >= 0,
+            abs_mantissa,
+            -exponent,
+        )),
+        _ => JsonValue::String(decimal_to_string(mantissa, scale)),
+    }
+}
+
+fn child_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn columntree_to_json_values(
+    tree: ColumnTree<'_>,
+    path: &str,
+    options: &JsonOptions<'_>,
+) -> Vec<JsonValue> {
+    let values = columntree_to_json_values_untransformed(tree, path, options);
+    match options.transforms {
+        None => values,
+        Some(transforms) => transforms.apply(path, values),
+    }
+}
+
+fn columntree_to_json_values_untransformed(
+    tree: ColumnTree<'_>,
+    path: &str,
+    options: &JsonOptions<'_>,
+) -> Vec<JsonValue> {
     match tree {
         ColumnTree::Boolean(column) => {
             map_nullable_json_values(column.iter(), |b| JsonValue::Boolean(b != 0))
@@ -59,65 +849,29 @@ pub fn columntree_to_json_rows(tree: ColumnTree<'_>) -> Vec<JsonValue> {
         | ColumnTree::Long(column) => {
             map_nullable_json_values(column.iter(), |b| JsonValue::Number(b.into()))
         }
-        ColumnTree::Float(column) | ColumnTree::Double(column) => {
-            map_nullable_json_values(column.iter(), |b| JsonValue::Number(b.into()))
-        }
+        ColumnTree::Float(column) => map_nullable_json_values(column.iter(), |f| {
+            float_to_json(f, true, options.float_format)
+        }),
+        ColumnTree::Double(column) => map_nullable_json_values(column.iter(), |f| {
+            float_to_json(f, false, options.float_format)
+        }),
         ColumnTree::String(column) => map_nullable_json_values(column.iter(), |s| {
             JsonValue::String(String::from_utf8_lossy(s).into_owned())
         }),
-        ColumnTree::Timestamp(column) => {
-            map_nullable_json_values(column.iter(), |(seconds, nanoseconds)| {
-                let mut s = chrono::DateTime::from_timestamp(
-                    seconds,
-                    nanoseconds
-                        .try_into()
-                        .expect("More than 2**32 nanoseconds in a second"),
-                )
-                .expect("Could not create NaiveDateTime")
-                .format("%Y-%m-%d %H:%M:%S.%f")
-                .to_string()
-                .trim_end_matches("0")
-                .to_string();
-                if s.ends_with(".") {
-                    s.push('0');
-                }
-                JsonValue::String(s)
+        ColumnTree::Timestamp(column) | ColumnTree::TimestampInstant(column) => {
+            map_nullable_json_values(column.to_timestamp_strings().into_iter(), JsonValue::String)
+        }
+        ColumnTree::Date(column) => {
+            map_nullable_json_values(column.to_date_strings().into_iter(), JsonValue::String)
+        }
+        ColumnTree::Decimal64(column) => {
+            map_nullable_json_values(column.iter(), |n| decimal_to_json(n.mantissa(), n.scale()))
+        }
+        ColumnTree::Decimal128(column) => {
+            map_nullable_json_values(column.iter_raw(), |(mantissa, scale)| {
+                decimal_to_json(mantissa, scale)
             })
         }
-        ColumnTree::Date(column) => map_nullable_json_values(column.iter(), |days| {
-            let substract = days <= 0;
-            let days = chrono::Days::new(
-                days.abs()
-                    .try_into()
-                    .expect("Failed to convert positive days from i64 to u64"),
-            );
-            let date = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
-            let date = if substract {
-                date.checked_sub_days(days)
-            } else {
-                date.checked_add_days(days)
-            };
-
-            let s = date
-                .expect("Overflowed NaiveDate")
-                .format("%Y-%m-%d")
-                .to_string();
-            JsonValue::String(s)
-        }),
-        ColumnTree::Decimal64(column) => map_nullable_json_values(column.iter(), |n| {
-            JsonValue::Number(
-                n.to_f64()
-                    .expect("Decimal cannot be represented with f64")
-                    .into(),
-            )
-        }),
-        ColumnTree::Decimal128(column) => map_nullable_json_values(column.iter(), |n| {
-            JsonValue::Number(
-                n.to_f64()
-                    .expect("Decimal cannot be represented with f64")
-                    .into(),
-            )
-        }),
         ColumnTree::Binary(column) => map_nullable_json_values(column.iter(), |s| {
             JsonValue::Array(
                 s.iter()
@@ -134,57 +888,53 @@ pub fn columntree_to_json_rows(tree: ColumnTree<'_>) -> Vec<JsonValue> {
                 assert_eq!(num_elements, not_null.len() as u64);
             }
             let num_fields = elements.len();
-            let num_not_null_elements = match not_null {
-                None => num_elements,
-                Some(not_null) => not_null
-                    .iter()
-                    .filter(|&&b| b != 0)
-                    .count()
-                    .try_into()
-                    .expect("Could not convert usize to u64"),
-            };
-
-            let mut objects: Vec<_> = (0..num_not_null_elements)
+
+            // Fields have a value for every row, including rows where the struct
+            // is null (where they are null too)
+            let mut objects: Vec<_> = (0..num_elements)
                 .map(|_| json::object::Object::with_capacity(num_fields))
                 .collect();
 
             for (field_name, subtree) in elements.into_iter() {
-                for (subvalue, object) in iter::zip(
-                    columntree_to_json_rows(subtree).into_iter(),
-                    objects.iter_mut(),
-                ) {
-                    object.insert(&field_name, subvalue);
+                let field_path = child_path(path, &field_name);
+                let mut key = field_name.as_str();
+                if let Some(transforms) = options.transforms {
+                    if transforms.omissions.contains(&field_path) {
+                        continue;
+                    }
+                    if let Some(new_key) = transforms.renames.get(&field_path) {
+                        key = new_key;
+                    }
+                }
+                let subvalues = columntree_to_json_values(subtree, &field_path, options);
+                assert_eq!(
+                    subvalues.len(),
+                    objects.len(),
+                    "Struct field {} has {} values, but the struct has {} rows",
+                    field_path,
+                    subvalues.len(),
+                    objects.len()
+                );
+                for (subvalue, object) in iter::zip(subvalues, objects.iter_mut()) {
+                    object.insert(key, subvalue);
                 }
             }
 
             match not_null {
                 None => objects.into_iter().map(JsonValue::Object).collect(),
-                Some(not_null) => {
-                    let mut values = Vec::with_capacity(not_null.len());
-                    let mut objects_iter = objects.into_iter();
-                    for &b in not_null {
+                Some(not_null) => iter::zip(not_null, objects)
+                    .map(|(&b, object)| {
                         if b == 0 {
-                            values.push(JsonValue::Null);
+                            JsonValue::Null
                         } else {
-                            values.push(JsonValue::Object(
-                                objects_iter
-                                    .next()
-                                    .expect("Struct field vector unexpectedly too short"),
-                            ));
+                            JsonValue::Object(object)
                         }
-                    }
-
-                    assert_eq!(
-                        objects_iter.next(),
-                        None,
-                        "Struct field vector unexpectedly too long"
-                    );
-                    values
-                }
+                    })
+                    .collect(),
             }
         }
         ColumnTree::List { offsets, elements } => {
-            let values = columntree_to_json_rows(*elements);
+            let values = columntree_to_json_values(*elements, &child_path(path, "_elem"), options);
             offsets
                 .into_iter()
                 .map(|v| match v {
@@ -198,8 +948,10 @@ pub fn columntree_to_json_rows(tree: ColumnTree<'_>) -> Vec<JsonValue> {
             keys,
             elements,
         } => {
-            let keys: Vec<JsonValue> = columntree_to_json_rows(*keys);
-            let values: Vec<JsonValue> = columntree_to_json_rows(*elements);
+            let keys: Vec<JsonValue> =
+                columntree_to_json_values(*keys, &child_path(path, "_key"), options);
+            let values: Vec<JsonValue> =
+                columntree_to_json_values(*elements, &child_path(path, "_value"), options);
             offsets
                 .into_iter()
                 .map(|v| match v {
@@ -220,6 +972,112 @@ pub fn columntree_to_json_rows(tree: ColumnTree<'_>) -> Vec<JsonValue> {
                 })
                 .collect()
         }
-        _ => todo!("{:?}", tree),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_float_formats() {
+        assert_eq!(parse_float("0"), json::number::Number::from(0));
+        assert_eq!(
+            parse_float("1.5"),
+            json::number::Number::from_parts(true, 15, -1)
+        );
+        assert_eq!(parse_float("-1.5e3"), json::number::Number::from(-1500));
+        assert_eq!(
+            parse_float("1.25e-7"),
+            json::number::Number::from_parts(true, 125, -9)
+        );
+        assert_eq!(
+            parse_float("123456789012345678901234567890"),
+            json::number::Number::from_parts(true, 12345678901234567890, 10)
+        );
+        assert_eq!(
+            parse_float("0.123456789012345678901234567890"),
+            json::number::Number::from_parts(true, 1234567890123456789, -19)
+        );
+    }
+
+    #[test]
+    fn decimal_strings() {
+        assert_eq!(decimal_to_string(0, 0), "0");
+        assert_eq!(decimal_to_string(0, 2), "0");
+        assert_eq!(decimal_to_string(12345, 2), "123.45");
+        assert_eq!(decimal_to_string(-12340, 2), "-123.4");
+        assert_eq!(decimal_to_string(5, 3), "0.005");
+        assert_eq!(decimal_to_string(-5, 3), "-0.005");
+        assert_eq!(decimal_to_string(1200, 0), "1200");
+        assert_eq!(
+            decimal_to_string(i128::MAX, 6),
+            "170141183460469231731687303715884.105727"
+        );
+        assert_eq!(
+            decimal_to_string(-99999999999999999999999999999999999999, 38),
+            "-0.99999999999999999999999999999999999999"
+        );
+    }
+
+    #[test]
+    fn decimals_to_json() {
+        assert_eq!(json::stringify(decimal_to_json(12340, 2)), "123.4");
+        assert_eq!(json::stringify(decimal_to_json(-5, 3)), "-0.005");
+        assert_eq!(
+            json::stringify(decimal_to_json(-18446744073709551615, 10)),
+            "-1844674407.3709551615"
+        );
+        assert_eq!(
+            decimal_to_json(i128::MAX, 6),
+            JsonValue::String("170141183460469231731687303715884.105727".to_owned())
+        );
+        assert_eq!(
+            decimal_to_json(99999999999999999999999999999999999999, 38),
+            JsonValue::String("0.99999999999999999999999999999999999999".to_owned())
+        );
+    }
+
+    #[test]
+    fn shortest_roundtrip() {
+        let format = FloatFormat::ShortestRoundtrip;
+        assert_eq!(json::stringify(float_to_json(0.1, false, format)), "0.1");
+        assert_eq!(
+            json::stringify(float_to_json(f64::from(0.1f32), false, format)),
+            "0.10000000149011612"
+        );
+        assert_eq!(
+            json::stringify(float_to_json(f64::from(0.1f32), true, format)),
+            "0.1"
+        );
+        assert_eq!(json::stringify(float_to_json(-2., true, format)), "-2");
+        assert_eq!(
+            json::stringify(float_to_json(f64::NAN, false, format)),
+            "null"
+        );
+    }
+
+    #[test]
+    fn fixed_and_scientific() {
+        assert_eq!(
+            json::stringify(float_to_json(1.23456, false, FloatFormat::Fixed(2))),
+            "1.23"
+        );
+        assert_eq!(
+            json::stringify(float_to_json(1234.5678, false, FloatFormat::Fixed(0))),
+            "1235"
+        );
+        assert_eq!(
+            json::stringify(float_to_json(1234.5678, false, FloatFormat::Scientific(2))),
+            "1230"
+        );
+        assert_eq!(
+            json::stringify(float_to_json(
+                f64::from(0.1f32),
+                true,
+                FloatFormat::Scientific(3)
+            )),
+            "0.1"
+        );
     }
 }
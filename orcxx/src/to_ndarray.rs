@@ -0,0 +1,153 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Exports numeric columns of ORC batches as [`ndarray`] matrices
+//!
+//! Rows are on the first axis, and columns on the second axis. Booleans, integers,
+//! and dates are converted to `f64`.
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::*;
+//! use orcxx::structured_reader::StructuredRowReader;
+//!
+//! let orc_path = "orc/examples/TestOrcFile.test1.orc";
+//! let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+//! let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let options = reader::RowReaderOptions::default().include_names(["int1", "double1"]);
+//! let mut row_reader = reader.row_reader(&options).unwrap();
+//! let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1024);
+//!
+//! while let Some(columns) = structured_row_reader.next() {
+//!     let matrix = to_ndarray::columntree_to_array2(columns).unwrap();
+//!     assert_eq!(matrix.shape(), [2, 2]);
+//!     println!("{}", matrix.mean_axis(ndarray::Axis(0)).unwrap());
+//! }
+//! ```
+
+use std::convert::TryInto;
+
+use ndarray::{Array1, Array2, ArrayView2, CowArray, Ix2, ShapeBuilder};
+use thiserror::Error;
+
+use structured_reader::ColumnTree;
+use vector::{DoubleVectorBatch, LongVectorBatch};
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ToNdarrayError {
+    #[error("ORC root type must be a struct")]
+    NotAStruct,
+    #[error("Batch contains null rows")]
+    NullRows,
+    #[error("Column {0} is not numeric")]
+    NotNumeric(String),
+    #[error("Column {0} contains nulls")]
+    ContainsNulls(String),
+}
+
+/// A column, borrowed from the batch if it already contains `f64` values
+enum Column<'a> {
+    Borrowed(&'a [f64]),
+    Owned(Array1<f64>),
+}
+
+impl Column<'_> {
+    fn as_slice(&self) -> &[f64] {
+        match self {
+            Column::Borrowed(values) => values,
+            Column::Owned(values) => values
+                .as_slice()
+                .expect("Array1::from_vec returned a non-contiguous array"),
+        }
+    }
+}
+
+fn longs_to_column<'a>(
+    name: &str,
+    column: LongVectorBatch<'a>,
+) -> Result<Column<'a>, ToNdarrayError> {
+    match column.try_as_slice_not_null() {
+        Some(values) => Ok(Column::Owned(values.iter().map(|&n| n as f64).collect())),
+        None => Err(ToNdarrayError::ContainsNulls(name.to_owned())),
+    }
+}
+
+fn doubles_to_column<'a>(
+    name: &str,
+    column: DoubleVectorBatch<'a>,
+) -> Result<Column<'a>, ToNdarrayError> {
+    match column.try_as_slice_not_null() {
+        Some(values) => Ok(Column::Borrowed(values)),
+        None => Err(ToNdarrayError::ContainsNulls(name.to_owned())),
+    }
+}
+
+/// Returns the columns of a batch as a matrix of `num_rows` rows and
+/// `num_columns` columns.
+///
+/// When the batch has a single column of type `double`, the matrix is a view of
+/// this column. Otherwise, columns are copied into a new matrix, in column-major
+/// order.
+pub fn columntree_to_array2(
+    tree: ColumnTree<'_>,
+) -> Result<CowArray<'_, f64, Ix2>, ToNdarrayError> {
+    let (not_null, num_elements, elements) = match tree {
+        ColumnTree::Struct {
+            not_null,
+            num_elements,
+            elements,
+        } => (not_null, num_elements, elements),
+        _ => return Err(ToNdarrayError::NotAStruct),
+    };
+    if not_null
+        .map(|not_null| not_null.contains(&0))
+        .unwrap_or(false)
+    {
+        return Err(ToNdarrayError::NullRows);
+    }
+    let num_rows: usize = num_elements
+        .try_into()
+        .expect("could not convert u64 to usize");
+
+    let columns = elements
+        .into_iter()
+        .map(|(name, column)| match column {
+            ColumnTree::Boolean(column)
+            | ColumnTree::Byte(column)
+            | ColumnTree::Short(column)
+            | ColumnTree::Int(column)
+            | ColumnTree::Long(column)
+            | ColumnTree::Date(column) => longs_to_column(&name, column),
+            // f32 values are stored as f64 by the C++ library
+            ColumnTree::Float(column) | ColumnTree::Double(column) => {
+                doubles_to_column(&name, column)
+            }
+            _ => Err(ToNdarrayError::NotNumeric(name)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let [Column::Borrowed(values)] = columns.as_slice() {
+        let view = ArrayView2::from_shape((num_rows, 1), values)
+            .expect("column length does not match number of rows");
+        return Ok(CowArray::from(view));
+    }
+
+    let num_columns = columns.len();
+    let mut data = Vec::with_capacity(num_rows * num_columns);
+    for column in &columns {
+        let values = column.as_slice();
+        assert_eq!(
+            values.len(),
+            num_rows,
+            "column length does not match number of rows"
+        );
+        data.extend_from_slice(values);
+    }
+    let array = Array2::from_shape_vec((num_rows, num_columns).f(), data)
+        .expect("could not build Array2 from columns");
+    Ok(CowArray::from(array))
+}
@@ -0,0 +1,70 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Converts ORC files to Parquet, through Arrow arrays (see [`to_arrow`](::to_arrow))
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::*;
+//!
+//! let orc_path = "orc/examples/TestOrcFile.test1.orc";
+//! let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+//! let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let mut row_reader = reader.row_reader(&reader::RowReaderOptions::default()).unwrap();
+//!
+//! let mut parquet_file = Vec::new();
+//! to_parquet::write_parquet(&mut row_reader, 1024, &mut parquet_file, None)
+//!     .expect("Could not convert to Parquet");
+//! assert_eq!(&parquet_file[0..4], b"PAR1");
+//! ```
+
+use std::io::Write;
+use std::sync::Arc;
+
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use thiserror::Error;
+
+use reader::RowReader;
+use structured_reader::StructuredRowReader;
+use to_arrow::ToArrowError;
+
+// Re-exported for compatibility, they used to be defined in this module
+pub use to_arrow::{
+    columntree_to_arrow_array, columntree_to_record_batch, kind_to_arrow_datatype,
+    kind_to_arrow_schema,
+};
+
+#[derive(Error, Debug)]
+pub enum ToParquetError {
+    #[error("{0}")]
+    ToArrow(#[from] ToArrowError),
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] ParquetError),
+}
+
+/// Reads all remaining rows from `row_reader`, `batch_size` at a time, and writes
+/// them as a Parquet file to `writer`.
+pub fn write_parquet<W: Write + Send>(
+    row_reader: &mut RowReader,
+    batch_size: u64,
+    writer: W,
+    properties: Option<WriterProperties>,
+) -> Result<(), ToParquetError> {
+    let kind = row_reader.selected_kind();
+    let schema = Arc::new(kind_to_arrow_schema(&kind)?);
+    let mut parquet_writer = ArrowWriter::try_new(writer, schema.clone(), properties)?;
+
+    let mut structured_row_reader = StructuredRowReader::new(row_reader, batch_size);
+    while let Some(columns) = structured_row_reader.next() {
+        parquet_writer.write(&columntree_to_record_batch(columns, &kind, schema.clone())?)?;
+    }
+
+    parquet_writer.close()?;
+    Ok(())
+}
@@ -0,0 +1,300 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Produces line-separated JSON documents from ORC, using `serde_json`
+//!
+//! This is an alternative to [`to_json`](::to_json) (enabled by the `json` feature),
+//! which produces [`serde_json::Value`] instead of `json::JsonValue`. Objects have
+//! the same structure and their keys are in the same order, but column transforms
+//! are not supported.
+//!
+//! # Example
+//!
+//! ```
+//! use orcxx::*;
+//!
+//! let orc_path = "orc/examples/TestOrcFile.test1.orc";
+//! let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+//! let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let mut row_reader = reader.row_reader(&reader::RowReaderOptions::default()).unwrap();
+//!
+//! let mut structured_row_reader = structured_reader::StructuredRowReader::new(&mut row_reader, 1024);
+//!
+//! while let Some(columns) = structured_row_reader.next() {
+//!     for object in to_serde_json::columntree_to_json_rows(columns) {
+//!         println!("{}", serde_json::to_string_pretty(&object).unwrap());
+//!     }
+//! }
+//! ```
+
+use std::convert::TryInto;
+use std::io;
+use std::iter;
+use std::ops::Range;
+
+use serde_json::{Map, Number, Value};
+
+use structured_reader::ColumnTree;
+use vector::{decimal_to_string, trim_decimal, DecimalVectorBatch};
+
+/// Non-finite floats are converted to `null`, like [`to_json`](::to_json) does
+fn float_to_json(value: f64) -> Value {
+    Number::from_f64(value)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+/// Decimals are numbers when [`serde_json::Number`] represents them exactly: integers
+/// which fit in 64 bits, and decimals with at most 15 significant digits (which
+/// `f64` round-trips). Others are strings with their exact value.
+fn decimal_to_json(mantissa: i128, scale: u32) -> Value {
+    let (mantissa, scale) = trim_decimal(mantissa, scale);
+    if scale == 0 {
+        let integer: Result<i64, _> = mantissa.try_into();
+        if let Ok(integer) = integer {
+            return integer.into();
+        }
+        let integer: Result<u64, _> = mantissa.try_into();
+        if let Ok(integer) = integer {
+            return integer.into();
+        }
+    } else if mantissa.unsigned_abs() < 1_000_000_000_000_000 {
+        let value: f64 = decimal_to_string(mantissa, scale)
+            .parse()
+            .expect("Could not parse formatted decimal");
+        return float_to_json(value);
+    }
+    Value::String(decimal_to_string(mantissa, scale))
+}
+
+/// Given a set of columns (as a [`ColumnTree`]), returns a vector of rows
+/// represented as [`serde_json::Value`].
+///
+/// Objects only have the fields in the [`RowReader`](::reader::RowReader)'s
+/// [`selected_kind`](::reader::RowReader::selected_kind), in the same order.
+pub fn columntree_to_json_rows(tree: ColumnTree<'_>) -> Vec<Value> {
+    columntree_to_json_values(tree)
+}
+
+/// Same as [`columntree_to_json_rows`], but serializes rows to `writer`, one JSON
+/// document per line.
+///
+/// # Example
+///
+/// ```
+/// use orcxx::*;
+///
+/// let orc_path = "orc/examples/TestOrcFile.test1.orc";
+/// let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+/// let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+///
+/// let options = reader::RowReaderOptions::default().include_names(["int1", "string1"]);
+/// let mut row_reader = reader.row_reader(&options).unwrap();
+///
+/// let mut structured_row_reader = structured_reader::StructuredRowReader::new(&mut row_reader, 1024);
+///
+/// let mut lines = Vec::new();
+/// while let Some(columns) = structured_row_reader.next() {
+///     to_serde_json::write_json_lines(columns, &mut lines).expect("Could not write");
+/// }
+/// assert_eq!(
+///     String::from_utf8(lines).unwrap(),
+///     "{\"int1\":65536,\"string1\":\"hi\"}\n{\"int1\":65536,\"string1\":\"bye\"}\n"
+/// );
+/// ```
+pub fn write_json_lines<W: io::Write>(tree: ColumnTree<'_>, writer: &mut W) -> io::Result<()> {
+    // Rows are built and written one at a time, so the batch is never held as JSON
+    // values all at once
+    for row in columntree_to_json_value_iter(tree) {
+        serde_json::to_writer(&mut *writer, &row)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn columntree_to_json_values(tree: ColumnTree<'_>) -> Vec<Value> {
+    columntree_to_json_value_iter(tree).collect()
+}
+
+type JsonValueIter<'a> = Box<dyn Iterator<Item = Value> + 'a>;
+
+fn map_nullable_json_value_iter<'a, V, C: Iterator<Item = Option<V>> + 'a, F>(
+    column: C,
+    f: F,
+) -> JsonValueIter<'a>
+where
+    F: Fn(V) -> Value + 'a,
+{
+    Box::new(column.map(move |v| match v {
+        None => Value::Null,
+        Some(v) => f(v),
+    }))
+}
+
+/// Takes `range.len()` values from `values`, after skipping values before
+/// `range.start` (`position` is the index of the next value of `values`)
+fn take_range<'a, 'b>(
+    values: &'b mut JsonValueIter<'a>,
+    position: &mut usize,
+    range: Range<usize>,
+) -> iter::Take<&'b mut JsonValueIter<'a>> {
+    assert!(
+        range.start >= *position,
+        "Offsets overlap: {} < {}",
+        range.start,
+        *position
+    );
+    for _ in *position..range.start {
+        values.next().expect("Offset past the end of the elements");
+    }
+    *position = range.end;
+    values.take(range.len())
+}
+
+/// Returns an iterator which yields the value of each row of `tree`, computed only
+/// when it is needed.
+fn columntree_to_json_value_iter(tree: ColumnTree<'_>) -> JsonValueIter<'_> {
+    match tree {
+        ColumnTree::Boolean(column) => {
+            map_nullable_json_value_iter(column.iter(), |b| Value::Bool(b != 0))
+        }
+        ColumnTree::Byte(column)
+        | ColumnTree::Short(column)
+        | ColumnTree::Int(column)
+        | ColumnTree::Long(column) => map_nullable_json_value_iter(column.iter(), |n| n.into()),
+        ColumnTree::Float(column) => map_nullable_json_value_iter(column.iter(), |f| {
+            // Go through the shortest representation of the f32, so 0.1f32 is
+            // 0.1 instead of 0.10000000149011612
+            float_to_json(
+                (f as f32)
+                    .to_string()
+                    .parse()
+                    .expect("Could not parse formatted f32"),
+            )
+        }),
+        ColumnTree::Double(column) => map_nullable_json_value_iter(column.iter(), float_to_json),
+        ColumnTree::String(column) => map_nullable_json_value_iter(column.iter(), |s| {
+            Value::String(String::from_utf8_lossy(s).into_owned())
+        }),
+        ColumnTree::Timestamp(column) | ColumnTree::TimestampInstant(column) => {
+            map_nullable_json_value_iter(column.to_timestamp_strings().into_iter(), Value::String)
+        }
+        ColumnTree::Date(column) => {
+            map_nullable_json_value_iter(column.to_date_strings().into_iter(), Value::String)
+        }
+        ColumnTree::Decimal64(column) => map_nullable_json_value_iter(column.iter(), |n| {
+            decimal_to_json(n.mantissa(), n.scale())
+        }),
+        ColumnTree::Decimal128(column) => {
+            map_nullable_json_value_iter(column.iter_raw(), |(mantissa, scale)| {
+                decimal_to_json(mantissa, scale)
+            })
+        }
+        ColumnTree::Binary(column) => map_nullable_json_value_iter(column.iter(), |s| {
+            Value::Array(s.iter().map(|&byte| byte.into()).collect())
+        }),
+        ColumnTree::Struct {
+            not_null,
+            num_elements,
+            elements,
+        } => {
+            if let Some(not_null) = not_null {
+                assert_eq!(num_elements, not_null.len() as u64);
+            }
+            let num_fields = elements.len();
+
+            // Fields have a value for every row, including rows where the struct
+            // is null (where they are null too)
+            let mut fields: Vec<_> = elements
+                .into_iter()
+                .map(|(field_name, subtree)| (field_name, columntree_to_json_value_iter(subtree)))
+                .collect();
+            let mut not_null = not_null.map(|not_null| not_null.iter());
+            Box::new((0..num_elements).map(move |row| {
+                let mut object = Map::with_capacity(num_fields);
+                for (field_name, subvalues) in fields.iter_mut() {
+                    let subvalue = subvalues.next().unwrap_or_else(|| {
+                        panic!(
+                            "Struct field {} has {} values, but the struct has {} rows",
+                            field_name, row, num_elements
+                        )
+                    });
+                    object.insert(field_name.clone(), subvalue);
+                }
+                match not_null.as_mut().and_then(|not_null| not_null.next()) {
+                    Some(&0) => Value::Null,
+                    _ => Value::Object(object),
+                }
+            }))
+        }
+        ColumnTree::List { offsets, elements } => {
+            let mut values = columntree_to_json_value_iter(*elements);
+            let mut position = 0;
+            Box::new(offsets.map(move |v| match v {
+                Some(range) => {
+                    Value::Array(take_range(&mut values, &mut position, range).collect())
+                }
+                None => Value::Null,
+            }))
+        }
+        ColumnTree::Map {
+            offsets,
+            keys,
+            elements,
+        } => {
+            let mut keys = columntree_to_json_value_iter(*keys);
+            let mut values = columntree_to_json_value_iter(*elements);
+            let mut keys_position = 0;
+            let mut values_position = 0;
+            Box::new(offsets.map(move |v| {
+                match v {
+                    Some(range) => Value::Array(
+                        iter::zip(
+                            take_range(&mut keys, &mut keys_position, range.clone()),
+                            take_range(&mut values, &mut values_position, range),
+                        )
+                        .map(|(key, value)| {
+                            let mut object = Map::with_capacity(2);
+                            object.insert("key".to_owned(), key);
+                            object.insert("value".to_owned(), value);
+                            Value::Object(object)
+                        })
+                        .collect(),
+                    ),
+                    None => Value::Null,
+                }
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimals_to_json() {
+        assert_eq!(decimal_to_json(12340, 2).to_string(), "123.4");
+        assert_eq!(decimal_to_json(-5, 3).to_string(), "-0.005");
+        assert_eq!(decimal_to_json(-1200, 0).to_string(), "-1200");
+        assert_eq!(
+            decimal_to_json(18446744073709551615, 0).to_string(),
+            "18446744073709551615"
+        );
+        assert_eq!(
+            decimal_to_json(999999999999999, 5).to_string(),
+            "9999999999.99999"
+        );
+        assert_eq!(
+            decimal_to_json(1000000000000001, 5),
+            Value::String("10000000000.00001".to_owned())
+        );
+        assert_eq!(
+            decimal_to_json(i128::MAX, 6),
+            Value::String("170141183460469231731687303715884.105727".to_owned())
+        );
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Dynamically-typed ORC values
+
+use rust_decimal::Decimal;
+
+use deserialize::{decimal_from_raw, DeserializationError};
+use kind::Kind;
+use structured_reader::{columnvectorbatch_to_columntree, ColumnTree};
+use vector::{BorrowedColumnVectorBatch, DecimalVectorBatch};
+use Timestamp;
+
+/// A single value of any [`Kind`], for code which does not know the schema of the
+/// files it works on at compile time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrcValue {
+    Null,
+    Boolean(bool),
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    /// Value of a `string`, `varchar`, or `char` column
+    String(String),
+    Binary(Vec<u8>),
+    Timestamp(Timestamp),
+    Decimal(Decimal),
+    /// Number of days since the epoch
+    Date(i64),
+    List(Vec<OrcValue>),
+    Map(Vec<(OrcValue, OrcValue)>),
+    Struct(Vec<(String, OrcValue)>),
+    /// Value of the `tag`-th variant of a union
+    Union {
+        tag: u8,
+        value: Box<OrcValue>,
+    },
+}
+
+impl OrcValue {
+    /// Returns whether this value can be stored in a column of the given kind.
+    ///
+    /// [`OrcValue::Null`] matches any kind, `varchar` and `char` values must not be
+    /// longer than the kind allows, and decimals must have the same scale and
+    /// fit in its precision.
+    pub fn matches_kind(&self, kind: &Kind) -> bool {
+        match (self, kind) {
+            (OrcValue::Null, _) => true,
+            (OrcValue::Boolean(_), Kind::Boolean)
+            | (OrcValue::Byte(_), Kind::Byte)
+            | (OrcValue::Short(_), Kind::Short)
+            | (OrcValue::Int(_), Kind::Int)
+            | (OrcValue::Long(_), Kind::Long)
+            | (OrcValue::Float(_), Kind::Float)
+            | (OrcValue::Double(_), Kind::Double)
+            | (OrcValue::String(_), Kind::String)
+            | (OrcValue::Binary(_), Kind::Binary)
+            | (OrcValue::Timestamp(_), Kind::Timestamp)
+            | (OrcValue::Timestamp(_), Kind::TimestampInstant)
+            | (OrcValue::Date(_), Kind::Date) => true,
+            (OrcValue::String(s), Kind::Varchar(max_length))
+            | (OrcValue::String(s), Kind::Char(max_length)) => {
+                s.chars().count() as u64 <= *max_length
+            }
+            (OrcValue::Decimal(d), Kind::Decimal { precision, scale }) => {
+                let digits = d.mantissa().unsigned_abs().to_string().len() as u64;
+                u64::from(d.scale()) == *scale && digits <= *precision
+            }
+            (OrcValue::List(elements), Kind::List(element_kind)) => elements
+                .iter()
+                .all(|element| element.matches_kind(element_kind)),
+            (OrcValue::Map(entries), Kind::Map { key, value }) => {
+                entries.iter().all(|(entry_key, entry_value)| {
+                    entry_key.matches_kind(key) && entry_value.matches_kind(value)
+                })
+            }
+            (OrcValue::Struct(fields), Kind::Struct(field_kinds)) => {
+                fields.len() == field_kinds.len()
+                    && fields.iter().zip(field_kinds).all(
+                        |((name, field), (expected_name, field_kind))| {
+                            name == expected_name && field.matches_kind(field_kind)
+                        },
+                    )
+            }
+            (OrcValue::Union { tag, value }, Kind::Union(variants)) => variants
+                .get(usize::from(*tag))
+                .map(|variant| value.matches_kind(variant))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Returns the value of each row of a vector batch of the given kind
+    /// (usually [`RowReader::selected_kind`](::reader::RowReader::selected_kind)).
+    ///
+    /// Returns an error if a string is not valid UTF-8, or a decimal does not fit
+    /// in [`Decimal`].
+    ///
+    /// # Panics
+    ///
+    /// If `batch` is not a vector batch of type `kind`, or if `kind` contains unions or
+    /// timestamps with local timezone, which are not supported yet.
+    pub fn rows_from_vector_batch(
+        batch: BorrowedColumnVectorBatch<'_>,
+        kind: &Kind,
+    ) -> Result<Vec<OrcValue>, DeserializationError> {
+        values_from_columntree(columnvectorbatch_to_columntree(batch, kind), kind)
+    }
+}
+
+fn nullable<T>(value: Option<T>, f: impl FnOnce(T) -> OrcValue) -> OrcValue {
+    value.map(f).unwrap_or(OrcValue::Null)
+}
+
+fn values_from_columntree(
+    tree: ColumnTree<'_>,
+    kind: &Kind,
+) -> Result<Vec<OrcValue>, DeserializationError> {
+    Ok(match tree {
+        ColumnTree::Boolean(column) => column
+            .iter()
+            .map(|b| nullable(b, |b| OrcValue::Boolean(b != 0)))
+            .collect(),
+        ColumnTree::Byte(column) => column
+            .iter()
+            .map(|n| nullable(n, |n| OrcValue::Byte(n as i8)))
+            .collect(),
+        ColumnTree::Short(column) => column
+            .iter()
+            .map(|n| nullable(n, |n| OrcValue::Short(n as i16)))
+            .collect(),
+        ColumnTree::Int(column) => column
+            .iter()
+            .map(|n| nullable(n, |n| OrcValue::Int(n as i32)))
+            .collect(),
+        ColumnTree::Long(column) => column.iter().map(|n| nullable(n, OrcValue::Long)).collect(),
+        ColumnTree::Float(column) => column
+            .iter()
+            .map(|n| nullable(n, |n| OrcValue::Float(n as f32)))
+            .collect(),
+        ColumnTree::Double(column) => column
+            .iter()
+            .map(|n| nullable(n, OrcValue::Double))
+            .collect(),
+        ColumnTree::String(column) => column
+            .iter()
+            .enumerate()
+            .map(|(row, s)| match s {
+                None => Ok(OrcValue::Null),
+                Some(s) => std::str::from_utf8(s)
+                    .map(|s| OrcValue::String(s.to_owned()))
+                    .map_err(|e| DeserializationError::Utf8Error(e).at_row(row)),
+            })
+            .collect::<Result<_, _>>()?,
+        ColumnTree::Binary(column) => column
+            .iter()
+            .map(|s| nullable(s, |s| OrcValue::Binary(s.to_vec())))
+            .collect(),
+        ColumnTree::Timestamp(column) | ColumnTree::TimestampInstant(column) => column
+            .iter()
+            .map(|ts| {
+                nullable(ts, |(seconds, nanoseconds)| {
+                    OrcValue::Timestamp(Timestamp {
+                        seconds,
+                        nanoseconds,
+                    })
+                })
+            })
+            .collect(),
+        ColumnTree::Date(column) => column.iter().map(|n| nullable(n, OrcValue::Date)).collect(),
+        ColumnTree::Decimal64(column) => column
+            .iter()
+            .map(|d| nullable(d, OrcValue::Decimal))
+            .collect(),
+        ColumnTree::Decimal128(column) => column
+            .iter_raw()
+            .enumerate()
+            .map(|(row, d)| match d {
+                None => Ok(OrcValue::Null),
+                Some(d) => decimal_from_raw(d)
+                    .map(OrcValue::Decimal)
+                    .map_err(|e| e.at_row(row)),
+            })
+            .collect::<Result<_, _>>()?,
+        ColumnTree::List { offsets, elements } => {
+            let element_kind = match kind {
+                Kind::List(element_kind) => element_kind,
+                _ => panic!("Got ColumnTree::List for {:?}", kind),
+            };
+            let elements = values_from_columntree(*elements, element_kind)?;
+            offsets
+                .map(|range| nullable(range, |range| OrcValue::List(elements[range].to_vec())))
+                .collect()
+        }
+        ColumnTree::Map {
+            offsets,
+            keys,
+            elements,
+        } => {
+            let (key_kind, value_kind) = match kind {
+                Kind::Map { key, value } => (key, value),
+                _ => panic!("Got ColumnTree::Map for {:?}", kind),
+            };
+            let keys = values_from_columntree(*keys, key_kind)?;
+            let values = values_from_columntree(*elements, value_kind)?;
+            offsets
+                .map(|range| {
+                    nullable(range, |range| {
+                        OrcValue::Map(
+                            keys[range.clone()]
+                                .iter()
+                                .cloned()
+                                .zip(values[range].iter().cloned())
+                                .collect(),
+                        )
+                    })
+                })
+                .collect()
+        }
+        ColumnTree::Struct {
+            not_null,
+            num_elements,
+            elements,
+        } => {
+            let field_kinds = match kind {
+                Kind::Struct(field_kinds) => field_kinds,
+                _ => panic!("Got ColumnTree::Struct for {:?}", kind),
+            };
+            // Struct children are aligned with their parent, so they have one
+            // value for every row, including null ones
+            let mut fields: Vec<_> = elements
+                .into_iter()
+                .zip(field_kinds)
+                .map(|((name, subtree), (_, field_kind))| {
+                    Ok((
+                        name,
+                        values_from_columntree(subtree, field_kind)?.into_iter(),
+                    ))
+                })
+                .collect::<Result<_, DeserializationError>>()?;
+            (0..num_elements as usize)
+                .map(|i| {
+                    let row: Vec<_> = fields
+                        .iter_mut()
+                        .map(|(name, values)| {
+                            (
+                                name.clone(),
+                                values.next().expect("Struct field is too short"),
+                            )
+                        })
+                        .collect();
+                    match not_null {
+                        Some(not_null) if not_null[i] == 0 => OrcValue::Null,
+                        _ => OrcValue::Struct(row),
+                    }
+                })
+                .collect()
+        }
+    })
+}
@@ -6,21 +6,30 @@
 //! Containers for columns of values of uniform types
 //!
 //! Structures in this modules are returned by [`RowReader`](crate::reader::RowReader) and
-//! [`StructuredRowReader`](crate::structured_reader::StructuredRowReader) and cannot
-//! be instantiated directly.
+//! [`StructuredRowReader`](crate::structured_reader::StructuredRowReader), and
+//! empty batches are created by [`OwnedColumnVectorBatch::new`].
+//!
+//! [`OwnedColumnVectorBatch::borrow_mut`] returns mutable counterparts of these
+//! structures (eg. [`LongVectorBatchMut`]), which can be filled in order to be
+//! written with a [`Writer`](crate::writer::Writer).
 
 use std::convert::TryInto;
 use std::fmt;
 use std::marker::PhantomData;
 use std::ops::Range;
 use std::os::raw::c_char;
+use std::pin::Pin;
 use std::ptr;
 
-use cxx::UniquePtr;
+use cxx::{let_cxx_string, SharedPtr, UniquePtr};
 use rust_decimal::Decimal;
 
 use errors::{OrcError, OrcResult};
+use kind;
+use kind::Kind;
 use memorypool;
+use memorypool::DataBuffer;
+use reader::SelectedColumn;
 
 // TODO: remove $function_name when https://github.com/rust-lang/rust/issues/29599
 // is stabilized
@@ -76,6 +85,13 @@ pub(crate) mod ffi {
         type DoubleDataBuffer = crate::memorypool::ffi::DoubleDataBuffer;
         type StringDataBuffer = crate::memorypool::ffi::StringDataBuffer;
         type CharDataBuffer = crate::memorypool::ffi::CharDataBuffer;
+        type UCharDataBuffer = crate::memorypool::ffi::UCharDataBuffer;
+        type UInt64DataBuffer = crate::memorypool::ffi::UInt64DataBuffer;
+    }
+
+    #[namespace = "orc"]
+    unsafe extern "C++" {
+        type Type = crate::kind::ffi::Type;
     }
 
     #[namespace = "orc"]
@@ -93,10 +109,34 @@ pub(crate) mod ffi {
         type StructVectorBatch;
         type ListVectorBatch;
         type MapVectorBatch;
+        type UnionVectorBatch;
     }
 
     impl UniquePtr<ColumnVectorBatch> {}
 
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        #[rust_name = "Type_createRowBatch"]
+        fn createRowBatch(orc_type: &Type, size: u64) -> UniquePtr<ColumnVectorBatch>;
+    }
+
+    #[namespace = "orc"]
+    unsafe extern "C++" {
+        fn resize(self: Pin<&mut ColumnVectorBatch>, capacity: u64);
+    }
+
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        fn setString(vectorBatch: Pin<&mut StringVectorBatch>, index: u64, value: &[u8]);
+        fn setInt128(
+            vectorBatch: Pin<&mut Decimal128VectorBatch>,
+            index: u64,
+            highBits: i64,
+            lowBits: u64,
+        );
+        fn clearBatch(vectorBatch: Pin<&mut ColumnVectorBatch>);
+    }
+
     #[namespace = "orcxx_rs"]
     unsafe extern "C++" {
         type ColumnVectorBatchPtr;
@@ -112,6 +152,10 @@ pub(crate) mod ffi {
         fn get_numElements(vectorBatch: &ColumnVectorBatch) -> u64;
         fn get_hasNulls(vectorBatch: &ColumnVectorBatch) -> bool;
         fn get_notNull(vectorBatch: &ColumnVectorBatch) -> &CharDataBuffer;
+        fn get_capacity(vectorBatch: &ColumnVectorBatch) -> u64;
+
+        fn set_numElements(vectorBatch: Pin<&mut ColumnVectorBatch>, numElements: u64);
+        fn set_hasNulls(vectorBatch: Pin<&mut ColumnVectorBatch>, hasNulls: bool);
 
         #[rust_name = "LongVectorBatch_get_data"]
         fn get_data(vectorBatch: &LongVectorBatch) -> &Int64DataBuffer;
@@ -159,6 +203,13 @@ pub(crate) mod ffi {
         fn get_elements(vectorBatch: &MapVectorBatch) -> &UniquePtr<ColumnVectorBatch>;
         #[rust_name = "MapVectorBatch_get_offsets"]
         fn get_offsets(vectorBatch: &MapVectorBatch) -> &Int64DataBuffer;
+
+        #[rust_name = "UnionVectorBatch_get_tags"]
+        fn get_tags(vectorBatch: &UnionVectorBatch) -> &UCharDataBuffer;
+        #[rust_name = "UnionVectorBatch_get_offsets"]
+        fn get_offsets(vectorBatch: &UnionVectorBatch) -> &UInt64DataBuffer;
+        #[rust_name = "UnionVectorBatch_get_children"]
+        fn get_children(vectorBatch: &UnionVectorBatch) -> &CxxVector<ColumnVectorBatchPtr>;
     }
 
     #[namespace = "orcxx_rs::utils"]
@@ -181,6 +232,8 @@ pub(crate) mod ffi {
         fn try_into(vectorBatch: &ColumnVectorBatch) -> Result<&ListVectorBatch>;
         #[rust_name = "try_into_MapVectorBatch"]
         fn try_into(vectorBatch: &ColumnVectorBatch) -> Result<&MapVectorBatch>;
+        #[rust_name = "try_into_UnionVectorBatch"]
+        fn try_into(vectorBatch: &ColumnVectorBatch) -> Result<&UnionVectorBatch>;
 
         #[rust_name = "LongVectorBatch_into_ColumnVectorBatch"]
         fn try_into(vectorBatch: &LongVectorBatch) -> &ColumnVectorBatch;
@@ -200,6 +253,52 @@ pub(crate) mod ffi {
         fn try_into(vectorBatch: &ListVectorBatch) -> &ColumnVectorBatch;
         #[rust_name = "MapVectorBatch_into_ColumnVectorBatch"]
         fn try_into(vectorBatch: &MapVectorBatch) -> &ColumnVectorBatch;
+        #[rust_name = "UnionVectorBatch_into_ColumnVectorBatch"]
+        fn try_into(vectorBatch: &UnionVectorBatch) -> &ColumnVectorBatch;
+
+        #[rust_name = "try_into_LongVectorBatchMut"]
+        fn try_into(vectorBatch: Pin<&mut ColumnVectorBatch>) -> Result<Pin<&mut LongVectorBatch>>;
+        #[rust_name = "try_into_DoubleVectorBatchMut"]
+        fn try_into(
+            vectorBatch: Pin<&mut ColumnVectorBatch>,
+        ) -> Result<Pin<&mut DoubleVectorBatch>>;
+        #[rust_name = "try_into_StringVectorBatchMut"]
+        fn try_into(
+            vectorBatch: Pin<&mut ColumnVectorBatch>,
+        ) -> Result<Pin<&mut StringVectorBatch>>;
+        #[rust_name = "try_into_TimestampVectorBatchMut"]
+        fn try_into(
+            vectorBatch: Pin<&mut ColumnVectorBatch>,
+        ) -> Result<Pin<&mut TimestampVectorBatch>>;
+        #[rust_name = "try_into_Decimal128VectorBatchMut"]
+        fn try_into(
+            vectorBatch: Pin<&mut ColumnVectorBatch>,
+        ) -> Result<Pin<&mut Decimal128VectorBatch>>;
+        #[rust_name = "try_into_StructVectorBatchMut"]
+        fn try_into(
+            vectorBatch: Pin<&mut ColumnVectorBatch>,
+        ) -> Result<Pin<&mut StructVectorBatch>>;
+        #[rust_name = "try_into_ListVectorBatchMut"]
+        fn try_into(vectorBatch: Pin<&mut ColumnVectorBatch>) -> Result<Pin<&mut ListVectorBatch>>;
+        #[rust_name = "try_into_MapVectorBatchMut"]
+        fn try_into(vectorBatch: Pin<&mut ColumnVectorBatch>) -> Result<Pin<&mut MapVectorBatch>>;
+
+        #[rust_name = "LongVectorBatchMut_into_ColumnVectorBatch"]
+        fn try_into(vectorBatch: Pin<&mut LongVectorBatch>) -> Pin<&mut ColumnVectorBatch>;
+        #[rust_name = "DoubleVectorBatchMut_into_ColumnVectorBatch"]
+        fn try_into(vectorBatch: Pin<&mut DoubleVectorBatch>) -> Pin<&mut ColumnVectorBatch>;
+        #[rust_name = "StringVectorBatchMut_into_ColumnVectorBatch"]
+        fn try_into(vectorBatch: Pin<&mut StringVectorBatch>) -> Pin<&mut ColumnVectorBatch>;
+        #[rust_name = "TimestampVectorBatchMut_into_ColumnVectorBatch"]
+        fn try_into(vectorBatch: Pin<&mut TimestampVectorBatch>) -> Pin<&mut ColumnVectorBatch>;
+        #[rust_name = "Decimal128VectorBatchMut_into_ColumnVectorBatch"]
+        fn try_into(vectorBatch: Pin<&mut Decimal128VectorBatch>) -> Pin<&mut ColumnVectorBatch>;
+        #[rust_name = "StructVectorBatchMut_into_ColumnVectorBatch"]
+        fn try_into(vectorBatch: Pin<&mut StructVectorBatch>) -> Pin<&mut ColumnVectorBatch>;
+        #[rust_name = "ListVectorBatchMut_into_ColumnVectorBatch"]
+        fn try_into(vectorBatch: Pin<&mut ListVectorBatch>) -> Pin<&mut ColumnVectorBatch>;
+        #[rust_name = "MapVectorBatchMut_into_ColumnVectorBatch"]
+        fn try_into(vectorBatch: Pin<&mut MapVectorBatch>) -> Pin<&mut ColumnVectorBatch>;
 
         #[rust_name = "ColumnVectorBatch_toString"]
         fn toString(type_: &ColumnVectorBatch) -> UniquePtr<CxxString>;
@@ -221,6 +320,8 @@ pub(crate) mod ffi {
         fn toString(type_: &ListVectorBatch) -> UniquePtr<CxxString>;
         #[rust_name = "MapVectorBatch_toString"]
         fn toString(type_: &MapVectorBatch) -> UniquePtr<CxxString>;
+        #[rust_name = "UnionVectorBatch_toString"]
+        fn toString(type_: &UnionVectorBatch) -> UniquePtr<CxxString>;
     }
 }
 
@@ -248,10 +349,31 @@ pub trait ColumnVectorBatch<'a> {
         })
     }
 
+    /// Same as [`ColumnVectorBatch::not_null`], but yields `true` for rows which are
+    /// not null
+    fn not_null_bools(&self) -> Option<NotNullBools<'a>> {
+        self.not_null()
+            .map(|not_null| not_null.iter().map(is_not_null as fn(&i8) -> bool))
+    }
+
+    /// Returns the number of null rows
+    fn null_count(&self) -> u64 {
+        match self.not_null() {
+            Some(not_null) => not_null.iter().filter(|&&b| b == 0).count() as u64,
+            None => 0,
+        }
+    }
+
+    /// Same as [`ColumnVectorBatch::not_null`], but packs flags into a [`Bitmap`],
+    /// which can be converted to Arrow's `NullBuffer` with the `arrow` feature.
+    fn validity(&self) -> Option<Bitmap> {
+        self.not_null_bools().map(Iterator::collect)
+    }
+
     /// Same as [`BorrowedColumnVectorBatch::not_null`] but returns a pointer
     fn not_null_ptr(&self) -> Option<ptr::NonNull<i8>> {
         if ffi::get_hasNulls(self.inner()) {
-            let not_null = ffi::get_notNull(self.inner()).data();
+            let not_null = ffi::get_notNull(self.inner()).checked_data(self.num_elements());
             assert_ne!(not_null, ptr::null());
 
             // This is safe because we just checked it is not null
@@ -262,8 +384,21 @@ pub trait ColumnVectorBatch<'a> {
     }
 }
 
+fn is_not_null(b: &i8) -> bool {
+    *b != 0
+}
+
+/// Iterator returned by [`ColumnVectorBatch::not_null_bools`]
+pub type NotNullBools<'a> = std::iter::Map<std::slice::Iter<'a, i8>, fn(&i8) -> bool>;
+
 /// A column (or set of column) of a stripe, with values of unknown type.
-pub struct OwnedColumnVectorBatch(pub(crate) UniquePtr<ffi::ColumnVectorBatch>);
+pub struct OwnedColumnVectorBatch(
+    pub(crate) UniquePtr<ffi::ColumnVectorBatch>,
+    // Memory pool the batch was allocated from, see ReaderOptions::memory_budget.
+    // Declared after the batch, so it is dropped after it, even when the batch
+    // outlives its reader.
+    #[allow(dead_code)] pub(crate) Option<SharedPtr<::reader::ffi::MemoryPool>>,
+);
 
 impl_debug!(OwnedColumnVectorBatch, ffi::ColumnVectorBatch_toString);
 
@@ -274,9 +409,27 @@ impl<'a> ColumnVectorBatch<'a> for &'a OwnedColumnVectorBatch {
 }
 
 impl OwnedColumnVectorBatch {
+    /// Returns an empty batch of the given type, which can hold up to `size` rows
+    /// before growing. It can be written by a [`Writer`](::writer::Writer) of the
+    /// same type.
+    pub fn new(kind: &Kind, size: u64) -> OrcResult<OwnedColumnVectorBatch> {
+        let_cxx_string!(type_string = kind.to_type_string());
+        let orc_type = kind::ffi::buildTypeFromString(&type_string).map_err(OrcError)?;
+        Ok(OwnedColumnVectorBatch(
+            ffi::Type_createRowBatch(&orc_type, size),
+            None,
+        ))
+    }
+
     pub fn borrow(&self) -> BorrowedColumnVectorBatch<'_> {
         BorrowedColumnVectorBatch(&self.0)
     }
+
+    /// Returns a view of the batch which can be used to change its content, eg.
+    /// before passing it to [`Writer::write_batch`](::writer::Writer::write_batch)
+    pub fn borrow_mut(&mut self) -> BorrowedColumnVectorBatchMut<'_> {
+        BorrowedColumnVectorBatchMut(self.0.pin_mut())
+    }
 }
 
 unsafe impl Send for OwnedColumnVectorBatch {}
@@ -349,6 +502,215 @@ impl<'a> BorrowedColumnVectorBatch<'a> {
             .map_err(OrcError)
             .map(MapVectorBatch)
     }
+
+    pub fn try_into_unions(&self) -> OrcResult<UnionVectorBatch<'a>> {
+        ffi::try_into_UnionVectorBatch(self.0)
+            .map_err(OrcError)
+            .map(UnionVectorBatch)
+    }
+
+    /// Checks the batch has the given type, and that its buffers, offsets, and
+    /// children are consistent with its number of rows (which must be at least
+    /// `min_rows`), so the C++ library can read it without going out of bounds.
+    ///
+    /// `path` is the name of the column (empty for the root), used in error messages.
+    pub(crate) fn check_consistency(
+        &self,
+        kind: &Kind,
+        min_rows: u64,
+        path: &str,
+    ) -> Result<(), String> {
+        let rows = self.num_elements();
+        let column = if path.is_empty() { "(root)" } else { path };
+        if rows < min_rows {
+            return Err(format!(
+                "Column {} has {} rows, expected at least {}",
+                column, rows, min_rows
+            ));
+        }
+        let check_buffer = |name: &str, size: u64, expected: u64| {
+            if size < expected {
+                Err(format!(
+                    "Column {} has {} items in its {} buffer, expected at least {}",
+                    column, size, name, expected
+                ))
+            } else {
+                Ok(())
+            }
+        };
+        let mismatch = |e: OrcError| {
+            format!(
+                "Column {} does not match type {}: {}",
+                column,
+                kind.to_type_string(),
+                e
+            )
+        };
+        if ffi::get_hasNulls(self.0) {
+            check_buffer("notNull", ffi::get_notNull(self.0).size(), rows)?;
+        }
+        match kind {
+            Kind::Boolean | Kind::Byte | Kind::Short | Kind::Int | Kind::Long | Kind::Date => {
+                let batch = self.try_into_longs().map_err(mismatch)?;
+                check_buffer("data", ffi::LongVectorBatch_get_data(batch.0).size(), rows)
+            }
+            Kind::Float | Kind::Double => {
+                let batch = self.try_into_doubles().map_err(mismatch)?;
+                check_buffer(
+                    "data",
+                    ffi::DoubleVectorBatch_get_data(batch.0).size(),
+                    rows,
+                )
+            }
+            Kind::String | Kind::Binary | Kind::Varchar(_) | Kind::Char(_) => {
+                let batch = self.try_into_strings().map_err(mismatch)?;
+                check_buffer(
+                    "data",
+                    ffi::StringVectorBatch_get_data(batch.0).size(),
+                    rows,
+                )?;
+                check_buffer(
+                    "length",
+                    ffi::StringVectorBatch_get_length(batch.0).size(),
+                    rows,
+                )
+            }
+            Kind::Timestamp | Kind::TimestampInstant => {
+                let batch = self.try_into_timestamps().map_err(mismatch)?;
+                check_buffer(
+                    "data",
+                    ffi::TimestampVectorBatch_get_data(batch.0).size(),
+                    rows,
+                )?;
+                check_buffer(
+                    "nanoseconds",
+                    ffi::TimestampVectorBatch_get_nanoseconds(batch.0).size(),
+                    rows,
+                )
+            }
+            Kind::Decimal { .. } => match self.try_into_decimals64() {
+                Ok(batch) => check_buffer(
+                    "values",
+                    ffi::Decimal64VectorBatch_get_values(batch.0).size(),
+                    rows,
+                ),
+                Err(_) => {
+                    let batch = self.try_into_decimals128().map_err(mismatch)?;
+                    check_buffer(
+                        "values",
+                        ffi::Decimal128VectorBatch_get_values(batch.0).size(),
+                        rows,
+                    )
+                }
+            },
+            Kind::Struct(fields_kinds) => {
+                let fields = self.try_into_structs().map_err(mismatch)?.fields();
+                if fields.len() != fields_kinds.len() {
+                    return Err(format!(
+                        "Column {} has {} fields, expected {}",
+                        column,
+                        fields.len(),
+                        fields_kinds.len()
+                    ));
+                }
+                for ((name, field_kind), field) in fields_kinds.iter().zip(fields.iter()) {
+                    let field_path = if path.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{}.{}", path, name)
+                    };
+                    // Fields have a row for each row of the structure, even null ones
+                    field.check_consistency(field_kind, rows, &field_path)?;
+                }
+                Ok(())
+            }
+            Kind::List(elements_kind) => {
+                let batch = self.try_into_lists().map_err(mismatch)?;
+                check_buffer(
+                    "offsets",
+                    ffi::ListVectorBatch_get_offsets(batch.0).size(),
+                    rows + 1,
+                )?;
+                let end = check_offsets(batch.raw_offsets(), column)?;
+                batch
+                    .elements()
+                    .check_consistency(elements_kind, end, &format!("{}._elem", path))
+            }
+            Kind::Map { key, value } => {
+                let batch = self.try_into_maps().map_err(mismatch)?;
+                check_buffer(
+                    "offsets",
+                    ffi::MapVectorBatch_get_offsets(batch.0).size(),
+                    rows + 1,
+                )?;
+                let end = check_offsets(batch.raw_offsets(), column)?;
+                batch
+                    .keys()
+                    .check_consistency(key, end, &format!("{}._key", path))?;
+                batch
+                    .elements()
+                    .check_consistency(value, end, &format!("{}._value", path))
+            }
+            Kind::Union(variants) => {
+                let batch = self.try_into_unions().map_err(mismatch)?;
+                check_buffer("tags", ffi::UnionVectorBatch_get_tags(batch.0).size(), rows)?;
+                check_buffer(
+                    "offsets",
+                    ffi::UnionVectorBatch_get_offsets(batch.0).size(),
+                    rows,
+                )?;
+                let children = batch.children();
+                if children.len() != variants.len() {
+                    return Err(format!(
+                        "Column {} has {} variants, expected {}",
+                        column,
+                        children.len(),
+                        variants.len()
+                    ));
+                }
+                // Number of rows needed in each child
+                let mut min_children_rows = vec![0u64; children.len()];
+                for value in batch.iter().flatten() {
+                    let (tag, offset) = value;
+                    let min_child_rows =
+                        min_children_rows.get_mut(usize::from(tag)).ok_or_else(|| {
+                            format!(
+                                "Column {} has tag {}, but only {} variants",
+                                column,
+                                tag,
+                                children.len()
+                            )
+                        })?;
+                    *min_child_rows = std::cmp::max(*min_child_rows, offset.saturating_add(1));
+                }
+                for (i, ((variant, child), min_child_rows)) in variants
+                    .iter()
+                    .zip(children.iter())
+                    .zip(min_children_rows)
+                    .enumerate()
+                {
+                    child.check_consistency(variant, min_child_rows, &format!("{}.{}", path, i))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Checks list or map offsets are not negative and do not decrease, and returns
+/// the last one (the number of items in the child batches).
+fn check_offsets(offsets: &[i64], path: &str) -> Result<u64, String> {
+    let mut previous = 0;
+    for &offset in offsets {
+        if offset < previous {
+            return Err(format!(
+                "Column {} has offset {} after {}",
+                path, offset, previous
+            ));
+        }
+        previous = offset;
+    }
+    Ok(previous as u64)
 }
 
 unsafe impl Send for BorrowedColumnVectorBatch<'_> {}
@@ -379,10 +741,120 @@ impl<'a> StructVectorBatch<'a> {
             })
             .collect()
     }
+
+    /// Same as [`fields`](StructVectorBatch::fields), but pairs each field with its
+    /// name and column, so they can be looked up regardless of which columns are
+    /// selected.
+    ///
+    /// `column` describes this batch, eg. the result of
+    /// [`RowReader::selected_column_tree`](::reader::RowReader::selected_column_tree)
+    /// for the root batch, or [`StructField::column`] for nested structures.
+    ///
+    /// # Panics
+    ///
+    /// If `column` is not a structure with as many fields as this batch.
+    pub fn named_fields(&self, column: &SelectedColumn) -> StructFields<'a> {
+        let names = match &column.kind {
+            Kind::Struct(fields) => fields.iter().map(|(name, _)| name.clone()),
+            kind => panic!("Expected a structure column, got {:?}", kind),
+        };
+        let batches = self.fields();
+        assert_eq!(
+            batches.len(),
+            column.children.len(),
+            "Batch has {} fields, but column has {}",
+            batches.len(),
+            column.children.len()
+        );
+        StructFields(
+            names
+                .zip(column.children.iter().cloned())
+                .zip(batches)
+                .map(|((name, column), batch)| StructField {
+                    name,
+                    column,
+                    batch,
+                })
+                .collect(),
+        )
+    }
 }
 
 unsafe impl Send for StructVectorBatch<'_> {}
 
+/// A field of a [`StructVectorBatch`], with its name and column.
+///
+/// It is constructed through [`StructVectorBatch::named_fields`]
+#[derive(Debug)]
+pub struct StructField<'a> {
+    name: String,
+    column: SelectedColumn,
+    batch: BorrowedColumnVectorBatch<'a>,
+}
+
+impl<'a> StructField<'a> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the id of the field's column in the file
+    pub fn column_id(&self) -> u64 {
+        self.column.id
+    }
+
+    pub fn column(&self) -> &SelectedColumn {
+        &self.column
+    }
+
+    pub fn batch(&self) -> &BorrowedColumnVectorBatch<'a> {
+        &self.batch
+    }
+
+    pub fn into_batch(self) -> BorrowedColumnVectorBatch<'a> {
+        self.batch
+    }
+}
+
+/// Fields of a [`StructVectorBatch`] in schema order, which can also be looked up
+/// by name or column id.
+///
+/// It is constructed through [`StructVectorBatch::named_fields`]
+#[derive(Debug)]
+pub struct StructFields<'a>(Vec<StructField<'a>>);
+
+impl<'a> StructFields<'a> {
+    /// Returns the field with the given name, if any
+    pub fn by_name(&self, name: &str) -> Option<&StructField<'a>> {
+        self.0.iter().find(|field| field.name == name)
+    }
+
+    /// Returns the field whose column has the given id in the file, if any
+    pub fn by_column_id(&self, column_id: u64) -> Option<&StructField<'a>> {
+        self.0.iter().find(|field| field.column.id == column_id)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, StructField<'a>> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for StructFields<'a> {
+    type Item = StructField<'a>;
+    type IntoIter = std::vec::IntoIter<StructField<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 /// A specialized [`ColumnVectorBatch`] whose values are known to be integer-like.
 ///
 /// It is constructed through [`BorrowedColumnVectorBatch::try_into_longs`]
@@ -394,9 +866,9 @@ impl_upcast!(
     ffi::LongVectorBatch_into_ColumnVectorBatch
 );
 
-impl LongVectorBatch<'_> {
+impl<'a> LongVectorBatch<'a> {
     /// Returns an `Option<u64>` iterator
-    pub fn iter(&self) -> LongVectorBatchIterator<'_> {
+    pub fn iter(&self) -> LongVectorBatchIterator<'a> {
         let data = ffi::LongVectorBatch_get_data(self.0);
         let num_elements = self.num_elements();
         let not_null = self.not_null_ptr();
@@ -405,7 +877,10 @@ impl LongVectorBatch<'_> {
     }
 
     /// Returns a `u64` iterator if there are no null values, or `None` if there are
-    pub fn try_iter_not_null(&self) -> Option<NotNullLongVectorBatchIterator<'_>> {
+    ///
+    /// See also [`LongVectorBatch::try_as_slice_not_null`], which avoids the
+    /// per-element overhead of iterators.
+    pub fn try_iter_not_null(&self) -> Option<NotNullLongVectorBatchIterator<'a>> {
         let data = ffi::LongVectorBatch_get_data(self.0);
         let num_elements = self.num_elements();
 
@@ -415,16 +890,279 @@ impl LongVectorBatch<'_> {
             Some(unsafe { NotNullLongVectorBatchIterator::new(data, num_elements) })
         }
     }
+
+    /// Returns the values as a slice if there are no null values, or `None` if there are
+    pub fn try_as_slice_not_null(&self) -> Option<&'a [i64]> {
+        if self.not_null_ptr().is_some() {
+            return None;
+        }
+        let num_elements = self
+            .num_elements()
+            .try_into()
+            .expect("could not convert u64 to usize");
+        if num_elements == 0 {
+            return Some(&[]);
+        }
+        let data = ffi::LongVectorBatch_get_data(self.0).checked_data(num_elements as u64);
+        // This should be safe because the 'data' array should have as many items as
+        // num_elements(), as none of the items are null.
+        Some(unsafe { std::slice::from_raw_parts(data, num_elements) })
+    }
+
+    /// Packs values into a [`Bitmap`] (non-zero values are `true`, and nulls are
+    /// `false`), along with a bitmap of not-null values if there are any nulls.
+    ///
+    /// This is meant for boolean columns, which are decoded to 64-bit integers.
+    pub fn to_bitmap(&self) -> (Bitmap, Option<Bitmap>) {
+        match self.try_as_slice_not_null() {
+            Some(values) => {
+                let words = values
+                    .chunks(64)
+                    .map(|chunk| {
+                        chunk
+                            .iter()
+                            .enumerate()
+                            .fold(0, |word, (i, &value)| word | (u64::from(value != 0) << i))
+                    })
+                    .collect();
+                (
+                    Bitmap {
+                        words,
+                        len: values.len(),
+                    },
+                    None,
+                )
+            }
+            None => (
+                self.iter().map(|value| value.unwrap_or(0) != 0).collect(),
+                self.validity(),
+            ),
+        }
+    }
+
+    /// Returns all values of a `date` column (days since 1970-01-01) as
+    /// [`chrono::NaiveDate`], or `None` for null values
+    ///
+    /// # Panics
+    ///
+    /// If a date is out of the range supported by `chrono`.
+    #[cfg(feature = "chrono")]
+    pub fn to_naive_dates(&self) -> Vec<Option<chrono::NaiveDate>> {
+        let mut cache = DateCache::new(days_to_naive_date);
+        self.iter()
+            .map(|days| days.map(|days| cache.get(days)))
+            .collect()
+    }
+
+    /// Same as [`to_naive_dates`](LongVectorBatch::to_naive_dates), but formats
+    /// dates as `%Y-%m-%d`
+    ///
+    /// # Panics
+    ///
+    /// If a date is out of the range supported by `chrono`.
+    #[cfg(feature = "chrono")]
+    pub fn to_date_strings(&self) -> Vec<Option<String>> {
+        let mut cache =
+            DateCache::new(|days| days_to_naive_date(days).format("%Y-%m-%d").to_string());
+        self.iter()
+            .map(|days| days.map(|days| cache.get(days)))
+            .collect()
+    }
 }
 
 unsafe impl Send for LongVectorBatch<'_> {}
 
+/// Number of days between 0001-01-01 and 1970-01-01
+#[cfg(feature = "chrono")]
+const UNIX_EPOCH_DAYS_FROM_CE: i64 = 719_163;
+
+#[cfg(feature = "chrono")]
+fn days_to_naive_date(days: i64) -> chrono::NaiveDate {
+    days_to_naive_date_opt(days).expect("Date out of chrono::NaiveDate's range")
+}
+
+/// Returns `None` if the date is out of the range supported by `chrono`
+#[cfg(feature = "chrono")]
+pub(crate) fn days_to_naive_date_opt(days: i64) -> Option<chrono::NaiveDate> {
+    days.checked_add(UNIX_EPOCH_DAYS_FROM_CE)
+        .and_then(|days| days.try_into().ok())
+        .and_then(chrono::NaiveDate::from_num_days_from_ce_opt)
+}
+
+/// Direct-mapped cache of values computed from dates, as date columns tend to
+/// repeat the same few values
+#[cfg(feature = "chrono")]
+struct DateCache<T, F> {
+    entries: Vec<Option<(i64, T)>>,
+    f: F,
+}
+
+#[cfg(feature = "chrono")]
+impl<T: Clone, F: Fn(i64) -> T> DateCache<T, F> {
+    const SIZE: usize = 64;
+
+    fn new(f: F) -> Self {
+        DateCache {
+            entries: vec![None; Self::SIZE],
+            f,
+        }
+    }
+
+    fn get(&mut self, days: i64) -> T {
+        let entry = &mut self.entries[days.rem_euclid(Self::SIZE as i64) as usize];
+        match entry {
+            Some((cached_days, value)) if *cached_days == days => value.clone(),
+            _ => {
+                let value = (self.f)(days);
+                *entry = Some((days, value.clone()));
+                value
+            }
+        }
+    }
+}
+
+/// Packed booleans, 64 per word with the least significant bit first.
+///
+/// It is returned by [`LongVectorBatch::to_bitmap`], and is 64 times smaller than
+/// the integers boolean columns are decoded to. It is also returned by
+/// [`ColumnVectorBatch::validity`].
+///
+/// This is the same layout as Arrow's, so with the `arrow` feature, bitmaps can be
+/// converted to Arrow's `BooleanBuffer` and `NullBuffer`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Bitmap {
+    /// Bits past `len` are always unset
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitmap {
+    pub fn new() -> Bitmap {
+        Bitmap::default()
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        if self.len % 64 == 0 {
+            self.words.push(0);
+        }
+        if bit {
+            *self.words.last_mut().unwrap() |= 1 << (self.len % 64);
+        }
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bit at `index`, or `None` if it is out of bounds
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index < self.len {
+            Some(self.words[index / 64] & (1 << (index % 64)) != 0)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of `true` bits
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(move |i| self.words[i / 64] & (1 << (i % 64)) != 0)
+    }
+
+    /// Returns the bits in `range` as a new bitmap
+    ///
+    /// # Panics
+    ///
+    /// If `range` is out of bounds.
+    pub fn slice(&self, range: Range<usize>) -> Bitmap {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "Range {:?} out of bounds of a bitmap of length {}",
+            range,
+            self.len
+        );
+        range
+            .map(|i| self.words[i / 64] & (1 << (i % 64)) != 0)
+            .collect()
+    }
+
+    /// Returns the packed bits, 64 per word with the least significant bit first
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<Bitmap> for arrow::buffer::BooleanBuffer {
+    fn from(bitmap: Bitmap) -> arrow::buffer::BooleanBuffer {
+        let bytes: Vec<u8> = bitmap
+            .words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        arrow::buffer::BooleanBuffer::new(arrow::buffer::Buffer::from_vec(bytes), 0, bitmap.len)
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<Bitmap> for arrow::buffer::NullBuffer {
+    fn from(bitmap: Bitmap) -> arrow::buffer::NullBuffer {
+        arrow::buffer::NullBuffer::new(bitmap.into())
+    }
+}
+
+impl std::iter::FromIterator<bool> for Bitmap {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Bitmap {
+        let iter = iter.into_iter();
+        let mut bitmap = Bitmap {
+            words: Vec::with_capacity((iter.size_hint().0 + 63) / 64),
+            len: 0,
+        };
+        for bit in iter {
+            bitmap.push(bit);
+        }
+        bitmap
+    }
+}
+
+/// Returns the number of non-null values in `not_null[start..end]`, or `end - start`
+/// if there is no `not_null` array
+///
+/// # Safety
+///
+/// `start` must not be negative nor greater than `end`, and `end` must not be
+/// greater than the length of `not_null`.
+unsafe fn count_not_null(not_null: Option<ptr::NonNull<i8>>, start: isize, end: isize) -> isize {
+    match not_null {
+        None => end - start,
+        Some(not_null) => {
+            let not_null =
+                std::slice::from_raw_parts(not_null.as_ptr().offset(start), (end - start) as usize);
+            not_null.iter().filter(|&&b| b != 0).count() as isize
+        }
+    }
+}
+
 /// Iterator on [`LongVectorBatch`] that may yield `None`.
 #[derive(Debug, Clone)]
 pub struct LongVectorBatchIterator<'a> {
     batch: PhantomData<&'a LongVectorBatch<'a>>,
     data_index: isize,
     not_null_index: isize,
+    /// Index in `data` after the last value not returned yet, computed by the first
+    /// call to `next_back`
+    data_end: Option<isize>,
     data: *const i64,
     not_null: Option<ptr::NonNull<i8>>,
     num_elements: isize,
@@ -436,14 +1174,12 @@ impl<'a> LongVectorBatchIterator<'a> {
         not_null: Option<ptr::NonNull<i8>>,
         num_elements: u64,
     ) -> LongVectorBatchIterator<'a> {
-        // TODO: do this once https://github.com/apache/orc/commit/294a5e28f7f0420eb1fdc76dffc33608692c1b20
-        // is released:
-        // assert_eq!(std::mem::size_of(u64)*num_elements, data_buffer.size())
         LongVectorBatchIterator {
             batch: PhantomData,
             data_index: 0,
             not_null_index: 0,
-            data: data_buffer.data(),
+            data_end: None,
+            data: data_buffer.checked_data(num_elements),
             not_null,
             num_elements: num_elements
                 .try_into()
@@ -481,8 +1217,56 @@ impl Iterator for LongVectorBatchIterator<'_> {
 
         Some(Some(datum))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.not_null_index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for LongVectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<Option<i64>> {
+        if self.not_null_index >= self.num_elements {
+            return None;
+        }
+
+        let data_end = match self.data_end {
+            Some(data_end) => data_end,
+            // This should be safe because 'num_elements' is not greater than the
+            // length of 'not_null'
+            None => {
+                self.data_index
+                    + unsafe {
+                        count_not_null(self.not_null, self.not_null_index, self.num_elements)
+                    }
+            }
+        };
+
+        self.num_elements -= 1;
+
+        if let Some(not_null) = self.not_null {
+            let not_null = not_null.as_ptr();
+            // This should be safe because we just checked num_elements was greater
+            // than not_null_index
+            if unsafe { *not_null.offset(self.num_elements) } == 0 {
+                self.data_end = Some(data_end);
+                return Some(None);
+            }
+        }
+
+        let data_end = data_end - 1;
+        self.data_end = Some(data_end);
+
+        // This should be safe because 'data_end' is now the index in 'data' of the
+        // last non-null value not returned yet, and we checked this one is not null.
+        let datum = unsafe { *self.data.offset(data_end) };
+
+        Some(Some(datum))
+    }
 }
 
+impl ExactSizeIterator for LongVectorBatchIterator<'_> {}
+
 /// Iterator on [`LongVectorBatch`] that may not yield `None`.
 #[derive(Debug, Clone)]
 pub struct NotNullLongVectorBatchIterator<'a> {
@@ -497,13 +1281,10 @@ impl<'a> NotNullLongVectorBatchIterator<'a> {
         data_buffer: &memorypool::ffi::Int64DataBuffer,
         num_elements: u64,
     ) -> NotNullLongVectorBatchIterator<'a> {
-        // TODO: do this once https://github.com/apache/orc/commit/294a5e28f7f0420eb1fdc76dffc33608692c1b20
-        // is released:
-        // assert_eq!(std::mem::size_of(u64)*num_elements, data_buffer.size())
         NotNullLongVectorBatchIterator {
             batch: PhantomData,
             index: 0,
-            data: data_buffer.data(),
+            data: data_buffer.checked_data(num_elements),
             num_elements: num_elements
                 .try_into()
                 .expect("could not convert u64 to isize"),
@@ -528,8 +1309,31 @@ impl Iterator for NotNullLongVectorBatchIterator<'_> {
 
         Some(datum)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for NotNullLongVectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<i64> {
+        if self.index >= self.num_elements {
+            return None;
+        }
+
+        self.num_elements -= 1;
+
+        // This should be safe because 'num_elements' was greater than 'index', which
+        // is not negative.
+        let datum = unsafe { *self.data.offset(self.num_elements) };
+
+        Some(datum)
+    }
 }
 
+impl ExactSizeIterator for NotNullLongVectorBatchIterator<'_> {}
+
 /// A specialized [`ColumnVectorBatch`] whose values are known to be floating-point-like
 ///
 /// It is constructed through [`BorrowedColumnVectorBatch::try_into_doubles`]
@@ -541,19 +1345,20 @@ impl_upcast!(
     ffi::DoubleVectorBatch_into_ColumnVectorBatch
 );
 
-impl DoubleVectorBatch<'_> {
+impl<'a> DoubleVectorBatch<'a> {
     /// Returns an `Option<f64>` iterator
-    pub fn iter(&self) -> DoubleVectorBatchIterator<'_> {
-        let data = ffi::DoubleVectorBatch_get_data(self.0).data();
+    pub fn iter(&self) -> DoubleVectorBatchIterator<'a> {
         let vector_batch =
             BorrowedColumnVectorBatch(ffi::DoubleVectorBatch_into_ColumnVectorBatch(self.0));
         let num_elements = vector_batch.num_elements();
+        let data = ffi::DoubleVectorBatch_get_data(self.0).checked_data(num_elements);
         let not_null = vector_batch.not_null_ptr();
 
         DoubleVectorBatchIterator {
             batch: PhantomData,
             data_index: 0,
             not_null_index: 0,
+            data_end: None,
             data,
             not_null,
             num_elements: num_elements
@@ -563,11 +1368,14 @@ impl DoubleVectorBatch<'_> {
     }
 
     /// Returns a `f64` iterator if there are no null values, or `None` if there are
-    pub fn try_iter_not_null(&self) -> Option<NotNullDoubleVectorBatchIterator<'_>> {
-        let data = ffi::DoubleVectorBatch_get_data(self.0).data();
+    ///
+    /// See also [`DoubleVectorBatch::try_as_slice_not_null`], which avoids the
+    /// per-element overhead of iterators.
+    pub fn try_iter_not_null(&self) -> Option<NotNullDoubleVectorBatchIterator<'a>> {
         let vector_batch =
             BorrowedColumnVectorBatch(ffi::DoubleVectorBatch_into_ColumnVectorBatch(self.0));
         let num_elements = vector_batch.num_elements();
+        let data = ffi::DoubleVectorBatch_get_data(self.0).checked_data(num_elements);
 
         if vector_batch.not_null_ptr().is_some() {
             None
@@ -582,6 +1390,26 @@ impl DoubleVectorBatch<'_> {
             })
         }
     }
+
+    /// Returns the values as a slice if there are no null values, or `None` if there are
+    pub fn try_as_slice_not_null(&self) -> Option<&'a [f64]> {
+        let vector_batch =
+            BorrowedColumnVectorBatch(ffi::DoubleVectorBatch_into_ColumnVectorBatch(self.0));
+        if vector_batch.not_null_ptr().is_some() {
+            return None;
+        }
+        let num_elements = vector_batch
+            .num_elements()
+            .try_into()
+            .expect("could not convert u64 to usize");
+        if num_elements == 0 {
+            return Some(&[]);
+        }
+        let data = ffi::DoubleVectorBatch_get_data(self.0).checked_data(num_elements as u64);
+        // This should be safe because the 'data' array should have as many items as
+        // num_elements(), as none of the items are null.
+        Some(unsafe { std::slice::from_raw_parts(data, num_elements) })
+    }
 }
 
 unsafe impl Send for DoubleVectorBatch<'_> {}
@@ -592,6 +1420,9 @@ pub struct DoubleVectorBatchIterator<'a> {
     batch: PhantomData<&'a DoubleVectorBatch<'a>>,
     data_index: isize,
     not_null_index: isize,
+    /// Index in `data` after the last value not returned yet, computed by the first
+    /// call to `next_back`
+    data_end: Option<isize>,
     data: *const f64,
     not_null: Option<ptr::NonNull<i8>>,
     num_elements: isize,
@@ -626,14 +1457,62 @@ impl Iterator for DoubleVectorBatchIterator<'_> {
 
         Some(Some(datum))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.not_null_index) as usize;
+        (len, Some(len))
+    }
 }
 
-/// Iterator on [`DoubleVectorBatch`] that may not yield `None`
-#[derive(Debug, Clone)]
-pub struct NotNullDoubleVectorBatchIterator<'a> {
-    batch: PhantomData<&'a DoubleVectorBatch<'a>>,
-    index: isize,
-    data: *const f64,
+impl DoubleEndedIterator for DoubleVectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<Option<f64>> {
+        if self.not_null_index >= self.num_elements {
+            return None;
+        }
+
+        let data_end = match self.data_end {
+            Some(data_end) => data_end,
+            // This should be safe because 'num_elements' is not greater than the
+            // length of 'not_null'
+            None => {
+                self.data_index
+                    + unsafe {
+                        count_not_null(self.not_null, self.not_null_index, self.num_elements)
+                    }
+            }
+        };
+
+        self.num_elements -= 1;
+
+        if let Some(not_null) = self.not_null {
+            let not_null = not_null.as_ptr();
+            // This should be safe because we just checked num_elements was greater
+            // than not_null_index
+            if unsafe { *not_null.offset(self.num_elements) } == 0 {
+                self.data_end = Some(data_end);
+                return Some(None);
+            }
+        }
+
+        let data_end = data_end - 1;
+        self.data_end = Some(data_end);
+
+        // This should be safe because 'data_end' is now the index in 'data' of the
+        // last non-null value not returned yet, and we checked this one is not null.
+        let datum = unsafe { *self.data.offset(data_end) };
+
+        Some(Some(datum))
+    }
+}
+
+impl ExactSizeIterator for DoubleVectorBatchIterator<'_> {}
+
+/// Iterator on [`DoubleVectorBatch`] that may not yield `None`
+#[derive(Debug, Clone)]
+pub struct NotNullDoubleVectorBatchIterator<'a> {
+    batch: PhantomData<&'a DoubleVectorBatch<'a>>,
+    index: isize,
+    data: *const f64,
     num_elements: isize,
 }
 
@@ -654,8 +1533,31 @@ impl Iterator for NotNullDoubleVectorBatchIterator<'_> {
 
         Some(datum)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for NotNullDoubleVectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<f64> {
+        if self.index >= self.num_elements {
+            return None;
+        }
+
+        self.num_elements -= 1;
+
+        // This should be safe because 'num_elements' was greater than 'index', which
+        // is not negative.
+        let datum = unsafe { *self.data.offset(self.num_elements) };
+
+        Some(datum)
+    }
 }
 
+impl ExactSizeIterator for NotNullDoubleVectorBatchIterator<'_> {}
+
 /// A specialized [`ColumnVectorBatch`] whose values are known to be string-like.
 ///
 /// It is constructed through [`BorrowedColumnVectorBatch::try_into_strings`]
@@ -667,14 +1569,14 @@ impl_upcast!(
     ffi::StringVectorBatch_into_ColumnVectorBatch
 );
 
-impl StringVectorBatch<'_> {
+impl<'a> StringVectorBatch<'a> {
     /// Returns an `Option<&[u8]>` iterator
-    pub fn iter(&self) -> StringVectorBatchIterator<'_> {
-        let data = ffi::StringVectorBatch_get_data(self.0).data();
-        let lengths = ffi::StringVectorBatch_get_length(self.0).data();
+    pub fn iter(&self) -> StringVectorBatchIterator<'a> {
         let vector_batch =
             BorrowedColumnVectorBatch(ffi::StringVectorBatch_into_ColumnVectorBatch(self.0));
         let num_elements = vector_batch.num_elements();
+        let data = ffi::StringVectorBatch_get_data(self.0).checked_data(num_elements);
+        let lengths = ffi::StringVectorBatch_get_length(self.0).checked_data(num_elements);
         let not_null = vector_batch.not_null_ptr();
 
         StringVectorBatchIterator {
@@ -690,12 +1592,12 @@ impl StringVectorBatch<'_> {
     }
 
     /// Returns a `&[u8]` iterator if there are no null values, or `None` if there are
-    pub fn try_iter_not_null(&self) -> Option<NotNullStringVectorBatchIterator<'_>> {
-        let data = ffi::StringVectorBatch_get_data(self.0).data();
-        let lengths = ffi::StringVectorBatch_get_length(self.0).data();
+    pub fn try_iter_not_null(&self) -> Option<NotNullStringVectorBatchIterator<'a>> {
         let vector_batch =
             BorrowedColumnVectorBatch(ffi::StringVectorBatch_into_ColumnVectorBatch(self.0));
         let num_elements = vector_batch.num_elements();
+        let data = ffi::StringVectorBatch_get_data(self.0).checked_data(num_elements);
+        let lengths = ffi::StringVectorBatch_get_length(self.0).checked_data(num_elements);
 
         if vector_batch.not_null_ptr().is_some() {
             None
@@ -717,7 +1619,7 @@ impl StringVectorBatch<'_> {
     ///
     /// Use [`StringVectorBatch::ranges`] to get the range of each string within
     /// this array.
-    pub fn bytes(&self) -> &[u8] {
+    pub fn bytes(&self) -> &'a [u8] {
         let data_buffer = ffi::StringVectorBatch_get_blob(self.0);
 
         // This should be safe because we trust the data_buffer to be self-consistent
@@ -745,7 +1647,7 @@ impl StringVectorBatch<'_> {
         let vector_batch =
             BorrowedColumnVectorBatch(ffi::StringVectorBatch_into_ColumnVectorBatch(self.0));
         let num_elements = vector_batch.num_elements();
-        let lengths = ffi::StringVectorBatch_get_length(self.0).data();
+        let lengths = ffi::StringVectorBatch_get_length(self.0).checked_data(num_elements);
         match vector_batch.not_null_ptr() {
             None => {
                 let mut current_index = 0usize;
@@ -786,6 +1688,61 @@ impl StringVectorBatch<'_> {
 
         ranges
     }
+
+    /// Same as [`StringVectorBatch::ranges`], but returns `None` unless the strings
+    /// are actually stored in order in the array returned by
+    /// [`StringVectorBatch::bytes`], which is not the case for dictionary-encoded
+    /// columns.
+    pub fn contiguous_ranges(&self) -> Option<Vec<Option<Range<usize>>>> {
+        let ranges = self.ranges();
+        let bytes = self.bytes();
+        let total_length = ranges
+            .iter()
+            .rev()
+            .flatten()
+            .next()
+            .map(|range| range.end)
+            .unwrap_or(0);
+        if total_length != bytes.len() {
+            return None;
+        }
+        // The C++ library fills the blob sequentially, so the strings are contiguous
+        // if the first non-empty one is at the start of the blob.
+        let (data, _) = self.raw_strings();
+        let first_non_empty = ranges.iter().position(|range| {
+            range
+                .as_ref()
+                .map(|range| !range.is_empty())
+                .unwrap_or(false)
+        });
+        match first_non_empty {
+            None => Some(ranges),
+            Some(i) if data[i] as *const u8 == bytes.as_ptr() => Some(ranges),
+            Some(_) => None,
+        }
+    }
+
+    /// Returns the pointer to and length of each string, without checking them.
+    /// Values of nulls are meaningless.
+    pub(crate) fn raw_strings(&self) -> (&[*mut c_char], &[i64]) {
+        let num_elements = self
+            .num_elements()
+            .try_into()
+            .expect("could not convert u64 to usize");
+        // This should be safe because both arrays have as many items as num_elements()
+        unsafe {
+            (
+                std::slice::from_raw_parts(
+                    ffi::StringVectorBatch_get_data(self.0).checked_data(num_elements as u64),
+                    num_elements,
+                ),
+                std::slice::from_raw_parts(
+                    ffi::StringVectorBatch_get_length(self.0).checked_data(num_elements as u64),
+                    num_elements,
+                ),
+            )
+        }
+    }
 }
 
 unsafe impl Send for StringVectorBatch<'_> {}
@@ -834,8 +1791,46 @@ impl<'a> Iterator for StringVectorBatchIterator<'a> {
         let datum = datum as *const u8;
         Some(Some(unsafe { std::slice::from_raw_parts(datum, length) }))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl<'a> DoubleEndedIterator for StringVectorBatchIterator<'a> {
+    fn next_back(&mut self) -> Option<Option<&'a [u8]>> {
+        if self.index >= self.num_elements {
+            return None;
+        }
+
+        self.num_elements -= 1;
+
+        if let Some(not_null) = self.not_null {
+            let not_null = not_null.as_ptr();
+            // This should be safe because we just checked num_elements was greater
+            // than index
+            if unsafe { *not_null.offset(self.num_elements) } == 0 {
+                return Some(None);
+            }
+        }
+
+        // These two should be safe because 'num_elements' was greater than 'index',
+        // which is not negative.
+        let datum = unsafe { *self.data.offset(self.num_elements) };
+        let length = unsafe { *self.lengths.offset(self.num_elements) };
+
+        let length = length.try_into().expect("could not convert u64 to usize");
+
+        // Should be safe because the length indicates the number of bytes in
+        // the string.
+        let datum = datum as *const u8;
+        Some(Some(unsafe { std::slice::from_raw_parts(datum, length) }))
+    }
 }
 
+impl<'a> ExactSizeIterator for StringVectorBatchIterator<'a> {}
+
 /// Iterator on [`StringVectorBatch`] that may not yield `None`.
 #[derive(Debug, Clone)]
 pub struct NotNullStringVectorBatchIterator<'a> {
@@ -869,8 +1864,37 @@ impl<'a> Iterator for NotNullStringVectorBatchIterator<'a> {
         let datum = datum as *const u8;
         Some(unsafe { std::slice::from_raw_parts(datum, length) })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl<'a> DoubleEndedIterator for NotNullStringVectorBatchIterator<'a> {
+    fn next_back(&mut self) -> Option<&'a [u8]> {
+        if self.index >= self.num_elements {
+            return None;
+        }
+
+        self.num_elements -= 1;
+
+        // This should be safe because 'num_elements' was greater than 'index', which
+        // is not negative.
+        let datum = unsafe { *self.data.offset(self.num_elements) };
+        let length = unsafe { *self.lengths.offset(self.num_elements) };
+
+        let length = length.try_into().expect("could not convert u64 to usize");
+
+        // Should be safe because the length indicates the number of bytes in
+        // the string.
+        let datum = datum as *const u8;
+        Some(unsafe { std::slice::from_raw_parts(datum, length) })
+    }
 }
 
+impl<'a> ExactSizeIterator for NotNullStringVectorBatchIterator<'a> {}
+
 /// A specialized [`ColumnVectorBatch`] whose values are known to be timestamps,
 /// represented by seconds and nanoseconds since 1970-01-01 GMT.
 ///
@@ -886,11 +1910,12 @@ impl_upcast!(
 impl TimestampVectorBatch<'_> {
     /// Returns an `Option<(i64, i64)>` iterator
     pub fn iter(&self) -> TimestampVectorBatchIterator<'_> {
-        let data = ffi::TimestampVectorBatch_get_data(self.0).data();
-        let nanoseconds = ffi::TimestampVectorBatch_get_nanoseconds(self.0).data();
         let vector_batch =
             BorrowedColumnVectorBatch(ffi::TimestampVectorBatch_into_ColumnVectorBatch(self.0));
         let num_elements = vector_batch.num_elements();
+        let data = ffi::TimestampVectorBatch_get_data(self.0).checked_data(num_elements);
+        let nanoseconds =
+            ffi::TimestampVectorBatch_get_nanoseconds(self.0).checked_data(num_elements);
         let not_null = vector_batch.not_null_ptr();
 
         TimestampVectorBatchIterator {
@@ -907,11 +1932,12 @@ impl TimestampVectorBatch<'_> {
 
     /// Returns an `(i64, i64)` iterator if there are no null values, or `None` if there are
     pub fn try_iter_not_null(&self) -> Option<NotNullTimestampVectorBatchIterator<'_>> {
-        let data = ffi::TimestampVectorBatch_get_data(self.0).data();
-        let nanoseconds = ffi::TimestampVectorBatch_get_nanoseconds(self.0).data();
         let vector_batch =
             BorrowedColumnVectorBatch(ffi::TimestampVectorBatch_into_ColumnVectorBatch(self.0));
         let num_elements = vector_batch.num_elements();
+        let data = ffi::TimestampVectorBatch_get_data(self.0).checked_data(num_elements);
+        let nanoseconds =
+            ffi::TimestampVectorBatch_get_nanoseconds(self.0).checked_data(num_elements);
 
         if vector_batch.not_null_ptr().is_some() {
             None
@@ -927,6 +1953,72 @@ impl TimestampVectorBatch<'_> {
             })
         }
     }
+
+    /// Returns all timestamps as [`chrono::DateTime`], or `None` for null values
+    ///
+    /// # Panics
+    ///
+    /// If a timestamp is out of the range supported by `chrono`.
+    #[cfg(feature = "chrono")]
+    pub fn to_datetimes(&self) -> Vec<Option<chrono::DateTime<chrono::Utc>>> {
+        self.iter()
+            .map(|timestamp| {
+                timestamp.map(|(seconds, nanoseconds)| {
+                    chrono::DateTime::from_timestamp(
+                        seconds,
+                        nanoseconds
+                            .try_into()
+                            .expect("More than 2**32 nanoseconds in a second"),
+                    )
+                    .expect("Timestamp out of chrono::DateTime's range")
+                })
+            })
+            .collect()
+    }
+
+    /// Same as [`to_datetimes`](TimestampVectorBatch::to_datetimes), but formats
+    /// timestamps as `%Y-%m-%d %H:%M:%S.%f`, without trailing zeros in the fractional
+    /// part (eg. `2000-03-12 15:00:00.0` or `2000-03-12 15:00:01.123`)
+    ///
+    /// # Panics
+    ///
+    /// If a timestamp is out of the range supported by `chrono`.
+    #[cfg(feature = "chrono")]
+    pub fn to_timestamp_strings(&self) -> Vec<Option<String>> {
+        self.to_datetimes()
+            .into_iter()
+            .map(|datetime| {
+                datetime.map(|datetime| {
+                    let mut s = datetime.format("%Y-%m-%d %H:%M:%S.%f").to_string();
+                    s.truncate(s.trim_end_matches('0').len());
+                    if s.ends_with('.') {
+                        s.push('0');
+                    }
+                    s
+                })
+            })
+            .collect()
+    }
+
+    /// Returns all timestamps as [`time::OffsetDateTime`] in UTC, or `None` for
+    /// null values
+    ///
+    /// # Panics
+    ///
+    /// If a timestamp is out of the range supported by `time`.
+    #[cfg(feature = "time")]
+    pub fn to_offset_datetimes(&self) -> Vec<Option<time::OffsetDateTime>> {
+        self.iter()
+            .map(|timestamp| {
+                timestamp.map(|(seconds, nanoseconds)| {
+                    time::OffsetDateTime::from_unix_timestamp_nanos(
+                        i128::from(seconds) * 1_000_000_000 + i128::from(nanoseconds),
+                    )
+                    .expect("Timestamp out of time::OffsetDateTime's range")
+                })
+            })
+            .collect()
+    }
 }
 
 unsafe impl Send for TimestampVectorBatch<'_> {}
@@ -970,8 +2062,41 @@ impl Iterator for TimestampVectorBatchIterator<'_> {
 
         Some(Some((datum, nanoseconds)))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for TimestampVectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<Option<(i64, i64)>> {
+        if self.index >= self.num_elements {
+            return None;
+        }
+
+        self.num_elements -= 1;
+
+        if let Some(not_null) = self.not_null {
+            let not_null = not_null.as_ptr();
+            // This should be safe because we just checked num_elements was greater
+            // than index
+            if unsafe { *not_null.offset(self.num_elements) } == 0 {
+                return Some(None);
+            }
+        }
+
+        // These two should be safe because 'num_elements' was greater than 'index',
+        // which is not negative.
+        let datum = unsafe { *self.data.offset(self.num_elements) };
+        let nanoseconds = unsafe { *self.nanoseconds.offset(self.num_elements) };
+
+        Some(Some((datum, nanoseconds)))
+    }
 }
 
+impl ExactSizeIterator for TimestampVectorBatchIterator<'_> {}
+
 /// Iterator on [`TimestampVectorBatch`] that may not yield `None`.
 #[derive(Debug, Clone)]
 pub struct NotNullTimestampVectorBatchIterator<'a> {
@@ -1000,12 +2125,36 @@ impl Iterator for NotNullTimestampVectorBatchIterator<'_> {
 
         Some((datum, nanoseconds))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for NotNullTimestampVectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<(i64, i64)> {
+        if self.index >= self.num_elements {
+            return None;
+        }
+
+        self.num_elements -= 1;
+
+        // This should be safe because 'num_elements' was greater than 'index', which
+        // is not negative.
+        let datum = unsafe { *self.data.offset(self.num_elements) };
+        let nanoseconds = unsafe { *self.nanoseconds.offset(self.num_elements) };
+
+        Some((datum, nanoseconds))
+    }
 }
 
+impl ExactSizeIterator for NotNullTimestampVectorBatchIterator<'_> {}
+
 /// Common methods of [`Decimal64VectorBatch`] and [`Decimal128VectorBatch`]
 pub trait DecimalVectorBatch<'a> {
-    type IteratorType: Iterator<Item = Option<Decimal>>;
-    type NotNullIteratorType: Iterator<Item = Decimal>;
+    type IteratorType: DoubleEndedIterator<Item = Option<Decimal>> + ExactSizeIterator;
+    type NotNullIteratorType: DoubleEndedIterator<Item = Decimal> + ExactSizeIterator;
 
     /// total number of digits
     fn precision(&self) -> i32;
@@ -1039,16 +2188,17 @@ impl<'a> DecimalVectorBatch<'a> for Decimal64VectorBatch<'a> {
     }
 
     fn iter(&self) -> Decimal64VectorBatchIterator<'a> {
-        let data = ffi::Decimal64VectorBatch_get_values(self.0).data();
         let vector_batch =
             BorrowedColumnVectorBatch(ffi::Decimal64VectorBatch_into_ColumnVectorBatch(self.0));
         let num_elements = vector_batch.num_elements();
+        let data = ffi::Decimal64VectorBatch_get_values(self.0).checked_data(num_elements);
         let not_null = vector_batch.not_null_ptr();
 
         Decimal64VectorBatchIterator {
             batch: PhantomData,
             data_index: 0,
             not_null_index: 0,
+            data_end: None,
             data,
             not_null,
             num_elements: num_elements
@@ -1062,10 +2212,10 @@ impl<'a> DecimalVectorBatch<'a> for Decimal64VectorBatch<'a> {
     }
 
     fn try_iter_not_null(&self) -> Option<NotNullDecimal64VectorBatchIterator<'a>> {
-        let data = ffi::Decimal64VectorBatch_get_values(self.0).data();
         let vector_batch =
             BorrowedColumnVectorBatch(ffi::Decimal64VectorBatch_into_ColumnVectorBatch(self.0));
         let num_elements = vector_batch.num_elements();
+        let data = ffi::Decimal64VectorBatch_get_values(self.0).checked_data(num_elements);
 
         if vector_batch.not_null_ptr().is_some() {
             None
@@ -1094,6 +2244,9 @@ pub struct Decimal64VectorBatchIterator<'a> {
     batch: PhantomData<&'a Decimal64VectorBatch<'a>>,
     data_index: isize,
     not_null_index: isize,
+    /// Index in `data` after the last value not returned yet, computed by the first
+    /// call to `next_back`
+    data_end: Option<isize>,
     data: *const i64,
     not_null: Option<ptr::NonNull<i8>>,
     num_elements: isize,
@@ -1129,8 +2282,56 @@ impl Iterator for Decimal64VectorBatchIterator<'_> {
 
         Some(Some(Decimal::new(datum, self.scale)))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.not_null_index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Decimal64VectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<Option<Decimal>> {
+        if self.not_null_index >= self.num_elements {
+            return None;
+        }
+
+        let data_end = match self.data_end {
+            Some(data_end) => data_end,
+            // This should be safe because 'num_elements' is not greater than the
+            // length of 'not_null'
+            None => {
+                self.data_index
+                    + unsafe {
+                        count_not_null(self.not_null, self.not_null_index, self.num_elements)
+                    }
+            }
+        };
+
+        self.num_elements -= 1;
+
+        if let Some(not_null) = self.not_null {
+            let not_null = not_null.as_ptr();
+            // This should be safe because we just checked num_elements was greater
+            // than not_null_index
+            if unsafe { *not_null.offset(self.num_elements) } == 0 {
+                self.data_end = Some(data_end);
+                return Some(None);
+            }
+        }
+
+        let data_end = data_end - 1;
+        self.data_end = Some(data_end);
+
+        // This should be safe because 'data_end' is now the index in 'data' of the
+        // last non-null value not returned yet, and we checked this one is not null.
+        let datum = unsafe { *self.data.offset(data_end) };
+
+        Some(Some(Decimal::new(datum, self.scale)))
+    }
 }
 
+impl ExactSizeIterator for Decimal64VectorBatchIterator<'_> {}
+
 /// Iterator on [`Decimal64VectorBatch`] that may not yield `None`.
 #[derive(Debug, Clone)]
 pub struct NotNullDecimal64VectorBatchIterator<'a> {
@@ -1158,8 +2359,31 @@ impl Iterator for NotNullDecimal64VectorBatchIterator<'_> {
 
         Some(Decimal::new(datum, self.scale))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for NotNullDecimal64VectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<Decimal> {
+        if self.index >= self.num_elements {
+            return None;
+        }
+
+        self.num_elements -= 1;
+
+        // This should be safe because 'num_elements' was greater than 'index', which
+        // is not negative.
+        let datum = unsafe { *self.data.offset(self.num_elements) };
+
+        Some(Decimal::new(datum, self.scale))
+    }
 }
 
+impl ExactSizeIterator for NotNullDecimal64VectorBatchIterator<'_> {}
+
 /// A specialized [`ColumnVectorBatch`] whose values are known to be 64-bits decimal numbers
 ///
 /// It is constructed through [`BorrowedColumnVectorBatch::try_into_decimals128`]
@@ -1187,33 +2411,14 @@ impl<'a> DecimalVectorBatch<'a> for Decimal128VectorBatch<'a> {
     }
 
     fn iter(&self) -> Decimal128VectorBatchIterator<'a> {
-        let data = ffi::Decimal128VectorBatch_get_values(self.0).data();
-        let vector_batch =
-            BorrowedColumnVectorBatch(ffi::Decimal128VectorBatch_into_ColumnVectorBatch(self.0));
-        let num_elements = vector_batch.num_elements();
-        let not_null = vector_batch.not_null_ptr();
-
-        Decimal128VectorBatchIterator {
-            batch: PhantomData,
-            data_index: 0,
-            not_null_index: 0,
-            data,
-            not_null,
-            num_elements: num_elements
-                .try_into()
-                .expect("could not convert u64 to isize"),
-            scale: self
-                .scale()
-                .try_into()
-                .expect("Could not convert scale from i32 to u43"),
-        }
+        Decimal128VectorBatchIterator(self.iter_raw())
     }
 
     fn try_iter_not_null(&self) -> Option<NotNullDecimal128VectorBatchIterator<'a>> {
-        let data = ffi::Decimal128VectorBatch_get_values(self.0).data();
         let vector_batch =
             BorrowedColumnVectorBatch(ffi::Decimal128VectorBatch_into_ColumnVectorBatch(self.0));
         let num_elements = vector_batch.num_elements();
+        let data = ffi::Decimal128VectorBatch_get_values(self.0).checked_data(num_elements);
 
         if vector_batch.not_null_ptr().is_some() {
             None
@@ -1234,24 +2439,88 @@ impl<'a> DecimalVectorBatch<'a> for Decimal128VectorBatch<'a> {
     }
 }
 
+impl<'a> Decimal128VectorBatch<'a> {
+    /// Returns an iterator on the mantissa and scale of each value, which
+    /// are exact even when they do not fit in [`Decimal`].
+    pub fn iter_raw(&self) -> RawDecimal128VectorBatchIterator<'a> {
+        let vector_batch =
+            BorrowedColumnVectorBatch(ffi::Decimal128VectorBatch_into_ColumnVectorBatch(self.0));
+        let num_elements = vector_batch.num_elements();
+        let data = ffi::Decimal128VectorBatch_get_values(self.0).checked_data(num_elements);
+        let not_null = vector_batch.not_null_ptr();
+
+        RawDecimal128VectorBatchIterator {
+            batch: PhantomData,
+            data_index: 0,
+            not_null_index: 0,
+            data_end: None,
+            data,
+            not_null,
+            num_elements: num_elements
+                .try_into()
+                .expect("could not convert u64 to isize"),
+            scale: self
+                .scale()
+                .try_into()
+                .expect("Could not convert scale from i32 to u43"),
+        }
+    }
+}
+
 unsafe impl Send for Decimal128VectorBatch<'_> {}
 
-/// Iterator on [`Decimal128VectorBatch`]
+/// Removes trailing zeros after the decimal point, like Hive and the reference
+/// `.jsn.gz` files do
+#[cfg(any(feature = "json", feature = "serde_json"))]
+pub(crate) fn trim_decimal(mut mantissa: i128, mut scale: u32) -> (i128, u32) {
+    while scale > 0 && mantissa % 10 == 0 {
+        mantissa /= 10;
+        scale -= 1;
+    }
+    (mantissa, scale)
+}
+
+/// Returns the exact representation of `mantissa * 10^-scale`, without trailing
+/// zeros
+#[cfg(any(feature = "json", feature = "serde_json"))]
+pub(crate) fn decimal_to_string(mantissa: i128, scale: u32) -> String {
+    let (mantissa, scale) = trim_decimal(mantissa, scale);
+    let scale = scale as usize;
+    let digits = mantissa.unsigned_abs().to_string();
+    let sign = if mantissa < 0 { "-" } else { "" };
+    if scale == 0 {
+        format!("{}{}", sign, digits)
+    } else if digits.len() > scale {
+        let (integer_part, fractional_part) = digits.split_at(digits.len() - scale);
+        format!("{}{}.{}", sign, integer_part, fractional_part)
+    } else {
+        format!("{}0.{:0>width$}", sign, digits, width = scale)
+    }
+}
+
+/// Iterator on [`Decimal128VectorBatch`] that yields the raw value and scale of each
+/// decimal, without converting it to [`Decimal`].
+///
+/// Unlike [`Decimal128VectorBatchIterator`], this works on values whose mantissa
+/// does not fit in the 96 bits of [`Decimal`].
 #[derive(Debug, Clone)]
-pub struct Decimal128VectorBatchIterator<'a> {
+pub struct RawDecimal128VectorBatchIterator<'a> {
     batch: PhantomData<&'a Decimal128VectorBatch<'a>>,
     data_index: isize,
     not_null_index: isize,
+    /// Index in `data` after the last value not returned yet, computed by the first
+    /// call to `next_back`
+    data_end: Option<isize>,
     data: *const memorypool::ffi::Int128,
     not_null: Option<ptr::NonNull<i8>>,
     num_elements: isize,
     scale: u32,
 }
 
-impl Iterator for Decimal128VectorBatchIterator<'_> {
-    type Item = Option<Decimal>;
+impl Iterator for RawDecimal128VectorBatchIterator<'_> {
+    type Item = Option<(i128, u32)>;
 
-    fn next(&mut self) -> Option<Option<Decimal>> {
+    fn next(&mut self) -> Option<Option<(i128, u32)>> {
         if self.not_null_index >= self.num_elements {
             return None;
         }
@@ -1283,29 +2552,118 @@ impl Iterator for Decimal128VectorBatchIterator<'_> {
 
         let datum = (datum.getHighBits() as i128) << 64 | (datum.getLowBits() as i128);
 
-        Some(Some(Decimal::from_i128_with_scale(datum, self.scale)))
+        Some(Some((datum, self.scale)))
     }
-}
 
-/// Iterator on [`Decimal128VectorBatch`] that may not yield `None`
-#[derive(Debug, Clone)]
-pub struct NotNullDecimal128VectorBatchIterator<'a> {
-    batch: PhantomData<&'a Decimal128VectorBatch<'a>>,
-    index: isize,
-    data: *const memorypool::ffi::Int128,
-    num_elements: isize,
-    scale: u32,
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.not_null_index) as usize;
+        (len, Some(len))
+    }
 }
 
-impl Iterator for NotNullDecimal128VectorBatchIterator<'_> {
-    type Item = Decimal;
-
-    fn next(&mut self) -> Option<Decimal> {
-        if self.index >= self.num_elements {
+impl DoubleEndedIterator for RawDecimal128VectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<Option<(i128, u32)>> {
+        if self.not_null_index >= self.num_elements {
             return None;
         }
 
-        // This should be safe because 'num_elements' should be exactly
+        let data_end = match self.data_end {
+            Some(data_end) => data_end,
+            // This should be safe because 'num_elements' is not greater than the
+            // length of 'not_null'
+            None => {
+                self.data_index
+                    + unsafe {
+                        count_not_null(self.not_null, self.not_null_index, self.num_elements)
+                    }
+            }
+        };
+
+        self.num_elements -= 1;
+
+        if let Some(not_null) = self.not_null {
+            let not_null = not_null.as_ptr();
+            // This should be safe because we just checked num_elements was greater
+            // than not_null_index
+            if unsafe { *not_null.offset(self.num_elements) } == 0 {
+                self.data_end = Some(data_end);
+                return Some(None);
+            }
+        }
+
+        let data_end = data_end - 1;
+        self.data_end = Some(data_end);
+
+        // This should be safe because 'data_end' is now the index in 'data' of the
+        // last non-null value not returned yet, and we checked this one is not null.
+        //
+        // We need to do a round-trip of conversion through i128 because Int128 is
+        // opaque, so it is not sized, so .offset() would just return the initial
+        // pointer.
+        let datum = unsafe {
+            &*((self.data as *const i128).offset(data_end) as *const memorypool::ffi::Int128)
+        };
+
+        let datum = (datum.getHighBits() as i128) << 64 | (datum.getLowBits() as i128);
+
+        Some(Some((datum, self.scale)))
+    }
+}
+
+impl ExactSizeIterator for RawDecimal128VectorBatchIterator<'_> {}
+
+/// Iterator on [`Decimal128VectorBatch`]
+///
+/// # Panics
+///
+/// When a value does not fit in [`Decimal`]; use
+/// [`Decimal128VectorBatch::iter_raw`] to read them.
+#[derive(Debug, Clone)]
+pub struct Decimal128VectorBatchIterator<'a>(RawDecimal128VectorBatchIterator<'a>);
+
+impl Iterator for Decimal128VectorBatchIterator<'_> {
+    type Item = Option<Decimal>;
+
+    fn next(&mut self) -> Option<Option<Decimal>> {
+        self.0.next().map(|datum| {
+            datum.map(|(mantissa, scale)| Decimal::from_i128_with_scale(mantissa, scale))
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Decimal128VectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<Option<Decimal>> {
+        self.0.next_back().map(|datum| {
+            datum.map(|(mantissa, scale)| Decimal::from_i128_with_scale(mantissa, scale))
+        })
+    }
+}
+
+impl ExactSizeIterator for Decimal128VectorBatchIterator<'_> {}
+
+/// Iterator on [`Decimal128VectorBatch`] that may not yield `None`
+#[derive(Debug, Clone)]
+pub struct NotNullDecimal128VectorBatchIterator<'a> {
+    batch: PhantomData<&'a Decimal128VectorBatch<'a>>,
+    index: isize,
+    data: *const memorypool::ffi::Int128,
+    num_elements: isize,
+    scale: u32,
+}
+
+impl Iterator for NotNullDecimal128VectorBatchIterator<'_> {
+    type Item = Decimal;
+
+    fn next(&mut self) -> Option<Decimal> {
+        if self.index >= self.num_elements {
+            return None;
+        }
+
+        // This should be safe because 'num_elements' should be exactly
         // the number of element in the array,
         // and we checked 'index' is lower than 'num_elements'.
         //
@@ -1322,8 +2680,40 @@ impl Iterator for NotNullDecimal128VectorBatchIterator<'_> {
 
         Some(Decimal::from_i128_with_scale(datum, self.scale))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for NotNullDecimal128VectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<Decimal> {
+        if self.index >= self.num_elements {
+            return None;
+        }
+
+        self.num_elements -= 1;
+
+        // This should be safe because 'num_elements' was greater than 'index', which
+        // is not negative.
+        //
+        // We need to do a round-trip of conversion through i128 because Int128 is
+        // opaque, so it is not sized, so .offset() would just return the initial
+        // pointer.
+        let datum = unsafe {
+            &*((self.data as *const i128).offset(self.num_elements)
+                as *const memorypool::ffi::Int128)
+        };
+
+        let datum = (datum.getHighBits() as i128) << 64 | (datum.getLowBits() as i128);
+
+        Some(Decimal::from_i128_with_scale(datum, self.scale))
+    }
 }
 
+impl ExactSizeIterator for NotNullDecimal128VectorBatchIterator<'_> {}
+
 /// A specialized [`ColumnVectorBatch`] whose values are lists of other values
 ///
 /// It is constructed through [`BorrowedColumnVectorBatch::try_into_lists`]
@@ -1336,9 +2726,11 @@ impl_upcast!(
 );
 
 impl<'a> ListVectorBatch<'a> {
-    /// The flat vector of all elements of all lists
+    /// The flat vector of all elements of all lists.
+    ///
+    /// Null lists have no elements; and elements may be null themselves, which is
+    /// recorded in their own [`not_null`](ColumnVectorBatch::not_null).
     pub fn elements(&self) -> BorrowedColumnVectorBatch<'a> {
-        // TODO: notNull
         BorrowedColumnVectorBatch(ffi::ListVectorBatch_get_elements(self.0))
     }
 
@@ -1366,6 +2758,31 @@ impl<'a> ListVectorBatch<'a> {
             Some(unsafe { NotNullRangeVectorBatchIterator::new(offsets, num_elements) })
         }
     }
+
+    /// Offset of each list in the flat vector, collected into a `Vec` to allow
+    /// random access. `None` values indicate absent lists.
+    ///
+    /// This is equivalent to `self.iter_offsets().collect()`.
+    pub fn ranges(&self) -> Vec<Option<Range<usize>>> {
+        self.iter_offsets().collect()
+    }
+
+    /// Returns the offsets array, without checking it. It has one more item than
+    /// the number of elements.
+    pub(crate) fn raw_offsets(&self) -> &'a [i64] {
+        let num_elements: usize = self
+            .num_elements()
+            .try_into()
+            .expect("could not convert u64 to usize");
+        // This should be safe because the offsets array has one more item than
+        // num_elements()
+        unsafe {
+            std::slice::from_raw_parts(
+                ffi::ListVectorBatch_get_offsets(self.0).checked_data(num_elements as u64 + 1),
+                num_elements + 1,
+            )
+        }
+    }
 }
 
 unsafe impl Send for ListVectorBatch<'_> {}
@@ -1383,15 +2800,19 @@ impl_upcast!(
 );
 
 impl<'a> MapVectorBatch<'a> {
-    /// The flat vector of all keys of all maps
+    /// The flat vector of all keys of all maps.
+    ///
+    /// Null maps have no keys; and keys may be null themselves, which is recorded
+    /// in their own [`not_null`](ColumnVectorBatch::not_null).
     pub fn keys(&self) -> BorrowedColumnVectorBatch<'a> {
-        // TODO: notNull
         BorrowedColumnVectorBatch(ffi::MapVectorBatch_get_keys(self.0))
     }
 
-    /// The flat vector of all values of all maps
+    /// The flat vector of all values of all maps.
+    ///
+    /// Null maps have no values; and values may be null themselves, which is
+    /// recorded in their own [`not_null`](ColumnVectorBatch::not_null).
     pub fn elements(&self) -> BorrowedColumnVectorBatch<'a> {
-        // TODO: notNull
         BorrowedColumnVectorBatch(ffi::MapVectorBatch_get_elements(self.0))
     }
 
@@ -1419,10 +2840,147 @@ impl<'a> MapVectorBatch<'a> {
             Some(unsafe { NotNullRangeVectorBatchIterator::new(offsets, num_elements) })
         }
     }
+
+    /// Offset of each map in the flat vector, collected into a `Vec` to allow
+    /// random access. `None` values indicate absent maps.
+    ///
+    /// This is equivalent to `self.iter_offsets().collect()`.
+    pub fn ranges(&self) -> Vec<Option<Range<usize>>> {
+        self.iter_offsets().collect()
+    }
+
+    /// Returns the offsets array, without checking it. It has one more item than
+    /// the number of elements.
+    pub(crate) fn raw_offsets(&self) -> &'a [i64] {
+        let num_elements: usize = self
+            .num_elements()
+            .try_into()
+            .expect("could not convert u64 to usize");
+        // This should be safe because the offsets array has one more item than
+        // num_elements()
+        unsafe {
+            std::slice::from_raw_parts(
+                ffi::MapVectorBatch_get_offsets(self.0).checked_data(num_elements as u64 + 1),
+                num_elements + 1,
+            )
+        }
+    }
 }
 
 unsafe impl Send for MapVectorBatch<'_> {}
 
+/// A specialized [`ColumnVectorBatch`] whose values are each of one of several
+/// types (the variants)
+///
+/// Values of each variant are stored in their own vector, in
+/// [`children`](UnionVectorBatch::children); each row has a tag, the index of its
+/// variant, and an offset, the index of its value in the vector of that variant.
+///
+/// It is constructed through [`BorrowedColumnVectorBatch::try_into_unions`]
+pub struct UnionVectorBatch<'a>(&'a ffi::UnionVectorBatch);
+
+impl_debug!(UnionVectorBatch<'a>, ffi::UnionVectorBatch_toString);
+impl_upcast!(
+    UnionVectorBatch<'a>,
+    ffi::UnionVectorBatch_into_ColumnVectorBatch
+);
+
+impl<'a> UnionVectorBatch<'a> {
+    /// The vector of values of each variant
+    pub fn children(&self) -> Vec<BorrowedColumnVectorBatch<'a>> {
+        ffi::UnionVectorBatch_get_children(self.0)
+            .iter()
+            .map(|batch_ptr| {
+                BorrowedColumnVectorBatch(unsafe {
+                    // This is safe for the same reason as in StructVectorBatch::fields
+                    &*ffi::ColumnVectorBatchPtr_make_ptr(batch_ptr)
+                })
+            })
+            .collect()
+    }
+
+    /// Index of the variant of each row. Items for null rows are unspecified.
+    pub fn tags(&self) -> &'a [u8] {
+        let num_elements = self.num_elements();
+        let tags = ffi::UnionVectorBatch_get_tags(self.0).checked_data(num_elements);
+        // This should be safe because the tags array has num_elements() items
+        unsafe {
+            std::slice::from_raw_parts(
+                tags,
+                num_elements
+                    .try_into()
+                    .expect("could not convert u64 to usize"),
+            )
+        }
+    }
+
+    /// Index of the value of each row in the vector of its variant. Items for null
+    /// rows are unspecified.
+    pub fn offsets(&self) -> &'a [u64] {
+        let num_elements = self.num_elements();
+        let offsets = ffi::UnionVectorBatch_get_offsets(self.0).checked_data(num_elements);
+        // This should be safe because the offsets array has num_elements() items
+        unsafe {
+            std::slice::from_raw_parts(
+                offsets,
+                num_elements
+                    .try_into()
+                    .expect("could not convert u64 to usize"),
+            )
+        }
+    }
+
+    /// Returns the tag and offset of each row, or `None` for null rows
+    pub fn iter(&self) -> UnionVectorBatchIterator<'a> {
+        UnionVectorBatchIterator {
+            tags: self.tags().iter(),
+            offsets: self.offsets().iter(),
+            not_null: self.not_null().map(|not_null| not_null.iter()),
+        }
+    }
+}
+
+unsafe impl Send for UnionVectorBatch<'_> {}
+
+/// Iterator on [`UnionVectorBatch`] that yields `(tag, offset)` pairs, or `None`
+/// for null rows.
+#[derive(Debug, Clone)]
+pub struct UnionVectorBatchIterator<'a> {
+    tags: std::slice::Iter<'a, u8>,
+    offsets: std::slice::Iter<'a, u64>,
+    not_null: Option<std::slice::Iter<'a, i8>>,
+}
+
+impl Iterator for UnionVectorBatchIterator<'_> {
+    type Item = Option<(u8, u64)>;
+
+    fn next(&mut self) -> Option<Option<(u8, u64)>> {
+        let tag = *self.tags.next()?;
+        let offset = *self.offsets.next()?;
+        match self.not_null.as_mut().map(|not_null| not_null.next()) {
+            Some(Some(0)) => Some(None),
+            _ => Some(Some((tag, offset))),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.tags.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for UnionVectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<Option<(u8, u64)>> {
+        let tag = *self.tags.next_back()?;
+        let offset = *self.offsets.next_back()?;
+        match self.not_null.as_mut().map(|not_null| not_null.next_back()) {
+            Some(Some(0)) => Some(None),
+            _ => Some(Some((tag, offset))),
+        }
+    }
+}
+
+impl ExactSizeIterator for UnionVectorBatchIterator<'_> {}
+
 /// Iterator on the `offset` columns of [`ListVectorBatch`] and [`MapVectorBatch`],
 /// which may yield `None`.
 ///
@@ -1434,6 +2992,9 @@ pub struct RangeVectorBatchIterator<'a> {
     batch: PhantomData<&'a LongVectorBatch<'a>>,
     data_index: isize,
     not_null_index: isize,
+    /// Index in `data` after the last value not returned yet, computed by the first
+    /// call to `next_back`
+    data_end: Option<isize>,
     data: *const i64,
     not_null: Option<ptr::NonNull<i8>>,
     num_elements: isize,
@@ -1445,14 +3006,12 @@ impl<'a> RangeVectorBatchIterator<'a> {
         not_null: Option<ptr::NonNull<i8>>,
         num_elements: u64,
     ) -> RangeVectorBatchIterator<'a> {
-        // TODO: do this once https://github.com/apache/orc/commit/294a5e28f7f0420eb1fdc76dffc33608692c1b20
-        // is released:
-        // assert_eq!(std::mem::size_of(u64)*num_elements, data_buffer.size())
         RangeVectorBatchIterator {
             batch: PhantomData,
             data_index: 0,
             not_null_index: 0,
-            data: data_buffer.data(),
+            data_end: None,
+            data: data_buffer.checked_data(num_elements + 1),
             not_null,
             num_elements: num_elements
                 .try_into()
@@ -1494,8 +3053,61 @@ impl Iterator for RangeVectorBatchIterator<'_> {
 
         Some(Some(datum..next_datum))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.not_null_index) as usize;
+        (len, Some(len))
+    }
 }
 
+impl DoubleEndedIterator for RangeVectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<Option<Range<usize>>> {
+        if self.not_null_index >= self.num_elements {
+            return None;
+        }
+
+        let data_end = match self.data_end {
+            Some(data_end) => data_end,
+            // This should be safe because 'num_elements' is not greater than the
+            // length of 'not_null'
+            None => {
+                self.data_index
+                    + unsafe {
+                        count_not_null(self.not_null, self.not_null_index, self.num_elements)
+                    }
+            }
+        };
+
+        self.num_elements -= 1;
+
+        if let Some(not_null) = self.not_null {
+            let not_null = not_null.as_ptr();
+            // This should be safe because we just checked num_elements was greater
+            // than not_null_index
+            if unsafe { *not_null.offset(self.num_elements) } == 0 {
+                self.data_end = Some(data_end);
+                return Some(None);
+            }
+        }
+
+        let data_end = data_end - 1;
+        self.data_end = Some(data_end);
+
+        // This should be safe because 'data_end' is now the index in 'data' of the
+        // last non-null value not returned yet, and we checked this one is not null.
+        let next_datum = unsafe { *self.data.offset(data_end + 1) }
+            .try_into()
+            .expect("could not convert i64 to usize");
+
+        // No chek needed as datum can't be larger than next_datum
+        let datum = unsafe { *self.data.offset(data_end) } as usize;
+
+        Some(Some(datum..next_datum))
+    }
+}
+
+impl ExactSizeIterator for RangeVectorBatchIterator<'_> {}
+
 /// Iterator on the `offset` columns of [`ListVectorBatch`] and [`MapVectorBatch`],
 /// which may not yield `None`.
 ///
@@ -1515,13 +3127,10 @@ impl<'a> NotNullRangeVectorBatchIterator<'a> {
         data_buffer: &memorypool::ffi::Int64DataBuffer,
         num_elements: u64,
     ) -> NotNullRangeVectorBatchIterator<'a> {
-        // TODO: do this once https://github.com/apache/orc/commit/294a5e28f7f0420eb1fdc76dffc33608692c1b20
-        // is released:
-        // assert_eq!(std::mem::size_of(u64)*num_elements, data_buffer.size())
         NotNullRangeVectorBatchIterator {
             batch: PhantomData,
             index: 0,
-            data: data_buffer.data(),
+            data: data_buffer.checked_data(num_elements + 1),
             num_elements: num_elements
                 .try_into()
                 .expect("could not convert u64 to isize"),
@@ -1551,4 +3160,774 @@ impl Iterator for NotNullRangeVectorBatchIterator<'_> {
 
         Some(datum..next_datum)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.num_elements - self.index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for NotNullRangeVectorBatchIterator<'_> {
+    fn next_back(&mut self) -> Option<Range<usize>> {
+        if self.index >= self.num_elements {
+            return None;
+        }
+
+        self.num_elements -= 1;
+
+        // This should be safe because 'num_elements' was greater than 'index', which
+        // is not negative.
+        let next_datum = unsafe { *self.data.offset(self.num_elements + 1) }
+            .try_into()
+            .expect("could not convert i64 to usize");
+
+        // No chek needed as datum can't be larger than next_datum
+        let datum = unsafe { *self.data.offset(self.num_elements) } as usize;
+
+        Some(datum..next_datum)
+    }
+}
+
+impl ExactSizeIterator for NotNullRangeVectorBatchIterator<'_> {}
+
+/// Zips two iterators of nullable values (typically from two columns of the same
+/// batch, or the keys and values of a [`MapVectorBatch`]) into an iterator of
+/// `Option<(A, B)>`, which yields `None` when either value is null.
+///
+/// # Panics
+///
+/// When iterated, if the iterators do not have the same length.
+///
+/// # Example
+///
+/// ```
+/// use orcxx::*;
+///
+/// let orc_path = "orc/examples/TestOrcFile.test1.orc";
+/// let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+/// let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+///
+/// let options = reader::RowReaderOptions::default().include_names(["int1", "string1"]);
+/// let mut row_reader = reader.row_reader(&options).unwrap();
+/// let mut batch = row_reader.row_batch(1024);
+/// assert!(row_reader.read_into(&mut batch));
+///
+/// let struct_vector = batch.borrow().try_into_structs().unwrap();
+/// let columns = struct_vector.fields();
+/// let ints = columns[0].try_into_longs().unwrap();
+/// let strings = columns[1].try_into_strings().unwrap();
+///
+/// let pairs: Vec<_> = zip_nulls(ints.iter(), strings.iter()).collect();
+/// assert_eq!(pairs, [Some((65536, &b"hi"[..])), Some((65536, &b"bye"[..]))]);
+/// ```
+pub fn zip_nulls<A, B, IA, IB>(a: IA, b: IB) -> ZipNulls<IA::IntoIter, IB::IntoIter>
+where
+    IA: IntoIterator<Item = Option<A>>,
+    IB: IntoIterator<Item = Option<B>>,
+{
+    ZipNulls {
+        a: a.into_iter(),
+        b: b.into_iter(),
+    }
+}
+
+/// Iterator returned by [`zip_nulls`]
+#[derive(Debug, Clone)]
+pub struct ZipNulls<IA, IB> {
+    a: IA,
+    b: IB,
+}
+
+impl<A, B, IA, IB> Iterator for ZipNulls<IA, IB>
+where
+    IA: Iterator<Item = Option<A>>,
+    IB: Iterator<Item = Option<B>>,
+{
+    type Item = Option<(A, B)>;
+
+    fn next(&mut self) -> Option<Option<(A, B)>> {
+        match (self.a.next(), self.b.next()) {
+            (None, None) => None,
+            (Some(Some(a)), Some(Some(b))) => Some(Some((a, b))),
+            (Some(_), Some(_)) => Some(None),
+            _ => panic!("zip_nulls got iterators of different lengths"),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.a.size_hint()
+    }
+}
+
+impl<A, B, IA, IB> DoubleEndedIterator for ZipNulls<IA, IB>
+where
+    IA: DoubleEndedIterator<Item = Option<A>> + ExactSizeIterator,
+    IB: DoubleEndedIterator<Item = Option<B>> + ExactSizeIterator,
+{
+    fn next_back(&mut self) -> Option<Option<(A, B)>> {
+        // Values at the back of iterators of different lengths do not match
+        assert_eq!(
+            self.a.len(),
+            self.b.len(),
+            "zip_nulls got iterators of different lengths"
+        );
+        match (self.a.next_back(), self.b.next_back()) {
+            (None, None) => None,
+            (Some(Some(a)), Some(Some(b))) => Some(Some((a, b))),
+            (Some(_), Some(_)) => Some(None),
+            _ => panic!("zip_nulls got iterators of different lengths"),
+        }
+    }
+}
+
+impl<A, B, IA, IB> ExactSizeIterator for ZipNulls<IA, IB>
+where
+    IA: ExactSizeIterator<Item = Option<A>>,
+    IB: ExactSizeIterator<Item = Option<B>>,
+{
+}
+
+/// Same as [`ColumnVectorBatch::validity`], but for pairs of rows of two batches:
+/// bits are `true` for rows which are null in neither batch. Returns `None` if
+/// neither batch contains null values.
+///
+/// # Panics
+///
+/// If the batches do not have the same number of elements.
+pub fn zip_validity<'a, 'b, VA, VB>(a: &VA, b: &VB) -> Option<Bitmap>
+where
+    VA: ColumnVectorBatch<'a>,
+    VB: ColumnVectorBatch<'b>,
+{
+    assert_eq!(
+        a.num_elements(),
+        b.num_elements(),
+        "zip_validity got batches of different lengths"
+    );
+    match (a.not_null_bools(), b.not_null_bools()) {
+        (None, None) => None,
+        (Some(not_null), None) | (None, Some(not_null)) => Some(not_null.collect()),
+        (Some(a), Some(b)) => Some(a.zip(b).map(|(a, b)| a && b).collect()),
+    }
+}
+
+/// Returns the number of rows which are null in either batch, ie. the number of
+/// `None` yielded by [`zip_nulls`] on their iterators.
+///
+/// # Panics
+///
+/// If the batches do not have the same number of elements.
+pub fn zip_null_count<'a, 'b, VA, VB>(a: &VA, b: &VB) -> u64
+where
+    VA: ColumnVectorBatch<'a>,
+    VB: ColumnVectorBatch<'b>,
+{
+    match zip_validity(a, b) {
+        Some(validity) => (validity.len() - validity.count_ones()) as u64,
+        None => 0,
+    }
+}
+
+/// Implements [`IntoIterator`] on references to a batch, so batches can be passed
+/// directly to [`zip_nulls`]
+macro_rules! impl_into_iterator {
+    ($struct_name:ident, $iterator_name:ident) => {
+        impl<'a, 'b> IntoIterator for &'b $struct_name<'a> {
+            type Item = <$iterator_name<'a> as Iterator>::Item;
+            type IntoIter = $iterator_name<'a>;
+
+            fn into_iter(self) -> $iterator_name<'a> {
+                self.iter()
+            }
+        }
+    };
+}
+
+impl_into_iterator!(LongVectorBatch, LongVectorBatchIterator);
+impl_into_iterator!(DoubleVectorBatch, DoubleVectorBatchIterator);
+impl_into_iterator!(StringVectorBatch, StringVectorBatchIterator);
+impl_into_iterator!(Decimal64VectorBatch, Decimal64VectorBatchIterator);
+impl_into_iterator!(Decimal128VectorBatch, Decimal128VectorBatchIterator);
+
+impl<'a> IntoIterator for &'a TimestampVectorBatch<'_> {
+    type Item = Option<(i64, i64)>;
+    type IntoIter = TimestampVectorBatchIterator<'a>;
+
+    fn into_iter(self) -> TimestampVectorBatchIterator<'a> {
+        self.iter()
+    }
+}
+
+/// Minimum number of rows allocated when a batch grows because a row is pushed
+/// into it
+const MIN_GROWTH: u64 = 1024;
+
+macro_rules! impl_upcast_mut {
+    ($struct_name:ident<$lifetime:lifetime>, $function_name:path, $function_name_mut:path) => {
+        impl<$lifetime> ColumnVectorBatchMut for $struct_name<$lifetime> {
+            fn inner(&self) -> &ffi::ColumnVectorBatch {
+                $function_name(&self.0)
+            }
+
+            fn inner_mut(&mut self) -> Pin<&mut ffi::ColumnVectorBatch> {
+                $function_name_mut(self.0.as_mut())
+            }
+        }
+    };
+}
+
+/// Common methods of [`BorrowedColumnVectorBatchMut`] and its specialized structs.
+///
+/// Rows are appended with `push` and `push_null`, which grow the batch as needed,
+/// and existing rows are overwritten with `set`, `set_null`, and `set_not_null`.
+pub trait ColumnVectorBatchMut {
+    fn inner(&self) -> &ffi::ColumnVectorBatch;
+    fn inner_mut(&mut self) -> Pin<&mut ffi::ColumnVectorBatch>;
+
+    fn num_elements(&self) -> u64 {
+        ffi::get_numElements(self.inner())
+    }
+
+    /// Number of rows the batch can hold without reallocating
+    fn capacity(&self) -> u64 {
+        ffi::get_capacity(self.inner())
+    }
+
+    /// Makes sure the batch can hold at least `capacity` rows without reallocating.
+    ///
+    /// This does not reserve anything for the fields of structures, which must be
+    /// reserved separately.
+    fn reserve(&mut self, capacity: u64) {
+        self.inner_mut().resize(capacity)
+    }
+
+    /// Removes all rows from this batch and the batches it contains.
+    fn clear(&mut self) {
+        ffi::clearBatch(self.inner_mut())
+    }
+
+    /// Marks the index-th row as null
+    ///
+    /// # Panics
+    ///
+    /// If `index` is not lower than [`num_elements`](ColumnVectorBatchMut::num_elements)
+    fn set_null(&mut self, index: u64) {
+        set_not_null_flag(self.inner_mut(), index, false)
+    }
+
+    /// Marks the index-th row as not null, without changing its value
+    ///
+    /// # Safety
+    ///
+    /// The row must hold a value, written by the `set` or `push` method of the
+    /// specialized struct (and, for structures, by their fields). Rows appended
+    /// with [`push_null`](ColumnVectorBatchMut::push_null) are not initialized, so
+    /// marking them as not null would expose uninitialized memory (including string
+    /// pointers) to readers of the batch.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is not lower than [`num_elements`](ColumnVectorBatchMut::num_elements)
+    unsafe fn set_not_null(&mut self, index: u64) {
+        set_not_null_flag(self.inner_mut(), index, true)
+    }
+
+    /// Appends a null row, whose value is not initialized
+    fn push_null(&mut self) {
+        let index = push_row(self.inner_mut());
+        self.set_null(index)
+    }
+}
+
+/// Appends a row to the batch, growing it if needed, and returns its index.
+///
+/// Neither the value of the row nor its notNull flag are initialized.
+fn push_row(mut batch: Pin<&mut ffi::ColumnVectorBatch>) -> u64 {
+    let index = ffi::get_numElements(&batch);
+    let capacity = ffi::get_capacity(&batch);
+    if index >= capacity {
+        batch
+            .as_mut()
+            .resize(std::cmp::max(MIN_GROWTH, capacity.saturating_mul(2)));
+    }
+    ffi::set_numElements(batch, index + 1);
+    index
+}
+
+fn set_not_null_flag(mut batch: Pin<&mut ffi::ColumnVectorBatch>, index: u64, not_null: bool) {
+    let num_elements = ffi::get_numElements(&batch);
+    assert!(
+        index < num_elements,
+        "Index {} is out of bounds of a batch with {} rows",
+        index,
+        num_elements
+    );
+    let num_elements: usize = num_elements
+        .try_into()
+        .expect("could not convert u64 to usize");
+    let index: usize = index.try_into().expect("could not convert u64 to usize");
+
+    let flags = ffi::get_notNull(&batch).checked_data(num_elements as u64) as *mut c_char;
+    if !not_null && !ffi::get_hasNulls(&batch) {
+        // Flags are ignored when the batch has no nulls, so they may be stale
+        // (eg. from a previous read)
+        // This is safe because we just checked the buffer is large enough
+        unsafe { ptr::write_bytes(flags, 1, num_elements) };
+        ffi::set_hasNulls(batch.as_mut(), true);
+    }
+    // This is safe because we checked the index is in bounds
+    unsafe { *flags.add(index) = not_null as c_char };
+}
+
+/// A column (or set of column) of a stripe, with values of unknown type, which
+/// can be modified.
+///
+/// It is constructed through [`OwnedColumnVectorBatch::borrow_mut`]
+pub struct BorrowedColumnVectorBatchMut<'a>(Pin<&'a mut ffi::ColumnVectorBatch>);
+
+impl_debug!(
+    BorrowedColumnVectorBatchMut<'a>,
+    ffi::ColumnVectorBatch_toString
+);
+
+impl ColumnVectorBatchMut for BorrowedColumnVectorBatchMut<'_> {
+    fn inner(&self) -> &ffi::ColumnVectorBatch {
+        &self.0
+    }
+
+    fn inner_mut(&mut self) -> Pin<&mut ffi::ColumnVectorBatch> {
+        self.0.as_mut()
+    }
 }
+
+impl<'a> BorrowedColumnVectorBatchMut<'a> {
+    pub fn try_into_longs(self) -> OrcResult<LongVectorBatchMut<'a>> {
+        ffi::try_into_LongVectorBatchMut(self.0)
+            .map_err(OrcError)
+            .map(LongVectorBatchMut)
+    }
+
+    pub fn try_into_doubles(self) -> OrcResult<DoubleVectorBatchMut<'a>> {
+        ffi::try_into_DoubleVectorBatchMut(self.0)
+            .map_err(OrcError)
+            .map(DoubleVectorBatchMut)
+    }
+
+    pub fn try_into_strings(self) -> OrcResult<StringVectorBatchMut<'a>> {
+        ffi::try_into_StringVectorBatchMut(self.0)
+            .map_err(OrcError)
+            .map(StringVectorBatchMut)
+    }
+
+    pub fn try_into_timestamps(self) -> OrcResult<TimestampVectorBatchMut<'a>> {
+        ffi::try_into_TimestampVectorBatchMut(self.0)
+            .map_err(OrcError)
+            .map(TimestampVectorBatchMut)
+    }
+
+    pub fn try_into_decimals128(self) -> OrcResult<Decimal128VectorBatchMut<'a>> {
+        ffi::try_into_Decimal128VectorBatchMut(self.0)
+            .map_err(OrcError)
+            .map(Decimal128VectorBatchMut)
+    }
+
+    pub fn try_into_structs(self) -> OrcResult<StructVectorBatchMut<'a>> {
+        ffi::try_into_StructVectorBatchMut(self.0)
+            .map_err(OrcError)
+            .map(StructVectorBatchMut)
+    }
+
+    pub fn try_into_lists(self) -> OrcResult<ListVectorBatchMut<'a>> {
+        ffi::try_into_ListVectorBatchMut(self.0)
+            .map_err(OrcError)
+            .map(ListVectorBatchMut)
+    }
+
+    pub fn try_into_maps(self) -> OrcResult<MapVectorBatchMut<'a>> {
+        ffi::try_into_MapVectorBatchMut(self.0)
+            .map_err(OrcError)
+            .map(MapVectorBatchMut)
+    }
+}
+
+/// Returns a mutable reference to a child batch (the elements of a list, or the
+/// keys or values of a map)
+fn child_mut(child: &UniquePtr<ffi::ColumnVectorBatch>) -> BorrowedColumnVectorBatchMut<'_> {
+    let child = child.as_ptr() as *mut ffi::ColumnVectorBatch;
+    assert!(!child.is_null(), "child batch is null");
+    BorrowedColumnVectorBatchMut(unsafe {
+        // This is safe because the child is owned by its parent batch, which is
+        // mutably borrowed as long as it is
+        Pin::new_unchecked(&mut *child)
+    })
+}
+
+/// Sets the end offset of the index-th list or map, so it has `length` items after
+/// the end of the previous one.
+///
+/// # Safety
+///
+/// `offsets` must point to at least `index + 2` items.
+unsafe fn write_length(offsets: *mut i64, index: u64, length: u64) {
+    let index: usize = index.try_into().expect("could not convert u64 to usize");
+    if index == 0 {
+        // Offsets may be stale (eg. from a previous read) after a clear
+        *offsets = 0;
+    }
+    let length: i64 = length.try_into().expect("length overflows i64");
+    *offsets.add(index + 1) = *offsets.add(index) + length;
+}
+
+unsafe impl Send for BorrowedColumnVectorBatchMut<'_> {}
+
+/// Mutable counterpart of [`StructVectorBatch`].
+///
+/// Fields are modified independently of the structure, and must end up with as many
+/// rows as the structure itself, including for rows where the structure is null.
+///
+/// It is constructed through [`BorrowedColumnVectorBatchMut::try_into_structs`]
+pub struct StructVectorBatchMut<'a>(Pin<&'a mut ffi::StructVectorBatch>);
+
+impl_debug!(StructVectorBatchMut<'a>, ffi::StructVectorBatch_toString);
+impl_upcast_mut!(
+    StructVectorBatchMut<'a>,
+    ffi::StructVectorBatch_into_ColumnVectorBatch,
+    ffi::StructVectorBatchMut_into_ColumnVectorBatch
+);
+
+impl StructVectorBatchMut<'_> {
+    pub fn fields(&mut self) -> Vec<BorrowedColumnVectorBatchMut<'_>> {
+        ffi::StructVectorBatch_get_fields(&self.0)
+            .iter()
+            .map(|batch_ptr| {
+                BorrowedColumnVectorBatchMut(unsafe {
+                    // This is safe because fields are distinct batches, owned by
+                    // this StructVectorBatch, which is mutably borrowed as long as
+                    // they are
+                    Pin::new_unchecked(
+                        &mut *(ffi::ColumnVectorBatchPtr_make_ptr(batch_ptr)
+                            as *mut ffi::ColumnVectorBatch),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Appends a row which is not null. A value should be pushed to each field as well.
+    ///
+    /// Fields are not grown by this method; [`Writer::write_batch`](::writer::Writer::write_batch)
+    /// rejects batches whose fields have fewer rows than the structure.
+    pub fn push(&mut self) {
+        let index = push_row(self.inner_mut());
+        set_not_null_flag(self.inner_mut(), index, true)
+    }
+}
+
+unsafe impl Send for StructVectorBatchMut<'_> {}
+
+/// Mutable counterpart of [`LongVectorBatch`].
+///
+/// It is constructed through [`BorrowedColumnVectorBatchMut::try_into_longs`]
+pub struct LongVectorBatchMut<'a>(Pin<&'a mut ffi::LongVectorBatch>);
+
+impl_debug!(LongVectorBatchMut<'a>, ffi::LongVectorBatch_toString);
+impl_upcast_mut!(
+    LongVectorBatchMut<'a>,
+    ffi::LongVectorBatch_into_ColumnVectorBatch,
+    ffi::LongVectorBatchMut_into_ColumnVectorBatch
+);
+
+impl LongVectorBatchMut<'_> {
+    /// Overwrites the index-th row, and marks it as not null
+    ///
+    /// # Panics
+    ///
+    /// If `index` is not lower than [`num_elements`](ColumnVectorBatchMut::num_elements)
+    pub fn set(&mut self, index: u64, value: i64) {
+        set_not_null_flag(self.inner_mut(), index, true);
+        let data = ffi::LongVectorBatch_get_data(&self.0).checked_data(index + 1) as *mut i64;
+        let index: usize = index.try_into().expect("could not convert u64 to usize");
+        // This is safe because set_not_null_flag checked the index is in bounds
+        unsafe { *data.add(index) = value };
+    }
+
+    /// Appends a row which is not null
+    pub fn push(&mut self, value: i64) {
+        let index = push_row(self.inner_mut());
+        self.set(index, value)
+    }
+}
+
+unsafe impl Send for LongVectorBatchMut<'_> {}
+
+/// Mutable counterpart of [`DoubleVectorBatch`].
+///
+/// It is constructed through [`BorrowedColumnVectorBatchMut::try_into_doubles`]
+pub struct DoubleVectorBatchMut<'a>(Pin<&'a mut ffi::DoubleVectorBatch>);
+
+impl_debug!(DoubleVectorBatchMut<'a>, ffi::DoubleVectorBatch_toString);
+impl_upcast_mut!(
+    DoubleVectorBatchMut<'a>,
+    ffi::DoubleVectorBatch_into_ColumnVectorBatch,
+    ffi::DoubleVectorBatchMut_into_ColumnVectorBatch
+);
+
+impl DoubleVectorBatchMut<'_> {
+    /// Overwrites the index-th row, and marks it as not null
+    ///
+    /// # Panics
+    ///
+    /// If `index` is not lower than [`num_elements`](ColumnVectorBatchMut::num_elements)
+    pub fn set(&mut self, index: u64, value: f64) {
+        set_not_null_flag(self.inner_mut(), index, true);
+        let data = ffi::DoubleVectorBatch_get_data(&self.0).checked_data(index + 1) as *mut f64;
+        let index: usize = index.try_into().expect("could not convert u64 to usize");
+        // This is safe because set_not_null_flag checked the index is in bounds
+        unsafe { *data.add(index) = value };
+    }
+
+    /// Appends a row which is not null
+    pub fn push(&mut self, value: f64) {
+        let index = push_row(self.inner_mut());
+        self.set(index, value)
+    }
+}
+
+unsafe impl Send for DoubleVectorBatchMut<'_> {}
+
+/// Mutable counterpart of [`TimestampVectorBatch`], for both `timestamp` and
+/// `timestamp with local time zone` columns.
+///
+/// It is constructed through [`BorrowedColumnVectorBatchMut::try_into_timestamps`]
+pub struct TimestampVectorBatchMut<'a>(Pin<&'a mut ffi::TimestampVectorBatch>);
+
+impl_debug!(
+    TimestampVectorBatchMut<'a>,
+    ffi::TimestampVectorBatch_toString
+);
+impl_upcast_mut!(
+    TimestampVectorBatchMut<'a>,
+    ffi::TimestampVectorBatch_into_ColumnVectorBatch,
+    ffi::TimestampVectorBatchMut_into_ColumnVectorBatch
+);
+
+impl TimestampVectorBatchMut<'_> {
+    /// Overwrites the index-th row with the given number of seconds since the epoch
+    /// and nanoseconds within the second, and marks it as not null
+    ///
+    /// # Panics
+    ///
+    /// If `index` is not lower than [`num_elements`](ColumnVectorBatchMut::num_elements)
+    pub fn set(&mut self, index: u64, seconds: i64, nanoseconds: i64) {
+        set_not_null_flag(self.inner_mut(), index, true);
+        let data = ffi::TimestampVectorBatch_get_data(&self.0).checked_data(index + 1) as *mut i64;
+        let nanoseconds_data =
+            ffi::TimestampVectorBatch_get_nanoseconds(&self.0).checked_data(index + 1) as *mut i64;
+        let index: usize = index.try_into().expect("could not convert u64 to usize");
+        // This is safe because set_not_null_flag checked the index is in bounds
+        unsafe {
+            *data.add(index) = seconds;
+            *nanoseconds_data.add(index) = nanoseconds;
+        }
+    }
+
+    /// Appends a row which is not null
+    pub fn push(&mut self, seconds: i64, nanoseconds: i64) {
+        let index = push_row(self.inner_mut());
+        self.set(index, seconds, nanoseconds)
+    }
+}
+
+unsafe impl Send for TimestampVectorBatchMut<'_> {}
+
+/// Mutable counterpart of [`Decimal128VectorBatch`].
+///
+/// Values are given as their mantissa, in the scale of the column.
+///
+/// It is constructed through [`BorrowedColumnVectorBatchMut::try_into_decimals128`]
+pub struct Decimal128VectorBatchMut<'a>(Pin<&'a mut ffi::Decimal128VectorBatch>);
+
+impl_debug!(
+    Decimal128VectorBatchMut<'a>,
+    ffi::Decimal128VectorBatch_toString
+);
+impl_upcast_mut!(
+    Decimal128VectorBatchMut<'a>,
+    ffi::Decimal128VectorBatch_into_ColumnVectorBatch,
+    ffi::Decimal128VectorBatchMut_into_ColumnVectorBatch
+);
+
+impl Decimal128VectorBatchMut<'_> {
+    /// Overwrites the index-th row, and marks it as not null
+    ///
+    /// # Panics
+    ///
+    /// If `index` is not lower than [`num_elements`](ColumnVectorBatchMut::num_elements)
+    pub fn set(&mut self, index: u64, mantissa: i128) {
+        set_not_null_flag(self.inner_mut(), index, true);
+        ffi::setInt128(
+            self.0.as_mut(),
+            index,
+            (mantissa >> 64) as i64,
+            mantissa as u64,
+        )
+    }
+
+    /// Appends a row which is not null
+    pub fn push(&mut self, mantissa: i128) {
+        let index = push_row(self.inner_mut());
+        self.set(index, mantissa)
+    }
+}
+
+unsafe impl Send for Decimal128VectorBatchMut<'_> {}
+
+/// Mutable counterpart of [`StringVectorBatch`].
+///
+/// Values are copied to a buffer owned by the batch, which is emptied by
+/// [`clear`](ColumnVectorBatchMut::clear).
+///
+/// It is constructed through [`BorrowedColumnVectorBatchMut::try_into_strings`]
+pub struct StringVectorBatchMut<'a>(Pin<&'a mut ffi::StringVectorBatch>);
+
+impl_debug!(StringVectorBatchMut<'a>, ffi::StringVectorBatch_toString);
+impl_upcast_mut!(
+    StringVectorBatchMut<'a>,
+    ffi::StringVectorBatch_into_ColumnVectorBatch,
+    ffi::StringVectorBatchMut_into_ColumnVectorBatch
+);
+
+impl StringVectorBatchMut<'_> {
+    /// Overwrites the index-th row, and marks it as not null
+    ///
+    /// # Panics
+    ///
+    /// If `index` is not lower than [`num_elements`](ColumnVectorBatchMut::num_elements)
+    pub fn set<V: AsRef<[u8]>>(&mut self, index: u64, value: V) {
+        set_not_null_flag(self.inner_mut(), index, true);
+        ffi::setString(self.0.as_mut(), index, value.as_ref())
+    }
+
+    /// Appends a row which is not null
+    pub fn push<V: AsRef<[u8]>>(&mut self, value: V) {
+        let index = push_row(self.inner_mut());
+        self.set(index, value)
+    }
+}
+
+unsafe impl Send for StringVectorBatchMut<'_> {}
+
+/// Mutable counterpart of [`ListVectorBatch`].
+///
+/// Elements are pushed to [`elements`](ListVectorBatchMut::elements) independently
+/// of the lists, and must end up with as many rows as the sum of the lengths of
+/// the lists.
+///
+/// Rows should be appended with [`push`](ListVectorBatchMut::push) and
+/// [`push_null`](ColumnVectorBatchMut::push_null), which write the offsets of the
+/// lists; rows appended through a [`BorrowedColumnVectorBatchMut`] do not.
+///
+/// It is constructed through [`BorrowedColumnVectorBatchMut::try_into_lists`]
+pub struct ListVectorBatchMut<'a>(Pin<&'a mut ffi::ListVectorBatch>);
+
+impl_debug!(ListVectorBatchMut<'a>, ffi::ListVectorBatch_toString);
+
+impl ColumnVectorBatchMut for ListVectorBatchMut<'_> {
+    fn inner(&self) -> &ffi::ColumnVectorBatch {
+        ffi::ListVectorBatch_into_ColumnVectorBatch(&self.0)
+    }
+
+    fn inner_mut(&mut self) -> Pin<&mut ffi::ColumnVectorBatch> {
+        ffi::ListVectorBatchMut_into_ColumnVectorBatch(self.0.as_mut())
+    }
+
+    /// Appends a null list, which has no elements
+    fn push_null(&mut self) {
+        let index = push_row(self.inner_mut());
+        self.set_null(index);
+        self.set_length(index, 0)
+    }
+}
+
+impl ListVectorBatchMut<'_> {
+    /// The flat vector of all elements of all lists
+    pub fn elements(&mut self) -> BorrowedColumnVectorBatchMut<'_> {
+        child_mut(ffi::ListVectorBatch_get_elements(&self.0))
+    }
+
+    /// Appends a list which is not null, made of the next `length` rows of
+    /// [`elements`](ListVectorBatchMut::elements)
+    pub fn push(&mut self, length: u64) {
+        let index = push_row(self.inner_mut());
+        set_not_null_flag(self.inner_mut(), index, true);
+        self.set_length(index, length)
+    }
+
+    fn set_length(&mut self, index: u64, length: u64) {
+        let offsets = ffi::ListVectorBatch_get_offsets(&self.0).checked_data(index + 2) as *mut i64;
+        // This is safe because push_row() grew the offsets along with the batch
+        unsafe { write_length(offsets, index, length) }
+    }
+}
+
+unsafe impl Send for ListVectorBatchMut<'_> {}
+
+/// Mutable counterpart of [`MapVectorBatch`].
+///
+/// Keys and values are pushed to [`keys`](MapVectorBatchMut::keys) and
+/// [`elements`](MapVectorBatchMut::elements) independently of the maps, and must
+/// both end up with as many rows as the sum of the lengths of the maps.
+///
+/// Rows should be appended with [`push`](MapVectorBatchMut::push) and
+/// [`push_null`](ColumnVectorBatchMut::push_null), which write the offsets of the
+/// maps; rows appended through a [`BorrowedColumnVectorBatchMut`] do not.
+///
+/// It is constructed through [`BorrowedColumnVectorBatchMut::try_into_maps`]
+pub struct MapVectorBatchMut<'a>(Pin<&'a mut ffi::MapVectorBatch>);
+
+impl_debug!(MapVectorBatchMut<'a>, ffi::MapVectorBatch_toString);
+
+impl ColumnVectorBatchMut for MapVectorBatchMut<'_> {
+    fn inner(&self) -> &ffi::ColumnVectorBatch {
+        ffi::MapVectorBatch_into_ColumnVectorBatch(&self.0)
+    }
+
+    fn inner_mut(&mut self) -> Pin<&mut ffi::ColumnVectorBatch> {
+        ffi::MapVectorBatchMut_into_ColumnVectorBatch(self.0.as_mut())
+    }
+
+    /// Appends a null map, which has no entries
+    fn push_null(&mut self) {
+        let index = push_row(self.inner_mut());
+        self.set_null(index);
+        self.set_length(index, 0)
+    }
+}
+
+impl MapVectorBatchMut<'_> {
+    /// The flat vector of all keys of all maps
+    pub fn keys(&mut self) -> BorrowedColumnVectorBatchMut<'_> {
+        child_mut(ffi::MapVectorBatch_get_keys(&self.0))
+    }
+
+    /// The flat vector of all values of all maps
+    pub fn elements(&mut self) -> BorrowedColumnVectorBatchMut<'_> {
+        child_mut(ffi::MapVectorBatch_get_elements(&self.0))
+    }
+
+    /// Appends a map which is not null, made of the next `length` rows of
+    /// [`keys`](MapVectorBatchMut::keys) and [`elements`](MapVectorBatchMut::elements)
+    pub fn push(&mut self, length: u64) {
+        let index = push_row(self.inner_mut());
+        set_not_null_flag(self.inner_mut(), index, true);
+        self.set_length(index, length)
+    }
+
+    fn set_length(&mut self, index: u64, length: u64) {
+        let offsets = ffi::MapVectorBatch_get_offsets(&self.0).checked_data(index + 2) as *mut i64;
+        // This is safe because push_row() grew the offsets along with the batch
+        unsafe { write_length(offsets, index, length) }
+    }
+}
+
+unsafe impl Send for MapVectorBatchMut<'_> {}
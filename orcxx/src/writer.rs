@@ -0,0 +1,255 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Low-level column-oriented writer for ORC files.
+//!
+//! [`Writer`] writes [`OwnedColumnVectorBatch`]es, which are allocated with
+//! [`Writer::row_batch`] and filled through [`OwnedColumnVectorBatch::borrow_mut`],
+//! or read from other files by a [`RowReader`](::reader::RowReader) with the same
+//! type.
+//!
+//! # Example
+//!
+//! Copying a file:
+//!
+//! ```
+//! extern crate tempfile;
+//! # extern crate orcxx;
+//!
+//! use orcxx::reader::{InputStream, Reader, RowReaderOptions};
+//! use orcxx::writer::{OutputStream, Writer, WriterOptions};
+//!
+//! let input_stream = InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+//!     .expect("Could not open .orc");
+//! let reader = Reader::new(input_stream).expect("Could not read .orc");
+//! let mut row_reader = reader.row_reader(&RowReaderOptions::default()).unwrap();
+//!
+//! let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+//! let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+//! let mut writer = Writer::new(output_stream, &reader.kind(), &WriterOptions::default())
+//!     .expect("Could not create writer");
+//!
+//! let mut batch = row_reader.row_batch(1024);
+//! while row_reader.read_into(&mut batch) {
+//!     writer.write_batch(&mut batch).expect("Could not write batch");
+//! }
+//! writer.close().expect("Could not close writer");
+//!
+//! let input_stream = InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+//! assert_eq!(Reader::new(input_stream).unwrap().row_count(), 2);
+//! ```
+
+use cxx::{let_cxx_string, UniquePtr};
+
+use errors::{OrcError, OrcResult, WriteError};
+use kind;
+use vector::OwnedColumnVectorBatch;
+
+#[cxx::bridge]
+pub(crate) mod ffi {
+    #[namespace = "orcxx_rs::utils"]
+    unsafe extern "C++" {
+        include!("cpp-utils.hh");
+        include!("orc/OrcFile.hh");
+
+        #[rust_name = "WriterOptions_new"]
+        fn construct() -> UniquePtr<WriterOptions>;
+    }
+
+    // Reimport types from other modules
+    #[namespace = "orc"]
+    unsafe extern "C++" {
+        type ColumnVectorBatch = crate::vector::ffi::ColumnVectorBatch;
+        type Type = crate::kind::ffi::Type;
+    }
+
+    #[namespace = "orc"]
+    unsafe extern "C++" {
+        type OutputStream;
+
+        fn writeLocalFile(path: &CxxString) -> Result<UniquePtr<OutputStream>>;
+    }
+
+    #[namespace = "orc"]
+    unsafe extern "C++" {
+        type WriterOptions;
+
+        fn setStripeSize(self: Pin<&mut WriterOptions>, size: u64) -> Pin<&mut WriterOptions>;
+        fn setCompressionBlockSize(
+            self: Pin<&mut WriterOptions>,
+            size: u64,
+        ) -> Pin<&mut WriterOptions>;
+        fn setRowIndexStride(self: Pin<&mut WriterOptions>, stride: u64)
+            -> Pin<&mut WriterOptions>;
+    }
+
+    #[namespace = "orcxx_rs"]
+    unsafe extern "C++" {
+        #[rust_name = "WriterOptions_setCompression"]
+        fn setCompression(options: Pin<&mut WriterOptions>, kind: u8);
+        #[rust_name = "WriterOptions_setColumnsUseBloomFilter"]
+        fn setColumnsUseBloomFilter(options: Pin<&mut WriterOptions>, columns: &[u64]);
+    }
+
+    #[namespace = "orc"]
+    unsafe extern "C++" {
+        type Writer;
+
+        unsafe fn createWriter(
+            type_: &Type,
+            stream: *mut OutputStream,
+            options: &WriterOptions,
+        ) -> Result<UniquePtr<Writer>>;
+
+        fn createRowBatch(&self, size: u64) -> UniquePtr<ColumnVectorBatch>;
+
+        fn add(self: Pin<&mut Writer>, rowsToAdd: Pin<&mut ColumnVectorBatch>) -> Result<()>;
+
+        fn close(self: Pin<&mut Writer>) -> Result<()>;
+    }
+}
+
+/// Compression codec of ORC files, set with [`WriterOptions::compression`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    None = 0,
+    Zlib = 1,
+    Snappy = 2,
+    Lzo = 3,
+    Lz4 = 4,
+    Zstd = 5,
+}
+
+/// Options passed to [`Writer::new`]
+pub struct WriterOptions(UniquePtr<ffi::WriterOptions>);
+
+impl Default for WriterOptions {
+    fn default() -> WriterOptions {
+        WriterOptions(ffi::WriterOptions_new())
+    }
+}
+
+impl WriterOptions {
+    /// Sets the size of stripes (in bytes) the writer aims for
+    pub fn stripe_size(mut self, size: u64) -> WriterOptions {
+        self.0.pin_mut().setStripeSize(size);
+        self
+    }
+
+    /// Sets the size of compression blocks (in bytes)
+    pub fn compression_block_size(mut self, size: u64) -> WriterOptions {
+        self.0.pin_mut().setCompressionBlockSize(size);
+        self
+    }
+
+    /// Sets the number of rows between row index entries, which are used to seek
+    /// within stripes; 0 disables row indexes
+    pub fn row_index_stride(mut self, stride: u64) -> WriterOptions {
+        self.0.pin_mut().setRowIndexStride(stride);
+        self
+    }
+
+    /// Sets the compression codec (zlib by default)
+    pub fn compression(mut self, compression: Compression) -> WriterOptions {
+        ffi::WriterOptions_setCompression(self.0.pin_mut(), compression as u8);
+        self
+    }
+
+    /// Sets the ids of the columns to write bloom filters of, in each row group
+    /// (none by default); they can be read with
+    /// [`Reader::bloom_filters`](::reader::Reader::bloom_filters)
+    pub fn bloom_filter_columns<I: IntoIterator<Item = u64>>(
+        mut self,
+        columns: I,
+    ) -> WriterOptions {
+        let columns: Vec<u64> = columns.into_iter().collect();
+        ffi::WriterOptions_setColumnsUseBloomFilter(self.0.pin_mut(), &columns);
+        self
+    }
+}
+
+unsafe impl Send for WriterOptions {}
+unsafe impl Sync for WriterOptions {}
+
+/// Output for [`Writer::new`]
+pub struct OutputStream(UniquePtr<ffi::OutputStream>);
+
+impl OutputStream {
+    /// Creates a file (or truncates it if it exists)
+    pub fn to_local_file(file_name: &str) -> OrcResult<OutputStream> {
+        let_cxx_string!(cxx_file_name = file_name);
+        ffi::writeLocalFile(&cxx_file_name)
+            .map(OutputStream)
+            .map_err(OrcError)
+    }
+}
+
+unsafe impl Send for OutputStream {}
+
+/// Writes ORC files from column vector batches.
+///
+/// [`Writer::close`] must be called after the last batch, to write the file's
+/// footer; otherwise the file is not readable.
+pub struct Writer {
+    // Declared before output_stream so it is dropped first, as it points to it
+    writer: UniquePtr<ffi::Writer>,
+    _output_stream: OutputStream,
+    kind: kind::Kind,
+}
+
+impl Writer {
+    /// Returns a writer of rows of the given type
+    pub fn new(
+        mut output_stream: OutputStream,
+        kind: &kind::Kind,
+        options: &WriterOptions,
+    ) -> OrcResult<Writer> {
+        let_cxx_string!(type_string = kind.to_type_string());
+        let orc_type = kind::ffi::buildTypeFromString(&type_string).map_err(OrcError)?;
+        // Safe because the output stream outlives the writer (see field order)
+        let writer = unsafe {
+            ffi::createWriter(
+                &orc_type,
+                output_stream.0.pin_mut().get_unchecked_mut(),
+                &options.0,
+            )
+        }
+        .map_err(OrcError)?;
+        Ok(Writer {
+            writer,
+            _output_stream: output_stream,
+            kind: kind.clone(),
+        })
+    }
+
+    /// Returns an empty batch of the writer's type, which can hold up to `size` rows
+    pub fn row_batch(&self, size: u64) -> OwnedColumnVectorBatch {
+        OwnedColumnVectorBatch(self.writer.createRowBatch(size), None)
+    }
+
+    /// Writes all rows of the batch, which must have the writer's type.
+    ///
+    /// The batch is checked before it is passed to the C++ library, which would
+    /// otherwise read out of bounds: it must have the writer's type, fields of
+    /// structures must have as many rows as the structure, offsets of lists and
+    /// maps must not decrease or exceed the number of rows of their elements, etc.
+    pub fn write_batch(&mut self, batch: &mut OwnedColumnVectorBatch) -> Result<(), WriteError> {
+        batch
+            .borrow()
+            .check_consistency(&self.kind, 0, "")
+            .map_err(WriteError::InvalidBatch)?;
+        self.writer
+            .pin_mut()
+            .add(batch.0.pin_mut())
+            .map_err(|e| WriteError::OrcError(OrcError(e)))
+    }
+
+    /// Writes the end of the file, and flushes it
+    pub fn close(mut self) -> OrcResult<()> {
+        self.writer.pin_mut().close().map_err(OrcError)
+    }
+}
+
+unsafe impl Send for Writer {}
@@ -0,0 +1,56 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(not(feature = "cardinality"))]
+compile_error!("Feature 'cardinality' must be enabled for this test.");
+
+extern crate orcxx;
+
+use orcxx::cardinality::{estimate_cardinalities, HyperLogLog};
+use orcxx::reader;
+
+#[test]
+fn test1() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let options =
+        reader::RowReaderOptions::default().include_names(["boolean1", "int1", "string1", "list"]);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+
+    assert_eq!(
+        estimate_cardinalities(&mut row_reader, 1, 14),
+        vec![
+            ("int1".to_owned(), 1),
+            ("string1".to_owned(), 2),
+            ("list._elem.int1".to_owned(), 5),
+            ("list._elem.string1".to_owned(), 5),
+        ]
+    );
+}
+
+#[test]
+fn accuracy() {
+    let mut hll = HyperLogLog::new(14);
+    let mut other_hll = HyperLogLog::new(14);
+    for i in 0u64..100_000 {
+        hll.insert(&i.to_le_bytes());
+        // Duplicates should not be counted
+        hll.insert(&(i / 2).to_le_bytes());
+        other_hll.insert(&(i + 50_000).to_le_bytes());
+    }
+    let estimate = hll.estimate();
+    assert!((98_000. ..102_000.).contains(&estimate), "{}", estimate);
+
+    hll.merge(&other_hll);
+    let estimate = hll.estimate();
+    assert!((147_000. ..153_000.).contains(&estimate), "{}", estimate);
+}
+
+#[test]
+#[should_panic(expected = "different precisions")]
+fn merge_different_precisions() {
+    HyperLogLog::new(10).merge(&HyperLogLog::new(12));
+}
@@ -0,0 +1,54 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+
+use orcxx::conformance::ConformanceOptions;
+use orcxx::reader;
+
+fn reader(orc_path: &str) -> reader::Reader {
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+#[test]
+fn conformant_files() {
+    for orc_path in [
+        "orc/examples/TestOrcFile.test1.orc",
+        "orc/examples/TestOrcFile.testStringAndBinaryStatistics.orc",
+        "orc/examples/TestOrcFile.testSeek.orc",
+        "orc/examples/nulls-at-end-snappy.orc",
+    ] {
+        let reader = reader(orc_path);
+        let report = reader
+            .conformance_report(&ConformanceOptions::default().batch_size(100))
+            .unwrap();
+        assert_eq!(
+            report.checked_stripes,
+            (0..reader.stripes().count() as u64).collect::<Vec<_>>()
+        );
+        assert!(
+            report.is_conformant(),
+            "{}: {:?}",
+            orc_path,
+            report.violations
+        );
+    }
+}
+
+#[test]
+fn sampled_stripes() {
+    let reader = reader("orc/examples/TestOrcFile.testSeek.orc");
+    let num_stripes = reader.stripes().count() as u64;
+    assert!(num_stripes > 1);
+    let report = reader
+        .conformance_report(&ConformanceOptions::default().stripe_step(2))
+        .unwrap();
+    assert_eq!(
+        report.checked_stripes,
+        (0..num_stripes).step_by(2).collect::<Vec<_>>()
+    );
+    assert!(report.is_conformant(), "{:?}", report.violations);
+}
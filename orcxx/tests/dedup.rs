@@ -0,0 +1,71 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+
+use orcxx::dedup::DedupRowIterator;
+
+#[test]
+fn window() {
+    let rows = vec![1, 2, 1, 2, 3, 3, 4, 1, 4, 5, 2];
+    let deduplicated: Vec<_> =
+        DedupRowIterator::with_window(rows.into_iter(), 3, |&row| row).collect();
+    // 1 was evicted by 2, 3, and 4 before it appeared again, but 4 was not
+    assert_eq!(deduplicated, vec![1, 2, 3, 4, 1, 5, 2]);
+}
+
+#[test]
+fn window_of_one() {
+    let rows = vec![1, 1, 2, 1, 1];
+    let deduplicated: Vec<_> =
+        DedupRowIterator::with_window(rows.into_iter(), 1, |&row| row).collect();
+    assert_eq!(deduplicated, vec![1, 2, 1]);
+}
+
+#[test]
+#[should_panic(expected = "window_size must be positive")]
+fn empty_window() {
+    DedupRowIterator::with_window(Vec::<u64>::new().into_iter(), 0, |&row| row);
+}
+
+#[test]
+fn sorted() {
+    let rows = vec![
+        ("a", 1),
+        ("a", 2),
+        ("b", 3),
+        ("c", 4),
+        ("c", 5),
+        ("c", 6),
+        ("a", 7),
+    ];
+    let deduplicated: Vec<_> = DedupRowIterator::sorted(rows.into_iter(), |row| row.0).collect();
+    assert_eq!(deduplicated, vec![("a", 1), ("b", 3), ("c", 4), ("a", 7)]);
+}
+
+#[test]
+fn owned_keys() {
+    let rows = vec!["foo".to_owned(), "bar".to_owned(), "foo".to_owned()];
+    let deduplicated: Vec<_> =
+        DedupRowIterator::with_window(rows.into_iter(), 10, |row| row.clone()).collect();
+    assert_eq!(deduplicated, vec!["foo".to_owned(), "bar".to_owned()]);
+}
+
+#[test]
+fn size_hint() {
+    let rows = vec![1, 1, 1];
+    let mut deduplicated = DedupRowIterator::sorted(rows.clone().into_iter(), |&row| row);
+    assert_eq!(deduplicated.size_hint(), (1, Some(3)));
+    assert_eq!(deduplicated.next(), Some(1));
+    // The tail is only made of duplicates
+    assert_eq!(deduplicated.size_hint(), (0, Some(2)));
+    assert_eq!(deduplicated.next(), None);
+
+    let mut deduplicated = DedupRowIterator::with_window(rows.into_iter(), 2, |&row| row);
+    assert_eq!(deduplicated.size_hint(), (1, Some(3)));
+    assert_eq!(deduplicated.next(), Some(1));
+    assert_eq!(deduplicated.size_hint(), (0, Some(2)));
+    assert_eq!(deduplicated.next(), None);
+}
@@ -0,0 +1,107 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(not(feature = "json"))]
+compile_error!("Feature 'json' must be enabled for this test.");
+
+extern crate orcxx;
+extern crate pretty_assertions;
+
+use pretty_assertions::assert_eq;
+
+use orcxx::diff::{compare, CompareOptions};
+use orcxx::kind::KindDifference;
+use orcxx::reader;
+
+fn open(orc_path: &str) -> reader::Reader {
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+#[test]
+fn identical() {
+    let comparison = compare(
+        &open("orc/examples/TestOrcFile.test1.orc"),
+        &open("orc/examples/TestOrcFile.test1.orc"),
+        &CompareOptions::default().batch_size(1),
+    )
+    .unwrap();
+    assert!(comparison.is_equal(), "{:?}", comparison);
+}
+
+#[test]
+fn different_rows() {
+    let a = open("orc/examples/TestOrcFile.test1.orc");
+    let b = open("orc/examples/TestOrcFile.testStringAndBinaryStatistics.orc");
+    let comparison = compare(&a, &b, &CompareOptions::default()).unwrap();
+
+    assert!(!comparison.is_equal());
+    assert_eq!(comparison.row_count_a, 2);
+    assert_eq!(comparison.row_count_b, 4);
+    assert!(comparison
+        .schema_differences
+        .contains(&KindDifference::OnlyInFirst("boolean1".to_owned())));
+    assert_eq!(
+        comparison
+            .row_differences
+            .iter()
+            .map(|difference| (
+                difference.key.as_u64(),
+                difference.a.is_some(),
+                difference.b.is_some()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (Some(0), true, true),
+            (Some(1), true, true),
+            (Some(2), false, true),
+            (Some(3), false, true),
+        ]
+    );
+
+    let comparison = compare(&a, &b, &CompareOptions::default().max_row_differences(1)).unwrap();
+    assert_eq!(comparison.row_differences.len(), 1);
+}
+
+#[test]
+fn by_key() {
+    let a = open("orc/examples/TestOrcFile.test1.orc");
+    let comparison = compare(&a, &a, &CompareOptions::default().key("string1")).unwrap();
+    assert!(comparison.is_equal(), "{:?}", comparison);
+
+    let b = open("orc/examples/TestOrcFile.testStringAndBinaryStatistics.orc");
+    assert!(compare(&a, &b, &CompareOptions::default().key("int1")).is_err());
+    let comparison = compare(&a, &b, &CompareOptions::default().key("string1")).unwrap();
+    assert_eq!(
+        comparison
+            .row_differences
+            .iter()
+            .map(|difference| (
+                difference.key.to_string(),
+                difference.a.is_some(),
+                difference.b.is_some()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            ("hi".to_owned(), true, true),
+            ("bye".to_owned(), true, false),
+            ("foo".to_owned(), false, true),
+            ("bar".to_owned(), false, true),
+            ("null".to_owned(), false, true),
+        ]
+    );
+}
+
+#[test]
+fn by_duplicate_key() {
+    // Both rows have int1 = 65536, so only the last row of the second file is kept
+    let a = open("orc/examples/TestOrcFile.test1.orc");
+    let comparison = compare(&a, &a, &CompareOptions::default().key("int1")).unwrap();
+    assert_eq!(comparison.row_differences.len(), 1);
+    let difference = &comparison.row_differences[0];
+    assert_eq!(difference.key.as_i64(), Some(65536));
+    assert_eq!(difference.a.as_ref().unwrap()["boolean1"], false);
+    assert_eq!(difference.b.as_ref().unwrap()["boolean1"], true);
+}
@@ -0,0 +1,124 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(not(feature = "digest"))]
+compile_error!("Feature 'digest' must be enabled for this test.");
+
+extern crate orcxx;
+extern crate tempfile;
+
+use orcxx::digest::digest_columns;
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx::vector::ColumnVectorBatchMut;
+use orcxx::writer::{OutputStream, Writer, WriterOptions};
+
+fn digest(orc_path: &str, columns: &[&str], batch_size: u64) -> Vec<(String, u64)> {
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let options = reader::RowReaderOptions::default().include_names(columns);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    digest_columns(&mut row_reader, batch_size)
+}
+
+/// Asserts hashes do not depend on the batch size
+#[test]
+fn batch_size_independent() {
+    let columns = ["boolean1", "int1", "string1", "list", "map"];
+    let expected = digest("orc/examples/TestOrcFile.test1.orc", &columns, 1024);
+    assert_eq!(
+        expected
+            .iter()
+            .map(|(column, _)| column.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "",
+            "boolean1",
+            "int1",
+            "string1",
+            "list",
+            "list._elem",
+            "list._elem.int1",
+            "list._elem.string1",
+            "map",
+            "map._key",
+            "map._value",
+            "map._value.int1",
+            "map._value.string1",
+        ]
+    );
+    for batch_size in [1, 2, 3] {
+        assert_eq!(
+            digest("orc/examples/TestOrcFile.test1.orc", &columns, batch_size),
+            expected,
+            "batch_size = {batch_size}"
+        );
+    }
+}
+
+/// Asserts the hash of a column does not depend on which other columns are selected
+#[test]
+fn projection_independent() {
+    let hashes = digest("orc/examples/TestOrcFile.test1.orc", &["int1"], 1024);
+    let more_hashes = digest(
+        "orc/examples/TestOrcFile.test1.orc",
+        &["int1", "string1"],
+        1024,
+    );
+    assert_eq!(hashes[1].0, "int1");
+    assert_eq!(hashes[1], more_hashes[1]);
+    // The root struct's hash only covers its nulls, not its children
+    assert_eq!(hashes[0], more_hashes[0]);
+}
+
+/// Writes a file with a single `decimal(38,2)` column holding the given mantissas
+fn write_decimals(mantissas: &[Option<i128>]) -> tempfile::TempPath {
+    let kind = Kind::Struct(vec![(
+        "d".to_owned(),
+        Kind::Decimal {
+            precision: 38,
+            scale: 2,
+        },
+    )]);
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+    let mut batch = writer.row_batch(1024);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        for _ in mantissas {
+            structs.push();
+        }
+        let mut decimals = structs
+            .fields()
+            .into_iter()
+            .next()
+            .unwrap()
+            .try_into_decimals128()
+            .unwrap();
+        for &mantissa in mantissas {
+            match mantissa {
+                Some(mantissa) => decimals.push(mantissa),
+                None => decimals.push_null(),
+            }
+        }
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+    path
+}
+
+/// Asserts decimals which do not fit in [`rust_decimal::Decimal`] are hashed
+#[test]
+fn wide_decimals() {
+    let wide = 1i128 << 100;
+    let path = write_decimals(&[Some(wide), None, Some(-wide)]);
+    let hashes = digest(path.to_str().unwrap(), &["d"], 1024);
+    assert_eq!(hashes[1].0, "d");
+
+    let other_path = write_decimals(&[Some(wide + 1), None, Some(-wide)]);
+    let other_hashes = digest(other_path.to_str().unwrap(), &["d"], 1024);
+    assert_ne!(hashes[1], other_hashes[1]);
+}
@@ -0,0 +1,228 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(not(feature = "arrow"))]
+compile_error!("Feature 'arrow' must be enabled for this test.");
+
+extern crate arrow;
+extern crate orcxx;
+extern crate pretty_assertions;
+extern crate tempfile;
+
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, BinaryArray, BooleanArray, Date32Array, Decimal128Array, Float32Array, Float64Array,
+    Int32Array, Int64Array, Int64Builder, Int8Array, ListArray, MapBuilder, MapFieldNames,
+    RecordBatch, StringArray, StringBuilder, StructArray, TimestampNanosecondArray,
+};
+use arrow::buffer::{NullBuffer, OffsetBuffer};
+use arrow::datatypes::{DataType, Field, Fields, Int64Type};
+use pretty_assertions::assert_eq;
+
+use orcxx::from_arrow::{arrow_schema_to_kind, record_batch_to_vector_batch, FromArrowError};
+use orcxx::kind::Kind;
+use orcxx::reader::{InputStream, Reader, RowReaderOptions};
+use orcxx::to_arrow::vector_batch_to_record_batch;
+use orcxx::writer::{OutputStream, Writer, WriterOptions};
+
+fn record_batch() -> RecordBatch {
+    let struct_fields = Fields::from(vec![
+        Field::new("a", DataType::Int32, true),
+        Field::new("b", DataType::Utf8, true),
+    ]);
+    let structs = StructArray::new(
+        struct_fields,
+        vec![
+            Arc::new(Int32Array::from(vec![Some(1), None, Some(3)])) as ArrayRef,
+            Arc::new(StringArray::from(vec![Some("x"), None, None])) as ArrayRef,
+        ],
+        Some(NullBuffer::from(vec![true, false, true])),
+    );
+    let lists = ListArray::from_iter_primitive::<Int64Type, _, _>(vec![
+        Some(vec![Some(1), None]),
+        None,
+        Some(vec![]),
+    ]);
+    // Same field names as the maps returned by to_arrow
+    let mut maps = MapBuilder::new(
+        Some(MapFieldNames {
+            entry: "entries".to_owned(),
+            key: "key".to_owned(),
+            value: "value".to_owned(),
+        }),
+        StringBuilder::new(),
+        Int64Builder::new(),
+    );
+    maps.keys().append_value("a");
+    maps.values().append_null();
+    maps.keys().append_value("b");
+    maps.values().append_value(2);
+    maps.append(true).unwrap();
+    maps.append(true).unwrap();
+    maps.append(false).unwrap();
+    let maps = maps.finish();
+    // Nullable like the fields returned by to_arrow
+    RecordBatch::try_from_iter_with_nullable([
+        (
+            "boolean1",
+            Arc::new(BooleanArray::from(vec![Some(true), Some(false), None])) as ArrayRef,
+            true,
+        ),
+        (
+            "byte1",
+            Arc::new(Int8Array::from(vec![Some(1), Some(-100), None])) as ArrayRef,
+            true,
+        ),
+        (
+            "long1",
+            Arc::new(Int64Array::from(vec![i64::MIN, 0, i64::MAX])) as ArrayRef,
+            true,
+        ),
+        (
+            "float1",
+            Arc::new(Float32Array::from(vec![Some(1.5), None, Some(-2.)])) as ArrayRef,
+            true,
+        ),
+        (
+            "double1",
+            Arc::new(Float64Array::from(vec![-15., -5., 0.5])) as ArrayRef,
+            true,
+        ),
+        (
+            "bytes1",
+            Arc::new(BinaryArray::from(vec![
+                Some(&b"\x00\x01"[..]),
+                None,
+                Some(&b""[..]),
+            ])) as ArrayRef,
+            true,
+        ),
+        (
+            "string1",
+            Arc::new(StringArray::from(vec![Some("hi"), Some("bye"), None])) as ArrayRef,
+            true,
+        ),
+        (
+            "date1",
+            Arc::new(Date32Array::from(vec![Some(-1), None, Some(19000)])) as ArrayRef,
+            true,
+        ),
+        (
+            "timestamp1",
+            Arc::new(TimestampNanosecondArray::from(vec![
+                Some(-1_500_000_000),
+                None,
+                Some(1),
+            ])) as ArrayRef,
+            true,
+        ),
+        (
+            "timestamp2",
+            Arc::new(
+                TimestampNanosecondArray::from(vec![
+                    Some(0),
+                    Some(1_700_000_000_123_456_789),
+                    None,
+                ])
+                .with_timezone("UTC"),
+            ) as ArrayRef,
+            true,
+        ),
+        ("middle", Arc::new(structs) as ArrayRef, true),
+        ("list", Arc::new(lists) as ArrayRef, true),
+        ("map", Arc::new(maps) as ArrayRef, true),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn kind() {
+    assert_eq!(
+        arrow_schema_to_kind(&record_batch().schema()).unwrap(),
+        Kind::new(
+            "struct<boolean1:boolean,byte1:tinyint,long1:bigint,float1:float,double1:double,\
+             bytes1:binary,string1:string,date1:date,timestamp1:timestamp,\
+             timestamp2:timestamp with local time zone,middle:struct<a:int,b:string>,\
+             list:array<bigint>,map:map<string,bigint>>"
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn roundtrip() {
+    let record_batch = record_batch();
+    let kind = arrow_schema_to_kind(&record_batch.schema()).unwrap();
+
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+    let mut batch = record_batch_to_vector_batch(&record_batch).unwrap();
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    let input_stream = InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    let reader = Reader::new(input_stream).unwrap();
+    assert_eq!(reader.kind(), kind);
+    let mut row_reader = reader.row_reader(&RowReaderOptions::default()).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+
+    let read_record_batch = vector_batch_to_record_batch(batch.borrow(), &kind).unwrap();
+    assert_eq!(read_record_batch, record_batch);
+}
+
+/// Asserts items of null lists are not written, as ORC lists cannot have any
+#[test]
+fn null_lists_with_items() {
+    let lists = ListArray::new(
+        Arc::new(Field::new("item", DataType::Int64, true)),
+        OffsetBuffer::new(vec![0, 2, 3].into()),
+        Arc::new(Int64Array::from(vec![1, 2, 3])),
+        Some(NullBuffer::from(vec![false, true])),
+    );
+    let record_batch =
+        RecordBatch::try_from_iter_with_nullable([("list", Arc::new(lists) as ArrayRef, true)])
+            .unwrap();
+    let kind = arrow_schema_to_kind(&record_batch.schema()).unwrap();
+
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+    let mut batch = record_batch_to_vector_batch(&record_batch).unwrap();
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    let input_stream = InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    let reader = Reader::new(input_stream).unwrap();
+    let mut row_reader = reader.row_reader(&RowReaderOptions::default()).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+
+    let read_record_batch = vector_batch_to_record_batch(batch.borrow(), &kind).unwrap();
+    let expected: ArrayRef = Arc::new(ListArray::from_iter_primitive::<Int64Type, _, _>(vec![
+        None,
+        Some(vec![Some(3)]),
+    ]));
+    assert_eq!(read_record_batch.column(0), &expected);
+}
+
+#[test]
+fn unsupported_type() {
+    let record_batch = RecordBatch::try_from_iter([(
+        "decimal1",
+        Arc::new(
+            Decimal128Array::from(vec![0])
+                .with_precision_and_scale(10, 2)
+                .unwrap(),
+        ) as ArrayRef,
+    )])
+    .unwrap();
+    assert!(matches!(
+        record_batch_to_vector_batch(&record_batch),
+        Err(FromArrowError::UnsupportedDataType(_))
+    ));
+}
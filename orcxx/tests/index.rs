@@ -0,0 +1,107 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate pretty_assertions;
+
+use pretty_assertions::assert_eq;
+
+use orcxx::index::{self, ColumnRange, IndexError, OrcxxIndex, StripeIndex};
+use orcxx::reader;
+
+#[test]
+fn build_test1() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let index = index::build(orc_path).unwrap();
+
+    let input_stream = reader::InputStream::from_local_file(orc_path).unwrap();
+    let reader = reader::Reader::new(input_stream).unwrap();
+    assert_eq!(index.schema_fingerprint, reader.schema_fingerprint());
+    assert_eq!(index.row_count, 2);
+
+    assert_eq!(index.stripes.len(), 1);
+    let stripe = &index.stripes[0];
+    assert_eq!(stripe.offset, 3); // Right after the magic bytes
+    assert!(stripe.length > 0);
+    assert_eq!(stripe.rows(), 0..2);
+    assert_eq!(stripe.columns.len(), 24);
+    assert_eq!(stripe.column_range(1), None); // boolean1
+    assert_eq!(
+        stripe.column_range(4), // int1
+        Some(&ColumnRange::Integer {
+            min: 65536,
+            max: 65536
+        })
+    );
+    assert_eq!(
+        stripe.column_range(7), // double1
+        Some(&ColumnRange::Double {
+            min: -15.,
+            max: -5.
+        })
+    );
+    assert_eq!(
+        stripe.column_range(9), // string1
+        Some(&ColumnRange::String {
+            min: b"bye".to_vec(),
+            max: b"hi".to_vec()
+        })
+    );
+    assert_eq!(stripe.column_range(100), None);
+}
+
+#[test]
+fn serialization() {
+    let index = index::build("orc/examples/TestOrcFile.testStripeLevelStats.orc").unwrap();
+    let mut serialized = Vec::new();
+    index.write_to(&mut serialized).unwrap();
+    assert_eq!(OrcxxIndex::read_from(&serialized[..]).unwrap(), index);
+
+    // Truncated
+    assert!(matches!(
+        OrcxxIndex::read_from(&serialized[..serialized.len() - 1]),
+        Err(IndexError::Io(_))
+    ));
+
+    // Not an index
+    serialized[0] = b'X';
+    assert!(matches!(
+        OrcxxIndex::read_from(&serialized[..]),
+        Err(IndexError::InvalidFormat(_))
+    ));
+}
+
+#[test]
+fn stripes() {
+    let stripe = |first_row, row_count| StripeIndex {
+        offset: 0,
+        length: 0,
+        first_row,
+        row_count,
+        columns: Vec::new(),
+    };
+    let index = OrcxxIndex {
+        schema_fingerprint: 0,
+        row_count: 40,
+        stripes: vec![
+            stripe(0, 10),
+            stripe(10, 10),
+            stripe(20, 10),
+            stripe(30, 10),
+        ],
+    };
+    assert_eq!(index.stripe_of_row(0), Some(&index.stripes[0]));
+    assert_eq!(index.stripe_of_row(19), Some(&index.stripes[1]));
+    assert_eq!(index.stripe_of_row(20), Some(&index.stripes[2]));
+    assert_eq!(index.stripe_of_row(40), None);
+    assert_eq!(index.row_ranges(), vec![0..40]);
+
+    let index = index.retain_stripes(|stripe| stripe.first_row != 20);
+    assert_eq!(index.stripe_of_row(19), Some(&index.stripes[1]));
+    assert_eq!(index.stripe_of_row(25), None);
+    assert_eq!(index.stripe_of_row(30), Some(&index.stripes[2]));
+    assert_eq!(index.row_ranges(), vec![0..20, 30..40]);
+    assert_eq!(index.row_count, 40);
+}
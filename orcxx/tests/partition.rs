@@ -0,0 +1,128 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(not(feature = "json"))]
+compile_error!("Feature 'json' must be enabled for this test.");
+
+extern crate json;
+extern crate orcxx;
+extern crate pretty_assertions;
+
+use pretty_assertions::assert_eq;
+
+use orcxx::partition::{partition_to_ndjson, PartitionError};
+use orcxx::reader;
+
+fn row_reader(orc_path: &str, columns: &[&str]) -> reader::RowReader {
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let options = reader::RowReaderOptions::default().include_names(columns.iter().copied());
+    reader.row_reader(&options).unwrap()
+}
+
+type Partitions = Vec<(Option<String>, Vec<json::JsonValue>)>;
+
+/// Returns the partitions and their rows, parsed back from JSON
+fn partition(orc_path: &str, columns: &[&str], column: &str) -> Result<Partitions, PartitionError> {
+    let mut row_reader = row_reader(orc_path, columns);
+    let mut opened = Vec::new();
+    let partitions = partition_to_ndjson(&mut row_reader, 1, column, |key| {
+        opened.push(key.map(ToOwned::to_owned));
+        Ok(Vec::new())
+    })?;
+    assert_eq!(
+        opened,
+        partitions
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>()
+    );
+    Ok(partitions
+        .into_iter()
+        .map(|(key, ndjson)| {
+            let ndjson = String::from_utf8(ndjson).unwrap();
+            let rows = ndjson.lines().map(|line| json::parse(line).unwrap());
+            (key, rows.collect())
+        })
+        .collect())
+}
+
+#[test]
+fn partition_by_string() {
+    let partitions = partition(
+        "orc/examples/TestOrcFile.test1.orc",
+        &["int1", "string1"],
+        "string1",
+    )
+    .unwrap();
+    assert_eq!(
+        partitions,
+        vec![
+            (
+                Some("hi".to_owned()),
+                vec![json::object! {"int1" => 65536, "string1" => "hi"}]
+            ),
+            (
+                Some("bye".to_owned()),
+                vec![json::object! {"int1" => 65536, "string1" => "bye"}]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn partition_by_int() {
+    let partitions = partition(
+        "orc/examples/TestOrcFile.test1.orc",
+        &["int1", "string1"],
+        "int1",
+    )
+    .unwrap();
+    assert_eq!(
+        partitions,
+        vec![(
+            Some("65536".to_owned()),
+            vec![
+                json::object! {"int1" => 65536, "string1" => "hi"},
+                json::object! {"int1" => 65536, "string1" => "bye"},
+            ]
+        )]
+    );
+}
+
+#[test]
+fn partition_by_nullable() {
+    let partitions = partition(
+        "orc/examples/TestOrcFile.testStringAndBinaryStatistics.orc",
+        &["string1"],
+        "string1",
+    )
+    .unwrap();
+    assert_eq!(partitions.len(), 4);
+    assert_eq!(
+        partitions[2],
+        (None, vec![json::object! {"string1" => json::Null}])
+    );
+}
+
+#[test]
+fn unknown_column() {
+    match partition(
+        "orc/examples/TestOrcFile.test1.orc",
+        &["int1", "string1"],
+        "long1",
+    ) {
+        Err(PartitionError::UnknownColumn(column)) => assert_eq!(column, "long1"),
+        r => panic!("{:?}", r),
+    }
+}
+
+#[test]
+fn unsupported_kind() {
+    match partition("orc/examples/TestOrcFile.test1.orc", &["list"], "list") {
+        Err(PartitionError::UnsupportedKind { column, .. }) => assert_eq!(column, "list"),
+        r => panic!("{:?}", r),
+    }
+}
@@ -91,7 +91,6 @@ fn emptyFile() {
     test_apache_file!("TestOrcFile.emptyFile");
 }
 #[test]
-#[ignore] // Differs on representation of some Decimals
 fn metaData() {
     test_apache_file!("TestOrcFile.metaData");
 }
@@ -158,7 +157,6 @@ fn testLzo() {
     test_apache_file!("TestVectorOrcFile.testLzo");
 }
 #[test]
-#[ignore] // Differs on representation of some Decimals
 fn decimal() {
     test_apache_file!("decimal");
 }
@@ -168,7 +166,7 @@ fn zlib() {
     test_apache_file!("demo-12-zlib");
 }
 #[test]
-#[ignore] // Overflows the JSON library
+#[ignore] // Overflows the JSON library; tested with serde_json in to_serde_json.rs
 fn nulls_at_end_snappy() {
     test_apache_file!("nulls-at-end-snappy");
 }
@@ -181,22 +179,18 @@ fn orc_index_int_string() {
     test_apache_file!("orc_index_int_string");
 }
 #[test]
-#[ignore] // Differs on representation of some Decimals
 fn orc_split_elim() {
     test_apache_file!("orc_split_elim");
 }
 #[test]
-#[ignore] // Differs on representation of some Decimals
 fn orc_split_elim_cpp() {
     test_apache_file!("orc_split_elim_cpp");
 }
 #[test]
-#[ignore] // Differs on representation of some Decimals
 fn orc_split_elim_new() {
     test_apache_file!("orc_split_elim_new");
 }
 #[test]
-#[ignore] // Differs on representation of some Decimals
 fn over1k_bloom() {
     test_apache_file!("over1k_bloom");
 }
@@ -8,6 +8,7 @@ extern crate pretty_assertions;
 extern crate tempfile;
 
 use std::io::Write;
+use std::num::NonZeroU64;
 
 use pretty_assertions::assert_eq;
 
@@ -19,6 +20,20 @@ use orcxx::*;
 fn nonexistent_file() {
     let stream_res = reader::InputStream::from_local_file("orc/examples/nonexistent.orc");
     assert!(matches!(stream_res, Err(errors::OrcError(_))));
+    let e = stream_res.err().unwrap();
+    assert_eq!(e.kind(), errors::OrcErrorKind::Io, "{:?}", e);
+    assert!(e.what().contains("nonexistent.orc"), "{}", e.what());
+}
+
+/// Asserts selecting a column which is not in the file returns a distinct Error
+#[test]
+fn missing_column() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not open");
+    let reader = reader::Reader::new(input_stream).expect("Could not read");
+    let options = reader::RowReaderOptions::default().include_names(["int1", "nonexistent"]);
+    let e = reader.row_reader(&options).err().unwrap();
+    assert_eq!(e.kind(), errors::OrcErrorKind::MissingColumn, "{:?}", e);
 }
 
 /// Asserts reading an empty file returns an Error
@@ -28,7 +43,7 @@ fn empty_file() {
     let stream_res = reader::InputStream::from_local_file(&temp_file.path().display().to_string())
         .expect("could not open local file");
     let reader = reader::Reader::new(stream_res);
-    assert!(matches!(reader, Err(errors::OrcError(_))))
+    assert!(matches!(reader, Err(errors::ReaderError::NotAnOrcFile)))
 }
 
 /// Asserts reading gibberish returns an Error
@@ -40,7 +55,28 @@ fn nonorc_file() {
     let stream_res = reader::InputStream::from_local_file(&temp_file.path().display().to_string())
         .expect("could not open local file");
     let reader = reader::Reader::new(stream_res);
-    assert!(matches!(reader, Err(errors::OrcError(_))))
+    assert!(matches!(reader, Err(errors::ReaderError::NotAnOrcFile)))
+}
+
+/// Asserts reading a truncated ORC file returns a distinct Error
+#[test]
+fn truncated_file() {
+    let contents = std::fs::read("orc/examples/TestOrcFile.test1.orc").unwrap();
+    let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+    temp_file
+        .write_all(&contents[0..contents.len() / 2])
+        .unwrap();
+    temp_file.flush().unwrap();
+    let stream_res = reader::InputStream::from_local_file(&temp_file.path().display().to_string())
+        .expect("could not open local file");
+    let reader = reader::Reader::new(stream_res);
+    match reader {
+        Err(errors::ReaderError::Corrupted(e)) => {
+            assert_eq!(e.kind(), errors::OrcErrorKind::Parse, "{:?}", e)
+        }
+        Err(e) => panic!("Unexpected error: {}", e),
+        Ok(_) => panic!("Unexpected success"),
+    }
 }
 
 #[test]
@@ -64,6 +100,178 @@ fn select_nonexistent_column() {
     ));
 }
 
+#[test]
+fn selected_columns() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    let options = reader::RowReaderOptions::default().include_names(vec!["byte1", "list"]);
+    let row_reader = reader.row_reader(&options).unwrap();
+    assert_eq!(
+        row_reader.selected_columns(),
+        vec![
+            (0, "".to_owned()),
+            (2, "byte1".to_owned()),
+            (15, "list".to_owned()),
+            (16, "list._elem".to_owned()),
+            (17, "list._elem.int1".to_owned()),
+            (18, "list._elem.string1".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn selected_column_tree() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    let options = reader::RowReaderOptions::default().include_names(vec!["byte1", "list"]);
+    let row_reader = reader.row_reader(&options).unwrap();
+    let item_kind = kind::Kind::new("struct<int1:int,string1:string>").unwrap();
+    assert_eq!(
+        row_reader.selected_column_tree(),
+        reader::SelectedColumn {
+            id: 0,
+            kind: row_reader.selected_kind(),
+            children: vec![
+                reader::SelectedColumn {
+                    id: 2,
+                    kind: kind::Kind::Byte,
+                    children: vec![],
+                },
+                reader::SelectedColumn {
+                    id: 15,
+                    kind: kind::Kind::List(Box::new(item_kind.clone())),
+                    children: vec![reader::SelectedColumn {
+                        id: 16,
+                        kind: item_kind,
+                        children: vec![
+                            reader::SelectedColumn {
+                                id: 17,
+                                kind: kind::Kind::Int,
+                                children: vec![],
+                            },
+                            reader::SelectedColumn {
+                                id: 18,
+                                kind: kind::Kind::String,
+                                children: vec![],
+                            },
+                        ],
+                    }],
+                },
+            ],
+        }
+    );
+}
+
+#[test]
+fn columns() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    let columns = reader.columns();
+    assert_eq!(columns.len(), 24);
+    assert_eq!(columns[0], (0, "".to_owned()));
+    assert_eq!(columns[9], (9, "string1".to_owned()));
+    assert_eq!(
+        columns[19..],
+        [
+            (19, "map".to_owned()),
+            (20, "map._key".to_owned()),
+            (21, "map._value".to_owned()),
+            (22, "map._value.int1".to_owned()),
+            (23, "map._value.string1".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn include_indexes() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    // byte1 and string1
+    let options = reader::RowReaderOptions::default().include_indexes([1, 8]);
+    let row_reader = reader.row_reader(&options).unwrap();
+    assert_eq!(
+        row_reader.selected_kind(),
+        kind::Kind::new("struct<byte1:tinyint,string1:string>").unwrap()
+    );
+    assert_eq!(
+        row_reader.selected_columns(),
+        vec![
+            (0, "".to_owned()),
+            (2, "byte1".to_owned()),
+            (9, "string1".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn include_types() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    // list._elem.string1, map._key, and map._value.int1
+    let options = reader::RowReaderOptions::default().include_types([18, 20, 22]);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    assert_eq!(
+        row_reader.selected_kind(),
+        kind::Kind::new(
+            "struct<list:array<struct<string1:string>>,map:map<string,struct<int1:int>>>"
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        row_reader.selected_columns(),
+        vec![
+            (0, "".to_owned()),
+            (15, "list".to_owned()),
+            (16, "list._elem".to_owned()),
+            (18, "list._elem.string1".to_owned()),
+            (19, "map".to_owned()),
+            (20, "map._key".to_owned()),
+            (21, "map._value".to_owned()),
+            (22, "map._value.int1".to_owned()),
+        ]
+    );
+
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    let struct_vector = batch.borrow().try_into_structs().unwrap();
+    let map_vector = struct_vector.fields()[1].try_into_maps().unwrap();
+    let keys: Vec<_> = map_vector
+        .keys()
+        .try_into_strings()
+        .unwrap()
+        .iter()
+        .map(|key| key.map(|key| String::from_utf8(key.to_vec()).unwrap()))
+        .collect();
+    assert_eq!(
+        keys,
+        vec![Some("chani".to_owned()), Some("mauddib".to_owned())]
+    );
+}
+
+#[test]
+fn include_types_map_keys_only() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    let options = reader::RowReaderOptions::default().include_types([20]);
+    let e = match reader.row_reader(&options) {
+        Ok(_) => panic!("Map column was read without its values"),
+        Err(e) => e,
+    };
+    assert_eq!(e.kind(), errors::OrcErrorKind::InvalidArgument, "{:?}", e);
+    assert!(
+        e.what()
+            .contains("Map column 19 must be read with both its keys and its values"),
+        "{:?}",
+        e
+    );
+}
+
 #[test]
 fn read_file() {
     let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
@@ -271,3 +479,630 @@ fn seek() {
             .collect::<Vec<_>>()
     );
 }
+
+/// Asserts schemas of the example files have the same fingerprint if and only if
+/// they are equal
+#[test]
+fn schema_fingerprint() {
+    let mut kinds = std::collections::HashMap::new();
+    for entry in std::fs::read_dir("orc/examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension() != Some("orc".as_ref()) {
+            continue;
+        }
+        let reader = match reader::InputStream::from_local_file(&path.display().to_string())
+            .ok()
+            .and_then(|input_stream| reader::Reader::new(input_stream).ok())
+        {
+            Some(reader) => reader,
+            None => continue, // Some example files are deliberately corrupted
+        };
+        let kind = reader.kind();
+        assert_eq!(reader.schema_fingerprint(), kind.fingerprint());
+        if let Some(other_kind) = kinds.insert(kind.fingerprint(), kind.clone()) {
+            assert_eq!(
+                kind,
+                other_kind,
+                "{} has the same fingerprint as a different schema",
+                path.display()
+            );
+        }
+    }
+    assert!(
+        kinds.len() > 10,
+        "Found only {} distinct schemas",
+        kinds.len()
+    );
+}
+
+#[test]
+fn read_from_memory() {
+    let contents: std::sync::Arc<[u8]> = std::fs::read("orc/examples/TestOrcFile.test1.orc")
+        .unwrap()
+        .into();
+    let input_stream = reader::InputStream::from_arc(contents.clone());
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    drop(reader);
+    drop(contents); // The stream keeps its own reference
+
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    assert_eq!((&batch).num_elements(), 2);
+    assert!(!row_reader.read_into(&mut batch));
+
+    let contents = br#"{"foo": "bar"}"#;
+    // Safe because 'contents' is static
+    let input_stream =
+        unsafe { reader::InputStream::from_raw_parts(contents.as_ptr(), contents.len()) };
+    assert!(matches!(
+        reader::Reader::new(input_stream),
+        Err(errors::ReaderError::NotAnOrcFile)
+    ));
+}
+
+#[test]
+fn read_from_vec_and_slice() {
+    let contents = std::fs::read("orc/examples/TestOrcFile.test1.orc").unwrap();
+    let input_streams = vec![
+        reader::InputStream::from_slice(&contents),
+        reader::InputStream::from_vec(contents),
+    ];
+    for input_stream in input_streams {
+        let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+        let mut row_reader = reader
+            .row_reader(&reader::RowReaderOptions::default())
+            .unwrap();
+        drop(reader);
+
+        let mut batch = row_reader.row_batch(1024);
+        assert!(row_reader.read_into(&mut batch));
+        assert_eq!((&batch).num_elements(), 2);
+        assert!(!row_reader.read_into(&mut batch));
+    }
+
+    assert!(matches!(
+        reader::Reader::new(reader::InputStream::from_slice(br#"{"foo": "bar"}"#)),
+        Err(errors::ReaderError::NotAnOrcFile)
+    ));
+}
+
+#[test]
+fn read_from_reader() {
+    let contents = std::fs::read("orc/examples/TestOrcFile.test1.orc").unwrap();
+    let input_stream = reader::InputStream::from_reader(std::io::Cursor::new(contents)).unwrap();
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    drop(reader);
+
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    assert_eq!((&batch).num_elements(), 2);
+    assert!(!row_reader.read_into(&mut batch));
+}
+
+/// Errors of the Rust reader are returned by the C++ library
+#[test]
+fn read_from_failing_reader() {
+    /// Fails to read anything but the magic bytes
+    struct FailingReader(std::io::Cursor<Vec<u8>>);
+
+    impl std::io::Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.position() >= 3 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "injected failure",
+                ));
+            }
+            self.0.read(buf)
+        }
+    }
+
+    impl std::io::Seek for FailingReader {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    let contents = std::fs::read("orc/examples/TestOrcFile.test1.orc").unwrap();
+    let input_stream =
+        reader::InputStream::from_reader(FailingReader(std::io::Cursor::new(contents))).unwrap();
+    match reader::Reader::new(input_stream) {
+        Err(errors::ReaderError::Corrupted(e)) => {
+            assert!(e.what().contains("injected failure"), "{}", e.what());
+            assert_eq!(e.kind(), errors::OrcErrorKind::Io);
+        }
+        Err(e) => panic!("Unexpected error: {}", e),
+        Ok(_) => panic!("Unexpected success"),
+    }
+}
+
+/// Panics of the Rust reader are returned as errors instead of aborting
+#[test]
+fn read_from_panicking_reader() {
+    /// Panics when reading anything but the magic bytes
+    struct PanickingReader(std::io::Cursor<Vec<u8>>);
+
+    impl std::io::Read for PanickingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.position() >= 3 {
+                panic!("injected panic");
+            }
+            self.0.read(buf)
+        }
+    }
+
+    impl std::io::Seek for PanickingReader {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    let contents = std::fs::read("orc/examples/TestOrcFile.test1.orc").unwrap();
+    let input_stream =
+        reader::InputStream::from_reader(PanickingReader(std::io::Cursor::new(contents))).unwrap();
+    match reader::Reader::new(input_stream) {
+        Err(errors::ReaderError::Corrupted(e)) => {
+            assert!(e.what().contains("Reader panicked"), "{}", e.what());
+            assert_eq!(e.kind(), errors::OrcErrorKind::Io);
+        }
+        Err(e) => panic!("Unexpected error: {}", e),
+        Ok(_) => panic!("Unexpected success"),
+    }
+}
+
+#[test]
+fn read_from_bytes() {
+    static CONTENTS: &[u8] = include_bytes!("../orc/examples/TestOrcFile.test1.orc");
+    let reader = reader::Reader::new(reader::InputStream::from_bytes(CONTENTS))
+        .expect("Could not create reader");
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    assert_eq!((&batch).num_elements(), 2);
+    assert!(!row_reader.read_into(&mut batch));
+}
+
+#[test]
+fn read_rows_into() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(1);
+    assert_eq!(row_reader.read_rows_into(&mut batch), NonZeroU64::new(1));
+    let mut batch = row_reader.row_batch(1024);
+    assert_eq!(row_reader.read_rows_into(&mut batch), NonZeroU64::new(1));
+    assert_eq!(row_reader.read_rows_into(&mut batch), None);
+}
+
+#[test]
+fn max_value_length() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let options = reader::RowReaderOptions::default()
+        .include_names(["string1"])
+        .max_value_length(2, reader::OversizedValuePolicy::Truncate);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert_eq!(row_reader.try_read_into(&mut batch), Ok(true));
+    let strings = batch.borrow().try_into_structs().unwrap().fields()[0]
+        .try_into_strings()
+        .unwrap();
+    assert_eq!(
+        strings.iter().collect::<Vec<_>>(),
+        vec![Some(&b"hi"[..]), Some(&b"by"[..])]
+    );
+    assert_eq!(row_reader.try_read_into(&mut batch), Ok(false));
+
+    let options = reader::RowReaderOptions::default()
+        .include_names(["string1"])
+        .max_value_length(2, reader::OversizedValuePolicy::Fail);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    // Rejected before decoding, because "hi" and "bye" are 2.5 bytes long on average
+    assert_eq!(
+        row_reader.try_read_into(&mut batch),
+        Err(errors::ReadError::OversizedStripe {
+            first_row: 0,
+            column_id: 9,
+            max_length: 2,
+        })
+    );
+
+    let options = reader::RowReaderOptions::default()
+        .include_names(["string1"])
+        .max_value_length(3, reader::OversizedValuePolicy::Fail);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert_eq!(row_reader.try_read_into(&mut batch), Ok(true));
+}
+
+/// Tests values longer than the limit are found after decoding when they are
+/// not longer on average
+#[test]
+fn max_value_length_after_decoding() {
+    let kind = kind::Kind::new("struct<s:string>").unwrap();
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = writer::OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer =
+        writer::Writer::new(output_stream, &kind, &writer::WriterOptions::default()).unwrap();
+    let mut batch = writer.row_batch(3);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        for _ in 0..3 {
+            structs.push();
+        }
+        let mut fields = structs.fields().into_iter();
+        let mut strings = fields.next().unwrap().try_into_strings().unwrap();
+        for s in ["a", "b", "cccc"] {
+            strings.push(s);
+        }
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    let input_stream = reader::InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    let reader = reader::Reader::new(input_stream).unwrap();
+    let options =
+        reader::RowReaderOptions::default().max_value_length(2, reader::OversizedValuePolicy::Fail);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert_eq!(
+        row_reader.try_read_into(&mut batch),
+        Err(errors::ReadError::OversizedValue(
+            errors::OversizedValueError {
+                first_row: 0,
+                count: 1,
+                max_length: 2,
+            }
+        ))
+    );
+}
+
+#[test]
+fn shard() {
+    let input_stream =
+        reader::InputStream::from_local_file("orc/examples/TestOrcFile.testStripeLevelStats.orc")
+            .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    let row_count = reader.row_count();
+
+    let mut boundaries = vec![0];
+    for stripe in reader.stripes() {
+        boundaries.push(boundaries.last().unwrap() + stripe.rows_count());
+    }
+    assert!(boundaries.len() > 2, "{:?}", boundaries);
+
+    assert_eq!(
+        reader.shard(std::num::NonZeroUsize::new(1).unwrap()),
+        vec![0..row_count]
+    );
+
+    for n_shards in 1..boundaries.len() + 2 {
+        let shards = reader.shard(std::num::NonZeroUsize::new(n_shards).unwrap());
+        assert_eq!(shards.len(), n_shards);
+        assert_eq!(shards[0].start, 0);
+        assert_eq!(shards.last().unwrap().end, row_count);
+        for pair in shards.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start, "{:?}", shards);
+        }
+        for shard in &shards {
+            assert!(boundaries.contains(&shard.end), "{:?}", shards);
+        }
+    }
+}
+
+#[test]
+fn row_group_start() {
+    let input_stream =
+        reader::InputStream::from_local_file("orc/examples/TestOrcFile.testStripeLevelStats.orc")
+            .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    let stride = reader.row_index_stride();
+    let row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+
+    let mut stripe_start = 0;
+    for stripe in reader.stripes() {
+        let stripe_end = stripe_start + stripe.rows_count();
+        for row_number in [stripe_start, stripe_start + 1, stripe_end - 1] {
+            let row_group_start = row_reader.row_group_start(row_number);
+            assert!(
+                (stripe_start..=row_number).contains(&row_group_start),
+                "row {} is in stripe {}..{}, but got row group start {}",
+                row_number,
+                stripe_start,
+                stripe_end,
+                row_group_start
+            );
+            if stride > 0 {
+                assert_eq!((row_group_start - stripe_start) % stride, 0);
+                assert!(row_number - row_group_start < stride);
+            }
+        }
+        stripe_start = stripe_end;
+    }
+}
+
+#[test]
+fn row_number_range_of_current_batch() {
+    let input_stream =
+        reader::InputStream::from_local_file("orc/examples/TestOrcFile.testStripeLevelStats.orc")
+            .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    assert!(row_reader.row_number_range_of_current_batch().is_empty());
+
+    let mut batch = row_reader.row_batch(1000);
+    let mut ranges = Vec::new();
+    while row_reader.read_into(&mut batch) {
+        let range = row_reader.row_number_range_of_current_batch();
+        assert_eq!(range.start, row_reader.get_row_number());
+        assert_eq!(range.end - range.start, batch.borrow().num_elements());
+        ranges.push(range);
+    }
+    assert!(
+        ranges.len() > 2,
+        "Test file should have more than 2 batches"
+    );
+    assert_eq!(ranges[0].start, 0);
+    for pair in ranges.windows(2) {
+        assert_eq!(pair[0].end, pair[1].start);
+    }
+    assert_eq!(ranges.last().unwrap().end, reader.row_count());
+    assert_eq!(
+        row_reader.row_number_range_of_current_batch(),
+        reader.row_count()..reader.row_count()
+    );
+
+    // Resume from a checkpoint, with a new row reader
+    let checkpoint = ranges[1].end;
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    row_reader.seek_to_row(checkpoint);
+    assert_eq!(
+        row_reader.row_number_range_of_current_batch(),
+        checkpoint..checkpoint
+    );
+    assert!(row_reader.read_into(&mut batch));
+    assert_eq!(
+        row_reader.row_number_range_of_current_batch().start,
+        checkpoint
+    );
+}
+
+#[test]
+fn byte_range() {
+    let input_stream =
+        reader::InputStream::from_local_file("orc/examples/TestOrcFile.testStripeLevelStats.orc")
+            .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let count_rows = |options: &reader::RowReaderOptions| {
+        let mut row_reader = reader.row_reader(options).unwrap();
+        let mut batch = row_reader.row_batch(1024);
+        let mut count = 0;
+        while let Some(rows) = row_reader.read_rows_into(&mut batch) {
+            count += u64::from(rows);
+        }
+        count
+    };
+
+    let stripes: Vec<_> = reader.stripes().collect();
+    assert!(stripes.len() > 1, "Test file should have several stripes");
+    for stripe in &stripes {
+        let options =
+            reader::RowReaderOptions::default().range(stripe.offset(), stripe.bytes_count());
+        assert_eq!(count_rows(&options), stripe.rows_count());
+    }
+
+    // Stripes starting in the second half of the file
+    let middle = stripes[stripes.len() / 2].offset();
+    let options = reader::RowReaderOptions::default().range(middle, u64::MAX - middle);
+    assert_eq!(
+        count_rows(&options),
+        stripes[stripes.len() / 2..]
+            .iter()
+            .map(|stripe| stripe.rows_count())
+            .sum::<u64>()
+    );
+
+    // No stripe starts in the magic bytes at the beginning of the file
+    let options = reader::RowReaderOptions::default().range(0, 3);
+    assert_eq!(count_rows(&options), 0);
+}
+
+#[test]
+fn stripe_row_reader() {
+    let input_stream =
+        reader::InputStream::from_local_file("orc/examples/TestOrcFile.testStripeLevelStats.orc")
+            .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let stripes: Vec<_> = reader.stripes().collect();
+    assert!(stripes.len() > 1, "Test file should have several stripes");
+    assert!(reader.stripe(stripes.len() as u64).is_none());
+
+    let mut first_row = 0;
+    for (stripe_index, stripe) in (0..).zip(&stripes) {
+        assert_eq!(stripe.first_row(), first_row);
+        assert_eq!(reader.stripe(stripe_index).unwrap().first_row(), first_row);
+
+        // Ranges set in the options are ignored
+        let options = reader::RowReaderOptions::default().range(0, 3);
+        let mut row_reader = reader.stripe_row_reader(stripe_index, &options).unwrap();
+        let mut batch = row_reader.row_batch(1024);
+        assert!(row_reader.read_into(&mut batch));
+        assert_eq!(row_reader.get_row_number(), first_row);
+        let mut count = batch.borrow().num_elements();
+        while let Some(rows) = row_reader.read_rows_into(&mut batch) {
+            count += u64::from(rows);
+        }
+        assert_eq!(count, stripe.rows_count());
+
+        first_row += stripe.rows_count();
+    }
+    assert_eq!(first_row, reader.row_count());
+
+    let e = match reader
+        .stripe_row_reader(stripes.len() as u64, &reader::RowReaderOptions::default())
+    {
+        Ok(_) => panic!("Out-of-range stripe should be an error"),
+        Err(e) => e,
+    };
+    assert_eq!(e.kind(), errors::OrcErrorKind::InvalidArgument, "{:?}", e);
+}
+
+#[test]
+fn estimated_memory() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let all_columns = reader
+        .estimated_memory(&reader::RowReaderOptions::default(), None)
+        .unwrap();
+    assert!(all_columns > 0);
+
+    let options = reader::RowReaderOptions::default().include_names(vec!["int1"]);
+    let int1 = reader.estimated_memory(&options, None).unwrap();
+    assert!(int1 > 0);
+    assert!(int1 < all_columns);
+
+    let by_index = reader::RowReaderOptions::default().include_indexes(vec![3]);
+    assert_eq!(reader.estimated_memory(&by_index, None).unwrap(), int1);
+
+    for stripe_index in 0..(reader.stripes().count() as u64) {
+        assert!(
+            reader
+                .estimated_memory(&options, Some(stripe_index))
+                .unwrap()
+                <= int1
+        );
+    }
+
+    let stripes_count = reader.stripes().count() as u64;
+    let e = reader
+        .estimated_memory(&options, Some(stripes_count))
+        .expect_err("Out of range stripe");
+    assert_eq!(e.kind(), errors::OrcErrorKind::InvalidArgument, "{:?}", e);
+}
+
+#[test]
+fn column_iter() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let strings = reader.column_iter::<Option<String>>("string1").unwrap();
+    assert_eq!(strings.len(), 2);
+    assert_eq!(
+        strings.collect::<Vec<_>>(),
+        [Some("hi".to_owned()), Some("bye".to_owned())]
+    );
+
+    // Values span several batches
+    let mut longs: row_iterator::ColumnIterator<i64> =
+        row_iterator::ColumnIterator::new(&reader, "long1", NonZeroU64::new(1).unwrap()).unwrap();
+    assert_eq!(longs.len(), 2);
+    assert_eq!(longs.next(), Some(i64::MAX));
+    assert_eq!(longs.len(), 1);
+    assert_eq!(longs.next(), Some(i64::MAX));
+    assert_eq!(longs.len(), 0);
+    assert_eq!(longs.next(), None);
+
+    assert!(matches!(
+        reader.column_iter::<i64>("abc"),
+        Err(errors::OpenOrcError::KindError(_))
+    ));
+    assert!(matches!(
+        reader.column_iter::<String>("int1"),
+        Err(errors::OpenOrcError::KindError(_))
+    ));
+}
+
+#[test]
+fn serialized_file_tail() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+    let tail = reader.serialized_file_tail();
+    assert!(!tail.is_empty());
+
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not read");
+    let options = reader::ReaderOptions::default().serialized_file_tail(&tail);
+    let cached_reader = reader::Reader::new_with_options(input_stream, options)
+        .expect("Could not create reader from serialized tail");
+    assert_eq!(cached_reader.kind(), reader.kind());
+    assert_eq!(cached_reader.row_count(), 2);
+
+    let mut row_reader = cached_reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    assert_eq!(batch.borrow().num_elements(), 2);
+}
+
+#[test]
+fn tail_location() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let mut contents = std::fs::read(orc_path).unwrap();
+    let length = contents.len() as u64;
+    contents.extend_from_slice(b"trailing data, which is not part of the ORC file");
+
+    let input_stream = reader::InputStream::from_vec(contents);
+    let options = reader::ReaderOptions::default().tail_location(length);
+    let reader =
+        reader::Reader::new_with_options(input_stream, options).expect("Could not create reader");
+    assert_eq!(reader.row_count(), 2);
+}
+
+#[test]
+fn memory_budget() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not read");
+    let options = reader::ReaderOptions::default().memory_budget(10);
+    assert!(matches!(
+        reader::Reader::new_with_options(input_stream, options),
+        Err(errors::ReaderError::Corrupted(_))
+    ));
+
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not read");
+    let options = reader::ReaderOptions::default().memory_budget(100 * 1024 * 1024);
+    let reader =
+        reader::Reader::new_with_options(input_stream, options).expect("Could not create reader");
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+
+    // Batches exceeding the budget are errors, not panics
+    assert!(row_reader.try_row_batch(100_000_000).is_err());
+
+    // Batches keep the memory pool alive after the reader is dropped
+    drop(row_reader);
+    drop(reader);
+    assert_eq!(batch.borrow().num_elements(), 2);
+    drop(batch);
+}
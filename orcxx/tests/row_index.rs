@@ -0,0 +1,171 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate pretty_assertions;
+extern crate tempfile;
+
+use pretty_assertions::assert_eq;
+
+use orcxx::errors::OrcErrorKind;
+use orcxx::kind::Kind;
+use orcxx::reader::{InputStream, Reader, RowReaderOptions};
+use orcxx::row_index::{self, RowGroup};
+use orcxx::statistics::ColumnStatistics;
+use orcxx::writer::{OutputStream, Writer, WriterOptions};
+
+const NUM_ROWS: i64 = 1000;
+const ROW_INDEX_STRIDE: i64 = 100;
+
+/// Writes a single stripe of `struct<id:bigint,name:string,other:bigint>` rows,
+/// with bloom filters on `id` and `name`
+fn write_file() -> tempfile::TempPath {
+    let kind = Kind::new("struct<id:bigint,name:string,other:bigint>").unwrap();
+    let options = WriterOptions::default()
+        .row_index_stride(ROW_INDEX_STRIDE as u64)
+        .bloom_filter_columns([1, 2]);
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &options).unwrap();
+
+    let mut batch = writer.row_batch(NUM_ROWS as u64);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        for _ in 0..NUM_ROWS {
+            structs.push();
+        }
+        let mut fields = structs.fields().into_iter();
+        let mut ids = fields.next().unwrap().try_into_longs().unwrap();
+        let mut names = fields.next().unwrap().try_into_strings().unwrap();
+        let mut others = fields.next().unwrap().try_into_longs().unwrap();
+        for i in 0..NUM_ROWS {
+            ids.push(i);
+            names.push(format!("name{}", i));
+            others.push(-i);
+        }
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    path
+}
+
+fn open(path: &tempfile::TempPath) -> Reader {
+    let input_stream = InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    Reader::new(input_stream).unwrap()
+}
+
+#[test]
+fn row_groups() {
+    let reader = open(&write_file());
+    let expected: Vec<_> = (0..10)
+        .map(|index| RowGroup {
+            stripe: 0,
+            index: index as usize,
+            rows: (index * 100)..(index * 100 + 100),
+        })
+        .collect();
+    assert_eq!(row_index::row_groups(&reader), expected);
+}
+
+#[test]
+fn row_group_statistics() {
+    let reader = open(&write_file());
+    let statistics: Vec<_> = reader
+        .row_group_statistics(0, 3)
+        .expect("Missing statistics")
+        .into_iter()
+        .map(|row_group| row_group.statistics)
+        .collect();
+    let expected: Vec<_> = (0..NUM_ROWS / ROW_INDEX_STRIDE)
+        .map(|index| {
+            let (first, last) = (index * ROW_INDEX_STRIDE, (index + 1) * ROW_INDEX_STRIDE - 1);
+            ColumnStatistics::Integer {
+                min: -last,
+                max: -first,
+                sum: Some(-(first + last) * ROW_INDEX_STRIDE / 2),
+            }
+        })
+        .collect();
+    assert_eq!(statistics, expected);
+
+    assert!(reader.row_group_statistics(0, 4).is_none());
+    assert!(reader.row_group_statistics(1, 3).is_none());
+}
+
+#[test]
+fn bloom_filters() {
+    let reader = open(&write_file());
+
+    let ids = reader
+        .bloom_filters(0, 1)
+        .unwrap()
+        .expect("Missing bloom filters");
+    assert_eq!(ids.len(), 10);
+    for row_group in 0..10 {
+        let first = row_group as i64 * ROW_INDEX_STRIDE;
+        for id in first..(first + ROW_INDEX_STRIDE) {
+            assert!(ids.might_contain_long(row_group, id));
+        }
+    }
+    // Bloom filters have false positives, but not in all row groups
+    assert!(
+        (0..10)
+            .filter(|&row_group| ids.might_contain_long(row_group, 5))
+            .count()
+            < 10
+    );
+
+    let names = reader
+        .bloom_filters(0, 2)
+        .unwrap()
+        .expect("Missing bloom filters");
+    assert_eq!(names.len(), 10);
+    assert!(names.might_contain_bytes(4, "name450"));
+    assert!(
+        (0..10)
+            .filter(|&row_group| names.might_contain_bytes(row_group, "name450"))
+            .count()
+            < 10
+    );
+
+    assert!(reader.bloom_filters(0, 3).unwrap().is_none());
+
+    for stripe_index in [1, u64::from(u32::MAX) + 1] {
+        match reader.bloom_filters(stripe_index, 1) {
+            Err(e) => assert_eq!(e.kind(), OrcErrorKind::InvalidArgument, "{:?}", e),
+            Ok(_) => panic!("Unexpected success for stripe {}", stripe_index),
+        }
+    }
+}
+
+/// Tests reading only the row groups which may contain a value
+#[test]
+fn prune_row_groups() {
+    let reader = open(&write_file());
+    let mut row_reader = reader.row_reader(&RowReaderOptions::default()).unwrap();
+    let mut batch = row_reader.row_batch(ROW_INDEX_STRIDE as u64);
+
+    let mut found = false;
+    for row_group in row_index::row_groups(&reader) {
+        let statistics = reader
+            .row_group_statistics(row_group.stripe, 1)
+            .expect("Missing statistics");
+        let may_contain = match statistics[row_group.index].statistics {
+            ColumnStatistics::Integer { min, max, .. } => (min..=max).contains(&742),
+            _ => true,
+        };
+        if !may_contain {
+            continue;
+        }
+        assert_eq!(row_group.rows, 700..800);
+        row_reader.seek_to_row(row_group.rows.start);
+        assert!(row_reader.read_into(&mut batch));
+        let structs = batch.borrow().try_into_structs().unwrap();
+        let ids = structs.fields()[0].try_into_longs().unwrap();
+        found = ids.iter().any(|id| id == Some(742));
+    }
+    assert!(found);
+}
@@ -0,0 +1,184 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests for [`RowIterator`] on files whose root is not a structure, so rows can be
+//! deserialized without `#[derive(OrcDeserialize)]`
+
+extern crate orcxx;
+extern crate pretty_assertions;
+extern crate tempfile;
+
+use std::num::NonZeroU64;
+
+use pretty_assertions::assert_eq;
+
+use orcxx::deserialize::{DeserializationError, OrcDeserialize};
+use orcxx::kind::Kind;
+use orcxx::reader::{InputStream, Reader, RowReaderOptions};
+use orcxx::row_iterator::{OnError, RowIterator};
+use orcxx::vector::ColumnVectorBatchMut;
+use orcxx::writer::{OutputStream, Writer, WriterOptions};
+
+const NUM_ROWS: i64 = 1000;
+
+/// Writes a file of `bigint` rows, where every tenth row is null
+fn write_file() -> tempfile::TempPath {
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let options = WriterOptions::default().row_index_stride(100);
+    let mut writer = Writer::new(output_stream, &Kind::Long, &options).unwrap();
+
+    let mut batch = writer.row_batch(NUM_ROWS as u64);
+    {
+        let mut longs = batch.borrow_mut().try_into_longs().unwrap();
+        for i in 0..NUM_ROWS {
+            if i % 10 == 0 {
+                longs.push_null();
+            } else {
+                longs.push(i);
+            }
+        }
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    path
+}
+
+fn row_iterator<T: OrcDeserialize + Default>(
+    path: &tempfile::TempPath,
+    batch_size: u64,
+) -> RowIterator<T> {
+    let input_stream = InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    let reader = Reader::new(input_stream).unwrap();
+    RowIterator::new_with_options(
+        &reader,
+        NonZeroU64::new(batch_size).unwrap(),
+        &RowReaderOptions::default(),
+    )
+    .unwrap()
+}
+
+/// Returns whether `e` is [`DeserializationError::UnexpectedNull`], possibly located
+fn is_unexpected_null(e: &DeserializationError) -> bool {
+    match e {
+        DeserializationError::Located { source, .. } => is_unexpected_null(source),
+        e => matches!(e, DeserializationError::UnexpectedNull(_)),
+    }
+}
+
+fn expected_rows() -> Vec<Option<i64>> {
+    (0..NUM_ROWS)
+        .map(|i| if i % 10 == 0 { None } else { Some(i) })
+        .collect()
+}
+
+#[test]
+fn forward() {
+    let path = write_file();
+    for batch_size in [1, 7, 100, 1024] {
+        let rows = row_iterator::<Option<i64>>(&path, batch_size);
+        assert_eq!(rows.len(), NUM_ROWS as usize);
+        assert_eq!(
+            rows.collect::<Vec<_>>(),
+            expected_rows(),
+            "batch_size = {}",
+            batch_size
+        );
+    }
+}
+
+#[test]
+fn backward() {
+    let path = write_file();
+    for batch_size in [1, 7, 100, 1024] {
+        let mut rows = row_iterator::<Option<i64>>(&path, batch_size);
+        let expected_rows = expected_rows();
+
+        // Yield all rows forward, then all of them again backward
+        assert_eq!(rows.by_ref().collect::<Vec<_>>(), expected_rows);
+        assert_eq!(rows.len(), 0);
+        let mut rows_backward: Vec<_> = rows.by_ref().rev().collect();
+        rows_backward.reverse();
+        assert_eq!(rows_backward, expected_rows, "batch_size = {}", batch_size);
+        assert_eq!(rows.len(), NUM_ROWS as usize);
+
+        // Go forward across a batch boundary, then back
+        assert_eq!(rows.by_ref().take(15).last(), Some(expected_rows[14]));
+        assert_eq!(rows.next_back(), Some(expected_rows[14]));
+        assert_eq!(rows.len(), NUM_ROWS as usize - 14);
+    }
+}
+
+#[test]
+fn seek() {
+    let path = write_file();
+    for batch_size in [1, 7, 100, 1024] {
+        let rows = row_iterator::<Option<i64>>(&path, batch_size).seek(542);
+        assert_eq!(rows.position(), 542);
+        assert_eq!(rows.len(), (NUM_ROWS - 542) as usize);
+        assert_eq!(
+            rows.collect::<Vec<_>>(),
+            expected_rows()[542..],
+            "batch_size = {}",
+            batch_size
+        );
+    }
+}
+
+#[test]
+fn range() {
+    let path = write_file();
+    let rows = row_iterator::<Option<i64>>(&path, 64).range(95..205);
+    assert_eq!(rows.len(), 110);
+    assert_eq!(rows.collect::<Vec<_>>(), expected_rows()[95..205]);
+}
+
+#[test]
+fn on_error_default() {
+    let path = write_file();
+    let mut rows = row_iterator::<i64>(&path, 64).on_error(OnError::Default);
+    assert_eq!(
+        rows.by_ref().collect::<Vec<_>>(),
+        expected_rows()
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect::<Vec<_>>()
+    );
+    let errors = rows.take_errors();
+    assert_eq!(
+        errors.iter().map(|(row, _)| *row).collect::<Vec<_>>(),
+        (0..NUM_ROWS as u64).step_by(10).collect::<Vec<_>>()
+    );
+    assert!(errors.iter().all(|(_, e)| is_unexpected_null(e)));
+}
+
+#[test]
+fn skip_errors() {
+    let path = write_file();
+    for batch_size in [1, 7, 100, 1024] {
+        let mut rows = row_iterator::<i64>(&path, batch_size).skip_errors();
+        assert_eq!(
+            rows.by_ref().collect::<Vec<_>>(),
+            expected_rows().into_iter().flatten().collect::<Vec<_>>(),
+            "batch_size = {}",
+            batch_size
+        );
+        assert_eq!(rows.take_errors().len(), (NUM_ROWS / 10) as usize);
+    }
+}
+
+#[test]
+fn try_rows() {
+    let path = write_file();
+    let rows: Vec<_> = row_iterator::<i64>(&path, 64).try_rows().collect();
+    assert_eq!(rows.len(), NUM_ROWS as usize);
+    for (i, row) in rows.into_iter().enumerate() {
+        match row {
+            Ok(value) => assert_eq!(value, i as i64),
+            Err(e) => assert!(i % 10 == 0 && is_unexpected_null(&e), "{:?}", e),
+        }
+    }
+}
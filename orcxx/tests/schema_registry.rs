@@ -0,0 +1,84 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+
+use orcxx::kind::{Kind, KindDifference};
+use orcxx::schema_registry::{SchemaRegistry, SchemaRegistryError};
+
+const ORC_PATH: &str = "orc/examples/TestOrcFile.test1.orc";
+
+fn test1_kind() -> Kind {
+    Kind::new(
+        "struct<boolean1:boolean,byte1:tinyint,short1:smallint,int1:int,long1:bigint,\
+         float1:float,double1:double,bytes1:binary,string1:string,\
+         middle:struct<list:array<struct<int1:int,string1:string>>>,\
+         list:array<struct<int1:int,string1:string>>,\
+         map:map<string,struct<int1:int,string1:string>>>",
+    )
+    .unwrap()
+}
+
+#[test]
+fn matching_schema() {
+    let mut registry = SchemaRegistry::new();
+    registry.register_kind("test1", test1_kind());
+    let reader = registry.open_checked(ORC_PATH, "test1").unwrap();
+    assert_eq!(reader.row_count(), 2);
+}
+
+#[test]
+fn drifted_schema() {
+    let mut registry = SchemaRegistry::new();
+    registry.register_kind(
+        "test1",
+        Kind::new("struct<boolean1:boolean,byte1:int,extra:string>").unwrap(),
+    );
+    match registry.open_checked(ORC_PATH, "test1") {
+        Err(SchemaRegistryError::Drift {
+            dataset,
+            differences,
+        }) => {
+            assert_eq!(dataset, "test1");
+            assert_eq!(
+                differences[0..2],
+                [
+                    KindDifference::Mismatch {
+                        path: "byte1".to_owned(),
+                        first: Kind::Int,
+                        second: Kind::Byte
+                    },
+                    KindDifference::OnlyInFirst("extra".to_owned()),
+                ]
+            );
+            assert_eq!(
+                differences[2],
+                KindDifference::OnlyInSecond("short1".to_owned())
+            );
+        }
+        Err(e) => panic!("Unexpected error: {}", e),
+        Ok(_) => panic!("Drift not detected"),
+    }
+}
+
+#[test]
+fn checkable_kind() {
+    let mut registry = SchemaRegistry::new();
+    registry.register::<i64, _>("longs");
+    assert!(registry.check("longs", &Kind::Long).is_ok());
+    assert!(matches!(
+        registry.open_checked(ORC_PATH, "longs"),
+        Err(SchemaRegistryError::Unsupported { .. })
+    ));
+}
+
+#[test]
+fn unknown_dataset() {
+    let registry = SchemaRegistry::new();
+    assert!(matches!(
+        registry.open_checked(ORC_PATH, "test1"),
+        Err(SchemaRegistryError::UnknownDataset(_))
+    ));
+}
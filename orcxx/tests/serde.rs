@@ -0,0 +1,253 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(not(feature = "serde"))]
+compile_error!("Feature 'serde' must be enabled for this test.");
+
+extern crate orcxx;
+extern crate pretty_assertions;
+#[macro_use]
+extern crate serde;
+extern crate tempfile;
+
+use std::collections::HashMap;
+
+use pretty_assertions::assert_eq;
+
+use orcxx::deserialize::DeserializationError;
+use orcxx::kind::Kind;
+use orcxx::reader::{InputStream, Reader, RowReaderOptions};
+use orcxx::serde::{from_vector_batch, SerdeError};
+use orcxx::value::OrcValue;
+use orcxx::vector::{ColumnVectorBatchMut, OwnedColumnVectorBatch};
+use orcxx::writer::{OutputStream, Writer, WriterOptions};
+
+fn read_batch(path: &str, options: &RowReaderOptions) -> (OwnedColumnVectorBatch, Kind) {
+    let input_stream = InputStream::from_local_file(path).expect("Could not open .orc");
+    let reader = Reader::new(input_stream).expect("Could not read .orc");
+    let mut row_reader = reader.row_reader(options).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    (batch, row_reader.selected_kind())
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Test1 {
+    boolean1: bool,
+    byte1: i8,
+    #[serde(flatten)]
+    numbers: Numbers,
+    double1: f64,
+    bytes1: Vec<u8>,
+    string1: Greeting,
+    middle: Middle,
+    list: Vec<Item>,
+    map: HashMap<String, Item>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Numbers {
+    short1: i16,
+    int1: i32,
+    long1: i64,
+    float1: f32,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Greeting {
+    Hi,
+    Bye,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Middle {
+    list: Vec<Item>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Item {
+    int1: i32,
+    string1: String,
+}
+
+fn item(int1: i32, string1: &str) -> Item {
+    Item {
+        int1,
+        string1: string1.to_owned(),
+    }
+}
+
+#[test]
+fn test1() {
+    let (batch, kind) = read_batch(
+        "orc/examples/TestOrcFile.test1.orc",
+        &RowReaderOptions::default(),
+    );
+    let rows: Vec<Test1> = from_vector_batch(batch.borrow(), &kind).unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Test1 {
+                boolean1: false,
+                byte1: 1,
+                numbers: Numbers {
+                    short1: 1024,
+                    int1: 65536,
+                    long1: i64::MAX,
+                    float1: 1.0,
+                },
+                double1: -15.0,
+                bytes1: vec![0, 1, 2, 3, 4],
+                string1: Greeting::Hi,
+                middle: Middle {
+                    list: vec![item(1, "bye"), item(2, "sigh")],
+                },
+                list: vec![item(3, "good"), item(4, "bad")],
+                map: HashMap::new(),
+            },
+            Test1 {
+                boolean1: true,
+                byte1: 100,
+                numbers: Numbers {
+                    short1: 2048,
+                    int1: 65536,
+                    long1: i64::MAX,
+                    float1: 2.0,
+                },
+                double1: -5.0,
+                bytes1: vec![],
+                string1: Greeting::Bye,
+                middle: Middle {
+                    list: vec![item(1, "bye"), item(2, "sigh")],
+                },
+                list: vec![
+                    item(100000000, "cat"),
+                    item(-100000, "in"),
+                    item(1234, "hat"),
+                ],
+                map: vec![
+                    ("chani".to_owned(), item(5, "chani")),
+                    ("mauddib".to_owned(), item(1, "mauddib")),
+                ]
+                .into_iter()
+                .collect(),
+            },
+        ]
+    );
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Nullable {
+    id: i64,
+    inner: Option<Inner>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Inner {
+    name: Option<String>,
+}
+
+#[test]
+fn nulls() {
+    let kind = Kind::new("struct<id:bigint,inner:struct<name:string>>").unwrap();
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+    let mut batch = writer.row_batch(3);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        for _ in 0..3 {
+            structs.push();
+        }
+        let mut fields = structs.fields().into_iter();
+        let mut ids = fields.next().unwrap().try_into_longs().unwrap();
+        let mut inners = fields.next().unwrap().try_into_structs().unwrap();
+        for id in 0..3 {
+            ids.push(id);
+        }
+        inners.push();
+        inners.push();
+        inners.push_null();
+        let mut names = inners.fields().pop().unwrap().try_into_strings().unwrap();
+        names.push("a");
+        names.push_null();
+        names.push_null();
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    let (batch, kind) = read_batch(path.to_str().unwrap(), &RowReaderOptions::default());
+    let rows: Vec<Nullable> = from_vector_batch(batch.borrow(), &kind).unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Nullable {
+                id: 0,
+                inner: Some(Inner {
+                    name: Some("a".to_owned())
+                }),
+            },
+            Nullable {
+                id: 1,
+                inner: Some(Inner { name: None }),
+            },
+            Nullable { id: 2, inner: None },
+        ]
+    );
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct WrongType {
+    string1: i32,
+}
+
+#[test]
+fn wrong_type() {
+    let (batch, kind) = read_batch(
+        "orc/examples/TestOrcFile.test1.orc",
+        &RowReaderOptions::default().include_names(["string1"]),
+    );
+    match from_vector_batch::<WrongType>(batch.borrow(), &kind) {
+        Err(SerdeError::Row { row: 0, .. }) => {}
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn unsupported_kind() {
+    let (batch, _) = read_batch(
+        "orc/examples/TestOrcFile.test1.orc",
+        &RowReaderOptions::default(),
+    );
+    let kind = Kind::new("struct<u:uniontype<int,string>>").unwrap();
+    assert_eq!(
+        from_vector_batch::<HashMap<String, i32>>(batch.borrow(), &kind).unwrap_err(),
+        SerdeError::UnsupportedKind(Kind::new("uniontype<int,string>").unwrap())
+    );
+}
+
+#[test]
+fn invalid_utf8() {
+    let kind = Kind::new("struct<name:string>").unwrap();
+    let mut batch = OwnedColumnVectorBatch::new(&kind, 2).unwrap();
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        structs.push();
+        structs.push();
+        let mut names = structs.fields().pop().unwrap().try_into_strings().unwrap();
+        names.push("a");
+        names.push(b"\xff");
+    }
+
+    assert!(matches!(
+        OrcValue::rows_from_vector_batch(batch.borrow(), &kind),
+        Err(DeserializationError::Located { row: Some(1), .. })
+    ));
+    assert!(matches!(
+        from_vector_batch::<HashMap<String, String>>(batch.borrow(), &kind),
+        Err(SerdeError::Custom(_))
+    ));
+}
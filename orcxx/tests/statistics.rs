@@ -0,0 +1,102 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate pretty_assertions;
+
+use pretty_assertions::assert_eq;
+
+use orcxx::reader::{InputStream, Reader};
+use orcxx::statistics::{ColumnStatistics, ColumnSummary};
+
+fn open(path: &str) -> Reader {
+    let input_stream = InputStream::from_local_file(path).expect("Could not open .orc");
+    Reader::new(input_stream).expect("Could not read .orc")
+}
+
+#[test]
+fn file_statistics() {
+    let reader = open("orc/examples/TestOrcFile.test1.orc");
+    let statistics: Vec<_> = reader
+        .statistics()
+        .expect("Missing statistics")
+        .into_iter()
+        .map(|column| column.expect("Missing column statistics"))
+        .collect();
+    assert_eq!(statistics.len(), 24);
+
+    let column = |id: usize| statistics[id].statistics.clone();
+    assert_eq!(
+        column(1), // boolean1
+        ColumnStatistics::Boolean {
+            false_count: 1,
+            true_count: 1
+        }
+    );
+    assert_eq!(
+        column(2), // byte1
+        ColumnStatistics::Integer {
+            min: 1,
+            max: 100,
+            sum: Some(101)
+        }
+    );
+    assert_eq!(
+        column(4), // int1
+        ColumnStatistics::Integer {
+            min: 65536,
+            max: 65536,
+            sum: Some(131072)
+        }
+    );
+    assert_eq!(
+        column(5), // long1, whose sum overflows
+        ColumnStatistics::Integer {
+            min: i64::MAX,
+            max: i64::MAX,
+            sum: None
+        }
+    );
+    assert_eq!(
+        column(7), // double1
+        ColumnStatistics::Double {
+            min: -15.,
+            max: -5.,
+            sum: Some(-20.)
+        }
+    );
+    assert_eq!(
+        column(8), // bytes1
+        ColumnStatistics::Binary {
+            total_length: Some(5)
+        }
+    );
+    assert_eq!(
+        column(9), // string1
+        ColumnStatistics::String {
+            min: b"bye".to_vec(),
+            max: b"hi".to_vec(),
+            total_length: Some(5)
+        }
+    );
+    assert_eq!(column(10), ColumnStatistics::Generic); // middle
+
+    assert_eq!(
+        statistics[9],
+        ColumnSummary {
+            number_of_values: 2,
+            has_null: false,
+            statistics: column(9),
+        }
+    );
+}
+
+#[test]
+fn stripe_statistics() {
+    let reader = open("orc/examples/TestOrcFile.test1.orc");
+
+    // test1 has a single stripe, so its statistics are the file's
+    assert_eq!(reader.stripe_statistics(0), reader.statistics());
+}
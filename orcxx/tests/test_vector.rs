@@ -3,9 +3,15 @@
 // License: GNU General Public License version 3, or any later version
 // See top-level LICENSE file for more information
 
+#[cfg(feature = "arrow")]
+extern crate arrow;
+#[cfg(feature = "chrono")]
+extern crate chrono;
 extern crate orcxx;
 
 use orcxx::reader;
+use orcxx::vector;
+use orcxx::vector::ColumnVectorBatch;
 
 #[test]
 fn test_string_bytes_and_ranges_without_nulls() {
@@ -34,6 +40,14 @@ fn test_string_bytes_and_ranges_without_nulls() {
     assert_eq!(string1_vector.bytes(), b"hibye");
     assert_eq!(bytes1_vector.ranges(), [Some(0..5), Some(5..5)]);
     assert_eq!(string1_vector.ranges(), [Some(0..2), Some(2..5)]);
+    assert_eq!(
+        bytes1_vector.contiguous_ranges(),
+        Some(bytes1_vector.ranges())
+    );
+    assert_eq!(
+        string1_vector.contiguous_ranges(),
+        Some(string1_vector.ranges())
+    );
 }
 
 #[test]
@@ -74,4 +88,564 @@ fn test_string_bytes_and_ranges_with_nulls() {
         string1_vector.ranges(),
         [Some(0..3), Some(3..6), None, Some(6..8)]
     );
+    assert_eq!(
+        bytes1_vector.contiguous_ranges(),
+        Some(bytes1_vector.ranges())
+    );
+    assert_eq!(
+        string1_vector.contiguous_ranges(),
+        Some(string1_vector.ranges())
+    );
+}
+
+/// Asserts contiguous_ranges() returns None when lengths do not match the blob,
+/// which is the case of truncated values
+#[test]
+fn test_string_contiguous_ranges_truncated() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let options = reader::RowReaderOptions::default()
+        .include_names(["string1"])
+        .max_value_length(2, reader::OversizedValuePolicy::Truncate);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+
+    assert!(row_reader.read_into(&mut batch));
+
+    let struct_vector = batch
+        .borrow()
+        .try_into_structs()
+        .expect("could not cast ColumnVectorBatch to StructDataBuffer");
+    let string1_vector = struct_vector.fields()[0].try_into_strings().unwrap();
+    assert_eq!(string1_vector.bytes(), b"hibye");
+    assert_eq!(string1_vector.ranges(), [Some(0..2), Some(2..4)]);
+    assert_eq!(string1_vector.contiguous_ranges(), None);
+    assert_eq!(
+        string1_vector.iter().collect::<Vec<_>>(),
+        [Some(&b"hi"[..]), Some(&b"by"[..])]
+    );
+}
+
+#[test]
+fn test_list_and_map_ranges() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default().include_names(["list", "map"]))
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+
+    assert!(row_reader.read_into(&mut batch));
+
+    let struct_vector = batch
+        .borrow()
+        .try_into_structs()
+        .expect("could not cast ColumnVectorBatch to StructDataBuffer");
+    let vectors = struct_vector.fields();
+    assert_eq!(vectors.len(), 2);
+
+    let list_vector = vectors[0].try_into_lists().unwrap();
+    let map_vector = vectors[1].try_into_maps().unwrap();
+    assert_eq!(list_vector.ranges(), [Some(0..2), Some(2..5)]);
+    assert_eq!(map_vector.ranges(), [Some(0..0), Some(0..2)]);
+    assert_eq!(
+        list_vector.ranges(),
+        list_vector.iter_offsets().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_union() {
+    let input_stream =
+        reader::InputStream::from_local_file("orc/examples/TestOrcFile.testUnionAndTimestamp.orc")
+            .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default().include_names(["union"]))
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(6);
+    assert!(row_reader.read_into(&mut batch));
+
+    let struct_vector = batch.borrow().try_into_structs().unwrap();
+    let union_vector = struct_vector.fields()[0].try_into_unions().unwrap();
+
+    // 42, "hello", null, null int, null string, 200000
+    assert_eq!(
+        union_vector.iter().collect::<Vec<_>>(),
+        [
+            Some((0, 0)),
+            Some((1, 0)),
+            None,
+            Some((0, 1)),
+            Some((1, 1)),
+            Some((0, 2))
+        ]
+    );
+    assert_eq!(union_vector.tags()[..2], [0, 1]);
+    assert_eq!(union_vector.offsets()[..2], [0, 0]);
+
+    let children = union_vector.children();
+    assert_eq!(children.len(), 2);
+    let ints: Vec<_> = children[0].try_into_longs().unwrap().iter().collect();
+    assert_eq!(ints, [Some(42), None, Some(200000)]);
+    let strings = children[1].try_into_strings().unwrap();
+    let strings: Vec<_> = strings.iter().collect();
+    assert_eq!(strings, [Some(&b"hello"[..]), None]);
+}
+
+#[test]
+fn test_double_ended() {
+    let input_stream =
+        reader::InputStream::from_local_file("orc/examples/TestOrcFile.testUnionAndTimestamp.orc")
+            .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default().include_names(["union"]))
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(6);
+    assert!(row_reader.read_into(&mut batch));
+
+    let struct_vector = batch.borrow().try_into_structs().unwrap();
+    let union_vector = struct_vector.fields()[0].try_into_unions().unwrap();
+
+    let mut unions = union_vector.iter();
+    assert_eq!(unions.len(), 6);
+    assert_eq!(unions.next_back(), Some(Some((0, 2))));
+    assert_eq!(unions.next(), Some(Some((0, 0))));
+    assert_eq!(unions.len(), 4);
+    assert_eq!(
+        unions.rev().collect::<Vec<_>>(),
+        [Some((1, 1)), Some((0, 1)), None, Some((1, 0))]
+    );
+
+    let children = union_vector.children();
+    let longs = children[0].try_into_longs().unwrap();
+    assert_eq!(longs.iter().len(), 3);
+    assert_eq!(
+        longs.iter().rev().collect::<Vec<_>>(),
+        [Some(200000), None, Some(42)]
+    );
+    let mut ints = longs.iter();
+    assert_eq!(ints.next_back(), Some(Some(200000)));
+    assert_eq!(ints.next(), Some(Some(42)));
+    assert_eq!(ints.len(), 1);
+    assert_eq!(ints.next_back(), Some(None));
+    assert_eq!(ints.next(), None);
+    assert_eq!(ints.next_back(), None);
+
+    let strings = children[1].try_into_strings().unwrap();
+    assert_eq!(strings.iter().len(), 2);
+    assert_eq!(
+        strings.iter().rev().collect::<Vec<_>>(),
+        [None, Some(&b"hello"[..])]
+    );
+
+    assert_eq!(
+        orcxx::zip_nulls(strings.iter(), longs.iter().take(2))
+            .rev()
+            .collect::<Vec<_>>(),
+        [None, Some((&b"hello"[..], 42))]
+    );
+}
+
+#[test]
+fn test_zip_nulls() {
+    let input_stream = reader::InputStream::from_local_file(
+        "orc/examples/TestOrcFile.testStringAndBinaryStatistics.orc",
+    )
+    .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default().include_names(["bytes1", "string1"]))
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+
+    assert!(row_reader.read_into(&mut batch));
+
+    let struct_vector = batch
+        .borrow()
+        .try_into_structs()
+        .expect("could not cast ColumnVectorBatch to StructDataBuffer");
+    let vectors = struct_vector.fields();
+
+    let bytes1_vector = vectors[0].try_into_strings().unwrap();
+    let string1_vector = vectors[1].try_into_strings().unwrap();
+    assert_eq!(
+        orcxx::zip_nulls(bytes1_vector.iter(), string1_vector.iter()).collect::<Vec<_>>(),
+        [
+            Some((&[0, 1, 2, 3, 4][..], &b"foo"[..])),
+            Some((&[0, 1, 2, 3][..], &b"bar"[..])),
+            None,
+            None
+        ]
+    );
+
+    // Batches can be passed directly
+    assert_eq!(
+        orcxx::zip_nulls(&bytes1_vector, &string1_vector).collect::<Vec<_>>(),
+        orcxx::zip_nulls(bytes1_vector.iter(), string1_vector.iter()).collect::<Vec<_>>(),
+    );
+
+    let validity = orcxx::zip_validity(&bytes1_vector, &string1_vector).unwrap();
+    assert_eq!(
+        validity.iter().collect::<Vec<_>>(),
+        [true, true, false, false]
+    );
+    assert_eq!(orcxx::zip_null_count(&bytes1_vector, &string1_vector), 2);
+    assert_eq!(orcxx::zip_null_count(&bytes1_vector, &bytes1_vector), 1);
+}
+
+#[test]
+#[should_panic(expected = "different lengths")]
+fn test_zip_nulls_different_lengths() {
+    orcxx::zip_nulls(vec![Some(1)], vec![Some(1), None]).for_each(drop);
+}
+
+#[test]
+#[cfg(all(feature = "chrono", feature = "time"))]
+fn test_timestamp_conversions() {
+    let input_stream =
+        reader::InputStream::from_local_file("orc/examples/TestOrcFile.testTimestamp.orc")
+            .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+
+    let mut num_timestamps = 0;
+    while row_reader.read_into(&mut batch) {
+        let timestamps = batch.borrow().try_into_timestamps().unwrap();
+        let expected: Vec<_> = timestamps.iter().collect();
+        num_timestamps += expected.len();
+        assert_eq!(
+            timestamps
+                .to_datetimes()
+                .into_iter()
+                .map(|datetime| datetime.map(|datetime| (
+                    datetime.timestamp(),
+                    i64::from(datetime.timestamp_subsec_nanos())
+                )))
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            timestamps
+                .to_offset_datetimes()
+                .into_iter()
+                .map(|datetime| datetime
+                    .map(|datetime| (datetime.unix_timestamp(), i64::from(datetime.nanosecond()))))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+    assert!(num_timestamps > 0);
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn test_date_conversions() {
+    let input_stream =
+        reader::InputStream::from_local_file("orc/examples/TestOrcFile.testDate1900.orc")
+            .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default().include_names(["date"]))
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+    let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+
+    let mut num_dates = 0;
+    while row_reader.read_into(&mut batch) {
+        let struct_vector = batch.borrow().try_into_structs().unwrap();
+        let dates = struct_vector.fields()[0].try_into_longs().unwrap();
+        let expected: Vec<_> = dates.iter().collect();
+        num_dates += expected.len();
+
+        let naive_dates = dates.to_naive_dates();
+        assert_eq!(
+            naive_dates
+                .iter()
+                .map(|date| date.map(|date| (date - epoch).num_days()))
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            dates.to_date_strings(),
+            naive_dates
+                .iter()
+                .map(|date| date.map(|date| date.format("%Y-%m-%d").to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+    assert!(num_dates > 0);
+}
+
+#[test]
+fn test_named_fields() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default().include_names(["list", "string1"]))
+        .unwrap();
+    let column = row_reader.selected_column_tree();
+
+    let mut batch = row_reader.row_batch(1024);
+
+    assert!(row_reader.read_into(&mut batch));
+
+    let struct_vector = batch
+        .borrow()
+        .try_into_structs()
+        .expect("could not cast ColumnVectorBatch to StructDataBuffer");
+    let fields = struct_vector.named_fields(&column);
+    assert_eq!(
+        fields
+            .iter()
+            .map(|field| (field.name(), field.column_id()))
+            .collect::<Vec<_>>(),
+        [("string1", 9), ("list", 15)]
+    );
+
+    let string1_vector = fields
+        .by_name("string1")
+        .unwrap()
+        .batch()
+        .try_into_strings()
+        .unwrap();
+    assert_eq!(string1_vector.bytes(), b"hibye");
+    assert!(fields.by_name("bytes1").is_none());
+
+    // Nested structures are looked up with their own column
+    let list = fields.by_column_id(15).unwrap();
+    assert_eq!(list.name(), "list");
+    let items_vector = list
+        .batch()
+        .try_into_lists()
+        .unwrap()
+        .elements()
+        .try_into_structs()
+        .unwrap();
+    let item_fields = items_vector.named_fields(&list.column().children[0]);
+    assert_eq!(item_fields.by_name("int1").unwrap().column_id(), 17);
+    let item_string1_vector = item_fields
+        .by_name("string1")
+        .unwrap()
+        .batch()
+        .try_into_strings()
+        .unwrap();
+    assert_eq!(item_string1_vector.bytes(), b"goodbadcatinhat");
+}
+
+#[test]
+fn test_as_slice_not_null() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default().include_names(["long1", "double1"]))
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+
+    assert!(row_reader.read_into(&mut batch));
+
+    let struct_vector = batch
+        .borrow()
+        .try_into_structs()
+        .expect("could not cast ColumnVectorBatch to StructDataBuffer");
+    let vectors = struct_vector.fields();
+    assert_eq!(
+        vectors[0].try_into_longs().unwrap().try_as_slice_not_null(),
+        Some(&[i64::MAX, i64::MAX][..])
+    );
+    assert_eq!(
+        vectors[1]
+            .try_into_doubles()
+            .unwrap()
+            .try_as_slice_not_null(),
+        Some(&[-15., -5.][..])
+    );
+
+    // The int variant of the union has nulls
+    let input_stream =
+        reader::InputStream::from_local_file("orc/examples/TestOrcFile.testUnionAndTimestamp.orc")
+            .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default().include_names(["union"]))
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(6);
+    assert!(row_reader.read_into(&mut batch));
+
+    let struct_vector = batch.borrow().try_into_structs().unwrap();
+    let union_vector = struct_vector.fields()[0].try_into_unions().unwrap();
+    let children = union_vector.children();
+    assert_eq!(
+        children[0]
+            .try_into_longs()
+            .unwrap()
+            .try_as_slice_not_null(),
+        None
+    );
+}
+
+#[test]
+fn test_validity() {
+    let input_stream =
+        reader::InputStream::from_local_file("orc/examples/TestOrcFile.testUnionAndTimestamp.orc")
+            .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default().include_names(["union"]))
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(6);
+    assert!(row_reader.read_into(&mut batch));
+
+    let struct_vector = batch.borrow().try_into_structs().unwrap();
+    assert_eq!(struct_vector.null_count(), 0);
+    assert!(struct_vector.not_null_bools().is_none());
+    assert_eq!(struct_vector.validity(), None);
+
+    let union_vector = struct_vector.fields()[0].try_into_unions().unwrap();
+    assert_eq!(union_vector.null_count(), 1);
+    assert_eq!(
+        union_vector.not_null_bools().unwrap().collect::<Vec<_>>(),
+        [true, true, false, true, true, true]
+    );
+
+    // [42, null, 200000]
+    let ints = union_vector.children()[0].try_into_longs().unwrap();
+    assert_eq!(ints.null_count(), 1);
+    let validity = ints.validity().unwrap();
+    assert_eq!(validity.iter().collect::<Vec<_>>(), [true, false, true]);
+
+    #[cfg(feature = "arrow")]
+    {
+        let nulls: arrow::buffer::NullBuffer = validity.into();
+        assert_eq!(nulls.len(), 3);
+        assert_eq!(nulls.null_count(), 1);
+        assert!(nulls.is_null(1));
+    }
+}
+
+#[test]
+fn test_bool_to_bitmap() {
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default().include_names(["boolean1"]))
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+
+    assert!(row_reader.read_into(&mut batch));
+
+    let struct_vector = batch
+        .borrow()
+        .try_into_structs()
+        .expect("could not cast ColumnVectorBatch to StructDataBuffer");
+    let vectors = struct_vector.fields();
+    let (values, validity) = vectors[0].try_into_longs().unwrap().to_bitmap();
+    assert_eq!(values.iter().collect::<Vec<_>>(), [false, true]);
+    assert_eq!(values.as_words(), [0b10]);
+    assert_eq!(validity, None);
+}
+
+#[test]
+fn test_bitmap() {
+    let bits: Vec<bool> = (0..150).map(|i| i % 3 == 0).collect();
+    let bitmap: vector::Bitmap = bits.iter().copied().collect();
+    assert_eq!(bitmap.len(), 150);
+    assert_eq!(bitmap.as_words().len(), 3);
+    assert_eq!(bitmap.count_ones(), 50);
+    assert_eq!(bitmap.get(63), Some(true));
+    assert_eq!(bitmap.get(64), Some(false));
+    assert_eq!(bitmap.get(150), None);
+    assert_eq!(bitmap.iter().collect::<Vec<_>>(), bits);
+    assert_eq!(
+        bitmap.slice(60..70).iter().collect::<Vec<_>>(),
+        bits[60..70]
+    );
+    assert!(bitmap.slice(70..70).is_empty());
+}
+
+#[test]
+fn test_mutate_batch() {
+    use vector::{ColumnVectorBatch, ColumnVectorBatchMut};
+
+    let input_stream = reader::InputStream::from_local_file("orc/examples/TestOrcFile.test1.orc")
+        .expect("Could not read");
+    let reader = reader::Reader::new(input_stream).expect("Could not create reader");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default().include_names(["int1", "string1"]))
+        .unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+
+    {
+        let mut struct_vector = batch.borrow_mut().try_into_structs().unwrap();
+        assert_eq!(struct_vector.num_elements(), 2);
+        struct_vector.push();
+        let mut fields = struct_vector.fields().into_iter();
+        let mut ints = fields.next().unwrap().try_into_longs().unwrap();
+        ints.set(0, 42);
+        ints.push_null();
+        let mut strings = fields.next().unwrap().try_into_strings().unwrap();
+        strings.set_null(0);
+        strings.push("new");
+        assert_eq!(strings.num_elements(), 3);
+    }
+
+    let struct_vector = batch.borrow().try_into_structs().unwrap();
+    let vectors = struct_vector.fields();
+    assert_eq!(
+        vectors[0]
+            .try_into_longs()
+            .unwrap()
+            .iter()
+            .collect::<Vec<_>>(),
+        [Some(42), Some(65536), None]
+    );
+    assert_eq!(
+        vectors[1]
+            .try_into_strings()
+            .unwrap()
+            .iter()
+            .collect::<Vec<_>>(),
+        [None, Some(&b"bye"[..]), Some(&b"new"[..])]
+    );
+
+    batch.borrow_mut().clear();
+    assert_eq!(
+        batch.borrow().try_into_structs().unwrap().fields()[1].num_elements(),
+        0
+    );
 }
@@ -0,0 +1,104 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+
+use orcxx::kind::Kind;
+use orcxx::testing::generate_rows;
+use orcxx::value::OrcValue;
+
+fn nested_kind() -> Kind {
+    Kind::new(
+        "struct<boolean1:boolean,byte1:tinyint,short1:smallint,int1:int,long1:bigint,\
+         float1:float,double1:double,bytes1:binary,string1:string,varchar1:varchar(3),\
+         char1:char(5),decimal1:decimal(10,2),decimal2:decimal(38,6),date1:date,\
+         timestamp1:timestamp,\
+         list:array<array<struct<int1:int,string1:string>>>,\
+         map:map<string,map<int,array<varchar(2)>>>,\
+         union:uniontype<int,struct<a:string,b:uniontype<date,binary>>>>",
+    )
+    .unwrap()
+}
+
+#[test]
+fn same_seed() {
+    let kind = nested_kind();
+    assert_eq!(
+        generate_rows(&kind, 100, 1234),
+        generate_rows(&kind, 100, 1234)
+    );
+}
+
+#[test]
+fn different_seeds() {
+    let kind = nested_kind();
+    assert_ne!(
+        generate_rows(&kind, 100, 1234),
+        generate_rows(&kind, 100, 1235)
+    );
+}
+
+#[test]
+fn rows_match_kind() {
+    let kind = nested_kind();
+    let rows = generate_rows(&kind, 1000, 42);
+    assert_eq!(rows.len(), 1000);
+    for row in &rows {
+        assert!(row.matches_kind(&kind), "{:?}", row);
+        assert_ne!(row, &OrcValue::Null);
+    }
+}
+
+#[test]
+fn matches_kind() {
+    assert!(OrcValue::String("abc".to_owned()).matches_kind(&Kind::Varchar(3)));
+    assert!(OrcValue::String("日本語".to_owned()).matches_kind(&Kind::Varchar(3)));
+    assert!(!OrcValue::String("abcd".to_owned()).matches_kind(&Kind::Varchar(3)));
+    assert!(OrcValue::Null.matches_kind(&Kind::Varchar(3)));
+    assert!(!OrcValue::Int(1).matches_kind(&Kind::Long));
+
+    let union = Kind::Union(vec![Kind::Int, Kind::String]);
+    assert!(OrcValue::Union {
+        tag: 1,
+        value: Box::new(OrcValue::String("a".to_owned()))
+    }
+    .matches_kind(&union));
+    assert!(!OrcValue::Union {
+        tag: 0,
+        value: Box::new(OrcValue::String("a".to_owned()))
+    }
+    .matches_kind(&union));
+    assert!(!OrcValue::Union {
+        tag: 2,
+        value: Box::new(OrcValue::Null)
+    }
+    .matches_kind(&union));
+}
+
+#[test]
+fn empty_union() {
+    let kind = Kind::Struct(vec![("union".to_owned(), Kind::Union(vec![]))]);
+    let rows = generate_rows(&kind, 10, 42);
+    for row in &rows {
+        assert_eq!(
+            row,
+            &OrcValue::Struct(vec![("union".to_owned(), OrcValue::Null)])
+        );
+    }
+}
+
+#[test]
+fn decimal_precision() {
+    let kind = Kind::Decimal {
+        precision: 5,
+        scale: 2,
+    };
+    for row in generate_rows(&kind, 1000, 42) {
+        match row {
+            OrcValue::Decimal(d) => assert!(d.mantissa().abs() < 100_000, "{}", d),
+            row => panic!("Unexpected value: {:?}", row),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(not(feature = "arrow"))]
+compile_error!("Feature 'arrow' must be enabled for this test.");
+
+extern crate arrow;
+extern crate orcxx;
+
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::{
+    DataType, Float64Type, Int64Type, Int8Type, TimeUnit, TimestampNanosecondType,
+};
+
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx::to_arrow::{kind_to_arrow_schema, vector_batch_to_record_batch, ToArrowError};
+use orcxx::vector::{ColumnVectorBatchMut, OwnedColumnVectorBatch};
+
+fn row_reader(options: &reader::RowReaderOptions) -> reader::RowReader {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    reader.row_reader(options).unwrap()
+}
+
+#[test]
+fn test1() {
+    let mut row_reader = row_reader(&reader::RowReaderOptions::default());
+    let kind = row_reader.selected_kind();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+
+    let record_batch = vector_batch_to_record_batch(batch.borrow(), &kind).unwrap();
+    assert_eq!(record_batch.num_rows(), 2);
+    assert_eq!(*record_batch.schema(), kind_to_arrow_schema(&kind).unwrap());
+
+    let byte1 = record_batch.column_by_name("byte1").unwrap();
+    assert_eq!(byte1.as_primitive::<Int8Type>().values(), &[1, 100]);
+
+    let long1 = record_batch.column_by_name("long1").unwrap();
+    assert_eq!(long1.null_count(), 0);
+    assert_eq!(
+        long1.as_primitive::<Int64Type>().values(),
+        &[i64::MAX, i64::MAX]
+    );
+
+    let double1 = record_batch.column_by_name("double1").unwrap();
+    assert_eq!(double1.as_primitive::<Float64Type>().values(), &[-15., -5.]);
+
+    let string1 = record_batch.column_by_name("string1").unwrap();
+    assert_eq!(
+        string1.as_string::<i32>().iter().collect::<Vec<_>>(),
+        vec![Some("hi"), Some("bye")]
+    );
+
+    let list = record_batch
+        .column_by_name("list")
+        .unwrap()
+        .as_list::<i32>();
+    assert_eq!(list.value_length(0), 2);
+    assert_eq!(list.value_length(1), 3);
+}
+
+#[test]
+fn selected_columns() {
+    let options = reader::RowReaderOptions::default().include_names(["long1", "string1"]);
+    let mut row_reader = row_reader(&options);
+    let kind = row_reader.selected_kind();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+
+    let record_batch = vector_batch_to_record_batch(batch.borrow(), &kind).unwrap();
+    assert_eq!(record_batch.num_columns(), 2);
+    assert_eq!(record_batch.num_rows(), 2);
+}
+
+#[test]
+fn not_a_struct() {
+    let mut row_reader = row_reader(&reader::RowReaderOptions::default());
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+
+    assert!(matches!(
+        vector_batch_to_record_batch(batch.borrow(), &Kind::Long),
+        Err(ToArrowError::NotAStruct(Kind::Long))
+    ));
+}
+
+/// Builds a batch of the given struct type, whose columns all contain the given
+/// (possibly invalid UTF-8) values
+fn strings_batch(kind: &Kind, values: &[Option<&[u8]>]) -> OwnedColumnVectorBatch {
+    let mut batch = OwnedColumnVectorBatch::new(kind, values.len() as u64).unwrap();
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        for _ in values {
+            structs.push();
+        }
+        for field in structs.fields() {
+            let mut strings = field.try_into_strings().unwrap();
+            for value in values {
+                match value {
+                    Some(value) => strings.push(value),
+                    None => strings.push_null(),
+                }
+            }
+        }
+    }
+    batch
+}
+
+#[test]
+fn strings() {
+    let kind = Kind::new("struct<string1:string,bytes1:binary>").unwrap();
+    let batch = strings_batch(&kind, &[Some(b"abc"), None, Some(b""), Some(b"d\xc3\xa9")]);
+
+    let record_batch = vector_batch_to_record_batch(batch.borrow(), &kind).unwrap();
+    let string1 = record_batch.column_by_name("string1").unwrap();
+    assert_eq!(
+        string1.as_string::<i32>().iter().collect::<Vec<_>>(),
+        vec![Some("abc"), None, Some(""), Some("dé")]
+    );
+    let bytes1 = record_batch.column_by_name("bytes1").unwrap();
+    assert_eq!(
+        bytes1.as_binary::<i32>().iter().collect::<Vec<_>>(),
+        vec![
+            Some(&b"abc"[..]),
+            None,
+            Some(&b""[..]),
+            Some(&b"d\xc3\xa9"[..])
+        ]
+    );
+}
+
+#[test]
+fn invalid_utf8() {
+    let kind = Kind::new("struct<string1:string>").unwrap();
+    let batch = strings_batch(&kind, &[Some(b"abc"), Some(b"\xff")]);
+    assert!(matches!(
+        vector_batch_to_record_batch(batch.borrow(), &kind),
+        Err(ToArrowError::Arrow(_))
+    ));
+
+    // Binary columns may contain anything
+    let kind = Kind::new("struct<bytes1:binary>").unwrap();
+    let batch = strings_batch(&kind, &[Some(b"abc"), Some(b"\xff")]);
+    assert!(vector_batch_to_record_batch(batch.borrow(), &kind).is_ok());
+}
+
+#[test]
+fn timestamp_instants() {
+    let kind = Kind::new("struct<ts:timestamp,instant:timestamp with local time zone>").unwrap();
+    let mut batch = OwnedColumnVectorBatch::new(&kind, 2).unwrap();
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        structs.push();
+        structs.push();
+        for field in structs.fields() {
+            let mut timestamps = field.try_into_timestamps().unwrap();
+            timestamps.push(1, 500_000_000);
+            timestamps.push_null();
+        }
+    }
+
+    let schema = kind_to_arrow_schema(&kind).unwrap();
+    assert_eq!(
+        schema.field_with_name("ts").unwrap().data_type(),
+        &DataType::Timestamp(TimeUnit::Nanosecond, None)
+    );
+    assert_eq!(
+        schema.field_with_name("instant").unwrap().data_type(),
+        &DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into()))
+    );
+
+    let record_batch = vector_batch_to_record_batch(batch.borrow(), &kind).unwrap();
+    for name in ["ts", "instant"] {
+        let column = record_batch.column_by_name(name).unwrap();
+        assert_eq!(
+            column.data_type(),
+            schema.field_with_name(name).unwrap().data_type()
+        );
+        assert_eq!(
+            column
+                .as_primitive::<TimestampNanosecondType>()
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![Some(1_500_000_000), None]
+        );
+    }
+}
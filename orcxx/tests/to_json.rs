@@ -0,0 +1,449 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(not(feature = "json"))]
+compile_error!("Feature 'json' must be enabled for this test.");
+
+#[macro_use]
+extern crate json;
+extern crate orcxx;
+extern crate pretty_assertions;
+extern crate tempfile;
+
+use json::JsonValue;
+
+use pretty_assertions::assert_eq;
+
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx::structured_reader::{OwnedColumnTree, StructuredRowReader};
+use orcxx::to_json::{
+    columntree_to_json_rows, columntree_to_json_rows_with_options,
+    columntree_to_json_rows_with_transforms, write_json_lines_with_options, ColumnTransforms,
+    JsonOptions,
+};
+use orcxx::vector::ColumnVectorBatchMut;
+use orcxx::writer::{OutputStream, Writer, WriterOptions};
+
+/// Asserts only selected columns are in the output
+#[test]
+fn projection() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let options = reader::RowReaderOptions::default().include_names(["string1", "list"]);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1024);
+    let mut objects = Vec::new();
+    while let Some(columns) = structured_row_reader.next() {
+        objects.extend(columntree_to_json_rows(columns));
+    }
+
+    assert_eq!(
+        objects,
+        vec![
+            object! {
+                "string1" => "hi",
+                "list" => array![
+                    object! {"int1" => 3, "string1" => "good"},
+                    object! {"int1" => 4, "string1" => "bad"},
+                ],
+            },
+            object! {
+                "string1" => "bye",
+                "list" => array![
+                    object! {"int1" => 100000000, "string1" => "cat"},
+                    object! {"int1" => -100000, "string1" => "in"},
+                    object! {"int1" => 1234, "string1" => "hat"},
+                ],
+            },
+        ]
+    );
+}
+
+/// Asserts [`StructuredRowReader::for_each_batch`] is called on every batch, and
+/// yields the same rows as [`StructuredRowReader::next`]
+#[test]
+fn for_each_batch() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let options = reader::RowReaderOptions::default().include_names(["string1", "list"]);
+
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1024);
+    let mut expected = Vec::new();
+    while let Some(columns) = structured_row_reader.next() {
+        expected.extend(columntree_to_json_rows(columns));
+    }
+
+    // One row per batch, so the callback is called once per row
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1);
+    let mut calls = 0;
+    let mut objects = Vec::new();
+    structured_row_reader.for_each_batch(|columns| {
+        calls += 1;
+        objects.extend(columntree_to_json_rows(columns))
+    });
+
+    assert_eq!(calls, 2);
+    assert_eq!(objects, expected);
+}
+
+/// Asserts owned column trees can be kept and sent to another thread
+#[test]
+fn owned_column_trees() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let options = reader::RowReaderOptions::default().include_names(["int1", "string1"]);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+
+    // One row per batch, so each batch is only valid if it was not reused
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1);
+    let batches: Vec<_> = structured_row_reader.owned_batches().collect();
+    assert_eq!(batches.len(), 2);
+    for columns in &batches {
+        assert_eq!(columns.num_elements(), 1);
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let consumer = std::thread::spawn(move || {
+        rx.into_iter()
+            .flat_map(|columns: OwnedColumnTree| columntree_to_json_rows(columns.borrow()))
+            .collect::<Vec<_>>()
+    });
+    for columns in batches {
+        tx.send(columns).unwrap();
+    }
+    drop(tx);
+
+    assert_eq!(
+        consumer.join().unwrap(),
+        vec![
+            object! {"int1" => 65536, "string1" => "hi"},
+            object! {"int1" => 65536, "string1" => "bye"},
+        ]
+    );
+}
+
+/// Asserts transforms are applied to their column, including in nested structures
+#[test]
+fn transforms() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let options = reader::RowReaderOptions::default().include_names(["string1", "list"]);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+
+    let mut transforms = ColumnTransforms::new();
+    transforms
+        .insert("string1", |value| value.as_str().map(str::len).into())
+        .insert("list._elem.string1", |_| JsonValue::Null);
+
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1024);
+    let mut objects = Vec::new();
+    while let Some(columns) = structured_row_reader.next() {
+        objects.extend(columntree_to_json_rows_with_transforms(
+            columns,
+            &transforms,
+        ));
+    }
+
+    assert_eq!(
+        objects,
+        vec![
+            object! {
+                "string1" => 2,
+                "list" => array![
+                    object! {"int1" => 3, "string1" => JsonValue::Null},
+                    object! {"int1" => 4, "string1" => JsonValue::Null},
+                ],
+            },
+            object! {
+                "string1" => 3,
+                "list" => array![
+                    object! {"int1" => 100000000, "string1" => JsonValue::Null},
+                    object! {"int1" => -100000, "string1" => JsonValue::Null},
+                    object! {"int1" => 1234, "string1" => JsonValue::Null},
+                ],
+            },
+        ]
+    );
+}
+
+/// Asserts renamed fields use their new key and omitted fields are absent, including
+/// in nested structures
+#[test]
+fn renames_and_omissions() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let options = reader::RowReaderOptions::default().include_names(["int1", "string1", "list"]);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+
+    let mut transforms = ColumnTransforms::new();
+    transforms
+        .omit("int1")
+        .rename("string1", "name")
+        .rename("list", "items")
+        .rename("list._elem.int1", "id")
+        .omit("list._elem.string1")
+        .insert("list._elem.int1", |value| {
+            value.as_i64().map(|n| n + 1).into()
+        });
+
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1024);
+    let mut objects = Vec::new();
+    while let Some(columns) = structured_row_reader.next() {
+        objects.extend(columntree_to_json_rows_with_transforms(
+            columns,
+            &transforms,
+        ));
+    }
+
+    assert_eq!(
+        objects,
+        vec![
+            object! {
+                "name" => "hi",
+                "items" => array![object! {"id" => 4}, object! {"id" => 5}],
+            },
+            object! {
+                "name" => "bye",
+                "items" => array![
+                    object! {"id" => 100000001},
+                    object! {"id" => -99999},
+                    object! {"id" => 1235},
+                ],
+            },
+        ]
+    );
+}
+
+/// Asserts [`write_json_lines_with_options`] writes the same rows as
+/// [`columntree_to_json_rows_with_options`], for every column type of the file
+#[test]
+fn write_json_lines() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+
+    let mut transforms = ColumnTransforms::new();
+    transforms
+        .omit("int1")
+        .rename("string1", "name")
+        .rename("list._elem.int1", "id")
+        .insert("middle.list._elem.string1", |value| {
+            value.as_str().map(str::len).into()
+        });
+
+    for transforms in [ColumnTransforms::new(), transforms] {
+        let options = JsonOptions::default().transforms(&transforms);
+        let mut row_reader = reader
+            .row_reader(&reader::RowReaderOptions::default())
+            .unwrap();
+        let mut expected = Vec::new();
+        StructuredRowReader::new(&mut row_reader, 1024).for_each_batch(|columns| {
+            expected.extend(columntree_to_json_rows_with_options(columns, &options))
+        });
+        let mut row_reader = reader
+            .row_reader(&reader::RowReaderOptions::default())
+            .unwrap();
+        let mut lines = Vec::new();
+        StructuredRowReader::new(&mut row_reader, 1024).for_each_batch(|columns| {
+            write_json_lines_with_options(columns, &options, &mut lines).unwrap()
+        });
+
+        let expected: Vec<_> = expected.iter().map(JsonValue::dump).collect();
+        let lines = String::from_utf8(lines).unwrap();
+        assert_eq!(lines.lines().collect::<Vec<_>>(), expected);
+        assert!(lines.ends_with('\n'));
+    }
+}
+
+/// Asserts fields of null structs do not shift the values of the following rows
+#[test]
+fn nested_null_structs() {
+    let kind = Kind::Struct(vec![
+        (
+            "outer".to_owned(),
+            Kind::Struct(vec![
+                ("a".to_owned(), Kind::Long),
+                ("s".to_owned(), Kind::String),
+            ]),
+        ),
+        ("n".to_owned(), Kind::Long),
+    ]);
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+    let mut batch = writer.row_batch(1024);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        for _ in 0..3 {
+            structs.push();
+        }
+        let mut fields = structs.fields().into_iter();
+        let mut outer = fields.next().unwrap().try_into_structs().unwrap();
+        let mut n = fields.next().unwrap().try_into_longs().unwrap();
+        outer.push();
+        outer.push_null();
+        outer.push();
+        let mut outer_fields = outer.fields().into_iter();
+        let mut a = outer_fields.next().unwrap().try_into_longs().unwrap();
+        let mut s = outer_fields.next().unwrap().try_into_strings().unwrap();
+        a.push(1);
+        a.push_null();
+        a.push_null();
+        s.push("x");
+        s.push_null();
+        s.push("z");
+        for i in 10..13 {
+            n.push(i);
+        }
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    let input_stream = reader::InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    let reader = reader::Reader::new(input_stream).unwrap();
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1024);
+    let mut objects = Vec::new();
+    while let Some(columns) = structured_row_reader.next() {
+        objects.extend(columntree_to_json_rows(columns));
+    }
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    let mut lines = Vec::new();
+    StructuredRowReader::new(&mut row_reader, 1024)
+        .for_each_batch(|columns| orcxx::to_json::write_json_lines(columns, &mut lines).unwrap());
+
+    assert_eq!(
+        String::from_utf8(lines).unwrap(),
+        "{\"outer\":{\"a\":1,\"s\":\"x\"},\"n\":10}\n\
+         {\"outer\":null,\"n\":11}\n\
+         {\"outer\":{\"a\":null,\"s\":\"z\"},\"n\":12}\n"
+    );
+    assert_eq!(
+        objects,
+        vec![
+            object! {"outer" => object! {"a" => 1, "s" => "x"}, "n" => 10},
+            object! {"outer" => JsonValue::Null, "n" => 11},
+            object! {"outer" => object! {"a" => JsonValue::Null, "s" => "z"}, "n" => 12},
+        ]
+    );
+}
+
+/// Asserts null lists and maps, and null values inside them, do not shift the
+/// values of the following rows
+#[test]
+fn nested_null_lists_and_maps() {
+    let kind = Kind::Struct(vec![
+        ("l".to_owned(), Kind::List(Box::new(Kind::Long))),
+        (
+            "m".to_owned(),
+            Kind::Map {
+                key: Box::new(Kind::String),
+                value: Box::new(Kind::Long),
+            },
+        ),
+        ("n".to_owned(), Kind::Long),
+    ]);
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+    let mut batch = writer.row_batch(1024);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        for _ in 0..4 {
+            structs.push();
+        }
+        let mut fields = structs.fields().into_iter();
+        let mut lists = fields.next().unwrap().try_into_lists().unwrap();
+        let mut maps = fields.next().unwrap().try_into_maps().unwrap();
+        let mut n = fields.next().unwrap().try_into_longs().unwrap();
+
+        // [1, null, 3], null, [], [null]
+        lists.push(3);
+        lists.push_null();
+        lists.push(0);
+        lists.push(1);
+        let mut elements = lists.elements().try_into_longs().unwrap();
+        elements.push(1);
+        elements.push_null();
+        elements.push(3);
+        elements.push_null();
+
+        // {"a": 1, "b": null}, null, {}, {"c": null}
+        maps.push(2);
+        maps.push_null();
+        maps.push(0);
+        maps.push(1);
+        let mut keys = maps.keys().try_into_strings().unwrap();
+        keys.push("a");
+        keys.push("b");
+        keys.push("c");
+        let mut values = maps.elements().try_into_longs().unwrap();
+        values.push(1);
+        values.push_null();
+        values.push_null();
+
+        for i in 10..14 {
+            n.push(i);
+        }
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    let input_stream = reader::InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    let reader = reader::Reader::new(input_stream).unwrap();
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1024);
+    let mut objects = Vec::new();
+    while let Some(columns) = structured_row_reader.next() {
+        objects.extend(columntree_to_json_rows(columns));
+    }
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    let mut lines = Vec::new();
+    StructuredRowReader::new(&mut row_reader, 1024)
+        .for_each_batch(|columns| orcxx::to_json::write_json_lines(columns, &mut lines).unwrap());
+
+    assert_eq!(
+        objects,
+        vec![
+            object! {
+                "l" => array![1, JsonValue::Null, 3],
+                "m" => array![
+                    object! {"key" => "a", "value" => 1},
+                    object! {"key" => "b", "value" => JsonValue::Null},
+                ],
+                "n" => 10,
+            },
+            object! {"l" => JsonValue::Null, "m" => JsonValue::Null, "n" => 11},
+            object! {"l" => array![], "m" => array![], "n" => 12},
+            object! {
+                "l" => array![JsonValue::Null],
+                "m" => array![object! {"key" => "c", "value" => JsonValue::Null}],
+                "n" => 13,
+            },
+        ]
+    );
+    let expected: Vec<_> = objects.iter().map(JsonValue::dump).collect();
+    let lines = String::from_utf8(lines).unwrap();
+    assert_eq!(lines.lines().collect::<Vec<_>>(), expected);
+}
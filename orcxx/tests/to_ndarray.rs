@@ -0,0 +1,58 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(not(feature = "ndarray"))]
+compile_error!("Feature 'ndarray' must be enabled for this test.");
+
+extern crate ndarray;
+extern crate orcxx;
+
+use ndarray::array;
+
+use orcxx::reader;
+use orcxx::structured_reader::StructuredRowReader;
+use orcxx::to_ndarray::{columntree_to_array2, ToNdarrayError};
+
+fn get_row_reader(columns: &[&str]) -> reader::RowReader {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let options = reader::RowReaderOptions::default().include_names(columns);
+    reader.row_reader(&options).unwrap()
+}
+
+#[test]
+fn numeric_columns() {
+    let mut row_reader = get_row_reader(&["boolean1", "int1", "float1", "double1"]);
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1024);
+    let columns = structured_row_reader.next().unwrap();
+    let matrix = columntree_to_array2(columns).unwrap();
+    assert!(!matrix.is_view());
+    assert_eq!(
+        matrix,
+        array![[0., 65536., 1., -15.], [1., 65536., 2., -5.]]
+    );
+}
+
+#[test]
+fn single_double_column() {
+    let mut row_reader = get_row_reader(&["double1"]);
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1024);
+    let columns = structured_row_reader.next().unwrap();
+    let matrix = columntree_to_array2(columns).unwrap();
+    assert!(matrix.is_view());
+    assert_eq!(matrix, array![[-15.], [-5.]]);
+}
+
+#[test]
+fn non_numeric_column() {
+    let mut row_reader = get_row_reader(&["int1", "string1"]);
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1024);
+    let columns = structured_row_reader.next().unwrap();
+    assert_eq!(
+        columntree_to_array2(columns).unwrap_err(),
+        ToNdarrayError::NotNumeric("string1".to_owned())
+    );
+}
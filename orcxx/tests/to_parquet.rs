@@ -0,0 +1,88 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(not(feature = "parquet"))]
+compile_error!("Feature 'parquet' must be enabled for this test.");
+
+extern crate arrow;
+extern crate orcxx;
+extern crate parquet;
+extern crate tempfile;
+
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::{DataType, Int32Type, Int64Type};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+use orcxx::reader;
+use orcxx::to_parquet::{kind_to_arrow_schema, write_parquet};
+
+#[test]
+fn test1_roundtrip() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    let expected_schema = kind_to_arrow_schema(&row_reader.selected_kind()).unwrap();
+
+    let mut file = tempfile::tempfile().unwrap();
+    write_parquet(&mut row_reader, 1, &mut file, None).unwrap();
+
+    let batches = ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+    let batch = arrow::compute::concat_batches(&batches[0].schema(), &batches).unwrap();
+    assert_eq!(
+        batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name())
+            .collect::<Vec<_>>(),
+        expected_schema
+            .fields()
+            .iter()
+            .map(|field| field.name())
+            .collect::<Vec<_>>()
+    );
+
+    let long1 = batch.column_by_name("long1").unwrap();
+    assert_eq!(
+        long1.as_primitive::<Int64Type>().values(),
+        &[9223372036854775807, 9223372036854775807]
+    );
+
+    let string1 = batch.column_by_name("string1").unwrap();
+    assert_eq!(
+        string1.as_string::<i32>().iter().collect::<Vec<_>>(),
+        vec![Some("hi"), Some("bye")]
+    );
+
+    let list = batch.column_by_name("list").unwrap().as_list::<i32>();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list.value_length(0), 2);
+    assert_eq!(list.value_length(1), 3);
+    let items = list.values().as_struct();
+    assert_eq!(
+        items
+            .column_by_name("int1")
+            .unwrap()
+            .as_primitive::<Int32Type>()
+            .values(),
+        &[3, 4, 100000000, -100000, 1234]
+    );
+
+    let map = batch.column_by_name("map").unwrap();
+    assert!(matches!(map.data_type(), DataType::Map(_, _)));
+    assert_eq!(map.as_map().value_length(0), 0);
+    assert_eq!(map.as_map().value_length(1), 2);
+}
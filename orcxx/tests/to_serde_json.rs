@@ -0,0 +1,132 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(not(feature = "serde_json"))]
+compile_error!("Feature 'serde_json' must be enabled for this test.");
+
+extern crate flate2;
+extern crate orcxx;
+extern crate pretty_assertions;
+#[macro_use]
+extern crate serde_json;
+
+use std::fs;
+use std::io::Read;
+
+use pretty_assertions::assert_eq;
+
+use orcxx::reader;
+use orcxx::structured_reader::StructuredRowReader;
+use orcxx::to_serde_json::{columntree_to_json_rows, write_json_lines};
+
+fn row_reader_options() -> reader::RowReaderOptions {
+    reader::RowReaderOptions::default().include_names([
+        "boolean1", "byte1", "long1", "float1", "double1", "bytes1", "string1", "list",
+    ])
+}
+
+#[test]
+fn test1() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let mut row_reader = reader.row_reader(&row_reader_options()).unwrap();
+
+    let mut objects = Vec::new();
+    StructuredRowReader::new(&mut row_reader, 1024)
+        .for_each_batch(|columns| objects.extend(columntree_to_json_rows(columns)));
+
+    assert_eq!(
+        objects,
+        vec![
+            json!({
+                "boolean1": false,
+                "byte1": 1,
+                "long1": i64::MAX,
+                "float1": 1.0,
+                "double1": -15.0,
+                "bytes1": [0, 1, 2, 3, 4],
+                "string1": "hi",
+                "list": [{"int1": 3, "string1": "good"}, {"int1": 4, "string1": "bad"}],
+            }),
+            json!({
+                "boolean1": true,
+                "byte1": 100,
+                "long1": i64::MAX,
+                "float1": 2.0,
+                "double1": -5.0,
+                "bytes1": [],
+                "string1": "bye",
+                "list": [
+                    {"int1": 100000000, "string1": "cat"},
+                    {"int1": -100000, "string1": "in"},
+                    {"int1": 1234, "string1": "hat"},
+                ],
+            }),
+        ]
+    );
+}
+
+/// Asserts keys are written in the order of the columns, and large integers are not
+/// rounded
+#[test]
+fn test1_lines() {
+    let orc_path = "orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let mut row_reader = reader.row_reader(&row_reader_options()).unwrap();
+
+    let mut lines = Vec::new();
+    StructuredRowReader::new(&mut row_reader, 1024)
+        .for_each_batch(|columns| write_json_lines(columns, &mut lines).unwrap());
+
+    assert_eq!(
+        String::from_utf8(lines).unwrap(),
+        "{\"boolean1\":false,\"byte1\":1,\"long1\":9223372036854775807,\"float1\":1.0,\
+         \"double1\":-15.0,\"bytes1\":[0,1,2,3,4],\"string1\":\"hi\",\
+         \"list\":[{\"int1\":3,\"string1\":\"good\"},{\"int1\":4,\"string1\":\"bad\"}]}\n\
+         {\"boolean1\":true,\"byte1\":100,\"long1\":9223372036854775807,\"float1\":2.0,\
+         \"double1\":-5.0,\"bytes1\":[],\"string1\":\"bye\",\
+         \"list\":[{\"int1\":100000000,\"string1\":\"cat\"},{\"int1\":-100000,\"string1\":\"in\"},\
+         {\"int1\":1234,\"string1\":\"hat\"}]}\n"
+    );
+}
+
+/// Equivalent of the `nulls_at_end_snappy` test of `read_apache.rs`, whose numbers
+/// overflow the `json` crate
+#[test]
+fn nulls_at_end_snappy() {
+    let orc_path = "orc/examples/nulls-at-end-snappy.orc";
+    let jsn_gz_path = "orc/examples/expected/nulls-at-end-snappy.jsn.gz";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+
+    let mut lines = Vec::new();
+    StructuredRowReader::new(&mut row_reader, 1024).for_each_batch(|columns| {
+        write_json_lines(columns, &mut lines).expect("Could not write");
+    });
+    let objects: Vec<serde_json::Value> = String::from_utf8(lines)
+        .expect("Invalid UTF-8")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Could not parse written line"))
+        .collect();
+
+    let mut expected_json = String::new();
+    flate2::read::GzDecoder::new(fs::File::open(jsn_gz_path).expect("Could not open .jsn.gz"))
+        .read_to_string(&mut expected_json)
+        .expect("Could not read .jsn.gz");
+    let expected_objects: Vec<serde_json::Value> = expected_json
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_str(line).expect("Could not parse line in .jsn.gz"))
+        .collect();
+
+    assert_eq!(reader.row_count(), objects.len() as u64);
+    // pretty_assertions consumes too much RAM and CPU on large diffs
+    assert!(objects == expected_objects);
+}
@@ -0,0 +1,305 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate pretty_assertions;
+extern crate tempfile;
+
+use std::collections::HashMap;
+
+use pretty_assertions::assert_eq;
+
+use orcxx::errors::WriteError;
+use orcxx::kind::Kind;
+use orcxx::reader::{FormatVersion, InputStream, Reader, RowReaderOptions, WriterId};
+use orcxx::structured_reader::{ColumnTree, StructuredRowReader};
+use orcxx::vector::{ColumnVectorBatchMut, OwnedColumnVectorBatch};
+use orcxx::writer::{Compression, OutputStream, Writer, WriterOptions};
+use orcxx::Timestamp;
+
+fn open(path: &str) -> Reader {
+    let input_stream = InputStream::from_local_file(path).expect("Could not open .orc");
+    Reader::new(input_stream).expect("Could not read .orc")
+}
+
+/// Copies test1 to a new file, and returns the path to the copy
+fn copy_test1(options: &WriterOptions) -> tempfile::TempPath {
+    let reader = open("orc/examples/TestOrcFile.test1.orc");
+    let mut row_reader = reader.row_reader(&RowReaderOptions::default()).unwrap();
+
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer =
+        Writer::new(output_stream, &reader.kind(), options).expect("Could not create writer");
+
+    // Small batches, so the file is written in several calls
+    let mut batch = row_reader.row_batch(1);
+    while row_reader.read_into(&mut batch) {
+        writer
+            .write_batch(&mut batch)
+            .expect("Could not write batch");
+    }
+    writer.close().expect("Could not close writer");
+
+    path
+}
+
+fn string1(reader: &Reader) -> (Vec<u8>, Vec<Option<std::ops::Range<usize>>>) {
+    let options = RowReaderOptions::default().include_names(["string1"]);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+
+    let struct_vector = batch.borrow().try_into_structs().unwrap();
+    let string1_vector = struct_vector.fields()[0].try_into_strings().unwrap();
+    (string1_vector.bytes().to_vec(), string1_vector.ranges())
+}
+
+#[test]
+fn copy() {
+    let path = copy_test1(&WriterOptions::default());
+
+    let original = open("orc/examples/TestOrcFile.test1.orc");
+    let copy = open(path.to_str().unwrap());
+
+    assert_eq!(copy.kind(), original.kind());
+    assert_eq!(copy.row_count(), 2);
+    assert_eq!(string1(&copy), string1(&original));
+}
+
+#[test]
+fn copy_with_options() {
+    for compression in [
+        Compression::None,
+        Compression::Zlib,
+        Compression::Snappy,
+        Compression::Lz4,
+        Compression::Zstd,
+    ] {
+        let options = WriterOptions::default()
+            .compression(compression)
+            .stripe_size(1024)
+            .compression_block_size(1024)
+            .row_index_stride(0);
+        let path = copy_test1(&options);
+
+        let copy = open(path.to_str().unwrap());
+        assert_eq!(copy.compression(), compression);
+        assert_eq!(copy.compression_block_size(), 1024, "{:?}", compression);
+        assert_eq!(copy.row_count(), 2, "{:?}", compression);
+        assert_eq!(
+            string1(&copy),
+            (b"hibye".to_vec(), vec![Some(0..2), Some(2..5)]),
+            "{:?}",
+            compression
+        );
+    }
+}
+
+#[test]
+fn file_metadata() {
+    let path = copy_test1(&WriterOptions::default());
+    let copy = open(path.to_str().unwrap());
+
+    assert_eq!(copy.writer_id(), WriterId::OrcCpp);
+    assert_eq!(
+        copy.format_version(),
+        FormatVersion {
+            major: 0,
+            minor: 12
+        }
+    );
+    assert_eq!(copy.format_version().to_string(), "0.12");
+    assert_eq!(copy.user_metadata(), HashMap::new());
+}
+
+#[test]
+fn write_new_batch() {
+    let kind = Kind::Struct(vec![
+        ("int1".to_owned(), Kind::Long),
+        ("string1".to_owned(), Kind::String),
+        ("double1".to_owned(), Kind::Double),
+    ]);
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+
+    // Smaller than the number of rows, so the batch needs to grow
+    let mut batch = writer.row_batch(1);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        for _ in 0..100 {
+            structs.push();
+        }
+        let mut fields = structs.fields().into_iter();
+        let mut ints = fields.next().unwrap().try_into_longs().unwrap();
+        let mut strings = fields.next().unwrap().try_into_strings().unwrap();
+        let mut doubles = fields.next().unwrap().try_into_doubles().unwrap();
+        for i in 0..100 {
+            ints.push(i);
+            strings.push(format!("row {}", i));
+            if i % 3 == 0 {
+                doubles.push_null();
+            } else {
+                doubles.push(i as f64 / 2.);
+            }
+        }
+        ints.set(1, -1);
+        strings.set(2, "overwritten");
+        strings.set_null(4);
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    let reader = open(path.to_str().unwrap());
+    assert_eq!(reader.kind(), kind);
+    assert_eq!(reader.row_count(), 100);
+
+    let mut row_reader = reader.row_reader(&RowReaderOptions::default()).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    let struct_vector = batch.borrow().try_into_structs().unwrap();
+    let fields = struct_vector.fields();
+
+    let ints: Vec<_> = fields[0].try_into_longs().unwrap().iter().collect();
+    let mut expected_ints: Vec<_> = (0..100).map(Some).collect();
+    expected_ints[1] = Some(-1);
+    assert_eq!(ints, expected_ints);
+
+    let strings: Vec<_> = fields[1]
+        .try_into_strings()
+        .unwrap()
+        .iter()
+        .map(|s| s.map(|s| String::from_utf8(s.to_vec()).unwrap()))
+        .collect();
+    let mut expected_strings: Vec<_> = (0..100).map(|i| Some(format!("row {}", i))).collect();
+    expected_strings[2] = Some("overwritten".to_owned());
+    expected_strings[4] = None;
+    assert_eq!(strings, expected_strings);
+
+    let doubles: Vec<_> = fields[2].try_into_doubles().unwrap().iter().collect();
+    let expected_doubles: Vec<_> = (0..100)
+        .map(|i| {
+            if i % 3 == 0 {
+                None
+            } else {
+                Some(i as f64 / 2.)
+            }
+        })
+        .collect();
+    assert_eq!(doubles, expected_doubles);
+}
+
+#[test]
+fn write_invalid_batch() {
+    let kind = Kind::Struct(vec![
+        ("int1".to_owned(), Kind::Long),
+        ("string1".to_owned(), Kind::String),
+    ]);
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+
+    // Fields with fewer rows than the structure
+    let mut batch = writer.row_batch(1);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        structs.push();
+        structs.push();
+        let mut fields = structs.fields().into_iter();
+        fields.next().unwrap().try_into_longs().unwrap().push(1);
+    }
+    assert_eq!(
+        writer.write_batch(&mut batch),
+        Err(WriteError::InvalidBatch(
+            "Column int1 has 1 rows, expected at least 2".to_owned()
+        ))
+    );
+
+    // Batch of an other type
+    let mut batch = OwnedColumnVectorBatch::new(&Kind::Struct(vec![]), 1).unwrap();
+    batch.borrow_mut().try_into_structs().unwrap().push();
+    assert_eq!(
+        writer.write_batch(&mut batch),
+        Err(WriteError::InvalidBatch(
+            "Column (root) has 0 fields, expected 2".to_owned()
+        ))
+    );
+
+    let mut batch = OwnedColumnVectorBatch::new(&Kind::Long, 1).unwrap();
+    assert!(matches!(
+        writer.write_batch(&mut batch),
+        Err(WriteError::InvalidBatch(_))
+    ));
+
+    writer.close().unwrap();
+}
+
+/// Writes and reads back a `timestamp with local time zone` column
+#[test]
+fn timestamp_instants() {
+    let kind = Kind::new("struct<ts:timestamp with local time zone>").unwrap();
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+
+    let values = [
+        Some((1_700_000_000, 123_456_789)),
+        None,
+        Some((0, 0)),
+        Some((-1, 500_000_000)),
+    ];
+    let mut batch = writer.row_batch(values.len() as u64);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        for _ in values {
+            structs.push();
+        }
+        let mut fields = structs.fields().into_iter();
+        let mut timestamps = fields.next().unwrap().try_into_timestamps().unwrap();
+        for value in values {
+            match value {
+                Some((seconds, nanoseconds)) => timestamps.push(seconds, nanoseconds),
+                None => timestamps.push_null(),
+            }
+        }
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    let reader = open(path.to_str().unwrap());
+    assert_eq!(reader.kind(), kind);
+    assert_eq!(
+        reader.kind(),
+        Kind::Struct(vec![("ts".to_owned(), Kind::TimestampInstant)])
+    );
+
+    let mut row_reader = reader.row_reader(&RowReaderOptions::default()).unwrap();
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, 1024);
+    let columns = structured_row_reader.next().unwrap();
+    let elements = match columns {
+        ColumnTree::Struct { elements, .. } => elements,
+        _ => panic!("Expected a struct, got {:?}", columns),
+    };
+    match &elements[0] {
+        (name, ColumnTree::TimestampInstant(column)) => {
+            assert_eq!(name, "ts");
+            assert_eq!(column.iter().collect::<Vec<_>>(), values);
+        }
+        (_, column) => panic!("Expected timestamp instants, got {:?}", column),
+    }
+
+    let timestamps: Vec<Option<Timestamp>> = reader.column_iter("ts").unwrap().collect();
+    assert_eq!(
+        timestamps,
+        values
+            .iter()
+            .map(|value| value.map(|(seconds, nanoseconds)| Timestamp {
+                seconds,
+                nanoseconds
+            }))
+            .collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,143 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Command-line tool to inspect ORC files
+//!
+//! ```text
+//! orcxx-cli cat [--columns <name1>,<name2>,...] <path>
+//! orcxx-cli schema <path>
+//! orcxx-cli count <path>
+//! orcxx-cli stats <path>
+//! orcxx-cli to-parquet <orc_path> <parquet_path>
+//! ```
+//!
+//! `to-parquet` needs the `parquet` feature.
+
+extern crate orcxx;
+
+use std::error::Error;
+#[cfg(feature = "parquet")]
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::{env, process};
+
+use orcxx::reader;
+use orcxx::statistics::ColumnSummary;
+use orcxx::structured_reader::StructuredRowReader;
+use orcxx::to_json::write_json_lines;
+#[cfg(feature = "parquet")]
+use orcxx::to_parquet::write_parquet;
+
+const USAGE: &str = "\
+Syntax:
+    orcxx-cli cat [--columns <name1>,<name2>,...] <path>
+    orcxx-cli schema <path>
+    orcxx-cli count <path>
+    orcxx-cli stats <path>
+    orcxx-cli to-parquet <orc_path> <parquet_path>
+
+Subcommands:
+    cat         Prints rows as JSON objects, one per line
+    schema      Prints the type of the file's rows
+    count       Prints the number of rows
+    stats       Prints the statistics of every column
+    to-parquet  Writes all rows to a Parquet file (needs the 'parquet' feature)
+";
+
+const BATCH_SIZE: u64 = 10240;
+
+fn open(orc_path: &str) -> Result<reader::Reader, Box<dyn Error>> {
+    let input_stream = reader::InputStream::from_local_file(orc_path)?;
+    Ok(reader::Reader::new(input_stream)?)
+}
+
+fn cat(orc_path: &str, columns: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let reader = open(orc_path)?;
+
+    let options = match columns {
+        None => reader::RowReaderOptions::default(),
+        Some(columns) => reader::RowReaderOptions::default().include_names(columns.split(',')),
+    };
+    let mut row_reader = reader.row_reader(&options)?;
+
+    let mut structured_row_reader = StructuredRowReader::new(&mut row_reader, BATCH_SIZE);
+
+    let stdout = io::stdout();
+    let mut stdout = BufWriter::new(stdout.lock());
+    while let Some(columns) = structured_row_reader.next() {
+        write_json_lines(columns, &mut stdout)?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+fn schema(orc_path: &str) -> Result<(), Box<dyn Error>> {
+    println!("{}", open(orc_path)?.kind().to_type_string());
+    Ok(())
+}
+
+fn count(orc_path: &str) -> Result<(), Box<dyn Error>> {
+    println!("{}", open(orc_path)?.row_count());
+    Ok(())
+}
+
+fn format_summary(summary: &ColumnSummary) -> String {
+    format!(
+        "values: {}, has_null: {}, {:?}",
+        summary.number_of_values, summary.has_null, summary.statistics
+    )
+}
+
+fn stats(orc_path: &str) -> Result<(), Box<dyn Error>> {
+    let reader = open(orc_path)?;
+    let statistics = reader.statistics().ok_or("File has no statistics")?;
+    for (column_id, path) in reader.columns() {
+        let path = if path.is_empty() { "<root>" } else { &path };
+        let summary = statistics
+            .get(column_id as usize)
+            .and_then(Option::as_ref)
+            .map(format_summary)
+            .unwrap_or_else(|| "no statistics".to_owned());
+        println!("{}\t{}\t{}", column_id, path, summary);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+fn to_parquet(orc_path: &str, parquet_path: &str) -> Result<(), Box<dyn Error>> {
+    let reader = open(orc_path)?;
+    let mut row_reader = reader.row_reader(&reader::RowReaderOptions::default())?;
+
+    let mut output = BufWriter::new(File::create(parquet_path)?);
+    write_parquet(&mut row_reader, BATCH_SIZE, &mut output, None)?;
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn to_parquet(_orc_path: &str, _parquet_path: &str) -> Result<(), Box<dyn Error>> {
+    Err("orcxx-cli was built without the 'parquet' feature".into())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        [_, "cat", path] => cat(path, None),
+        [_, "cat", "--columns", columns, path] => cat(path, Some(columns)),
+        [_, "schema", path] => schema(path),
+        [_, "count", path] => count(path),
+        [_, "stats", path] => stats(path),
+        [_, "to-parquet", orc_path, parquet_path] => to_parquet(orc_path, parquet_path),
+        _ => {
+            io::stderr().write_all(USAGE.as_bytes()).unwrap();
+            process::exit(1);
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("orcxx-cli: {}", e);
+        process::exit(1);
+    }
+}
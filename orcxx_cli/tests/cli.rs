@@ -0,0 +1,90 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::process::{Command, Output};
+
+const ORC_PATH: &str = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_orcxx-cli"))
+        .args(args)
+        .output()
+        .expect("Could not run orcxx-cli")
+}
+
+fn stdout(args: &[&str]) -> String {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn cat() {
+    assert_eq!(
+        stdout(&["cat", "--columns", "int1,string1", ORC_PATH]),
+        "{\"int1\":65536,\"string1\":\"hi\"}\n{\"int1\":65536,\"string1\":\"bye\"}\n"
+    );
+}
+
+#[test]
+fn schema() {
+    assert_eq!(
+        stdout(&["schema", ORC_PATH]),
+        "struct<boolean1:boolean,byte1:tinyint,short1:smallint,int1:int,long1:bigint,\
+         float1:float,double1:double,bytes1:binary,string1:string,\
+         middle:struct<list:array<struct<int1:int,string1:string>>>,\
+         list:array<struct<int1:int,string1:string>>,\
+         map:map<string,struct<int1:int,string1:string>>>\n"
+    );
+}
+
+#[test]
+fn count() {
+    assert_eq!(stdout(&["count", ORC_PATH]), "2\n");
+}
+
+#[test]
+fn stats() {
+    let output = stdout(&["stats", ORC_PATH]);
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines[0], "0\t<root>\tvalues: 2, has_null: false, Generic");
+    assert_eq!(
+        lines[4],
+        "4\tint1\tvalues: 2, has_null: false, Integer { min: 65536, max: 65536, sum: Some(131072) }"
+    );
+}
+
+#[test]
+fn errors() {
+    let output = run(&["count"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Syntax:"));
+
+    let output = run(&["count", "does-not-exist.orc"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("orcxx-cli: "));
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn to_parquet() {
+    let parquet_path = format!("{}/test1.parquet", env!("CARGO_TARGET_TMPDIR"));
+    assert_eq!(stdout(&["to-parquet", ORC_PATH, &parquet_path]), "");
+    let parquet_file = std::fs::read(&parquet_path).unwrap();
+    assert_eq!(&parquet_file[0..4], b"PAR1");
+    assert_eq!(&parquet_file[parquet_file.len() - 4..], b"PAR1");
+}
+
+#[cfg(not(feature = "parquet"))]
+#[test]
+fn to_parquet() {
+    let output = run(&["to-parquet", ORC_PATH, "test1.parquet"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("orcxx-cli: "));
+}
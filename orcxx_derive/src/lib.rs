@@ -13,8 +13,16 @@
 //! * [`bool`], [`i8`], [`i16`], [`i32`], [`i64`], [`f32`], [`f64`], [`String`], [`Vec<u8>`](Vec),
 //!   mapping to their respective ORC type
 //! * `Vec<T>` when `T` is a supported type, mapping to an ORC list
-//! * `HashMap<K, V>` and `Vec<(K, V)>` are not supported yet to deserialize ORC maps
-//!   (see <https://gitlab.softwareheritage.org/swh/devel/orcxx-rs/-/issues/1>)
+//! * [`Bitmap`](../orcxx/vector/struct.Bitmap.html), mapping to an ORC list of
+//!   booleans packed 64 per word, which is much smaller than `Vec<bool>`
+//! * `Box<T>`, `Rc<T>`, and `Arc<T>` when `T` is a supported type, mapping to the
+//!   same ORC type as `T`, so large nested structures can be shared without cloning
+//! * `HashMap<K, V>`, `BTreeMap<K, V>`, and `Vec<(K, V)>` when `K` and `V` are
+//!   supported types, mapping to an ORC map (`Vec<(K, V)>` keeps entries in the
+//!   order they are stored in)
+//!
+//! Structures may have lifetime and type parameters, as long as the type of each
+//! of their fields is supported.
 //!
 //! # About null values
 //!
@@ -26,6 +34,199 @@
 //! If you omit `Option`, then `orcxx_derive` will return an error early for files
 //! containing null values, and avoid this overhead for files which don't.
 //!
+//! `CheckableKind::check_kind_with_stats` tells ahead of time whether a given file
+//! can be read without `Option`, by looking for columns which may contain nulls in
+//! its statistics.
+//!
+//! # Structures without `Default`
+//!
+//! Structures do not need to implement [`Default`]: rows of `Option<T>` are built
+//! from the values of their fields, once all columns are read. However, [`Default`]
+//! is needed to read them without `Option` (eg. `RowIterator<T>` or `Vec<T>` fields)
+//! because their buffers are allocated before being filled; and all fields must
+//! implement [`Default`] for the same reason (which all supported types do, when
+//! structures are wrapped in `Option`).
+//!
+//! # Enums
+//!
+//! Enums map to ORC unions (`uniontype<...>`): each of their variants must have a
+//! single unnamed field, whose type maps to the union's variant at the same
+//! position (eg. `uniontype<int,string>` can be read into
+//! `enum IntOrString { Int(i32), String(String) }`). As with structures, variants
+//! should be wrapped in `Option` if they may contain null values, and the enum itself
+//! if the union may be null.
+//!
+//! # Borrowed fields
+//!
+//! `#[derive(OrcDeserializeRef)]` on a structure with a lifetime parameter `'a`
+//! implements `OrcDeserializeRef<'a>` instead of `OrcDeserialize`, so its fields
+//! can be `&'a str`, `&'a [u8]` (or `Option`s of them), borrowing from the batch
+//! they are read from instead of being copied, which is faster when scanning large
+//! files. Other fields can have any type supported by `OrcDeserialize` (which is
+//! copied as usual) or be structures deriving `OrcDeserializeRef`, but borrowing
+//! values cannot be in lists, maps, or `Option`s of structures.
+//!
+//! Rows are read with `OrcDeserializeRef::ref_from_vector_batch` (or
+//! `read_ref_from_vector_batch_with_kind`), and cannot outlive the batch:
+//!
+//! ```
+//! extern crate orcxx;
+//! extern crate orcxx_derive;
+//!
+//! use orcxx::deserialize::{CheckableKind, OrcDeserializeRef, OrcStruct};
+//! use orcxx::reader;
+//! use orcxx_derive::OrcDeserializeRef;
+//!
+//! #[derive(OrcDeserializeRef, Default, Debug, PartialEq, Eq)]
+//! struct Test1<'a> {
+//!     int1: i32,
+//!     string1: &'a str,
+//! }
+//!
+//! let orc_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+//! let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+//! let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let options = reader::RowReaderOptions::default().include_names(Test1::columns());
+//! let mut row_reader = reader.row_reader(&options).expect("Could not open ORC file");
+//! Test1::check_kind(&row_reader.selected_kind()).expect("Unexpected schema");
+//!
+//! let mut total_length = 0;
+//! let mut batch = row_reader.row_batch(1024);
+//! while row_reader.read_into(&mut batch) {
+//!     let batch = batch.borrow();
+//!     let rows = Test1::ref_from_vector_batch(&batch).unwrap();
+//!     total_length += rows.iter().map(|row| row.string1.len()).sum::<usize>();
+//! }
+//! assert_eq!(total_length, "hi".len() + "bye".len());
+//! ```
+//!
+//! # Lazy rows
+//!
+//! `#[orc(lazy)]` on a structure `T` (which must not have generic parameters) also
+//! generates a trait `TLazyRow`, implemented by `orcxx::lazy_row::LazyRow<'_, T>`,
+//! with a method returning a reference to the value of each field (which panics
+//! if the field cannot be deserialized), and a `try_` method returning a
+//! `Result` instead. Batches read by `LazyRowIterator` are not deserialized;
+//! instead, each field is deserialized (in the whole batch) the first time it is
+//! accessed, so columns which are not used are not deserialized.
+//!
+//! This needs the `lazy_row` feature:
+//!
+#![cfg_attr(feature = "lazy_row", doc = "```")]
+#![cfg_attr(not(feature = "lazy_row"), doc = "```ignore")]
+//! extern crate orcxx;
+//! extern crate orcxx_derive;
+//!
+//! use std::num::NonZeroU64;
+//!
+//! use orcxx::lazy_row::LazyRowIterator;
+//! use orcxx::reader;
+//! use orcxx_derive::OrcDeserialize;
+//!
+//! #[derive(OrcDeserialize, Default, Debug, PartialEq, Eq)]
+//! #[orc(lazy)]
+//! struct Test1 {
+//!     int1: i32,
+//!     string1: String,
+//!     bytes1: Vec<u8>,
+//! }
+//!
+//! let orc_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+//! let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+//! let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+//!
+//! let mut rows = LazyRowIterator::<Test1>::new(&reader, NonZeroU64::new(1024).unwrap())
+//!     .expect("Unexpected schema");
+//! let mut strings = Vec::new();
+//! while let Some(batch) = rows.next_batch() {
+//!     for row in batch.rows() {
+//!         // string1 is only deserialized in batches where it is needed, and
+//!         // bytes1 is never deserialized
+//!         if *row.int1() > 0 {
+//!             strings.push(row.try_string1().expect("Invalid string").clone());
+//!         }
+//!     }
+//! }
+//! assert_eq!(strings, vec!["hi".to_owned(), "bye".to_owned()]);
+//! ```
+//!
+//! Field attributes apply to lazy rows too.
+//!
+//! # Selecting columns
+//!
+//! By default, `check_kind` requires the selected columns to be exactly the
+//! fields of the structure, in the same order (as with
+//! `RowReaderOptions::include_names(T::columns())`, which `RowIterator` uses).
+//!
+//! With `#[orc(match_by_name)]`, fields are read from the columns of the same
+//! name, and columns which are not fields of the structure are ignored, so a
+//! structure may describe only a subset of a file's columns (eg. to read with
+//! default `RowReaderOptions`, and keep working when columns are added to the
+//! file). Matching columns by name needs their kind, so batches must be read
+//! with `OrcDeserialize::read_from_vector_batch_with_kind` (as `RowIterator`
+//! does); without it, fields are matched by position.
+//!
+//! # Structure attributes
+//!
+//! * `#[orc(match_by_name)]` matches fields to the columns of the same name,
+//!   instead of requiring them to be in the same order as in the file, and
+//!   ignores other columns (see [Selecting columns](#selecting-columns)).
+//! * `#[orc(lazy)]` generates accessors to deserialize fields on first access (see
+//!   [Lazy rows](#lazy-rows)).
+//!
+//! # Field attributes
+//!
+//! Attributes other than `#[orc(...)]` are ignored, so structures can also derive
+//! other traits, such as `serde`'s `Serialize` and `Deserialize`.
+//!
+//! * `#[orc(unsafe_assume_utf8)]` on a `String`, `Option<String>`, `&str` or
+//!   `Option<&str>` field skips UTF-8 validation of the column. By default, each
+//!   batch of strings is validated at once, which is cheap but not free. Only use
+//!   this attribute on files you trust: reading invalid UTF-8 with it is undefined
+//!   behavior. Therefore, the structure must also implement the unsafe
+//!   `orcxx::deserialize::AssumeUtf8` marker trait:
+//!
+//! ```
+//! extern crate orcxx;
+//! extern crate orcxx_derive;
+//!
+//! use orcxx_derive::OrcDeserialize;
+//!
+//! #[derive(OrcDeserialize, Default, Debug, PartialEq)]
+//! struct Test1 {
+//!     #[orc(unsafe_assume_utf8)]
+//!     string1: String,
+//! }
+//!
+//! // Safe because we only read files we wrote, from valid strings
+//! unsafe impl orcxx::deserialize::AssumeUtf8 for Test1 {}
+//! ```
+//!
+//! ```compile_fail
+//! extern crate orcxx;
+//! extern crate orcxx_derive;
+//!
+//! use orcxx_derive::OrcDeserialize;
+//!
+//! #[derive(OrcDeserialize, Default, Debug, PartialEq)]
+//! struct Test1 {
+//!     #[orc(unsafe_assume_utf8)]
+//!     string1: String,
+//! }
+//! ```
+//!
+//! * `#[orc(rename = "columnName")]` reads the field from the column with the
+//!   given name instead of the field's name, for columns whose name is not a
+//!   valid or idiomatic Rust identifier.
+//! * `#[orc(default)]` fills the field with `Default::default()` when its column
+//!   is missing from the file, instead of failing, so files written before the
+//!   column was added can still be read. `#[orc(default = "expression")]` fills it
+//!   with the given expression instead. `RowIterator::new` only selects columns
+//!   which exist in the file (see `OrcStruct::columns_in`). Without the kind of the
+//!   columns (eg. with `OrcDeserialize::from_vector_batch`), fields are matched to
+//!   columns by position, so only fields at the end of the structure can be missing.
+//!
 //! # Panics
 //!
 //! See [`orcxx`'s documentation](../orcxx/#panics).
@@ -74,6 +275,24 @@
 //! );
 //! ```
 //!
+//! Or, when opening the file yourself is not needed:
+//!
+//! ```
+//! extern crate orcxx;
+//! extern crate orcxx_derive;
+//!
+//! use orcxx_derive::OrcDeserialize;
+//!
+//! #[derive(OrcDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+//! struct Test1 {
+//!     long1: Option<i64>,
+//! }
+//!
+//! let orc_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+//! let rows: Vec<Option<Test1>> = orcxx::read_file(orc_path).expect("Could not read .orc");
+//! assert_eq!(rows.len(), 2);
+//! ```
+//!
 //! Or equivalently, to avoid cloning structures:
 //!
 //! <!-- Keep this in sync with README.md -->
@@ -165,38 +384,120 @@ extern crate syn;
 
 use proc_macro::TokenStream;
 use proc_macro2::Ident;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
+use syn::ext::IdentExt;
 use syn::*;
 
-/// `#[derive(OrcDeserialize)] struct T { ... }` implements
+/// `#[derive(OrcDeserialize)] struct T { ... }` or `enum T { ... }` implements
 /// [`OrcDeserialize`](../orcxx/deserialize/struct.OrcDeserialize.html),
 /// [`CheckableKind`](../orcxx/deserialize/struct.CheckableKind.html), and
 /// [`OrcStruct`](../orcxx/deserialize/struct.OrcStruct.html) for `T`
 ///
 /// This automatically gives implementations for `Option<T>` and `Vec<T>` as well.
-#[proc_macro_derive(OrcDeserialize)]
+#[proc_macro_derive(OrcDeserialize, attributes(orc))]
 pub fn orc_deserialize(input: TokenStream) -> TokenStream {
+    derive(input, false)
+}
+
+/// `#[derive(OrcDeserializeRef)] struct T<'a> { ... }` implements
+/// [`OrcDeserializeRef<'a>`](../orcxx/deserialize/struct.OrcDeserializeRef.html),
+/// [`CheckableKind`](../orcxx/deserialize/struct.CheckableKind.html), and
+/// [`OrcStruct`](../orcxx/deserialize/struct.OrcStruct.html) for `T`, whose fields
+/// may then borrow from batches (see [Borrowed fields](#borrowed-fields)).
+#[proc_macro_derive(OrcDeserializeRef, attributes(orc))]
+pub fn orc_deserialize_ref(input: TokenStream) -> TokenStream {
+    derive(input, true)
+}
+
+/// Shared implementation of `#[derive(OrcDeserialize)]` and, if `borrowed` is
+/// `true`, `#[derive(OrcDeserializeRef)]`
+fn derive(input: TokenStream, borrowed: bool) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
 
     let tokens = match ast.data {
         Data::Struct(DataStruct {
             fields: Fields::Named(FieldsNamed { named, .. }),
             ..
-        }) => impl_struct(
-            &ast.ident,
-            named
+        }) => {
+            let struct_attributes = match StructAttributes::parse(&ast.attrs) {
+                Ok(struct_attributes) => struct_attributes,
+                Err(e) => return compile_error(e),
+            };
+            let field_attributes = match named
                 .iter()
-                .map(|field| {
-                    field
-                        .ident
-                        .as_ref()
-                        .expect("#ident must not have anonymous fields")
-                })
-                .collect(),
-            named.iter().map(|field| &field.ty).collect(),
-        ),
+                .map(FieldAttributes::parse)
+                .collect::<Result<Vec<_>>>()
+            {
+                Ok(field_attributes) => field_attributes,
+                Err(e) => return compile_error(e),
+            };
+            let lifetime =
+                if borrowed {
+                    match ast.generics.lifetimes().next() {
+                        Some(param) => Some(param.lifetime.clone()),
+                        None => return compile_error(Error::new_spanned(
+                            &ast.ident,
+                            "OrcDeserializeRef needs a lifetime parameter to borrow from batches",
+                        )),
+                    }
+                } else {
+                    None
+                };
+            let lazy_impl = if struct_attributes.lazy {
+                if cfg!(not(feature = "lazy_row")) {
+                    return compile_error(Error::new_spanned(
+                        &ast.ident,
+                        "#[orc(lazy)] needs the lazy_row feature of orcxx_derive",
+                    ));
+                }
+                if !ast.generics.params.is_empty() {
+                    return compile_error(Error::new_spanned(
+                        &ast.ident,
+                        "#[orc(lazy)] is not supported on structures with generic parameters",
+                    ));
+                }
+                impl_lazy(&ast.vis, &ast.ident, &named, &field_attributes)
+            } else {
+                quote!()
+            };
+            let struct_impl: proc_macro2::TokenStream = impl_struct(
+                &ast.ident,
+                &ast.generics,
+                named
+                    .iter()
+                    .map(|field| {
+                        field
+                            .ident
+                            .as_ref()
+                            .expect("#ident must not have anonymous fields")
+                    })
+                    .collect(),
+                named.iter().map(|field| &field.ty).collect(),
+                struct_attributes,
+                field_attributes,
+                lifetime,
+            )
+            .into();
+            quote!(
+                #struct_impl
+                #lazy_impl
+            )
+            .into()
+        }
         Data::Struct(DataStruct { .. }) => panic!("#ident must have named fields"),
-        _ => panic!("#ident must be a structure"),
+        Data::Enum(DataEnum { .. }) if borrowed => {
+            return compile_error(Error::new_spanned(
+                &ast.ident,
+                "OrcDeserializeRef cannot be derived on enums",
+            ))
+        }
+        Data::Enum(DataEnum { variants, .. }) => {
+            match impl_enum(&ast.ident, &ast.generics, &variants) {
+                Ok(tokens) => tokens,
+                Err(e) => return compile_error(e),
+            }
+        }
+        _ => panic!("#ident must be a structure or an enum"),
     };
 
     //eprintln!("{}", tokens);
@@ -204,41 +505,382 @@ pub fn orc_deserialize(input: TokenStream) -> TokenStream {
     tokens
 }
 
-fn impl_struct(ident: &Ident, field_names: Vec<&Ident>, field_types: Vec<&Type>) -> TokenStream {
+/// Returns a call to `compile_error!` with the error's message
+///
+/// Unlike [`Error::to_compile_error`], this does not refer to `::core`, which
+/// does not resolve in crates using the 2015 edition.
+fn compile_error(error: Error) -> TokenStream {
+    let message = error.to_string();
+    quote_spanned!(error.span()=> compile_error!(#message);).into()
+}
+
+/// Options set on a structure with `#[orc(...)]`
+#[derive(Default)]
+struct StructAttributes {
+    /// `#[orc(match_by_name)]`: match fields to columns by name instead of position
+    match_by_name: bool,
+    /// `#[orc(lazy)]`: generate accessors for `orcxx::lazy_row::LazyRow`
+    lazy: bool,
+}
+
+impl StructAttributes {
+    fn parse(attrs: &[Attribute]) -> Result<StructAttributes> {
+        let mut attributes = StructAttributes::default();
+        for attr in attrs {
+            if !attr.path().is_ident("orc") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("match_by_name") {
+                    attributes.match_by_name = true;
+                    Ok(())
+                } else if meta.path.is_ident("lazy") {
+                    attributes.lazy = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported orc attribute"))
+                }
+            })?;
+        }
+        Ok(attributes)
+    }
+}
+
+/// Options set on a field with `#[orc(...)]`
+#[derive(Default)]
+struct FieldAttributes {
+    /// `#[orc(unsafe_assume_utf8)]`: skip UTF-8 validation of strings
+    assume_utf8: bool,
+    /// `#[orc(rename = "...")]`: name of the column, if different from the field's
+    rename: Option<String>,
+    /// `#[orc(default)]` or `#[orc(default = "...")]`: value of the field when its
+    /// column is missing
+    default: Option<Expr>,
+}
+
+impl FieldAttributes {
+    fn parse(field: &Field) -> Result<FieldAttributes> {
+        let mut attributes = FieldAttributes::default();
+        for attr in &field.attrs {
+            if !attr.path().is_ident("orc") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("unsafe_assume_utf8") {
+                    if !is_string_type(&field.ty) {
+                        return Err(Error::new_spanned(
+                            &field.ty,
+                            "#[orc(unsafe_assume_utf8)] is only supported on String, \
+                             Option<String>, &str, and Option<&str> fields",
+                        ));
+                    }
+                    attributes.assume_utf8 = true;
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    attributes.rename = Some(name.value());
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    attributes.default = Some(if meta.input.peek(Token![=]) {
+                        let expr: LitStr = meta.value()?.parse()?;
+                        expr.parse()?
+                    } else {
+                        parse_quote!(::std::default::Default::default())
+                    });
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported orc attribute"))
+                }
+            })?;
+        }
+        Ok(attributes)
+    }
+
+    /// Returns the name of the ORC column the field is read from
+    fn column_name(&self, field_name: &Ident) -> String {
+        match &self.rename {
+            Some(name) => name.clone(),
+            None => field_name.unraw().to_string(),
+        }
+    }
+
+    /// Returns code finding the field's column, then running `read` on it; or
+    /// running `fill_default` with the field's default value if the column is
+    /// missing and the field has one.
+    fn read_column(
+        &self,
+        field_name: &Ident,
+        field_index: usize,
+        read: proc_macro2::TokenStream,
+        fill_default: impl Fn(&Expr) -> proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let column_name = self.column_name(field_name);
+        let get_column = quote!(
+            let (column, field_kind): (&BorrowedColumnVectorBatch, _) = field?;
+        );
+        // Without the kind, columns missing at the end of the structure are only
+        // known from the number of columns
+        let field = quote!(
+            ::orcxx::deserialize::struct_field(orcxx_fields, #field_index, #column_name)
+                .and_then(|(column_index, field_kind)| match columns.get(column_index) {
+                    Some(column) => Ok((column, field_kind)),
+                    None => Err(DeserializationError::MissingField(#column_name.to_owned())),
+                })
+        );
+        match &self.default {
+            None => quote!(
+                let field = #field;
+                #get_column
+                #read
+            ),
+            Some(default) => {
+                let fill_default = fill_default(default);
+                quote!(
+                    match #field {
+                        Err(DeserializationError::MissingField(_)) => {
+                            #fill_default
+                        }
+                        field => {
+                            #get_column
+                            #read
+                        }
+                    }
+                )
+            }
+        }
+    }
+
+    /// Returns code deserializing `column` (whose kind is `field_kind`, if known)
+    /// into the field, given the type of deserialization target and an expression
+    /// building it; with `OrcDeserializeRef` if `borrowed` is `true`
+    fn read_field(
+        &self,
+        field_name: &Ident,
+        target_type: proc_macro2::TokenStream,
+        target: proc_macro2::TokenStream,
+        borrowed: bool,
+    ) -> proc_macro2::TokenStream {
+        let column_name = self.column_name(field_name);
+        if self.assume_utf8 {
+            let read_assume_utf8 = if borrowed {
+                quote!(
+                    ::orcxx::deserialize::OrcDeserializeRef::read_ref_from_vector_batch_assume_utf8
+                )
+            } else {
+                quote!(OrcDeserialize::read_from_vector_batch_assume_utf8)
+            };
+            quote!(
+                {
+                    fn orcxx_assume_utf8<T: ?Sized + ::orcxx::deserialize::AssumeUtf8>() {}
+                    orcxx_assume_utf8::<Self>();
+                }
+                let field_dst = &mut #target;
+                // Safe because the user asserted strings in this column are valid UTF-8,
+                // by implementing AssumeUtf8
+                unsafe {
+                    #read_assume_utf8::<#target_type>(
+                        column,
+                        field_dst,
+                    )
+                }
+                .map_err(|e| e.in_column(#column_name))?;
+            )
+        } else {
+            let read_with_optional_kind = if borrowed {
+                quote!(::orcxx::deserialize::read_ref_from_vector_batch_with_optional_kind)
+            } else {
+                quote!(::orcxx::deserialize::read_from_vector_batch_with_optional_kind)
+            };
+            quote!(
+                #read_with_optional_kind::<_, #target_type>(
+                    column,
+                    field_kind,
+                    &mut #target,
+                )
+                .map_err(|e| e.in_column(#column_name))?;
+            )
+        }
+    }
+}
+
+/// Returns whether `ty` is `String`, `&str`, or an `Option` of either, which are
+/// the only types `#[orc(unsafe_assume_utf8)]` changes the deserialization of
+fn is_string_type(ty: &Type) -> bool {
+    let is_string_or_str = |ty: &Type| match ty {
+        Type::Reference(TypeReference { elem, .. }) => {
+            matches!(&**elem, Type::Path(path) if path.path.is_ident("str"))
+        }
+        Type::Path(TypePath { qself: None, path }) => path.is_ident("String"),
+        _ => false,
+    };
+    if is_string_or_str(ty) {
+        return true;
+    }
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => match path.segments.last() {
+            Some(segment) if segment.ident == "Option" => match &segment.arguments {
+                PathArguments::AngleBracketed(arguments) => {
+                    matches!(
+                        arguments.args.iter().collect::<Vec<_>>()[..],
+                        [GenericArgument::Type(ty)] if is_string_or_str(ty)
+                    )
+                }
+                _ => false,
+            },
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn impl_struct(
+    ident: &Ident,
+    generics: &Generics,
+    field_names: Vec<&Ident>,
+    field_types: Vec<&Type>,
+    struct_attributes: StructAttributes,
+    field_attributes: Vec<FieldAttributes>,
+    lifetime: Option<Lifetime>,
+) -> TokenStream {
     let num_fields = field_names.len();
-    let unescaped_field_names: Vec<_> = field_names
+    let read_fields: Vec<_> = field_attributes
+        .iter()
+        .zip(field_names.iter())
+        .enumerate()
+        .map(|(field_index, (attributes, field_name))| {
+            let read = attributes.read_field(
+                field_name,
+                quote!(::orcxx::deserialize::MultiMap<&mut OrcxxTarget, _>),
+                quote!(dst.map(|struct_| &mut struct_.#field_name)),
+                lifetime.is_some(),
+            );
+            attributes.read_column(field_name, field_index, read, |default| {
+                quote!(
+                    for struct_ in dst.iter_mut().take(num_elements) {
+                        struct_.#field_name = #default;
+                    }
+                )
+            })
+        })
+        .collect();
+    // Nullable structures are built from their fields once all of them are read,
+    // so they do not need to implement Default
+    let field_values: Vec<_> = (0..num_fields)
+        .map(|i| format_ident!("orcxx_field_{}", i))
+        .collect();
+    let read_option_fields: Vec<_> = field_attributes
+        .iter()
+        .zip(field_names.iter())
+        .zip(field_types.iter().zip(field_values.iter()))
+        .enumerate()
+        .map(
+            |(field_index, ((attributes, field_name), (field_type, field_value)))| {
+                let read = attributes.read_field(
+                    field_name,
+                    quote!(Vec<#field_type>),
+                    quote!(#field_value),
+                    false,
+                );
+                attributes.read_column(field_name, field_index, read, |default| {
+                    quote!(
+                        for value in #field_value.iter_mut() {
+                            *value = #default;
+                        }
+                    )
+                })
+            },
+        )
+        .collect();
+    let column_names: Vec<_> = field_attributes
         .iter()
-        .map(|field_name| format_ident!("{}", field_name))
+        .zip(field_names.iter())
+        .map(|(attributes, field_name)| attributes.column_name(field_name))
         .collect();
+    let has_defaults: Vec<_> = field_attributes
+        .iter()
+        .map(|attributes| attributes.default.is_some())
+        .collect();
+    let field_indexes: Vec<_> = (0..num_fields).collect();
+    let match_by_name = struct_attributes.match_by_name;
+
+    // Generic structures need their fields to be deserializable, which cannot be
+    // checked on type parameters alone (eg. `Option<T>` needs `T: OrcDeserializeOption`)
+    let mut generics = generics.clone();
+    if !generics.params.is_empty() {
+        let where_clause = generics.make_where_clause();
+        for field_type in &field_types {
+            where_clause.predicates.push(match &lifetime {
+                Some(lifetime) => parse_quote!(
+                    #field_type: ::orcxx::deserialize::OrcDeserializeRef<#lifetime>
+                        + ::orcxx::deserialize::OrcStruct
+                ),
+                None => parse_quote!(
+                    #field_type: ::orcxx::deserialize::OrcDeserialize
+                        + ::orcxx::deserialize::OrcStruct
+                        + ::std::default::Default
+                ),
+            });
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     let check_kind_impl = quote!(
-        impl ::orcxx::deserialize::CheckableKind for #ident {
+        impl #impl_generics ::orcxx::deserialize::CheckableKind for #ident #ty_generics #where_clause {
             fn check_kind(kind: &::orcxx::kind::Kind) -> Result<(), String> {
                 use ::orcxx::kind::Kind;
 
                 match kind {
                     Kind::Struct(fields) => {
-                        let mut fields = fields.iter().enumerate();
+                        let expected_fields: &[(&str, fn(&Kind) -> Result<(), String>, bool)] = &[
+                            #(
+                                (
+                                    #column_names,
+                                    <#field_types as ::orcxx::deserialize::CheckableKind>::check_kind,
+                                    #has_defaults,
+                                ),
+                            )*
+                        ];
                         let mut errors = Vec::new();
-                        #(
-                            match fields.next() {
+                        // Without #[orc(match_by_name)], fields must be exactly the
+                        // columns, in the same order, except missing fields with a
+                        // default value.
+                        let mut position = 0;
+                        for &(column_name, check_field_kind, has_default) in expected_fields {
+                            let field = if #match_by_name {
+                                fields.iter().enumerate().find(|(_, (field_name, _))| field_name == column_name)
+                            } else if has_default && !fields.iter().any(|(field_name, _)| field_name == column_name) {
+                                None
+                            } else {
+                                position += 1;
+                                fields.iter().enumerate().nth(position - 1)
+                            };
+                            match field {
                                 Some((i, (field_name, field_type))) => {
-                                    if field_name != stringify!(#unescaped_field_names) {
+                                    if field_name != column_name {
                                         errors.push(format!(
                                                 "Field #{} must be called {}, not {}",
-                                                i, stringify!(#unescaped_field_names), field_name))
+                                                i, column_name, field_name))
                                     }
-                                    else if let Err(s) = <#field_types>::check_kind(field_type) {
+                                    else if let Err(s) = check_field_kind(field_type) {
                                         errors.push(format!(
                                             "Field {} cannot be decoded: {}",
-                                            stringify!(#unescaped_field_names), s));
+                                            column_name, s));
                                     }
                                 },
+                                // Filled with its default value instead
+                                None if has_default => {}
                                 None => errors.push(format!(
                                     "Field {} is missing",
-                                    stringify!(#unescaped_field_names)))
+                                    column_name))
                             }
-                        )*
+                        }
+                        if !#match_by_name {
+                            for (field_name, _) in fields.iter().skip(position) {
+                                errors.push(format!(
+                                    "Column {} is not a field of the structure",
+                                    field_name))
+                            }
+                        }
 
                         if errors.is_empty() {
                             Ok(())
@@ -256,29 +898,57 @@ fn impl_struct(ident: &Ident, field_names: Vec<&Ident>, field_types: Vec<&Type>)
                         kind))
                 }
             }
+
+            fn check_children_nulls(
+                column: &::orcxx::reader::SelectedColumn,
+                path: &str,
+                check: &mut ::orcxx::deserialize::NullabilityCheck,
+            ) {
+                let fields = match &column.kind {
+                    ::orcxx::kind::Kind::Struct(fields) => Some(&fields[..]),
+                    _ => None,
+                };
+                #(
+                    let child = ::orcxx::deserialize::struct_field(
+                        fields,
+                        #field_indexes,
+                        #column_names,
+                    )
+                    .ok()
+                    .and_then(|(index, _)| column.children.get(index));
+                    if let Some(child) = child {
+                        let mut field_path = path.to_string();
+                        if !path.is_empty() {
+                            field_path.push('.');
+                        }
+                        field_path.push_str(#column_names);
+                        <#field_types as ::orcxx::deserialize::CheckableKind>::check_nulls(
+                            child,
+                            &field_path,
+                            check,
+                        );
+                    }
+                )*
+            }
         }
     );
 
     let orc_struct_impl = quote!(
-        impl ::orcxx::deserialize::OrcStruct for #ident {
+        impl #impl_generics ::orcxx::deserialize::OrcStruct for #ident #ty_generics #where_clause {
             fn columns_with_prefix(prefix: &str) -> Vec<String> {
                 let mut columns = Vec::with_capacity(#num_fields);
 
-                // Hack to get types. Hopefully the compiler notices we don't
-                // actually use it at runtime.
-                let instance: #ident = Default::default();
-
                 #({
-                    #[inline(always)]
-                    fn add_columns<FieldType: ::orcxx::deserialize::OrcStruct>(columns: &mut Vec<String>, prefix: &str, _: FieldType) {
-                        let mut field_name_prefix = prefix.to_string();
-                        if prefix.len() != 0 {
-                            field_name_prefix.push_str(".");
-                        }
-                        field_name_prefix.push_str(stringify!(#unescaped_field_names));
-                        columns.extend(FieldType::columns_with_prefix(&field_name_prefix));
+                    let mut field_name_prefix = prefix.to_string();
+                    if prefix.len() != 0 {
+                        field_name_prefix.push_str(".");
                     }
-                    add_columns(&mut columns, prefix, instance.#field_names);
+                    field_name_prefix.push_str(#column_names);
+                    columns.extend(
+                        <#field_types as ::orcxx::deserialize::OrcStruct>::columns_with_prefix(
+                            &field_name_prefix,
+                        ),
+                    );
                 })*
                 columns
             }
@@ -296,101 +966,534 @@ fn impl_struct(ident: &Ident, field_names: Vec<&Ident>, field_types: Vec<&Type>)
 
         let src = src.try_into_structs().map_err(DeserializationError::MismatchedColumnKind)?;
         let columns = src.fields();
-        assert_eq!(
-            columns.len(),
-            #num_fields,
-            "{} has {} fields, but got {} columns.",
-            stringify!(ident), #num_fields, columns.len());
-        let mut columns = columns.into_iter();
+        let orcxx_fields: Option<&[(String, ::orcxx::kind::Kind)]> = match orcxx_kind {
+            Some(::orcxx::kind::Kind::Struct(fields)) => Some(&fields[..]),
+            _ => None,
+        };
+        if orcxx_fields.is_none() && columns.len() > #num_fields {
+            // Fields can only be matched to columns by position; missing columns
+            // at the end are filled with default values, if fields have one
+            return Err(DeserializationError::MismatchedColumnCount(format!(
+                "{} has {} fields, but got {} columns",
+                stringify!(#ident), #num_fields, columns.len())));
+        }
 
         let dst_len: u64 = dst.len().try_into().map_err(DeserializationError::UsizeOverflow)?;
         if src.num_elements() > dst_len {
             return Err(::orcxx::deserialize::DeserializationError::MismatchedLength { src: src.num_elements(), dst: dst_len });
         }
+        let num_elements: usize = src
+            .num_elements()
+            .try_into()
+            .map_err(DeserializationError::UsizeOverflow)?;
     );
 
-    let read_from_vector_batch_impl = quote!(
-        impl ::orcxx::deserialize::OrcDeserialize for #ident {
-            fn read_from_vector_batch<'a, 'b, T> (
-                src: &::orcxx::vector::BorrowedColumnVectorBatch, mut dst: &'b mut T
-            ) -> Result<usize, ::orcxx::deserialize::DeserializationError>
-            where
-                &'b mut T: ::orcxx::deserialize::DeserializationTarget<'a, Item=#ident> + 'b {
-                #prelude
+    // Every field overwrites all rows, so there is no need to reset them
+    let read_body = quote!(
+        #prelude
 
-                match src.not_null() {
-                    None => {
-                        for struct_ in dst.iter_mut() {
-                            *struct_ = Default::default()
-                        }
-                    },
-                    Some(not_null) => {
-                        for (struct_, &b) in dst.iter_mut().zip(not_null) {
-                            if b != 0 {
-                                *struct_ = Default::default()
-                            }
-                        }
-                    }
-                }
+        #(
+            #read_fields
+        )*
 
+        Ok(num_elements)
+    );
+
+    // Fields of structures without type parameters are read into buffers kept
+    // in thread-local variables, so they are not reallocated for every batch
+    // (statics cannot depend on type parameters)
+    let field_iters: Vec<_> = (0..num_fields)
+        .map(|i| format_ident!("orcxx_field_iter_{}", i))
+        .collect();
+    let field_lengths: Vec<_> = (0..num_fields)
+        .map(|i| format_ident!("orcxx_field_length_{}", i))
+        .collect();
+    let (take_buffers, return_buffers) = if generics.params.is_empty() {
+        let field_buffers: Vec<_> = (0..num_fields)
+            .map(|i| format_ident!("ORCXX_FIELD_BUFFER_{}", i))
+            .collect();
+        (
+            quote!(
+                #(
+                    thread_local! {
+                        static #field_buffers: ::std::cell::RefCell<Vec<#field_types>> =
+                            ::std::cell::RefCell::new(Vec::new());
+                    }
+                    let mut #field_values: Vec<#field_types> =
+                        #field_buffers.with(|buffer| ::std::mem::take(&mut *buffer.borrow_mut()));
+                    #field_values.clear();
+                )*
+            ),
+            quote!(
                 #(
-                    let column: BorrowedColumnVectorBatch = columns.next().expect(
-                        &format!("Failed to get '{}' column", stringify!(#field_names)));
-                    OrcDeserialize::read_from_vector_batch::<orcxx::deserialize::MultiMap<&mut T, _>>(
-                        &column,
-                        &mut dst.map(|struct_| &mut struct_.#field_names),
-                    )?;
+                    #field_buffers.with(|buffer| *buffer.borrow_mut() = #field_values);
                 )*
+            ),
+        )
+    } else {
+        (
+            quote!(
+                #(
+                    let mut #field_values: Vec<#field_types> = Vec::with_capacity(num_elements);
+                )*
+            ),
+            quote!(),
+        )
+    };
+    let read_options_body = quote!(
+        #prelude
 
-                Ok(src.num_elements().try_into().unwrap())
+        #take_buffers
+        #(
+            #field_values.resize_with(num_elements, Default::default);
+            #read_option_fields
+        )*
+
+        {
+            #(
+                let #field_lengths = #field_values.len();
+                let mut #field_iters = #field_values.drain(..);
+            )*
+            let mut not_null = src.not_null().map(|not_null| not_null.iter());
+            for struct_ in dst.iter_mut().take(num_elements) {
+                let value = #ident {
+                    #(
+                        #field_names: match #field_iters.next() {
+                            Some(value) => value,
+                            None => {
+                                return Err(::orcxx::deserialize::DeserializationError::MismatchedLength {
+                                    src: #field_lengths as u64,
+                                    dst: num_elements as u64,
+                                })
+                            }
+                        },
+                    )*
+                };
+                *struct_ = match not_null.as_mut().map(|not_null| not_null.next()) {
+                    Some(Some(&0)) => None,
+                    _ => Some(value),
+                };
+            }
+        }
+        #return_buffers
+
+        Ok(num_elements)
+    );
+
+    if let Some(lifetime) = lifetime {
+        return quote!(
+            #check_kind_impl
+            #orc_struct_impl
+
+            impl #impl_generics ::orcxx::deserialize::OrcDeserializeRef<#lifetime> for #ident #ty_generics #where_clause {
+                fn read_ref_from_vector_batch<'orcxx_b, OrcxxTarget> (
+                    src: &::orcxx::vector::BorrowedColumnVectorBatch<#lifetime>, mut dst: &'orcxx_b mut OrcxxTarget
+                ) -> Result<usize, ::orcxx::deserialize::DeserializationError>
+                where
+                    &'orcxx_b mut OrcxxTarget: ::orcxx::deserialize::DeserializationTarget<#lifetime, Item=#ident #ty_generics> + 'orcxx_b {
+                    let orcxx_kind: Option<&::orcxx::kind::Kind> = None;
+                    #read_body
+                }
+
+                fn read_ref_from_vector_batch_with_kind<'orcxx_b, OrcxxTarget> (
+                    src: &::orcxx::vector::BorrowedColumnVectorBatch<#lifetime>,
+                    kind: &::orcxx::kind::Kind,
+                    mut dst: &'orcxx_b mut OrcxxTarget
+                ) -> Result<usize, ::orcxx::deserialize::DeserializationError>
+                where
+                    &'orcxx_b mut OrcxxTarget: ::orcxx::deserialize::DeserializationTarget<#lifetime, Item=#ident #ty_generics> + 'orcxx_b {
+                    let orcxx_kind = Some(kind);
+                    #read_body
+                }
+            }
+        )
+        .into();
+    }
+
+    let read_from_vector_batch_impl = quote!(
+        impl #impl_generics ::orcxx::deserialize::OrcDeserialize for #ident #ty_generics #where_clause {
+            fn read_from_vector_batch<'orcxx_a, 'orcxx_b, OrcxxTarget> (
+                src: &::orcxx::vector::BorrowedColumnVectorBatch, mut dst: &'orcxx_b mut OrcxxTarget
+            ) -> Result<usize, ::orcxx::deserialize::DeserializationError>
+            where
+                Self: 'orcxx_a,
+                &'orcxx_b mut OrcxxTarget: ::orcxx::deserialize::DeserializationTarget<'orcxx_a, Item=#ident #ty_generics> + 'orcxx_b {
+                let orcxx_kind: Option<&::orcxx::kind::Kind> = None;
+                #read_body
+            }
+
+            fn read_from_vector_batch_with_kind<'orcxx_a, 'orcxx_b, OrcxxTarget> (
+                src: &::orcxx::vector::BorrowedColumnVectorBatch,
+                kind: &::orcxx::kind::Kind,
+                mut dst: &'orcxx_b mut OrcxxTarget
+            ) -> Result<usize, ::orcxx::deserialize::DeserializationError>
+            where
+                Self: 'orcxx_a,
+                &'orcxx_b mut OrcxxTarget: ::orcxx::deserialize::DeserializationTarget<'orcxx_a, Item=#ident #ty_generics> + 'orcxx_b {
+                let orcxx_kind = Some(kind);
+                #read_body
             }
         }
     );
 
     let read_options_from_vector_batch_impl = quote!(
-        impl ::orcxx::deserialize::OrcDeserializeOption for #ident {
-            fn read_options_from_vector_batch<'a, 'b, T> (
-                src: &::orcxx::vector::BorrowedColumnVectorBatch, mut dst: &'b mut T
+        impl #impl_generics ::orcxx::deserialize::OrcDeserializeOption for #ident #ty_generics #where_clause {
+            fn read_options_from_vector_batch<'orcxx_a, 'orcxx_b, OrcxxTarget> (
+                src: &::orcxx::vector::BorrowedColumnVectorBatch, mut dst: &'orcxx_b mut OrcxxTarget
             ) -> Result<usize, ::orcxx::deserialize::DeserializationError>
             where
-                &'b mut T: ::orcxx::deserialize::DeserializationTarget<'a, Item=Option<#ident>> + 'b {
-                #prelude
+                Self: 'orcxx_a,
+                &'orcxx_b mut OrcxxTarget: ::orcxx::deserialize::DeserializationTarget<'orcxx_a, Item=Option<#ident #ty_generics>> + 'orcxx_b {
+                let orcxx_kind: Option<&::orcxx::kind::Kind> = None;
+                #read_options_body
+            }
 
-                match src.not_null() {
-                    None => {
-                        for struct_ in dst.iter_mut() {
-                            *struct_ = Some(Default::default())
-                        }
-                    },
-                    Some(not_null) => {
-                        for (struct_, &b) in dst.iter_mut().zip(not_null) {
-                            if b != 0 {
-                                *struct_ = Some(Default::default())
+            fn read_options_from_vector_batch_with_kind<'orcxx_a, 'orcxx_b, OrcxxTarget> (
+                src: &::orcxx::vector::BorrowedColumnVectorBatch,
+                kind: &::orcxx::kind::Kind,
+                mut dst: &'orcxx_b mut OrcxxTarget
+            ) -> Result<usize, ::orcxx::deserialize::DeserializationError>
+            where
+                Self: 'orcxx_a,
+                &'orcxx_b mut OrcxxTarget: ::orcxx::deserialize::DeserializationTarget<'orcxx_a, Item=Option<#ident #ty_generics>> + 'orcxx_b {
+                let orcxx_kind = Some(kind);
+                #read_options_body
+            }
+        }
+    );
+
+    quote!(
+        #check_kind_impl
+        #orc_struct_impl
+
+        #read_from_vector_batch_impl
+        #read_options_from_vector_batch_impl
+    )
+    .into()
+}
+
+/// Implements `LazyStruct` for the structure, and generates a trait with an
+/// accessor for each field, implemented by `LazyRow<'_, #ident>`
+fn impl_lazy(
+    vis: &Visibility,
+    ident: &Ident,
+    fields: &punctuated::Punctuated<Field, Token![,]>,
+    field_attributes: &[FieldAttributes],
+) -> proc_macro2::TokenStream {
+    let num_fields = fields.len();
+    let field_names: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+    let field_types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+    let column_names: Vec<_> = field_attributes
+        .iter()
+        .zip(&field_names)
+        .map(|(attributes, field_name)| {
+            attributes.column_name(
+                field_name
+                    .as_ref()
+                    .expect("#ident must not have anonymous fields"),
+            )
+        })
+        .collect();
+    let trait_ident = format_ident!("{}LazyRow", ident);
+    let trait_doc = format!(
+        "Accessors to fields of [`{}`] in rows returned by `orcxx::lazy_row::LazyRowIterator`",
+        ident
+    );
+    let field_docs = column_names.iter().map(|column_name| {
+        format!(
+            "Returns the value of the field read from column `{}`, after deserializing \
+            this column in the whole batch if it was not already\n\n\
+            # Panics\n\n\
+            If the column cannot be deserialized",
+            column_name
+        )
+    });
+    let try_field_names: Vec<_> = field_names
+        .iter()
+        .map(|field_name| {
+            format_ident!(
+                "try_{}",
+                field_name
+                    .as_ref()
+                    .expect("#ident must not have anonymous fields")
+                    .unraw()
+            )
+        })
+        .collect();
+    let try_field_docs = column_names.iter().map(|column_name| {
+        format!(
+            "Returns the value of the field read from column `{}`, after deserializing \
+            this column in the whole batch if it was not already, or the error which \
+            occurred while deserializing it",
+            column_name
+        )
+    });
+    let try_fields = field_attributes
+        .iter()
+        .zip(&field_types)
+        .zip(&column_names)
+        .enumerate()
+        .map(|(field_index, ((attributes, field_type), column_name))| {
+            let default = match &attributes.default {
+                Some(default) => quote!(Some((|| #default) as fn() -> #field_type)),
+                None => quote!(None),
+            };
+            if attributes.assume_utf8 {
+                quote!(
+                    {
+                        fn orcxx_assume_utf8<T: ?Sized + ::orcxx::deserialize::AssumeUtf8>() {}
+                        orcxx_assume_utf8::<#ident>();
+                    }
+                    // Safe because the user asserted strings in this column are valid UTF-8,
+                    // by implementing AssumeUtf8
+                    unsafe {
+                        self.try_field_assume_utf8::<#field_type>(#field_index, #column_name, #default)
+                    }
+                )
+            } else {
+                quote!(self.try_field::<#field_type>(#field_index, #column_name, #default))
+            }
+        });
+
+    quote!(
+        impl ::orcxx::lazy_row::LazyStruct for #ident {
+            const NUM_FIELDS: usize = #num_fields;
+        }
+
+        #[doc = #trait_doc]
+        #vis trait #trait_ident<'orcxx_a> {
+            #(
+                #[doc = #field_docs]
+                fn #field_names(&self) -> &'orcxx_a #field_types;
+
+                #[doc = #try_field_docs]
+                fn #try_field_names(
+                    &self,
+                ) -> Result<&'orcxx_a #field_types, ::orcxx::deserialize::DeserializationError>;
+            )*
+        }
+
+        impl<'orcxx_a> #trait_ident<'orcxx_a> for ::orcxx::lazy_row::LazyRow<'orcxx_a, #ident> {
+            #(
+                fn #field_names(&self) -> &'orcxx_a #field_types {
+                    self.#try_field_names().unwrap_or_else(|e| {
+                        panic!(
+                            "LazyRow could not deserialize field {}: {:?}",
+                            #column_names, e
+                        )
+                    })
+                }
+
+                fn #try_field_names(
+                    &self,
+                ) -> Result<&'orcxx_a #field_types, ::orcxx::deserialize::DeserializationError> {
+                    #try_fields
+                }
+            )*
+        }
+    )
+}
+
+fn impl_enum(
+    ident: &Ident,
+    generics: &Generics,
+    variants: &punctuated::Punctuated<Variant, Token![,]>,
+) -> Result<TokenStream> {
+    let mut variant_names = Vec::new();
+    let mut variant_types = Vec::new();
+    for variant in variants {
+        match &variant.fields {
+            Fields::Unnamed(FieldsUnnamed { unnamed, .. }) if unnamed.len() == 1 => {
+                variant_names.push(&variant.ident);
+                variant_types.push(&unnamed[0].ty);
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    variant,
+                    "variants must have exactly one unnamed field, with the type of their ORC union variant",
+                ))
+            }
+        }
+    }
+    let num_variants = variant_names.len();
+    if num_variants > 256 {
+        return Err(Error::new_spanned(
+            ident,
+            "ORC unions cannot have more than 256 variants",
+        ));
+    }
+    let tags: Vec<_> = (0..num_variants)
+        .map(|i| proc_macro2::Literal::u8_unsuffixed(i as u8))
+        .collect();
+    let variant_indexes: Vec<_> = (0..num_variants).collect();
+    let variant_values: Vec<_> = (0..num_variants)
+        .map(|i| format_ident!("orcxx_variant_{}", i))
+        .collect();
+
+    let mut generics = generics.clone();
+    if !generics.params.is_empty() {
+        let where_clause = generics.make_where_clause();
+        for variant_type in &variant_types {
+            where_clause.predicates.push(parse_quote!(
+                #variant_type: ::orcxx::deserialize::OrcDeserialize + ::std::default::Default
+            ));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let check_kind_impl = quote!(
+        impl #impl_generics ::orcxx::deserialize::CheckableKind for #ident #ty_generics #where_clause {
+            fn check_kind(kind: &::orcxx::kind::Kind) -> Result<(), String> {
+                use ::orcxx::kind::Kind;
+
+                match kind {
+                    Kind::Union(variants) if variants.len() == #num_variants => {
+                        let mut errors = Vec::new();
+                        #(
+                            if let Err(s) = <#variant_types as ::orcxx::deserialize::CheckableKind>::check_kind(&variants[#variant_indexes]) {
+                                errors.push(format!(
+                                    "Variant {} cannot be decoded: {}",
+                                    stringify!(#variant_names), s));
                             }
+                        )*
+
+                        if errors.is_empty() {
+                            Ok(())
+                        }
+                        else {
+                            Err(format!(
+                                "{} cannot be decoded:\n\t{}",
+                                stringify!(#ident),
+                                errors.join("\n").replace("\n", "\n\t")))
                         }
                     }
+                    Kind::Union(variants) => Err(format!(
+                        "{} has {} variants, but the union has {}",
+                        stringify!(#ident),
+                        #num_variants,
+                        variants.len())),
+                    _ => Err(format!(
+                        "{} must be decoded from Kind::Union, not {:?}",
+                        stringify!(#ident),
+                        kind))
                 }
+            }
 
+            fn check_children_nulls(
+                column: &::orcxx::reader::SelectedColumn,
+                path: &str,
+                check: &mut ::orcxx::deserialize::NullabilityCheck,
+            ) {
                 #(
-                    let column: BorrowedColumnVectorBatch = columns.next().expect(
-                        &format!("Failed to get '{}' column", stringify!(#field_names)));
-                    OrcDeserialize::read_from_vector_batch::<::orcxx::deserialize::MultiMap<&mut T, _>>(
-                        &column,
-                        &mut dst.map(|struct_| &mut unsafe { struct_.as_mut().unwrap_unchecked() }.#field_names),
-                    )?;
+                    if let Some(child) = column.children.get(#variant_indexes) {
+                        <#variant_types as ::orcxx::deserialize::CheckableKind>::check_nulls(
+                            child,
+                            path,
+                            check,
+                        );
+                    }
                 )*
+            }
+        }
+    );
 
-                Ok(src.num_elements().try_into().unwrap())
+    let orc_struct_impl = quote!(
+        impl #impl_generics ::orcxx::deserialize::OrcStruct for #ident #ty_generics #where_clause {
+            fn columns_with_prefix(prefix: &str) -> Vec<String> {
+                vec![prefix.to_string()]
             }
         }
     );
 
-    quote!(
+    // Reads all values of each variant, then moves them to the rows they belong to
+    let prelude = quote!(
+        use ::orcxx::deserialize::DeserializationError;
+
+        let src = src.try_into_unions().map_err(DeserializationError::MismatchedColumnKind)?;
+        #(
+            let mut #variant_values: Vec<#variant_types> =
+                ::orcxx::deserialize::read_union_variant(&src, orcxx_kind, #tags)?;
+        )*
+        let read_value = |tag: u8, offset: u64| match tag {
+            #(
+                #tags => Ok(#ident::#variant_names(::orcxx::deserialize::union_value(
+                    &mut #variant_values,
+                    tag,
+                    offset,
+                )?)),
+            )*
+            _ => Err(DeserializationError::InvalidUnionValue { tag, offset }),
+        };
+    );
+
+    let read_body = quote!(
+        #prelude
+        ::orcxx::deserialize::read_unions(&src, dst, stringify!(#ident), read_value)
+    );
+
+    let read_options_body = quote!(
+        #prelude
+        ::orcxx::deserialize::read_nullable_unions(&src, dst, read_value)
+    );
+
+    let read_from_vector_batch_impl = quote!(
+        impl #impl_generics ::orcxx::deserialize::OrcDeserialize for #ident #ty_generics #where_clause {
+            fn read_from_vector_batch<'orcxx_a, 'orcxx_b, OrcxxTarget> (
+                src: &::orcxx::vector::BorrowedColumnVectorBatch, dst: &'orcxx_b mut OrcxxTarget
+            ) -> Result<usize, ::orcxx::deserialize::DeserializationError>
+            where
+                Self: 'orcxx_a,
+                &'orcxx_b mut OrcxxTarget: ::orcxx::deserialize::DeserializationTarget<'orcxx_a, Item=#ident #ty_generics> + 'orcxx_b {
+                let orcxx_kind: Option<&::orcxx::kind::Kind> = None;
+                #read_body
+            }
+
+            fn read_from_vector_batch_with_kind<'orcxx_a, 'orcxx_b, OrcxxTarget> (
+                src: &::orcxx::vector::BorrowedColumnVectorBatch,
+                kind: &::orcxx::kind::Kind,
+                dst: &'orcxx_b mut OrcxxTarget
+            ) -> Result<usize, ::orcxx::deserialize::DeserializationError>
+            where
+                Self: 'orcxx_a,
+                &'orcxx_b mut OrcxxTarget: ::orcxx::deserialize::DeserializationTarget<'orcxx_a, Item=#ident #ty_generics> + 'orcxx_b {
+                let orcxx_kind = Some(kind);
+                #read_body
+            }
+        }
+    );
+
+    let read_options_from_vector_batch_impl = quote!(
+        impl #impl_generics ::orcxx::deserialize::OrcDeserializeOption for #ident #ty_generics #where_clause {
+            fn read_options_from_vector_batch<'orcxx_a, 'orcxx_b, OrcxxTarget> (
+                src: &::orcxx::vector::BorrowedColumnVectorBatch, dst: &'orcxx_b mut OrcxxTarget
+            ) -> Result<usize, ::orcxx::deserialize::DeserializationError>
+            where
+                Self: 'orcxx_a,
+                &'orcxx_b mut OrcxxTarget: ::orcxx::deserialize::DeserializationTarget<'orcxx_a, Item=Option<#ident #ty_generics>> + 'orcxx_b {
+                let orcxx_kind: Option<&::orcxx::kind::Kind> = None;
+                #read_options_body
+            }
+
+            fn read_options_from_vector_batch_with_kind<'orcxx_a, 'orcxx_b, OrcxxTarget> (
+                src: &::orcxx::vector::BorrowedColumnVectorBatch,
+                kind: &::orcxx::kind::Kind,
+                dst: &'orcxx_b mut OrcxxTarget
+            ) -> Result<usize, ::orcxx::deserialize::DeserializationError>
+            where
+                Self: 'orcxx_a,
+                &'orcxx_b mut OrcxxTarget: ::orcxx::deserialize::DeserializationTarget<'orcxx_a, Item=Option<#ident #ty_generics>> + 'orcxx_b {
+                let orcxx_kind = Some(kind);
+                #read_options_body
+            }
+        }
+    );
+
+    Ok(quote!(
         #check_kind_impl
         #orc_struct_impl
 
         #read_from_vector_batch_impl
         #read_options_from_vector_batch_impl
     )
-    .into()
+    .into())
 }
@@ -0,0 +1,93 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(feature = "async")]
+
+extern crate futures;
+extern crate orcxx;
+extern crate orcxx_derive;
+extern crate tokio;
+
+use std::num::NonZeroU64;
+
+use futures::{Stream, StreamExt};
+
+use orcxx::async_reader::{AsyncInputStream, AsyncReader, AsyncRowIterator};
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+const ORC_PATH: &str = "../orcxx/orc/examples/TestOrcFile.testStripeLevelStats.orc";
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+}
+
+fn open() -> AsyncReader {
+    let runtime = runtime();
+    let input_stream = runtime
+        .block_on(AsyncInputStream::from_local_file(ORC_PATH))
+        .expect("Could not open .orc");
+    runtime
+        .block_on(AsyncReader::new(input_stream))
+        .expect("Could not read .orc")
+}
+
+fn expected_rows(reader: &AsyncReader) -> Vec<Option<Row>> {
+    RowIterator::new(reader.reader(), NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect()
+}
+
+#[test]
+fn all_rows() {
+    let reader = open();
+    let rows =
+        AsyncRowIterator::<Option<Row>>::new(&reader, NonZeroU64::new(300).unwrap()).unwrap();
+    assert_eq!(
+        runtime().block_on(rows.collect::<Vec<_>>()),
+        expected_rows(&reader)
+    );
+}
+
+#[test]
+fn from_row_iterator() {
+    let reader = open();
+    let rows = RowIterator::<Option<Row>>::new(reader.reader(), NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .range(4990..5010);
+    let rows = AsyncRowIterator::from_row_iterator(rows, NonZeroU64::new(7).unwrap());
+    assert_eq!(rows.size_hint(), (20, Some(20)));
+    assert_eq!(
+        runtime().block_on(rows.collect::<Vec<_>>()),
+        expected_rows(&reader)[4990..5010]
+    );
+}
+
+#[test]
+fn missing_file() {
+    let result = runtime().block_on(AsyncInputStream::from_local_file("does/not/exist.orc"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn from_sync_reader() {
+    let input_stream = reader::InputStream::from_local_file(ORC_PATH).unwrap();
+    let reader = AsyncReader::from(reader::Reader::new(input_stream).unwrap());
+    let rows =
+        AsyncRowIterator::<Option<Row>>::new(&reader, NonZeroU64::new(1000).unwrap()).unwrap();
+    assert_eq!(
+        runtime().block_on(rows.count()),
+        expected_rows(&reader).len()
+    );
+}
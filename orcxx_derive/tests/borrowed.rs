@@ -0,0 +1,132 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use orcxx::deserialize::{CheckableKind, OrcDeserializeRef, OrcStruct};
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx_derive::{OrcDeserialize, OrcDeserializeRef};
+
+const ORC_PATH: &str = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+
+#[derive(OrcDeserializeRef, Default, Debug, PartialEq)]
+struct Test1<'a> {
+    boolean1: bool,
+    bytes1: &'a [u8],
+    string1: &'a str,
+    list: Vec<Item>,
+}
+
+/// Copied as usual
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Item {
+    int1: i32,
+    string1: String,
+}
+
+#[derive(OrcDeserializeRef, Default, Debug, PartialEq)]
+#[orc(match_by_name)]
+struct Test1Option<'a> {
+    bytes1: Option<&'a [u8]>,
+    #[orc(unsafe_assume_utf8)]
+    string1: Option<&'a str>,
+}
+
+// Safe because the test file only contains valid UTF-8 strings
+unsafe impl orcxx::deserialize::AssumeUtf8 for Test1Option<'_> {}
+
+fn item(int1: i32, string1: &str) -> Item {
+    Item {
+        int1,
+        string1: string1.to_owned(),
+    }
+}
+
+fn row_reader(options: &reader::RowReaderOptions) -> reader::RowReader {
+    let input_stream = reader::InputStream::from_local_file(ORC_PATH).unwrap();
+    let reader = reader::Reader::new(input_stream).unwrap();
+    reader.row_reader(options).unwrap()
+}
+
+#[test]
+fn columns() {
+    assert_eq!(
+        Test1::columns(),
+        vec!["boolean1", "bytes1", "string1", "list.int1", "list.string1"]
+    );
+}
+
+#[test]
+fn ref_from_vector_batch() {
+    let options = reader::RowReaderOptions::default().include_names(Test1::columns());
+    let mut row_reader = row_reader(&options);
+    Test1::check_kind(&row_reader.selected_kind()).unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    let batch = batch.borrow();
+    let rows = Test1::ref_from_vector_batch(&batch).unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Test1 {
+                boolean1: false,
+                bytes1: &[0, 1, 2, 3, 4],
+                string1: "hi",
+                list: vec![item(3, "good"), item(4, "bad")],
+            },
+            Test1 {
+                boolean1: true,
+                bytes1: &[],
+                string1: "bye",
+                list: vec![
+                    item(100000000, "cat"),
+                    item(-100000, "in"),
+                    item(1234, "hat")
+                ],
+            },
+        ]
+    );
+}
+
+/// Tests reading with all columns selected, which matches fields by name
+#[test]
+fn read_ref_from_vector_batch_with_kind() {
+    let mut row_reader = row_reader(&reader::RowReaderOptions::default());
+    let kind = row_reader.selected_kind();
+    Test1Option::check_kind(&kind).unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    let batch = batch.borrow();
+    let mut rows = vec![Test1Option::default(), Test1Option::default()];
+    assert_eq!(
+        Test1Option::read_ref_from_vector_batch_with_kind(&batch, &kind, &mut rows),
+        Ok(2)
+    );
+    assert_eq!(
+        rows,
+        vec![
+            Test1Option {
+                bytes1: Some(&[0, 1, 2, 3, 4]),
+                string1: Some("hi"),
+            },
+            Test1Option {
+                bytes1: Some(&[]),
+                string1: Some("bye"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn check_kind() {
+    assert_eq!(
+        Test1Option::check_kind(&Kind::new("struct<bytes1:binary,string1:int>").unwrap()),
+        Err("Test1Option cannot be decoded:\n\tField string1 cannot be decoded: &str must be decoded from ORC String, not ORC Int".to_owned())
+    );
+}
@@ -0,0 +1,68 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+
+use orcxx::channel::{spawn_reader, spawn_row_iterator};
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+fn get_reader() -> reader::Reader {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.testStripeLevelStats.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+#[test]
+fn all_rows() {
+    let reader = get_reader();
+    let expected_rows: Vec<Option<Row>> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+
+    let (handle, receiver) =
+        spawn_reader::<Option<Row>>(&reader, NonZeroU64::new(300).unwrap(), 2).unwrap();
+    let mut rows = Vec::new();
+    for batch in receiver {
+        assert!(!batch.is_empty());
+        assert!(batch.len() <= 300);
+        rows.extend(batch);
+    }
+    handle.join().unwrap();
+    assert_eq!(rows, expected_rows);
+}
+
+#[test]
+fn range() {
+    let reader = get_reader();
+    let rows = RowIterator::<Option<Row>>::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .range(4990..5010);
+    let (handle, receiver) = spawn_row_iterator(rows, 7, 1);
+    let batch_lengths: Vec<_> = receiver.iter().map(|batch| batch.len()).collect();
+    handle.join().unwrap();
+    assert_eq!(batch_lengths, [7, 7, 6]);
+}
+
+/// The thread stops when the receiver is dropped
+#[test]
+fn drop_receiver() {
+    let reader = get_reader();
+    let (handle, receiver) =
+        spawn_reader::<Option<Row>>(&reader, NonZeroU64::new(10).unwrap(), 1).unwrap();
+    assert_eq!(receiver.recv().unwrap().len(), 10);
+    drop(receiver);
+    handle.join().unwrap();
+}
@@ -0,0 +1,113 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+extern crate tempfile;
+
+use orcxx::deserialize::{CheckableKind, NullabilityReport, NullableColumn, OrcStruct};
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx::vector::ColumnVectorBatchMut;
+use orcxx::writer::{OutputStream, Writer, WriterOptions};
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+struct NotNull {
+    int1: i64,
+    string1: String,
+}
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+struct Nullable {
+    int1: i64,
+    string1: Option<String>,
+}
+
+/// Writes a file where `int1` has no nulls, but `string1` does
+fn write_file() -> tempfile::TempPath {
+    let kind = Kind::Struct(vec![
+        ("int1".to_owned(), Kind::Long),
+        ("string1".to_owned(), Kind::String),
+    ]);
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+
+    let mut batch = writer.row_batch(2);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        structs.push();
+        structs.push();
+        let mut fields = structs.fields().into_iter();
+        let mut ints = fields.next().unwrap().try_into_longs().unwrap();
+        ints.push(1);
+        ints.push(2);
+        let mut strings = fields.next().unwrap().try_into_strings().unwrap();
+        strings.push("a");
+        strings.push_null();
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    path
+}
+
+fn check<T: CheckableKind + OrcStruct>(path: &tempfile::TempPath) -> NullabilityReport {
+    let input_stream = reader::InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    let reader = reader::Reader::new(input_stream).unwrap();
+    let options = reader::RowReaderOptions::default().include_names(T::columns());
+    let row_reader = reader.row_reader(&options).unwrap();
+    T::check_kind_with_stats(&reader, &row_reader).unwrap()
+}
+
+#[test]
+fn not_null() {
+    let path = write_file();
+    let report = check::<NotNull>(&path);
+    assert_eq!(
+        report.nullable_columns,
+        vec![NullableColumn {
+            path: "string1".to_owned(),
+            column_id: 2
+        }]
+    );
+    assert!(!report.is_ok());
+    assert_eq!(
+        report.to_string(),
+        "Column string1 (id 2) may contain nulls, but is not deserialized into an Option"
+    );
+}
+
+#[test]
+fn nullable() {
+    let path = write_file();
+    assert_eq!(check::<Nullable>(&path), NullabilityReport::default());
+    assert!(check::<Option<Nullable>>(&path).is_ok());
+    assert!(check::<Box<Nullable>>(&path).is_ok());
+}
+
+#[test]
+fn mismatched_kind() {
+    #[derive(OrcDeserialize, Default, Debug, PartialEq)]
+    struct Mismatched {
+        int1: String,
+    }
+
+    let path = write_file();
+    let input_stream = reader::InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    let reader = reader::Reader::new(input_stream).unwrap();
+    let options = reader::RowReaderOptions::default().include_names(["int1"]);
+    let row_reader = reader.row_reader(&options).unwrap();
+    assert!(Mismatched::check_kind_with_stats(&reader, &row_reader).is_err());
+}
+
+#[test]
+fn column_has_nulls() {
+    let path = write_file();
+    let input_stream = reader::InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    let reader = reader::Reader::new(input_stream).unwrap();
+    assert_eq!(reader.column_has_nulls(), Some(vec![false, false, true]));
+}
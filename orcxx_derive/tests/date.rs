@@ -0,0 +1,133 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#[cfg(feature = "chrono")]
+extern crate chrono;
+extern crate orcxx;
+extern crate orcxx_derive;
+extern crate tempfile;
+
+use orcxx::deserialize::{CheckableKind, OrcDeserialize};
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx::vector::ColumnVectorBatchMut;
+use orcxx::writer::{OutputStream, Writer, WriterOptions};
+use orcxx::Date;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+struct Dates {
+    date1: Date,
+    date2: Option<Date>,
+}
+
+/// Writes a file with a non-null date column and a nullable date column
+fn write_file() -> tempfile::TempPath {
+    let kind = Kind::Struct(vec![
+        ("date1".to_owned(), Kind::Date),
+        ("date2".to_owned(), Kind::Date),
+    ]);
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+
+    let mut batch = writer.row_batch(3);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        for _ in 0..3 {
+            structs.push();
+        }
+        let mut fields = structs.fields().into_iter();
+        let mut dates1 = fields.next().unwrap().try_into_longs().unwrap();
+        dates1.push(0);
+        dates1.push(-25209); // 1900-12-25
+        dates1.push(19000); // 2022-01-08
+        let mut dates2 = fields.next().unwrap().try_into_longs().unwrap();
+        dates2.push(1);
+        dates2.push_null();
+        dates2.push(-1);
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    path
+}
+
+fn read_file<T: OrcDeserialize + CheckableKind + Default>(path: &tempfile::TempPath) -> Vec<T> {
+    let input_stream = reader::InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    let reader = reader::Reader::new(input_stream).unwrap();
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    T::check_kind(&row_reader.selected_kind()).unwrap();
+
+    let mut rows = Vec::new();
+    let mut batch = row_reader.row_batch(1024);
+    while row_reader.read_into(&mut batch) {
+        rows.extend(T::from_vector_batch(&batch.borrow()).unwrap());
+    }
+    rows
+}
+
+#[test]
+fn test_date() {
+    let path = write_file();
+    assert_eq!(
+        read_file::<Dates>(&path),
+        vec![
+            Dates {
+                date1: Date(0),
+                date2: Some(Date(1)),
+            },
+            Dates {
+                date1: Date(-25209),
+                date2: None,
+            },
+            Dates {
+                date1: Date(19000),
+                date2: Some(Date(-1)),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_date_kind() {
+    assert_eq!(Date::check_kind(&Kind::Date), Ok(()));
+    assert!(Date::check_kind(&Kind::Long).is_err());
+}
+
+#[cfg(feature = "chrono")]
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+struct NaiveDates {
+    date1: chrono::NaiveDate,
+    date2: Option<chrono::NaiveDate>,
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_naive_date() {
+    use chrono::NaiveDate;
+
+    let path = write_file();
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    assert_eq!(
+        read_file::<NaiveDates>(&path),
+        vec![
+            NaiveDates {
+                date1: date(1970, 1, 1),
+                date2: Some(date(1970, 1, 2)),
+            },
+            NaiveDates {
+                date1: date(1900, 12, 25),
+                date2: None,
+            },
+            NaiveDates {
+                date1: date(2022, 1, 8),
+                date2: Some(date(1969, 12, 31)),
+            },
+        ]
+    );
+}
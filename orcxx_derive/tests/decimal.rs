@@ -11,7 +11,8 @@ extern crate rust_decimal_macros;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
-use orcxx::deserialize::{CheckableKind, OrcDeserialize};
+use orcxx::deserialize::{CheckableKind, DeserializationError, OrcDeserialize};
+use orcxx::kind::Kind;
 use orcxx::reader;
 use orcxx_derive::OrcDeserialize;
 
@@ -65,3 +66,52 @@ fn test_decimal() {
         _col0: Some(dec!(1739.17401))
     }));
 }
+
+#[derive(OrcDeserialize, Clone, Debug, PartialEq, Default)]
+struct NotNullRoot {
+    _col0: Decimal,
+}
+
+/// Tests non-nullable fields are read like nullable ones, unless the batch
+/// contains nulls
+#[test]
+fn test_decimal_not_null() {
+    let mut row_reader = row_reader();
+    NotNullRoot::check_kind(&row_reader.selected_kind()).unwrap();
+
+    let mut num_not_null_batches = 0;
+    let mut batch = row_reader.row_batch(10);
+    while row_reader.read_into(&mut batch) {
+        let rows = Root::from_vector_batch(&batch.borrow()).unwrap();
+        let not_null_rows = NotNullRoot::from_vector_batch(&batch.borrow());
+        if rows.contains(&Root { _col0: None }) {
+            assert!(matches!(
+                not_null_rows.unwrap_err().inner(),
+                DeserializationError::UnexpectedNull(_)
+            ));
+        } else {
+            num_not_null_batches += 1;
+            assert_eq!(
+                not_null_rows.unwrap(),
+                rows.into_iter()
+                    .map(|row| NotNullRoot {
+                        _col0: row._col0.unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+    assert!(num_not_null_batches > 0);
+}
+
+#[test]
+fn test_decimal_kind() {
+    assert_eq!(
+        NotNullRoot::check_kind(&Kind::new("struct<_col0:decimal(38,10)>").unwrap()),
+        Ok(())
+    );
+    assert_eq!(
+        NotNullRoot::check_kind(&Kind::new("struct<_col0:decimal(38,30)>").unwrap()),
+        Err("NotNullRoot cannot be decoded:\n\tField _col0 cannot be decoded: Decimal cannot be decoded from ORC Decimal { precision: 38, scale: 30 }, as its scale is greater than 28".to_owned())
+    );
+}
@@ -0,0 +1,187 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+
+use orcxx::deserialize::{CheckableKind, DeserializationError, OrcDeserialize, OrcStruct};
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+const ORC_PATH: &str = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+
+/// `int2` and `string2` are not in the file
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+#[orc(match_by_name)]
+struct Test1 {
+    int1: i32,
+    #[orc(default)]
+    int2: i64,
+    #[orc(default = "\"none\".to_owned()")]
+    string1: String,
+    #[orc(default = "\"none\".to_owned()")]
+    string2: String,
+    list: Vec<Item>,
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+#[orc(match_by_name)]
+struct Item {
+    string1: String,
+    #[orc(default = "-1")]
+    int2: i32,
+}
+
+fn item(string1: &str) -> Item {
+    Item {
+        string1: string1.to_owned(),
+        int2: -1,
+    }
+}
+
+fn expected_rows() -> Vec<Test1> {
+    vec![
+        Test1 {
+            int1: 65536,
+            int2: 0,
+            string1: "hi".to_owned(),
+            string2: "none".to_owned(),
+            list: vec![item("good"), item("bad")],
+        },
+        Test1 {
+            int1: 65536,
+            int2: 0,
+            string1: "bye".to_owned(),
+            string2: "none".to_owned(),
+            list: vec![item("cat"), item("in"), item("hat")],
+        },
+    ]
+}
+
+fn reader() -> reader::Reader {
+    let input_stream = reader::InputStream::from_local_file(ORC_PATH).unwrap();
+    reader::Reader::new(input_stream).unwrap()
+}
+
+#[test]
+fn columns_in() {
+    assert_eq!(
+        Test1::columns(),
+        vec![
+            "int1",
+            "int2",
+            "string1",
+            "string2",
+            "list.string1",
+            "list.int2"
+        ]
+    );
+    assert_eq!(
+        Test1::columns_in(&reader().kind()),
+        vec!["int1", "string1", "list.string1"]
+    );
+}
+
+#[test]
+fn read_file() {
+    let rows: Vec<Test1> = orcxx::read_file(ORC_PATH).unwrap();
+    assert_eq!(rows, expected_rows());
+}
+
+#[test]
+fn read_options() {
+    let rows: Vec<Option<Test1>> = orcxx::read_file(ORC_PATH).unwrap();
+    assert_eq!(
+        rows,
+        expected_rows().into_iter().map(Some).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn all_columns() {
+    let rows: RowIterator<Test1> = RowIterator::new_with_options(
+        &reader(),
+        NonZeroU64::new(1024).unwrap(),
+        &reader::RowReaderOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(rows.collect::<Vec<_>>(), expected_rows());
+}
+
+#[test]
+fn check_kind() {
+    let kind = Kind::new("struct<string1:string>").unwrap();
+    assert_eq!(Item::check_kind(&kind), Ok(()));
+
+    let kind = Kind::new("struct<int2:int>").unwrap();
+    assert_eq!(
+        Item::check_kind(&kind),
+        Err("Item cannot be decoded:\n\tField string1 is missing".to_owned())
+    );
+
+    // Columns which exist must still have the right type
+    let kind = Kind::new("struct<string1:string,int2:string>").unwrap();
+    assert_eq!(
+        Item::check_kind(&kind),
+        Err("Item cannot be decoded:\n\tField int2 cannot be decoded: i32 must be decoded from ORC Int, not ORC String".to_owned())
+    );
+}
+
+/// Fields are matched to columns by position
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Positional {
+    int1: i32,
+    #[orc(default = "-1")]
+    int2: i64,
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct PositionalNoDefault {
+    int1: i32,
+    int2: i64,
+}
+
+/// Without the kind, only fields at the end of the structure can be missing
+#[test]
+fn without_kind() {
+    let read_batch = |columns: &[&str]| {
+        let options = reader::RowReaderOptions::default().include_names(columns.iter().copied());
+        let mut row_reader = reader().row_reader(&options).unwrap();
+        let mut batch = row_reader.row_batch(1024);
+        assert!(row_reader.read_into(&mut batch));
+        batch
+    };
+
+    let batch = read_batch(&["int1"]);
+    assert_eq!(
+        Positional::from_vector_batch(&batch.borrow()).unwrap(),
+        vec![
+            Positional {
+                int1: 65536,
+                int2: -1,
+            },
+            Positional {
+                int1: 65536,
+                int2: -1,
+            },
+        ]
+    );
+    assert_eq!(
+        PositionalNoDefault::from_vector_batch(&batch.borrow()).unwrap_err(),
+        DeserializationError::MissingField("int2".to_owned())
+    );
+
+    let batch = read_batch(&["int1", "long1", "string1"]);
+    assert_eq!(
+        Positional::from_vector_batch(&batch.borrow()).unwrap_err(),
+        DeserializationError::MismatchedColumnCount(
+            "Positional has 2 fields, but got 3 columns".to_owned()
+        )
+    );
+}
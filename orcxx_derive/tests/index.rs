@@ -0,0 +1,109 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+
+use orcxx::index::{IndexError, OrcxxIndex};
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+fn get_reader() -> reader::Reader {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.testStripeLevelStats.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+#[test]
+fn skip_stripes() {
+    let reader = get_reader();
+    let index = OrcxxIndex::from_reader(&reader);
+    assert!(
+        index.stripes.len() > 1,
+        "Test file should have several stripes"
+    );
+    let expected_rows: Vec<Option<Row>> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+
+    for skipped_stripe in 0..index.stripes.len() {
+        let stripe_index = index
+            .clone()
+            .retain_stripes(|stripe| stripe.first_row != index.stripes[skipped_stripe].first_row);
+        let expected: Vec<_> = stripe_index
+            .stripes
+            .iter()
+            .flat_map(|stripe| {
+                expected_rows[stripe.first_row as usize..stripe.rows().end as usize].to_vec()
+            })
+            .collect();
+
+        for batch_size in [1, 7, 1000] {
+            let rows = RowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap())
+                .unwrap()
+                .with_index(&stripe_index)
+                .unwrap();
+            assert_eq!(rows.len(), expected.len());
+            let rows: Vec<Option<Row>> = rows.collect();
+            assert_eq!(
+                rows, expected,
+                "skipped stripe = {}, batch_size = {}",
+                skipped_stripe, batch_size
+            );
+
+            let rows = RowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap())
+                .unwrap()
+                .with_index(&stripe_index)
+                .unwrap()
+                .tail(0);
+            let mut rows: Vec<Option<Row>> = rows.rev().collect();
+            rows.reverse();
+            assert_eq!(
+                rows, expected,
+                "backward, skipped stripe = {}, batch_size = {}",
+                skipped_stripe, batch_size
+            );
+        }
+    }
+}
+
+#[test]
+fn skip_all_stripes() {
+    let reader = get_reader();
+    let index = OrcxxIndex::from_reader(&reader).retain_stripes(|_| false);
+    let rows = RowIterator::<Option<Row>>::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .with_index(&index)
+        .unwrap();
+    assert_eq!(rows.len(), 0);
+    assert_eq!(rows.count(), 0);
+}
+
+#[test]
+fn index_of_other_file() {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+    let other_index = orcxx::index::build(orc_path).unwrap();
+    let result = RowIterator::<Option<Row>>::new(&get_reader(), NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .with_index(&other_index);
+    assert!(matches!(result, Err(IndexError::Mismatch(_))));
+
+    // Same number of rows, but different schema
+    let mut other_index = OrcxxIndex::from_reader(&get_reader());
+    other_index.schema_fingerprint ^= 1;
+    let result = RowIterator::<Option<Row>>::new(&get_reader(), NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .with_index(&other_index);
+    assert!(matches!(result, Err(IndexError::Mismatch(_))));
+}
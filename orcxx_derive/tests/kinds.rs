@@ -5,9 +5,13 @@
 
 extern crate orcxx;
 extern crate orcxx_derive;
+extern crate tempfile;
 
-use orcxx::deserialize::CheckableKind;
+use orcxx::deserialize::{CheckableKind, DeserializationError, OrcDeserialize, OrcStruct};
+use orcxx::kind::Kind;
 use orcxx::reader;
+use orcxx::vector::{Bitmap, ColumnVectorBatchMut};
+use orcxx::writer::{OutputStream, Writer, WriterOptions};
 use orcxx_derive::OrcDeserialize;
 
 #[derive(OrcDeserialize, Default, Debug, PartialEq, Eq)]
@@ -52,3 +56,116 @@ fn incorrect_type() {
         Test1IncorrectType::check_kind(&row_reader.selected_kind()),
         Err("Test1IncorrectType cannot be decoded:\n\tField bytes1 cannot be decoded: String must be decoded from ORC String, not ORC Binary".to_string()));
 }
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq, Eq)]
+struct Flags {
+    flags: Bitmap,
+    maybe_flags: Option<Bitmap>,
+}
+
+/// Tests lists of booleans can be read as bitmaps, but not other lists
+#[test]
+fn bitmap() {
+    let kind = Kind::new("struct<flags:array<boolean>,maybe_flags:array<boolean>>").unwrap();
+    assert_eq!(Flags::check_kind(&kind), Ok(()));
+    assert_eq!(Flags::columns(), vec!["flags", "maybe_flags"]);
+
+    let kind = Kind::new("struct<flags:array<int>,maybe_flags:array<boolean>>").unwrap();
+    assert!(Flags::check_kind(&kind).is_err());
+}
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+struct Test1IncorrectItem {
+    int1: Option<f64>,
+}
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+struct Test1IncorrectList {
+    list: Option<Vec<Option<Test1IncorrectItem>>>,
+}
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+struct Test1IncorrectMap {
+    map: Option<Vec<(Option<String>, Option<Test1IncorrectItem>)>>,
+}
+
+/// Tests errors in children of lists and maps report the path of the column in
+/// the file
+#[test]
+fn incorrect_nested_type_path() {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+
+    let options = reader::RowReaderOptions::default().include_names(["list"]);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    let error = Test1IncorrectList::from_vector_batch(&batch.borrow()).unwrap_err();
+    assert_eq!(error.column(), "list._elem.int1");
+    assert!(matches!(
+        error.inner(),
+        DeserializationError::MismatchedColumnKind(_)
+    ));
+
+    let options = reader::RowReaderOptions::default().include_names(["map"]);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    let error = Test1IncorrectMap::from_vector_batch(&batch.borrow()).unwrap_err();
+    assert_eq!(error.column(), "map._value.int1");
+}
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq, Eq)]
+struct Names {
+    name: String,
+    code: Option<String>,
+}
+
+/// Tests strings can be read from varchar and char columns
+#[test]
+fn varchar_and_char() {
+    let kind = Kind::new("struct<name:varchar(10),code:char(2)>").unwrap();
+    assert_eq!(Names::check_kind(&kind), Ok(()));
+
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let output_stream = OutputStream::to_local_file(path.to_str().unwrap()).unwrap();
+    let mut writer = Writer::new(output_stream, &kind, &WriterOptions::default()).unwrap();
+    let mut batch = writer.row_batch(2);
+    {
+        let mut structs = batch.borrow_mut().try_into_structs().unwrap();
+        structs.push();
+        structs.push();
+        let mut fields = structs.fields().into_iter();
+        let mut names = fields.next().unwrap().try_into_strings().unwrap();
+        names.push("alice");
+        names.push("bob");
+        let mut codes = fields.next().unwrap().try_into_strings().unwrap();
+        codes.push("fr");
+        codes.push_null();
+    }
+    writer.write_batch(&mut batch).unwrap();
+    writer.close().unwrap();
+
+    let input_stream = reader::InputStream::from_local_file(path.to_str().unwrap()).unwrap();
+    let reader = reader::Reader::new(input_stream).unwrap();
+    let mut row_reader = reader
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    Names::check_kind(&row_reader.selected_kind()).unwrap();
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    assert_eq!(
+        Names::from_vector_batch(&batch.borrow()).unwrap(),
+        vec![
+            Names {
+                name: "alice".to_owned(),
+                code: Some("fr".to_owned()),
+            },
+            Names {
+                name: "bob".to_owned(),
+                code: None,
+            },
+        ]
+    );
+}
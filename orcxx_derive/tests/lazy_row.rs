@@ -0,0 +1,138 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(feature = "lazy_row")]
+
+/// Tests orcxx::lazy_row and #[orc(lazy)]
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+
+use orcxx::errors::OpenOrcError;
+use orcxx::lazy_row::LazyRowIterator;
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+#[orc(lazy)]
+struct Row {
+    int1: Option<i32>,
+    #[orc(rename = "string1")]
+    string: Option<String>,
+    #[orc(default = "42")]
+    not_a_column: i64,
+}
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+#[orc(lazy)]
+struct AssumeUtf8Row {
+    #[orc(unsafe_assume_utf8)]
+    string1: Option<String>,
+}
+
+// Safe because the test file only contains valid UTF-8 strings
+unsafe impl orcxx::deserialize::AssumeUtf8 for AssumeUtf8Row {}
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+#[orc(lazy)]
+struct Mismatched {
+    string1: i64,
+}
+
+fn get_reader() -> reader::Reader {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.testStripeLevelStats.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+#[test]
+fn lazy_row() {
+    let reader = get_reader();
+    let expected_rows: Vec<Row> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+    assert!(
+        expected_rows.len() > 10,
+        "Test file should have more than 10 rows"
+    );
+
+    for batch_size in [1, 7, 1000] {
+        let mut rows: LazyRowIterator<Row> =
+            LazyRowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap()).unwrap();
+        let mut expected_rows = expected_rows.iter();
+        while let Some(batch) = rows.next_batch() {
+            assert!(batch.len() as u64 <= batch_size);
+            assert_eq!(batch.rows().len(), batch.len());
+            assert!(batch.get(batch.len()).is_none());
+            for (index, row) in batch.rows().enumerate() {
+                let expected_row = expected_rows.next().expect("Too many rows");
+                assert_eq!(row.index(), index);
+                // Only deserialize string1 for some of the rows
+                if index % 2 == 0 {
+                    assert_eq!(row.string(), &expected_row.string);
+                }
+                assert_eq!(row.int1(), &expected_row.int1);
+                assert_eq!(row.not_a_column(), &42);
+            }
+        }
+        assert_eq!(expected_rows.next(), None, "Missing rows");
+    }
+}
+
+#[test]
+fn lazy_row_try_accessors() {
+    let reader = get_reader();
+    let expected_rows: Vec<Row> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+
+    let mut rows: LazyRowIterator<Row> =
+        LazyRowIterator::new(&reader, NonZeroU64::new(7).unwrap()).unwrap();
+    let mut expected_rows = expected_rows.iter();
+    while let Some(batch) = rows.next_batch() {
+        for row in batch.rows() {
+            let expected_row = expected_rows.next().expect("Too many rows");
+            assert_eq!(row.try_string(), Ok(&expected_row.string));
+            assert_eq!(row.try_int1(), Ok(&expected_row.int1));
+            assert_eq!(row.try_not_a_column(), Ok(&42));
+        }
+    }
+    assert_eq!(expected_rows.next(), None, "Missing rows");
+}
+
+#[test]
+fn lazy_row_assume_utf8() {
+    let reader = get_reader();
+    let expected_rows: Vec<Row> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+
+    let mut rows: LazyRowIterator<AssumeUtf8Row> =
+        LazyRowIterator::new(&reader, NonZeroU64::new(1000).unwrap()).unwrap();
+    let mut expected_rows = expected_rows.iter();
+    while let Some(batch) = rows.next_batch() {
+        for row in batch.rows() {
+            let expected_row = expected_rows.next().expect("Too many rows");
+            assert_eq!(row.string1(), &expected_row.string);
+        }
+    }
+    assert_eq!(expected_rows.next(), None, "Missing rows");
+}
+
+#[test]
+fn lazy_row_mismatched_kind() {
+    let reader = get_reader();
+    match LazyRowIterator::<Mismatched>::new(&reader, NonZeroU64::new(1000).unwrap()) {
+        Ok(_) => panic!("Kind mismatch was not detected"),
+        Err(OpenOrcError::KindError(msg)) => assert!(
+            msg.starts_with("Mismatched cannot be decoded:"),
+            "Unexpected error: {}",
+            msg
+        ),
+        Err(e) => panic!("Unexpected error: {}", e),
+    }
+}
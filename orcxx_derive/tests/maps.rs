@@ -0,0 +1,143 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroU64;
+
+use orcxx::deserialize::{CheckableKind, OrcDeserialize, OrcStruct};
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Item {
+    int1: i32,
+    string1: String,
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+struct HashMapRow {
+    string1: String,
+    map: HashMap<String, Item>,
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+struct BTreeMapRow {
+    string1: String,
+    map: Option<BTreeMap<String, Option<Item>>>,
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+struct VecRow {
+    map: Vec<(String, Item)>,
+}
+
+fn get_reader() -> reader::Reader {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+fn read<T>() -> Vec<T>
+where
+    T: OrcStruct + CheckableKind + OrcDeserialize + Default + Clone,
+{
+    let reader = get_reader();
+    let options = reader::RowReaderOptions::default().include_names(T::columns());
+    T::check_kind(&reader.row_reader(&options).unwrap().selected_kind()).unwrap();
+    RowIterator::new(&reader, NonZeroU64::new(1024).unwrap())
+        .unwrap()
+        .collect()
+}
+
+fn item(int1: i32, string1: &str) -> Item {
+    Item {
+        int1,
+        string1: string1.to_owned(),
+    }
+}
+
+#[test]
+fn columns() {
+    assert_eq!(HashMapRow::columns(), vec!["string1", "map"]);
+    assert_eq!(VecRow::columns(), vec!["map"]);
+}
+
+#[test]
+fn check_kind() {
+    let kind = Kind::new("struct<map:map<string,struct<int1:int,string1:string>>>").unwrap();
+    assert_eq!(VecRow::check_kind(&kind), Ok(()));
+
+    let kind = Kind::new("struct<map:map<int,struct<int1:int,string1:string>>>").unwrap();
+    assert!(VecRow::check_kind(&kind).is_err());
+
+    let kind = Kind::new("struct<map:array<struct<int1:int,string1:string>>>").unwrap();
+    assert!(VecRow::check_kind(&kind).is_err());
+}
+
+#[test]
+fn hash_map() {
+    assert_eq!(
+        read::<HashMapRow>(),
+        vec![
+            HashMapRow {
+                string1: "hi".to_owned(),
+                map: HashMap::new(),
+            },
+            HashMapRow {
+                string1: "bye".to_owned(),
+                map: vec![
+                    ("chani".to_owned(), item(5, "chani")),
+                    ("mauddib".to_owned(), item(1, "mauddib")),
+                ]
+                .into_iter()
+                .collect(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn btree_map() {
+    assert_eq!(
+        read::<BTreeMapRow>(),
+        vec![
+            BTreeMapRow {
+                string1: "hi".to_owned(),
+                map: Some(BTreeMap::new()),
+            },
+            BTreeMapRow {
+                string1: "bye".to_owned(),
+                map: Some(
+                    vec![
+                        ("chani".to_owned(), Some(item(5, "chani"))),
+                        ("mauddib".to_owned(), Some(item(1, "mauddib"))),
+                    ]
+                    .into_iter()
+                    .collect()
+                ),
+            },
+        ]
+    );
+}
+
+#[test]
+fn vec() {
+    let mut rows = read::<VecRow>();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].map, vec![]);
+    rows[1].map.sort();
+    assert_eq!(
+        rows[1].map,
+        vec![
+            ("chani".to_owned(), item(5, "chani")),
+            ("mauddib".to_owned(), item(1, "mauddib")),
+        ]
+    );
+}
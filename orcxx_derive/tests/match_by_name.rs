@@ -0,0 +1,124 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+
+use orcxx::deserialize::{CheckableKind, OrcDeserialize};
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+const ORC_PATH: &str = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+
+/// Same fields as in the file, but in a different order
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+#[orc(match_by_name)]
+struct Test1 {
+    string1: String,
+    list: Vec<Item>,
+    #[orc(rename = "int1")]
+    int: i32,
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+#[orc(match_by_name)]
+struct Item {
+    string1: String,
+    int1: i32,
+}
+
+fn item(int1: i32, string1: &str) -> Item {
+    Item {
+        string1: string1.to_owned(),
+        int1,
+    }
+}
+
+fn expected_rows() -> Vec<Test1> {
+    vec![
+        Test1 {
+            string1: "hi".to_owned(),
+            list: vec![item(3, "good"), item(4, "bad")],
+            int: 65536,
+        },
+        Test1 {
+            string1: "bye".to_owned(),
+            list: vec![
+                item(100000000, "cat"),
+                item(-100000, "in"),
+                item(1234, "hat"),
+            ],
+            int: 65536,
+        },
+    ]
+}
+
+fn reader() -> reader::Reader {
+    let input_stream = reader::InputStream::from_local_file(ORC_PATH).unwrap();
+    reader::Reader::new(input_stream).unwrap()
+}
+
+#[test]
+fn read_file() {
+    let rows: Vec<Test1> = orcxx::read_file(ORC_PATH).unwrap();
+    assert_eq!(rows, expected_rows());
+}
+
+#[test]
+fn read_options() {
+    let rows: Vec<Option<Test1>> = orcxx::read_file(ORC_PATH).unwrap();
+    assert_eq!(
+        rows,
+        expected_rows().into_iter().map(Some).collect::<Vec<_>>()
+    );
+}
+
+/// Tests columns which are not fields of the structure are ignored
+#[test]
+fn all_columns() {
+    let rows: RowIterator<Test1> = RowIterator::new_with_options(
+        &reader(),
+        NonZeroU64::new(1024).unwrap(),
+        &reader::RowReaderOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(rows.collect::<Vec<_>>(), expected_rows());
+}
+
+#[test]
+fn read_from_vector_batch_with_kind() {
+    let options = reader::RowReaderOptions::default().include_names(["int1", "string1"]);
+    let mut row_reader = reader().row_reader(&options).unwrap();
+    let kind = row_reader.selected_kind();
+    Item::check_kind(&kind).unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    let mut rows = vec![Item::default(); 2];
+    Item::read_from_vector_batch_with_kind(&batch.borrow(), &kind, &mut rows).unwrap();
+    assert_eq!(rows, vec![item(65536, "hi"), item(65536, "bye")]);
+}
+
+#[test]
+fn check_kind() {
+    let kind = Kind::new("struct<int1:int,string1:string>").unwrap();
+    assert_eq!(Item::check_kind(&kind), Ok(()));
+
+    let kind = Kind::new("struct<int1:int,string2:string>").unwrap();
+    assert_eq!(
+        Item::check_kind(&kind),
+        Err("Item cannot be decoded:\n\tField string1 is missing".to_owned())
+    );
+
+    let kind = Kind::new("struct<int1:string,string1:string>").unwrap();
+    assert_eq!(
+        Item::check_kind(&kind),
+        Err("Item cannot be decoded:\n\tField int1 cannot be decoded: i32 must be decoded from ORC Int, not ORC String".to_owned())
+    );
+}
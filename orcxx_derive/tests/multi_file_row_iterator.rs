@@ -0,0 +1,144 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+
+use orcxx::errors::OpenOrcError;
+use orcxx::multi_file_row_iterator::MultiFileRowIterator;
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+/// Both files have `int1:int` and `string1:string` columns
+const ORC_PATHS: [&str; 3] = [
+    "../orcxx/orc/examples/TestOrcFile.test1.orc",
+    "../orcxx/orc/examples/TestOrcFile.testStripeLevelStats.orc",
+    "../orcxx/orc/examples/TestOrcFile.test1.orc",
+];
+
+fn get_reader(orc_path: &str) -> reader::Reader {
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+fn expected_rows() -> Vec<Row> {
+    ORC_PATHS
+        .iter()
+        .flat_map(|path| {
+            RowIterator::<Row>::new(&get_reader(path), NonZeroU64::new(1024).unwrap()).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn iterate() {
+    let expected_rows = expected_rows();
+    let readers: Vec<_> = ORC_PATHS.iter().map(|path| get_reader(path)).collect();
+    for batch_size in [1, 7, 1024] {
+        let rows: MultiFileRowIterator<Row> =
+            MultiFileRowIterator::new(&readers, NonZeroU64::new(batch_size).unwrap()).unwrap();
+        assert_eq!(rows.row_count(), expected_rows.len() as u64);
+        assert_eq!(rows.len(), expected_rows.len());
+        assert_eq!(
+            rows.collect::<Vec<_>>(),
+            expected_rows,
+            "batch_size = {}",
+            batch_size
+        );
+
+        let rows: MultiFileRowIterator<Row> =
+            MultiFileRowIterator::from_paths(ORC_PATHS, NonZeroU64::new(batch_size).unwrap())
+                .unwrap();
+        assert_eq!(
+            rows.rev().collect::<Vec<_>>(),
+            expected_rows.iter().rev().cloned().collect::<Vec<_>>(),
+            "batch_size = {}",
+            batch_size
+        );
+    }
+}
+
+#[test]
+fn seek() {
+    let expected_rows = expected_rows();
+    let row_count = expected_rows.len();
+
+    // In the first file, the second one, the last one, and at file boundaries
+    let targets = [
+        row_count / 2,
+        0,
+        row_count - 1,
+        1,
+        2,
+        row_count - 2,
+        row_count - 3,
+        row_count / 3,
+    ];
+    for batch_size in [1, 7, 1024] {
+        let mut rows: MultiFileRowIterator<Row> =
+            MultiFileRowIterator::from_paths(ORC_PATHS, NonZeroU64::new(batch_size).unwrap())
+                .unwrap();
+        for &target in &targets {
+            rows = rows.seek(target as u64);
+            assert_eq!(
+                rows.len(),
+                row_count - target,
+                "batch_size = {}, target = {}",
+                batch_size,
+                target
+            );
+            assert_eq!(
+                rows.next(),
+                Some(expected_rows[target].clone()),
+                "batch_size = {}, target = {}",
+                batch_size,
+                target
+            );
+            assert_eq!(
+                rows.next_back(),
+                Some(expected_rows[target].clone()),
+                "batch_size = {}, target = {}",
+                batch_size,
+                target
+            );
+            assert_eq!(
+                rows.next_back(),
+                target.checked_sub(1).map(|i| expected_rows[i].clone()),
+                "batch_size = {}, target = {}",
+                batch_size,
+                target
+            );
+        }
+
+        rows = rows.seek(row_count as u64);
+        assert_eq!(rows.len(), 0);
+        assert_eq!(rows.next(), None);
+        assert_eq!(rows.next_back(), expected_rows.last().cloned());
+    }
+}
+
+#[test]
+fn incompatible_file() {
+    let paths = [
+        "../orcxx/orc/examples/TestOrcFile.test1.orc",
+        "../orcxx/orc/examples/TestOrcFile.testDate1900.orc",
+    ];
+    match MultiFileRowIterator::<Row>::from_paths(paths, NonZeroU64::new(1024).unwrap()) {
+        Err(OpenOrcError::KindError(msg)) => {
+            assert!(msg.starts_with("File 1 cannot be read: "), "{}", msg)
+        }
+        Err(e) => panic!("Unexpected error: {}", e),
+        Ok(_) => panic!("Unexpected success"),
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+/// Tests orcxx::row_iterator::RowIterator::next_batch
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+fn get_reader() -> reader::Reader {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.testStripeLevelStats.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+#[test]
+fn next_batch() {
+    let reader = get_reader();
+    let expected_rows: Vec<Row> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+
+    for batch_size in [1, 7, 1000] {
+        let mut rows: RowIterator<Row> =
+            RowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap()).unwrap();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = rows.next_batch() {
+            assert!(!chunk.is_empty(), "batch_size = {}", batch_size);
+            assert!(
+                chunk.len() as u64 <= batch_size,
+                "batch_size = {}, chunk.len() = {}",
+                batch_size,
+                chunk.len()
+            );
+            chunks.push(chunk.to_vec());
+        }
+        assert_eq!(
+            chunks.concat(),
+            expected_rows,
+            "batch_size = {}",
+            batch_size
+        );
+        assert_eq!(rows.len(), 0);
+        assert_eq!(rows.next(), None);
+    }
+}
+
+#[test]
+fn next_batch_mixed_with_next() {
+    let reader = get_reader();
+    let expected_rows: Vec<Row> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+    let row_count = expected_rows.len();
+
+    let mut rows: RowIterator<Row> =
+        RowIterator::new(&reader, NonZeroU64::new(7).unwrap()).unwrap();
+    assert_eq!(rows.next(), Some(expected_rows[0].clone()));
+    assert_eq!(rows.next(), Some(expected_rows[1].clone()));
+
+    // Remainder of the first batch
+    assert_eq!(rows.next_batch(), Some(&expected_rows[2..7]));
+    assert_eq!(rows.next_batch(), Some(&expected_rows[7..14]));
+    assert_eq!(rows.next(), Some(expected_rows[14].clone()));
+    assert_eq!(rows.len(), row_count - 15);
+
+    // After seeking, starts from the target row
+    let mut rows = rows.seek(10);
+    let chunk = rows.next_batch().unwrap().to_vec();
+    assert_eq!(chunk, expected_rows[10..10 + chunk.len()]);
+
+    // Moves back into the batch
+    let last = chunk.last().cloned();
+    assert_eq!(rows.next_back(), last);
+    assert_eq!(rows.next_batch(), Some(&chunk[chunk.len() - 1..]));
+
+    let mut rows = rows.tail(3);
+    let mut last_rows = Vec::new();
+    while let Some(chunk) = rows.next_batch() {
+        last_rows.extend_from_slice(chunk);
+    }
+    assert_eq!(last_rows, expected_rows[row_count - 3..]);
+}
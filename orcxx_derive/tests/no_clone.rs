@@ -0,0 +1,96 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+/// Tests RowIterator and read_file on types which do not implement Clone
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+fn get_reader() -> reader::Reader {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.testStripeLevelStats.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+fn row_ids(rows: &[Row]) -> Vec<(Option<i32>, Option<&str>)> {
+    rows.iter()
+        .map(|row| (row.int1, row.string1.as_deref()))
+        .collect()
+}
+
+#[test]
+fn iterate() {
+    let reader = get_reader();
+    let expected_rows: Vec<Row> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+    assert!(
+        expected_rows.len() > 10,
+        "Test file should have more than 10 rows"
+    );
+
+    for batch_size in [1, 7, 1000] {
+        let rows: Vec<Row> = RowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap())
+            .unwrap()
+            .collect();
+        assert_eq!(row_ids(&rows), row_ids(&expected_rows));
+
+        let mut rows: Vec<Row> = RowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap())
+            .unwrap()
+            .rev()
+            .collect();
+        rows.reverse();
+        assert_eq!(row_ids(&rows), row_ids(&expected_rows));
+    }
+}
+
+/// Rows moved out of the iterator's buffer are deserialized again when the
+/// iterator goes back to them
+#[test]
+fn yield_again() {
+    let reader = get_reader();
+    let expected_rows: Vec<Row> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+
+    for batch_size in [1, 7, 1000] {
+        let mut rows: RowIterator<Row> =
+            RowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap()).unwrap();
+        assert_eq!(rows.next().as_ref(), Some(&expected_rows[0]));
+        assert_eq!(rows.next().as_ref(), Some(&expected_rows[1]));
+        assert_eq!(rows.next_back().as_ref(), Some(&expected_rows[1]));
+        assert_eq!(rows.next_back().as_ref(), Some(&expected_rows[0]));
+        assert_eq!(rows.next().as_ref(), Some(&expected_rows[0]));
+        assert_eq!(
+            row_ids(rows.next_batch().unwrap()),
+            row_ids(&expected_rows[1..usize::min(batch_size as usize, expected_rows.len())])
+        );
+
+        let mut rows = rows.seek(0);
+        assert_eq!(rows.next().as_ref(), Some(&expected_rows[0]));
+        assert!(rows.take_errors().is_empty());
+    }
+}
+
+#[test]
+fn read_file() {
+    let rows: Vec<Row> =
+        orcxx::read_file("../orcxx/orc/examples/TestOrcFile.testStripeLevelStats.orc").unwrap();
+    let expected_rows: Vec<Row> = RowIterator::new(&get_reader(), NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(row_ids(&rows), row_ids(&expected_rows));
+}
@@ -0,0 +1,99 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+
+use orcxx::deserialize::{CheckableKind, OrcDeserialize, OrcStruct};
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+/// Structures without `Default` are only ever built from their deserialized fields
+#[derive(OrcDeserialize, Clone, Debug, PartialEq)]
+struct Test1NoDefault {
+    int1: Option<i32>,
+    string1: Option<String>,
+    list: Option<Vec<Option<Test1ItemNoDefault>>>,
+}
+
+#[derive(OrcDeserialize, Clone, Debug, PartialEq)]
+struct Test1ItemNoDefault {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+fn get_reader() -> reader::Reader {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+fn get_row_reader() -> reader::RowReader {
+    let reader = get_reader();
+    let options = reader::RowReaderOptions::default().include_names(Test1NoDefault::columns());
+    reader.row_reader(&options).unwrap()
+}
+
+fn item(int1: i32, string1: &str) -> Option<Test1ItemNoDefault> {
+    Some(Test1ItemNoDefault {
+        int1: Some(int1),
+        string1: Some(string1.to_owned()),
+    })
+}
+
+fn expected_rows() -> Vec<Option<Test1NoDefault>> {
+    vec![
+        Some(Test1NoDefault {
+            int1: Some(65536),
+            string1: Some("hi".to_owned()),
+            list: Some(vec![item(3, "good"), item(4, "bad")]),
+        }),
+        Some(Test1NoDefault {
+            int1: Some(65536),
+            string1: Some("bye".to_owned()),
+            list: Some(vec![
+                item(100000000, "cat"),
+                item(-100000, "in"),
+                item(1234, "hat"),
+            ]),
+        }),
+    ]
+}
+
+#[test]
+fn columns() {
+    assert_eq!(
+        Test1NoDefault::columns(),
+        vec!["int1", "string1", "list.int1", "list.string1"]
+    );
+}
+
+#[test]
+fn from_vector_batch() {
+    let mut row_reader = get_row_reader();
+    Test1NoDefault::check_kind(&row_reader.selected_kind()).unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+    let mut rows = Vec::new();
+    while row_reader.read_into(&mut batch) {
+        rows.extend(Option::<Test1NoDefault>::from_vector_batch(&batch.borrow()).unwrap());
+    }
+
+    assert_eq!(rows, expected_rows());
+}
+
+#[test]
+fn row_iterator() {
+    for batch_size in 1..4 {
+        let rows: Vec<Option<Test1NoDefault>> =
+            RowIterator::new(&get_reader(), NonZeroU64::new(batch_size).unwrap())
+                .unwrap()
+                .collect();
+        assert_eq!(rows, expected_rows(), "batch_size = {}", batch_size);
+    }
+}
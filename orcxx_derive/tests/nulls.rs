@@ -6,8 +6,11 @@
 extern crate orcxx;
 extern crate orcxx_derive;
 
+use std::num::NonZeroU64;
+
 use orcxx::deserialize::{CheckableKind, DeserializationError, OrcDeserialize};
 use orcxx::reader;
+use orcxx::row_iterator::{OnError, RowIterator};
 use orcxx_derive::OrcDeserialize;
 
 fn row_reader() -> reader::RowReader {
@@ -76,8 +79,217 @@ fn test_string_no_option() {
     assert!(row_reader.read_into(&mut batch));
     assert_eq!(
         Root::from_vector_batch(&batch.borrow()),
-        Err(DeserializationError::UnexpectedNull(
-            "String column contains nulls".to_owned()
-        ))
+        Err(DeserializationError::Located {
+            row: Some(2),
+            column: "string1".to_owned(),
+            source: Box::new(DeserializationError::UnexpectedNull(
+                "String column contains nulls".to_owned()
+            ))
+        })
     );
 }
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct RootNoOption {
+    bytes1: Vec<u8>,
+    string1: String,
+}
+
+fn row_iterator(batch_size: u64) -> RowIterator<RootNoOption> {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.testStringAndBinaryStatistics.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    RowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap()).unwrap()
+}
+
+#[test]
+fn test_row_iterator_skip_errors() {
+    for batch_size in [1, 2, 3, 1024] {
+        let mut iter = row_iterator(batch_size).skip_errors();
+        assert_eq!(
+            iter.by_ref().collect::<Vec<_>>(),
+            vec![
+                RootNoOption {
+                    bytes1: [0, 1, 2, 3, 4].to_vec(),
+                    string1: "foo".to_owned()
+                },
+                RootNoOption {
+                    bytes1: [0, 1, 2, 3].to_vec(),
+                    string1: "bar".to_owned()
+                },
+            ],
+            "batch_size = {batch_size}"
+        );
+        let errors = iter.take_errors();
+        assert_eq!(
+            errors
+                .iter()
+                .map(|(row_number, e)| (*row_number, e.column(), e.inner()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    2,
+                    "string1",
+                    &DeserializationError::UnexpectedNull(
+                        "String column contains nulls".to_owned()
+                    )
+                ),
+                (
+                    3,
+                    "bytes1",
+                    &DeserializationError::UnexpectedNull(
+                        "Vec<u8> column contains nulls".to_owned()
+                    )
+                ),
+            ],
+            "batch_size = {batch_size}"
+        );
+        assert_eq!(iter.take_errors(), vec![]);
+        assert_eq!(iter.position(), 4, "batch_size = {batch_size}");
+    }
+}
+
+#[test]
+fn test_row_iterator_skip_errors_seek() {
+    for batch_size in [1, 2, 3, 1024] {
+        let mut iter = row_iterator(batch_size).skip_errors();
+        assert_eq!(iter.by_ref().count(), 2, "batch_size = {batch_size}");
+        let mut iter = iter.seek(0);
+        assert_eq!(iter.by_ref().count(), 2, "batch_size = {batch_size}");
+        assert_eq!(
+            iter.take_errors()
+                .into_iter()
+                .map(|(row_number, _)| row_number)
+                .collect::<Vec<_>>(),
+            vec![2, 3],
+            "batch_size = {batch_size}"
+        );
+    }
+}
+
+#[test]
+fn test_row_iterator_skip_errors_size_hint() {
+    let mut iter = row_iterator(1024).skip_errors();
+    // Failing rows are not known before the batch is decoded
+    assert_eq!(iter.size_hint(), (0, Some(4)));
+    assert!(iter.next().is_some());
+    assert_eq!(iter.size_hint(), (1, Some(1)));
+    assert!(iter.next().is_some());
+    assert_eq!(iter.size_hint(), (0, Some(0)));
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_row_iterator_default_on_errors() {
+    for batch_size in [1, 2, 3, 1024] {
+        let mut iter = row_iterator(batch_size).on_error(OnError::Default);
+        assert_eq!(
+            iter.by_ref().collect::<Vec<_>>(),
+            vec![
+                RootNoOption {
+                    bytes1: [0, 1, 2, 3, 4].to_vec(),
+                    string1: "foo".to_owned()
+                },
+                RootNoOption {
+                    bytes1: [0, 1, 2, 3].to_vec(),
+                    string1: "bar".to_owned()
+                },
+                RootNoOption::default(),
+                RootNoOption::default(),
+            ],
+            "batch_size = {batch_size}"
+        );
+        assert_eq!(
+            iter.take_errors()
+                .into_iter()
+                .map(|(row_number, _)| row_number)
+                .collect::<Vec<_>>(),
+            vec![2, 3],
+            "batch_size = {batch_size}"
+        );
+    }
+}
+
+#[test]
+fn test_row_iterator_try_rows() {
+    for batch_size in [1, 2, 3, 1024] {
+        let rows: Vec<_> = row_iterator(batch_size).try_rows().collect();
+        assert_eq!(
+            rows[..2],
+            [
+                Ok(RootNoOption {
+                    bytes1: [0, 1, 2, 3, 4].to_vec(),
+                    string1: "foo".to_owned()
+                }),
+                Ok(RootNoOption {
+                    bytes1: [0, 1, 2, 3].to_vec(),
+                    string1: "bar".to_owned()
+                }),
+            ],
+            "batch_size = {batch_size}"
+        );
+        assert_eq!(
+            rows[2..]
+                .iter()
+                .map(|row| row.as_ref().map_err(|e| e.column()))
+                .collect::<Vec<_>>(),
+            vec![Err("string1"), Err("bytes1")],
+            "batch_size = {batch_size}"
+        );
+        assert_eq!(
+            row_iterator(batch_size)
+                .try_rows()
+                .rev()
+                .collect::<Vec<_>>(),
+            rows.into_iter().rev().collect::<Vec<_>>(),
+            "batch_size = {batch_size}"
+        );
+    }
+}
+
+#[test]
+fn test_row_iterator_try_next() {
+    for batch_size in [1, 2, 3, 1024] {
+        let mut iter = row_iterator(batch_size);
+        let mut rows = Vec::new();
+        while let Some(row) = iter.try_next() {
+            rows.push(row.map_err(|e| e.column().to_owned()));
+        }
+        assert_eq!(
+            rows,
+            vec![
+                Ok(RootNoOption {
+                    bytes1: [0, 1, 2, 3, 4].to_vec(),
+                    string1: "foo".to_owned()
+                }),
+                Ok(RootNoOption {
+                    bytes1: [0, 1, 2, 3].to_vec(),
+                    string1: "bar".to_owned()
+                }),
+                Err("string1".to_owned()),
+                Err("bytes1".to_owned()),
+            ],
+            "batch_size = {batch_size}"
+        );
+        assert_eq!(iter.take_errors(), vec![]);
+
+        let mut iter = row_iterator(batch_size);
+        let mut rows = Vec::new();
+        while let Some(row) = iter.try_next_back() {
+            rows.push(
+                row.map(|row| row.string1)
+                    .map_err(|e| e.column().to_owned()),
+            );
+        }
+        assert_eq!(
+            rows,
+            vec![
+                Err("bytes1".to_owned()),
+                Err("string1".to_owned()),
+                Ok("bar".to_owned()),
+                Ok("foo".to_owned()),
+            ],
+            "batch_size = {batch_size}"
+        );
+    }
+}
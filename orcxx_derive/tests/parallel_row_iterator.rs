@@ -8,7 +8,7 @@ use std::sync::Arc;
 
 use rayon::iter::{IndexedParallelIterator, ParallelIterator};
 
-use orcxx::parallel_row_iterator::ParallelRowIterator;
+use orcxx::parallel_row_iterator::{try_par_rows, ParallelRowIterator};
 use orcxx::reader;
 use orcxx::row_iterator::RowIterator;
 use orcxx_derive::OrcDeserialize;
@@ -52,8 +52,48 @@ fn test_seek() {
     );
 
     let mut par_rows = Vec::new();
-    ParallelRowIterator::<Row>::new(reader, 10.try_into().unwrap())
+    ParallelRowIterator::<Row>::new(reader.clone(), 10.try_into().unwrap())
         .unwrap()
         .collect_into_vec(&mut par_rows);
     assert_eq!(seq_rows, par_rows);
+
+    // Many small splits, which reuse row readers
+    for min_split_len in [1, 7, 1000] {
+        let mut par_rows = Vec::new();
+        ParallelRowIterator::<Row>::new(reader.clone(), 10.try_into().unwrap())
+            .unwrap()
+            .min_split_len(min_split_len)
+            .collect_into_vec(&mut par_rows);
+        assert_eq!(seq_rows, par_rows, "min_split_len = {}", min_split_len);
+    }
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct RowNoOption {
+    bytes1: Vec<u8>,
+    string1: String,
+}
+
+#[test]
+fn test_try_par_rows() {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.testStringAndBinaryStatistics.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = Arc::new(reader::Reader::new(input_stream).expect("Could not read .orc"));
+
+    for batch_size in [1, 2, 3, 1024] {
+        let seq_rows = RowIterator::<RowNoOption>::new(&reader, batch_size.try_into().unwrap())
+            .unwrap()
+            .try_rows()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            seq_rows.iter().map(|row| row.is_ok()).collect::<Vec<_>>(),
+            vec![true, true, false, false]
+        );
+
+        let mut par_rows = Vec::new();
+        try_par_rows::<RowNoOption>(reader.clone(), batch_size.try_into().unwrap())
+            .unwrap()
+            .collect_into_vec(&mut par_rows);
+        assert_eq!(seq_rows, par_rows, "batch_size = {}", batch_size);
+    }
 }
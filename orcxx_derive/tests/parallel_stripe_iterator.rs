@@ -0,0 +1,146 @@
+/// Tests orcxx::parallel_stripe_iterator::ParStripeIterator
+extern crate orcxx;
+extern crate orcxx_derive;
+extern crate rayon;
+extern crate tempfile;
+
+use std::convert::TryInto;
+use std::fs;
+use std::sync::Arc;
+
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+
+use orcxx::errors::OpenOrcError;
+use orcxx::parallel_stripe_iterator::{par_iter_orc_dir, ParStripeIterator};
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    boolean1: bool,
+    byte1: i8,
+    short1: i16,
+    int1: i32,
+    long1: i64,
+    float1: f32,
+    double1: f64,
+    bytes1: Vec<u8>,
+    string1: String,
+    list: Vec<Item>,
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Item {
+    int1: i32,
+    string1: String,
+}
+
+#[test]
+fn test_seek() {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.testSeek.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+
+    let seq_rows = RowIterator::<Row>::new(&reader, 10.try_into().unwrap())
+        .unwrap()
+        .collect::<Vec<_>>();
+    let stripe_sizes = reader
+        .stripes()
+        .map(|stripe| stripe.rows_count() as usize)
+        .collect::<Vec<_>>();
+    assert!(
+        stripe_sizes.len() > 1,
+        "Test file should have several stripes"
+    );
+
+    let reader = Arc::new(reader);
+
+    let mut par_stripes = Vec::new();
+    ParStripeIterator::<Row>::new(reader.clone(), 10.try_into().unwrap())
+        .unwrap()
+        .collect_into_vec(&mut par_stripes);
+    assert_eq!(
+        par_stripes.iter().map(Vec::len).collect::<Vec<_>>(),
+        stripe_sizes
+    );
+    assert_eq!(seq_rows, par_stripes.concat());
+
+    assert_eq!(
+        seq_rows,
+        ParStripeIterator::<Row>::new(reader, 1024.try_into().unwrap())
+            .unwrap()
+            .flatten_iter()
+            .collect::<Vec<_>>(),
+    );
+}
+
+fn read_rows(orc_path: &str) -> Vec<Row> {
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    RowIterator::<Row>::new(&reader, 1024.try_into().unwrap())
+        .unwrap()
+        .collect()
+}
+
+#[test]
+fn test_par_iter_orc_dir() {
+    let test1_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+    let seek_path = "../orcxx/orc/examples/TestOrcFile.testSeek.orc";
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    fs::copy(test1_path, dir.path().join("a.orc")).unwrap();
+    fs::copy(seek_path, dir.path().join("sub").join("b.orc")).unwrap();
+    fs::copy(test1_path, dir.path().join("c.orc.bak")).unwrap();
+    fs::write(dir.path().join("README"), "not an ORC file").unwrap();
+
+    let expected_rows = [read_rows(test1_path), read_rows(seek_path)].concat();
+
+    for batch_size in [1, 7, 1024] {
+        let rows = par_iter_orc_dir::<Row>(dir.path(), batch_size.try_into().unwrap())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(rows, expected_rows);
+    }
+}
+
+#[test]
+fn test_par_iter_orc_dir_schema_mismatch() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::copy(
+        "../orcxx/orc/examples/TestOrcFile.test1.orc",
+        dir.path().join("a.orc"),
+    )
+    .unwrap();
+    let bad_path = dir.path().join("b.orc");
+    fs::copy(
+        "../orcxx/orc/examples/TestOrcFile.testDate1900.orc",
+        &bad_path,
+    )
+    .unwrap();
+
+    let rows = par_iter_orc_dir::<Row>(dir.path(), 1024.try_into().unwrap())
+        .expect("Could not list directory")
+        .collect::<Result<Vec<_>, _>>();
+    match rows {
+        Ok(_) => panic!("Schema mismatch was not detected"),
+        Err(OpenOrcError::KindError(msg)) => assert!(
+            msg.starts_with(&format!("{} cannot be read: ", bad_path.display())),
+            "Unexpected error: {}",
+            msg
+        ),
+        Err(e) => panic!("Unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn test_par_iter_orc_dir_missing() {
+    let dir = tempfile::tempdir().unwrap();
+    match par_iter_orc_dir::<Row>(&dir.path().join("missing"), 1024.try_into().unwrap()) {
+        Ok(_) => panic!("Missing directory was not detected"),
+        Err(OpenOrcError::IoError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+        Err(e) => panic!("Unexpected error: {}", e),
+    }
+}
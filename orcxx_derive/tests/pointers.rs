@@ -0,0 +1,85 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+/// Tests deserializing fields wrapped in `Box`, `Rc`, and `Arc`
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Test1 {
+    int1: Box<i32>,
+    string1: Rc<String>,
+    middle: Arc<Middle>,
+    list: Option<Vec<Option<Box<Item>>>>,
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Middle {
+    list: Vec<Arc<Item>>,
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Item {
+    int1: i32,
+    string1: String,
+}
+
+fn item(int1: i32, string1: &str) -> Item {
+    Item {
+        int1,
+        string1: string1.to_owned(),
+    }
+}
+
+#[test]
+fn test1() {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+
+    let middle = Arc::new(Middle {
+        list: vec![Arc::new(item(1, "bye")), Arc::new(item(2, "sigh"))],
+    });
+
+    for batch_size in [1, 2, 1024] {
+        let rows: Vec<Test1> = RowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap())
+            .expect("Could not open ORC file")
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                Test1 {
+                    int1: Box::new(65536),
+                    string1: Rc::new("hi".to_owned()),
+                    middle: middle.clone(),
+                    list: Some(vec![
+                        Some(Box::new(item(3, "good"))),
+                        Some(Box::new(item(4, "bad"))),
+                    ]),
+                },
+                Test1 {
+                    int1: Box::new(65536),
+                    string1: Rc::new("bye".to_owned()),
+                    middle: middle.clone(),
+                    list: Some(vec![
+                        Some(Box::new(item(100000000, "cat"))),
+                        Some(Box::new(item(-100000, "in"))),
+                        Some(Box::new(item(1234, "hat"))),
+                    ]),
+                },
+            ],
+            "batch_size = {}",
+            batch_size
+        );
+    }
+}
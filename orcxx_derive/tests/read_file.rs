@@ -0,0 +1,76 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use orcxx::errors::{OpenOrcError, ReaderError};
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Test1 {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+fn expected_rows() -> Vec<Option<Test1>> {
+    vec![
+        Some(Test1 {
+            int1: Some(65536),
+            string1: Some("hi".to_owned()),
+        }),
+        Some(Test1 {
+            int1: Some(65536),
+            string1: Some("bye".to_owned()),
+        }),
+    ]
+}
+
+#[test]
+fn read_file() {
+    let rows: Vec<Option<Test1>> =
+        orcxx::read_file("../orcxx/orc/examples/TestOrcFile.test1.orc").unwrap();
+    assert_eq!(rows, expected_rows());
+}
+
+#[test]
+fn stream_file() {
+    let rows: RowIterator<Option<Test1>> =
+        orcxx::stream_file("../orcxx/orc/examples/TestOrcFile.test1.orc").unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows.collect::<Vec<_>>(), expected_rows());
+}
+
+#[test]
+fn read_nonexistent_file() {
+    match orcxx::read_file::<Option<Test1>>("nonexistent.orc") {
+        Err(OpenOrcError::OrcError(_)) => (),
+        r => panic!("{:?}", r),
+    }
+}
+
+#[test]
+fn read_non_orc_file() {
+    match orcxx::read_file::<Option<Test1>>("Cargo.toml") {
+        Err(OpenOrcError::ReaderError(ReaderError::NotAnOrcFile)) => (),
+        r => panic!("{:?}", r),
+    }
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Test1IncorrectType {
+    int1: Option<String>,
+}
+
+#[test]
+fn read_file_kind_error() {
+    match orcxx::read_file::<Option<Test1IncorrectType>>(
+        "../orcxx/orc/examples/TestOrcFile.test1.orc",
+    ) {
+        Err(OpenOrcError::KindError(_)) => (),
+        r => panic!("{:?}", r),
+    }
+}
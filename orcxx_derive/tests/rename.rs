@@ -0,0 +1,105 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use orcxx::deserialize::{CheckableKind, OrcDeserialize, OrcStruct};
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+struct Test1Renamed {
+    #[orc(rename = "long1")]
+    long: i64,
+    #[orc(rename = "string1")]
+    r#type: Option<String>,
+    #[orc(rename = "list")]
+    items: Vec<Item>,
+}
+
+#[derive(OrcDeserialize, Default, Debug, PartialEq)]
+struct Item {
+    #[orc(rename = "int1")]
+    id: i32,
+    string1: String,
+}
+
+fn row_reader(options: &reader::RowReaderOptions) -> reader::RowReader {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    reader.row_reader(options).unwrap()
+}
+
+#[test]
+fn columns() {
+    assert_eq!(
+        Test1Renamed::columns(),
+        vec!["long1", "string1", "list.int1", "list.string1"]
+    );
+}
+
+#[test]
+fn read() {
+    let options = reader::RowReaderOptions::default().include_names(Test1Renamed::columns());
+    let mut row_reader = row_reader(&options);
+    Test1Renamed::check_kind(&row_reader.selected_kind()).unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    let rows = Test1Renamed::from_vector_batch(&batch.borrow()).unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Test1Renamed {
+                long: i64::MAX,
+                r#type: Some("hi".to_owned()),
+                items: vec![
+                    Item {
+                        id: 3,
+                        string1: "good".to_owned()
+                    },
+                    Item {
+                        id: 4,
+                        string1: "bad".to_owned()
+                    },
+                ],
+            },
+            Test1Renamed {
+                long: i64::MAX,
+                r#type: Some("bye".to_owned()),
+                items: vec![
+                    Item {
+                        id: 100000000,
+                        string1: "cat".to_owned()
+                    },
+                    Item {
+                        id: -100000,
+                        string1: "in".to_owned()
+                    },
+                    Item {
+                        id: 1234,
+                        string1: "hat".to_owned()
+                    },
+                ],
+            },
+        ]
+    );
+}
+
+#[test]
+fn check_kind() {
+    let kind =
+        Kind::new("struct<long:bigint,string1:string,list:array<struct<int1:int,string1:string>>>")
+            .unwrap();
+    assert_eq!(
+        Test1Renamed::check_kind(&kind),
+        Err(
+            "Test1Renamed cannot be decoded:\n\tField #0 must be called long1, not long".to_owned()
+        )
+    );
+}
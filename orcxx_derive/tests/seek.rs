@@ -0,0 +1,182 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+fn get_reader() -> reader::Reader {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.testStripeLevelStats.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+#[test]
+fn seek_forward_and_backward() {
+    let reader = get_reader();
+    let expected_rows: Vec<Option<Row>> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+    let row_count = expected_rows.len();
+    assert!(row_count > 10, "Test file should have more than 10 rows");
+
+    // Some rows in the same batch as the previous one, some in other batches
+    let targets = [
+        row_count / 2,
+        row_count / 2 + 1,
+        row_count / 2 - 3,
+        0,
+        row_count - 1,
+        row_count / 3,
+        row_count / 3 - 1,
+    ];
+    for batch_size in [1, 7, 1000] {
+        let mut rows: RowIterator<Option<Row>> =
+            RowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap()).unwrap();
+        for &target in &targets {
+            rows = rows.seek(target as u64);
+            assert_eq!(
+                rows.len(),
+                row_count - target,
+                "batch_size = {}, target = {}",
+                batch_size,
+                target
+            );
+            assert_eq!(
+                rows.next(),
+                Some(expected_rows[target].clone()),
+                "batch_size = {}, target = {}",
+                batch_size,
+                target
+            );
+            assert_eq!(
+                rows.next_back(),
+                Some(expected_rows[target].clone()),
+                "batch_size = {}, target = {}",
+                batch_size,
+                target
+            );
+            assert_eq!(
+                rows.next_back(),
+                target.checked_sub(1).map(|i| expected_rows[i].clone()),
+                "batch_size = {}, target = {}",
+                batch_size,
+                target
+            );
+        }
+    }
+}
+
+#[test]
+fn seek_in_range() {
+    let reader = get_reader();
+    let expected_rows: Vec<Option<Row>> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+    let start = expected_rows.len() / 4;
+    let end = expected_rows.len() / 2;
+
+    for batch_size in [1, 7, 1000] {
+        let rows: RowIterator<Option<Row>> =
+            RowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap())
+                .unwrap()
+                .range(start as u64..end as u64);
+
+        // Seeking does not go past the start of the range, even if it is in the
+        // middle of a row group
+        let mut rows = rows.seek(start as u64 + 1);
+        assert_eq!(rows.next_back(), Some(expected_rows[start].clone()));
+        assert_eq!(rows.next_back(), None, "batch_size = {}", batch_size);
+
+        let rows = rows.seek(end as u64 - 1);
+        let rows: Vec<_> = rows.collect();
+        assert_eq!(rows, vec![expected_rows[end - 1].clone()]);
+    }
+}
+
+#[test]
+fn position_and_resume_at() {
+    let reader = get_reader();
+    let expected_rows: Vec<Option<Row>> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+    let row_count = expected_rows.len();
+
+    for batch_size in [1, 7, 1000] {
+        let batch_size = NonZeroU64::new(batch_size).unwrap();
+        let mut rows: RowIterator<Option<Row>> = RowIterator::new(&reader, batch_size).unwrap();
+        assert_eq!(rows.position(), 0);
+        for i in 0..row_count / 2 {
+            assert_eq!(rows.position(), i as u64);
+            rows.next().unwrap();
+        }
+        let checkpoint = rows.position();
+        assert_eq!(checkpoint, (row_count / 2) as u64);
+
+        let resumed_rows: RowIterator<Option<Row>> =
+            RowIterator::resume_at(&reader, batch_size, checkpoint).unwrap();
+        assert_eq!(resumed_rows.len(), row_count - row_count / 2);
+        assert_eq!(resumed_rows.position(), checkpoint);
+        assert_eq!(
+            resumed_rows.collect::<Vec<_>>(),
+            expected_rows[row_count / 2..],
+            "batch_size = {}",
+            batch_size
+        );
+
+        // Moving backward and seeking
+        rows.next_back().unwrap();
+        assert_eq!(rows.position(), checkpoint - 1);
+        let mut rows = rows.seek(3);
+        assert_eq!(rows.position(), 3);
+        rows.by_ref().for_each(drop);
+        assert_eq!(rows.position(), row_count as u64);
+
+        // Past the end
+        let mut resumed_rows: RowIterator<Option<Row>> =
+            RowIterator::resume_at(&reader, batch_size, u64::MAX).unwrap();
+        assert_eq!(resumed_rows.len(), 0);
+        assert_eq!(resumed_rows.position(), row_count as u64);
+        assert_eq!(resumed_rows.next(), None);
+        assert_eq!(resumed_rows.next_back(), expected_rows.last().cloned());
+    }
+}
+
+/// Tests `RowIterator::from_row_reader` starts at the row reader's position
+#[test]
+fn from_row_reader_after_read() {
+    let reader = get_reader();
+    let expected_rows: Vec<Option<Row>> = RowIterator::new(&reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect();
+    let row_count = expected_rows.len();
+
+    let options = reader::RowReaderOptions::default().include_names(["int1", "string1"]);
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    let mut batch = row_reader.row_batch(10);
+    assert!(row_reader.read_into(&mut batch));
+    let mut rows: RowIterator<Option<Row>> =
+        RowIterator::from_row_reader(row_reader, NonZeroU64::new(7).unwrap()).unwrap();
+    assert_eq!(rows.len(), row_count - 10);
+    assert_eq!(rows.next(), Some(expected_rows[10].clone()));
+
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    row_reader.seek_to_row(5);
+    let mut rows: RowIterator<Option<Row>> =
+        RowIterator::from_row_reader(row_reader, NonZeroU64::new(7).unwrap()).unwrap();
+    assert_eq!(rows.len(), row_count - 5);
+    assert_eq!(rows.next(), Some(expected_rows[5].clone()));
+}
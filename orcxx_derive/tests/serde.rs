@@ -0,0 +1,86 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+#[macro_use]
+extern crate serde;
+extern crate serde_json;
+
+use std::num::NonZeroU64;
+
+use orcxx::deserialize::OrcStruct;
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+/// Foreign attributes (on the structure and its fields) do not affect how it
+/// is read from ORC
+#[derive(OrcDeserialize, Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[serde(rename_all = "UPPERCASE", deny_unknown_fields)]
+struct Test1 {
+    #[serde(rename = "number")]
+    int1: i32,
+    #[orc(unsafe_assume_utf8)]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    string1: String,
+    #[serde(skip)]
+    list: Vec<Test1Item>,
+}
+
+// Safe because the test file only contains valid UTF-8 strings
+unsafe impl orcxx::deserialize::AssumeUtf8 for Test1 {}
+
+#[derive(OrcDeserialize, Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+struct Test1Item {
+    #[serde(rename = "i")]
+    int1: Option<i32>,
+}
+
+#[test]
+fn test_serde() {
+    assert_eq!(Test1::columns(), vec!["int1", "string1", "list.int1"]);
+
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+    let rows: Vec<Test1> = RowIterator::new(&reader, NonZeroU64::new(1024).unwrap())
+        .expect("Could not open ORC file")
+        .collect();
+
+    assert_eq!(
+        rows,
+        vec![
+            Test1 {
+                int1: 65536,
+                string1: "hi".to_owned(),
+                list: vec![Test1Item { int1: Some(3) }, Test1Item { int1: Some(4) }],
+            },
+            Test1 {
+                int1: 65536,
+                string1: "bye".to_owned(),
+                list: vec![
+                    Test1Item {
+                        int1: Some(100000000)
+                    },
+                    Test1Item {
+                        int1: Some(-100000)
+                    },
+                    Test1Item { int1: Some(1234) },
+                ],
+            },
+        ]
+    );
+
+    let json = serde_json::to_string(&rows[0]).unwrap();
+    assert_eq!(json, r#"{"number":65536,"STRING1":"hi"}"#);
+    assert_eq!(
+        serde_json::from_str::<Test1>(&json).unwrap(),
+        Test1 {
+            list: Vec::new(),
+            ..rows[0].clone()
+        }
+    );
+}
@@ -0,0 +1,78 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::{NonZeroU64, NonZeroUsize};
+
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+fn get_reader() -> reader::Reader {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.testStripeLevelStats.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    reader::Reader::new(input_stream).expect("Could not read .orc")
+}
+
+fn all_rows(reader: &reader::Reader) -> Vec<Option<Row>> {
+    RowIterator::new(reader, NonZeroU64::new(1000).unwrap())
+        .unwrap()
+        .collect()
+}
+
+#[test]
+fn shards() {
+    let reader = get_reader();
+    let expected_rows = all_rows(&reader);
+
+    for n_shards in 1..6 {
+        let shards: Vec<RowIterator<Option<Row>>> = RowIterator::shards(
+            &reader,
+            NonZeroU64::new(1000).unwrap(),
+            NonZeroUsize::new(n_shards).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(shards.len(), n_shards);
+
+        let ranges = reader.shard(NonZeroUsize::new(n_shards).unwrap());
+        let mut rows = Vec::new();
+        for (shard, range) in shards.into_iter().zip(ranges) {
+            assert_eq!(shard.len() as u64, range.end - range.start);
+            let shard_rows: Vec<_> = shard.collect();
+            assert_eq!(shard_rows.len() as u64, range.end - range.start);
+            rows.extend(shard_rows);
+        }
+        assert_eq!(rows, expected_rows, "n_shards = {}", n_shards);
+    }
+}
+
+/// Ranges which are not aligned on stripes stop exactly on their end
+#[test]
+fn unaligned_range() {
+    let reader = get_reader();
+    let expected_rows = all_rows(&reader);
+
+    for batch_size in [1, 7, 1000] {
+        let rows: Vec<Option<Row>> =
+            RowIterator::new(&reader, NonZeroU64::new(batch_size).unwrap())
+                .unwrap()
+                .range(4990..5010)
+                .collect();
+        assert_eq!(
+            rows,
+            expected_rows[4990..5010],
+            "batch_size = {}",
+            batch_size
+        );
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use std::num::NonZeroU64;
+
+use orcxx::deserialize::{CheckableKind, OrcDeserialize};
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx::row_iterator::RowIterator;
+use orcxx_derive::OrcDeserialize;
+
+const ORC_PATH: &str = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+
+/// Some of the file's fields
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+#[orc(match_by_name)]
+struct Test1 {
+    int1: i32,
+    string1: String,
+    list: Vec<Item>,
+}
+
+/// Only the second field of the structures in `list`
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+#[orc(match_by_name)]
+struct Item {
+    string1: String,
+}
+
+fn item(string1: &str) -> Item {
+    Item {
+        string1: string1.to_owned(),
+    }
+}
+
+fn expected_rows() -> Vec<Test1> {
+    vec![
+        Test1 {
+            int1: 65536,
+            string1: "hi".to_owned(),
+            list: vec![item("good"), item("bad")],
+        },
+        Test1 {
+            int1: 65536,
+            string1: "bye".to_owned(),
+            list: vec![item("cat"), item("in"), item("hat")],
+        },
+    ]
+}
+
+fn reader() -> reader::Reader {
+    let input_stream = reader::InputStream::from_local_file(ORC_PATH).unwrap();
+    reader::Reader::new(input_stream).unwrap()
+}
+
+#[test]
+fn read_file() {
+    let rows: Vec<Test1> = orcxx::read_file(ORC_PATH).unwrap();
+    assert_eq!(rows, expected_rows());
+}
+
+/// Tests all columns of the file can be selected, including those which are not
+/// fields of the structure
+#[test]
+fn all_columns() {
+    let rows: RowIterator<Test1> = RowIterator::new_with_options(
+        &reader(),
+        NonZeroU64::new(1024).unwrap(),
+        &reader::RowReaderOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(rows.collect::<Vec<_>>(), expected_rows());
+
+    let rows: RowIterator<Option<Test1>> = RowIterator::new_with_options(
+        &reader(),
+        NonZeroU64::new(1024).unwrap(),
+        &reader::RowReaderOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        rows.collect::<Vec<_>>(),
+        expected_rows().into_iter().map(Some).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn read_from_vector_batch_with_kind() {
+    let mut row_reader = reader()
+        .row_reader(&reader::RowReaderOptions::default())
+        .unwrap();
+    let kind = row_reader.selected_kind();
+    Test1::check_kind(&kind).unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    let mut rows = vec![Test1::default(); 2];
+    Test1::read_from_vector_batch_with_kind(&batch.borrow(), &kind, &mut rows).unwrap();
+    assert_eq!(rows, expected_rows());
+}
+
+#[test]
+fn check_kind() {
+    let kind = Kind::new("struct<int1:int,string2:string,string1:string>").unwrap();
+    assert_eq!(Item::check_kind(&kind), Ok(()));
+
+    let kind = Kind::new("struct<string2:string>").unwrap();
+    assert_eq!(
+        Item::check_kind(&kind),
+        Err("Item cannot be decoded:\n\tField string1 is missing".to_owned())
+    );
+}
@@ -34,7 +34,7 @@ fn get_row_reader() -> reader::RowReader {
 
 fn test_with_batch_size<
     const BATCH_SIZE: u64,
-    T: CheckableKind + OrcDeserialize + OrcStruct + Clone + PartialEq + std::fmt::Debug,
+    T: CheckableKind + OrcDeserialize + OrcStruct + Default + Clone + PartialEq + std::fmt::Debug,
 >(
     expected_rows: Vec<T>,
 ) {
@@ -68,6 +68,14 @@ fn test_with_batch_size<
         "Inconsistent set of rows when using RowIterator"
     );
 
+    assert_eq!(
+        expected_rows,
+        RowIterator::<T>::from_row_reader(get_row_reader(), BATCH_SIZE.try_into().unwrap())
+            .unwrap()
+            .collect::<Vec<_>>(),
+        "Inconsistent set of rows when RowIterator constructed from a RowReader"
+    );
+
     assert_eq!(
         expected_rows,
         RowIterator::<T>::new(&reader, BATCH_SIZE.try_into().unwrap())
@@ -127,9 +135,29 @@ fn test_with_batch_size<
         Some(&expected_rows[expected_rows.len() - 1])
     );
     assert_eq!(iter.next().as_ref(), None);
+
+    // Read only the last rows
+    for n in 0..=expected_rows.len() + 1 {
+        let iter = RowIterator::<T>::new(&reader, BATCH_SIZE.try_into().unwrap())
+            .unwrap()
+            .tail(n.try_into().unwrap());
+        let expected_tail = &expected_rows[expected_rows.len().saturating_sub(n)..];
+        assert_eq!(
+            iter.len(),
+            expected_tail.len(),
+            "Inconsistent tail({n}) length"
+        );
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            expected_tail,
+            "Inconsistent tail({n})"
+        );
+    }
 }
 
-fn test<T: CheckableKind + OrcDeserialize + OrcStruct + Clone + PartialEq + std::fmt::Debug>(
+fn test<
+    T: CheckableKind + OrcDeserialize + OrcStruct + Default + Clone + PartialEq + std::fmt::Debug,
+>(
     expected_rows: Vec<T>,
 ) {
     // Using a const generic so it is more obvious on stack traces which value
@@ -70,3 +70,32 @@ fn test_nested() {
         vec!["abc", "def.ghi", "def.jkl", "def2.ghi", "def2.jkl"]
     );
 }
+
+#[test]
+fn test_generic() {
+    #[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+    struct Test<T, U> {
+        abc: T,
+        def: Option<Vec<U>>,
+    }
+
+    #[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+    struct Inner {
+        ghi: i64,
+    }
+
+    let kind = Kind::Struct(vec![
+        ("abc".to_string(), Kind::String),
+        (
+            "def".to_string(),
+            Kind::List(Box::new(Kind::Struct(vec![(
+                "ghi".to_string(),
+                Kind::Long,
+            )]))),
+        ),
+    ]);
+    Test::<String, Inner>::check_kind(&kind).unwrap();
+    assert!(Test::<i32, Inner>::check_kind(&kind).is_err());
+
+    assert_eq!(Test::<String, Inner>::columns(), vec!["abc", "def.ghi"]);
+}
@@ -3,6 +3,8 @@
 // License: GNU General Public License version 3, or any later version
 // See top-level LICENSE file for more information
 
+#[cfg(feature = "chrono")]
+extern crate chrono;
 extern crate orcxx;
 extern crate orcxx_derive;
 extern crate rust_decimal;
@@ -88,3 +90,45 @@ fn test_timestamp() {
         ]
     );
 }
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_datetime() {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+    let mut row_reader = row_reader();
+    DateTime::<Utc>::check_kind(&row_reader.selected_kind()).unwrap();
+    NaiveDateTime::check_kind(&row_reader.selected_kind()).unwrap();
+
+    let mut datetimes: Vec<DateTime<Utc>> = Vec::new();
+    let mut naive_datetimes: Vec<NaiveDateTime> = Vec::new();
+
+    let mut batch = row_reader.row_batch(1024);
+    while row_reader.read_into(&mut batch) {
+        datetimes.extend(DateTime::<Utc>::from_vector_batch(&batch.borrow()).unwrap());
+        naive_datetimes.extend(NaiveDateTime::from_vector_batch(&batch.borrow()).unwrap());
+    }
+
+    assert_eq!(datetimes.len(), 12);
+    assert_eq!(
+        naive_datetimes[0],
+        NaiveDate::from_ymd_opt(2037, 1, 1)
+            .unwrap()
+            .and_hms_nano_opt(0, 0, 0, 999000)
+            .unwrap()
+    );
+    assert_eq!(
+        naive_datetimes[1],
+        NaiveDate::from_ymd_opt(2003, 1, 1)
+            .unwrap()
+            .and_hms_nano_opt(0, 0, 0, 222)
+            .unwrap()
+    );
+    assert_eq!(
+        naive_datetimes,
+        datetimes
+            .iter()
+            .map(|datetime| datetime.naive_utc())
+            .collect::<Vec<_>>()
+    );
+}
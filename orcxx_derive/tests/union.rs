@@ -0,0 +1,116 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use orcxx::deserialize::{CheckableKind, DeserializationError, OrcDeserialize, OrcStruct};
+use orcxx::kind::Kind;
+use orcxx::reader;
+use orcxx_derive::OrcDeserialize;
+
+/// Has a `union:uniontype<int,string>` column
+const ORC_PATH: &str = "../orcxx/orc/examples/TestOrcFile.testUnionAndTimestamp.orc";
+
+#[derive(OrcDeserialize, Clone, Debug, PartialEq)]
+enum IntOrString {
+    Int(Option<i32>),
+    String(Option<String>),
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct UnionRow {
+    #[orc(rename = "union")]
+    value: Option<IntOrString>,
+}
+
+#[derive(OrcDeserialize, Clone, Debug, PartialEq)]
+enum NotNullIntOrString {
+    Int(Option<i32>),
+    String(Option<String>),
+}
+
+impl Default for NotNullIntOrString {
+    fn default() -> Self {
+        NotNullIntOrString::Int(None)
+    }
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct NotNullUnionRow {
+    #[orc(rename = "union")]
+    value: NotNullIntOrString,
+}
+
+#[test]
+fn columns() {
+    assert_eq!(UnionRow::columns(), vec!["union"]);
+}
+
+#[test]
+fn read_file() {
+    let rows: Vec<UnionRow> = orcxx::read_file(ORC_PATH).unwrap();
+    assert_eq!(
+        rows[..6],
+        vec![
+            UnionRow {
+                value: Some(IntOrString::Int(Some(42)))
+            },
+            UnionRow {
+                value: Some(IntOrString::String(Some("hello".to_owned())))
+            },
+            UnionRow { value: None },
+            UnionRow {
+                value: Some(IntOrString::Int(None))
+            },
+            UnionRow {
+                value: Some(IntOrString::String(None))
+            },
+            UnionRow {
+                value: Some(IntOrString::Int(Some(200000)))
+            },
+        ]
+    );
+}
+
+#[test]
+fn unexpected_null() {
+    let input_stream = reader::InputStream::from_local_file(ORC_PATH).unwrap();
+    let reader = reader::Reader::new(input_stream).unwrap();
+    let options = reader::RowReaderOptions::default().include_names(NotNullUnionRow::columns());
+    let mut row_reader = reader.row_reader(&options).unwrap();
+    NotNullUnionRow::check_kind(&row_reader.selected_kind()).unwrap();
+
+    let mut batch = row_reader.row_batch(1024);
+    assert!(row_reader.read_into(&mut batch));
+    let error = NotNullUnionRow::from_vector_batch(&batch.borrow()).unwrap_err();
+    assert!(
+        matches!(error.inner(), DeserializationError::UnexpectedNull(_)),
+        "{:?}",
+        error
+    );
+    assert_eq!(error.row(), Some(2));
+    assert_eq!(error.column(), "union");
+}
+
+#[test]
+fn check_kind() {
+    assert_eq!(
+        IntOrString::check_kind(&Kind::new("uniontype<int,string>").unwrap()),
+        Ok(())
+    );
+    assert_eq!(
+        IntOrString::check_kind(&Kind::new("uniontype<int,int>").unwrap()),
+        Err("IntOrString cannot be decoded:\n\tVariant String cannot be decoded: String must be decoded from ORC String, not ORC Int".to_owned())
+    );
+    assert_eq!(
+        IntOrString::check_kind(&Kind::new("uniontype<int>").unwrap()),
+        Err("IntOrString has 2 variants, but the union has 1".to_owned())
+    );
+    assert_eq!(
+        IntOrString::check_kind(&Kind::Int),
+        Err("IntOrString must be decoded from Kind::Union, not Int".to_owned())
+    );
+}
@@ -0,0 +1,102 @@
+// Copyright (C) 2023 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+extern crate orcxx;
+extern crate orcxx_derive;
+
+use orcxx::deserialize::{CheckableKind, OrcDeserialize};
+use orcxx::reader;
+use orcxx_derive::OrcDeserialize;
+
+fn get_row_reader() -> reader::RowReader {
+    let orc_path = "../orcxx/orc/examples/TestOrcFile.test1.orc";
+    let input_stream = reader::InputStream::from_local_file(orc_path).expect("Could not open .orc");
+    let reader = reader::Reader::new(input_stream).expect("Could not read .orc");
+
+    let options = reader::RowReaderOptions::default().include_names(["string1", "list"]);
+    reader.row_reader(&options).unwrap()
+}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Test1 {
+    #[orc(unsafe_assume_utf8)]
+    string1: String,
+    list: Vec<Test1Item>,
+}
+
+// Safe because the test file only contains valid UTF-8 strings
+unsafe impl orcxx::deserialize::AssumeUtf8 for Test1 {}
+
+#[derive(OrcDeserialize, Clone, Default, Debug, PartialEq)]
+struct Test1Item {
+    #[orc(unsafe_assume_utf8)]
+    string1: Option<String>,
+}
+
+// Safe because the test file only contains valid UTF-8 strings
+unsafe impl orcxx::deserialize::AssumeUtf8 for Test1Item {}
+
+fn expected_rows() -> Vec<Test1> {
+    vec![
+        Test1 {
+            string1: "hi".to_owned(),
+            list: vec![
+                Test1Item {
+                    string1: Some("good".to_owned()),
+                },
+                Test1Item {
+                    string1: Some("bad".to_owned()),
+                },
+            ],
+        },
+        Test1 {
+            string1: "bye".to_owned(),
+            list: vec![
+                Test1Item {
+                    string1: Some("cat".to_owned()),
+                },
+                Test1Item {
+                    string1: Some("in".to_owned()),
+                },
+                Test1Item {
+                    string1: Some("hat".to_owned()),
+                },
+            ],
+        },
+    ]
+}
+
+/// Tests `#[orc(unsafe_assume_utf8)]` gives the same result as validated deserialization
+#[test]
+fn test_assume_utf8() {
+    let mut row_reader = get_row_reader();
+    Test1::check_kind(&row_reader.selected_kind()).unwrap();
+
+    let mut rows = Vec::new();
+    let mut batch = row_reader.row_batch(1024);
+    while row_reader.read_into(&mut batch) {
+        rows.extend(Test1::from_vector_batch(&batch.borrow()).unwrap());
+    }
+
+    assert_eq!(rows, expected_rows());
+}
+
+/// Tests `#[orc(unsafe_assume_utf8)]` on fields of an optional struct
+#[test]
+fn test_assume_utf8_option() {
+    let mut row_reader = get_row_reader();
+    Option::<Test1>::check_kind(&row_reader.selected_kind()).unwrap();
+
+    let mut rows = Vec::new();
+    let mut batch = row_reader.row_batch(1024);
+    while row_reader.read_into(&mut batch) {
+        rows.extend(Option::<Test1>::from_vector_batch(&batch.borrow()).unwrap());
+    }
+
+    assert_eq!(
+        rows,
+        expected_rows().into_iter().map(Some).collect::<Vec<_>>()
+    );
+}